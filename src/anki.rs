@@ -0,0 +1,200 @@
+// Anki 卡片导出：从带有 flashcard 标签的笔记中提取问答，写成 Anki 可以导入的文本文件
+//
+// 以 `Q:` 开头的行开始一个问题，其后以 `A:` 开头的行开始它的答案，答案持续到下一个 `Q:`、
+// 下一个标题或正文末尾；笔记中没有 `Q:` 时，每个标题是一个问题，其下到下一个标题之前的正文
+// 是答案。围栏代码块中的行不作为分隔。问题与答案按 Markdown 渲染为 HTML。
+//
+// 输出为制表符分隔的文本，开头的 `#guid column:1` 等行告诉 Anki 各列的含义。每张卡片的 GUID 为
+// 笔记的 ID（没有时为文件名）加上卡片在笔记中的序号，再次导入时 Anki 更新已有的卡片而不是重复添加。
+
+use std::io;
+
+use crate::index::tag_levels;
+use crate::markdown;
+use crate::note::{NEED_TAG, NoteMeta};
+use crate::org;
+use crate::vault::Vault;
+
+/// 导出卡片的笔记需要带有的标签，其下的层级标签同样计入
+pub const FLASHCARD_TAG: &str = "flashcard";
+
+/// 一张卡片
+#[derive(Debug, Clone)]
+pub struct Card {
+    /// 笔记的 ID 或文件名加上 `#序号`，序号从 1 开始
+    pub guid: String,
+    /// 问题，HTML
+    pub front: String,
+    /// 答案，HTML
+    pub back: String,
+    /// 笔记的标签，不含 flashcard；层级标签的 / 换成 Anki 的 ::
+    pub tags: Vec<String>,
+}
+
+/// 提取 vault 中带有 flashcard 标签的 markdown 笔记里的卡片，按文件名与出现顺序排列
+pub fn collect(vault: &Vault) -> io::Result<Vec<Card>> {
+    let mut notes: Vec<&NoteMeta> = vault
+        .published()
+        .filter(|note| !org::is_org(&note.name))
+        .filter(|note| {
+            note.tags
+                .iter()
+                .any(|tag| tag_levels(tag).contains(&FLASHCARD_TAG))
+        })
+        .collect();
+    notes.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut cards = Vec::new();
+    for note in notes {
+        let body = vault.note_body(note)?;
+        let tags: Vec<String> = note
+            .tags
+            .iter()
+            .filter(|tag| *tag != NEED_TAG && *tag != FLASHCARD_TAG)
+            .map(|tag| {
+                tag.replace('/', "::")
+                    .split_whitespace()
+                    .collect::<Vec<_>>()
+                    .join("_")
+            })
+            .collect();
+        let key = note.id.as_deref().unwrap_or(&note.name);
+        for (i, (question, answer)) in pairs(&body).into_iter().enumerate() {
+            cards.push(Card {
+                guid: format!("{}#{}", key, i + 1),
+                front: render(&question),
+                back: render(&answer),
+                tags: tags.clone(),
+            });
+        }
+    }
+    Ok(cards)
+}
+
+/// Anki 文本导入格式：列依次为 GUID、问题、答案与以空格分隔的标签，使用 Basic 笔记类型
+pub fn to_tsv(cards: &[Card]) -> String {
+    let mut out = String::from(
+        "#separator:tab\n#html:true\n#notetype:Basic\n#guid column:1\n#tags column:4\n",
+    );
+    for card in cards {
+        let row = [
+            card.guid.as_str(),
+            card.front.as_str(),
+            card.back.as_str(),
+            &card.tags.join(" "),
+        ];
+        let cells: Vec<String> = row.iter().map(|cell| tsv_field(cell)).collect();
+        out.push_str(&cells.join("\t"));
+        out.push('\n');
+    }
+    out
+}
+
+/// 正文中的问答，均为 Markdown 原文；有 `Q:` 时只取 Q/A，否则取标题与其下的正文
+pub fn pairs(body: &str) -> Vec<(String, String)> {
+    let qa = qa_pairs(body);
+    if qa.is_empty() {
+        heading_pairs(body)
+    } else {
+        qa
+    }
+}
+
+fn qa_pairs(body: &str) -> Vec<(String, String)> {
+    let mut pairs = Vec::new();
+    let mut question: Option<Vec<&str>> = None;
+    let mut answer: Option<Vec<&str>> = None;
+    let mut finish = |question: &mut Option<Vec<&str>>, answer: &mut Option<Vec<&str>>| {
+        if let (Some(q), Some(a)) = (question.take(), answer.take()) {
+            let (q, a) = (q.join("\n"), a.join("\n"));
+            if !q.trim().is_empty() && !a.trim().is_empty() {
+                pairs.push((q.trim().to_string(), a.trim().to_string()));
+            }
+        }
+    };
+    let mut in_code_block = false;
+    for line in body.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code_block = !in_code_block;
+        } else if !in_code_block {
+            if let Some(rest) = marker(trimmed, 'Q') {
+                finish(&mut question, &mut answer);
+                question = Some(vec![rest]);
+                continue;
+            }
+            if question.is_some()
+                && answer.is_none()
+                && let Some(rest) = marker(trimmed, 'A')
+            {
+                answer = Some(vec![rest]);
+                continue;
+            }
+            if heading(trimmed).is_some() {
+                finish(&mut question, &mut answer);
+                continue;
+            }
+        }
+        if let Some(lines) = answer.as_mut().or(question.as_mut()) {
+            lines.push(line);
+        }
+    }
+    finish(&mut question, &mut answer);
+    pairs
+}
+
+fn heading_pairs(body: &str) -> Vec<(String, String)> {
+    let mut pairs = Vec::new();
+    let mut current: Option<(&str, Vec<&str>)> = None;
+    let mut in_code_block = false;
+    for line in body.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code_block = !in_code_block;
+        } else if !in_code_block && let Some(title) = heading(trimmed) {
+            pairs.extend(current.take());
+            current = Some((title, Vec::new()));
+            continue;
+        }
+        if let Some((_, lines)) = current.as_mut() {
+            lines.push(line);
+        }
+    }
+    pairs.extend(current);
+    pairs
+        .into_iter()
+        .map(|(title, lines)| (title.to_string(), lines.join("\n").trim().to_string()))
+        .filter(|(title, answer)| !title.is_empty() && !answer.is_empty())
+        .collect()
+}
+
+// `Q:` 或 `Q：` 之后的文字，字母不区分大小写
+fn marker(line: &str, letter: char) -> Option<&str> {
+    let rest = line
+        .strip_prefix(letter)
+        .or_else(|| line.strip_prefix(letter.to_ascii_lowercase()))?;
+    let rest = rest.strip_prefix(':').or_else(|| rest.strip_prefix('：'))?;
+    Some(rest.trim())
+}
+
+// ATX 标题的文字
+fn heading(line: &str) -> Option<&str> {
+    let rest = line.trim_start_matches('#');
+    let level = line.len() - rest.len();
+    ((1..=6).contains(&level) && (rest.is_empty() || rest.starts_with([' ', '\t'])))
+        .then(|| rest.trim().trim_end_matches('#').trim_end())
+}
+
+// 维基链接在卡片中没有地址，只保留文字
+fn render(text: &str) -> String {
+    markdown::to_html(text, &|_| None).trim_end().to_string()
+}
+
+// 含制表符、换行或引号的字段加双引号，其中的引号写两遍
+fn tsv_field(text: &str) -> String {
+    if text.contains(['\t', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
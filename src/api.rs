@@ -0,0 +1,188 @@
+// gtx serve --api 的 JSON 接口，供网页前端与其他客户端读取笔记索引
+//
+// 接口（均为 GET）:
+//   /api/notes                 全部笔记，?query= 时只返回满足查询的笔记，写法同 gtx query
+//   /api/notes/{文件名}         一篇笔记，另有 backlinks 字段列出指向它的链接
+//   /api/tags                  全部标签及笔记数
+//   /api/tags/{标签}            含有该标签或其下层级标签的笔记
+//   /api/dates/{日期}           创建于该年、该月或该日的笔记，如 2024、2024-05、20240501
+//   /api/search?q=&limit=      全文搜索，有搜索索引时按相关度排序
+// 笔记的字段与 gtx export 导出的相同，搜索结果的字段与 gtx rpc 的相同。
+
+use crate::export::{backlinks_to_json, note_to_json};
+use crate::fulltext::SearchIndex;
+use crate::index::tag_levels;
+use crate::json::Json;
+use crate::note::NoteMeta;
+use crate::query::Query;
+use crate::search::{self, SearchOptions};
+use crate::serve::percent_decode;
+use crate::vault::Vault;
+
+/// 接口的路径前缀
+pub const API_PREFIX: &str = "/api/";
+
+// search 默认返回的匹配数
+const DEFAULT_LIMIT: usize = 20;
+
+/// 接口使用的索引，目录变化时整体替换
+pub struct Api {
+    vault: Vault,
+    search_index: Option<SearchIndex>,
+}
+
+impl Api {
+    pub fn new(vault: Vault) -> Api {
+        let search_index = SearchIndex::load(vault.root());
+        Api {
+            vault,
+            search_index,
+        }
+    }
+
+    /// 应答一个请求，path 为 API_PREFIX 之后、已解码的路径，query 为 ? 之后的原文
+    ///
+    /// 返回 HTTP 状态与 JSON，出错时 JSON 为 `{"error": "说明"}`。
+    pub fn get(&self, path: &str, query: &str) -> (&'static str, Json) {
+        let params = query_params(query);
+        let param = |key: &str| {
+            params
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v.as_str())
+        };
+        let path = path.trim_end_matches('/');
+        let (resource, rest) = path.split_once('/').unwrap_or((path, ""));
+
+        match (resource, rest) {
+            ("notes", "") => match param("query") {
+                Some(text) => match Query::parse(text) {
+                    Ok(query) => self.notes(query.filter(self.vault.published())),
+                    Err(e) => error("400 Bad Request", e.to_string()),
+                },
+                None => {
+                    let mut notes: Vec<&NoteMeta> = self.vault.published().collect();
+                    notes.sort_by(|a, b| a.name.cmp(&b.name));
+                    self.notes(notes)
+                }
+            },
+            ("notes", name) => self.note(name),
+            ("tags", "") => {
+                let tags = &self.vault.indexes().tags;
+                let mut names: Vec<&String> = tags.get_inputs().iter().collect();
+                names.sort();
+                let items = names
+                    .into_iter()
+                    .map(|tag| {
+                        Json::Object(vec![
+                            ("tag".to_string(), tag.as_str().into()),
+                            ("count".to_string(), tags.get_i_count(tag).into()),
+                        ])
+                    })
+                    .collect();
+                ("200 OK", Json::Array(items))
+            }
+            ("tags", tag) => {
+                let mut notes: Vec<&NoteMeta> = self
+                    .vault
+                    .published()
+                    .filter(|note| note.tags.iter().any(|t| tag_levels(t).contains(&tag)))
+                    .collect();
+                if notes.is_empty() {
+                    return error("404 Not Found", format!("没有标签 '{}'", tag));
+                }
+                notes.sort_by(|a, b| a.name.cmp(&b.name));
+                self.notes(notes)
+            }
+            ("dates", date) if !date.is_empty() => {
+                let key: String = date.chars().filter(|&c| c != '-').collect();
+                if !matches!(key.len(), 4 | 6 | 8) || !key.chars().all(|c| c.is_ascii_digit()) {
+                    return error(
+                        "400 Bad Request",
+                        format!("无效的日期 '{}'，应为年、年月或完整日期", date),
+                    );
+                }
+                let notes = self.vault.published().filter(|note| {
+                    note.created
+                        .as_ref()
+                        .is_some_and(|(created, _)| created.to_string().starts_with(&key))
+                });
+                let mut notes: Vec<&NoteMeta> = notes.collect();
+                notes.sort_by(|a, b| a.created.cmp(&b.created).then(a.name.cmp(&b.name)));
+                self.notes(notes)
+            }
+            ("search", "") => {
+                let Some(text) = param("q").filter(|text| !text.trim().is_empty()) else {
+                    return error("400 Bad Request", "缺少参数 q".to_string());
+                };
+                let limit = match param("limit").map(str::parse) {
+                    Some(Ok(limit)) => limit,
+                    Some(Err(_)) => {
+                        return error("400 Bad Request", "limit 应为非负整数".to_string());
+                    }
+                    None => DEFAULT_LIMIT,
+                };
+                match search::search_with(
+                    &self.vault,
+                    self.search_index.as_ref(),
+                    text,
+                    &SearchOptions {
+                        published: true,
+                        ..SearchOptions::default()
+                    },
+                    limit,
+                ) {
+                    Ok(matches) => (
+                        "200 OK",
+                        Json::Array(matches.iter().map(|m| m.to_json()).collect()),
+                    ),
+                    Err(e) => error("400 Bad Request", e.to_string()),
+                }
+            }
+            _ => error("404 Not Found", format!("未知的接口 '{}'", path)),
+        }
+    }
+
+    fn notes<'a>(&self, notes: impl IntoIterator<Item = &'a NoteMeta>) -> (&'static str, Json) {
+        let links = &self.vault.indexes().links;
+        let items = notes
+            .into_iter()
+            .map(|note| note_to_json(note, links))
+            .collect();
+        ("200 OK", Json::Array(items))
+    }
+
+    // 文件名可以带 .md 后缀
+    fn note(&self, name: &str) -> (&'static str, Json) {
+        let name = name.strip_suffix(".md").unwrap_or(name);
+        let Some(note) = self.vault.published_note(name) else {
+            return error("404 Not Found", format!("找不到笔记 '{}'", name));
+        };
+        let links = &self.vault.indexes().links;
+        let Json::Object(mut fields) = note_to_json(note, links) else {
+            unreachable!("note_to_json 总是返回对象");
+        };
+        fields.push(("backlinks".to_string(), backlinks_to_json(links, name)));
+        ("200 OK", Json::Object(fields))
+    }
+}
+
+fn error(status: &'static str, message: String) -> (&'static str, Json) {
+    (
+        status,
+        Json::Object(vec![("error".to_string(), message.into())]),
+    )
+}
+
+// 解析 a=1&b=2，+ 表示空格，无法解码的参数被忽略
+fn query_params(query: &str) -> Vec<(String, String)> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .filter_map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            let decode = |text: &str| percent_decode(&text.replace('+', " "));
+            Some((decode(key)?, decode(value)?))
+        })
+        .collect()
+}
@@ -0,0 +1,166 @@
+// 归档旧笔记：把创建于某日之前的笔记移到 archive/ 目录，不再出现在主索引中
+//
+// 扫描笔记时跳过 archive/ 目录，移入其中的笔记自然不再加入标签页与日期页，也不再是链接目标。
+// 其他笔记中指向它们的 [[链接]] 保持原样，归档时另行列出，`gtx check` 把它们与失效链接分开报告；
+// 归档的笔记加上 archived 标签，全部归档笔记另由 archive-index.md 按年份列出。
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::date::Date;
+use crate::i18n;
+use crate::note::{NoteMeta, note_file_name};
+use crate::org;
+use crate::output::{LinkStyle, write_atomic};
+use crate::retag::{add_tag, backup};
+use crate::vault::Vault;
+
+/// 归档笔记所在的目录，位于博客目录下
+pub const ARCHIVE_DIR: &str = "archive";
+
+/// 归档索引页面的文件名，位于输出目录下
+pub const ARCHIVE_INDEX_PAGE: &str = "archive-index.md";
+
+/// 归档时加上的标签
+pub const ARCHIVED_TAG: &str = "archived";
+
+/// 一次归档的结果
+pub struct ArchiveReport {
+    /// 归档的笔记：原路径与新路径，按文件名排序
+    pub moved: Vec<(PathBuf, PathBuf)>,
+    /// archive/ 中已有同名文件、没有归档的笔记
+    pub skipped: Vec<PathBuf>,
+    /// 仍有链接指向归档笔记的笔记及各自的链接数，按文件名排序；这些链接不会改写
+    pub linked: Vec<(PathBuf, usize)>,
+    /// 原文件的备份目录，没有改写任何文件时为 None
+    pub backup_dir: Option<PathBuf>,
+}
+
+/// 链接目标是否为 archive/ 中存在的笔记，这样的链接不算失效：目标为归档前的名称，
+/// 或较早版本归档时改写成的 archive/文件名
+pub fn is_archived(root: &Path, target: &str) -> bool {
+    let name = target
+        .strip_prefix(ARCHIVE_DIR)
+        .and_then(|rest| rest.strip_prefix('/'))
+        .unwrap_or(target);
+    root.join(ARCHIVE_DIR).join(note_file_name(name)).is_file()
+}
+
+/// 把创建于 before 之前的 markdown 笔记移到 archive/，置顶笔记与 org 笔记保留在原处
+///
+/// tag_keys 为标签字段的键名，见 `retag::add_tag`。给归档的笔记加标签前将原文件复制到 backup_root 下
+/// 以时间戳命名的目录。其他笔记中指向归档笔记的链接不改写，记入 `ArchiveReport::linked`。
+/// dry_run 为 true 时只返回将要归档的笔记，不改动任何文件。
+pub fn archive_notes(
+    vault: &Vault,
+    before: Date,
+    tag_keys: &[&str],
+    backup_root: &Path,
+    dry_run: bool,
+) -> io::Result<ArchiveReport> {
+    let archive_dir = vault.root().join(ARCHIVE_DIR);
+    let mut report = ArchiveReport {
+        moved: Vec::new(),
+        skipped: Vec::new(),
+        linked: Vec::new(),
+        backup_dir: None,
+    };
+    let mut old: Vec<&NoteMeta> = vault
+        .notes()
+        .filter(|note| {
+            note.created
+                .as_ref()
+                .is_some_and(|(date, _)| *date < before)
+        })
+        .filter(|note| !note.pinned && !org::is_org(&note.name))
+        .collect();
+    old.sort_by(|a, b| a.name.cmp(&b.name));
+    let mut archived = Vec::new();
+    for note in old {
+        let to = archive_dir.join(note_file_name(&note.name));
+        if to.exists() {
+            report.skipped.push(vault.note_path(&note.name));
+        } else {
+            archived.push(note);
+        }
+    }
+
+    let links = &vault.indexes().links;
+    let is_archiving = |name: &str| archived.iter().any(|note| note.name == name);
+    let mut notes: Vec<&NoteMeta> = vault.notes().collect();
+    notes.sort_by(|a, b| a.name.cmp(&b.name));
+    for note in notes {
+        let path = vault.note_path(&note.name);
+        if !is_archiving(&note.name) {
+            let count = note
+                .links
+                .iter()
+                .filter(|link| links.resolve(&link.target).is_some_and(is_archiving))
+                .count();
+            if count > 0 {
+                report.linked.push((path, count));
+            }
+            continue;
+        }
+        if note.tags.iter().any(|tag| tag == ARCHIVED_TAG) || dry_run {
+            continue;
+        }
+        let content = fs::read_to_string(&path)?;
+        backup(vault.root(), &path, backup_root, &mut report.backup_dir)?;
+        write_atomic(&path, &add_tag(&content, tag_keys, ARCHIVED_TAG))?;
+    }
+
+    for note in archived {
+        let from = vault.note_path(&note.name);
+        // 子目录中的笔记归档到 archive/ 下同样的子目录
+        let to = archive_dir.join(note_file_name(&note.name));
+        if !dry_run {
+            fs::create_dir_all(to.parent().unwrap())?;
+            fs::rename(&from, &to)?;
+        }
+        report.moved.push((from, to));
+    }
+    Ok(report)
+}
+
+/// 归档索引页面的内容：archive/ 中的笔记按创建年份从新到旧分组，link_prefix 含义同 `WriteOptions`
+pub fn archive_index_page<'a>(
+    notes: impl Iterator<Item = &'a NoteMeta>,
+    style: LinkStyle,
+    link_prefix: &str,
+) -> String {
+    let mut out = i18n::trf("{}\n# 归档\n", &[&style.frontmatter("archive", "archive")]);
+    let mut by_year: BTreeMap<Option<u16>, Vec<&NoteMeta>> = BTreeMap::new();
+    for note in notes {
+        let year = note.created.as_ref().map(|(date, _)| date.year);
+        by_year.entry(year).or_default().push(note);
+    }
+    if by_year.is_empty() {
+        out.push_str(i18n::tr("没有归档的笔记\n"));
+    }
+    for (year, notes) in by_year.iter_mut().rev() {
+        match year {
+            Some(year) => {
+                let _ = writeln!(out, "\n## {}", year);
+            }
+            None => out.push_str(i18n::tr("\n## 没有创建时间\n")),
+        }
+        notes.sort_by(|a, b| b.created.cmp(&a.created).then_with(|| a.name.cmp(&b.name)));
+        for note in notes.iter() {
+            let target = format!("{}/{}", ARCHIVE_DIR, note.name);
+            let link = style.link(link_prefix, &target, Some(&note.title));
+            match &note.created {
+                Some((date, _)) => {
+                    let _ = writeln!(out, "- {} · {}", link, date);
+                }
+                None => {
+                    let _ = writeln!(out, "- {}", link);
+                }
+            }
+        }
+    }
+    out
+}
@@ -0,0 +1,147 @@
+// 附件索引：笔记中嵌入的图片与文件，以及附件目录中没有被引用的文件
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write;
+use std::io;
+
+use crate::i18n;
+use crate::note::NoteMeta;
+use crate::output::LinkStyle;
+use crate::vault::Vault;
+use crate::walk;
+
+/// 附件报告页面的文件名，位于输出目录下
+pub const ATTACHMENTS_PAGE: &str = "attachments.md";
+
+/// 未配置时的附件目录，相对于博客目录
+pub const DEFAULT_ATTACHMENTS_DIR: &str = "attachments";
+
+/// 笔记中的一处嵌入
+#[derive(Debug, Clone)]
+pub struct Embed {
+    pub source_name: String,
+    pub source_title: String,
+    /// 嵌入的原文目标
+    pub target: String,
+    pub line: usize,
+}
+
+/// 博客目录的附件使用情况
+#[derive(Debug, Clone, Default)]
+pub struct Attachments {
+    /// 附件相对于博客目录的路径 -> 使用它的嵌入，按来源文件名与行号排序
+    pub used: BTreeMap<String, Vec<Embed>>,
+    /// 找不到文件的嵌入，按来源文件名与行号排序
+    pub missing: Vec<Embed>,
+    /// 附件目录中没有被任何笔记嵌入的文件，按路径排序
+    pub unused: Vec<String>,
+}
+
+impl Attachments {
+    /// 收集 vault 中所有笔记的嵌入，dir 为附件目录，相对于博客目录
+    ///
+    /// `![](路径)` 按相对于博客目录的路径查找；只有文件名的 `![[文件]]` 另在附件目录的各层中按文件名查找，
+    /// 同名文件有多个时取路径最小的。
+    ///
+    /// follow_symlinks 为 true 时进入符号链接指向的目录（如共享的附件目录），同一文件或目录只列出一次，
+    /// 链接成环时不会重复进入；为 false 时跳过全部符号链接。
+    pub fn collect(vault: &Vault, dir: &str, follow_symlinks: bool) -> io::Result<Attachments> {
+        let root = vault.root();
+        let mut files = Vec::new();
+        let dir_path = root.join(dir);
+        if dir_path.is_dir() {
+            let prefix = dir.trim_end_matches('/');
+            let found = walk::files(&dir_path, follow_symlinks, &|_| false)?
+                .into_iter()
+                .filter(|entry| !entry.relative.rsplit('/').next().unwrap().starts_with('.'))
+                .collect();
+            files = walk::dedup(found)
+                .into_iter()
+                .map(|entry| format!("{}/{}", prefix, entry.relative))
+                .collect();
+        }
+
+        let mut by_name: HashMap<&str, &str> = HashMap::new();
+        for file in &files {
+            let name = file.rsplit('/').next().unwrap_or(file);
+            by_name.entry(name).or_insert(file);
+        }
+        let known: HashSet<&str> = files.iter().map(String::as_str).collect();
+
+        let mut notes: Vec<&NoteMeta> = vault.notes().collect();
+        notes.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut result = Attachments::default();
+        for note in notes {
+            for embed in &note.embeds {
+                let entry = Embed {
+                    source_name: note.name.clone(),
+                    source_title: note.title.clone(),
+                    target: embed.target.clone(),
+                    line: embed.line,
+                };
+                let target = embed.target.as_str();
+                let path = if known.contains(target) || root.join(target).is_file() {
+                    Some(target.to_string())
+                } else if !target.contains('/') {
+                    by_name.get(target).map(|path| path.to_string())
+                } else {
+                    None
+                };
+                match path {
+                    Some(path) => result.used.entry(path).or_default().push(entry),
+                    None => result.missing.push(entry),
+                }
+            }
+        }
+
+        result.unused = files
+            .into_iter()
+            .filter(|file| !result.used.contains_key(file))
+            .collect();
+        Ok(result)
+    }
+}
+
+/// 附件报告页面的内容：各附件被哪些笔记使用、缺失的附件与未引用的附件，link_prefix 含义同 `WriteOptions`
+pub fn attachments_page(attachments: &Attachments, style: LinkStyle, link_prefix: &str) -> String {
+    let mut out = i18n::trf(
+        "{}\n# 附件\n",
+        &[&style.frontmatter("attachments", "attachments")],
+    );
+
+    out.push_str(i18n::tr("\n## 已引用\n"));
+    if attachments.used.is_empty() {
+        out.push_str(i18n::tr("没有笔记嵌入附件\n"));
+    }
+    for (path, embeds) in &attachments.used {
+        let mut sources: Vec<&Embed> = embeds.iter().collect();
+        sources.dedup_by(|a, b| a.source_name == b.source_name);
+        let sources: Vec<String> = sources
+            .iter()
+            .map(|embed| style.link(link_prefix, &embed.source_name, Some(&embed.source_title)))
+            .collect();
+        let _ = writeln!(out, "- `{}`: {}", path, sources.join("、"));
+    }
+
+    if !attachments.missing.is_empty() {
+        out.push_str(i18n::tr("\n## 缺失\n"));
+        for embed in &attachments.missing {
+            let _ = writeln!(
+                out,
+                "- {} (L{}): `{}`",
+                style.link(link_prefix, &embed.source_name, Some(&embed.source_title)),
+                embed.line,
+                embed.target
+            );
+        }
+    }
+
+    if !attachments.unused.is_empty() {
+        out.push_str(i18n::tr("\n## 未引用\n"));
+        for path in &attachments.unused {
+            let _ = writeln!(out, "- `{}`", path);
+        }
+    }
+    out
+}
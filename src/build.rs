@@ -0,0 +1,380 @@
+// 完整的索引构建流程：扫描、清理旧页面、写入页面、保存缓存与生成文件清单
+
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+use crate::archive::ARCHIVE_INDEX_PAGE;
+use crate::attachments::ATTACHMENTS_PAGE;
+use crate::cache::{Affected, Cache};
+use crate::check::BROKEN_LINKS_PAGE;
+use crate::cleanup::Cleanup;
+use crate::format::Columns;
+use crate::fulltext::SearchIndex;
+use crate::i18n;
+use crate::log::{Progress, Timings};
+use crate::manifest::{Manifest, manifest_path};
+use crate::note::{FrontmatterKeys, NoteMeta};
+use crate::output::{
+    LinkStyle, PageTemplates, TEMPLATES_DIR, TagSorts, TimeFormat, TitleGroups, WriteOptions,
+    remove_stale_pages,
+};
+use crate::plan;
+use crate::query::{self, QUERIES_DIR};
+use crate::sqlite::{self, INDEX_DB};
+use crate::stats::STATS_PAGE;
+use crate::todos::TODOS_PAGE;
+use crate::vault::{DateSource, ScanOptions, ScanReport, Vault};
+
+/// 索引的存储方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Backend {
+    /// 只保存缓存文件，每次构建时在内存中建立索引
+    #[default]
+    Memory,
+    /// 另外把笔记、标签、别名与链接写入 SQLite 数据库 .gtx/index.db
+    Sqlite,
+}
+
+impl Backend {
+    /// 配置文件与命令行中使用的名称
+    pub fn name(self) -> &'static str {
+        match self {
+            Backend::Memory => "memory",
+            Backend::Sqlite => "sqlite",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Backend> {
+        match name {
+            "memory" => Some(Backend::Memory),
+            "sqlite" => Some(Backend::Sqlite),
+            _ => None,
+        }
+    }
+}
+
+/// 构建选项
+pub struct BuildOptions {
+    /// 旧页面的清理策略
+    pub cleanup: Cleanup,
+    /// 忽略缓存，重新解析全部笔记并重写所有页面
+    pub full: bool,
+    /// 报告进度：扫描与写入页面时显示进度条，verbose 级别下另外列出每个重新解析的文件，见 `log`
+    pub verbose: bool,
+    /// 生成页面的输出目录，None 表示写入博客目录本身
+    pub output: Option<PathBuf>,
+    /// index.md 中标签表格的列数，None 时使用默认列数
+    pub tag_columns: Option<Columns>,
+    /// index.md 中日期表格的列数，None 时使用默认列数
+    pub date_columns: Option<Columns>,
+    pub link_style: LinkStyle,
+    /// 不参与索引的笔记文件名通配符
+    pub exclude: Vec<String>,
+    /// 文件头字段的键名
+    pub keys: FrontmatterKeys,
+    pub backend: Backend,
+    /// 标签页中笔记的顺序
+    pub tag_sorts: TagSorts,
+    /// 日期页中时间的写法
+    pub time_format: TimeFormat,
+    /// a-z.md 中非拉丁字母标题的分组方式
+    pub title_groups: TitleGroups,
+    /// index.md 中 Mermaid 关系图包含的笔记数，0 表示不生成
+    pub mermaid: usize,
+    /// index.md 中列出的中心笔记数，0 表示不列出
+    pub hubs: usize,
+    /// 标签页与日期页中每篇笔记下的摘要的最大字符数，0 表示不显示
+    pub excerpt: usize,
+    /// 同时索引 .org 笔记
+    pub org: bool,
+    /// 没有 Created 字段的笔记的时间来源
+    pub date_source: DateSource,
+    /// 只重新解析这些文件名的笔记（如本次提交涉及的文件），其余笔记使用缓存；None 表示全部检查
+    pub changed: Option<HashSet<String>>,
+    /// 同时更新 .gtx/search 下的全文搜索索引
+    pub search: bool,
+    /// 草稿照常出现在标签页、日期页与 index.md 中
+    pub include_drafts: bool,
+    /// 标签页所在的目录，相对输出目录，为空时标签页直接位于输出目录下
+    pub tag_dir: String,
+    /// 跟随指向笔记的符号链接，指向同一文件的多个路径只索引一次
+    pub follow_symlinks: bool,
+}
+
+/// 一次构建的结果
+pub struct BuildReport {
+    /// 索引中的笔记数
+    pub notes: usize,
+    /// 增量构建时受影响的标签、日期与链接；完整构建时为 None
+    pub affected: Option<Affected>,
+    /// 读取或解析失败、未加入索引的文件
+    pub scan: ScanReport,
+    /// 创建时间无法识别的笔记：（文件路径，字段原文），按路径排序
+    pub invalid_created: Vec<(PathBuf, String)>,
+    /// 扫描、清理、写入与保存各阶段的耗时
+    pub timings: Timings,
+}
+
+/// 缓存文件的位置
+pub fn cache_path(root: &Path) -> PathBuf {
+    root.join(".gtx").join("cache.json")
+}
+
+/// 扫描 root 并在输出目录中生成全部页面，有可用缓存时只重写受影响的页面
+pub fn build(root: &Path, options: &BuildOptions) -> io::Result<BuildReport> {
+    // SQLite 索引在最后写入，先确认 sqlite3 可用，免得页面写完才失败
+    if options.backend == Backend::Sqlite && !plan::enabled() {
+        sqlite::check_available()?;
+    }
+    let mut timings = Timings::start();
+    // 缓存不可用或指定 full 时完整构建
+    let cache_path = cache_path(root);
+    let old_cache = if options.full {
+        None
+    } else {
+        Cache::load(&cache_path)
+    };
+    let cached = old_cache.is_some();
+    let mut full_rebuild = !cached;
+    // 缓存不可用时，由生成文件清单得知上次生成的页面，不再生成的照常清理
+    let mut old_cache = old_cache.unwrap_or_else(|| {
+        let manifest = Manifest::load(&manifest_path(root)).unwrap_or_default();
+        Cache {
+            output: manifest.output,
+            generated: manifest.files,
+            ..Cache::default()
+        }
+    });
+
+    let output = match &options.output {
+        Some(dir) => {
+            plan::create_dir_all(dir)?;
+            Some(canonicalize(dir)?)
+        }
+        None => None,
+    };
+    let out_dir = output.as_deref().unwrap_or(root);
+
+    // 输出目录变化时，清理旧目录中上次生成的页面，并全部重写
+    if old_cache.output != output {
+        let old_dir = old_cache.output.as_deref().unwrap_or(root);
+        for page in old_cache.generated.drain() {
+            let page_path = old_dir.join(page);
+            if page_path.exists() {
+                options.cleanup.apply(old_dir, &page_path)?;
+            }
+        }
+        full_rebuild = true;
+    }
+    // 链接写法、模板、标签页的排序、时间写法、是否包含草稿、标签页的目录或摘要长度变化时全部页面都需要重写
+    let templates = PageTemplates::load(&root.join(TEMPLATES_DIR))?;
+    let tag_sorts = options.tag_sorts.fingerprint();
+    if old_cache.link_style != options.link_style
+        || old_cache.templates != templates.fingerprint
+        || old_cache.tag_sorts != tag_sorts
+        || old_cache.time_format != options.time_format
+        || old_cache.include_drafts != options.include_drafts
+        || old_cache.tag_dir != options.tag_dir
+        || old_cache.excerpt != options.excerpt
+    {
+        full_rebuild = true;
+    }
+
+    let vault = Vault::scan_with(
+        root,
+        &ScanOptions {
+            cache: &old_cache,
+            verbose: options.verbose,
+            exclude: &options.exclude,
+            keys: &options.keys,
+            org: options.org,
+            date_source: options.date_source,
+            changed: options.changed.as_ref(),
+            drafts: options.include_drafts,
+            follow_symlinks: options.follow_symlinks,
+        },
+    )?;
+    timings.lap("扫描笔记");
+
+    // 增量构建时只重写受影响的页面
+    let mut new_cache = vault.to_cache();
+    new_cache.output = output.clone();
+    new_cache.link_style = options.link_style;
+    new_cache.templates = templates.fingerprint.clone();
+    new_cache.tag_sorts = tag_sorts;
+    new_cache.time_format = options.time_format;
+    new_cache.include_drafts = options.include_drafts;
+    new_cache.tag_dir = options.tag_dir.clone();
+    new_cache.excerpt = options.excerpt;
+    let affected = if full_rebuild {
+        None
+    } else {
+        Some(Affected::between(&old_cache, &new_cache))
+    };
+
+    let link_prefix = link_prefix(root, out_dir)?;
+    let progress = Progress::new("写入页面", 0, options.verbose);
+    new_cache.generated = vault.indexes().write_with(
+        out_dir,
+        &WriteOptions {
+            affected: affected.as_ref(),
+            cleanup: &options.cleanup,
+            link_prefix: &link_prefix,
+            tag_columns: options.tag_columns,
+            date_columns: options.date_columns,
+            link_style: options.link_style,
+            templates: Some(&templates),
+            tag_sorts: &options.tag_sorts,
+            time_format: options.time_format,
+            title_groups: options.title_groups,
+            mermaid: options.mermaid,
+            hubs: options.hubs,
+            excerpt: options.excerpt,
+            progress: Some(&progress),
+            tag_dir: &options.tag_dir,
+            previous: Some(&old_cache.generated),
+        },
+    )?;
+    drop(progress);
+
+    // gtx stats、gtx check、gtx attachments、gtx todos、gtx archive 写入的报告页面不由这里生成，保留到下次
+    for page in [
+        STATS_PAGE,
+        BROKEN_LINKS_PAGE,
+        ATTACHMENTS_PAGE,
+        TODOS_PAGE,
+        ARCHIVE_INDEX_PAGE,
+    ] {
+        if old_cache.generated.contains(page) && out_dir.join(page).exists() {
+            new_cache.generated.insert(page.to_string());
+        }
+    }
+    // gtx query 保存的查询页面按当前的笔记重新生成
+    for page in &old_cache.generated {
+        let page_path = out_dir.join(page);
+        if page.starts_with(&format!("{}/", QUERIES_DIR)) && page_path.exists() {
+            query::refresh_saved(&page_path, vault.notes(), options.link_style, &link_prefix)?;
+            new_cache.generated.insert(page.clone());
+        }
+    }
+    // 笔记中的查询块按当前的笔记重新生成结果
+    let notes: Vec<&NoteMeta> = vault.notes().collect();
+    for note in notes.iter().filter(|note| note.queries) {
+        query::refresh_blocks(&vault.note_path(&note.name), &notes, options.link_style)?;
+    }
+
+    timings.lap("写入页面");
+
+    // 不再存在的标签与日期对应的旧页面
+    remove_stale_pages(
+        out_dir,
+        &old_cache.generated,
+        &new_cache.generated,
+        &options.cleanup,
+    )?;
+    // 扫描时遇到的、缓存中没有记录的旧生成页面，本次没有重新生成的按清理策略处理
+    for file_path in vault.stale_pages() {
+        let regenerated = file_path.strip_prefix(out_dir).is_ok_and(|page| {
+            let parts: Vec<_> = page
+                .components()
+                .map(|part| part.as_os_str().to_string_lossy())
+                .collect();
+            new_cache.generated.contains(&parts.join("/"))
+        });
+        if regenerated {
+            continue;
+        }
+        options.cleanup.apply(root, file_path).map_err(|e| {
+            io::Error::new(
+                e.kind(),
+                i18n::trf("清理生成页面失败 {}: {}", &[&file_path.display(), &e]),
+            )
+        })?;
+    }
+    timings.lap("清理旧页面");
+
+    // 演练模式下不保存索引，下次构建时看到的改动与本次相同
+    if !plan::enabled() {
+        if options.backend == Backend::Sqlite {
+            sqlite::update(
+                &root.join(INDEX_DB),
+                cached.then_some(&old_cache),
+                &new_cache,
+            )?;
+        }
+        if options.search {
+            SearchIndex::update(root, &new_cache)?;
+        }
+        new_cache.save(&cache_path)?;
+        Manifest {
+            output: new_cache.output.clone(),
+            files: new_cache.generated.clone(),
+        }
+        .save(&manifest_path(root))?;
+        timings.lap("保存索引");
+    }
+
+    let mut invalid_created: Vec<(PathBuf, String)> = vault
+        .notes()
+        .filter_map(|note| {
+            let value = note.invalid_created.as_ref()?;
+            Some((vault.note_path(&note.name), value.clone()))
+        })
+        .collect();
+    invalid_created.sort();
+
+    Ok(BuildReport {
+        notes: new_cache.notes.len(),
+        affected,
+        scan: vault.report().clone(),
+        invalid_created,
+        timings,
+    })
+}
+
+/// 从输出目录指向笔记的链接前缀
+///
+/// 输出目录位于博客目录之内时链接按文件名解析，不需要前缀；
+/// 否则为从输出目录到博客目录的相对路径
+pub fn link_prefix(root: &Path, out_dir: &Path) -> io::Result<String> {
+    let root = canonicalize(root)?;
+    let out_dir = canonicalize(out_dir)?;
+    if out_dir.starts_with(&root) {
+        return Ok(String::new());
+    }
+
+    let root_parts: Vec<Component> = root.components().collect();
+    let out_parts: Vec<Component> = out_dir.components().collect();
+    let common = root_parts
+        .iter()
+        .zip(&out_parts)
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut prefix = "../".repeat(out_parts.len() - common);
+    for part in &root_parts[common..] {
+        prefix.push_str(&part.as_os_str().to_string_lossy());
+        prefix.push('/');
+    }
+    Ok(prefix)
+}
+
+// 同 fs::canonicalize；演练模式下输出目录可能还不存在，此时规范化已存在的上级目录再接上其余部分
+fn canonicalize(path: &Path) -> io::Result<PathBuf> {
+    match fs::canonicalize(path) {
+        Err(e) if plan::enabled() && e.kind() == io::ErrorKind::NotFound => {
+            let (Some(parent), Some(name)) = (path.parent(), path.file_name()) else {
+                return Err(e);
+            };
+            let parent = if parent.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                parent
+            };
+            Ok(canonicalize(parent)?.join(name))
+        }
+        result => result,
+    }
+}
@@ -0,0 +1,502 @@
+// 增量构建缓存，保存在 <目录>/.gtx/cache.json
+//
+// 记录每篇笔记的修改时间、大小与解析结果，未变化的笔记无需重新解析；
+// 同时记录上次生成的页面，扫描时跳过它们。
+
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, Metadata};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use crate::date::Date;
+use crate::index::tag_levels;
+use crate::json::{self, Json};
+use crate::links::Link;
+use crate::note::{FrontmatterKeys, NoteMeta};
+use crate::output::{LinkStyle, TimeFormat, write_atomic};
+use crate::todos::Task;
+use crate::vault::DateSource;
+
+// 缓存格式变化时递增，旧版本缓存会被丢弃
+const CACHE_VERSION: u64 = 15;
+
+/// 用于判断文件是否变化的信息
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FileStamp {
+    pub mtime_secs: u64,
+    pub mtime_nanos: u32,
+    pub size: u64,
+}
+
+impl FileStamp {
+    pub fn from_metadata(metadata: &Metadata) -> Self {
+        let mtime = metadata
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .unwrap_or_default();
+        FileStamp {
+            mtime_secs: mtime.as_secs(),
+            mtime_nanos: mtime.subsec_nanos(),
+            size: metadata.len(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct CachedNote {
+    pub stamp: FileStamp,
+    pub meta: NoteMeta,
+}
+
+#[derive(Default)]
+pub struct Cache {
+    /// 笔记名称（相对博客目录的路径，不含 .md） -> 缓存的解析结果
+    pub notes: HashMap<String, CachedNote>,
+    /// 上次生成的页面，相对输出目录的路径，以 / 分隔
+    pub generated: HashSet<String>,
+    /// 上次的输出目录，None 表示博客目录本身
+    pub output: Option<PathBuf>,
+    /// 解析笔记时使用的文件头键名
+    pub keys: FrontmatterKeys,
+    /// 解析笔记时没有 Created 字段的笔记的时间来源
+    pub date_source: DateSource,
+    /// 生成页面时的链接写法
+    pub link_style: LinkStyle,
+    /// 生成页面时自定义模板的摘要，没有自定义模板时为空
+    pub templates: String,
+    /// 生成页面时标签页排序设置的摘要，见 `TagSorts::fingerprint`
+    pub tag_sorts: String,
+    /// 生成页面时时间的写法
+    pub time_format: TimeFormat,
+    /// 生成页面时是否包含草稿
+    pub include_drafts: bool,
+    /// 生成页面时标签页所在的目录，为空时标签页位于输出目录下
+    pub tag_dir: String,
+    /// 生成页面时摘要的最大字符数，0 表示不显示
+    pub excerpt: usize,
+}
+
+impl Cache {
+    /// 缓存不存在或无法解析时返回 None，调用方应进行完整构建
+    pub fn load(path: &Path) -> Option<Cache> {
+        let text = fs::read_to_string(path).ok()?;
+        let root = match json::parse(&text) {
+            Ok(root) => root,
+            Err(e) => {
+                eprintln!("缓存文件 {} 已损坏，将完整重建: {}", path.display(), e);
+                return None;
+            }
+        };
+        if root.get("version").and_then(Json::as_u64) != Some(CACHE_VERSION) {
+            return None;
+        }
+
+        let mut cache = Cache::default();
+        for (name, entry) in root.get("notes")?.as_object()? {
+            cache
+                .notes
+                .insert(name.clone(), note_from_json(name, entry)?);
+        }
+        for page in root.get("generated")?.as_array()? {
+            cache.generated.insert(page.as_str()?.to_string());
+        }
+        // 旧缓存没有该字段，视为输出到博客目录
+        cache.output = root.get("output").and_then(Json::as_str).map(PathBuf::from);
+        if let Some(style) = root.get("link_style") {
+            cache.link_style = LinkStyle::from_name(style.as_str()?)?;
+        }
+        if let Some(templates) = root.get("templates") {
+            cache.templates = templates.as_str()?.to_string();
+        }
+        if let Some(tag_sorts) = root.get("tag_sorts") {
+            cache.tag_sorts = tag_sorts.as_str()?.to_string();
+        }
+        if let Some(format) = root.get("time_format") {
+            cache.time_format = TimeFormat::from_name(format.as_str()?)?;
+        }
+        if let Some(include) = root.get("include_drafts") {
+            cache.include_drafts = matches!(include, Json::Bool(true));
+        }
+        // 旧缓存没有该字段，当时标签页位于输出目录下
+        if let Some(excerpt) = root.get("excerpt") {
+            cache.excerpt = excerpt.as_u64()? as usize;
+        }
+        if let Some(tag_dir) = root.get("tag_dir") {
+            cache.tag_dir = tag_dir.as_str()?.to_string();
+        }
+        if let Some(source) = root.get("date_source") {
+            cache.date_source = DateSource::from_name(source.as_str()?)?;
+        }
+        if let Some(keys) = root.get("keys") {
+            cache.keys = FrontmatterKeys {
+                title: keys.get("title")?.as_str()?.to_string(),
+                created: keys.get("created")?.as_str()?.to_string(),
+                updated: keys.get("updated")?.as_str()?.to_string(),
+                tags: keys.get("tags")?.as_str()?.to_string(),
+                aliases: keys.get("aliases")?.as_str()?.to_string(),
+                id: keys.get("id")?.as_str()?.to_string(),
+                draft: keys.get("draft")?.as_str()?.to_string(),
+                pinned: keys.get("pinned")?.as_str()?.to_string(),
+                status: keys.get("status")?.as_str()?.to_string(),
+                summary: keys.get("summary")?.as_str()?.to_string(),
+                review: keys.get("review")?.as_str()?.to_string(),
+                fallbacks: match keys.get("fallbacks") {
+                    Some(pairs) => pairs
+                        .as_array()?
+                        .iter()
+                        .map(|pair| {
+                            let parts = pair.as_array()?;
+                            Some((
+                                parts.first()?.as_str()?.to_string(),
+                                parts.get(1)?.as_str()?.to_string(),
+                            ))
+                        })
+                        .collect::<Option<Vec<_>>>()?,
+                    None => Vec::new(),
+                },
+            };
+        }
+        Some(cache)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut names: Vec<&String> = self.notes.keys().collect();
+        names.sort();
+        let notes = names
+            .into_iter()
+            .map(|name| (name.clone(), note_to_json(&self.notes[name])))
+            .collect();
+
+        let mut generated: Vec<&str> = self.generated.iter().map(String::as_str).collect();
+        generated.sort();
+
+        let root = Json::Object(vec![
+            ("version".to_string(), CACHE_VERSION.into()),
+            ("notes".to_string(), Json::Object(notes)),
+            ("generated".to_string(), generated.into()),
+            (
+                "output".to_string(),
+                self.output
+                    .as_ref()
+                    .map(|dir| dir.to_string_lossy().into_owned())
+                    .into(),
+            ),
+            ("link_style".to_string(), self.link_style.name().into()),
+            ("templates".to_string(), self.templates.as_str().into()),
+            ("tag_sorts".to_string(), self.tag_sorts.as_str().into()),
+            ("time_format".to_string(), self.time_format.name().into()),
+            ("include_drafts".to_string(), self.include_drafts.into()),
+            ("tag_dir".to_string(), self.tag_dir.as_str().into()),
+            ("excerpt".to_string(), self.excerpt.into()),
+            ("date_source".to_string(), self.date_source.name().into()),
+            (
+                "keys".to_string(),
+                Json::Object(vec![
+                    ("title".to_string(), self.keys.title.as_str().into()),
+                    ("created".to_string(), self.keys.created.as_str().into()),
+                    ("updated".to_string(), self.keys.updated.as_str().into()),
+                    ("tags".to_string(), self.keys.tags.as_str().into()),
+                    ("aliases".to_string(), self.keys.aliases.as_str().into()),
+                    ("id".to_string(), self.keys.id.as_str().into()),
+                    ("draft".to_string(), self.keys.draft.as_str().into()),
+                    ("pinned".to_string(), self.keys.pinned.as_str().into()),
+                    ("status".to_string(), self.keys.status.as_str().into()),
+                    ("summary".to_string(), self.keys.summary.as_str().into()),
+                    ("review".to_string(), self.keys.review.as_str().into()),
+                    (
+                        "fallbacks".to_string(),
+                        Json::Array(
+                            self.keys
+                                .fallbacks
+                                .iter()
+                                .map(|(key, other)| {
+                                    Json::Array(vec![key.as_str().into(), other.as_str().into()])
+                                })
+                                .collect(),
+                        ),
+                    ),
+                ]),
+            ),
+        ]);
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        write_atomic(path, &root.to_pretty())
+    }
+}
+
+fn note_to_json(note: &CachedNote) -> Json {
+    let meta = &note.meta;
+    Json::Object(vec![
+        ("mtime_secs".to_string(), note.stamp.mtime_secs.into()),
+        (
+            "mtime_nanos".to_string(),
+            u64::from(note.stamp.mtime_nanos).into(),
+        ),
+        ("size".to_string(), note.stamp.size.into()),
+        ("title".to_string(), meta.title.as_str().into()),
+        ("created".to_string(), datetime_to_json(&meta.created)),
+        (
+            "invalid_created".to_string(),
+            meta.invalid_created.as_deref().into(),
+        ),
+        ("updated".to_string(), datetime_to_json(&meta.updated)),
+        ("tags".to_string(), meta.tags.clone().into()),
+        ("aliases".to_string(), meta.aliases.clone().into()),
+        ("id".to_string(), meta.id.as_deref().into()),
+        ("draft".to_string(), meta.draft.into()),
+        ("pinned".to_string(), meta.pinned.into()),
+        ("status".to_string(), meta.status.as_deref().into()),
+        ("words".to_string(), meta.words.into()),
+        ("summary".to_string(), meta.summary.as_deref().into()),
+        ("review".to_string(), meta.review.as_deref().into()),
+        ("queries".to_string(), meta.queries.into()),
+        (
+            "links".to_string(),
+            Json::Array(
+                meta.links
+                    .iter()
+                    .map(|link| Json::Array(vec![link.target.as_str().into(), link.line.into()]))
+                    .collect(),
+            ),
+        ),
+        (
+            "embeds".to_string(),
+            Json::Array(
+                meta.embeds
+                    .iter()
+                    .map(|link| Json::Array(vec![link.target.as_str().into(), link.line.into()]))
+                    .collect(),
+            ),
+        ),
+        (
+            "tasks".to_string(),
+            Json::Array(
+                meta.tasks
+                    .iter()
+                    .map(|task| {
+                        Json::Array(vec![
+                            task.text.as_str().into(),
+                            task.done.into(),
+                            task.line.into(),
+                            task.due.map(|due| due.to_string()).into(),
+                        ])
+                    })
+                    .collect(),
+            ),
+        ),
+    ])
+}
+
+fn note_from_json(name: &str, entry: &Json) -> Option<CachedNote> {
+    let stamp = FileStamp {
+        mtime_secs: entry.get("mtime_secs")?.as_u64()?,
+        mtime_nanos: entry.get("mtime_nanos")?.as_u64()? as u32,
+        size: entry.get("size")?.as_u64()?,
+    };
+
+    let created = datetime_from_json(entry.get("created")?)?;
+    let updated = datetime_from_json(entry.get("updated")?)?;
+
+    let tags = entry
+        .get("tags")?
+        .as_array()?
+        .iter()
+        .map(|tag| tag.as_str().map(str::to_string))
+        .collect::<Option<Vec<_>>>()?;
+
+    let aliases = entry
+        .get("aliases")?
+        .as_array()?
+        .iter()
+        .map(|alias| alias.as_str().map(str::to_string))
+        .collect::<Option<Vec<_>>>()?;
+
+    let links = links_from_json(entry.get("links")?)?;
+    let embeds = links_from_json(entry.get("embeds")?)?;
+
+    // 待办事项保存为 [文本, 是否完成, 行号, 截止日期或 null]
+    let tasks = entry
+        .get("tasks")?
+        .as_array()?
+        .iter()
+        .map(|task| {
+            let parts = task.as_array()?;
+            Some(Task {
+                text: parts.first()?.as_str()?.to_string(),
+                done: matches!(parts.get(1)?, Json::Bool(true)),
+                line: parts.get(2)?.as_u64()? as usize,
+                due: match parts.get(3)? {
+                    Json::Null => None,
+                    value => Some(Date::parse(value.as_str()?).ok()?.0),
+                },
+            })
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    Some(CachedNote {
+        stamp,
+        meta: NoteMeta {
+            name: name.to_string(),
+            title: entry.get("title")?.as_str()?.to_string(),
+            created,
+            invalid_created: match entry.get("invalid_created")? {
+                Json::Null => None,
+                value => Some(value.as_str()?.to_string()),
+            },
+            updated,
+            tags,
+            aliases,
+            id: match entry.get("id")? {
+                Json::Null => None,
+                value => Some(value.as_str()?.to_string()),
+            },
+            links,
+            embeds,
+            tasks,
+            draft: matches!(entry.get("draft")?, Json::Bool(true)),
+            pinned: matches!(entry.get("pinned")?, Json::Bool(true)),
+            status: match entry.get("status")? {
+                Json::Null => None,
+                value => Some(value.as_str()?.to_string()),
+            },
+            words: entry.get("words")?.as_u64()? as usize,
+            summary: match entry.get("summary")? {
+                Json::Null => None,
+                value => Some(value.as_str()?.to_string()),
+            },
+            review: match entry.get("review")? {
+                Json::Null => None,
+                value => Some(value.as_str()?.to_string()),
+            },
+            queries: matches!(entry.get("queries")?, Json::Bool(true)),
+        },
+    })
+}
+
+// 链接与嵌入保存为 [目标, 行号]
+fn links_from_json(value: &Json) -> Option<Vec<Link>> {
+    value
+        .as_array()?
+        .iter()
+        .map(|link| {
+            let parts = link.as_array()?;
+            Some(Link {
+                target: parts.first()?.as_str()?.to_string(),
+                line: parts.get(1)?.as_u64()? as usize,
+            })
+        })
+        .collect()
+}
+
+// 日期与时间保存为 [YYYYMMDD, 时间]
+fn datetime_to_json(datetime: &Option<(Date, String)>) -> Json {
+    datetime
+        .as_ref()
+        .map(|(date, time)| vec![date.to_string(), time.clone()])
+        .into()
+}
+
+// 外层 None 表示缓存格式错误，内层 None 表示字段为 null
+fn datetime_from_json(value: &Json) -> Option<Option<(Date, String)>> {
+    match value {
+        Json::Null => Some(None),
+        value => {
+            let parts = value.as_array()?;
+            let (date, _) = Date::parse(parts.first()?.as_str()?).ok()?;
+            Some(Some((date, parts.get(1)?.as_str()?.to_string())))
+        }
+    }
+}
+
+/// 两次构建之间发生变化的笔记所涉及的标签、日期与链接目标
+pub struct Affected {
+    pub tags: HashSet<String>,
+    pub dates: HashSet<String>,
+    /// 更新日期页
+    pub updated: HashSet<String>,
+    /// 链接目标原文，以及变化笔记自身的文件名、标题、别名和 ID
+    pub links: HashSet<String>,
+}
+
+impl Affected {
+    /// 比较两份缓存中的笔记
+    pub fn between(old: &Cache, new: &Cache) -> Self {
+        let mut affected = Affected {
+            tags: HashSet::new(),
+            dates: HashSet::new(),
+            updated: HashSet::new(),
+            links: HashSet::new(),
+        };
+
+        let names: HashSet<&String> = old.notes.keys().chain(new.notes.keys()).collect();
+        for name in names {
+            let before = old.notes.get(name).map(|n| &n.meta);
+            let after = new.notes.get(name).map(|n| &n.meta);
+            if before.map(indexed_fields) == after.map(indexed_fields) {
+                continue;
+            }
+            for meta in before.into_iter().chain(after) {
+                // 层级标签的上级页面列出子标签与全部笔记，同样受影响
+                affected.tags.extend(
+                    meta.tags
+                        .iter()
+                        .flat_map(|tag| tag_levels(tag))
+                        .map(str::to_string),
+                );
+                affected
+                    .dates
+                    .extend(meta.created.iter().map(|(date, _)| date.to_string()));
+                affected
+                    .updated
+                    .extend(meta.updated.iter().map(|(date, _)| date.to_string()));
+                affected
+                    .links
+                    .extend(meta.links.iter().map(|link| link.target.clone()));
+                affected.links.insert(meta.name.clone());
+                affected.links.insert(meta.title.clone());
+                affected.links.extend(meta.aliases.iter().cloned());
+                affected.links.extend(meta.id.iter().cloned());
+            }
+        }
+
+        affected
+    }
+}
+
+// 生成页面用到的字段
+type IndexedFields<'a> = (
+    &'a String,
+    &'a Option<(Date, String)>,
+    &'a Option<(Date, String)>,
+    &'a Vec<String>,
+    &'a Vec<String>,
+    &'a Option<String>,
+    &'a Vec<Link>,
+    usize,
+    bool,
+    bool,
+    &'a Option<String>,
+    &'a Option<String>,
+);
+
+// 标签页与日期页显示字数、阅读时间与摘要，字数与摘要同样计入
+fn indexed_fields(meta: &NoteMeta) -> IndexedFields<'_> {
+    (
+        &meta.title,
+        &meta.created,
+        &meta.updated,
+        &meta.tags,
+        &meta.aliases,
+        &meta.id,
+        &meta.links,
+        meta.words,
+        meta.draft,
+        meta.pinned,
+        &meta.status,
+        &meta.summary,
+    )
+}
@@ -0,0 +1,71 @@
+// 失效链接检查
+
+use std::fmt::Write;
+
+use crate::i18n;
+use crate::index::{DateTree, IndexSet, date_tree};
+use crate::output::LinkStyle;
+
+/// 失效链接报告页面的文件名，位于输出目录下
+pub const BROKEN_LINKS_PAGE: &str = "broken-links.md";
+
+/// 一条找不到目标的链接
+#[derive(Debug, Clone)]
+pub struct BrokenLink {
+    pub source_name: String,
+    pub source_title: String,
+    /// 链接目标，已去掉别名、标题锚点与 .md 后缀
+    pub target: String,
+    pub line: usize,
+}
+
+/// 找出所有目标既不是笔记文件名、标题，也不是标签页、日期页、年月汇总页的链接，
+/// 按来源文件名与行号排序
+pub fn broken_links(indexes: &IndexSet) -> Vec<BrokenLink> {
+    let mut broken = Vec::new();
+    let rollups = date_tree(&indexes.dates);
+    for (target, sources) in indexes.links.unresolved() {
+        if is_generated_page(indexes, &rollups, target) {
+            continue;
+        }
+        broken.extend(sources.iter().map(|source| BrokenLink {
+            source_name: source.source_name.clone(),
+            source_title: source.source_title.clone(),
+            target: target.to_string(),
+            line: source.line,
+        }));
+    }
+    broken.sort_by(|a, b| a.source_name.cmp(&b.source_name).then(a.line.cmp(&b.line)));
+    broken
+}
+
+/// 链接目标是否为标签页、日期页或年月汇总页，这些生成的页面可以作为链接目标
+///
+/// rollups 为 `date_tree(&indexes.dates)`。
+pub fn is_generated_page(indexes: &IndexSet, rollups: &DateTree, target: &str) -> bool {
+    indexes.tags.get_inputs().contains(target)
+        || indexes.dates.get_inputs().contains(target)
+        || rollups.contains_key(target)
+        || rollups.values().any(|months| months.contains_key(target))
+}
+
+/// 失效链接报告页面的内容，link_prefix 含义同 `WriteOptions`
+pub fn broken_links_page(broken: &[BrokenLink], style: LinkStyle, link_prefix: &str) -> String {
+    let mut out = i18n::trf(
+        "{}\n# 失效链接\n",
+        &[&style.frontmatter("broken-links", "broken-links")],
+    );
+    if broken.is_empty() {
+        out.push_str(i18n::tr("没有失效链接\n"));
+    }
+    for link in broken {
+        let _ = writeln!(
+            out,
+            "{} (L{}): `[[{}]]`",
+            style.link(link_prefix, &link.source_name, Some(&link.source_title)),
+            link.line,
+            link.target
+        );
+    }
+    out
+}
@@ -0,0 +1,94 @@
+// 旧的生成页面的清理
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::output::unique_dir;
+use crate::plan::{self, Action};
+
+/// 旧的生成页面的清理策略
+pub enum Cleanup {
+    /// 移动到隔离目录（默认），见 `Cleanup::quarantine`
+    Quarantine {
+        trash_dir: PathBuf,
+        /// 本次运行在 trash_dir 下新建的目录，第一次移动文件时创建
+        run_dir: OnceLock<PathBuf>,
+    },
+    /// 直接删除，必须显式传入 --purge
+    Purge,
+}
+
+impl Cleanup {
+    /// 移动到 trash_dir 的策略：每次运行的文件放在 trash_dir 下以时间戳命名的新目录中，
+    /// 保留相对输出目录的路径，不同目录中的同名页面与以后的运行都不会相互覆盖
+    pub fn quarantine(trash_dir: PathBuf) -> Cleanup {
+        Cleanup::Quarantine {
+            trash_dir,
+            run_dir: OnceLock::new(),
+        }
+    }
+
+    /// 按策略处理 dir 下的一个文件，演练模式下只记录将要进行的操作
+    pub fn apply(&self, dir: &Path, file_path: &Path) -> io::Result<()> {
+        let relative = file_path
+            .strip_prefix(dir)
+            .unwrap_or_else(|_| Path::new(file_path.file_name().unwrap()));
+        if plan::enabled() {
+            plan::record(match self {
+                Cleanup::Quarantine { trash_dir, .. } => {
+                    Action::Move(file_path.to_path_buf(), trash_dir.join(relative))
+                }
+                Cleanup::Purge => Action::Remove(file_path.to_path_buf()),
+            });
+            return Ok(());
+        }
+        match self {
+            Cleanup::Quarantine { trash_dir, run_dir } => {
+                let run_dir = match run_dir.get() {
+                    Some(run_dir) => run_dir,
+                    None => {
+                        let created = unique_dir(trash_dir)?;
+                        run_dir.get_or_init(|| created)
+                    }
+                };
+                let target = free_path(&run_dir.join(relative));
+                if let Some(parent) = target.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                // 跨文件系统时 rename 会失败，退回复制后删除
+                if fs::rename(file_path, &target).is_err() {
+                    fs::copy(file_path, &target)?;
+                    fs::remove_file(file_path)?;
+                }
+                println!(
+                    "已移动到隔离目录: {} -> {}",
+                    file_path.display(),
+                    target.display()
+                );
+            }
+            Cleanup::Purge => {
+                fs::remove_file(file_path)?;
+                println!("成功删除文件: {}", file_path.display());
+            }
+        }
+        Ok(())
+    }
+}
+
+// path 已存在时在文件名后加序号（a.md → a.1.md），不覆盖已隔离的文件
+fn free_path(path: &Path) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let ext = path
+        .extension()
+        .map(|ext| format!(".{}", ext.to_string_lossy()))
+        .unwrap_or_default();
+    let mut candidate = path.to_path_buf();
+    let mut i = 0;
+    while candidate.exists() {
+        i += 1;
+        candidate = path.with_file_name(format!("{}.{}{}", stem, i, ext));
+    }
+    candidate
+}
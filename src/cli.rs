@@ -0,0 +1,2590 @@
+// 命令行参数解析
+//
+// 子命令形式: gtx <子命令> [选项] [参数]
+// 为兼容旧用法，`gtx` 与 `gtx <目录路径>` 仍等价于 `gtx index [目录路径]`。
+
+use std::collections::VecDeque;
+use std::fmt;
+
+use crate::date::Date;
+use crate::i18n;
+
+const MAIN_HELP: &str = "\
+gtx - 为博客目录生成标签与日期大纲
+
+用法:
+    gtx [子命令] [选项]
+    gtx <目录路径>            等价于 gtx index <目录路径>
+
+子命令:
+    index    扫描目录并生成 index.md、标签页与日期页
+    new      创建一篇带有文件头的新笔记
+    open     按标题模糊查找笔记并用编辑器打开
+    today    打开今天的日记，不存在时先创建
+    search   在笔记全文中搜索
+    query    按标签、创建日期与标题筛选笔记
+    recent   列出最近创建或修改的笔记
+    random   随机打开一篇笔记，可限定标签
+    review   按间隔重复安排复习到期的笔记
+    list     列出全部笔记，可供 fzf 等选择器使用
+    stats    统计笔记、标签与字数
+    todos    列出正文中未完成的待办事项
+    check    检查找不到目标的 [[链接]]
+    attachments 列出笔记嵌入的图片与文件，检查缺失与未引用的附件
+    doctor   检查笔记文件头，可自动补全缺少的字段
+    clean    清理已不存在的标签与日期留下的旧生成页面
+    graph    导出笔记之间的链接关系图
+    export   导出全部笔记的索引数据
+    import   从其他笔记软件的导出文件导入笔记
+    rename   重命名笔记并改写指向它的 [[链接]]
+    merge    把一篇笔记并入另一篇，改写指向它的 [[链接]]
+    merge-vaults 把另一个博客目录中的笔记与附件合并到当前目录
+    archive  把旧笔记移到 archive/ 目录，不再出现在主索引中
+    tag      管理标签（rename、merge、add、remove）
+    hook     安装提交时更新索引的 git 钩子
+    tui      在终端界面中按标签与日期浏览笔记
+    watch    监视目录，文件变化时增量重建
+    serve    在本地启动网页预览
+    rpc      在标准输入输出上应答 JSON-RPC 请求，供编辑器插件使用
+    lsp      启动语言服务器，在编辑器中补全链接与标签并提示失效链接
+    help     显示帮助信息
+
+选项:
+        --vault <名称>  使用用户配置中 [vault.<名称>] 的博客目录与选项，可写在子命令之后
+    -h, --help          显示帮助信息
+    -V, --version       显示版本号
+
+博客目录:
+    未指定目录时依次使用环境变量 GTX_VAULT、用户配置中的 vault 与用户主目录下的 .data
+    （Windows 上主目录取 %USERPROFILE%）；--vault 指定时使用该博客目录的 path
+
+使用 `gtx help <子命令>` 或 `gtx <子命令> --help` 查看子命令的帮助。";
+
+const INDEX_HELP: &str = "\
+扫描目录并生成 index.md、标签页与日期页
+
+用法:
+    gtx index [选项] [目录路径]
+
+参数:
+    [目录路径]    博客目录，默认为 ~/.data
+
+选项:
+    -o, --output <目录>       生成页面的输出目录，默认为博客目录本身
+        --columns <列数|auto> index.md 中表格的列数，auto 按终端宽度自动计算，
+                              默认标签 4 列、日期 7 列
+        --exclude <通配符>    不参与索引的笔记文件名，可重复使用，写法同 .gtxignore
+        --backend <方式>      索引的存储方式：memory（默认）或 sqlite，
+                              sqlite 另外把索引写入 <目录路径>/.gtx/index.db
+        --link-style <写法>   生成页面的链接写法：wiki（默认）、markdown、obsidian 或 org，
+                              同配置项 link_style；--flavor 与之相同
+        --org                 同时索引 .org 笔记（#+TITLE、#+DATE、#+FILETAGS），
+                              同配置项 org
+        --include-drafts      草稿（Draft: true 或带有 draft 标签）照常出现在标签页、日期页与
+                              index.md 中，默认只列在 drafts.md；同配置项 include_drafts
+        --no-follow-symlinks  跳过符号链接，默认跟随指向笔记的链接，指向同一文件的多个路径只索引一次；
+                              同配置项 follow_symlinks = false
+        --tag-dir <目录>      标签页所在的目录，相对输出目录，默认为 tags；
+                              为空字符串时标签页与笔记放在一起，同名的笔记不会被覆盖；
+                              同配置项 tag_dir
+        --dates-from <来源>   没有 Created 字段的笔记的时间来源：frontmatter（默认）或 git，
+                              git 以 git log --follow 的首次与最近一次提交为创建与更新时间；
+                              同配置项 dates_from
+        --sort <顺序>         标签页中笔记的顺序：created、created-desc、title 或 modified，
+                              默认按加入索引的顺序；同配置项 tag_sort
+        --mermaid <数量>      在 index.md 中嵌入链接最多的几篇笔记的 Mermaid 关系图，
+                              0 表示不生成（默认）；同配置项 mermaid
+        --hubs <数量>         在 index.md 的 Hubs 部分列出 PageRank 中心度最高的几篇笔记，
+                              0 表示不列出（默认）；同配置项 hubs
+        --excerpt <字数>      在标签页与日期页的每篇笔记下显示至多这么多字的摘要，取自 Summary
+                              字段或正文第一段，0 表示不显示（默认）；同配置项 excerpt
+        --trash-dir <目录>    旧生成页面的隔离目录，默认为 <目录路径>/.gtx/trash
+        --purge               直接删除旧生成页面而不是移动到隔离目录
+        --full                忽略缓存，重新解析全部笔记并重写所有页面
+    -n, --dry-run             只列出将新建、改写、删除或移动到隔离目录的页面，
+                              不修改任何文件，也不保存索引
+        --changed-only        只重新解析本次 git 提交涉及的笔记，其余笔记直接使用缓存，
+                              供 gtx hook install 安装的钩子使用
+        --with-search         同时更新 <目录路径>/.gtx/search 下的全文搜索索引，
+                              gtx search 据此按相关度排序；同配置项 with_search
+    -q, --quiet               不输出处理过程与耗时，只报告错误
+    -v, --verbose             列出每个重新解析的文件，不显示进度条
+    -h, --help                显示帮助信息";
+
+const NEW_HELP: &str = "\
+创建一篇带有文件头的新笔记
+
+用法:
+    gtx new [选项] <标题>
+
+参数:
+    <标题>    笔记标题，写入 Title 字段
+
+选项:
+    -d, --dir <目录路径>    博客目录，默认为 ~/.data
+    -n, --name <文件名>     文件名（不含 .md），默认与标题相同
+    -t, --tags <标签>       以逗号分隔的标签列表，省略时为 NeedTag
+    -z, --zettel            生成 YYYYMMDDHHMM 形式的 ID 写入 ID 字段，文件名默认为 \"ID 标题\"；
+                            同一分钟内的 ID 已被占用时依次加一
+    -h, --help              显示帮助信息";
+
+const OPEN_HELP: &str = "\
+按标题、别名或文件名模糊查找笔记，并用 $EDITOR（未设置时为 vi）打开；
+有多篇笔记匹配时列出候选，输入序号选择
+
+用法:
+    gtx open [选项] <查询>
+
+参数:
+    <查询>    标题中依次出现的字符，忽略大小写与空白
+
+选项:
+    -d, --dir <目录路径>    博客目录，默认为 ~/.data
+    -p, --print             只输出选中笔记的路径，不打开编辑器
+    -h, --help              显示帮助信息";
+
+const TODAY_HELP: &str = "\
+打开今天的日记 YYYY-MM-DD.md，不存在时先创建：Created 为当前时间，Tags 含 journal，
+下次生成索引时出现在当天的日期页中
+
+用法:
+    gtx today [选项] [目录路径]
+
+参数:
+    [目录路径]    博客目录，默认为 ~/.data
+
+选项:
+    -p, --print    只输出日记的路径，不打开编辑器
+    -h, --help     显示帮助信息
+
+存在 <目录路径>/.gtx/templates/journal.md 时按其内容创建日记，可用变量 frontmatter
+（默认的文件头）、title（YYYY-MM-DD）、date（YYYYMMDD）与 time（HH:MM）。";
+
+const TUI_HELP: &str = "\
+在终端界面中浏览笔记：左侧两栏按标签与月份筛选，中间为笔记列表，右侧预览所选笔记
+
+用法:
+    gtx tui [选项] [目录路径]
+
+参数:
+    [目录路径]    博客目录，默认为 ~/.data
+
+按键:
+    Tab、h、l、←、→    切换栏
+    j、k、↑、↓         移动光标，PgUp、PgDn 翻页，g、G 到首尾
+    /                   输入过滤文本，按标题、别名与文件名筛选；Enter 结束输入
+    Esc                 清除过滤文本
+    Enter               在笔记栏中用 $EDITOR（未设置时为 vi）打开所选笔记
+    q、Ctrl-C           退出
+
+选项:
+    -d, --dir <目录路径>    博客目录，与 [目录路径] 相同
+    -h, --help              显示帮助信息";
+
+const SEARCH_HELP: &str = "\
+在笔记全文（含文件头）中搜索，输出文件、标题、行号与匹配片段
+
+有 gtx index --with-search 建立的搜索索引时，返回含有查询中全部词的笔记，
+按 BM25 相关度排序，每篇笔记显示第一处匹配；否则逐行搜索全部笔记。
+
+用法:
+    gtx search [选项] <查询>
+
+参数:
+    <查询>    要搜索的文本，使用 --regex 时为正则表达式；
+              使用搜索索引时以空白分隔的词都必须出现，\"...\" 中的内容按短语匹配，
+              不区分大小写
+
+选项:
+    -d, --dir <目录路径>     博客目录，默认为 ~/.data
+    -i, --ignore-case        忽略大小写
+    -e, --regex              将查询视为正则表达式，总是逐行搜索
+    -C, --context <行数>     显示匹配行前后的行数，默认为 0
+    -f, --filter <查询>      只搜索满足条件的笔记，写法同 gtx query，如 'tag:rust created:2024'
+    -n, --limit <数量>       使用搜索索引时最多显示的笔记数，默认为 20
+        --scan               不使用搜索索引，逐行搜索
+    -h, --help               显示帮助信息";
+
+const QUERY_HELP: &str = "\
+按标签、创建日期与标题筛选笔记，输出文件、标题、创建时间与标签；没有匹配时退出码为 1
+
+用法:
+    gtx query [选项] <查询>
+
+参数:
+    <查询>    由条件与 AND、OR、NOT、括号组成，相邻的条件之间默认为 AND:
+                  tag:rust                 含有标签 rust 或其下的层级标签
+                  created:2024-05          创建于某年、某月或某日，date: 相同
+                  created:>=2024-01-01     比较创建日期，支持 >=、<=、>、<
+                  title:入门 或 入门       标题包含该文本，忽略大小写
+              含空白的值加双引号，如 title:\"rust 入门\"
+
+选项:
+    -d, --dir <目录路径>     博客目录，默认为 ~/.data
+    -s, --save <名称>        同时保存为输出目录下的 queries/<名称>.md，
+                             之后每次构建索引时按当前的笔记重新生成
+    -o, --output <目录>      保存页面的输出目录，默认为博客目录本身
+    -h, --help               显示帮助信息
+
+示例:
+    gtx query \"tag:rust AND tag:async AND created:>=2024-01-01\"
+    gtx query -s 待整理 \"NOT tag:done AND (tag:idea OR tag:draft)\"
+
+笔记中的 ```gtx 代码块是查询块，查询之外可以加 sort:created|updated|title|name [asc|desc]
+与 limit:N。构建索引时查询结果写在代码块之后的 <!-- gtx:begin --> 与 <!-- gtx:end --> 之间，
+导出 html 时查询块直接换成结果列表。";
+
+const RECENT_HELP: &str = "\
+列出最近几天内创建或修改的笔记，按最近一次活动从新到旧排列；没有时退出码为 1
+
+创建时间取自 Created 字段，修改时间为文件的修改时间；修改时间晚于创建时间一天以上
+才视为修改过。两者都按 UTC 比较，Created 不做时区换算。
+
+用法:
+    gtx recent [选项] [目录路径]
+
+参数:
+    [目录路径]    博客目录，默认为 ~/.data
+
+选项:
+        --days <天数>    时间范围，默认为 7
+    -h, --help           显示帮助信息";
+
+const RANDOM_HELP: &str = "\
+随机选出一篇笔记并用 $EDITOR（未设置时为 vi）打开，用于随手重温旧笔记；
+笔记取自索引，草稿默认不在其中
+
+用法:
+    gtx random [选项] [目录路径]
+
+参数:
+    [目录路径]    博客目录，默认为 ~/.data
+
+选项:
+    -t, --tag <标签>        只从含有该标签或其下层级标签的笔记中选取
+    -p, --print             只输出选中笔记的路径，不打开编辑器
+    -h, --help              显示帮助信息";
+
+const REVIEW_HELP: &str = "\
+逐篇列出复习到期的笔记，输入 0–5 的评分后按 SM-2 算法计算下次复习日期并写回 Review 字段
+
+Review 字段写作 `Review: 2024-06-10 6 2.50`，依次为下次复习的日期、间隔天数与难度系数，
+后两项可以省略；写上今天的日期即可把笔记加入复习。评分低于 3 时间隔回到 1 天，
+否则依次为 1 天、6 天，之后乘以难度系数。日期按 UTC 计算。
+
+用法:
+    gtx review [选项] [目录路径]
+
+参数:
+    [目录路径]    博客目录，默认为 ~/.data
+
+选项:
+    -l, --list              只列出到期的笔记，不询问评分
+    -e, --edit              评分前先用 $EDITOR（未设置时为 vi）打开笔记
+    -h, --help              显示帮助信息";
+
+const LIST_HELP: &str = "\
+列出全部笔记，按文件名排序
+
+用法:
+    gtx list [选项] [目录路径]
+
+参数:
+    [目录路径]    博客目录，默认为 ~/.data
+
+选项:
+        --format <格式>    输出格式：plain（默认，文件名与标题）或 fzf，
+                           fzf 每行为 路径<TAB>标题<TAB>标签，标签以逗号分隔，
+                           可直接交给 fzf、skim 等选择器，如
+                           gtx list --format fzf | fzf -d '\\t' --with-nth 2.. | cut -f1
+    -0, --null             每条记录以 NUL 字符结尾而不是换行，供 xargs -0 使用
+    -f, --filter <查询>    只列出满足条件的笔记，写法同 gtx query
+    -h, --help             显示帮助信息";
+
+const STATS_HELP: &str = "\
+统计笔记数、标签数、每月笔记数、字数最多与最少的笔记以及没有标签的笔记
+
+--graph 同时统计笔记之间的链接：每篇笔记的入链与出链数、PageRank 中心度，以及不分方向的
+连通分量；中心度最高的笔记为中心笔记，最大连通分量以外、至少有两篇笔记的分量为孤岛。
+
+用法:
+    gtx stats [选项] [目录路径]
+
+参数:
+    [目录路径]    博客目录，默认为 ~/.data
+
+选项:
+    -n, --top <数量>        列出字数最多与最少的笔记各几篇，默认为 5；
+                            同时为 --graph 列出的中心笔记数
+    -g, --graph             同时统计链接图
+    -w, --write             同时写入输出目录下的 stats.md
+    -o, --output <目录>     stats.md 的输出目录，默认为博客目录本身
+    -h, --help              显示帮助信息";
+
+const TODOS_HELP: &str = "\
+列出正文中 `- [ ] 任务` 形式的待办事项，按截止日期排序，没有截止日期的排在最后
+
+任务中的 @due(2024-06-01) 为截止日期；写入的 todos.md 按截止日期与标签分组。
+
+用法:
+    gtx todos [选项] [目录路径]
+
+参数:
+    [目录路径]    博客目录，默认为 ~/.data
+
+选项:
+    -a, --all               同时列出已完成的 `- [x]` 事项
+    -t, --tag <标签>        只列出含有该标签或其下层级标签的笔记中的事项
+    -w, --write             同时写入输出目录下的 todos.md
+    -o, --output <目录>     todos.md 的输出目录，默认为博客目录本身
+    -h, --help              显示帮助信息";
+
+const CHECK_HELP: &str = "\
+检查正文中找不到目标笔记的 [[链接]]，输出来源文件与行号；有失效链接时退出码为 1
+
+链接目标按文件名、标题依次匹配，指向标签页与日期页的链接视为有效。
+
+用法:
+    gtx check [选项] [目录路径]
+
+参数:
+    [目录路径]    博客目录，默认为 ~/.data
+
+选项:
+    -w, --write             同时写入输出目录下的 broken-links.md
+    -o, --output <目录>     broken-links.md 的输出目录，默认为博客目录本身
+    -h, --help              显示帮助信息";
+
+const ATTACHMENTS_HELP: &str = "\
+列出笔记中 ![[文件]] 与 ![](路径) 嵌入的图片与文件，以及使用它们的笔记；
+检查找不到文件的嵌入与附件目录中没有被引用的文件，有任何一种时退出码为 1
+
+用法:
+    gtx attachments [选项] [目录路径]
+
+参数:
+    [目录路径]    博客目录，默认为 ~/.data
+
+选项:
+    -a, --attachments <目录>    附件目录，相对于博客目录，默认为 attachments
+    -w, --write                 同时写入输出目录下的 attachments.md
+    -o, --output <目录>         attachments.md 的输出目录，默认为博客目录本身
+        --no-follow-symlinks    不进入符号链接指向的目录，默认跟随，同一文件只列出一次；
+                                同配置项 follow_symlinks = false
+    -h, --help                  显示帮助信息";
+
+const DOCTOR_HELP: &str = "\
+检查每篇笔记的文件头：Title 存在、Created 可以解析、Tags 不为空，按问题类别分组输出；
+有未修复的问题时退出码为 1
+
+用法:
+    gtx doctor [选项] [目录路径]
+
+参数:
+    [目录路径]    博客目录，默认为 ~/.data
+
+选项:
+        --fix     补全能自动修复的问题：缺少 Title 时使用文件名，
+                  缺少 Created 时使用文件的修改时间
+    -h, --help    显示帮助信息";
+
+const CLEAN_HELP: &str = "\
+完整重建索引，并按生成文件清单 .gtx/manifest.json 清理本次不再生成的页面，
+如已不存在的标签与日期的页面；缓存格式升级或输出目录变化后留下的旧页面同样会被清理
+
+用法:
+    gtx clean [选项] [目录路径]
+
+参数:
+    [目录路径]    博客目录，默认为 ~/.data
+
+选项:
+        --trash-dir <目录>    旧生成页面的隔离目录，默认为 <目录路径>/.gtx/trash
+        --purge               直接删除旧生成页面而不是移动到隔离目录
+    -n, --dry-run             只列出将清理与改写的页面，不修改任何文件
+    -h, --help                显示帮助信息";
+
+const GRAPH_HELP: &str = "\
+导出笔记之间的链接关系图：节点为笔记，按第一个标签着色；边为正文中的 [[链接]]。
+使用 --tags 时改为导出标签共现图：节点为标签，边连接在同一篇笔记中出现的标签
+
+用法:
+    gtx graph [选项] [目录路径]
+
+参数:
+    [目录路径]    博客目录，默认为 ~/.data
+
+选项:
+    -f, --format <格式>     输出格式，目前支持 dot（Graphviz），默认为 dot
+    -t, --tags              导出标签共现图而不是笔记链接关系图
+    -o, --output <文件>     写入文件而不是标准输出
+    -h, --help              显示帮助信息";
+
+const EXPORT_HELP: &str = "\
+导出全部笔记的文件名、路径、标题、创建日期与时间、标签与链接
+
+用法:
+    gtx export [选项] [目录路径]
+
+参数:
+    [目录路径]    博客目录，默认为 ~/.data
+
+选项:
+    -f, --format <格式>     输出格式，json、csv、opml、html、hugo、zola、jekyll、anki
+                            或 epub，默认为 json
+    -o, --output <路径>     json、csv、opml 与 anki 写入文件而不是标准输出；epub 必须指定，为电子书文件；
+                            其他格式必须指定，为站点目录
+        --base-url <地址>   html 站点的绝对地址，如 https://example.com/notes/，用于 sitemap.xml
+        --robots            html 格式同时写入 robots.txt，允许抓取全部页面并指向 sitemap.xml；
+                            需要 --base-url
+    -t, --tag <标签>        epub 只收入带有该标签的笔记，含其下的层级标签
+        --title <书名>      epub 的书名，默认为标签名，没有标签时为目录名
+        --include-drafts    同时导出草稿（Draft: true 或带有 draft 标签），默认不导出；
+                            同配置项 include_drafts
+    -h, --help              显示帮助信息
+
+csv 格式每篇笔记一行，列为 path、title、date、time、tags（以 ; 分隔，没有标签时为 NeedTag）
+与 words，第一行为表头，便于在电子表格中分析。
+
+opml 格式输出标签的大纲，层级标签嵌套在上级标签之下，每个标签下列出直接带有它的笔记，
+笔记链接到相对目录的路径，可导入大纲与思维导图软件。
+
+html 格式将每篇笔记渲染为网页，维基链接转为相对链接，并生成与 index.md
+结构相同的 index.html、标签页与日期页。图片等附件不会复制。用 --base-url 指定站点地址时
+同时生成列出全部页面的 sitemap.xml，最后修改日期取笔记的 Created 与 Updated 字段；
+站点地图要求绝对地址，没有 --base-url 时不生成，并给出警告。
+
+hugo 与 zola 格式把笔记复制到站点目录下的 content/，文件头改写为 title、date 与 tags
+（zola 的 tags 位于 [taxonomies] 中），[[链接]] 改写为 Hugo 的 ref 短代码或 Zola 的
+@/ 内部链接，找不到目标的链接只保留文字。用 --include-drafts 导出的草稿在文件头中标记 draft。
+
+jekyll 格式按创建日期与标题写入站点目录下的 _posts/YYYY-MM-DD-标题.md，没有创建日期的
+笔记与草稿写入 _drafts/；标签同时作为 categories，[[链接]] 改写为 post_url，并生成按分类
+列出文章的 categories.md。
+
+各种格式都不含草稿，指向草稿的链接只保留文字；用 --include-drafts 或配置项 include_drafts
+一并导出。
+
+anki 格式从带有 flashcard 标签的笔记中提取卡片：`Q:` 行开始问题，其后的 `A:` 行开始答案，
+答案持续到下一个 `Q:` 或标题；没有 `Q:` 的笔记以每个标题为问题、其下的正文为答案。输出为
+Anki 可以直接导入的制表符分隔文本，卡片以笔记的 ID（没有时为文件名）加序号为 GUID，
+再次导入时更新已有的卡片。
+
+epub 格式把笔记合成一本电子书，每篇笔记一章，并生成目录。章节按文件头的 Order 字段
+（数字，小的在前）排列，没有 Order 的笔记排在其后，按创建日期。指向书中其他笔记的
+[[链接]] 转为章节之间的链接，其余链接只保留文字；图片不会打包，只保留说明文字。";
+
+const IMPORT_HELP: &str = "\
+从其他笔记软件的导出文件导入笔记
+
+用法:
+    gtx import <来源> [选项] <路径>
+
+来源:
+    joplin    Joplin 导出的 JEX 文件或 RAW 目录
+    enex      Evernote 导出的 ENEX 文件
+    notion    Notion 导出的 markdown ZIP 解压后的目录
+
+参数:
+    <路径>    导出的文件或目录
+
+选项:
+    -d, --dir <目录路径>    博客目录，默认为 ~/.data
+    -h, --help              显示帮助信息
+
+每篇笔记写入一个以标题命名的文件，文件头包含 Title、Updated、Created 与 Tags，
+时间为导出文件中记录的 UTC 时间。与已有文件重名时在文件名后加上序号，不会覆盖已有文件。
+joplin：笔记之间的链接改写为 [[链接]]，图片等附件复制到 <目录路径>/attachments/。
+enex：正文由 HTML 转换为 markdown，附件与加密内容不会导入。
+notion：文件名去掉 Notion 附加的哈希，页面属性中的 Tags、Created、Last edited time
+写入文件头，页面之间的链接改写为 [[链接]]，附件复制到 <目录路径>/attachments/。";
+
+const HOOK_HELP: &str = "\
+安装提交时更新索引的 git 钩子
+
+用法:
+    gtx hook install [选项] [目录路径]
+
+参数:
+    [目录路径]    博客目录，默认为 ~/.data，需位于 git 仓库中
+
+选项:
+        --post-commit    安装为 post-commit 钩子，默认为 pre-commit
+    -f, --force          覆盖不是 gtx 安装的同名钩子
+    -h, --help           显示帮助信息
+
+钩子运行 gtx index --quiet --changed-only，只重新解析提交中的笔记。
+pre-commit 钩子在索引失败（如笔记解析失败）时中止提交；post-commit 钩子不影响提交。";
+
+const TAG_HELP: &str = "\
+管理标签
+
+用法:
+    gtx tag rename [选项] <旧标签> <新标签>
+    gtx tag merge [选项] <标签>... --into <新标签>
+    gtx tag add [选项] <标签>... --query <查询>
+    gtx tag remove [选项] <标签>... --query <查询>
+
+子命令:
+    rename    将所有笔记文件头中的旧标签改为新标签，层级标签的下级一并改名
+    merge     将多个标签合并为一个，同一笔记中合并后重复的标签只保留一个
+    add       为查询匹配的每篇笔记加上标签，已有该标签的笔记不改动
+    remove    从查询匹配的每篇笔记中删去标签，下级标签保留
+
+查询的写法同 `gtx query`，如 \"tag:rust AND created:<2023\"。add 与 remove 先算出全部笔记的
+改动再统一写入。都会把原文件备份到 <目录路径>/.gtx/backup，完成后重新构建索引，
+不再使用的标签页按 index 的方式清理
+
+选项:
+    -d, --dir <目录路径>    博客目录，默认为 ~/.data
+    -n, --dry-run           只列出将被改写的笔记，不修改文件
+    --into <新标签>         merge 合并到的标签
+    -q, --query <查询>      add 与 remove 要改写的笔记
+    -h, --help              显示帮助信息";
+
+const RENAME_HELP: &str = "\
+重命名笔记文件，并改写所有笔记中指向它的 [[链接]]，完成后重新构建索引
+
+用法:
+    gtx rename [选项] <旧文件名> <新文件名>
+
+参数:
+    <旧文件名>    笔记的文件名，可以省略 .md
+    <新文件名>    新的文件名，不能已经存在
+
+[[旧文件名]]、[[旧文件名|别名]]、[[旧文件名#标题]] 与 ![[旧文件名]] 都会改为新文件名，
+链接中的别名与标题保持不变。改写前原文件备份到 <目录路径>/.gtx/backup
+
+选项:
+    -d, --dir <目录路径>    博客目录，默认为 ~/.data
+    -t, --title <新标题>    同时修改笔记的标题，按旧标题书写的链接改为新标题
+    -n, --dry-run           只列出将被改写的笔记，不修改文件
+    -h, --help              显示帮助信息";
+
+const MERGE_HELP: &str = "\
+把第二篇笔记并入第一篇并删除它，改写所有笔记中指向它的 [[链接]]，完成后重新构建索引
+
+用法:
+    gtx merge [选项] <保留的笔记> <并入的笔记>
+
+参数:
+    <保留的笔记>    合并后保留的笔记文件名，可以省略 .md
+    <并入的笔记>    并入后删除的笔记文件名
+
+并入的笔记正文接在保留的笔记末尾，标签取两者之和，Created 取较早的一个；指向并入笔记的
+[[链接]]（包括按其标题书写的链接）改为指向保留的笔记。改写前原文件备份到 <目录路径>/.gtx/backup
+
+选项:
+    -d, --dir <目录路径>    博客目录，默认为 ~/.data
+    -n, --dry-run           只列出将被改写的笔记，不修改文件
+    -h, --help              显示帮助信息";
+
+const MERGE_VAULTS_HELP: &str = "\
+把另一个博客目录中的笔记与附件复制到当前博客目录，完成后重新构建索引；来源目录不会被修改
+
+用法:
+    gtx merge-vaults [选项] <来源目录>
+
+参数:
+    <来源目录>    要合并进来的博客目录，其中生成的页面与 .gtx 不会被复制
+
+与当前目录中的笔记同名且内容相同的笔记视为已经合并，直接跳过；同名而内容不同时为冲突，
+默认列出冲突后退出，不复制任何文件。--rename 为冲突的笔记在文件名后加上序号（如 笔记 2.md），
+并改写来源笔记中指向它们的 [[链接]]。附件目录中的文件按相同的相对路径复制，同名而内容不同的附件保留原样
+
+选项:
+    -d, --dir <目录路径>        合并到的博客目录，默认为 ~/.data
+        --rename                为重名而内容不同的笔记改名后复制
+    -a, --attachments <目录>    附件目录，相对于博客目录，默认为 attachments
+    -n, --dry-run               只列出将复制与改名的笔记，不修改文件
+    -h, --help                  显示帮助信息";
+
+const ARCHIVE_HELP: &str = "\
+把创建于某日之前的笔记移到 <目录路径>/archive/，完成后重新构建索引并写入 archive-index.md
+
+用法:
+    gtx archive --before <日期> [选项] [目录路径]
+
+参数:
+    [目录路径]    博客目录，默认为 ~/.data
+
+归档的笔记加上 archived 标签，不再出现在标签页、日期页与 index.md 中；其他笔记中指向它们的
+[[链接]] 保持原样，归档时列出仍有这类链接的笔记，gtx check 把它们与失效链接分开列出。
+archive-index.md 按年份列出 archive/ 中的全部笔记。置顶笔记、org 笔记与 archive/ 中已有同名文件的
+笔记保留在原处。加标签前原文件备份到 <目录路径>/.gtx/backup
+
+选项:
+    -b, --before <日期>     归档创建于该日期之前的笔记，写法同 Created，如 2022-01-01
+    -o, --output <目录>     archive-index.md 的输出目录，默认为博客目录本身
+    -n, --dry-run           只列出将归档的笔记与指向它们的链接，不修改文件
+    -h, --help              显示帮助信息";
+
+const WATCH_HELP: &str = "\
+监视目录，笔记变化时增量重建受影响的页面
+
+用法:
+    gtx watch [选项] [目录路径]
+
+参数:
+    [目录路径]    博客目录，默认为 ~/.data
+
+选项:
+        --interval <毫秒>     检查文件变化的间隔，默认为 500
+        --debounce <毫秒>     变化后等待文件稳定的时间，默认为 300
+    -o, --output <目录>       生成页面的输出目录，默认为博客目录本身
+        --columns <列数|auto> index.md 中表格的列数，auto 按终端宽度自动计算，
+                              默认标签 4 列、日期 7 列
+        --exclude <通配符>    不参与索引的笔记文件名，可重复使用，写法同 .gtxignore
+        --backend <方式>      索引的存储方式：memory（默认）或 sqlite，
+                              sqlite 另外把索引写入 <目录路径>/.gtx/index.db
+        --link-style <写法>   生成页面的链接写法：wiki（默认）、markdown、obsidian 或 org，
+                              同配置项 link_style；--flavor 与之相同
+        --org                 同时索引 .org 笔记（#+TITLE、#+DATE、#+FILETAGS），
+                              同配置项 org
+        --include-drafts      草稿（Draft: true 或带有 draft 标签）照常出现在标签页、日期页与
+                              index.md 中，默认只列在 drafts.md；同配置项 include_drafts
+        --no-follow-symlinks  跳过符号链接，默认跟随指向笔记的链接，指向同一文件的多个路径只索引一次；
+                              同配置项 follow_symlinks = false
+        --dates-from <来源>   没有 Created 字段的笔记的时间来源：frontmatter（默认）或 git，
+                              git 以 git log --follow 的首次与最近一次提交为创建与更新时间；
+                              同配置项 dates_from
+        --sort <顺序>         标签页中笔记的顺序：created、created-desc、title 或 modified，
+                              默认按加入索引的顺序；同配置项 tag_sort
+        --mermaid <数量>      在 index.md 中嵌入链接最多的几篇笔记的 Mermaid 关系图，
+                              0 表示不生成（默认）；同配置项 mermaid
+        --hubs <数量>         在 index.md 的 Hubs 部分列出 PageRank 中心度最高的几篇笔记，
+                              0 表示不列出（默认）；同配置项 hubs
+        --excerpt <字数>      在标签页与日期页的每篇笔记下显示至多这么多字的摘要，取自 Summary
+                              字段或正文第一段，0 表示不显示（默认）；同配置项 excerpt
+        --trash-dir <目录>    旧生成页面的隔离目录，默认为 <目录路径>/.gtx/trash
+        --purge               直接删除旧生成页面而不是移动到隔离目录
+    -h, --help                显示帮助信息";
+
+const SERVE_HELP: &str = "\
+在本地启动网页预览，首页为标签与日期索引
+
+用法:
+    gtx serve [选项] [目录路径]
+
+参数:
+    [目录路径]    博客目录，默认为 ~/.data
+
+选项:
+    -p, --port <端口>     监听的端口，默认为 8080，只接受来自本机的连接
+    -w, --watch           同时监视目录：文件变化时像 gtx watch 一样增量重建，
+                          并自动刷新浏览器中打开的页面
+        --api             同时在 /api/ 下提供 JSON 接口：/api/notes、/api/notes/<文件名>、
+                          /api/tags、/api/tags/<标签>、/api/dates/<日期>、/api/search?q=<查询>，
+                          与 --watch 同用时随目录变化更新
+    -h, --help            显示帮助信息";
+
+const RPC_HELP: &str = "\
+在标准输入输出上应答 JSON-RPC 2.0 请求，供编辑器与插件把 gtx 作为常驻后端
+
+启动时扫描一次目录，之后的请求直接使用内存中的索引，reload 时重新扫描。
+每行一个请求，应答同样每行一个；输入结束时退出。
+
+用法:
+    gtx rpc [选项] [目录路径]
+
+参数:
+    [目录路径]    博客目录，默认为 ~/.data
+
+方法:
+    query       {\"query\": \"tag:rust\"}     满足查询的笔记，写法同 gtx query
+    tag         {\"tag\": \"rust\"}           含有该标签或其下层级标签的笔记
+    search      {\"query\": \"...\", \"limit\": 20}  全文搜索，有搜索索引时按相关度排序
+    resolve     {\"target\": \"链接目标\"}     链接指向的笔记，找不到时为 null
+    backlinks   {\"name\": \"笔记\"}          指向该笔记的链接
+    reload      {}                     重新扫描目录
+
+选项:
+    -h, --help    显示帮助信息
+
+示例:
+    echo '{\"jsonrpc\": \"2.0\", \"id\": 1, \"method\": \"tag\", \"params\": {\"tag\": \"rust\"}}' | gtx rpc";
+
+const LSP_HELP: &str = "\
+启动语言服务器（LSP），通过标准输入输出与编辑器通信
+
+    - 在 [[ 之后补全笔记文件名，在行首或空白之后的 # 之后补全标签
+    - 跳转到光标所在的 [[链接]] 指向的笔记
+    - 打开或修改笔记时提示找不到目标的 [[链接]]，规则同 gtx check
+
+启动时扫描一次目录，保存文档时重新扫描。
+
+用法:
+    gtx lsp [选项] [目录路径]
+
+参数:
+    [目录路径]    博客目录，默认为 ~/.data
+
+选项:
+    -h, --help    显示帮助信息";
+
+/// 解析后的命令行：全局的 --vault 与子命令
+pub struct Invocation {
+    /// 用户配置中 [vault.名称] 的名称
+    pub vault: Option<String>,
+    pub command: Command,
+}
+
+pub enum Command {
+    Index(IndexArgs),
+    New(NewArgs),
+    Open(OpenArgs),
+    Today(TodayArgs),
+    Tui(TuiArgs),
+    Search(SearchArgs),
+    Query(QueryArgs),
+    Recent(RecentArgs),
+    Random(RandomArgs),
+    Review(ReviewArgs),
+    List(ListArgs),
+    Stats(StatsArgs),
+    Todos(TodosArgs),
+    Check(CheckArgs),
+    Attachments(AttachmentsArgs),
+    Doctor(DoctorArgs),
+    Clean(CleanArgs),
+    Graph(GraphArgs),
+    Export(ExportArgs),
+    Import(ImportArgs),
+    Retag(RetagArgs),
+    TagEdit(TagEditArgs),
+    Hook(HookArgs),
+    Rename(RenameArgs),
+    Merge(MergeArgs),
+    MergeVaults(MergeVaultsArgs),
+    Archive(ArchiveArgs),
+    Watch(WatchArgs),
+    Serve(ServeArgs),
+    Rpc(RpcArgs),
+    Lsp(LspArgs),
+    // 打印文本后正常退出（帮助、版本号）
+    Print(String),
+}
+
+pub struct IndexArgs {
+    pub dir: Option<String>,
+    pub output: Option<String>,
+    pub columns: Option<ColumnsArg>,
+    pub exclude: Vec<String>,
+    pub backend: Option<BackendArg>,
+    pub link_style: Option<LinkStyleArg>,
+    pub sort: Option<TagSortArg>,
+    pub mermaid: Option<usize>,
+    pub hubs: Option<usize>,
+    pub excerpt: Option<usize>,
+    pub org: bool,
+    pub include_drafts: bool,
+    pub no_follow_symlinks: bool,
+    pub tag_dir: Option<String>,
+    pub dates_from: Option<DateSourceArg>,
+    pub trash_dir: Option<String>,
+    pub purge: bool,
+    pub full: bool,
+    pub dry_run: bool,
+    pub changed_only: bool,
+    pub with_search: bool,
+    pub quiet: bool,
+    pub verbose: bool,
+}
+
+pub struct NewArgs {
+    pub title: String,
+    pub dir: Option<String>,
+    pub name: Option<String>,
+    pub tags: Vec<String>,
+    /// 生成时间戳 ID
+    pub zettel: bool,
+}
+
+pub struct OpenArgs {
+    pub query: String,
+    pub dir: Option<String>,
+    pub print: bool,
+}
+
+pub struct TodayArgs {
+    pub dir: Option<String>,
+    pub print: bool,
+}
+
+pub struct TuiArgs {
+    pub dir: Option<String>,
+}
+
+#[derive(Clone, Copy)]
+pub enum ListFormat {
+    /// 文件名与标题
+    Plain,
+    /// 路径、标题与标签，以制表符分隔
+    Fzf,
+}
+
+pub struct ListArgs {
+    pub dir: Option<String>,
+    pub format: ListFormat,
+    pub null: bool,
+    pub filter: Option<String>,
+}
+
+pub struct RecentArgs {
+    pub dir: Option<String>,
+    pub days: usize,
+}
+
+pub struct RandomArgs {
+    pub dir: Option<String>,
+    pub tag: Option<String>,
+    pub print: bool,
+}
+
+pub struct ReviewArgs {
+    pub dir: Option<String>,
+    pub list: bool,
+    pub edit: bool,
+}
+
+pub struct SearchArgs {
+    pub query: String,
+    pub dir: Option<String>,
+    pub ignore_case: bool,
+    pub regex: bool,
+    pub context: usize,
+    pub filter: Option<String>,
+    pub limit: usize,
+    pub scan: bool,
+}
+
+pub struct QueryArgs {
+    pub query: String,
+    pub dir: Option<String>,
+    /// 保存的查询页面名称
+    pub save: Option<String>,
+    pub output: Option<String>,
+}
+
+pub struct StatsArgs {
+    pub dir: Option<String>,
+    pub output: Option<String>,
+    pub top: usize,
+    pub write: bool,
+    pub graph: bool,
+}
+
+pub struct TodosArgs {
+    pub dir: Option<String>,
+    pub output: Option<String>,
+    pub write: bool,
+    pub all: bool,
+    pub tag: Option<String>,
+}
+
+pub struct CheckArgs {
+    pub dir: Option<String>,
+    pub output: Option<String>,
+    pub write: bool,
+}
+
+pub struct AttachmentsArgs {
+    pub dir: Option<String>,
+    pub output: Option<String>,
+    pub write: bool,
+    /// 附件目录，未指定时使用配置或默认值
+    pub attachments: Option<String>,
+    pub no_follow_symlinks: bool,
+}
+
+pub struct DoctorArgs {
+    pub dir: Option<String>,
+    pub fix: bool,
+}
+
+pub struct CleanArgs {
+    pub dir: Option<String>,
+    pub trash_dir: Option<String>,
+    pub purge: bool,
+    pub dry_run: bool,
+}
+
+#[derive(Clone, Copy)]
+pub enum GraphFormat {
+    Dot,
+}
+
+pub struct GraphArgs {
+    pub dir: Option<String>,
+    pub format: GraphFormat,
+    /// 导出标签共现图
+    pub tags: bool,
+    pub output: Option<String>,
+}
+
+#[derive(Clone, Copy)]
+pub enum ExportFormat {
+    Json,
+    /// 每篇笔记一行的 CSV
+    Csv,
+    /// 标签层级的 OPML 大纲
+    Opml,
+    /// 静态站点，输出到目录
+    Html,
+    /// Hugo 的 content/ 目录
+    Hugo,
+    /// Zola 的 content/ 目录
+    Zola,
+    /// Jekyll 的 _posts/ 目录
+    Jekyll,
+    /// Anki 可以导入的卡片文本
+    Anki,
+    /// EPUB 电子书，输出到文件
+    Epub,
+}
+
+pub struct ExportArgs {
+    pub dir: Option<String>,
+    pub format: ExportFormat,
+    pub output: Option<String>,
+    /// html 站点的绝对地址，用于 sitemap.xml，没有时不写入 sitemap.xml
+    pub base_url: Option<String>,
+    /// html 站点同时写入 robots.txt
+    pub robots: bool,
+    /// epub 只收入带有该标签的笔记
+    pub tag: Option<String>,
+    /// epub 的书名
+    pub title: Option<String>,
+    /// 同时导出草稿
+    pub include_drafts: bool,
+}
+
+#[derive(Clone, Copy)]
+pub enum ImportSource {
+    /// Joplin 的 JEX 文件或 RAW 目录
+    Joplin,
+    /// Evernote 的 ENEX 文件
+    Enex,
+    /// 解压后的 Notion markdown 导出目录
+    Notion,
+}
+
+pub struct ImportArgs {
+    pub dir: Option<String>,
+    pub source: ImportSource,
+    pub path: String,
+}
+
+/// hook install 的参数
+pub struct HookArgs {
+    pub dir: Option<String>,
+    pub post_commit: bool,
+    pub force: bool,
+}
+
+/// tag rename 与 tag merge 的参数，rename 时 olds 只有一项
+pub struct RetagArgs {
+    pub dir: Option<String>,
+    pub olds: Vec<String>,
+    pub new: String,
+    pub dry_run: bool,
+}
+
+/// tag add 与 tag remove 的参数
+pub struct TagEditArgs {
+    pub dir: Option<String>,
+    pub tags: Vec<String>,
+    pub query: String,
+    /// 为 true 时删去标签，否则加上标签
+    pub remove: bool,
+    pub dry_run: bool,
+}
+
+pub struct RenameArgs {
+    pub dir: Option<String>,
+    pub old: String,
+    pub new: String,
+    pub title: Option<String>,
+    pub dry_run: bool,
+}
+
+pub struct MergeArgs {
+    pub dir: Option<String>,
+    /// 保留的笔记
+    pub keep: String,
+    /// 并入后删除的笔记
+    pub absorb: String,
+    pub dry_run: bool,
+}
+
+pub struct MergeVaultsArgs {
+    pub dir: Option<String>,
+    /// 来源博客目录
+    pub source: String,
+    pub rename: bool,
+    /// 附件目录，未指定时使用配置或默认值
+    pub attachments: Option<String>,
+    pub dry_run: bool,
+}
+
+pub struct ArchiveArgs {
+    pub dir: Option<String>,
+    pub output: Option<String>,
+    pub before: Date,
+    pub dry_run: bool,
+}
+
+pub struct WatchArgs {
+    pub dir: Option<String>,
+    pub output: Option<String>,
+    pub columns: Option<ColumnsArg>,
+    pub exclude: Vec<String>,
+    pub backend: Option<BackendArg>,
+    pub link_style: Option<LinkStyleArg>,
+    pub sort: Option<TagSortArg>,
+    pub mermaid: Option<usize>,
+    pub hubs: Option<usize>,
+    pub excerpt: Option<usize>,
+    pub org: bool,
+    pub include_drafts: bool,
+    pub no_follow_symlinks: bool,
+    pub dates_from: Option<DateSourceArg>,
+    pub trash_dir: Option<String>,
+    pub purge: bool,
+    pub interval: u64,
+    pub debounce: u64,
+}
+
+pub struct RpcArgs {
+    pub dir: Option<String>,
+}
+
+pub struct LspArgs {
+    pub dir: Option<String>,
+}
+
+pub struct ServeArgs {
+    pub dir: Option<String>,
+    pub port: u16,
+    pub watch: bool,
+    pub api: bool,
+}
+
+// --backend 的取值
+#[derive(Clone, Copy)]
+pub enum BackendArg {
+    Memory,
+    Sqlite,
+}
+
+// --link-style 的取值
+#[derive(Clone, Copy)]
+pub enum LinkStyleArg {
+    Wiki,
+    Markdown,
+    Obsidian,
+    Org,
+}
+
+// --dates-from 的取值
+#[derive(Clone, Copy)]
+pub enum DateSourceArg {
+    Frontmatter,
+    Git,
+}
+
+// --sort 的取值
+#[derive(Clone, Copy)]
+pub enum TagSortArg {
+    Created,
+    CreatedDesc,
+    Title,
+    Modified,
+}
+
+// --columns 的取值
+#[derive(Clone, Copy)]
+pub enum ColumnsArg {
+    Fixed(usize),
+    Auto,
+}
+
+#[derive(Debug)]
+pub struct CliError(String);
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for CliError {}
+
+enum Arg {
+    // 选项名与 `--name=value` 形式中内联的值
+    Flag(String, Option<String>),
+    Positional(String),
+}
+
+struct ArgStream {
+    rest: VecDeque<String>,
+    // 遇到 `--` 后其余参数一律视为位置参数
+    only_positional: bool,
+}
+
+impl ArgStream {
+    fn new(args: &[String]) -> Self {
+        Self {
+            rest: args.iter().cloned().collect(),
+            only_positional: false,
+        }
+    }
+
+    fn next(&mut self) -> Option<Arg> {
+        let raw = self.rest.pop_front()?;
+        if self.only_positional {
+            return Some(Arg::Positional(raw));
+        }
+        if raw == "--" {
+            self.only_positional = true;
+            return self.next();
+        }
+        if let Some(long) = raw.strip_prefix("--") {
+            return Some(match long.split_once('=') {
+                Some((name, value)) => Arg::Flag(format!("--{}", name), Some(value.to_string())),
+                None => Arg::Flag(raw, None),
+            });
+        }
+        if raw.starts_with('-') && raw.len() > 1 {
+            return Some(Arg::Flag(raw, None));
+        }
+        Some(Arg::Positional(raw))
+    }
+
+    // 取出选项的值：优先使用内联值，否则取下一个参数
+    fn value(&mut self, flag: &str, inline: Option<String>) -> Result<String, CliError> {
+        inline
+            .or_else(|| self.rest.pop_front())
+            .ok_or_else(|| CliError(i18n::trf("选项 {} 需要一个值", &[&flag])))
+    }
+}
+
+fn parse_number(flag: &str, value: &str) -> Result<usize, CliError> {
+    value.parse().map_err(|_| {
+        CliError(i18n::trf(
+            "选项 {} 需要一个非负整数，而不是 '{}'",
+            &[&flag, &value],
+        ))
+    })
+}
+
+fn parse_columns(flag: &str, value: &str) -> Result<ColumnsArg, CliError> {
+    if value == "auto" {
+        return Ok(ColumnsArg::Auto);
+    }
+    match parse_number(flag, value)? {
+        0 => Err(CliError(i18n::trf("选项 {} 的列数必须大于 0", &[&flag]))),
+        n => Ok(ColumnsArg::Fixed(n)),
+    }
+}
+
+fn parse_backend(flag: &str, value: &str) -> Result<BackendArg, CliError> {
+    match value {
+        "memory" => Ok(BackendArg::Memory),
+        "sqlite" => Ok(BackendArg::Sqlite),
+        _ => Err(CliError(i18n::trf(
+            "选项 {} 应为 memory 或 sqlite，而不是 '{}'",
+            &[&flag, &value],
+        ))),
+    }
+}
+
+fn parse_link_style(flag: &str, value: &str) -> Result<LinkStyleArg, CliError> {
+    match value {
+        "wiki" => Ok(LinkStyleArg::Wiki),
+        "markdown" => Ok(LinkStyleArg::Markdown),
+        "obsidian" => Ok(LinkStyleArg::Obsidian),
+        "org" => Ok(LinkStyleArg::Org),
+        _ => Err(CliError(i18n::trf(
+            "选项 {} 应为 wiki、markdown、obsidian 或 org，而不是 '{}'",
+            &[&flag, &value],
+        ))),
+    }
+}
+
+fn parse_date_source(flag: &str, value: &str) -> Result<DateSourceArg, CliError> {
+    match value {
+        "frontmatter" => Ok(DateSourceArg::Frontmatter),
+        "git" => Ok(DateSourceArg::Git),
+        _ => Err(CliError(i18n::trf(
+            "选项 {} 应为 frontmatter 或 git，而不是 '{}'",
+            &[&flag, &value],
+        ))),
+    }
+}
+
+fn parse_date(flag: &str, value: &str) -> Result<Date, CliError> {
+    Date::parse(value).map(|(date, _)| date).map_err(|_| {
+        CliError(i18n::trf(
+            "选项 {} 需要一个日期，如 2022-01-01，而不是 '{}'",
+            &[&flag, &value],
+        ))
+    })
+}
+
+fn parse_tag_sort(flag: &str, value: &str) -> Result<TagSortArg, CliError> {
+    match value {
+        "created" => Ok(TagSortArg::Created),
+        "created-desc" => Ok(TagSortArg::CreatedDesc),
+        "title" => Ok(TagSortArg::Title),
+        "modified" => Ok(TagSortArg::Modified),
+        _ => Err(CliError(i18n::trf(
+            "选项 {} 应为 created、created-desc、title 或 modified，而不是 '{}'",
+            &[&flag, &value],
+        ))),
+    }
+}
+
+fn unknown_flag(flag: &str) -> CliError {
+    CliError(i18n::trf("未知选项 '{}'", &[&flag]))
+}
+
+fn unexpected_arg(arg: &str) -> CliError {
+    CliError(i18n::trf("多余的参数 '{}'", &[&arg]))
+}
+
+pub fn parse(args: &[String]) -> Result<Invocation, CliError> {
+    let (vault, args) = take_vault(args)?;
+    Ok(Invocation {
+        vault,
+        command: parse_command(&args)?,
+    })
+}
+
+// 取出任意位置的 --vault <名称>，`--` 之后的参数原样保留
+fn take_vault(args: &[String]) -> Result<(Option<String>, Vec<String>), CliError> {
+    let mut vault = None;
+    let mut rest = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            rest.push(arg.clone());
+            rest.extend(args.cloned());
+            break;
+        }
+        if arg == "--vault" {
+            let name = args
+                .next()
+                .ok_or_else(|| CliError(i18n::trf("选项 {} 需要一个值", &[&arg])))?;
+            vault = Some(name.clone());
+        } else if let Some(name) = arg.strip_prefix("--vault=") {
+            vault = Some(name.to_string());
+        } else {
+            rest.push(arg.clone());
+        }
+    }
+    Ok((vault, rest))
+}
+
+fn parse_command(args: &[String]) -> Result<Command, CliError> {
+    let Some(first) = args.first() else {
+        return parse_index(args);
+    };
+    let rest = &args[1..];
+
+    match first.as_str() {
+        "-h" | "--help" => Ok(Command::Print(MAIN_HELP.to_string())),
+        "-V" | "--version" => Ok(Command::Print(format!("gtx {}", env!("CARGO_PKG_VERSION")))),
+        "help" => match rest.first().map(String::as_str) {
+            None => Ok(Command::Print(MAIN_HELP.to_string())),
+            Some(name) => subcommand_help(name)
+                .map(|help| Command::Print(help.to_string()))
+                .ok_or_else(|| CliError(i18n::trf("未知子命令 '{}'", &[&name]))),
+        },
+        "index" => parse_index(rest),
+        "new" => parse_new(rest),
+        "open" => parse_open(rest),
+        "today" => parse_today(rest),
+        "tui" => parse_tui(rest),
+        "search" => parse_search(rest),
+        "query" => parse_query(rest),
+        "recent" => parse_recent(rest),
+        "random" => parse_random(rest),
+        "review" => parse_review(rest),
+        "list" => parse_list(rest),
+        "stats" => parse_stats(rest),
+        "todos" => parse_todos(rest),
+        "check" => parse_check(rest),
+        "attachments" => parse_attachments(rest),
+        "doctor" => parse_doctor(rest),
+        "clean" => parse_clean(rest),
+        "graph" => parse_graph(rest),
+        "export" => parse_export(rest),
+        "import" => parse_import(rest),
+        "tag" => parse_tag(rest),
+        "hook" => parse_hook(rest),
+        "rename" => parse_rename(rest),
+        "merge" => parse_merge(rest),
+        "merge-vaults" => parse_merge_vaults(rest),
+        "archive" => parse_archive(rest),
+        "watch" => parse_watch(rest),
+        "serve" => parse_serve(rest),
+        "rpc" => parse_rpc(rest),
+        "lsp" => parse_lsp(rest),
+        // 旧用法: gtx [选项] <目录路径>
+        _ => parse_index(args),
+    }
+}
+
+fn subcommand_help(name: &str) -> Option<&'static str> {
+    match name {
+        "index" => Some(INDEX_HELP),
+        "new" => Some(NEW_HELP),
+        "open" => Some(OPEN_HELP),
+        "today" => Some(TODAY_HELP),
+        "tui" => Some(TUI_HELP),
+        "search" => Some(SEARCH_HELP),
+        "query" => Some(QUERY_HELP),
+        "recent" => Some(RECENT_HELP),
+        "random" => Some(RANDOM_HELP),
+        "review" => Some(REVIEW_HELP),
+        "list" => Some(LIST_HELP),
+        "stats" => Some(STATS_HELP),
+        "todos" => Some(TODOS_HELP),
+        "check" => Some(CHECK_HELP),
+        "attachments" => Some(ATTACHMENTS_HELP),
+        "doctor" => Some(DOCTOR_HELP),
+        "clean" => Some(CLEAN_HELP),
+        "graph" => Some(GRAPH_HELP),
+        "export" => Some(EXPORT_HELP),
+        "import" => Some(IMPORT_HELP),
+        "tag" => Some(TAG_HELP),
+        "hook" => Some(HOOK_HELP),
+        "rename" => Some(RENAME_HELP),
+        "merge" => Some(MERGE_HELP),
+        "merge-vaults" => Some(MERGE_VAULTS_HELP),
+        "archive" => Some(ARCHIVE_HELP),
+        "watch" => Some(WATCH_HELP),
+        "serve" => Some(SERVE_HELP),
+        "rpc" => Some(RPC_HELP),
+        "lsp" => Some(LSP_HELP),
+        _ => None,
+    }
+}
+
+fn parse_index(args: &[String]) -> Result<Command, CliError> {
+    let mut stream = ArgStream::new(args);
+    let mut dir = None;
+    let mut output = None;
+    let mut columns = None;
+    let mut exclude = Vec::new();
+    let mut backend = None;
+    let mut link_style = None;
+    let mut sort = None;
+    let mut mermaid = None;
+    let mut hubs = None;
+    let mut excerpt = None;
+    let mut org = false;
+    let mut include_drafts = false;
+    let mut no_follow_symlinks = false;
+    let mut tag_dir = None;
+    let mut dates_from = None;
+    let mut trash_dir = None;
+    let mut purge = false;
+    let mut full = false;
+    let mut dry_run = false;
+    let mut changed_only = false;
+    let mut with_search = false;
+    let mut quiet = false;
+    let mut verbose = false;
+
+    while let Some(arg) = stream.next() {
+        match arg {
+            Arg::Flag(flag, inline) => match flag.as_str() {
+                "-h" | "--help" => return Ok(Command::Print(INDEX_HELP.to_string())),
+                "-o" | "--output" => output = Some(stream.value(&flag, inline)?),
+                "--columns" => columns = Some(parse_columns(&flag, &stream.value(&flag, inline)?)?),
+                "--exclude" => exclude.push(stream.value(&flag, inline)?),
+                "--backend" => backend = Some(parse_backend(&flag, &stream.value(&flag, inline)?)?),
+                "--link-style" | "--flavor" => {
+                    link_style = Some(parse_link_style(&flag, &stream.value(&flag, inline)?)?)
+                }
+                "--sort" => sort = Some(parse_tag_sort(&flag, &stream.value(&flag, inline)?)?),
+                "--mermaid" => mermaid = Some(parse_number(&flag, &stream.value(&flag, inline)?)?),
+                "--hubs" => hubs = Some(parse_number(&flag, &stream.value(&flag, inline)?)?),
+                "--excerpt" => excerpt = Some(parse_number(&flag, &stream.value(&flag, inline)?)?),
+                "--org" => org = true,
+                "--include-drafts" => include_drafts = true,
+                "--no-follow-symlinks" => no_follow_symlinks = true,
+                "--tag-dir" => tag_dir = Some(stream.value(&flag, inline)?),
+                "--dates-from" => {
+                    dates_from = Some(parse_date_source(&flag, &stream.value(&flag, inline)?)?)
+                }
+                "--trash-dir" => trash_dir = Some(stream.value(&flag, inline)?),
+                "--purge" => purge = true,
+                "--full" => full = true,
+                "-n" | "--dry-run" => dry_run = true,
+                "--changed-only" => changed_only = true,
+                "--with-search" => with_search = true,
+                "-q" | "--quiet" => quiet = true,
+                "-v" | "--verbose" => verbose = true,
+                _ => return Err(unknown_flag(&flag)),
+            },
+            Arg::Positional(value) if dir.is_none() => dir = Some(value),
+            Arg::Positional(value) => return Err(unexpected_arg(&value)),
+        }
+    }
+
+    if quiet && verbose {
+        return Err(CliError(
+            i18n::tr("--quiet 与 --verbose 不能同时使用").to_string(),
+        ));
+    }
+    if purge && trash_dir.is_some() {
+        return Err(CliError(
+            i18n::tr("--purge 与 --trash-dir 不能同时使用").to_string(),
+        ));
+    }
+    if full && changed_only {
+        return Err(CliError(
+            i18n::tr("--full 与 --changed-only 不能同时使用").to_string(),
+        ));
+    }
+
+    Ok(Command::Index(IndexArgs {
+        dir,
+        output,
+        columns,
+        exclude,
+        backend,
+        link_style,
+        sort,
+        mermaid,
+        hubs,
+        excerpt,
+        org,
+        include_drafts,
+        no_follow_symlinks,
+        tag_dir,
+        dates_from,
+        trash_dir,
+        purge,
+        full,
+        dry_run,
+        changed_only,
+        with_search,
+        quiet,
+        verbose,
+    }))
+}
+
+fn parse_new(args: &[String]) -> Result<Command, CliError> {
+    let mut stream = ArgStream::new(args);
+    let mut title = None;
+    let mut dir = None;
+    let mut name = None;
+    let mut tags = Vec::new();
+    let mut zettel = false;
+
+    while let Some(arg) = stream.next() {
+        match arg {
+            Arg::Flag(flag, inline) => match flag.as_str() {
+                "-h" | "--help" => return Ok(Command::Print(NEW_HELP.to_string())),
+                "-z" | "--zettel" => zettel = true,
+                "-d" | "--dir" => dir = Some(stream.value(&flag, inline)?),
+                "-n" | "--name" => name = Some(stream.value(&flag, inline)?),
+                "-t" | "--tags" => tags.extend(
+                    stream
+                        .value(&flag, inline)?
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|s| !s.is_empty())
+                        .map(str::to_string),
+                ),
+                _ => return Err(unknown_flag(&flag)),
+            },
+            Arg::Positional(value) if title.is_none() => title = Some(value),
+            Arg::Positional(value) => return Err(unexpected_arg(&value)),
+        }
+    }
+
+    let title = title.ok_or_else(|| CliError(i18n::tr("缺少参数 <标题>").to_string()))?;
+    Ok(Command::New(NewArgs {
+        title,
+        dir,
+        name,
+        tags,
+        zettel,
+    }))
+}
+
+fn parse_open(args: &[String]) -> Result<Command, CliError> {
+    let mut stream = ArgStream::new(args);
+    let mut query = None;
+    let mut dir = None;
+    let mut print = false;
+
+    while let Some(arg) = stream.next() {
+        match arg {
+            Arg::Flag(flag, inline) => match flag.as_str() {
+                "-h" | "--help" => return Ok(Command::Print(OPEN_HELP.to_string())),
+                "-d" | "--dir" => dir = Some(stream.value(&flag, inline)?),
+                "-p" | "--print" => print = true,
+                _ => return Err(unknown_flag(&flag)),
+            },
+            Arg::Positional(value) if query.is_none() => query = Some(value),
+            Arg::Positional(value) => return Err(unexpected_arg(&value)),
+        }
+    }
+
+    let query = query.ok_or_else(|| CliError(i18n::tr("缺少参数 <查询>").to_string()))?;
+    Ok(Command::Open(OpenArgs { query, dir, print }))
+}
+
+fn parse_today(args: &[String]) -> Result<Command, CliError> {
+    let mut stream = ArgStream::new(args);
+    let mut dir = None;
+    let mut print = false;
+
+    while let Some(arg) = stream.next() {
+        match arg {
+            Arg::Flag(flag, _) => match flag.as_str() {
+                "-h" | "--help" => return Ok(Command::Print(TODAY_HELP.to_string())),
+                "-p" | "--print" => print = true,
+                _ => return Err(unknown_flag(&flag)),
+            },
+            Arg::Positional(value) if dir.is_none() => dir = Some(value),
+            Arg::Positional(value) => return Err(unexpected_arg(&value)),
+        }
+    }
+
+    Ok(Command::Today(TodayArgs { dir, print }))
+}
+
+fn parse_tui(args: &[String]) -> Result<Command, CliError> {
+    let mut stream = ArgStream::new(args);
+    let mut dir = None;
+
+    while let Some(arg) = stream.next() {
+        match arg {
+            Arg::Flag(flag, inline) => match flag.as_str() {
+                "-h" | "--help" => return Ok(Command::Print(TUI_HELP.to_string())),
+                "-d" | "--dir" => dir = Some(stream.value(&flag, inline)?),
+                _ => return Err(unknown_flag(&flag)),
+            },
+            Arg::Positional(value) if dir.is_none() => dir = Some(value),
+            Arg::Positional(value) => return Err(unexpected_arg(&value)),
+        }
+    }
+
+    Ok(Command::Tui(TuiArgs { dir }))
+}
+
+fn parse_search(args: &[String]) -> Result<Command, CliError> {
+    let mut stream = ArgStream::new(args);
+    let mut query = None;
+    let mut dir = None;
+    let mut ignore_case = false;
+    let mut regex = false;
+    let mut context = 0;
+    let mut filter = None;
+    let mut limit = 20;
+    let mut scan = false;
+
+    while let Some(arg) = stream.next() {
+        match arg {
+            Arg::Flag(flag, inline) => match flag.as_str() {
+                "-h" | "--help" => return Ok(Command::Print(SEARCH_HELP.to_string())),
+                "-d" | "--dir" => dir = Some(stream.value(&flag, inline)?),
+                "-i" | "--ignore-case" => ignore_case = true,
+                "-e" | "--regex" => regex = true,
+                "-C" | "--context" => context = parse_number(&flag, &stream.value(&flag, inline)?)?,
+                "-f" | "--filter" => filter = Some(stream.value(&flag, inline)?),
+                "-n" | "--limit" => limit = parse_number(&flag, &stream.value(&flag, inline)?)?,
+                "--scan" => scan = true,
+                _ => return Err(unknown_flag(&flag)),
+            },
+            Arg::Positional(value) if query.is_none() => query = Some(value),
+            Arg::Positional(value) => return Err(unexpected_arg(&value)),
+        }
+    }
+
+    let query = query.ok_or_else(|| CliError(i18n::tr("缺少参数 <查询>").to_string()))?;
+    Ok(Command::Search(SearchArgs {
+        query,
+        dir,
+        ignore_case,
+        regex,
+        context,
+        filter,
+        limit,
+        scan,
+    }))
+}
+
+fn parse_query(args: &[String]) -> Result<Command, CliError> {
+    let mut stream = ArgStream::new(args);
+    let mut query = None;
+    let mut dir = None;
+    let mut save = None;
+    let mut output = None;
+
+    while let Some(arg) = stream.next() {
+        match arg {
+            Arg::Flag(flag, inline) => match flag.as_str() {
+                "-h" | "--help" => return Ok(Command::Print(QUERY_HELP.to_string())),
+                "-d" | "--dir" => dir = Some(stream.value(&flag, inline)?),
+                "-s" | "--save" => save = Some(stream.value(&flag, inline)?),
+                "-o" | "--output" => output = Some(stream.value(&flag, inline)?),
+                _ => return Err(unknown_flag(&flag)),
+            },
+            Arg::Positional(value) if query.is_none() => query = Some(value),
+            Arg::Positional(value) => return Err(unexpected_arg(&value)),
+        }
+    }
+
+    let query = query.ok_or_else(|| CliError(i18n::tr("缺少参数 <查询>").to_string()))?;
+    if let Some(name) = &save
+        && (name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.'))
+    {
+        return Err(CliError(i18n::trf("无效的页面名称 '{}'", &[&name])));
+    }
+    Ok(Command::Query(QueryArgs {
+        query,
+        dir,
+        save,
+        output,
+    }))
+}
+
+fn parse_recent(args: &[String]) -> Result<Command, CliError> {
+    let mut stream = ArgStream::new(args);
+    let mut dir = None;
+    let mut days = 7;
+
+    while let Some(arg) = stream.next() {
+        match arg {
+            Arg::Flag(flag, inline) => match flag.as_str() {
+                "-h" | "--help" => return Ok(Command::Print(RECENT_HELP.to_string())),
+                "--days" => days = parse_number(&flag, &stream.value(&flag, inline)?)?,
+                _ => return Err(unknown_flag(&flag)),
+            },
+            Arg::Positional(value) if dir.is_none() => dir = Some(value),
+            Arg::Positional(value) => return Err(unexpected_arg(&value)),
+        }
+    }
+
+    Ok(Command::Recent(RecentArgs { dir, days }))
+}
+
+fn parse_random(args: &[String]) -> Result<Command, CliError> {
+    let mut stream = ArgStream::new(args);
+    let mut dir = None;
+    let mut tag = None;
+    let mut print = false;
+
+    while let Some(arg) = stream.next() {
+        match arg {
+            Arg::Flag(flag, inline) => match flag.as_str() {
+                "-h" | "--help" => return Ok(Command::Print(RANDOM_HELP.to_string())),
+                "-t" | "--tag" => tag = Some(stream.value(&flag, inline)?),
+                "-p" | "--print" => print = true,
+                _ => return Err(unknown_flag(&flag)),
+            },
+            Arg::Positional(value) if dir.is_none() => dir = Some(value),
+            Arg::Positional(value) => return Err(unexpected_arg(&value)),
+        }
+    }
+
+    Ok(Command::Random(RandomArgs { dir, tag, print }))
+}
+
+fn parse_review(args: &[String]) -> Result<Command, CliError> {
+    let mut stream = ArgStream::new(args);
+    let mut dir = None;
+    let mut list = false;
+    let mut edit = false;
+
+    while let Some(arg) = stream.next() {
+        match arg {
+            Arg::Flag(flag, _) => match flag.as_str() {
+                "-h" | "--help" => return Ok(Command::Print(REVIEW_HELP.to_string())),
+                "-l" | "--list" => list = true,
+                "-e" | "--edit" => edit = true,
+                _ => return Err(unknown_flag(&flag)),
+            },
+            Arg::Positional(value) if dir.is_none() => dir = Some(value),
+            Arg::Positional(value) => return Err(unexpected_arg(&value)),
+        }
+    }
+
+    Ok(Command::Review(ReviewArgs { dir, list, edit }))
+}
+
+fn parse_list(args: &[String]) -> Result<Command, CliError> {
+    let mut stream = ArgStream::new(args);
+    let mut dir = None;
+    let mut format = ListFormat::Plain;
+    let mut null = false;
+    let mut filter = None;
+
+    while let Some(arg) = stream.next() {
+        match arg {
+            Arg::Flag(flag, inline) => match flag.as_str() {
+                "-h" | "--help" => return Ok(Command::Print(LIST_HELP.to_string())),
+                "--format" => {
+                    format = match stream.value(&flag, inline)?.as_str() {
+                        "plain" => ListFormat::Plain,
+                        "fzf" => ListFormat::Fzf,
+                        other => {
+                            return Err(CliError(i18n::trf(
+                                "不支持的格式 '{}'，可选: plain, fzf",
+                                &[&other],
+                            )));
+                        }
+                    }
+                }
+                "-0" | "--null" => null = true,
+                "-f" | "--filter" => filter = Some(stream.value(&flag, inline)?),
+                _ => return Err(unknown_flag(&flag)),
+            },
+            Arg::Positional(value) if dir.is_none() => dir = Some(value),
+            Arg::Positional(value) => return Err(unexpected_arg(&value)),
+        }
+    }
+
+    Ok(Command::List(ListArgs {
+        dir,
+        format,
+        null,
+        filter,
+    }))
+}
+
+fn parse_stats(args: &[String]) -> Result<Command, CliError> {
+    let mut stream = ArgStream::new(args);
+    let mut dir = None;
+    let mut output = None;
+    let mut top = 5;
+    let mut write = false;
+    let mut graph = false;
+
+    while let Some(arg) = stream.next() {
+        match arg {
+            Arg::Flag(flag, inline) => match flag.as_str() {
+                "-h" | "--help" => return Ok(Command::Print(STATS_HELP.to_string())),
+                "-g" | "--graph" => graph = true,
+                "-n" | "--top" => top = parse_number(&flag, &stream.value(&flag, inline)?)?,
+                "-w" | "--write" => write = true,
+                "-o" | "--output" => output = Some(stream.value(&flag, inline)?),
+                _ => return Err(unknown_flag(&flag)),
+            },
+            Arg::Positional(value) if dir.is_none() => dir = Some(value),
+            Arg::Positional(value) => return Err(unexpected_arg(&value)),
+        }
+    }
+
+    Ok(Command::Stats(StatsArgs {
+        dir,
+        output,
+        top,
+        write,
+        graph,
+    }))
+}
+
+fn parse_todos(args: &[String]) -> Result<Command, CliError> {
+    let mut stream = ArgStream::new(args);
+    let mut dir = None;
+    let mut output = None;
+    let mut write = false;
+    let mut all = false;
+    let mut tag = None;
+
+    while let Some(arg) = stream.next() {
+        match arg {
+            Arg::Flag(flag, inline) => match flag.as_str() {
+                "-h" | "--help" => return Ok(Command::Print(TODOS_HELP.to_string())),
+                "-a" | "--all" => all = true,
+                "-t" | "--tag" => tag = Some(stream.value(&flag, inline)?),
+                "-w" | "--write" => write = true,
+                "-o" | "--output" => output = Some(stream.value(&flag, inline)?),
+                _ => return Err(unknown_flag(&flag)),
+            },
+            Arg::Positional(value) if dir.is_none() => dir = Some(value),
+            Arg::Positional(value) => return Err(unexpected_arg(&value)),
+        }
+    }
+
+    Ok(Command::Todos(TodosArgs {
+        dir,
+        output,
+        write,
+        all,
+        tag,
+    }))
+}
+
+fn parse_check(args: &[String]) -> Result<Command, CliError> {
+    let mut stream = ArgStream::new(args);
+    let mut dir = None;
+    let mut output = None;
+    let mut write = false;
+
+    while let Some(arg) = stream.next() {
+        match arg {
+            Arg::Flag(flag, inline) => match flag.as_str() {
+                "-h" | "--help" => return Ok(Command::Print(CHECK_HELP.to_string())),
+                "-w" | "--write" => write = true,
+                "-o" | "--output" => output = Some(stream.value(&flag, inline)?),
+                _ => return Err(unknown_flag(&flag)),
+            },
+            Arg::Positional(value) if dir.is_none() => dir = Some(value),
+            Arg::Positional(value) => return Err(unexpected_arg(&value)),
+        }
+    }
+
+    Ok(Command::Check(CheckArgs { dir, output, write }))
+}
+
+fn parse_attachments(args: &[String]) -> Result<Command, CliError> {
+    let mut stream = ArgStream::new(args);
+    let mut dir = None;
+    let mut output = None;
+    let mut write = false;
+    let mut attachments = None;
+    let mut no_follow_symlinks = false;
+
+    while let Some(arg) = stream.next() {
+        match arg {
+            Arg::Flag(flag, inline) => match flag.as_str() {
+                "-h" | "--help" => return Ok(Command::Print(ATTACHMENTS_HELP.to_string())),
+                "-a" | "--attachments" => attachments = Some(stream.value(&flag, inline)?),
+                "-w" | "--write" => write = true,
+                "-o" | "--output" => output = Some(stream.value(&flag, inline)?),
+                "--no-follow-symlinks" => no_follow_symlinks = true,
+                _ => return Err(unknown_flag(&flag)),
+            },
+            Arg::Positional(value) if dir.is_none() => dir = Some(value),
+            Arg::Positional(value) => return Err(unexpected_arg(&value)),
+        }
+    }
+
+    Ok(Command::Attachments(AttachmentsArgs {
+        dir,
+        output,
+        write,
+        attachments,
+        no_follow_symlinks,
+    }))
+}
+
+fn parse_doctor(args: &[String]) -> Result<Command, CliError> {
+    let mut stream = ArgStream::new(args);
+    let mut dir = None;
+    let mut fix = false;
+
+    while let Some(arg) = stream.next() {
+        match arg {
+            Arg::Flag(flag, _) => match flag.as_str() {
+                "-h" | "--help" => return Ok(Command::Print(DOCTOR_HELP.to_string())),
+                "--fix" => fix = true,
+                _ => return Err(unknown_flag(&flag)),
+            },
+            Arg::Positional(value) if dir.is_none() => dir = Some(value),
+            Arg::Positional(value) => return Err(unexpected_arg(&value)),
+        }
+    }
+
+    Ok(Command::Doctor(DoctorArgs { dir, fix }))
+}
+
+fn parse_clean(args: &[String]) -> Result<Command, CliError> {
+    let mut stream = ArgStream::new(args);
+    let mut dir = None;
+    let mut trash_dir = None;
+    let mut purge = false;
+    let mut dry_run = false;
+
+    while let Some(arg) = stream.next() {
+        match arg {
+            Arg::Flag(flag, inline) => match flag.as_str() {
+                "-h" | "--help" => return Ok(Command::Print(CLEAN_HELP.to_string())),
+                "--trash-dir" => trash_dir = Some(stream.value(&flag, inline)?),
+                "--purge" => purge = true,
+                "-n" | "--dry-run" => dry_run = true,
+                _ => return Err(unknown_flag(&flag)),
+            },
+            Arg::Positional(value) if dir.is_none() => dir = Some(value),
+            Arg::Positional(value) => return Err(unexpected_arg(&value)),
+        }
+    }
+
+    if purge && trash_dir.is_some() {
+        return Err(CliError(
+            i18n::tr("--purge 与 --trash-dir 不能同时使用").to_string(),
+        ));
+    }
+    Ok(Command::Clean(CleanArgs {
+        dir,
+        trash_dir,
+        purge,
+        dry_run,
+    }))
+}
+
+fn parse_graph(args: &[String]) -> Result<Command, CliError> {
+    let mut stream = ArgStream::new(args);
+    let mut dir = None;
+    let mut format = GraphFormat::Dot;
+    let mut tags = false;
+    let mut output = None;
+
+    while let Some(arg) = stream.next() {
+        match arg {
+            Arg::Flag(flag, inline) => match flag.as_str() {
+                "-h" | "--help" => return Ok(Command::Print(GRAPH_HELP.to_string())),
+                "-f" | "--format" => {
+                    format = match stream.value(&flag, inline)?.as_str() {
+                        "dot" => GraphFormat::Dot,
+                        other => {
+                            return Err(CliError(i18n::trf(
+                                "不支持的格式 '{}'，可选: dot",
+                                &[&other],
+                            )));
+                        }
+                    }
+                }
+                "-t" | "--tags" => tags = true,
+                "-o" | "--output" => output = Some(stream.value(&flag, inline)?),
+                _ => return Err(unknown_flag(&flag)),
+            },
+            Arg::Positional(value) if dir.is_none() => dir = Some(value),
+            Arg::Positional(value) => return Err(unexpected_arg(&value)),
+        }
+    }
+
+    Ok(Command::Graph(GraphArgs {
+        dir,
+        format,
+        tags,
+        output,
+    }))
+}
+
+fn parse_export(args: &[String]) -> Result<Command, CliError> {
+    let mut stream = ArgStream::new(args);
+    let mut dir = None;
+    let mut format = ExportFormat::Json;
+    let mut output = None;
+    let mut base_url = None;
+    let mut robots = false;
+    let mut tag = None;
+    let mut title = None;
+    let mut include_drafts = false;
+
+    while let Some(arg) = stream.next() {
+        match arg {
+            Arg::Flag(flag, inline) => match flag.as_str() {
+                "-h" | "--help" => return Ok(Command::Print(EXPORT_HELP.to_string())),
+                "-f" | "--format" => {
+                    format = match stream.value(&flag, inline)?.as_str() {
+                        "json" => ExportFormat::Json,
+                        "csv" => ExportFormat::Csv,
+                        "opml" => ExportFormat::Opml,
+                        "html" => ExportFormat::Html,
+                        "hugo" => ExportFormat::Hugo,
+                        "zola" => ExportFormat::Zola,
+                        "jekyll" => ExportFormat::Jekyll,
+                        "anki" => ExportFormat::Anki,
+                        "epub" => ExportFormat::Epub,
+                        other => {
+                            return Err(CliError(i18n::trf(
+                                "不支持的格式 '{}'，可选: json, csv, opml, html, hugo, zola, jekyll, anki, epub",
+                                &[&other],
+                            )));
+                        }
+                    }
+                }
+                "-o" | "--output" => output = Some(stream.value(&flag, inline)?),
+                "--base-url" => base_url = Some(stream.value(&flag, inline)?),
+                "--robots" => robots = true,
+                "-t" | "--tag" => tag = Some(stream.value(&flag, inline)?),
+                "--title" => title = Some(stream.value(&flag, inline)?),
+                "--include-drafts" => include_drafts = true,
+                _ => return Err(unknown_flag(&flag)),
+            },
+            Arg::Positional(value) if dir.is_none() => dir = Some(value),
+            Arg::Positional(value) => return Err(unexpected_arg(&value)),
+        }
+    }
+
+    if !matches!(format, ExportFormat::Html) && (base_url.is_some() || robots) {
+        return Err(CliError(
+            i18n::tr("--base-url 与 --robots 只用于 html 格式").to_string(),
+        ));
+    }
+    if let Some(url) = &base_url
+        && !(url.starts_with("https://") || url.starts_with("http://"))
+    {
+        return Err(CliError(i18n::trf(
+            "--base-url 应为以 http:// 或 https:// 开头的绝对地址: {}",
+            &[url],
+        )));
+    }
+    if robots && base_url.is_none() {
+        return Err(CliError(
+            i18n::tr("--robots 需要用 --base-url 指定站点地址").to_string(),
+        ));
+    }
+    if matches!(format, ExportFormat::Epub) {
+        if output.is_none() {
+            return Err(CliError(
+                i18n::tr("epub 格式需要用 -o 指定输出文件").to_string(),
+            ));
+        }
+    } else if tag.is_some() || title.is_some() {
+        return Err(CliError(
+            i18n::tr("--tag 与 --title 只用于 epub 格式").to_string(),
+        ));
+    }
+    let site_format = match format {
+        ExportFormat::Html => Some("html"),
+        ExportFormat::Hugo => Some("hugo"),
+        ExportFormat::Zola => Some("zola"),
+        ExportFormat::Jekyll => Some("jekyll"),
+        ExportFormat::Json
+        | ExportFormat::Csv
+        | ExportFormat::Opml
+        | ExportFormat::Anki
+        | ExportFormat::Epub => None,
+    };
+    if let Some(name) = site_format
+        && output.is_none()
+    {
+        return Err(CliError(i18n::trf(
+            "{} 格式需要用 -o 指定站点目录",
+            &[&name],
+        )));
+    }
+
+    Ok(Command::Export(ExportArgs {
+        dir,
+        format,
+        output,
+        base_url,
+        robots,
+        tag,
+        title,
+        include_drafts,
+    }))
+}
+
+fn parse_import(args: &[String]) -> Result<Command, CliError> {
+    let mut stream = ArgStream::new(args);
+    let mut dir = None;
+    let mut positionals = Vec::new();
+
+    while let Some(arg) = stream.next() {
+        match arg {
+            Arg::Flag(flag, inline) => match flag.as_str() {
+                "-h" | "--help" => return Ok(Command::Print(IMPORT_HELP.to_string())),
+                "-d" | "--dir" => dir = Some(stream.value(&flag, inline)?),
+                _ => return Err(unknown_flag(&flag)),
+            },
+            Arg::Positional(value) if positionals.len() < 2 => positionals.push(value),
+            Arg::Positional(value) => return Err(unexpected_arg(&value)),
+        }
+    }
+
+    let mut positionals = positionals.into_iter();
+    let source = match positionals.next().as_deref() {
+        None => return Ok(Command::Print(IMPORT_HELP.to_string())),
+        Some("joplin") => ImportSource::Joplin,
+        Some("enex") => ImportSource::Enex,
+        Some("notion") => ImportSource::Notion,
+        Some(other) => {
+            return Err(CliError(i18n::trf(
+                "不支持的来源 '{}'，可选: joplin、enex、notion",
+                &[&other],
+            )));
+        }
+    };
+    let path = positionals
+        .next()
+        .ok_or_else(|| CliError(i18n::tr("缺少参数 <路径>").to_string()))?;
+
+    Ok(Command::Import(ImportArgs { dir, source, path }))
+}
+
+fn parse_hook(args: &[String]) -> Result<Command, CliError> {
+    match args.first().map(String::as_str) {
+        None | Some("-h" | "--help") => Ok(Command::Print(HOOK_HELP.to_string())),
+        Some("install") => parse_hook_install(&args[1..]),
+        Some(other) => Err(CliError(i18n::trf("未知的 hook 子命令 '{}'", &[&other]))),
+    }
+}
+
+fn parse_hook_install(args: &[String]) -> Result<Command, CliError> {
+    let mut stream = ArgStream::new(args);
+    let mut dir = None;
+    let mut post_commit = false;
+    let mut force = false;
+
+    while let Some(arg) = stream.next() {
+        match arg {
+            Arg::Flag(flag, _) => match flag.as_str() {
+                "-h" | "--help" => return Ok(Command::Print(HOOK_HELP.to_string())),
+                "--post-commit" => post_commit = true,
+                "-f" | "--force" => force = true,
+                _ => return Err(unknown_flag(&flag)),
+            },
+            Arg::Positional(value) if dir.is_none() => dir = Some(value),
+            Arg::Positional(value) => return Err(unexpected_arg(&value)),
+        }
+    }
+
+    Ok(Command::Hook(HookArgs {
+        dir,
+        post_commit,
+        force,
+    }))
+}
+
+fn parse_tag(args: &[String]) -> Result<Command, CliError> {
+    match args.first().map(String::as_str) {
+        None | Some("-h" | "--help") => Ok(Command::Print(TAG_HELP.to_string())),
+        Some("rename") => parse_tag_rename(&args[1..]),
+        Some("merge") => parse_tag_merge(&args[1..]),
+        Some("add") => parse_tag_edit(&args[1..], false),
+        Some("remove") => parse_tag_edit(&args[1..], true),
+        Some(other) => Err(CliError(i18n::trf("未知的 tag 子命令 '{}'", &[&other]))),
+    }
+}
+
+fn parse_tag_rename(args: &[String]) -> Result<Command, CliError> {
+    let mut stream = ArgStream::new(args);
+    let mut dir = None;
+    let mut names = Vec::new();
+    let mut dry_run = false;
+
+    while let Some(arg) = stream.next() {
+        match arg {
+            Arg::Flag(flag, inline) => match flag.as_str() {
+                "-h" | "--help" => return Ok(Command::Print(TAG_HELP.to_string())),
+                "-d" | "--dir" => dir = Some(stream.value(&flag, inline)?),
+                "-n" | "--dry-run" => dry_run = true,
+                _ => return Err(unknown_flag(&flag)),
+            },
+            Arg::Positional(value) if names.len() < 2 => names.push(value),
+            Arg::Positional(value) => return Err(unexpected_arg(&value)),
+        }
+    }
+
+    let mut names = names.into_iter();
+    let old = names
+        .next()
+        .ok_or_else(|| CliError(i18n::tr("缺少参数 <旧标签>").to_string()))?;
+    let new = names
+        .next()
+        .ok_or_else(|| CliError(i18n::tr("缺少参数 <新标签>").to_string()))?;
+    retag_args(dir, vec![old], new, dry_run)
+}
+
+fn parse_tag_merge(args: &[String]) -> Result<Command, CliError> {
+    let mut stream = ArgStream::new(args);
+    let mut dir = None;
+    let mut olds = Vec::new();
+    let mut into = None;
+    let mut dry_run = false;
+
+    while let Some(arg) = stream.next() {
+        match arg {
+            Arg::Flag(flag, inline) => match flag.as_str() {
+                "-h" | "--help" => return Ok(Command::Print(TAG_HELP.to_string())),
+                "-d" | "--dir" => dir = Some(stream.value(&flag, inline)?),
+                "-n" | "--dry-run" => dry_run = true,
+                "--into" => into = Some(stream.value(&flag, inline)?),
+                _ => return Err(unknown_flag(&flag)),
+            },
+            Arg::Positional(value) => olds.push(value),
+        }
+    }
+
+    if olds.is_empty() {
+        return Err(CliError(i18n::tr("缺少参数 <标签>").to_string()));
+    }
+    let new = into.ok_or_else(|| CliError(i18n::tr("缺少选项 --into <新标签>").to_string()))?;
+    retag_args(dir, olds, new, dry_run)
+}
+
+fn parse_tag_edit(args: &[String], remove: bool) -> Result<Command, CliError> {
+    let mut stream = ArgStream::new(args);
+    let mut dir = None;
+    let mut tags = Vec::new();
+    let mut query = None;
+    let mut dry_run = false;
+
+    while let Some(arg) = stream.next() {
+        match arg {
+            Arg::Flag(flag, inline) => match flag.as_str() {
+                "-h" | "--help" => return Ok(Command::Print(TAG_HELP.to_string())),
+                "-d" | "--dir" => dir = Some(stream.value(&flag, inline)?),
+                "-q" | "--query" => query = Some(stream.value(&flag, inline)?),
+                "-n" | "--dry-run" => dry_run = true,
+                _ => return Err(unknown_flag(&flag)),
+            },
+            Arg::Positional(value) => tags.push(value),
+        }
+    }
+
+    if tags.is_empty() {
+        return Err(CliError(i18n::tr("缺少参数 <标签>").to_string()));
+    }
+    check_tags(&tags)?;
+    tags.sort_unstable();
+    tags.dedup();
+    let query = query.ok_or_else(|| CliError(i18n::tr("缺少选项 --query <查询>").to_string()))?;
+    Ok(Command::TagEdit(TagEditArgs {
+        dir,
+        tags,
+        query,
+        remove,
+        dry_run,
+    }))
+}
+
+fn parse_rename(args: &[String]) -> Result<Command, CliError> {
+    let mut stream = ArgStream::new(args);
+    let mut dir = None;
+    let mut names = Vec::new();
+    let mut title = None;
+    let mut dry_run = false;
+
+    while let Some(arg) = stream.next() {
+        match arg {
+            Arg::Flag(flag, inline) => match flag.as_str() {
+                "-h" | "--help" => return Ok(Command::Print(RENAME_HELP.to_string())),
+                "-d" | "--dir" => dir = Some(stream.value(&flag, inline)?),
+                "-t" | "--title" => title = Some(stream.value(&flag, inline)?),
+                "-n" | "--dry-run" => dry_run = true,
+                _ => return Err(unknown_flag(&flag)),
+            },
+            Arg::Positional(value) if names.len() < 2 => names.push(value),
+            Arg::Positional(value) => return Err(unexpected_arg(&value)),
+        }
+    }
+
+    // 文件名可以带 .md 后缀
+    let mut names = names
+        .into_iter()
+        .map(|name| name.strip_suffix(".md").unwrap_or(&name).to_string());
+    let old = names
+        .next()
+        .ok_or_else(|| CliError(i18n::tr("缺少参数 <旧文件名>").to_string()))?;
+    let new = names
+        .next()
+        .ok_or_else(|| CliError(i18n::tr("缺少参数 <新文件名>").to_string()))?;
+    if new.is_empty() || new.contains(['/', '\\']) || new.starts_with('.') {
+        return Err(CliError(i18n::trf("无效的文件名 '{}'", &[&new])));
+    }
+    Ok(Command::Rename(RenameArgs {
+        dir,
+        old,
+        new,
+        title,
+        dry_run,
+    }))
+}
+
+fn parse_merge(args: &[String]) -> Result<Command, CliError> {
+    let mut stream = ArgStream::new(args);
+    let mut dir = None;
+    let mut names = Vec::new();
+    let mut dry_run = false;
+
+    while let Some(arg) = stream.next() {
+        match arg {
+            Arg::Flag(flag, inline) => match flag.as_str() {
+                "-h" | "--help" => return Ok(Command::Print(MERGE_HELP.to_string())),
+                "-d" | "--dir" => dir = Some(stream.value(&flag, inline)?),
+                "-n" | "--dry-run" => dry_run = true,
+                _ => return Err(unknown_flag(&flag)),
+            },
+            Arg::Positional(value) if names.len() < 2 => names.push(value),
+            Arg::Positional(value) => return Err(unexpected_arg(&value)),
+        }
+    }
+
+    let mut names = names
+        .into_iter()
+        .map(|name| name.strip_suffix(".md").unwrap_or(&name).to_string());
+    let keep = names
+        .next()
+        .ok_or_else(|| CliError(i18n::tr("缺少参数 <保留的笔记>").to_string()))?;
+    let absorb = names
+        .next()
+        .ok_or_else(|| CliError(i18n::tr("缺少参数 <并入的笔记>").to_string()))?;
+    Ok(Command::Merge(MergeArgs {
+        dir,
+        keep,
+        absorb,
+        dry_run,
+    }))
+}
+
+// 标签按空白分隔，也不能包含列表语法中的字符
+fn check_tags<'a>(tags: impl IntoIterator<Item = &'a String>) -> Result<(), CliError> {
+    for tag in tags {
+        let tag = tag.trim_matches('/');
+        if tag.is_empty() || tag.contains(|c: char| c.is_whitespace() || "[],#\"'".contains(c)) {
+            return Err(CliError(i18n::trf("无效的标签 '{}'", &[&tag])));
+        }
+    }
+    Ok(())
+}
+
+fn retag_args(
+    dir: Option<String>,
+    mut olds: Vec<String>,
+    new: String,
+    dry_run: bool,
+) -> Result<Command, CliError> {
+    check_tags(olds.iter().chain([&new]))?;
+    // 新标签本身不需要改写
+    olds.retain(|old| *old != new);
+    olds.sort_unstable();
+    olds.dedup();
+    if olds.is_empty() {
+        return Err(CliError(i18n::tr("新旧标签相同").to_string()));
+    }
+
+    Ok(Command::Retag(RetagArgs {
+        dir,
+        olds,
+        new,
+        dry_run,
+    }))
+}
+
+fn parse_merge_vaults(args: &[String]) -> Result<Command, CliError> {
+    let mut stream = ArgStream::new(args);
+    let mut dir = None;
+    let mut source = None;
+    let mut rename = false;
+    let mut attachments = None;
+    let mut dry_run = false;
+
+    while let Some(arg) = stream.next() {
+        match arg {
+            Arg::Flag(flag, inline) => match flag.as_str() {
+                "-h" | "--help" => return Ok(Command::Print(MERGE_VAULTS_HELP.to_string())),
+                "-d" | "--dir" => dir = Some(stream.value(&flag, inline)?),
+                "--rename" => rename = true,
+                "-a" | "--attachments" => attachments = Some(stream.value(&flag, inline)?),
+                "-n" | "--dry-run" => dry_run = true,
+                _ => return Err(unknown_flag(&flag)),
+            },
+            Arg::Positional(value) if source.is_none() => source = Some(value),
+            Arg::Positional(value) => return Err(unexpected_arg(&value)),
+        }
+    }
+
+    let source = source.ok_or_else(|| CliError(i18n::tr("缺少参数 <来源目录>").to_string()))?;
+    Ok(Command::MergeVaults(MergeVaultsArgs {
+        dir,
+        source,
+        rename,
+        attachments,
+        dry_run,
+    }))
+}
+
+fn parse_archive(args: &[String]) -> Result<Command, CliError> {
+    let mut stream = ArgStream::new(args);
+    let mut dir = None;
+    let mut output = None;
+    let mut before = None;
+    let mut dry_run = false;
+
+    while let Some(arg) = stream.next() {
+        match arg {
+            Arg::Flag(flag, inline) => match flag.as_str() {
+                "-h" | "--help" => return Ok(Command::Print(ARCHIVE_HELP.to_string())),
+                "-b" | "--before" => {
+                    before = Some(parse_date(&flag, &stream.value(&flag, inline)?)?)
+                }
+                "-o" | "--output" => output = Some(stream.value(&flag, inline)?),
+                "-n" | "--dry-run" => dry_run = true,
+                _ => return Err(unknown_flag(&flag)),
+            },
+            Arg::Positional(value) if dir.is_none() => dir = Some(value),
+            Arg::Positional(value) => return Err(unexpected_arg(&value)),
+        }
+    }
+
+    let before =
+        before.ok_or_else(|| CliError(i18n::tr("缺少选项 --before <日期>").to_string()))?;
+    Ok(Command::Archive(ArchiveArgs {
+        dir,
+        output,
+        before,
+        dry_run,
+    }))
+}
+
+fn parse_watch(args: &[String]) -> Result<Command, CliError> {
+    let mut stream = ArgStream::new(args);
+    let mut dir = None;
+    let mut output = None;
+    let mut columns = None;
+    let mut exclude = Vec::new();
+    let mut backend = None;
+    let mut link_style = None;
+    let mut sort = None;
+    let mut mermaid = None;
+    let mut hubs = None;
+    let mut excerpt = None;
+    let mut org = false;
+    let mut include_drafts = false;
+    let mut no_follow_symlinks = false;
+    let mut dates_from = None;
+    let mut trash_dir = None;
+    let mut purge = false;
+    let mut interval = 500;
+    let mut debounce = 300;
+
+    while let Some(arg) = stream.next() {
+        match arg {
+            Arg::Flag(flag, inline) => match flag.as_str() {
+                "-h" | "--help" => return Ok(Command::Print(WATCH_HELP.to_string())),
+                "--interval" => interval = parse_number(&flag, &stream.value(&flag, inline)?)?,
+                "--debounce" => debounce = parse_number(&flag, &stream.value(&flag, inline)?)?,
+                "-o" | "--output" => output = Some(stream.value(&flag, inline)?),
+                "--columns" => columns = Some(parse_columns(&flag, &stream.value(&flag, inline)?)?),
+                "--exclude" => exclude.push(stream.value(&flag, inline)?),
+                "--backend" => backend = Some(parse_backend(&flag, &stream.value(&flag, inline)?)?),
+                "--link-style" | "--flavor" => {
+                    link_style = Some(parse_link_style(&flag, &stream.value(&flag, inline)?)?)
+                }
+                "--sort" => sort = Some(parse_tag_sort(&flag, &stream.value(&flag, inline)?)?),
+                "--mermaid" => mermaid = Some(parse_number(&flag, &stream.value(&flag, inline)?)?),
+                "--hubs" => hubs = Some(parse_number(&flag, &stream.value(&flag, inline)?)?),
+                "--excerpt" => excerpt = Some(parse_number(&flag, &stream.value(&flag, inline)?)?),
+                "--org" => org = true,
+                "--include-drafts" => include_drafts = true,
+                "--no-follow-symlinks" => no_follow_symlinks = true,
+                "--dates-from" => {
+                    dates_from = Some(parse_date_source(&flag, &stream.value(&flag, inline)?)?)
+                }
+                "--trash-dir" => trash_dir = Some(stream.value(&flag, inline)?),
+                "--purge" => purge = true,
+                _ => return Err(unknown_flag(&flag)),
+            },
+            Arg::Positional(value) if dir.is_none() => dir = Some(value),
+            Arg::Positional(value) => return Err(unexpected_arg(&value)),
+        }
+    }
+
+    if purge && trash_dir.is_some() {
+        return Err(CliError(
+            i18n::tr("--purge 与 --trash-dir 不能同时使用").to_string(),
+        ));
+    }
+
+    Ok(Command::Watch(WatchArgs {
+        dir,
+        output,
+        columns,
+        exclude,
+        backend,
+        link_style,
+        sort,
+        mermaid,
+        hubs,
+        excerpt,
+        org,
+        include_drafts,
+        no_follow_symlinks,
+        dates_from,
+        trash_dir,
+        purge,
+        interval: interval as u64,
+        debounce: debounce as u64,
+    }))
+}
+
+fn parse_rpc(args: &[String]) -> Result<Command, CliError> {
+    let mut stream = ArgStream::new(args);
+    let mut dir = None;
+
+    while let Some(arg) = stream.next() {
+        match arg {
+            Arg::Flag(flag, _) => match flag.as_str() {
+                "-h" | "--help" => return Ok(Command::Print(RPC_HELP.to_string())),
+                _ => return Err(unknown_flag(&flag)),
+            },
+            Arg::Positional(value) if dir.is_none() => dir = Some(value),
+            Arg::Positional(value) => return Err(unexpected_arg(&value)),
+        }
+    }
+
+    Ok(Command::Rpc(RpcArgs { dir }))
+}
+
+fn parse_lsp(args: &[String]) -> Result<Command, CliError> {
+    let mut stream = ArgStream::new(args);
+    let mut dir = None;
+
+    while let Some(arg) = stream.next() {
+        match arg {
+            Arg::Flag(flag, _) => match flag.as_str() {
+                "-h" | "--help" => return Ok(Command::Print(LSP_HELP.to_string())),
+                // 编辑器常以 --stdio 启动语言服务器，本来就使用标准输入输出
+                "--stdio" => {}
+                _ => return Err(unknown_flag(&flag)),
+            },
+            Arg::Positional(value) if dir.is_none() => dir = Some(value),
+            Arg::Positional(value) => return Err(unexpected_arg(&value)),
+        }
+    }
+
+    Ok(Command::Lsp(LspArgs { dir }))
+}
+
+fn parse_serve(args: &[String]) -> Result<Command, CliError> {
+    let mut stream = ArgStream::new(args);
+    let mut dir = None;
+    let mut port = 8080;
+    let mut watch = false;
+    let mut api = false;
+
+    while let Some(arg) = stream.next() {
+        match arg {
+            Arg::Flag(flag, inline) => match flag.as_str() {
+                "-h" | "--help" => return Ok(Command::Print(SERVE_HELP.to_string())),
+                "-p" | "--port" => {
+                    let value = stream.value(&flag, inline)?;
+                    port = value.parse().map_err(|_| {
+                        CliError(i18n::trf(
+                            "选项 {} 需要一个端口号，而不是 '{}'",
+                            &[&flag, &value],
+                        ))
+                    })?;
+                }
+                "-w" | "--watch" => watch = true,
+                "--api" => api = true,
+                _ => return Err(unknown_flag(&flag)),
+            },
+            Arg::Positional(value) if dir.is_none() => dir = Some(value),
+            Arg::Positional(value) => return Err(unexpected_arg(&value)),
+        }
+    }
+
+    Ok(Command::Serve(ServeArgs {
+        dir,
+        port,
+        watch,
+        api,
+    }))
+}
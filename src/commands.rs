@@ -0,0 +1,438 @@
+// 各个子命令的实现：读取配置、打开博客目录，调用库中的功能并打印结果
+
+mod browse;
+mod edit;
+mod index;
+mod maintain;
+mod note;
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::OnceLock;
+
+use crate::build::{self, BuildOptions, BuildReport};
+use crate::cache::Cache;
+use crate::cleanup::Cleanup;
+use crate::cli::{ColumnsArg, Command, Invocation};
+use crate::config::{self, Config};
+use crate::format::Columns;
+use crate::i18n::{self, Lang};
+use crate::log;
+use crate::manifest::{Manifest, manifest_path};
+use crate::note::FrontmatterKeys;
+use crate::output::{DEFAULT_TAG_DIR, LinkStyle, TagSorts, write_page};
+use crate::plan::Action;
+use crate::vault::{ScanOptions, ScanReport};
+use crate::{GtxError, Vault};
+
+// --vault 选择的博客目录名称，见 `run`
+static VAULT_PROFILE: OnceLock<String> = OnceLock::new();
+
+/// 运行解析好的命令；--vault 选择的博客目录在这里记下，打开博客目录时使用
+pub fn run(invocation: Invocation) -> Result<(), GtxError> {
+    if let Some(name) = invocation.vault {
+        let _ = VAULT_PROFILE.set(name);
+    }
+    match invocation.command {
+        Command::Index(args) => index::run_index(args),
+        Command::New(args) => note::run_new(args),
+        Command::Open(args) => browse::run_open(args),
+        Command::Today(args) => note::run_today(args),
+        Command::Tui(args) => browse::run_tui(args),
+        Command::Search(args) => browse::run_search(args),
+        Command::Query(args) => browse::run_query(args),
+        Command::Recent(args) => browse::run_recent(args),
+        Command::Random(args) => browse::run_random(args),
+        Command::Review(args) => browse::run_review(args),
+        Command::List(args) => browse::run_list(args),
+        Command::Stats(args) => browse::run_stats(args),
+        Command::Todos(args) => browse::run_todos(args),
+        Command::Check(args) => maintain::run_check(args),
+        Command::Attachments(args) => maintain::run_attachments(args),
+        Command::Doctor(args) => maintain::run_doctor(args),
+        Command::Clean(args) => maintain::run_clean(args),
+        Command::Graph(args) => maintain::run_graph(args),
+        Command::Export(args) => maintain::run_export(args),
+        Command::Import(args) => edit::run_import(args),
+        Command::Retag(args) => edit::run_retag(args),
+        Command::TagEdit(args) => edit::run_tag_edit(args),
+        Command::Hook(args) => maintain::run_hook(args),
+        Command::Rename(args) => edit::run_rename(args),
+        Command::Merge(args) => edit::run_merge(args),
+        Command::MergeVaults(args) => edit::run_merge_vaults(args),
+        Command::Archive(args) => edit::run_archive(args),
+        Command::Watch(args) => index::run_watch(args),
+        Command::Serve(args) => index::run_serve(args),
+        Command::Rpc(args) => index::run_rpc(args),
+        Command::Lsp(args) => index::run_lsp(args),
+        Command::Print(text) => {
+            println!("{}", text);
+            Ok(())
+        }
+    }
+}
+
+// 读取用户配置与博客目录下的 gtx.toml，后者优先
+// 博客目录依次取命令行参数、环境变量 GTX_VAULT、用户配置中的 vault 与用户主目录下的 .data
+fn open_vault(dir: Option<String>) -> Result<(String, Config), GtxError> {
+    let user_config = match config::user_config_path() {
+        Some(path) => load_config(&path, true)?,
+        None => Config::default(),
+    };
+    // --vault 选择的博客目录：其 path 优先于 GTX_VAULT，其余选项覆盖用户配置的顶层设置
+    let (user_config, profile_path) = match VAULT_PROFILE.get() {
+        Some(name) => {
+            let profile = user_config
+                .profile(name)
+                .cloned()
+                .ok_or_else(|| unknown_profile(name, &user_config))?;
+            let path = profile.vault.clone().ok_or_else(|| {
+                GtxError::Vault(i18n::trf("用户配置中的 [vault.{}] 没有设置 path", &[name]))
+            })?;
+            (user_config.merge(profile), Some(path))
+        }
+        None => (user_config, None),
+    };
+
+    let dir_path = match dir {
+        Some(dir) => dir,
+        None => match profile_path
+            .or_else(config::env_vault)
+            .or_else(|| user_config.vault.clone())
+        {
+            Some(vault) => vault.to_string_lossy().into_owned(),
+            None => config::home_dir()
+                .ok_or_else(|| {
+                    GtxError::Vault(i18n::trf(
+                        "无法确定用户主目录，请设置 HOME（Windows 上为 USERPROFILE）或 {}",
+                        &[&config::VAULT_ENV],
+                    ))
+                })?
+                .join(config::DEFAULT_VAULT_DIR)
+                .to_string_lossy()
+                .into_owned(),
+        },
+    };
+    check_vault_dir(&dir_path)?;
+
+    let vault_config = load_config(&Path::new(&dir_path).join(config::VAULT_CONFIG), false)?;
+    let config = user_config.merge(vault_config);
+    // 配置中的语言只在没有设置 GTX_LANG 时生效
+    if let Some(lang) = config.lang
+        && env_lang()?.is_none()
+    {
+        i18n::set_lang(lang);
+    }
+    Ok((dir_path, config))
+}
+
+// --vault 指定的名称不在用户配置中
+fn unknown_profile(name: &str, config: &Config) -> GtxError {
+    let names: Vec<&str> = config
+        .profiles
+        .iter()
+        .map(|(profile, _)| profile.as_str())
+        .collect();
+    if names.is_empty() {
+        GtxError::Usage(i18n::trf(
+            "用户配置中没有 [vault.{}]，尚未定义任何博客目录",
+            &[&name],
+        ))
+    } else {
+        GtxError::Usage(i18n::trf(
+            "用户配置中没有 [vault.{}]，可用: {}",
+            &[&name, &names.join(", ")],
+        ))
+    }
+}
+
+/// 环境变量 GTX_LANG 指定的语言，未设置或为空时返回 None
+pub fn env_lang() -> Result<Option<Lang>, GtxError> {
+    match env::var(i18n::LANG_ENV) {
+        Ok(value) if !value.is_empty() => Lang::parse(&value).map(Some).ok_or_else(|| {
+            GtxError::Usage(i18n::trf(
+                "环境变量 {} 应为 zh 或 en，而不是 '{}'",
+                &[&i18n::LANG_ENV, &value],
+            ))
+        }),
+        _ => Ok(None),
+    }
+}
+
+// 配置文件不存在时使用空配置；user 见 `Config::load`
+fn load_config(path: &Path, user: bool) -> Result<Config, GtxError> {
+    Ok(Config::load(path, user)?.unwrap_or_default())
+}
+
+// 文件头键名，未配置时使用默认值；配置了多个键名时第一个用于写入，其余作为读取时的备选
+fn frontmatter_keys(config: &Config) -> FrontmatterKeys {
+    let default = FrontmatterKeys::default();
+    let mut fallbacks = Vec::new();
+    let mut key = |names: &Option<Vec<String>>, default: String| match names.as_deref() {
+        Some([first, rest @ ..]) => {
+            fallbacks.extend(rest.iter().map(|other| (first.clone(), other.clone())));
+            first.clone()
+        }
+        _ => default,
+    };
+    FrontmatterKeys {
+        title: key(&config.title_key, default.title),
+        created: key(&config.created_key, default.created),
+        updated: key(&config.updated_key, default.updated),
+        tags: key(&config.tags_key, default.tags),
+        aliases: key(&config.aliases_key, default.aliases),
+        id: key(&config.id_key, default.id),
+        draft: key(&config.draft_key, default.draft),
+        pinned: key(&config.pinned_key, default.pinned),
+        status: key(&config.status_key, default.status),
+        summary: key(&config.summary_key, default.summary),
+        review: key(&config.review_key, default.review),
+        fallbacks,
+    }
+}
+
+// 检查路径是否存在且为目录
+fn check_vault_dir(dir_path: &str) -> Result<(), GtxError> {
+    let path = Path::new(dir_path);
+
+    if !path.exists() {
+        return Err(GtxError::Vault(i18n::trf("路径 '{}' 不存在", &[&dir_path])));
+    }
+
+    if !path.is_dir() {
+        return Err(GtxError::Vault(i18n::trf("'{}' 不是目录", &[&dir_path])));
+    }
+    Ok(())
+}
+
+// 按命令行选项确定旧页面的清理策略
+fn cleanup_for(path: &Path, purge: bool, trash_dir: Option<String>) -> Cleanup {
+    if purge {
+        Cleanup::Purge
+    } else {
+        Cleanup::quarantine(
+            trash_dir.map_or_else(|| path.join(".gtx").join("trash"), PathBuf::from),
+        )
+    }
+}
+
+// 命令行选项优先于配置文件
+fn build_options(
+    path: &Path,
+    config: &Config,
+    output: Option<String>,
+    columns: Option<ColumnsArg>,
+    purge: bool,
+    trash_dir: Option<String>,
+) -> BuildOptions {
+    let columns = columns.map(|columns| match columns {
+        ColumnsArg::Fixed(n) => Columns::Fixed(n),
+        ColumnsArg::Auto => Columns::auto(),
+    });
+    BuildOptions {
+        cleanup: cleanup_for(path, purge, trash_dir),
+        full: false,
+        verbose: false,
+        output: output.map(PathBuf::from).or_else(|| config.output.clone()),
+        tag_columns: columns.or(config.tag_columns),
+        date_columns: columns.or(config.date_columns),
+        link_style: config.link_style.unwrap_or_default(),
+        exclude: config.exclude.clone(),
+        keys: frontmatter_keys(config),
+        backend: config.backend.unwrap_or_default(),
+        tag_sorts: TagSorts {
+            default: config.tag_sort,
+            per_tag: config.tag_sorts.clone(),
+        },
+        time_format: config.time_format.unwrap_or_default(),
+        title_groups: config.az_groups.unwrap_or_default(),
+        mermaid: config.mermaid.unwrap_or(0),
+        hubs: config.hubs.unwrap_or(0),
+        excerpt: config.excerpt.unwrap_or(0),
+        org: config.org.unwrap_or(false),
+        date_source: config.dates_from.unwrap_or_default(),
+        changed: None,
+        search: config.with_search.unwrap_or(false),
+        include_drafts: config.include_drafts.unwrap_or(false),
+        tag_dir: tag_dir(config),
+        follow_symlinks: config.follow_symlinks.unwrap_or(true),
+    }
+}
+
+// 标签页所在的目录，未配置时为 tags
+fn tag_dir(config: &Config) -> String {
+    config
+        .tag_dir
+        .clone()
+        .unwrap_or_else(|| DEFAULT_TAG_DIR.to_string())
+}
+
+fn print_report(report: &BuildReport) {
+    if let Some(affected) = &report.affected {
+        log::info(i18n::trf(
+            "\n增量构建: {} 个标签页、{} 个日期页受影响",
+            &[&affected.tags.len(), &affected.dates.len()],
+        ));
+    }
+}
+
+// 列出演练模式下记下的文件操作，博客目录中的文件显示相对路径
+fn print_plan(root: &Path, actions: &[Action]) {
+    // 输出目录为规范化后的路径，博客目录内的其余页面以命令行给出的路径开头
+    let canonical = fs::canonicalize(root).unwrap_or_else(|_| root.to_path_buf());
+    let show = |path: &Path| {
+        path.strip_prefix(root)
+            .or_else(|_| path.strip_prefix(&canonical))
+            .unwrap_or(path)
+            .display()
+            .to_string()
+    };
+    println!("{}", i18n::tr("\n演练模式，未修改任何文件"));
+    if actions.is_empty() {
+        println!("{}", i18n::tr("没有需要改动的页面"));
+        return;
+    }
+    let mut counts = [0; 4];
+    for action in actions {
+        let line = match action {
+            Action::Create(path) => {
+                counts[0] += 1;
+                i18n::trf("  新建: {}", &[&show(path)])
+            }
+            Action::Update(path) => {
+                counts[1] += 1;
+                i18n::trf("  改写: {}", &[&show(path)])
+            }
+            Action::Remove(path) => {
+                counts[2] += 1;
+                i18n::trf("  删除: {}", &[&show(path)])
+            }
+            Action::Move(path, target) => {
+                counts[3] += 1;
+                i18n::trf("  移动到隔离目录: {} -> {}", &[&show(path), &show(target)])
+            }
+        };
+        println!("{}", line);
+    }
+    println!(
+        "{}",
+        i18n::trf(
+            "共 {} 项：新建 {}，改写 {}，删除 {}，移动到隔离目录 {}",
+            &[
+                &actions.len(),
+                &counts[0],
+                &counts[1],
+                &counts[2],
+                &counts[3]
+            ]
+        )
+    );
+}
+
+// 在输出的最后列出扫描时跳过的文件
+fn print_skipped(report: &ScanReport) {
+    if !report.is_empty() {
+        eprintln!("\n{}", report);
+    }
+}
+
+// 只读使用缓存扫描目录，返回缓存以便写入报告页面后更新
+fn scan_vault(path: &Path, config: &Config) -> Result<(Option<Cache>, Vault), GtxError> {
+    let cache = Cache::load(&build::cache_path(path));
+    let vault = Vault::scan_with(
+        path,
+        &ScanOptions {
+            cache: cache.as_ref().unwrap_or(&Cache::default()),
+            verbose: false,
+            exclude: &config.exclude,
+            keys: &frontmatter_keys(config),
+            org: config.org.unwrap_or(false),
+            date_source: config.dates_from.unwrap_or_default(),
+            changed: None,
+            drafts: config.include_drafts.unwrap_or(false),
+            follow_symlinks: config.follow_symlinks.unwrap_or(true),
+        },
+    )
+    .map_err(|e| GtxError::io(i18n::trf("无法读取目录 '{}'", &[&path.display()]), e))?;
+    print_skipped(vault.report());
+    Ok((cache, vault))
+}
+
+// 在输出目录写入报告页面，render 接收链接写法与链接前缀
+fn write_report(
+    path: &Path,
+    config: &Config,
+    output: Option<String>,
+    (cache, vault): (Option<Cache>, &Vault),
+    page: &str,
+    render: impl Fn(LinkStyle, &str) -> String,
+) -> Result<(), GtxError> {
+    let output = output.map(PathBuf::from).or_else(|| config.output.clone());
+    let out_dir = output.clone().unwrap_or_else(|| path.to_path_buf());
+    let page_path = out_dir.join(page);
+    fs::create_dir_all(page_path.parent().unwrap_or(&out_dir))?;
+    let link_prefix = build::link_prefix(path, &out_dir)?;
+    // 与笔记等用户文件同名时不覆盖，已在 write_page 中报告
+    if !write_page(
+        &out_dir,
+        page,
+        cache.as_ref().map(|cache| &cache.generated),
+        &render(config.link_style.unwrap_or_default(), &link_prefix),
+    )? {
+        return Ok(());
+    }
+
+    // 记入缓存的生成页面，避免被下次构建当作旧页面清理
+    let mut cache = cache.unwrap_or_else(|| {
+        let mut cache = vault.to_cache();
+        cache.output = output.and_then(|dir| fs::canonicalize(dir).ok());
+        cache
+    });
+    cache.generated.insert(page.to_string());
+    cache.save(&build::cache_path(path))?;
+    Manifest {
+        output: cache.output.clone(),
+        files: cache.generated.clone(),
+    }
+    .save(&manifest_path(path))?;
+    println!("{}", i18n::trf("\n已写入: {}", &[&page_path.display()]));
+    Ok(())
+}
+
+// 字段 key 的键名：用于写入的键名在前，其后是读取时的备选
+fn field_keys<'k>(keys: &'k FrontmatterKeys, key: &'k str) -> Vec<&'k str> {
+    let mut field_keys = vec![key];
+    field_keys.extend(
+        keys.fallbacks
+            .iter()
+            .filter(|(primary, _)| primary == key)
+            .map(|(_, other)| other.as_str()),
+    );
+    field_keys
+}
+
+// 用 $EDITOR（未设置时为 vi）打开 path，等待编辑器退出
+fn launch_editor(path: &Path) -> Result<(), GtxError> {
+    // $EDITOR 可以带参数，如 "code -w"
+    let editor = env::var("EDITOR")
+        .ok()
+        .filter(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string());
+    let mut words = editor.split_whitespace();
+    let program = words.next().unwrap_or("vi");
+    let status = process::Command::new(program)
+        .args(words)
+        .arg(path)
+        .status()
+        .map_err(|e| GtxError::io(i18n::trf("无法启动编辑器 '{}'", &[&editor]), e))?;
+    if !status.success() {
+        return Err(GtxError::io(
+            i18n::trf("编辑器 '{}' 异常退出", &[&editor]),
+            io::Error::other(status.to_string()),
+        ));
+    }
+    Ok(())
+}
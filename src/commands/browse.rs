@@ -0,0 +1,524 @@
+// 查找与浏览笔记的子命令：search、query、recent、list、stats、todos、random、review、open 与 tui
+
+use std::collections::HashSet;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::build;
+use crate::cache::Cache;
+use crate::cli::{
+    ListArgs, ListFormat, OpenArgs, QueryArgs, RandomArgs, RecentArgs, ReviewArgs, SearchArgs,
+    StatsArgs, TodosArgs, TuiArgs,
+};
+use crate::date;
+use crate::fulltext::{self, SearchIndex};
+use crate::i18n;
+use crate::index::tag_levels;
+use crate::note::note_file_name;
+use crate::open::{self, FuzzyMatch};
+use crate::query::{self, QUERIES_DIR, Query};
+use crate::review;
+use crate::search::{self, SearchOptions};
+use crate::stats::{STATS_PAGE, Stats};
+use crate::todos::{self, TODOS_PAGE};
+use crate::tui;
+use crate::vault::ScanOptions;
+use crate::{GtxError, Vault};
+
+use super::{
+    field_keys, frontmatter_keys, launch_editor, open_vault, print_skipped, scan_vault, tag_dir,
+    write_report,
+};
+
+pub fn run_search(args: SearchArgs) -> Result<(), GtxError> {
+    let (dir_path, config) = &open_vault(args.dir)?;
+    let path = Path::new(dir_path);
+
+    // 只读使用缓存，避免重新解析未变化的笔记
+    let cache = Cache::load(&build::cache_path(path)).unwrap_or_default();
+    let vault = Vault::scan_with(
+        path,
+        &ScanOptions {
+            cache: &cache,
+            verbose: false,
+            exclude: &config.exclude,
+            keys: &frontmatter_keys(config),
+            org: config.org.unwrap_or(false),
+            date_source: config.dates_from.unwrap_or_default(),
+            changed: None,
+            drafts: config.include_drafts.unwrap_or(false),
+            follow_symlinks: config.follow_symlinks.unwrap_or(true),
+        },
+    )
+    .map_err(|e| GtxError::io(i18n::trf("无法读取目录 '{}'", &[&dir_path]), e))?;
+    print_skipped(vault.report());
+
+    let filter = match &args.filter {
+        Some(text) => Some(Query::parse(text).map_err(|e| GtxError::Usage(e.to_string()))?),
+        None => None,
+    };
+    let options = SearchOptions {
+        ignore_case: args.ignore_case,
+        regex: args.regex,
+        context: args.context,
+        filter,
+        published: false,
+    };
+    // 正则表达式总是逐行搜索；没有搜索索引或查询中没有可检索的词时同样逐行搜索
+    let index = (!args.regex && !args.scan && !fulltext::parse_query(&args.query).is_empty())
+        .then(|| SearchIndex::load(path))
+        .flatten();
+    let matches = match &index {
+        Some(index) => search::ranked(&vault, index, &args.query, &options, args.limit),
+        // 查询无法解析为正则表达式时属于参数错误
+        None => search::search(&vault, &args.query, &options)
+            .map_err(|e| GtxError::Usage(e.to_string()))?,
+    };
+
+    if matches.is_empty() {
+        return Err(GtxError::NotFound(i18n::trf(
+            "没有找到匹配 '{}' 的内容",
+            &[&args.query],
+        )));
+    }
+
+    let mut notes = HashSet::new();
+    for (i, m) in matches.iter().enumerate() {
+        notes.insert(&m.name);
+        if args.context == 0 {
+            println!(
+                "{}:{}: [{}] {}",
+                note_file_name(&m.name),
+                m.line,
+                m.title,
+                m.snippet
+            );
+            continue;
+        }
+        if i > 0 {
+            println!("--");
+        }
+        println!("{}:{}: [{}]", note_file_name(&m.name), m.line, m.title);
+        for (line, text) in &m.before {
+            println!("{:>6}- {}", line, text);
+        }
+        println!("{:>6}: {}", m.line, m.snippet);
+        for (line, text) in &m.after {
+            println!("{:>6}- {}", line, text);
+        }
+    }
+    if index.is_some() {
+        println!(
+            "{}",
+            i18n::trf("\n共 {} 篇笔记，按相关度排序", &[&notes.len()])
+        );
+    } else {
+        println!(
+            "{}",
+            i18n::trf(
+                "\n共 {} 处匹配，涉及 {} 篇笔记",
+                &[&matches.len(), &notes.len()]
+            )
+        );
+    }
+
+    Ok(())
+}
+
+pub fn run_query(args: QueryArgs) -> Result<(), GtxError> {
+    // 查询无法解析时属于参数错误
+    let query = Query::parse(&args.query).map_err(|e| GtxError::Usage(e.to_string()))?;
+    let (dir_path, config) = &open_vault(args.dir)?;
+    let path = Path::new(dir_path);
+    let (cache, vault) = scan_vault(path, config)?;
+
+    let notes = query.filter(vault.notes());
+    for meta in &notes {
+        let created = meta.created.as_ref().map_or_else(
+            || "-".to_string(),
+            |(date, time)| format!("{} {}", date, time),
+        );
+        println!(
+            "{}.md: [{}] {} {}",
+            meta.name,
+            meta.title,
+            created.trim_end(),
+            meta.tags.join(", ")
+        );
+    }
+
+    if let Some(name) = &args.save {
+        write_report(
+            path,
+            config,
+            args.output,
+            (cache, &vault),
+            &format!("{}/{}.md", QUERIES_DIR, name),
+            |style, prefix| query::saved_page(name, &args.query, &notes, style, prefix),
+        )?;
+    }
+
+    if notes.is_empty() {
+        return Err(GtxError::NotFound(i18n::trf(
+            "没有笔记满足查询 '{}'",
+            &[&args.query],
+        )));
+    }
+    println!("{}", i18n::trf("\n共 {} 篇笔记", &[&notes.len()]));
+    Ok(())
+}
+
+pub fn run_recent(args: RecentArgs) -> Result<(), GtxError> {
+    let (dir_path, config) = &open_vault(args.dir)?;
+    let (_, vault) = scan_vault(Path::new(dir_path), config)?;
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    let since = now.saturating_sub(args.days as u64 * 86_400);
+    let mut found = false;
+    for note in vault.indexes().recent() {
+        let created = note.created_at().filter(|&created| created >= since);
+        let modified = note.modified.filter(|&modified| modified >= since);
+        if created.is_none() && (modified.is_none() || !note.is_modified()) {
+            continue;
+        }
+        found = true;
+        let (kind, when) = match (note.is_modified(), note.created) {
+            (false, Some((date, time))) => (i18n::tr("创建"), format!("{} {}", date, time)),
+            _ => {
+                let (date, time) = date::from_timestamp(note.latest());
+                (i18n::tr("修改"), format!("{} {}", date, time))
+            }
+        };
+        println!(
+            "{:<14}  {}  {} ({}.md)",
+            when.trim_end(),
+            kind,
+            note.title,
+            note.name
+        );
+    }
+    if !found {
+        return Err(GtxError::NotFound(i18n::trf(
+            "最近 {} 天没有创建或修改的笔记",
+            &[&args.days],
+        )));
+    }
+    Ok(())
+}
+
+pub fn run_list(args: ListArgs) -> Result<(), GtxError> {
+    let (dir_path, config) = &open_vault(args.dir)?;
+    let (_, vault) = scan_vault(Path::new(dir_path), config)?;
+    let filter = match &args.filter {
+        Some(text) => Some(Query::parse(text).map_err(|e| GtxError::Usage(e.to_string()))?),
+        None => None,
+    };
+
+    let mut notes: Vec<_> = vault
+        .notes()
+        .filter(|note| filter.as_ref().is_none_or(|filter| filter.matches(note)))
+        .collect();
+    notes.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let end = if args.null { '\0' } else { '\n' };
+    let mut out = BufWriter::new(io::stdout().lock());
+    for note in notes {
+        let result = match args.format {
+            ListFormat::Plain => {
+                write!(out, "{}  {}{}", note_file_name(&note.name), note.title, end)
+            }
+            // 字段中的制表符会打乱列，替换为空格
+            ListFormat::Fzf => write!(
+                out,
+                "{}\t{}\t{}{}",
+                vault.note_path(&note.name).display(),
+                note.title.replace('\t', " "),
+                note.tags.join(",").replace('\t', " "),
+                end
+            ),
+        };
+        // 选择器提前退出时停止输出
+        match result {
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => return Ok(()),
+            result => result?,
+        }
+    }
+    match out.flush() {
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        result => Ok(result?),
+    }
+}
+
+pub fn run_stats(args: StatsArgs) -> Result<(), GtxError> {
+    let (dir_path, config) = &open_vault(args.dir)?;
+    let path = Path::new(dir_path);
+    let (cache, vault) = scan_vault(path, config)?;
+
+    let stats = Stats::collect(&vault, args.top, args.graph);
+    print!("{}", stats.to_text());
+
+    if args.write {
+        write_report(
+            path,
+            config,
+            args.output,
+            (cache, &vault),
+            STATS_PAGE,
+            |style, prefix| stats.to_markdown(style, prefix),
+        )?;
+    }
+
+    Ok(())
+}
+
+pub fn run_todos(args: TodosArgs) -> Result<(), GtxError> {
+    let (dir_path, config) = &open_vault(args.dir)?;
+    let path = Path::new(dir_path);
+    let (cache, vault) = scan_vault(path, config)?;
+
+    let notes = vault.notes().filter(|note| match &args.tag {
+        Some(tag) => note
+            .tags
+            .iter()
+            .any(|t| tag_levels(t).contains(&tag.as_str())),
+        None => true,
+    });
+    let items = todos::collect(notes, args.all);
+    for item in &items {
+        let due = item
+            .task
+            .due
+            .map_or_else(String::new, |due| i18n::trf(" (截止 {})", &[&due]));
+        println!(
+            "{}:{}: [{}] {}{}",
+            note_file_name(&item.note.name),
+            item.task.line,
+            if item.task.done { "x" } else { " " },
+            item.task.text,
+            due
+        );
+    }
+
+    if args.write {
+        write_report(
+            path,
+            config,
+            args.output,
+            (cache, &vault),
+            TODOS_PAGE,
+            |style, prefix| todos::todos_page(&items, style, prefix, &tag_dir(config)),
+        )?;
+    }
+
+    if items.is_empty() {
+        println!("{}", i18n::tr("没有待办事项"));
+    }
+    Ok(())
+}
+
+// 候选过多时只列出得分最高的几篇
+const OPEN_CANDIDATES: usize = 20;
+
+pub fn run_random(args: RandomArgs) -> Result<(), GtxError> {
+    let (dir_path, config) = &open_vault(args.dir)?;
+    let (_, vault) = scan_vault(Path::new(dir_path), config)?;
+
+    let indexes = vault.indexes();
+    let notes = match &args.tag {
+        Some(tag) => indexes.tagged(tag),
+        None => indexes.titles(),
+    };
+    if notes.is_empty() {
+        return Err(GtxError::NotFound(match &args.tag {
+            Some(tag) => i18n::trf("没有笔记含有标签 '{}'", &[tag]),
+            None => i18n::tr("没有笔记").to_string(),
+        }));
+    }
+    let (name, _) = notes[random_below(notes.len())];
+
+    let note_path = vault.note_path(name);
+    if args.print {
+        println!("{}", note_path.display());
+        return Ok(());
+    }
+
+    launch_editor(&note_path)
+}
+
+pub fn run_review(args: ReviewArgs) -> Result<(), GtxError> {
+    let (dir_path, config) = &open_vault(args.dir)?;
+    let (_, vault) = scan_vault(Path::new(dir_path), config)?;
+    let keys = frontmatter_keys(config);
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    let (today, _) = date::from_timestamp(now);
+    let (due, invalid) = review::due_notes(&vault, today);
+    for note in invalid {
+        eprintln!(
+            "{}",
+            i18n::trf(
+                "警告: {}.md 的 {} 字段无法识别，应为 日期 [间隔天数] [难度系数]",
+                &[&note.name, &keys.review]
+            )
+        );
+    }
+    if due.is_empty() {
+        println!("{}", i18n::tr("没有需要复习的笔记"));
+        return Ok(());
+    }
+
+    let review_keys = field_keys(&keys, &keys.review);
+    let mut reviewed = 0;
+    for (i, item) in due.iter().enumerate() {
+        let note = item.note;
+        println!(
+            "[{}/{}] {} ({}.md) · {}",
+            i + 1,
+            due.len(),
+            note.title,
+            note.name,
+            i18n::trf("应于 {} 复习", &[&item.schedule.due])
+        );
+        if args.list {
+            continue;
+        }
+        let path = vault.note_path(&note.name);
+        if args.edit {
+            launch_editor(&path)?;
+        }
+        let quality = loop {
+            print!(
+                "{}",
+                i18n::tr("评分 0–5（5 为毫不费力），回车跳过，q 退出: ")
+            );
+            io::stdout().flush()?;
+            let mut line = String::new();
+            if io::stdin().read_line(&mut line)? == 0 {
+                break None;
+            }
+            match line.trim() {
+                "" => break Some(None),
+                "q" | "Q" => break None,
+                text => match text.parse::<u8>() {
+                    Ok(quality) if quality <= review::MAX_QUALITY => break Some(Some(quality)),
+                    _ => eprintln!("{}", i18n::trf("无效的评分 '{}'", &[&text])),
+                },
+            }
+        };
+        let Some(quality) = quality else {
+            break;
+        };
+        let Some(quality) = quality else {
+            continue;
+        };
+        let next = item.schedule.review(today, quality);
+        review::record(&path, &review_keys, &next).map_err(|e| {
+            GtxError::io(i18n::trf("无法更新 '{}.md' 的复习日期", &[&note.name]), e)
+        })?;
+        reviewed += 1;
+        println!(
+            "{}",
+            i18n::trf("下次复习: {}（{} 天后）", &[&next.due, &next.interval])
+        );
+    }
+    if !args.list {
+        println!("{}", i18n::trf("\n复习了 {} 篇笔记", &[&reviewed]));
+    }
+    Ok(())
+}
+
+// [0, n) 中的随机数；RandomState 每个进程以不同的随机种子初始化，再混入当前时间
+fn random_below(n: usize) -> usize {
+    let mut hasher = RandomState::new().build_hasher();
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_nanos());
+    hasher.write_u128(nanos);
+    (hasher.finish() % n as u64) as usize
+}
+
+pub fn run_open(args: OpenArgs) -> Result<(), GtxError> {
+    let (dir_path, config) = &open_vault(args.dir)?;
+    let (_, vault) = scan_vault(Path::new(dir_path), config)?;
+
+    let matches = open::find(&vault, &args.query);
+    // 唯一一篇完全匹配的笔记直接打开
+    let exact: Vec<&FuzzyMatch> = matches.iter().filter(|m| m.exact).collect();
+    let chosen = match (exact.as_slice(), matches.as_slice()) {
+        (_, []) => {
+            return Err(GtxError::NotFound(i18n::trf(
+                "没有标题匹配 '{}' 的笔记",
+                &[&args.query],
+            )));
+        }
+        ([only], _) => *only,
+        (_, [only]) => only,
+        _ => choose_note(&matches)?,
+    };
+
+    let note_path = vault.note_path(&chosen.name);
+    if args.print {
+        println!("{}", note_path.display());
+        return Ok(());
+    }
+
+    launch_editor(&note_path)
+}
+
+pub fn run_tui(args: TuiArgs) -> Result<(), GtxError> {
+    let (dir_path, config) = &open_vault(args.dir)?;
+    let (_, vault) = scan_vault(Path::new(dir_path), config)?;
+    tui::run(&vault, &mut |path| {
+        launch_editor(path).map_err(|e| io::Error::other(e.to_string()))
+    })
+    .map_err(|e| GtxError::io(i18n::tr("终端界面出错"), e))
+}
+
+// 列出候选并从标准输入读取序号
+fn choose_note(matches: &[FuzzyMatch]) -> Result<&FuzzyMatch, GtxError> {
+    let shown = &matches[..matches.len().min(OPEN_CANDIDATES)];
+    for (i, m) in shown.iter().enumerate() {
+        if m.matched == m.title {
+            println!("{:>3}. {} ({}.md)", i + 1, m.title, m.name);
+        } else {
+            println!(
+                "{:>3}. {}",
+                i + 1,
+                i18n::trf("{} ({}.md，匹配: {})", &[&m.title, &m.name, &m.matched])
+            );
+        }
+    }
+    if matches.len() > shown.len() {
+        println!(
+            "{}",
+            i18n::trf(
+                "     …… 另有 {} 篇，请使用更具体的查询",
+                &[&(matches.len() - shown.len())]
+            )
+        );
+    }
+    print!(
+        "{}",
+        i18n::trf("选择要打开的笔记 [1-{}]，直接回车取消: ", &[&shown.len()])
+    );
+    io::stdout().flush()?;
+
+    let mut line = String::new();
+    io::stdin().read_line(&mut line)?;
+    let line = line.trim();
+    if line.is_empty() {
+        return Err(GtxError::NotFound(i18n::tr("已取消").to_string()));
+    }
+    line.parse::<usize>()
+        .ok()
+        .and_then(|n| n.checked_sub(1))
+        .and_then(|i| shown.get(i))
+        .ok_or_else(|| GtxError::Usage(i18n::trf("无效的序号 '{}'", &[&line])))
+}
@@ -0,0 +1,505 @@
+// 批量修改笔记的子命令：archive、tag、rename、merge、merge-vaults 与 import
+
+use std::fs;
+use std::path::Path;
+
+use crate::GtxError;
+use crate::archive::{self, ARCHIVE_DIR, ARCHIVE_INDEX_PAGE};
+use crate::attachments::DEFAULT_ATTACHMENTS_DIR;
+use crate::build;
+use crate::cli::{
+    ArchiveArgs, ImportArgs, ImportSource, MergeArgs, MergeVaultsArgs, RenameArgs, RetagArgs,
+    TagEditArgs,
+};
+use crate::i18n;
+use crate::import;
+use crate::merge::{self, MergeOptions};
+use crate::org;
+use crate::query::Query;
+use crate::rename;
+use crate::retag;
+
+use super::{
+    build_options, check_vault_dir, field_keys, frontmatter_keys, open_vault, print_report,
+    scan_vault, write_report,
+};
+
+pub fn run_archive(args: ArchiveArgs) -> Result<(), GtxError> {
+    let (dir_path, config) = &open_vault(args.dir)?;
+    let path = Path::new(dir_path);
+    let (_, vault) = scan_vault(path, config)?;
+
+    let keys = frontmatter_keys(config);
+    let report = archive::archive_notes(
+        &vault,
+        args.before,
+        &field_keys(&keys, &keys.tags),
+        &path.join(".gtx").join("backup"),
+        args.dry_run,
+    )
+    .map_err(|e| GtxError::io(i18n::tr("无法归档笔记"), e))?;
+
+    let moved = if args.dry_run {
+        "将归档: {} -> {}"
+    } else {
+        "已归档: {} -> {}"
+    };
+    for (from, to) in &report.moved {
+        println!("{}", i18n::trf(moved, &[&from.display(), &to.display()]));
+    }
+    // 指向归档笔记的链接保持原样，gtx check 把它们与失效链接分开列出
+    for (file_path, count) in &report.linked {
+        println!(
+            "{}",
+            i18n::trf(
+                "{} 处链接指向归档的笔记（未改写）: {}",
+                &[&count, &file_path.display()]
+            )
+        );
+    }
+    for file_path in &report.skipped {
+        eprintln!(
+            "{}",
+            i18n::trf(
+                "跳过（{} 中已有同名文件）: {}",
+                &[&ARCHIVE_DIR, &file_path.display()]
+            )
+        );
+    }
+    if report.moved.is_empty() {
+        println!(
+            "{}",
+            i18n::trf("没有创建于 {} 之前的笔记需要归档", &[&args.before])
+        );
+    }
+    if let Some(backup_dir) = &report.backup_dir {
+        println!(
+            "{}",
+            i18n::trf("原文件已备份到: {}", &[&backup_dir.display()])
+        );
+    }
+    if args.dry_run {
+        return Ok(());
+    }
+
+    // 归档的笔记不再属于主索引，重新构建后写入归档索引
+    if !report.moved.is_empty() {
+        let options = build_options(path, config, None, None, false, None);
+        let build_report = build::build(path, &options)?;
+        print_report(&build_report);
+    }
+    let archive_dir = path.join(ARCHIVE_DIR);
+    let archived = if archive_dir.is_dir() {
+        Some(scan_vault(&archive_dir, config)?.1)
+    } else {
+        None
+    };
+    let (cache, vault) = scan_vault(path, config)?;
+    write_report(
+        path,
+        config,
+        args.output,
+        (cache, &vault),
+        ARCHIVE_INDEX_PAGE,
+        |style, link_prefix| {
+            let notes = archived.iter().flat_map(|vault| vault.notes());
+            archive::archive_index_page(notes, style, link_prefix)
+        },
+    )
+}
+
+pub fn run_retag(args: RetagArgs) -> Result<(), GtxError> {
+    let (dir_path, config) = &open_vault(args.dir)?;
+    let path = Path::new(dir_path);
+    let (_, vault) = scan_vault(path, config)?;
+
+    let keys = frontmatter_keys(config);
+    let olds: Vec<&str> = args.olds.iter().map(String::as_str).collect();
+    let report = retag::rename_in_vault(
+        &vault,
+        &field_keys(&keys, &keys.tags),
+        &olds,
+        &args.new,
+        &path.join(".gtx").join("backup"),
+        args.dry_run,
+    )?;
+
+    for file_path in &report.renamed {
+        println!(
+            "{}: {}",
+            if args.dry_run {
+                i18n::tr("将改写")
+            } else {
+                i18n::tr("已改写")
+            },
+            file_path.display()
+        );
+    }
+    for file_path in &report.skipped {
+        eprintln!(
+            "{}",
+            i18n::trf(
+                "跳过（文件头中找不到标签 '{}'）: {}",
+                &[&args.olds.join("', '"), &file_path.display()]
+            )
+        );
+    }
+    if report.renamed.is_empty() && report.skipped.is_empty() {
+        return Err(GtxError::NotFound(i18n::trf(
+            "没有笔记含有标签 '{}'",
+            &[&args.olds.join("', '")],
+        )));
+    }
+    if let Some(backup_dir) = &report.backup_dir {
+        println!(
+            "{}",
+            i18n::trf("原文件已备份到: {}", &[&backup_dir.display()])
+        );
+    }
+    if args.dry_run || report.renamed.is_empty() {
+        return Ok(());
+    }
+
+    // 重新构建索引，使标签页与新标签一致
+    let options = build_options(path, config, None, None, false, None);
+    let build_report = build::build(path, &options)?;
+    print_report(&build_report);
+    println!("{}", i18n::tr("\n索引构建完成！"));
+    Ok(())
+}
+
+pub fn run_tag_edit(args: TagEditArgs) -> Result<(), GtxError> {
+    let query = Query::parse(&args.query).map_err(|e| GtxError::Usage(e.to_string()))?;
+    let (dir_path, config) = &open_vault(args.dir)?;
+    let path = Path::new(dir_path);
+    let (_, vault) = scan_vault(path, config)?;
+
+    let keys = frontmatter_keys(config);
+    let tags: Vec<&str> = args.tags.iter().map(String::as_str).collect();
+    let (adds, removes): (&[&str], &[&str]) = if args.remove {
+        (&[], &tags)
+    } else {
+        (&tags, &[])
+    };
+    let report = retag::edit_in_vault(
+        &vault,
+        &query,
+        &field_keys(&keys, &keys.tags),
+        adds,
+        removes,
+        &path.join(".gtx").join("backup"),
+        args.dry_run,
+    )?;
+
+    for file_path in &report.renamed {
+        println!(
+            "{}: {}",
+            if args.dry_run {
+                i18n::tr("将改写")
+            } else {
+                i18n::tr("已改写")
+            },
+            file_path.display()
+        );
+    }
+    for file_path in &report.skipped {
+        eprintln!(
+            "{}",
+            i18n::trf(
+                "跳过（文件头中找不到标签 '{}'）: {}",
+                &[&args.tags.join("', '"), &file_path.display()]
+            )
+        );
+    }
+    if report.renamed.is_empty() {
+        println!("{}", i18n::tr("没有需要改写的笔记"));
+        return Ok(());
+    }
+    if let Some(backup_dir) = &report.backup_dir {
+        println!(
+            "{}",
+            i18n::trf("原文件已备份到: {}", &[&backup_dir.display()])
+        );
+    }
+    if args.dry_run {
+        return Ok(());
+    }
+
+    let options = build_options(path, config, None, None, false, None);
+    let build_report = build::build(path, &options)?;
+    print_report(&build_report);
+    println!("{}", i18n::tr("\n索引构建完成！"));
+    Ok(())
+}
+
+pub fn run_rename(args: RenameArgs) -> Result<(), GtxError> {
+    let (dir_path, config) = &open_vault(args.dir)?;
+    let path = Path::new(dir_path);
+    let (_, vault) = scan_vault(path, config)?;
+
+    if !vault.notes().any(|meta| meta.name == args.old) {
+        return Err(GtxError::NotFound(i18n::trf(
+            "找不到笔记 '{}.md'",
+            &[&args.old],
+        )));
+    }
+    if args.old == args.new {
+        return Err(GtxError::Usage(
+            i18n::tr("新文件名与旧文件名相同").to_string(),
+        ));
+    }
+    let keys = frontmatter_keys(config);
+    let report = rename::rename_note(
+        &vault,
+        &args.old,
+        &args.new,
+        args.title
+            .as_deref()
+            .map(|title| (keys.title.as_str(), title)),
+        &path.join(".gtx").join("backup"),
+        args.dry_run,
+    )
+    .map_err(|e| GtxError::io(i18n::trf("无法重命名 '{}.md'", &[&args.old]), e))?;
+
+    let (renamed, rewritten) = if args.dry_run {
+        ("将重命名: {} -> {}", "将改写 {} 处链接: {}")
+    } else {
+        ("已重命名: {} -> {}", "已改写 {} 处链接: {}")
+    };
+    println!(
+        "{}",
+        i18n::trf(renamed, &[&report.from.display(), &report.to.display()])
+    );
+    for (file_path, count) in &report.rewritten {
+        println!("{}", i18n::trf(rewritten, &[&count, &file_path.display()]));
+    }
+    if let Some(backup_dir) = &report.backup_dir {
+        println!(
+            "{}",
+            i18n::trf("原文件已备份到: {}", &[&backup_dir.display()])
+        );
+    }
+    if args.dry_run {
+        return Ok(());
+    }
+
+    // 重新构建索引，旧文件名的页面按 index 的方式清理
+    let options = build_options(path, config, None, None, false, None);
+    let build_report = build::build(path, &options)?;
+    print_report(&build_report);
+    println!("{}", i18n::tr("\n索引构建完成！"));
+    Ok(())
+}
+
+pub fn run_merge(args: MergeArgs) -> Result<(), GtxError> {
+    let (dir_path, config) = &open_vault(args.dir)?;
+    let path = Path::new(dir_path);
+    let (_, vault) = scan_vault(path, config)?;
+
+    for name in [&args.keep, &args.absorb] {
+        if !vault.notes().any(|meta| meta.name == *name) {
+            return Err(GtxError::NotFound(i18n::trf("找不到笔记 '{}.md'", &[name])));
+        }
+        if org::is_org(name) {
+            return Err(GtxError::Usage(i18n::trf(
+                "不能合并 org 笔记 '{}'",
+                &[name],
+            )));
+        }
+    }
+    if args.keep == args.absorb {
+        return Err(GtxError::Usage(
+            i18n::tr("不能把笔记并入它自己").to_string(),
+        ));
+    }
+    let keys = frontmatter_keys(config);
+    let report = rename::merge_notes(
+        &vault,
+        &args.keep,
+        &args.absorb,
+        &field_keys(&keys, &keys.tags),
+        &field_keys(&keys, &keys.created),
+        &path.join(".gtx").join("backup"),
+        args.dry_run,
+    )
+    .map_err(|e| GtxError::io(i18n::trf("无法合并 '{}.md'", &[&args.absorb]), e))?;
+
+    let (merged, tags, created, rewritten) = if args.dry_run {
+        (
+            "将合并: {} -> {}",
+            "将加上标签: {}",
+            "将改用创建时间: {}",
+            "将改写 {} 处链接: {}",
+        )
+    } else {
+        (
+            "已合并: {} -> {}",
+            "已加上标签: {}",
+            "已改用创建时间: {}",
+            "已改写 {} 处链接: {}",
+        )
+    };
+    println!(
+        "{}",
+        i18n::trf(merged, &[&report.removed.display(), &report.kept.display()])
+    );
+    if !report.tags.is_empty() {
+        println!("{}", i18n::trf(tags, &[&report.tags.join(", ")]));
+    }
+    if let Some(value) = &report.created {
+        println!("{}", i18n::trf(created, &[value]));
+    }
+    for (file_path, count) in &report.rewritten {
+        println!("{}", i18n::trf(rewritten, &[&count, &file_path.display()]));
+    }
+    if let Some(backup_dir) = &report.backup_dir {
+        println!(
+            "{}",
+            i18n::trf("原文件已备份到: {}", &[&backup_dir.display()])
+        );
+    }
+    if args.dry_run {
+        return Ok(());
+    }
+
+    let options = build_options(path, config, None, None, false, None);
+    let build_report = build::build(path, &options)?;
+    print_report(&build_report);
+    println!("{}", i18n::tr("\n索引构建完成！"));
+    Ok(())
+}
+
+pub fn run_merge_vaults(args: MergeVaultsArgs) -> Result<(), GtxError> {
+    let (dir_path, config) = &open_vault(args.dir)?;
+    let path = Path::new(dir_path);
+    check_vault_dir(&args.source)?;
+    let source = Path::new(&args.source);
+    if fs::canonicalize(source)? == fs::canonicalize(path)? {
+        return Err(GtxError::Usage(
+            i18n::tr("来源目录与合并到的博客目录相同").to_string(),
+        ));
+    }
+    // 来源目录只读扫描，使用其中的缓存跳过上次生成的页面
+    let (_, vault) = scan_vault(source, config)?;
+
+    let attachments = args
+        .attachments
+        .or_else(|| config.attachments.clone())
+        .unwrap_or_else(|| DEFAULT_ATTACHMENTS_DIR.to_string());
+    let report = merge::merge_vaults(
+        &vault,
+        path,
+        &MergeOptions {
+            rename: args.rename,
+            attachments: &attachments,
+            dry_run: args.dry_run,
+        },
+    )
+    .map_err(|e| GtxError::io(i18n::trf("无法合并 '{}'", &[&args.source]), e))?;
+
+    if report.blocked() {
+        for file_path in &report.conflicts {
+            eprintln!("{}", i18n::trf("重名: {}", &[&file_path.display()]));
+        }
+        return Err(GtxError::Usage(i18n::trf(
+            "{} 篇笔记与当前目录中的笔记重名而内容不同，没有复制任何文件；使用 --rename 为它们改名",
+            &[&report.conflicts.len()],
+        )));
+    }
+
+    let (copied, renamed, rewritten) = if args.dry_run {
+        (
+            "将复制: {} -> {}",
+            "将重命名: {} -> {}",
+            "将改写 {} 处链接: {}",
+        )
+    } else {
+        (
+            "已复制: {} -> {}",
+            "已重命名: {} -> {}",
+            "已改写 {} 处链接: {}",
+        )
+    };
+    for (from, to) in &report.copied {
+        println!("{}", i18n::trf(copied, &[&from.display(), &to.display()]));
+    }
+    for (old, new) in &report.renamed {
+        println!("{}", i18n::trf(renamed, &[&old, &new]));
+    }
+    for (file_path, count) in &report.rewritten {
+        println!("{}", i18n::trf(rewritten, &[&count, &file_path.display()]));
+    }
+    for file in &report.skipped_attachments {
+        eprintln!(
+            "{}",
+            i18n::trf("警告: 附件 {} 已存在且内容不同，保留原文件", &[&file])
+        );
+    }
+    println!(
+        "{}",
+        i18n::trf(
+            "共 {} 篇笔记、{} 个附件，{} 篇笔记已存在且内容相同",
+            &[
+                &report.copied.len(),
+                &report.attachments,
+                &report.identical.len()
+            ]
+        )
+    );
+    if args.dry_run {
+        return Ok(());
+    }
+
+    // 合并后的笔记一起重新构建索引
+    let options = build_options(path, config, None, None, false, None);
+    let build_report = build::build(path, &options)?;
+    print_report(&build_report);
+    println!("{}", i18n::tr("\n索引构建完成！"));
+    Ok(())
+}
+
+pub fn run_import(args: ImportArgs) -> Result<(), GtxError> {
+    let (dir_path, config) = &open_vault(args.dir)?;
+    let keys = frontmatter_keys(config);
+    let source = Path::new(&args.path);
+    if !source.exists() {
+        return Err(GtxError::Usage(i18n::trf("'{}' 不存在", &[&args.path])));
+    }
+
+    let (name, report) = match args.source {
+        ImportSource::Joplin => (
+            "Joplin",
+            import::import_joplin(source, Path::new(dir_path), &keys)
+                .map_err(|e| GtxError::io(i18n::trf("无法导入 '{}'", &[&args.path]), e))?,
+        ),
+        ImportSource::Enex => (
+            "Evernote",
+            import::import_enex(source, Path::new(dir_path), &keys)
+                .map_err(|e| GtxError::io(i18n::trf("无法导入 '{}'", &[&args.path]), e))?,
+        ),
+        ImportSource::Notion => {
+            if !source.is_dir() {
+                return Err(GtxError::Usage(i18n::trf(
+                    "'{}' 不是目录，请先解压 Notion 导出的 ZIP 文件",
+                    &[&args.path],
+                )));
+            }
+            (
+                "Notion",
+                import::import_notion(source, Path::new(dir_path), &keys)
+                    .map_err(|e| GtxError::io(i18n::trf("无法导入 '{}'", &[&args.path]), e))?,
+            )
+        }
+    };
+    for path in &report.notes {
+        println!("{}", i18n::trf("已创建: {}", &[&path.display()]));
+    }
+    println!(
+        "{}",
+        i18n::trf(
+            "已从 {} 导入 {} 篇笔记、{} 个附件",
+            &[&name, &report.notes.len(), &report.attachments]
+        )
+    );
+    Ok(())
+}
@@ -0,0 +1,330 @@
+// gtx index、watch、serve、rpc 与 lsp：构建页面，以及监视目录并持续构建或提供服务
+
+use std::io;
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use crate::GtxError;
+use crate::build::{self, Backend, BuildOptions};
+use crate::cli::{
+    BackendArg, DateSourceArg, IndexArgs, LinkStyleArg, LspArgs, RpcArgs, ServeArgs, TagSortArg,
+    WatchArgs,
+};
+use crate::hook;
+use crate::i18n;
+use crate::log::{self, Level};
+use crate::lsp::LanguageServer;
+use crate::output::{LinkStyle, TagSort, TagSorts};
+use crate::plan;
+use crate::rpc::Server;
+use crate::serve::{self, Preview};
+use crate::site;
+use crate::vault::DateSource;
+use crate::watch::Watcher;
+
+use super::{build_options, open_vault, print_plan, print_report, print_skipped, scan_vault};
+
+pub fn run_index(args: IndexArgs) -> Result<(), GtxError> {
+    log::set_level(if args.quiet {
+        Level::Quiet
+    } else if args.verbose {
+        Level::Verbose
+    } else {
+        Level::Normal
+    });
+    let (dir_path, config) = &open_vault(args.dir)?;
+    let path = Path::new(dir_path);
+    let options = build_options(
+        path,
+        config,
+        args.output,
+        args.columns,
+        args.purge,
+        args.trash_dir,
+    );
+    let changed = if args.changed_only {
+        let files = hook::changed_files(path)
+            .map_err(|e| GtxError::io(i18n::tr("无法读取本次提交涉及的文件"), e))?;
+        Some(files)
+    } else {
+        None
+    };
+    let options = BuildOptions {
+        full: args.full,
+        verbose: !args.quiet,
+        changed,
+        backend: backend_for(args.backend, &options),
+        link_style: link_style_for(args.link_style, &options),
+        tag_sorts: tag_sorts_for(args.sort, &options),
+        mermaid: args.mermaid.unwrap_or(options.mermaid),
+        hubs: args.hubs.unwrap_or(options.hubs),
+        excerpt: args.excerpt.unwrap_or(options.excerpt),
+        org: args.org || options.org,
+        date_source: date_source_for(args.dates_from, &options),
+        search: args.with_search || options.search,
+        include_drafts: args.include_drafts || options.include_drafts,
+        follow_symlinks: !args.no_follow_symlinks && options.follow_symlinks,
+        tag_dir: args
+            .tag_dir
+            .map_or(options.tag_dir, |dir| dir.trim_matches('/').to_string()),
+        exclude: [options.exclude, args.exclude].concat(),
+        ..options
+    };
+
+    if args.dry_run {
+        plan::enable();
+    }
+    let report = build::build(path, &options)
+        .map_err(|e| GtxError::io(i18n::trf("无法构建目录 '{}' 的索引", &[&dir_path]), e))?;
+    print_report(&report);
+    if args.dry_run {
+        print_plan(path, &plan::take());
+    } else {
+        log::info(i18n::tr("\n索引构建完成！"));
+    }
+    log::info(&report.timings);
+    // 创建时间无法识别的笔记仍然索引，只是不出现在日期页中
+    for (path, value) in &report.invalid_created {
+        eprintln!(
+            "{}",
+            i18n::trf("{}: 无法识别的创建时间 '{}'", &[&path.display(), value])
+        );
+    }
+    print_skipped(&report.scan);
+
+    if !report.scan.is_empty() {
+        return Err(GtxError::Parse(i18n::trf(
+            "{} 个文件解析失败",
+            &[&report.scan.len()],
+        )));
+    }
+    Ok(())
+}
+
+pub fn run_watch(args: WatchArgs) -> Result<(), GtxError> {
+    let (dir_path, config) = &open_vault(args.dir)?;
+    let path = Path::new(dir_path);
+    let options = build_options(
+        path,
+        config,
+        args.output,
+        args.columns,
+        args.purge,
+        args.trash_dir,
+    );
+    let options = BuildOptions {
+        backend: backend_for(args.backend, &options),
+        link_style: link_style_for(args.link_style, &options),
+        tag_sorts: tag_sorts_for(args.sort, &options),
+        mermaid: args.mermaid.unwrap_or(options.mermaid),
+        hubs: args.hubs.unwrap_or(options.hubs),
+        excerpt: args.excerpt.unwrap_or(options.excerpt),
+        org: args.org || options.org,
+        date_source: date_source_for(args.dates_from, &options),
+        include_drafts: args.include_drafts || options.include_drafts,
+        follow_symlinks: !args.no_follow_symlinks && options.follow_symlinks,
+        exclude: [options.exclude, args.exclude].concat(),
+        ..options
+    };
+
+    // 启动时先构建一次，之后只处理变化
+    let report = build::build(path, &options)?;
+    print_report(&report);
+    println!(
+        "{}",
+        i18n::trf("索引构建完成，共 {} 篇笔记", &[&report.notes])
+    );
+    print_skipped(&report.scan);
+
+    let mut watcher = Watcher::new(
+        path,
+        Duration::from_millis(args.interval),
+        Duration::from_millis(args.debounce),
+    )?;
+    println!(
+        "{}",
+        i18n::trf("正在监视 '{}'，按 Ctrl-C 退出", &[&dir_path])
+    );
+
+    loop {
+        let changed = watcher.wait_for_changes()?;
+        println!();
+        for file_path in &changed {
+            println!("{}", i18n::trf("变化: {}", &[&file_path.display()]));
+        }
+        match build::build(path, &options) {
+            Ok(report) => {
+                print_report(&report);
+                println!(
+                    "{}",
+                    i18n::trf("索引构建完成，共 {} 篇笔记", &[&report.notes])
+                );
+                print_skipped(&report.scan);
+            }
+            // 构建失败时继续监视，等待下一次修改
+            Err(e) => eprintln!("{}", i18n::trf("错误: 构建失败: {}", &[&e])),
+        }
+        // 忽略本次构建写入的页面
+        watcher.resync()?;
+    }
+}
+
+pub fn run_rpc(args: RpcArgs) -> Result<(), GtxError> {
+    let (dir_path, config) = &open_vault(args.dir)?;
+    let path = Path::new(dir_path);
+    let scan = || {
+        scan_vault(path, config)
+            .map(|(_, vault)| vault)
+            .map_err(|e| io::Error::other(e.to_string()))
+    };
+    let mut server = Server::new(&scan)?;
+    server.run(io::stdin().lock(), io::stdout().lock())?;
+    Ok(())
+}
+
+pub fn run_lsp(args: LspArgs) -> Result<(), GtxError> {
+    let (dir_path, config) = &open_vault(args.dir)?;
+    let path = Path::new(dir_path);
+    let scan = || {
+        scan_vault(path, config)
+            .map(|(_, vault)| vault)
+            .map_err(|e| io::Error::other(e.to_string()))
+    };
+    let mut server = LanguageServer::new(&scan)?;
+    server.run(io::stdin().lock(), io::stdout().lock())?;
+    Ok(())
+}
+
+pub fn run_serve(args: ServeArgs) -> Result<(), GtxError> {
+    let (dir_path, config) = open_vault(args.dir)?;
+    let path = PathBuf::from(&dir_path);
+
+    let listener = TcpListener::bind(("127.0.0.1", args.port))
+        .map_err(|e| GtxError::io(i18n::trf("无法监听端口 {}", &[&args.port]), e))?;
+    if args.watch {
+        // 与 gtx watch 相同，启动时先构建一次
+        let report = build::build(
+            &path,
+            &build_options(&path, &config, None, None, false, None),
+        )?;
+        print_report(&report);
+        println!(
+            "{}",
+            i18n::trf("索引构建完成，共 {} 篇笔记", &[&report.notes])
+        );
+    }
+    let (_, vault) = scan_vault(&path, &config)?;
+    let mut preview = Preview::new(&path, site::render_site(&vault)?, args.watch);
+    if args.api {
+        preview = preview.with_api(vault);
+    }
+    let preview = Arc::new(preview);
+    println!(
+        "{}",
+        i18n::trf(
+            "预览地址: http://127.0.0.1:{}/，按 Ctrl-C 退出",
+            &[&args.port]
+        )
+    );
+    if args.api {
+        println!(
+            "{}",
+            i18n::trf("JSON 接口: http://127.0.0.1:{}/api/notes", &[&args.port])
+        );
+    }
+
+    if args.watch {
+        let mut watcher = Watcher::new(
+            &path,
+            Duration::from_millis(500),
+            Duration::from_millis(300),
+        )?;
+        let preview = Arc::clone(&preview);
+        thread::spawn(move || {
+            let options = build_options(&path, &config, None, None, false, None);
+            loop {
+                let changed = match watcher.wait_for_changes() {
+                    Ok(changed) => changed,
+                    Err(e) => {
+                        eprintln!("{}", i18n::trf("错误: 无法监视目录: {}", &[&e]));
+                        return;
+                    }
+                };
+                println!();
+                for file_path in &changed {
+                    println!("{}", i18n::trf("变化: {}", &[&file_path.display()]));
+                }
+                // 构建或渲染失败时保留旧页面，等待下一次修改
+                match build::build(&path, &options) {
+                    Ok(report) => print_report(&report),
+                    Err(e) => eprintln!("{}", i18n::trf("错误: 构建失败: {}", &[&e])),
+                }
+                match scan_vault(&path, &config)
+                    .and_then(|(_, vault)| Ok((site::render_site(&vault)?, vault)))
+                {
+                    Ok((pages, vault)) => {
+                        preview.update(pages);
+                        preview.update_api(vault);
+                        println!("{}", i18n::tr("预览已更新"));
+                    }
+                    Err(e) => eprintln!("{}", i18n::trf("错误: 渲染失败: {}", &[&e])),
+                }
+                // 忽略本次构建写入的页面
+                if let Err(e) = watcher.resync() {
+                    eprintln!("{}", i18n::trf("错误: 无法监视目录: {}", &[&e]));
+                    return;
+                }
+            }
+        });
+    }
+
+    serve::serve(listener, preview)?;
+    Ok(())
+}
+
+// 命令行指定的链接写法优先于配置文件
+fn link_style_for(arg: Option<LinkStyleArg>, options: &BuildOptions) -> LinkStyle {
+    match arg {
+        Some(LinkStyleArg::Wiki) => LinkStyle::Wiki,
+        Some(LinkStyleArg::Markdown) => LinkStyle::Markdown,
+        Some(LinkStyleArg::Obsidian) => LinkStyle::Obsidian,
+        Some(LinkStyleArg::Org) => LinkStyle::Org,
+        None => options.link_style,
+    }
+}
+
+// 命令行指定的时间来源优先于配置文件
+fn date_source_for(arg: Option<DateSourceArg>, options: &BuildOptions) -> DateSource {
+    match arg {
+        Some(DateSourceArg::Frontmatter) => DateSource::Frontmatter,
+        Some(DateSourceArg::Git) => DateSource::Git,
+        None => options.date_source,
+    }
+}
+
+// 命令行指定的顺序替换配置文件中的默认顺序，单个标签的设置仍然有效
+fn tag_sorts_for(arg: Option<TagSortArg>, options: &BuildOptions) -> TagSorts {
+    let sort = match arg {
+        Some(TagSortArg::Created) => TagSort::Created,
+        Some(TagSortArg::CreatedDesc) => TagSort::CreatedDesc,
+        Some(TagSortArg::Title) => TagSort::Title,
+        Some(TagSortArg::Modified) => TagSort::Modified,
+        None => return options.tag_sorts.clone(),
+    };
+    TagSorts {
+        default: Some(sort),
+        ..options.tag_sorts.clone()
+    }
+}
+
+// 命令行指定的存储方式优先于配置文件
+fn backend_for(arg: Option<BackendArg>, options: &BuildOptions) -> Backend {
+    match arg {
+        Some(BackendArg::Memory) => Backend::Memory,
+        Some(BackendArg::Sqlite) => Backend::Sqlite,
+        None => options.backend,
+    }
+}
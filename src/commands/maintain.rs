@@ -0,0 +1,373 @@
+// 检查与导出的子命令：check、attachments、doctor、clean、graph、export 与 hook
+
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::GtxError;
+use crate::anki;
+use crate::archive;
+use crate::attachments::{self, ATTACHMENTS_PAGE, Attachments, DEFAULT_ATTACHMENTS_DIR};
+use crate::build::{self, BuildOptions};
+use crate::cache::Cache;
+use crate::check::{self, BROKEN_LINKS_PAGE};
+use crate::cli::{
+    AttachmentsArgs, CheckArgs, CleanArgs, DoctorArgs, ExportArgs, ExportFormat, GraphArgs,
+    GraphFormat, HookArgs,
+};
+use crate::date;
+use crate::doctor::{self, Diagnosis, IssueKind};
+use crate::epub;
+use crate::export;
+use crate::graph;
+use crate::hook::{self, HookKind};
+use crate::i18n;
+use crate::manifest::{Manifest, manifest_path};
+use crate::plan;
+use crate::site;
+use crate::ssg::{self, Generator};
+
+use super::{
+    build_options, frontmatter_keys, open_vault, print_plan, print_skipped, scan_vault,
+    write_report,
+};
+
+pub fn run_check(args: CheckArgs) -> Result<(), GtxError> {
+    let (dir_path, config) = &open_vault(args.dir)?;
+    let path = Path::new(dir_path);
+    let (cache, vault) = scan_vault(path, config)?;
+
+    // 指向归档笔记的链接不算失效，单独列出
+    let (archived, broken): (Vec<_>, Vec<_>) = check::broken_links(vault.indexes())
+        .into_iter()
+        .partition(|link| archive::is_archived(path, &link.target));
+    for link in &broken {
+        println!(
+            "{}.md:{}: [{}] [[{}]]",
+            link.source_name, link.line, link.source_title, link.target
+        );
+    }
+    if !archived.is_empty() {
+        println!("{}", i18n::tr("\n指向归档笔记的链接:"));
+        for link in &archived {
+            println!(
+                "{}.md:{}: [{}] [[{}]]",
+                link.source_name, link.line, link.source_title, link.target
+            );
+        }
+    }
+
+    if args.write {
+        write_report(
+            path,
+            config,
+            args.output,
+            (cache, &vault),
+            BROKEN_LINKS_PAGE,
+            |style, prefix| check::broken_links_page(&broken, style, prefix),
+        )?;
+    }
+
+    if broken.is_empty() {
+        println!("{}", i18n::tr("没有失效链接"));
+    } else {
+        let notes: HashSet<&String> = broken.iter().map(|link| &link.source_name).collect();
+        return Err(GtxError::NotFound(i18n::trf(
+            "\n共 {} 条失效链接，涉及 {} 篇笔记",
+            &[&broken.len(), &notes.len()],
+        )));
+    }
+
+    Ok(())
+}
+
+pub fn run_attachments(args: AttachmentsArgs) -> Result<(), GtxError> {
+    let (dir_path, config) = &open_vault(args.dir)?;
+    let path = Path::new(dir_path);
+    let (cache, vault) = scan_vault(path, config)?;
+
+    let dir = args
+        .attachments
+        .or_else(|| config.attachments.clone())
+        .unwrap_or_else(|| DEFAULT_ATTACHMENTS_DIR.to_string());
+    let follow_symlinks = !args.no_follow_symlinks && config.follow_symlinks.unwrap_or(true);
+    let attachments = Attachments::collect(&vault, &dir, follow_symlinks)
+        .map_err(|e| GtxError::io(i18n::trf("无法读取附件目录 '{}'", &[&dir]), e))?;
+
+    for (file, embeds) in &attachments.used {
+        let mut sources: Vec<&str> = embeds.iter().map(|e| e.source_name.as_str()).collect();
+        sources.dedup();
+        println!("{}: {}", file, sources.join(", "));
+    }
+    for embed in &attachments.missing {
+        println!(
+            "{}",
+            i18n::trf(
+                "{}.md:{}: 找不到附件 '{}'",
+                &[&embed.source_name, &embed.line, &embed.target]
+            )
+        );
+    }
+    for file in &attachments.unused {
+        println!("{}", i18n::trf("未引用: {}", &[&file]));
+    }
+
+    if args.write {
+        write_report(
+            path,
+            config,
+            args.output,
+            (cache, &vault),
+            ATTACHMENTS_PAGE,
+            |style, prefix| attachments::attachments_page(&attachments, style, prefix),
+        )?;
+    }
+
+    if attachments.missing.is_empty() && attachments.unused.is_empty() {
+        println!(
+            "{}",
+            i18n::trf(
+                "\n共 {} 个附件，没有缺失或未引用的附件",
+                &[&attachments.used.len()]
+            )
+        );
+        return Ok(());
+    }
+    Err(GtxError::NotFound(i18n::trf(
+        "\n共 {} 个附件，{} 处嵌入找不到文件，{} 个文件未被引用",
+        &[
+            &attachments.used.len(),
+            &attachments.missing.len(),
+            &attachments.unused.len(),
+        ],
+    )))
+}
+
+pub fn run_doctor(args: DoctorArgs) -> Result<(), GtxError> {
+    let (dir_path, config) = &open_vault(args.dir)?;
+    let path = Path::new(dir_path);
+    let keys = frontmatter_keys(config);
+    let cache = Cache::load(&build::cache_path(path)).unwrap_or_default();
+    let diagnose = || {
+        doctor::diagnose(
+            path,
+            &cache,
+            &config.exclude,
+            &keys,
+            config.follow_symlinks.unwrap_or(true),
+        )
+        .map_err(|e| GtxError::io(i18n::trf("无法读取目录 '{}'", &[&dir_path]), e))
+    };
+    let Diagnosis {
+        mut issues,
+        mut skipped,
+    } = diagnose()?;
+
+    if args.fix {
+        let report = doctor::fix(&issues, &keys, &file_datetime)?;
+        for file in &report.files {
+            println!("{}", i18n::trf("已修复: {}", &[&file.display()]));
+        }
+        if !report.files.is_empty() {
+            println!(
+                "{}",
+                i18n::trf(
+                    "共修复 {} 个问题，涉及 {} 篇笔记\n",
+                    &[&report.fixed.len(), &report.files.len()]
+                )
+            );
+            // 重新检查，剩下的问题需要手动处理
+            Diagnosis { issues, skipped } = diagnose()?;
+        }
+    }
+
+    if issues.is_empty() && skipped.is_empty() {
+        println!("{}", i18n::tr("没有发现问题"));
+        return Ok(());
+    }
+    let mut kind: Option<IssueKind> = None;
+    for issue in &issues {
+        if kind != Some(issue.kind) {
+            let count = issues.iter().filter(|i| i.kind == issue.kind).count();
+            if kind.is_some() {
+                println!();
+            }
+            println!(
+                "{}",
+                i18n::trf("{}（{}）:", &[&issue.kind.describe(&keys), &count])
+            );
+            kind = Some(issue.kind);
+        }
+        let name = issue.path.file_name().unwrap_or_default().to_string_lossy();
+        if issue.detail.is_empty() {
+            println!("  {}", name);
+        } else {
+            println!("  {}: {}", name, issue.detail);
+        }
+    }
+    print_skipped(&skipped);
+    let fixable = issues.iter().filter(|issue| issue.fixable).count();
+    let hint = if fixable > 0 {
+        i18n::trf("，其中 {} 个可以用 --fix 自动修复", &[&fixable])
+    } else {
+        String::new()
+    };
+    Err(GtxError::NotFound(i18n::trf(
+        "\n共 {} 个问题{}",
+        &[&(issues.len() + skipped.len()), &hint],
+    )))
+}
+
+pub fn run_clean(args: CleanArgs) -> Result<(), GtxError> {
+    let (dir_path, config) = &open_vault(args.dir)?;
+    let path = Path::new(dir_path);
+    let Some(manifest) = Manifest::load(&manifest_path(path)) else {
+        return Err(GtxError::NotFound(i18n::trf(
+            "找不到生成文件清单 {}，请先运行 gtx index",
+            &[&manifest_path(path).display()],
+        )));
+    };
+
+    // 完整重建时清单中不再生成的页面都按清理策略处理；没有配置输出目录时沿用上次的
+    let options = build_options(path, config, None, None, args.purge, args.trash_dir);
+    let options = BuildOptions {
+        full: true,
+        output: options.output.or(manifest.output),
+        ..options
+    };
+    if args.dry_run {
+        plan::enable();
+    }
+    let report = build::build(path, &options)
+        .map_err(|e| GtxError::io(i18n::trf("无法构建目录 '{}' 的索引", &[&dir_path]), e))?;
+    if args.dry_run {
+        print_plan(path, &plan::take());
+    } else {
+        println!("{}", i18n::tr("\n清理完成"));
+    }
+    print_skipped(&report.scan);
+    Ok(())
+}
+
+pub fn run_graph(args: GraphArgs) -> Result<(), GtxError> {
+    let (dir_path, config) = &open_vault(args.dir)?;
+    let (_, vault) = scan_vault(Path::new(dir_path), config)?;
+
+    let text = match args.format {
+        GraphFormat::Dot if args.tags => graph::tags_to_dot(vault.indexes()),
+        GraphFormat::Dot => graph::to_dot(&vault),
+    };
+    write_or_print(args.output, &text)
+}
+
+pub fn run_export(args: ExportArgs) -> Result<(), GtxError> {
+    let (dir_path, mut config) = open_vault(args.dir)?;
+    if args.include_drafts {
+        config.include_drafts = Some(true);
+    }
+    let (_, vault) = scan_vault(Path::new(&dir_path), &config)?;
+
+    let text = match args.format {
+        ExportFormat::Json => export::to_json(&vault).to_pretty() + "\n",
+        ExportFormat::Csv => export::to_csv(&vault),
+        ExportFormat::Opml => export::to_opml(&vault),
+        ExportFormat::Anki => anki::to_tsv(&anki::collect(&vault)?),
+        ExportFormat::Html => {
+            // 解析参数时已保证 html 格式指定了输出目录
+            let site_dir = args.output.unwrap_or_default();
+            let pages = site::write_site(
+                &vault,
+                Path::new(&site_dir),
+                args.base_url.as_deref(),
+                args.robots,
+            )?;
+            if args.base_url.is_none() {
+                eprintln!(
+                    "{}",
+                    i18n::tr("警告: 没有用 --base-url 指定站点的绝对地址，未写入 sitemap.xml")
+                );
+            }
+            println!(
+                "{}",
+                i18n::trf("已导出 {} 个页面到 {}", &[&pages, &site_dir])
+            );
+            return Ok(());
+        }
+        ExportFormat::Epub => {
+            let chapters = epub::chapters(&vault, args.tag.as_deref())?;
+            if chapters.is_empty() {
+                return Err(GtxError::NotFound(match &args.tag {
+                    Some(tag) => i18n::trf("没有带有标签 '{}' 的笔记", &[tag]),
+                    None => i18n::tr("没有可以收入电子书的笔记").to_string(),
+                }));
+            }
+            let title = args.title.or(args.tag).unwrap_or_else(|| {
+                Path::new(&dir_path).file_name().map_or_else(
+                    || "gtx".to_string(),
+                    |name| name.to_string_lossy().into_owned(),
+                )
+            });
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_secs());
+            // 解析参数时已保证 epub 格式指定了输出文件
+            let path = args.output.unwrap_or_default();
+            fs::write(&path, epub::to_epub(&vault, &chapters, &title, now))?;
+            println!(
+                "{}",
+                i18n::trf("已导出 {} 章到 {}", &[&chapters.len(), &path])
+            );
+            return Ok(());
+        }
+        ExportFormat::Hugo | ExportFormat::Zola | ExportFormat::Jekyll => {
+            let generator = match args.format {
+                ExportFormat::Zola => Generator::Zola,
+                ExportFormat::Jekyll => Generator::Jekyll,
+                _ => Generator::Hugo,
+            };
+            let site_dir = args.output.unwrap_or_default();
+            let notes = ssg::write_content(&vault, Path::new(&site_dir), generator)?;
+            println!(
+                "{}",
+                i18n::trf("已导出 {} 篇笔记到 {}", &[&notes, &site_dir])
+            );
+            return Ok(());
+        }
+    };
+    write_or_print(args.output, &text)
+}
+
+pub fn run_hook(args: HookArgs) -> Result<(), GtxError> {
+    let (dir_path, _) = &open_vault(args.dir)?;
+    let kind = if args.post_commit {
+        HookKind::PostCommit
+    } else {
+        HookKind::PreCommit
+    };
+    let gtx = env::current_exe().map_err(|e| GtxError::io(i18n::tr("无法确定 gtx 的路径"), e))?;
+    let path = hook::install(Path::new(dir_path), kind, &gtx, args.force)
+        .map_err(|e| GtxError::io(i18n::trf("无法安装 {} 钩子", &[&kind.file_name()]), e))?;
+    println!("{}", i18n::trf("已安装: {}", &[&path.display()]));
+    Ok(())
+}
+
+// 指定文件时写入文件，否则输出到标准输出
+fn write_or_print(file: Option<String>, text: &str) -> Result<(), GtxError> {
+    match file {
+        Some(file) => {
+            fs::write(&file, text)?;
+            println!("{}", i18n::trf("已写入: {}", &[&file]));
+        }
+        None => print!("{}", text),
+    }
+    Ok(())
+}
+
+// 文件修改时间的本地日期与时间，格式为 ("YYYYMMDD", "HH:MM")，见 `date::local`
+fn file_datetime(path: &Path) -> io::Result<(String, String)> {
+    let (date, time) = date::local(fs::metadata(path)?.modified()?);
+    Ok((date.to_string(), time.to_string()))
+}
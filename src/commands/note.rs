@@ -0,0 +1,193 @@
+// 新建笔记的子命令：new 与 today
+
+use std::collections::HashSet;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::GtxError;
+use crate::cli::{NewArgs, TodayArgs};
+use crate::date::{self, Date, Time};
+use crate::i18n;
+use crate::json::Json;
+use crate::note::{NEED_TAG, timestamp_id};
+use crate::output::TEMPLATES_DIR;
+use crate::template::Template;
+
+use super::{frontmatter_keys, launch_editor, open_vault};
+
+pub fn run_new(args: NewArgs) -> Result<(), GtxError> {
+    let (dir_path, config) = &open_vault(args.dir)?;
+    let keys = frontmatter_keys(config);
+    let (date, time) = date::now();
+
+    let id = args
+        .zettel
+        .then(|| zettel_id(Path::new(dir_path), date, time))
+        .transpose()
+        .map_err(|e| GtxError::io(i18n::trf("无法读取目录 '{}'", &[&dir_path]), e))?;
+    let name = match (args.name, &id) {
+        (Some(name), _) => name,
+        (None, Some(id)) => format!("{} {}", id, args.title),
+        (None, None) => args.title.clone(),
+    };
+    // 文件名中不能出现路径分隔符
+    let name = name.replace(['/', '\\'], "-");
+    let note_path = Path::new(dir_path).join(format!("{}.md", name));
+
+    // create_new 在文件已存在时失败，检查与创建之间不会覆盖别处刚写入的同名笔记
+    let context = i18n::trf("无法创建笔记 '{}'", &[&note_path.display()]);
+    let file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&note_path)
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::AlreadyExists => GtxError::io(
+                context,
+                io::Error::new(io::ErrorKind::AlreadyExists, i18n::tr("文件已存在")),
+            ),
+            _ => GtxError::io(context, e),
+        })?;
+    let mut writer = BufWriter::new(file);
+    writeln!(writer, "---")?;
+    writeln!(writer, "{}: {}", keys.title, args.title)?;
+    if let Some(id) = &id {
+        writeln!(writer, "{}: {}", keys.id, id)?;
+    }
+    writeln!(writer, "{}: {} {}", keys.updated, date, time)?;
+    writeln!(writer, "{}: {} {}", keys.created, date, time)?;
+    // 没有指定标签时写入 NeedTag，与索引中未分类的笔记一致，doctor 不会报告缺少标签
+    let tags = if args.tags.is_empty() {
+        NEED_TAG.to_string()
+    } else {
+        args.tags.join(" ")
+    };
+    writeln!(writer, "{}: {}", keys.tags, tags)?;
+    writeln!(writer, "---")?;
+    writer.flush()?;
+
+    println!("{}", i18n::trf("已创建: {}", &[&note_path.display()]));
+    Ok(())
+}
+
+// 由当前日期与时间生成的卡片盒 ID；目录中已有文件名以该 ID 开头的笔记时依次加一
+fn zettel_id(dir: &Path, date: Date, time: Time) -> io::Result<String> {
+    let mut taken = HashSet::new();
+    for entry in fs::read_dir(dir)? {
+        if let Some(id) = entry?.file_name().to_str().and_then(timestamp_id) {
+            taken.insert(id);
+        }
+    }
+    Ok(free_id(&taken, date, time))
+}
+
+// 从 date time 起第一个未被占用的 YYYYMMDDHHMM；按分钟递增，进位到小时、日期与年份
+fn free_id(taken: &HashSet<String>, date: Date, time: Time) -> String {
+    let mut secs = date::to_timestamp(date, Some(time));
+    loop {
+        let (date, time) = date::from_timestamp(secs);
+        let id = format!("{}{:02}{:02}", date, time.hour, time.minute);
+        if !taken.contains(&id) {
+            return id;
+        }
+        secs += 60;
+    }
+}
+
+// 日记的标签与模板文件名，模板位于 TEMPLATES_DIR 下
+const JOURNAL_TAG: &str = "journal";
+
+const JOURNAL_TEMPLATE: &str = "journal.md";
+
+pub fn run_today(args: TodayArgs) -> Result<(), GtxError> {
+    let (dir_path, config) = &open_vault(args.dir)?;
+    let keys = frontmatter_keys(config);
+
+    // 文件名为 YYYY-MM-DD，不会与 YYYYMMDD 的日期页和 YYYY-MM 的月汇总页重名
+    let (date, time) = current_datetime();
+    let title = format!("{}-{}-{}", &date[..4], &date[4..6], &date[6..]);
+    let note_path = Path::new(dir_path).join(format!("{}.md", title));
+
+    if !note_path.exists() {
+        let frontmatter = format!(
+            "---\n{}: {}\n{}: {} {}\n{}: {} {}\n{}: {}\n---\n",
+            keys.title,
+            title,
+            keys.updated,
+            date,
+            time,
+            keys.created,
+            date,
+            time,
+            keys.tags,
+            JOURNAL_TAG
+        );
+        let template_path = Path::new(dir_path)
+            .join(TEMPLATES_DIR)
+            .join(JOURNAL_TEMPLATE);
+        let content = match fs::read_to_string(&template_path) {
+            Ok(text) => {
+                let template_error = |e| {
+                    GtxError::Parse(i18n::trf(
+                        "模板 {} 解析失败，{}",
+                        &[&template_path.display(), &e],
+                    ))
+                };
+                let context = Json::Object(vec![
+                    ("frontmatter".to_string(), frontmatter.into()),
+                    ("title".to_string(), title.as_str().into()),
+                    ("date".to_string(), date.as_str().into()),
+                    ("time".to_string(), time.as_str().into()),
+                ]);
+                Template::parse(&text)
+                    .and_then(|template| template.render(&context))
+                    .map_err(template_error)?
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => frontmatter,
+            Err(e) => {
+                return Err(GtxError::io(
+                    i18n::trf("无法读取模板 '{}'", &[&template_path.display()]),
+                    e,
+                ));
+            }
+        };
+        let context = i18n::trf("无法创建笔记 '{}'", &[&note_path.display()]);
+        let mut file = File::create_new(&note_path).map_err(|e| GtxError::io(&context, e))?;
+        file.write_all(content.as_bytes())
+            .map_err(|e| GtxError::io(context, e))?;
+        println!("{}", i18n::trf("已创建: {}", &[&note_path.display()]));
+    }
+
+    if args.print {
+        println!("{}", note_path.display());
+        return Ok(());
+    }
+    launch_editor(&note_path)
+}
+
+// 当前的本地日期与时间，格式为 ("YYYYMMDD", "HH:MM")，见 `date::now`
+fn current_datetime() -> (String, String) {
+    let (date, time) = date::now();
+    (date.to_string(), time.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn free_id_carries_into_hour_day_and_year() {
+        let taken: HashSet<String> = ["202312312359", "202401011159", "202401011201"]
+            .iter()
+            .map(|id| id.to_string())
+            .collect();
+        let at = |y, mo, d, h, mi| (Date::new(y, mo, d).unwrap(), Time::new(h, mi).unwrap());
+
+        let (date, time) = at(2024, 1, 1, 11, 58);
+        assert_eq!(free_id(&taken, date, time), "202401011158");
+        let (date, time) = at(2024, 1, 1, 11, 59);
+        assert_eq!(free_id(&taken, date, time), "202401011200");
+        let (date, time) = at(2023, 12, 31, 23, 59);
+        assert_eq!(free_id(&taken, date, time), "202401010000");
+    }
+}
@@ -0,0 +1,826 @@
+// 配置文件：博客目录下的 gtx.toml 与用户目录下的 ~/.config/gtx/config.toml
+//
+// 只实现 TOML 的一个子集：
+//   - `键 = 值`，值为字符串（"..." 或 '...'）、整数、布尔值或字符串数组
+//   - `[表名]` 形式的表头，键可以加引号
+//   - `#` 开头的注释与空行
+//
+// 示例:
+//   vault = "~/notes"       # 只在用户配置中有效
+//   output = "_gtx"
+//   columns = "auto"        # 或具体数字，tag_columns / date_columns 可分别设置
+//   link_style = "markdown" # 或 "wiki"、"obsidian"、"org"
+//   backend = "sqlite"      # 或 "memory"
+//   exclude = ["draft-*.md", "README.md"]
+//   tag_sort = "created-desc" # 或 "created"、"title"、"modified"
+//   time_format = "12h"     # 或 "24h"
+//   lang = "en"             # 输出语言，或 "zh"；环境变量 GTX_LANG 优先
+//   mermaid = 20            # index.md 中嵌入链接最多的 20 篇笔记的 Mermaid 关系图
+//   hubs = 10               # index.md 中列出 PageRank 中心度最高的 10 篇笔记
+//   excerpt = 80            # 标签页与日期页中每篇笔记下显示至多 80 字的摘要
+//   org = true              # 同时索引 .org 笔记
+//   dates_from = "git"      # 没有 Created 的笔记按 git 历史补全时间，或 "frontmatter"
+//   with_search = true      # 同时更新 .gtx/search 下的全文搜索索引
+//   attachments = "assets"  # gtx attachments 检查的附件目录，相对于博客目录
+//   include_drafts = true   # 草稿照常出现在标签页、日期页与 index.md 中
+//   tag_dir = "topics"      # 标签页所在的目录，默认 "tags"，为空时与笔记放在一起
+//   follow_symlinks = false # 跳过符号链接，默认跟随
+//   az_groups = "block"     # a-z.md 中非拉丁字母的标题按 Unicode 区块分组，或 "letter"
+//
+//   [tag_sort]              # 单个标签的顺序，作用于其下的层级标签
+//   "project/gtx" = "title"
+//
+//   [vault.work]            # 命名的博客目录，gtx --vault work 使用，只在用户配置中有效
+//   path = "~/work-notes"   # 其余键与顶层相同，覆盖顶层的设置
+//   link_style = "obsidian"
+//
+//   [vault.work.frontmatter] # 该博客目录的键名与标签顺序，写法同下
+//   title = "title"
+//
+//   [frontmatter]            # 数组中的第一个键名用于写入，读取时依次尝试
+//   title = "title"
+//   created = ["created_at", "Created"]
+//   updated = "modified"
+//   tags = "tags"
+//   aliases = "aliases"
+//   id = "uid"
+//   draft = "draft"
+//   pinned = "pinned"
+//   status = "state"
+//   summary = "description"
+//   review = "next_review"
+
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::build::Backend;
+use crate::format::Columns;
+use crate::i18n::{self, Lang};
+use crate::output::{LinkStyle, TagSort, TimeFormat, TitleGroups};
+use crate::vault::DateSource;
+
+/// 博客目录下配置文件的文件名
+pub const VAULT_CONFIG: &str = "gtx.toml";
+
+/// 配置文件的内容，未设置的项为 None
+#[derive(Debug, Default, Clone)]
+pub struct Config {
+    /// 默认的博客目录，只在用户配置中有效
+    pub vault: Option<PathBuf>,
+    /// [vault.名称] 表中命名的博客目录及其选项，vault 为 path 的值；只在用户配置中有效
+    pub profiles: Vec<(String, Config)>,
+    pub output: Option<PathBuf>,
+    pub tag_columns: Option<Columns>,
+    pub date_columns: Option<Columns>,
+    pub link_style: Option<LinkStyle>,
+    pub backend: Option<Backend>,
+    /// 标签页的默认顺序
+    pub tag_sort: Option<TagSort>,
+    /// [tag_sort] 表中单个标签的顺序
+    pub tag_sorts: Vec<(String, TagSort)>,
+    pub time_format: Option<TimeFormat>,
+    /// a-z.md 中非拉丁字母标题的分组方式
+    pub az_groups: Option<TitleGroups>,
+    /// 输出语言
+    pub lang: Option<Lang>,
+    /// index.md 中 Mermaid 关系图包含的笔记数
+    pub mermaid: Option<usize>,
+    /// index.md 中列出的中心笔记数
+    pub hubs: Option<usize>,
+    /// 标签页与日期页中摘要的最大字符数
+    pub excerpt: Option<usize>,
+    /// 是否同时索引 .org 笔记
+    pub org: Option<bool>,
+    /// 没有 Created 字段的笔记的时间来源
+    pub dates_from: Option<DateSource>,
+    /// 是否同时更新全文搜索索引
+    pub with_search: Option<bool>,
+    pub attachments: Option<String>,
+    pub include_drafts: Option<bool>,
+    /// 标签页所在的目录，相对输出目录，首尾的 / 已去掉
+    pub tag_dir: Option<String>,
+    /// 是否跟随指向笔记与附件目录的符号链接
+    pub follow_symlinks: Option<bool>,
+    /// 不参与索引的笔记文件名通配符
+    pub exclude: Vec<String>,
+    /// [frontmatter] 表中的键名，第一个用于写入
+    pub title_key: Option<Vec<String>>,
+    pub created_key: Option<Vec<String>>,
+    pub updated_key: Option<Vec<String>>,
+    pub tags_key: Option<Vec<String>>,
+    pub aliases_key: Option<Vec<String>>,
+    pub id_key: Option<Vec<String>>,
+    pub draft_key: Option<Vec<String>>,
+    pub pinned_key: Option<Vec<String>>,
+    pub status_key: Option<Vec<String>>,
+    pub summary_key: Option<Vec<String>>,
+    pub review_key: Option<Vec<String>>,
+}
+
+#[derive(Debug)]
+pub struct Error {
+    pub path: PathBuf,
+    /// 出错的行号，从 1 开始；为 0 时表示读取文件失败
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.line == 0 {
+            f.write_str(&i18n::trf(
+                "无法读取配置文件 {}: {}",
+                &[&self.path.display(), &self.message],
+            ))
+        } else {
+            f.write_str(&i18n::trf(
+                "配置文件 {} 第 {} 行: {}",
+                &[&self.path.display(), &self.line, &self.message],
+            ))
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// 指定博客目录的环境变量，优先于用户配置中的 vault
+pub const VAULT_ENV: &str = "GTX_VAULT";
+
+/// 没有指定博客目录时使用用户主目录下的这个目录
+pub const DEFAULT_VAULT_DIR: &str = ".data";
+
+/// 用户主目录：$HOME，Windows 上没有 HOME 时取 %USERPROFILE% 或 %HOMEDRIVE%%HOMEPATH%
+pub fn home_dir() -> Option<PathBuf> {
+    let var = |name: &str| env::var_os(name).filter(|value| !value.is_empty());
+    if let Some(home) = var("HOME").or_else(|| var("USERPROFILE")) {
+        return Some(PathBuf::from(home));
+    }
+    let mut home = var("HOMEDRIVE")?;
+    home.push(var("HOMEPATH")?);
+    Some(PathBuf::from(home))
+}
+
+/// 环境变量 GTX_VAULT 指定的博客目录，未设置或为空时返回 None
+pub fn env_vault() -> Option<PathBuf> {
+    env::var_os(VAULT_ENV)
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+}
+
+/// 用户配置文件的位置：$XDG_CONFIG_HOME/gtx/config.toml 或 ~/.config/gtx/config.toml
+pub fn user_config_path() -> Option<PathBuf> {
+    let base = match env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => home_dir()?.join(".config"),
+    };
+    Some(base.join("gtx").join("config.toml"))
+}
+
+impl Config {
+    /// 读取配置文件，文件不存在时返回 None；user 为 false 时为博客目录中的 gtx.toml，见 `parse`
+    ///
+    /// 配置中的相对路径以配置文件所在目录为基准
+    pub fn load(path: &Path, user: bool) -> Result<Option<Config>, Error> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => {
+                return Err(Error {
+                    path: path.to_path_buf(),
+                    line: 0,
+                    message: e.to_string(),
+                });
+            }
+        };
+        let base = path.parent().unwrap_or(Path::new("."));
+        Config::parse(&text, base, user)
+            .map(Some)
+            .map_err(|(line, message)| Error {
+                path: path.to_path_buf(),
+                line,
+                message,
+            })
+    }
+
+    /// 解析配置文本，失败时返回（行号，错误信息）
+    ///
+    /// vault 与 [vault.名称] 只在用户配置中有效，user 为 false 时出现即报错
+    pub fn parse(text: &str, base: &Path, user: bool) -> Result<Config, (usize, String)> {
+        let mut config = Config::default();
+        let mut table = String::new();
+        let mut lines = text.lines().enumerate();
+
+        while let Some((index, raw)) = lines.next() {
+            let line_no = index + 1;
+            let line = strip_comment(raw).trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(name) = line.strip_prefix('[') {
+                let name = name
+                    .strip_suffix(']')
+                    .ok_or((line_no, i18n::tr("表头缺少 ']'").to_string()))?;
+                table = name.trim().to_string();
+                let known = match profile_table(&table) {
+                    Some((name, sub)) => {
+                        !name.is_empty() && matches!(sub, "" | "frontmatter" | "tag_sort")
+                    }
+                    None => table == "frontmatter" || table == "tag_sort",
+                };
+                if !known {
+                    return Err((line_no, i18n::trf("未知的表 [{}]", &[&table])));
+                }
+                if !user && profile_table(&table).is_some() {
+                    return Err((line_no, i18n::trf("[{}] 只能写在用户配置中", &[&table])));
+                }
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or((line_no, i18n::tr("应为 `键 = 值`").to_string()))?;
+            let key = unquote_key(key.trim());
+            if !user && table.is_empty() && key == "vault" {
+                return Err((line_no, i18n::tr("vault 只能写在用户配置中").to_string()));
+            }
+            let mut value = value.trim().to_string();
+
+            // 数组可以跨行书写
+            if value.starts_with('[') {
+                while !array_closed(&value) {
+                    let (_, next) = lines
+                        .next()
+                        .ok_or((line_no, i18n::tr("数组缺少 ']'").to_string()))?;
+                    value.push(' ');
+                    value.push_str(strip_comment(next).trim());
+                }
+            }
+
+            let value = parse_value(&value).map_err(|message| (line_no, message))?;
+            config
+                .set(&table, key, value, base)
+                .map_err(|message| (line_no, message))?;
+        }
+
+        Ok(config)
+    }
+
+    fn set(&mut self, table: &str, key: &str, value: TomlValue, base: &Path) -> Result<(), String> {
+        if let Some((name, sub)) = profile_table(table) {
+            let profile = self.profile_mut(name);
+            return match (sub, key) {
+                ("", "path") => {
+                    profile.vault = Some(resolve_path(base, &value.string(key)?));
+                    Ok(())
+                }
+                _ => profile.set(sub, key, value, base),
+            };
+        }
+        match (table, key) {
+            ("", "vault") => self.vault = Some(resolve_path(base, &value.string(key)?)),
+            ("", "output") => self.output = Some(resolve_path(base, &value.string(key)?)),
+            ("", "columns") => {
+                let columns = value.columns(key)?;
+                self.tag_columns = Some(columns);
+                self.date_columns = Some(columns);
+            }
+            ("", "tag_columns") => self.tag_columns = Some(value.columns(key)?),
+            ("", "date_columns") => self.date_columns = Some(value.columns(key)?),
+            ("", "link_style") => {
+                let name = value.string(key)?;
+                self.link_style = Some(LinkStyle::from_name(&name).ok_or_else(|| {
+                    i18n::trf("link_style 应为 \"wiki\"、\"markdown\"、\"obsidian\" 或 \"org\"，而不是 \"{}\"", &[&name])
+                })?);
+            }
+            ("", "backend") => {
+                let name = value.string(key)?;
+                self.backend = Some(Backend::from_name(&name).ok_or_else(|| {
+                    i18n::trf(
+                        "backend 应为 \"memory\" 或 \"sqlite\"，而不是 \"{}\"",
+                        &[&name],
+                    )
+                })?);
+            }
+            ("", "exclude") => self.exclude = value.strings(key)?,
+            ("", "tag_sort") => self.tag_sort = Some(value.tag_sort(key)?),
+            ("", "time_format") => {
+                let name = value.string(key)?;
+                self.time_format = Some(TimeFormat::from_name(&name).ok_or_else(|| {
+                    i18n::trf(
+                        "time_format 应为 \"24h\" 或 \"12h\"，而不是 \"{}\"",
+                        &[&name],
+                    )
+                })?);
+            }
+            ("", "lang") => {
+                let name = value.string(key)?;
+                self.lang = Some(Lang::parse(&name).ok_or_else(|| {
+                    i18n::trf("lang 应为 \"zh\" 或 \"en\"，而不是 \"{}\"", &[&name])
+                })?);
+            }
+            ("", "az_groups") => {
+                let name = value.string(key)?;
+                self.az_groups = Some(TitleGroups::from_name(&name).ok_or_else(|| {
+                    i18n::trf(
+                        "az_groups 应为 \"letter\" 或 \"block\"，而不是 \"{}\"",
+                        &[&name],
+                    )
+                })?);
+            }
+            ("", "mermaid") => self.mermaid = Some(value.count(key)?),
+            ("", "hubs") => self.hubs = Some(value.count(key)?),
+            ("", "excerpt") => self.excerpt = Some(value.count(key)?),
+            ("", "org") => self.org = Some(value.boolean(key)?),
+            ("", "dates_from") => {
+                let name = value.string(key)?;
+                self.dates_from = Some(DateSource::from_name(&name).ok_or_else(|| {
+                    i18n::trf(
+                        "dates_from 应为 \"frontmatter\" 或 \"git\"，而不是 \"{}\"",
+                        &[&name],
+                    )
+                })?);
+            }
+            ("", "with_search") => self.with_search = Some(value.boolean(key)?),
+            ("", "attachments") => self.attachments = Some(value.string(key)?),
+            ("", "include_drafts") => self.include_drafts = Some(value.boolean(key)?),
+            ("", "tag_dir") => {
+                self.tag_dir = Some(value.string(key)?.trim_matches('/').to_string())
+            }
+            ("", "follow_symlinks") => self.follow_symlinks = Some(value.boolean(key)?),
+            ("tag_sort", tag) => {
+                let sort = value.tag_sort(key)?;
+                self.tag_sorts.push((tag.to_string(), sort));
+            }
+            ("frontmatter", "title") => self.title_key = Some(value.key_names(key)?),
+            ("frontmatter", "created") => self.created_key = Some(value.key_names(key)?),
+            ("frontmatter", "updated") => self.updated_key = Some(value.key_names(key)?),
+            ("frontmatter", "tags") => self.tags_key = Some(value.key_names(key)?),
+            ("frontmatter", "aliases") => self.aliases_key = Some(value.key_names(key)?),
+            ("frontmatter", "id") => self.id_key = Some(value.key_names(key)?),
+            ("frontmatter", "draft") => self.draft_key = Some(value.key_names(key)?),
+            ("frontmatter", "pinned") => self.pinned_key = Some(value.key_names(key)?),
+            ("frontmatter", "status") => self.status_key = Some(value.key_names(key)?),
+            ("frontmatter", "summary") => self.summary_key = Some(value.key_names(key)?),
+            ("frontmatter", "review") => self.review_key = Some(value.key_names(key)?),
+            ("", _) => return Err(i18n::trf("未知的配置项 '{}'", &[&key])),
+            (_, _) => return Err(i18n::trf("未知的配置项 '{}.{}'", &[&table, &key])),
+        }
+        Ok(())
+    }
+
+    /// 名为 name 的博客目录的配置
+    pub fn profile(&self, name: &str) -> Option<&Config> {
+        self.profiles
+            .iter()
+            .find(|(profile, _)| profile == name)
+            .map(|(_, config)| config)
+    }
+
+    // 名为 name 的博客目录的配置，不存在时新建
+    fn profile_mut(&mut self, name: &str) -> &mut Config {
+        let index = match self
+            .profiles
+            .iter()
+            .position(|(profile, _)| profile == name)
+        {
+            Some(index) => index,
+            None => {
+                self.profiles.push((name.to_string(), Config::default()));
+                self.profiles.len() - 1
+            }
+        };
+        &mut self.profiles[index].1
+    }
+
+    /// 以 other 中设置了的项覆盖当前配置，exclude 合并
+    pub fn merge(mut self, other: Config) -> Config {
+        self.vault = other.vault.or(self.vault);
+        self.profiles.extend(other.profiles);
+        self.output = other.output.or(self.output);
+        self.tag_columns = other.tag_columns.or(self.tag_columns);
+        self.date_columns = other.date_columns.or(self.date_columns);
+        self.link_style = other.link_style.or(self.link_style);
+        self.backend = other.backend.or(self.backend);
+        self.tag_sort = other.tag_sort.or(self.tag_sort);
+        self.tag_sorts.extend(other.tag_sorts);
+        self.time_format = other.time_format.or(self.time_format);
+        self.az_groups = other.az_groups.or(self.az_groups);
+        self.lang = other.lang.or(self.lang);
+        self.mermaid = other.mermaid.or(self.mermaid);
+        self.hubs = other.hubs.or(self.hubs);
+        self.excerpt = other.excerpt.or(self.excerpt);
+        self.org = other.org.or(self.org);
+        self.dates_from = other.dates_from.or(self.dates_from);
+        self.with_search = other.with_search.or(self.with_search);
+        self.attachments = other.attachments.or(self.attachments);
+        self.include_drafts = other.include_drafts.or(self.include_drafts);
+        self.tag_dir = other.tag_dir.or(self.tag_dir);
+        self.follow_symlinks = other.follow_symlinks.or(self.follow_symlinks);
+        self.exclude.extend(other.exclude);
+        self.title_key = other.title_key.or(self.title_key);
+        self.created_key = other.created_key.or(self.created_key);
+        self.updated_key = other.updated_key.or(self.updated_key);
+        self.tags_key = other.tags_key.or(self.tags_key);
+        self.aliases_key = other.aliases_key.or(self.aliases_key);
+        self.id_key = other.id_key.or(self.id_key);
+        self.draft_key = other.draft_key.or(self.draft_key);
+        self.pinned_key = other.pinned_key.or(self.pinned_key);
+        self.status_key = other.status_key.or(self.status_key);
+        self.summary_key = other.summary_key.or(self.summary_key);
+        self.review_key = other.review_key.or(self.review_key);
+        self
+    }
+}
+
+// [vault.名称] 与 [vault.名称.表名] 形式的表头，返回（名称，表名）；表名为空表示顶层的键
+fn profile_table(table: &str) -> Option<(&str, &str)> {
+    let rest = table.strip_prefix("vault.")?;
+    Some(rest.split_once('.').unwrap_or((rest, "")))
+}
+
+// 去掉键两侧的引号，层级标签等含 / 的键需要加引号
+fn unquote_key(key: &str) -> &str {
+    match key.chars().next() {
+        Some(q @ ('"' | '\'')) if key.len() >= 2 && key.ends_with(q) => &key[1..key.len() - 1],
+        _ => key,
+    }
+}
+
+#[derive(Debug)]
+enum TomlValue {
+    String(String),
+    Integer(i64),
+    Boolean(bool),
+    Array(Vec<TomlValue>),
+}
+
+impl TomlValue {
+    fn type_name(&self) -> &'static str {
+        match self {
+            TomlValue::String(_) => i18n::tr("字符串"),
+            TomlValue::Integer(_) => i18n::tr("整数"),
+            TomlValue::Boolean(_) => i18n::tr("布尔值"),
+            TomlValue::Array(_) => i18n::tr("数组"),
+        }
+    }
+
+    fn string(self, key: &str) -> Result<String, String> {
+        match self {
+            TomlValue::String(s) => Ok(s),
+            other => Err(i18n::trf(
+                "{} 应为字符串，而不是{}",
+                &[&key, &other.type_name()],
+            )),
+        }
+    }
+
+    fn strings(self, key: &str) -> Result<Vec<String>, String> {
+        match self {
+            TomlValue::Array(items) => items.into_iter().map(|item| item.string(key)).collect(),
+            other => Err(i18n::trf(
+                "{} 应为字符串数组，而不是{}",
+                &[&key, &other.type_name()],
+            )),
+        }
+    }
+
+    // 字符串或非空的字符串数组
+    fn key_names(self, key: &str) -> Result<Vec<String>, String> {
+        match self {
+            TomlValue::String(s) => Ok(vec![s]),
+            TomlValue::Array(items) if !items.is_empty() => {
+                items.into_iter().map(|item| item.string(key)).collect()
+            }
+            TomlValue::Array(_) => Err(i18n::trf("{} 不能为空数组", &[&key])),
+            other => Err(i18n::trf(
+                "{} 应为字符串或字符串数组，而不是{}",
+                &[&key, &other.type_name()],
+            )),
+        }
+    }
+
+    fn tag_sort(self, key: &str) -> Result<TagSort, String> {
+        let name = self.string(key)?;
+        TagSort::from_name(&name).ok_or_else(|| {
+            i18n::trf(
+                "{} 应为 \"created\"、\"created-desc\"、\"title\" 或 \"modified\"，而不是 \"{}\"",
+                &[&key, &name],
+            )
+        })
+    }
+
+    fn boolean(self, key: &str) -> Result<bool, String> {
+        match self {
+            TomlValue::Boolean(b) => Ok(b),
+            other => Err(i18n::trf(
+                "{} 应为 true 或 false，而不是{}",
+                &[&key, &other.type_name()],
+            )),
+        }
+    }
+
+    // 非负整数
+    fn count(self, key: &str) -> Result<usize, String> {
+        match self {
+            TomlValue::Integer(n) if n >= 0 => Ok(n as usize),
+            TomlValue::Integer(n) => Err(i18n::trf("{} 应为非负整数，而不是 {}", &[&key, &n])),
+            other => Err(i18n::trf(
+                "{} 应为非负整数，而不是{}",
+                &[&key, &other.type_name()],
+            )),
+        }
+    }
+
+    // 正整数或 "auto"
+    fn columns(self, key: &str) -> Result<Columns, String> {
+        match self {
+            TomlValue::Integer(n) if n > 0 => Ok(Columns::Fixed(n as usize)),
+            TomlValue::String(s) if s == "auto" => Ok(Columns::auto()),
+            TomlValue::Integer(n) => Err(i18n::trf(
+                "{} 应为正整数或 \"auto\"，而不是 {}",
+                &[&key, &n],
+            )),
+            TomlValue::String(s) => Err(i18n::trf(
+                "{} 应为正整数或 \"auto\"，而不是 \"{}\"",
+                &[&key, &s],
+            )),
+            other => Err(i18n::trf(
+                "{} 应为正整数或 \"auto\"，而不是{}",
+                &[&key, &other.type_name()],
+            )),
+        }
+    }
+}
+
+// 去掉不在字符串内的 `#` 注释
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match quote {
+            Some('"') if escaped => escaped = false,
+            Some('"') if c == '\\' => escaped = true,
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '#' => return &line[..i],
+            None => {}
+        }
+    }
+    line
+}
+
+// 方括号是否已配对（忽略字符串中的括号）
+fn array_closed(value: &str) -> bool {
+    let mut depth = 0i32;
+    let mut quote = None;
+    for c in value.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '[' => depth += 1,
+            None if c == ']' => depth -= 1,
+            None => {}
+        }
+    }
+    depth <= 0
+}
+
+fn parse_value(text: &str) -> Result<TomlValue, String> {
+    let mut chars = text.chars().peekable();
+    let value = parse_item(&mut chars)?;
+    if chars.any(|c| !c.is_whitespace()) {
+        return Err(i18n::trf("无法解析的值 '{}'", &[&text]));
+    }
+    Ok(value)
+}
+
+fn parse_item(chars: &mut std::iter::Peekable<std::str::Chars>) -> Result<TomlValue, String> {
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+    match chars.peek().copied() {
+        None => Err(i18n::tr("缺少值").to_string()),
+        Some('"') => {
+            chars.next();
+            let mut s = String::new();
+            loop {
+                match chars.next() {
+                    None => return Err(i18n::tr("字符串缺少结尾的 '\"'").to_string()),
+                    Some('"') => return Ok(TomlValue::String(s)),
+                    Some('\\') => match chars.next() {
+                        Some('n') => s.push('\n'),
+                        Some('t') => s.push('\t'),
+                        Some('\\') => s.push('\\'),
+                        Some('"') => s.push('"'),
+                        Some(c) => return Err(i18n::trf("不支持的转义 '\\{}'", &[&c])),
+                        None => return Err(i18n::tr("字符串缺少结尾的 '\"'").to_string()),
+                    },
+                    Some(c) => s.push(c),
+                }
+            }
+        }
+        Some('\'') => {
+            chars.next();
+            let mut s = String::new();
+            loop {
+                match chars.next() {
+                    None => return Err(i18n::tr("字符串缺少结尾的 \"'\"").to_string()),
+                    Some('\'') => return Ok(TomlValue::String(s)),
+                    Some(c) => s.push(c),
+                }
+            }
+        }
+        Some('[') => {
+            chars.next();
+            let mut items = Vec::new();
+            loop {
+                while chars.next_if(|c| c.is_whitespace()).is_some() {}
+                if chars.next_if_eq(&']').is_some() {
+                    return Ok(TomlValue::Array(items));
+                }
+                items.push(parse_item(chars)?);
+                while chars.next_if(|c| c.is_whitespace()).is_some() {}
+                match chars.next() {
+                    Some(',') => {}
+                    Some(']') => return Ok(TomlValue::Array(items)),
+                    _ => return Err(i18n::tr("数组中缺少 ',' 或 ']'").to_string()),
+                }
+            }
+        }
+        Some(_) => {
+            let mut word = String::new();
+            while let Some(c) = chars.next_if(|c| !c.is_whitespace() && *c != ',' && *c != ']') {
+                word.push(c);
+            }
+            match word.as_str() {
+                "true" => return Ok(TomlValue::Boolean(true)),
+                "false" => return Ok(TomlValue::Boolean(false)),
+                _ => {}
+            }
+            word.replace('_', "")
+                .parse()
+                .map(TomlValue::Integer)
+                .map_err(|_| i18n::trf("无法解析的值 '{}'", &[&word]))
+        }
+    }
+}
+
+// 展开开头的 ~（Windows 上也接受 ~\），相对路径以 base 为基准
+fn resolve_path(base: &Path, path: &str) -> PathBuf {
+    if let Some(rest) = path.strip_prefix('~')
+        && (rest.is_empty() || rest.starts_with('/') || rest.starts_with('\\'))
+        && let Some(home) = home_dir()
+    {
+        return home.join(rest.trim_start_matches(['/', '\\']));
+    }
+    base.join(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> Config {
+        Config::parse(text, Path::new("/base"), true).unwrap()
+    }
+
+    fn error(text: &str) -> (usize, String) {
+        Config::parse(text, Path::new("/base"), true).unwrap_err()
+    }
+
+    #[test]
+    fn strings_and_escapes() {
+        let config = parse(
+            "attachments = \"a\\\\b \\\"c\\\" # 不是注释\\t\\n\"\n\
+             tag_dir = '/C:\\raw/' # 注释\n\
+             output = \"out\"\n",
+        );
+        assert_eq!(
+            config.attachments.as_deref(),
+            Some("a\\b \"c\" # 不是注释\t\n")
+        );
+        assert_eq!(config.tag_dir.as_deref(), Some("C:\\raw"));
+        assert_eq!(config.output, Some(PathBuf::from("/base/out")));
+        assert_eq!(error("attachments = \"a\\x\"").0, 1);
+        assert_eq!(error("\nattachments = \"a").0, 2);
+        assert_eq!(error("attachments = 'a' b").0, 1);
+    }
+
+    #[test]
+    fn arrays_integers_and_booleans() {
+        let config = parse(
+            "exclude = [\"a-*.md\", 'b, ]c',\n  \"d\", # 注释\n]\n\
+             mermaid = 1_000\n\
+             org = true\n\
+             columns = 3\n",
+        );
+        assert_eq!(config.exclude, ["a-*.md", "b, ]c", "d"]);
+        assert_eq!(config.mermaid, Some(1000));
+        assert_eq!(config.org, Some(true));
+        assert_eq!(config.tag_columns, Some(Columns::Fixed(3)));
+        assert_eq!(config.date_columns, Some(Columns::Fixed(3)));
+        assert!(parse("exclude = []").exclude.is_empty());
+
+        assert_eq!(error("exclude = [\"a\"\n").0, 1);
+        assert_eq!(error("exclude = [\"a\" \"b\"]").0, 1);
+        assert_eq!(error("exclude = [1]").0, 1);
+        assert_eq!(error("mermaid = -1").0, 1);
+        assert_eq!(error("org = \"yes\"").0, 1);
+        assert_eq!(error("columns = 0").0, 1);
+    }
+
+    #[test]
+    fn tables() {
+        let config = parse(
+            "link_style = \"markdown\"\n\
+             [frontmatter]\n\
+             created = [\"date\", \"Created\"]\n\
+             tags = 'keywords'\n\
+             [tag_sort]\n\
+             \"project/gtx\" = \"title\"\n",
+        );
+        assert_eq!(config.link_style, Some(LinkStyle::Markdown));
+        assert_eq!(
+            config.created_key,
+            Some(vec!["date".to_string(), "Created".to_string()])
+        );
+        assert_eq!(config.tags_key, Some(vec!["keywords".to_string()]));
+        assert_eq!(
+            config.tag_sorts,
+            [("project/gtx".to_string(), TagSort::Title)]
+        );
+
+        assert_eq!(error("[frontmatter\n").0, 1);
+        assert_eq!(error("org = true\n[unknown]\n").0, 2);
+        assert_eq!(error("[frontmatter]\ncolor = \"x\"").0, 2);
+        assert_eq!(error("[frontmatter]\ntitle = []").0, 2);
+        assert_eq!(error("colour = 1").0, 1);
+        assert_eq!(error("no value").0, 1);
+    }
+
+    #[test]
+    fn vault_profiles() {
+        let config = parse(
+            "vault = \"notes\"\n\
+             [vault.work]\n\
+             path = \"work\"\n\
+             link_style = \"obsidian\"\n\
+             [vault.work.frontmatter]\n\
+             title = \"name\"\n\
+             [vault.\"my diary\"]\n\
+             path = \"diary\"\n",
+        );
+        assert_eq!(config.vault, Some(PathBuf::from("/base/notes")));
+        assert_eq!(config.link_style, None);
+        let work = config.profile("work").unwrap();
+        assert_eq!(work.vault, Some(PathBuf::from("/base/work")));
+        assert_eq!(work.link_style, Some(LinkStyle::Obsidian));
+        assert_eq!(work.title_key, Some(vec!["name".to_string()]));
+        assert!(config.profile("\"my diary\"").is_some());
+        assert!(config.profile("home").is_none());
+
+        assert_eq!(error("[vault.]\n").0, 1);
+        assert_eq!(error("[vault.work.other]\n").0, 1);
+    }
+
+    #[test]
+    fn vault_settings_only_in_the_user_config() {
+        let vault = |text: &str| Config::parse(text, Path::new("/base"), false);
+        assert!(vault("output = \"out\"\n[frontmatter]\ntitle = \"t\"").is_ok());
+        assert_eq!(vault("output = \"out\"\nvault = \"x\"").unwrap_err().0, 2);
+        assert_eq!(vault("[vault.work]\npath = \"x\"").unwrap_err().0, 1);
+        assert_eq!(vault("[vault.work.frontmatter]").unwrap_err().0, 1);
+        // [frontmatter] 表中的 vault 键不是博客目录
+        assert_eq!(vault("[frontmatter]\nvault = \"x\"").unwrap_err().0, 2);
+    }
+
+    #[test]
+    fn later_configs_take_precedence() {
+        let user = parse(
+            "output = \"user\"\n\
+             link_style = \"markdown\"\n\
+             exclude = [\"a\"]\n\
+             org = true\n\
+             [frontmatter]\n\
+             title = \"name\"\n",
+        );
+        let vault = Config::parse(
+            "output = \"vault\"\n\
+             exclude = [\"b\"]\n\
+             org = false\n",
+            Path::new("/vault"),
+            false,
+        )
+        .unwrap();
+        let merged = user.merge(vault);
+        assert_eq!(merged.output, Some(PathBuf::from("/vault/vault")));
+        assert_eq!(merged.link_style, Some(LinkStyle::Markdown));
+        assert_eq!(merged.exclude, ["a", "b"]);
+        assert_eq!(merged.org, Some(false));
+        assert_eq!(merged.title_key, Some(vec!["name".to_string()]));
+    }
+}
@@ -0,0 +1,672 @@
+// 文件头 Created 字段中的日期与时间，以及按本地时区换算的当前时间
+
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::i18n;
+
+/// 公历日期，按年月日排序，显示为 YYYYMMDD（日期页的文件名）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+}
+
+impl Date {
+    /// 检查年月日是否为真实存在的日期
+    pub fn new(year: u16, month: u8, day: u8) -> Option<Date> {
+        if year == 0 || !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+            return None;
+        }
+        Some(Date { year, month, day })
+    }
+
+    /// 解析 Created 字段的第一个词，返回日期与其中附带的时间（HH:MM，没有时为 None）
+    ///
+    /// 接受 `YYYYMMDD`、`YYYY-MM-DD`、`YYYY/MM/DD` 与 RFC 3339
+    /// （如 `2024-05-01T10:00:00+08:00`），时区只原样忽略，不做换算。
+    pub fn parse(text: &str) -> Result<(Date, Option<String>), String> {
+        let (date_part, time_part) = match text.split_once(['T', 't']) {
+            Some((date, time)) => (date, Some(time)),
+            None => (text, None),
+        };
+
+        let fields: Option<(&str, &str, &str)> =
+            if date_part.len() == 8 && date_part.bytes().all(|b| b.is_ascii_digit()) {
+                Some((&date_part[..4], &date_part[4..6], &date_part[6..]))
+            } else {
+                ['-', '/'].into_iter().find_map(|sep| {
+                    let mut parts = date_part.split(sep);
+                    match (parts.next(), parts.next(), parts.next(), parts.next()) {
+                        (Some(y), Some(m), Some(d), None) => Some((y, m, d)),
+                        _ => None,
+                    }
+                })
+            };
+        let format_error = || {
+            i18n::trf(
+                "无法识别的日期 '{}'，应为 YYYYMMDD、YYYY-MM-DD、YYYY/MM/DD 或 RFC 3339 格式",
+                &[&text],
+            )
+        };
+        let (year, month, day) = fields.ok_or_else(format_error)?;
+        // 年份为 4 位，月与日为 1 或 2 位
+        let number = |s: &str, lens: &[usize]| -> Option<u16> {
+            (lens.contains(&s.len()) && s.bytes().all(|b| b.is_ascii_digit()))
+                .then(|| s.parse().ok())
+                .flatten()
+        };
+        let (Some(year), Some(month), Some(day)) = (
+            number(year, &[4]),
+            number(month, &[1, 2]),
+            number(day, &[1, 2]),
+        ) else {
+            return Err(format_error());
+        };
+        let date = Date::new(year, month as u8, day as u8)
+            .ok_or_else(|| i18n::trf("日期 '{}' 不存在", &[&text]))?;
+
+        let time = match time_part {
+            None => None,
+            Some(time) => Some(parse_rfc3339_time(time).ok_or_else(format_error)?),
+        };
+        Ok((date, time))
+    }
+
+    /// 年份，形如 2024
+    pub fn year_key(&self) -> String {
+        format!("{:04}", self.year)
+    }
+
+    /// 年份与月份，形如 2024-05
+    pub fn month_key(&self) -> String {
+        format!("{:04}-{:02}", self.year, self.month)
+    }
+
+    /// ISO 8601 周，形如 2024-W21；年初与年末的几天可能属于相邻年份的周
+    pub fn week_key(&self) -> String {
+        let (year, week) = self.iso_week();
+        format!("{:04}-W{:02}", year, week)
+    }
+
+    /// ISO 8601 周所属的年份与周数，每周从周一开始，含有该年第一个周四的周为第 1 周
+    pub fn iso_week(&self) -> (u16, u8) {
+        let january_first = Date {
+            year: self.year,
+            month: 1,
+            day: 1,
+        };
+        let ordinal = self.days() - january_first.days() + 1;
+        let week = (ordinal - i64::from(self.weekday()) + 10) / 7;
+        if week < 1 {
+            (self.year - 1, weeks_in_year(self.year - 1))
+        } else if week > i64::from(weeks_in_year(self.year)) {
+            (self.year + 1, 1)
+        } else {
+            (self.year, week as u8)
+        }
+    }
+
+    /// 星期几，周一为 1，周日为 7
+    pub fn weekday(&self) -> u8 {
+        // 1970-01-01 为周四
+        ((self.days() + 3).rem_euclid(7) + 1) as u8
+    }
+
+    /// 之后第 n 天的日期，n 为负数时往前数
+    pub fn add_days(&self, n: i64) -> Date {
+        Date::from_days(self.days() + n)
+    }
+
+    /// 距 1970-01-01 的天数
+    pub fn days(&self) -> i64 {
+        let (month, day) = (i64::from(self.month), i64::from(self.day));
+        // 从三月开始计年，闰日落在年末
+        let year = i64::from(self.year) - i64::from(month <= 2);
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146_097 + day_of_era - 719_468
+    }
+
+    /// `days` 的逆运算
+    pub fn from_days(days: i64) -> Date {
+        let days = days + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days - era * 146_097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        };
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+        Date {
+            year: year as u16,
+            month: month as u8,
+            day: day as u8,
+        }
+    }
+}
+
+// ISO 8601 年的周数：1 月 1 日为周四，或闰年的 1 月 1 日为周三时有 53 周
+fn weeks_in_year(year: u16) -> u8 {
+    let january_first = Date {
+        year,
+        month: 1,
+        day: 1,
+    }
+    .weekday();
+    if january_first == 4 || (is_leap_year(year) && january_first == 3) {
+        53
+    } else {
+        52
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}{:02}{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// 一天中的时刻，精确到分钟，按时间先后排序
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Time {
+    pub hour: u8,
+    pub minute: u8,
+}
+
+impl Time {
+    pub fn new(hour: u8, minute: u8) -> Option<Time> {
+        (hour < 24 && minute < 60).then_some(Time { hour, minute })
+    }
+
+    /// 解析 Created 字段中的时间：`H:MM`、`HH:MM` 或 `HH:MM:SS`，秒数忽略；
+    /// 可带 `am`/`pm` 后缀（不区分大小写，如 `9:30pm`）。无法识别时返回 None
+    pub fn parse(text: &str) -> Option<Time> {
+        let lower = text.trim().to_ascii_lowercase();
+        let (clock, pm) = match lower.strip_suffix("am") {
+            Some(clock) => (clock, Some(false)),
+            None => match lower.strip_suffix("pm") {
+                Some(clock) => (clock, Some(true)),
+                None => (lower.as_str(), None),
+            },
+        };
+        let mut parts = clock.trim_end().split(':');
+        let number = |s: &str, lens: &[usize]| -> Option<u8> {
+            (lens.contains(&s.len()) && s.bytes().all(|b| b.is_ascii_digit()))
+                .then(|| s.parse().ok())
+                .flatten()
+        };
+        let hour = number(parts.next()?, &[1, 2])?;
+        let minute = number(parts.next()?, &[2])?;
+        if let Some(seconds) = parts.next() {
+            number(seconds.split('.').next()?, &[2]).filter(|s| *s <= 60)?;
+        }
+        if parts.next().is_some() {
+            return None;
+        }
+        let hour = match pm {
+            None => hour,
+            Some(_) if hour == 0 || hour > 12 => return None,
+            Some(false) => hour % 12,
+            Some(true) => hour % 12 + 12,
+        };
+        Time::new(hour, minute)
+    }
+
+    /// 12 小时制的写法，如 `9:05 AM`
+    pub fn to_12h(self) -> String {
+        let hour = match self.hour % 12 {
+            0 => 12,
+            hour => hour,
+        };
+        let suffix = if self.hour < 12 { "AM" } else { "PM" };
+        format!("{}:{:02} {}", hour, self.minute, suffix)
+    }
+}
+
+/// 24 小时制，HH:MM
+impl fmt::Display for Time {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}:{:02}", self.hour, self.minute)
+    }
+}
+
+/// Unix 秒对应的日期与时刻，按 UTC 计算
+pub fn from_timestamp(secs: u64) -> (Date, Time) {
+    let minutes = secs / 60;
+    let time = Time {
+        hour: (minutes / 60 % 24) as u8,
+        minute: (minutes % 60) as u8,
+    };
+    (Date::from_days((secs / 86_400) as i64), time)
+}
+
+/// 日期与时刻对应的 Unix 秒，按 UTC 计算，没有时刻时为当天 00:00；早于 1970 年的日期为 0
+pub fn to_timestamp(date: Date, time: Option<Time>) -> u64 {
+    let minutes = time.map_or(0, |time| i64::from(time.hour) * 60 + i64::from(time.minute));
+    (date.days() * 86_400 + minutes * 60).max(0) as u64
+}
+
+/// 时刻 time 在本地时区的日期与时刻
+///
+/// 时区按环境变量 TZ 确定：时区名（如 `Asia/Shanghai`）在 /usr/share/zoneinfo 中查找，
+/// 也可以是时区文件的绝对路径或 POSIX 写法（如 `CST-8`、`EST5EDT,M3.2.0,M11.1.0`）；
+/// 没有 TZ 时读取 /etc/localtime。找不到时区数据时（如 Windows）按 UTC 计算。
+pub fn local(time: SystemTime) -> (Date, Time) {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() as i64);
+    let offset = local_offset(secs).unwrap_or(0);
+    from_timestamp((secs + offset).max(0) as u64)
+}
+
+/// 当前的本地日期与时刻，见 `local`
+pub fn now() -> (Date, Time) {
+    local(SystemTime::now())
+}
+
+// Unix 秒 secs 时本地时区相对 UTC 的偏移秒数
+fn local_offset(secs: i64) -> Option<i64> {
+    let zone = match env::var("TZ") {
+        Ok(zone) => zone,
+        Err(_) => return tzif_offset(&fs::read("/etc/localtime").ok()?, secs),
+    };
+    let zone = zone.strip_prefix(':').unwrap_or(&zone);
+    if zone.is_empty() {
+        return Some(0);
+    }
+    let file = if zone.starts_with('/') {
+        fs::read(zone)
+    } else {
+        fs::read(Path::new("/usr/share/zoneinfo").join(zone))
+    };
+    match file {
+        Ok(data) => tzif_offset(&data, secs),
+        Err(_) => posix_offset(zone, secs),
+    }
+}
+
+// 时区文件（TZif，RFC 8536）中 secs 时的偏移：取此前最近一次切换的类型，
+// 晚于最后一次切换时按文件末尾的 POSIX 规则计算
+fn tzif_offset(data: &[u8], secs: i64) -> Option<i64> {
+    if data.get(..4)? != b"TZif" {
+        return None;
+    }
+    let u32_at = |at: usize| -> Option<usize> {
+        Some(u32::from_be_bytes(data.get(at..at + 4)?.try_into().ok()?) as usize)
+    };
+    // 头部之后依次为 isutcnt、isstdcnt、leapcnt、timecnt、typecnt、charcnt
+    let counts = |header: usize| -> Option<[usize; 6]> {
+        let mut counts = [0; 6];
+        for (i, count) in counts.iter_mut().enumerate() {
+            *count = u32_at(header + 20 + i * 4)?;
+        }
+        Some(counts)
+    };
+    let block_len = |[isut, isstd, leap, times, types, chars]: [usize; 6], size: usize| {
+        times * (size + 1) + types * 6 + chars + leap * (size + 4) + isstd + isut
+    };
+
+    // 第 2 版起，32 位的数据之后另有一份 64 位的数据与 POSIX 规则
+    let mut header = 0;
+    let mut size = 4;
+    let mut counts_now = counts(header)?;
+    if *data.get(4)? >= b'2' {
+        header = 44 + block_len(counts_now, 4);
+        size = 8;
+        counts_now = counts(header)?;
+    }
+    let [_, _, _, times, types, _] = counts_now;
+    let start = header + 44;
+    let time_at = |i: usize| -> Option<i64> {
+        let at = start + i * size;
+        Some(if size == 8 {
+            i64::from_be_bytes(data.get(at..at + 8)?.try_into().ok()?)
+        } else {
+            i64::from(i32::from_be_bytes(data.get(at..at + 4)?.try_into().ok()?))
+        })
+    };
+    let type_offset = |t: usize| -> Option<i64> {
+        let at = start + times * (size + 1) + t * 6;
+        Some(i64::from(i32::from_be_bytes(
+            data.get(at..at + 4)?.try_into().ok()?,
+        )))
+    };
+    if types == 0 {
+        return None;
+    }
+
+    let mut passed = 0;
+    while passed < times && time_at(passed)? <= secs {
+        passed += 1;
+    }
+    if passed == times && size == 8 {
+        let footer = data.get(start + block_len(counts_now, 8)..)?;
+        let footer = std::str::from_utf8(footer).ok()?.trim_matches('\n');
+        if let Some(offset) = posix_offset(footer, secs) {
+            return Some(offset);
+        }
+    }
+    let index = match passed {
+        0 => 0,
+        n => usize::from(*data.get(start + times * size + n - 1)?),
+    };
+    type_offset(index)
+}
+
+// POSIX 写法的时区 `std offset [dst [offset] [,start[/time],end[/time]]]` 在 secs 时的偏移；
+// 切换日期只支持 `Mm.w.d`，没有切换规则时按标准时间计算
+fn posix_offset(zone: &str, secs: i64) -> Option<i64> {
+    let rest = skip_zone_name(zone)?;
+    let (std_offset, rest) = parse_tz_time(rest)?;
+    // POSIX 中的偏移是 UTC 减去本地时间，与通常的写法相反
+    let std_offset = -std_offset;
+    if rest.is_empty() {
+        return Some(std_offset);
+    }
+    let rest = skip_zone_name(rest)?;
+    let (dst_offset, rest) = match rest.chars().next() {
+        Some(',') | None => (std_offset + 3600, rest),
+        Some(_) => {
+            let (offset, rest) = parse_tz_time(rest)?;
+            (-offset, rest)
+        }
+    };
+    let Some(rules) = rest.strip_prefix(',') else {
+        return Some(std_offset);
+    };
+    let (start, end) = rules.split_once(',')?;
+    let year = from_timestamp((secs + std_offset).max(0) as u64).0.year;
+    let start = tz_rule(start, year)? - std_offset;
+    let end = tz_rule(end, year)? - dst_offset;
+    let dst = if start < end {
+        start <= secs && secs < end
+    } else {
+        // 南半球：夏令时跨过年末
+        !(end <= secs && secs < start)
+    };
+    Some(if dst { dst_offset } else { std_offset })
+}
+
+// 跳过时区名：三个以上字母，或 `<` 与 `>` 括起的名称
+fn skip_zone_name(zone: &str) -> Option<&str> {
+    if let Some(quoted) = zone.strip_prefix('<') {
+        return quoted.split_once('>').map(|(_, rest)| rest);
+    }
+    let end = zone
+        .find(|c: char| !c.is_ascii_alphabetic())
+        .unwrap_or(zone.len());
+    (end >= 3).then(|| &zone[end..])
+}
+
+// 解析 `[+-]hh[:mm[:ss]]`，返回秒数与其后的文字
+fn parse_tz_time(text: &str) -> Option<(i64, &str)> {
+    let (sign, text) = match text.as_bytes().first()? {
+        b'-' => (-1, &text[1..]),
+        b'+' => (1, &text[1..]),
+        _ => (1, text),
+    };
+    let end = text
+        .find(|c: char| !c.is_ascii_digit() && c != ':')
+        .unwrap_or(text.len());
+    let mut secs = 0;
+    for (i, part) in text[..end].split(':').enumerate() {
+        if i > 2 || part.is_empty() {
+            return None;
+        }
+        secs += part.parse::<i64>().ok()? * [3600, 60, 1][i];
+    }
+    Some((sign * secs, &text[end..]))
+}
+
+// 切换规则 `Mm.w.d[/time]` 在 year 年对应的本地时刻（距 1970-01-01 00:00 的秒数）：
+// m 月第 w 个星期 d（0 为周日，w 为 5 表示最后一个），time 默认为 02:00
+fn tz_rule(rule: &str, year: u16) -> Option<i64> {
+    let (day, time) = match rule.split_once('/') {
+        Some((day, time)) => (
+            day,
+            parse_tz_time(time).filter(|(_, rest)| rest.is_empty())?.0,
+        ),
+        None => (rule, 7200),
+    };
+    let mut parts = day.strip_prefix('M')?.split('.');
+    let mut number = || parts.next()?.parse::<u8>().ok();
+    let (month, week, weekday) = (number()?, number()?, number()?);
+    if !(1..=5).contains(&week) || weekday > 6 {
+        return None;
+    }
+    let first = Date::new(year, month, 1)?;
+    // weekday() 以周一为 1、周日为 7，规则中周日为 0
+    let first_weekday = first.weekday() % 7;
+    let mut day = 1 + (weekday + 7 - first_weekday) % 7 + (week - 1) * 7;
+    while day > days_in_month(year, month) {
+        day -= 7;
+    }
+    Some(first.add_days(i64::from(day) - 1).days() * 86_400 + time)
+}
+
+fn is_leap_year(year: u16) -> bool {
+    year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400))
+}
+
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// RFC 3339 的时间部分 HH:MM[:SS[.frac]][Z|±HH:MM]，返回 HH:MM
+fn parse_rfc3339_time(time: &str) -> Option<String> {
+    let end = time.find(['Z', 'z', '+', '-']).unwrap_or(time.len());
+    let (clock, zone) = time.split_at(end);
+    let mut parts = clock.split(':');
+    let hour: u8 = parts.next().filter(|h| h.len() == 2)?.parse().ok()?;
+    let minute: u8 = parts.next().filter(|m| m.len() == 2)?.parse().ok()?;
+    if let Some(seconds) = parts.next() {
+        let whole = seconds.split('.').next()?;
+        if whole.len() != 2 || whole.parse::<u8>().ok()? > 60 {
+            return None;
+        }
+    }
+    if parts.next().is_some() || hour > 23 || minute > 59 {
+        return None;
+    }
+    let zone_ok = match zone {
+        "" | "Z" | "z" => true,
+        _ => {
+            let offset = &zone[1..];
+            offset.len() == 5
+                && offset.as_bytes()[2] == b':'
+                && offset[..2].parse::<u8>().is_ok()
+                && offset[3..].parse::<u8>().is_ok()
+        }
+    };
+    zone_ok.then(|| format!("{:02}:{:02}", hour, minute))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: u16, month: u8, day: u8) -> Date {
+        Date::new(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn date_formats() {
+        for text in ["20240501", "2024-05-01", "2024/05/01", "2024-5-1"] {
+            assert_eq!(Date::parse(text), Ok((date(2024, 5, 1), None)), "{}", text);
+        }
+        assert_eq!(
+            Date::parse("2024-05-01T10:00:00+08:00"),
+            Ok((date(2024, 5, 1), Some("10:00".to_string())))
+        );
+        assert_eq!(
+            Date::parse("2024-05-01t23:59:59.5Z"),
+            Ok((date(2024, 5, 1), Some("23:59".to_string())))
+        );
+        for text in [
+            "2024-05",
+            "24-05-01",
+            "2024-05-01-02",
+            "2024.05.01",
+            "2024-05-01T25:00",
+            "公元",
+        ] {
+            assert!(Date::parse(text).is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn calendar_rules() {
+        assert!(Date::new(2024, 2, 29).is_some());
+        assert!(Date::new(2023, 2, 29).is_none());
+        assert!(Date::new(1900, 2, 29).is_none());
+        assert!(Date::new(2000, 2, 29).is_some());
+        assert!(Date::new(2024, 4, 31).is_none());
+        assert!(Date::new(2024, 13, 1).is_none());
+        assert!(Date::new(0, 1, 1).is_none());
+        assert_eq!(
+            Date::parse("2023-02-29").unwrap_err(),
+            "日期 '2023-02-29' 不存在"
+        );
+    }
+
+    #[test]
+    fn day_arithmetic() {
+        assert_eq!(date(1970, 1, 1).days(), 0);
+        assert_eq!(date(2000, 3, 1).days(), 11_017);
+        assert_eq!(date(1969, 12, 31).days(), -1);
+        for days in [-1000, 0, 59, 60, 11_016, 19_844, 100_000] {
+            assert_eq!(Date::from_days(days).days(), days);
+        }
+        assert_eq!(date(2024, 2, 28).add_days(1), date(2024, 2, 29));
+        assert_eq!(date(2024, 3, 1).add_days(-1), date(2024, 2, 29));
+        assert_eq!(date(2024, 12, 31).add_days(1), date(2025, 1, 1));
+        assert_eq!(date(2024, 5, 20).weekday(), 1);
+        assert_eq!(date(2024, 5, 26).weekday(), 7);
+    }
+
+    #[test]
+    fn iso_weeks() {
+        assert_eq!(date(2024, 5, 21).week_key(), "2024-W21");
+        // 年初属于上一年的最后一周，年末属于下一年的第一周
+        assert_eq!(date(2021, 1, 3).week_key(), "2020-W53");
+        assert_eq!(date(2024, 12, 30).week_key(), "2025-W01");
+        assert_eq!(date(2026, 1, 1).week_key(), "2026-W01");
+        assert_eq!(date(2020, 12, 31).week_key(), "2020-W53");
+        assert_eq!(date(2024, 5, 1).month_key(), "2024-05");
+        assert_eq!(date(2024, 5, 1).to_string(), "20240501");
+    }
+
+    #[test]
+    fn times() {
+        assert_eq!(Time::parse("9:05"), Time::new(9, 5));
+        assert_eq!(Time::parse("09:05:59"), Time::new(9, 5));
+        assert_eq!(Time::parse("12:00 AM"), Time::new(0, 0));
+        assert_eq!(Time::parse("12:30pm"), Time::new(12, 30));
+        assert_eq!(Time::parse("11:59 Pm"), Time::new(23, 59));
+        for text in [
+            "24:00",
+            "9:5",
+            "13:00 pm",
+            "0:10am",
+            "9",
+            "09:00:00:00",
+            "下午",
+        ] {
+            assert_eq!(Time::parse(text), None, "{}", text);
+        }
+        assert_eq!(Time::new(0, 5).unwrap().to_12h(), "12:05 AM");
+        assert_eq!(Time::new(13, 0).unwrap().to_12h(), "1:00 PM");
+        assert_eq!(Time::new(7, 3).unwrap().to_string(), "07:03");
+    }
+
+    #[test]
+    fn timestamps() {
+        assert_eq!(
+            from_timestamp(0),
+            (date(1970, 1, 1), Time::new(0, 0).unwrap())
+        );
+        assert_eq!(
+            from_timestamp(1_716_301_920),
+            (date(2024, 5, 21), Time::new(14, 32).unwrap())
+        );
+        assert_eq!(
+            to_timestamp(date(2024, 5, 21), Time::new(14, 32)),
+            1_716_301_920
+        );
+        assert_eq!(to_timestamp(date(2024, 5, 21), None), 1_716_249_600);
+        assert_eq!(to_timestamp(date(1969, 12, 31), None), 0);
+    }
+
+    // 2024-01-15 与 2024-07-15 的 12:00 UTC
+    const WINTER: i64 = 1_705_320_000;
+    const SUMMER: i64 = 1_721_044_800;
+
+    #[test]
+    fn posix_zones() {
+        assert_eq!(posix_offset("UTC0", SUMMER), Some(0));
+        assert_eq!(posix_offset("CST-8", SUMMER), Some(8 * 3600));
+        assert_eq!(posix_offset("<+0530>-5:30", WINTER), Some(19_800));
+        let new_york = "EST5EDT,M3.2.0,M11.1.0";
+        assert_eq!(posix_offset(new_york, WINTER), Some(-5 * 3600));
+        assert_eq!(posix_offset(new_york, SUMMER), Some(-4 * 3600));
+        // 2024-03-10 02:00 EST 切换到夏令时
+        assert_eq!(posix_offset(new_york, 1_710_054_000 - 1), Some(-5 * 3600));
+        assert_eq!(posix_offset(new_york, 1_710_054_000), Some(-4 * 3600));
+        // 南半球的夏令时跨过年末
+        let sydney = "AEST-10AEDT,M10.1.0,M4.1.0/3";
+        assert_eq!(posix_offset(sydney, WINTER), Some(11 * 3600));
+        assert_eq!(posix_offset(sydney, SUMMER), Some(10 * 3600));
+        assert_eq!(posix_offset("X", SUMMER), None);
+    }
+
+    // 只有一种类型、没有切换的时区文件；footer 非空时写成第 2 版
+    fn tzif(offset: i32, footer: &str) -> Vec<u8> {
+        let block = |data: &mut Vec<u8>, version: u8| {
+            data.extend_from_slice(b"TZif");
+            data.push(version);
+            data.extend_from_slice(&[0; 15]);
+            for count in [0u32, 0, 0, 0, 1, 4] {
+                data.extend_from_slice(&count.to_be_bytes());
+            }
+            data.extend_from_slice(&offset.to_be_bytes());
+            data.extend_from_slice(&[0, 0]);
+            data.extend_from_slice(b"LMT\0");
+        };
+        let mut data = Vec::new();
+        if footer.is_empty() {
+            block(&mut data, 0);
+        } else {
+            block(&mut data, b'2');
+            block(&mut data, b'2');
+            data.extend_from_slice(format!("\n{}\n", footer).as_bytes());
+        }
+        data
+    }
+
+    #[test]
+    fn zone_files() {
+        assert_eq!(tzif_offset(&tzif(3600, ""), SUMMER), Some(3600));
+        let berlin = tzif(3600, "CET-1CEST,M3.5.0,M10.5.0/3");
+        assert_eq!(tzif_offset(&berlin, WINTER), Some(3600));
+        assert_eq!(tzif_offset(&berlin, SUMMER), Some(7200));
+        assert_eq!(tzif_offset(b"not a zone file", SUMMER), None);
+    }
+}
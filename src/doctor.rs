@@ -0,0 +1,219 @@
+// 文件头检查：找出缺少标题、创建时间或标签的笔记，并修复其中能自动补全的问题
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::cache::Cache;
+use crate::date::Date;
+use crate::frontmatter::{self, Value};
+use crate::i18n;
+use crate::note::{FrontmatterKeys, is_generated, read_text};
+use crate::output::write_atomic;
+use crate::vault::{ScanReport, note_files};
+
+/// 问题的类别，按输出顺序排列
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum IssueKind {
+    /// 文件头无法解析
+    Malformed,
+    MissingTitle,
+    MissingCreated,
+    /// 创建时间无法解析为日期
+    InvalidCreated,
+    MissingTags,
+}
+
+impl IssueKind {
+    /// 分组输出时的标题
+    pub fn describe(self, keys: &FrontmatterKeys) -> String {
+        match self {
+            IssueKind::Malformed => i18n::tr("文件头格式错误").to_string(),
+            IssueKind::MissingTitle => i18n::trf("缺少 {}", &[&keys.title]),
+            IssueKind::MissingCreated => i18n::trf("缺少 {}", &[&keys.created]),
+            IssueKind::InvalidCreated => i18n::trf("{} 无法解析", &[&keys.created]),
+            IssueKind::MissingTags => i18n::trf("缺少 {}", &[&keys.tags]),
+        }
+    }
+}
+
+/// 一篇笔记的一个问题
+#[derive(Debug, Clone)]
+pub struct Issue {
+    pub path: PathBuf,
+    pub kind: IssueKind,
+    /// 补充说明，可能为空
+    pub detail: String,
+    /// 能否由 `fix` 自动修复：字段完全不存在时才补全，已有但为空的字段需要手动处理
+    pub fixable: bool,
+}
+
+/// 修复的结果
+#[derive(Debug, Default)]
+pub struct FixReport {
+    /// 已修复的问题
+    pub fixed: Vec<Issue>,
+    /// 修改过的文件
+    pub files: Vec<PathBuf>,
+}
+
+/// 检查的结果
+#[derive(Debug, Default)]
+pub struct Diagnosis {
+    /// 按类别与文件名排序
+    pub issues: Vec<Issue>,
+    /// 无法读取、未检查的文件，与扫描时的报告相同
+    pub skipped: ScanReport,
+}
+
+/// 检查 root 下的全部笔记
+///
+/// 与扫描相同，跳过 .gtxignore、exclude 排除的文件与生成的页面；无法读取的文件记入 skipped，不中断检查。
+pub fn diagnose(
+    root: &Path,
+    cache: &Cache,
+    exclude: &[String],
+    keys: &FrontmatterKeys,
+    follow_symlinks: bool,
+) -> io::Result<Diagnosis> {
+    let mut diagnosis = Diagnosis::default();
+    let files = note_files(root, cache, exclude, false, follow_symlinks)?;
+    for path in files.into_iter().map(|entry| entry.path) {
+        match read_text(&path) {
+            Ok(content) => check_note(&path, &content, keys, &mut diagnosis.issues),
+            Err(e) => diagnosis.skipped.skip(&path, &e),
+        }
+    }
+    diagnosis
+        .issues
+        .sort_by(|a, b| a.kind.cmp(&b.kind).then_with(|| a.path.cmp(&b.path)));
+    Ok(diagnosis)
+}
+
+fn check_note(path: &Path, content: &str, keys: &FrontmatterKeys, issues: &mut Vec<Issue>) {
+    let mut report = |kind, detail: String, fixable| {
+        issues.push(Issue {
+            path: path.to_path_buf(),
+            kind,
+            detail,
+            fixable,
+        })
+    };
+    let header = match frontmatter::parse(content) {
+        Ok(Some((header, _))) => header,
+        // 没有文件头时三个字段都缺少，标题与创建时间可以补全
+        Ok(None) => {
+            report(
+                IssueKind::MissingTitle,
+                i18n::tr("没有文件头").to_string(),
+                true,
+            );
+            report(
+                IssueKind::MissingCreated,
+                i18n::tr("没有文件头").to_string(),
+                true,
+            );
+            report(
+                IssueKind::MissingTags,
+                i18n::tr("没有文件头").to_string(),
+                false,
+            );
+            return;
+        }
+        Err(e) => {
+            report(IssueKind::Malformed, e.to_string(), false);
+            return;
+        }
+    };
+    if is_generated(&header) {
+        return;
+    }
+
+    match keys.lookup(&header, &keys.title) {
+        None => report(IssueKind::MissingTitle, String::new(), true),
+        Some(value) if value.as_str().is_none_or(|title| title.trim().is_empty()) => report(
+            IssueKind::MissingTitle,
+            i18n::tr("字段为空").to_string(),
+            false,
+        ),
+        Some(_) => {}
+    }
+
+    match keys.lookup(&header, &keys.created).map(Value::words) {
+        None => report(IssueKind::MissingCreated, String::new(), true),
+        Some(words) if words.is_empty() => report(
+            IssueKind::InvalidCreated,
+            i18n::tr("字段为空").to_string(),
+            false,
+        ),
+        Some(words) => {
+            if let Err(e) = Date::parse(&words[0]) {
+                report(IssueKind::InvalidCreated, e, false);
+            }
+        }
+    }
+
+    let has_tags = keys
+        .lookup(&header, &keys.tags)
+        .is_some_and(|tags| !tags.tags().is_empty());
+    if !has_tags {
+        report(IssueKind::MissingTags, String::new(), false);
+    }
+}
+
+/// 修复能自动修复的问题：以文件名补全标题，以 created_for 的结果补全创建时间
+///
+/// created_for 返回文件的创建时间，形如 ("20240501", "10:00")，通常取自文件的修改时间。
+pub fn fix(
+    issues: &[Issue],
+    keys: &FrontmatterKeys,
+    created_for: &dyn Fn(&Path) -> io::Result<(String, String)>,
+) -> io::Result<FixReport> {
+    let mut report = FixReport::default();
+    let mut paths: Vec<&PathBuf> = issues
+        .iter()
+        .filter(|issue| issue.fixable)
+        .map(|issue| &issue.path)
+        .collect();
+    paths.sort();
+    paths.dedup();
+
+    for path in paths {
+        let fixes: Vec<&Issue> = issues
+            .iter()
+            .filter(|issue| issue.fixable && issue.path == *path)
+            .collect();
+        let mut lines = String::new();
+        for issue in &fixes {
+            match issue.kind {
+                IssueKind::MissingTitle => {
+                    let name = path.file_stem().unwrap_or_default().to_string_lossy();
+                    lines.push_str(&format!("{}: {}\n", keys.title, name));
+                }
+                IssueKind::MissingCreated => {
+                    let (date, time) = created_for(path)?;
+                    lines.push_str(&format!("{}: {} {}\n", keys.created, date, time));
+                }
+                _ => {}
+            }
+        }
+
+        let content = fs::read_to_string(path)?;
+        let fixed = match frontmatter::split(&content) {
+            // 新字段加在文件头的结束标记之前
+            Some((_, body)) => {
+                let header_end = content.len() - body.len();
+                let closing = content[..header_end]
+                    .trim_end_matches(['\n', '\r'])
+                    .rfind('\n')
+                    .map_or(0, |i| i + 1);
+                format!("{}{}{}", &content[..closing], lines, &content[closing..])
+            }
+            None => format!("---\n{}---\n{}", lines, content),
+        };
+        write_atomic(path, &fixed)?;
+        report.fixed.extend(fixes.into_iter().cloned());
+        report.files.push(path.clone());
+    }
+    Ok(report)
+}
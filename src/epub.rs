@@ -0,0 +1,237 @@
+// EPUB 导出：把选定的笔记按顺序合成一本 EPUB 3 电子书，便于离线阅读
+//
+// 文件结构:
+//   mimetype                    固定为 application/epub+zip，不压缩且位于首位
+//   META-INF/container.xml      指向 content.opf
+//   OEBPS/content.opf           书名、语言、修改时间、文件清单与阅读顺序
+//   OEBPS/nav.xhtml             目录，每篇笔记一项
+//   OEBPS/style.css
+//   OEBPS/chapter-001.xhtml     每篇笔记一章
+//
+// 笔记按文件头的 Order 字段（数字，小的在前）排列，没有 Order 的笔记排在其后，按创建日期与时间，
+// 再按文件名。查询块换成查询结果。指向书中其他笔记的维基链接转为章节之间的链接，其余链接只保留文字；图片不会打包，
+// 只保留说明文字。
+
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+
+use crate::date::{self, Date};
+use crate::frontmatter;
+use crate::index::tag_levels;
+use crate::markdown::{self, escape};
+use crate::note::NoteMeta;
+use crate::org;
+use crate::vault::Vault;
+use crate::zip::ZipWriter;
+
+/// 决定章节顺序的文件头字段
+pub const ORDER_KEY: &str = "Order";
+
+const STYLE: &str = "\
+body { font-family: serif; line-height: 1.6; }
+h1 { font-size: 1.5em; }
+.meta { color: #666; font-size: 0.9em; }
+.broken-link, .image { color: #555; }
+pre { white-space: pre-wrap; font-size: 0.85em; }
+code { font-family: monospace; }
+blockquote { margin-left: 0; padding-left: 1em; border-left: 3px solid #ccc; color: #555; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #ccc; padding: 0.2em 0.4em; }
+";
+
+/// 书中的一章
+pub struct Chapter {
+    /// 笔记文件名
+    pub name: String,
+    pub title: String,
+    pub created: Option<Date>,
+    /// 笔记正文的 Markdown 原文
+    pub body: String,
+}
+
+/// 书中的笔记，按章节顺序排列；tag 为 Some 时只取带有该标签（含其下层级标签）的笔记。
+/// org 笔记不是 Markdown，不会收入
+pub fn chapters(vault: &Vault, tag: Option<&str>) -> io::Result<Vec<Chapter>> {
+    let notes: Vec<&NoteMeta> = vault
+        .published()
+        .filter(|note| !org::is_org(&note.name))
+        .filter(|note| {
+            tag.is_none_or(|tag| {
+                note.tags
+                    .iter()
+                    .any(|note_tag| tag_levels(note_tag).contains(&tag))
+            })
+        })
+        .collect();
+
+    let mut ordered = Vec::new();
+    for note in notes {
+        let content = fs::read_to_string(vault.note_path(&note.name))?;
+        let order = match frontmatter::parse(&content) {
+            Ok(Some((header, _))) => header
+                .get_str(ORDER_KEY)
+                .and_then(|value| value.trim().parse::<f64>().ok()),
+            _ => None,
+        };
+        let chapter = Chapter {
+            name: note.name.clone(),
+            title: note.title.clone(),
+            created: note.created.as_ref().map(|(date, _)| *date),
+            body: vault.note_body(note)?,
+        };
+        ordered.push((order, note.created.clone(), chapter));
+    }
+    ordered.sort_by(|(order_a, created_a, a), (order_b, created_b, b)| {
+        let order = match (order_a, order_b) {
+            (Some(x), Some(y)) => x.total_cmp(y),
+            _ => order_b.is_some().cmp(&order_a.is_some()),
+        };
+        order
+            .then_with(|| created_b.is_some().cmp(&created_a.is_some()))
+            .then_with(|| created_a.cmp(created_b))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    Ok(ordered.into_iter().map(|(_, _, chapter)| chapter).collect())
+}
+
+/// 生成 EPUB 文件的内容；modified 为修改时间的 Unix 秒，写入 dcterms:modified
+pub fn to_epub(vault: &Vault, chapters: &[Chapter], title: &str, modified: u64) -> Vec<u8> {
+    let links = &vault.indexes().links;
+    let files: Vec<String> = (1..=chapters.len())
+        .map(|i| format!("chapter-{:03}.xhtml", i))
+        .collect();
+    let chapter_file = |target: &str| {
+        let name = links.resolve(target)?;
+        let i = chapters.iter().position(|chapter| chapter.name == name)?;
+        Some(files[i].clone())
+    };
+
+    let mut zip = ZipWriter::new();
+    zip.add("mimetype", b"application/epub+zip");
+    zip.add(
+        "META-INF/container.xml",
+        b"<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+          <container version=\"1.0\" xmlns=\"urn:oasis:names:tc:opendocument:xmlns:container\">\n\
+          <rootfiles>\n\
+          <rootfile full-path=\"OEBPS/content.opf\" media-type=\"application/oebps-package+xml\"/>\n\
+          </rootfiles>\n\
+          </container>\n",
+    );
+    zip.add(
+        "OEBPS/content.opf",
+        package(chapters, &files, title, modified).as_bytes(),
+    );
+
+    let mut nav = String::from("<nav epub:type=\"toc\" id=\"toc\">\n<h1>目录</h1>\n<ol>\n");
+    for (chapter, file) in chapters.iter().zip(&files) {
+        let _ = writeln!(
+            nav,
+            "<li><a href=\"{}\">{}</a></li>",
+            file,
+            escape(&chapter.title)
+        );
+    }
+    nav.push_str("</ol>\n</nav>\n");
+    zip.add("OEBPS/nav.xhtml", page(title, &nav).as_bytes());
+    zip.add("OEBPS/style.css", STYLE.as_bytes());
+
+    for (chapter, file) in chapters.iter().zip(&files) {
+        let mut body = format!("<h1>{}</h1>\n", escape(&chapter.title));
+        if let Some(date) = chapter.created {
+            let _ = writeln!(
+                body,
+                "<p class=\"meta\">{:04}-{:02}-{:02}</p>",
+                date.year, date.month, date.day
+            );
+        }
+        body.push_str(&to_xhtml(&markdown::to_html(&chapter.body, &chapter_file)));
+        zip.add(
+            &format!("OEBPS/{}", file),
+            page(&chapter.title, &body).as_bytes(),
+        );
+    }
+    zip.finish()
+}
+
+// content.opf：元数据、清单与阅读顺序
+fn package(chapters: &[Chapter], files: &[String], title: &str, modified: u64) -> String {
+    let (day, time) = date::from_timestamp(modified);
+    let mut opf = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <package xmlns=\"http://www.idpf.org/2007/opf\" version=\"3.0\" unique-identifier=\"book-id\" xml:lang=\"zh\">\n\
+         <metadata xmlns:dc=\"http://purl.org/dc/elements/1.1/\">\n\
+         <dc:identifier id=\"book-id\">urn:gtx:{}</dc:identifier>\n\
+         <dc:title>{}</dc:title>\n\
+         <dc:language>zh</dc:language>\n\
+         <meta property=\"dcterms:modified\">{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z</meta>\n\
+         </metadata>\n<manifest>\n\
+         <item id=\"nav\" href=\"nav.xhtml\" media-type=\"application/xhtml+xml\" properties=\"nav\"/>\n\
+         <item id=\"style\" href=\"style.css\" media-type=\"text/css\"/>\n",
+        escape(title),
+        escape(title),
+        day.year,
+        day.month,
+        day.day,
+        time.hour,
+        time.minute,
+        modified % 60
+    );
+    for (i, file) in files.iter().enumerate() {
+        let _ = writeln!(
+            opf,
+            "<item id=\"c{}\" href=\"{}\" media-type=\"application/xhtml+xml\"/>",
+            i + 1,
+            file
+        );
+    }
+    opf.push_str("</manifest>\n<spine>\n<itemref idref=\"nav\" linear=\"no\"/>\n");
+    for i in 1..=chapters.len() {
+        let _ = writeln!(opf, "<itemref idref=\"c{}\"/>", i);
+    }
+    opf.push_str("</spine>\n</package>\n");
+    opf
+}
+
+// 完整的 XHTML 页面
+fn page(title: &str, body: &str) -> String {
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<!DOCTYPE html>\n\
+         <html xmlns=\"http://www.w3.org/1999/xhtml\" xmlns:epub=\"http://www.idpf.org/2007/ops\" \
+         lang=\"zh\" xml:lang=\"zh\">\n<head>\n<meta charset=\"utf-8\"/>\n<title>{}</title>\n\
+         <link rel=\"stylesheet\" type=\"text/css\" href=\"style.css\"/>\n</head>\n<body>\n{}</body>\n</html>\n",
+        escape(title),
+        body
+    )
+}
+
+// markdown::to_html 输出的是 HTML：空元素改为自闭合，布尔属性写出属性值；
+// 图片换成说明文字。原始 HTML 已被转义，文本中不会出现未转义的 < 与 >
+fn to_xhtml(html: &str) -> String {
+    let html = html
+        .replace("<hr>", "<hr/>")
+        .replace("<br>", "<br/>")
+        .replace(
+            " disabled checked>",
+            " disabled=\"disabled\" checked=\"checked\"/>",
+        )
+        .replace(" disabled>", " disabled=\"disabled\"/>");
+
+    let mut out = String::with_capacity(html.len());
+    let mut rest = html.as_str();
+    while let Some(start) = rest.find("<img ") {
+        out.push_str(&rest[..start]);
+        let tag = &rest[start..];
+        let end = tag.find('>').map_or(tag.len(), |end| end + 1);
+        let alt = tag[..end]
+            .split_once(" alt=\"")
+            .and_then(|(_, value)| value.split_once('"'))
+            .map_or("", |(value, _)| value);
+        if !alt.is_empty() {
+            let _ = write!(out, "<span class=\"image\">[{}]</span>", alt);
+        }
+        rest = &tag[end..];
+    }
+    out.push_str(rest);
+    out
+}
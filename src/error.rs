@@ -0,0 +1,91 @@
+// 命令行使用的错误类型，每类错误对应不同的退出码，便于脚本区分
+
+use std::fmt;
+use std::io;
+
+use crate::config;
+
+/// gtx 命令的错误
+///
+/// 退出码：
+///
+/// | 退出码 | 含义 |
+/// | --- | --- |
+/// | 1 | 没有结果：search、query、open 没有匹配、check 发现失效链接、doctor 发现问题、tag 找不到标签 |
+/// | 2 | 命令行参数错误 |
+/// | 3 | 博客目录不存在或不是目录 |
+/// | 4 | 配置文件读取失败或格式错误 |
+/// | 5 | 有笔记解析失败，其余笔记已照常处理 |
+/// | 6 | 读写文件或网络失败 |
+#[derive(Debug)]
+pub enum GtxError {
+    /// 没有结果，不是真正的错误，输出时不加“错误”前缀
+    NotFound(String),
+    Usage(String),
+    Vault(String),
+    Config(config::Error),
+    Parse(String),
+    Io {
+        /// 出错时正在做的事，为空时只输出底层错误
+        context: String,
+        source: io::Error,
+    },
+}
+
+impl GtxError {
+    /// 带说明的 IO 错误
+    pub fn io(context: impl Into<String>, source: io::Error) -> GtxError {
+        GtxError::Io {
+            context: context.into(),
+            source,
+        }
+    }
+
+    /// 进程的退出码，见类型说明
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            GtxError::NotFound(_) => 1,
+            GtxError::Usage(_) => 2,
+            GtxError::Vault(_) => 3,
+            GtxError::Config(_) => 4,
+            GtxError::Parse(_) => 5,
+            GtxError::Io { .. } => 6,
+        }
+    }
+}
+
+impl fmt::Display for GtxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GtxError::NotFound(message)
+            | GtxError::Usage(message)
+            | GtxError::Vault(message)
+            | GtxError::Parse(message) => f.write_str(message),
+            GtxError::Config(e) => write!(f, "{}", e),
+            GtxError::Io { context, source } if context.is_empty() => write!(f, "{}", source),
+            GtxError::Io { context, source } => write!(f, "{}: {}", context, source),
+        }
+    }
+}
+
+impl std::error::Error for GtxError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GtxError::Config(e) => Some(e),
+            GtxError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for GtxError {
+    fn from(e: io::Error) -> Self {
+        GtxError::io("", e)
+    }
+}
+
+impl From<config::Error> for GtxError {
+    fn from(e: config::Error) -> Self {
+        GtxError::Config(e)
+    }
+}
@@ -0,0 +1,213 @@
+// 索引的 JSON、CSV 与 OPML 导出，供脚本、电子表格、静态站点生成器与大纲软件使用
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+use crate::index::tag_levels;
+use crate::json::Json;
+use crate::links::LinkIndex;
+use crate::markdown::{encode_url, escape};
+use crate::note::{NEED_TAG, NoteMeta, note_file_name, reading_minutes};
+use crate::vault::Vault;
+
+/// 导出全部笔记，按文件名排序
+///
+/// ```text
+/// {
+///   "notes": [
+///     {
+///       "name": "a",
+///       "path": "a.md",
+///       "title": "笔记A",
+///       "date": "20240501",
+///       "time": "10:00",
+///       "tags": ["rust"],
+///       "aliases": ["A"],
+///       "id": "202405011000",
+///       "words": 320,
+///       "minutes": 2,
+///       "links": [{"target": "b", "line": 7, "resolved": "b"}]
+///     }
+///   ]
+/// }
+/// ```
+///
+/// 没有创建时间的笔记 date 与 time 为 null，没有 ID 的笔记 id 为 null，无法解析的链接 resolved 为 null。
+pub fn to_json(vault: &Vault) -> Json {
+    let mut notes: Vec<&NoteMeta> = vault.published().collect();
+    notes.sort_by(|a, b| a.name.cmp(&b.name));
+    let links = &vault.indexes().links;
+
+    let notes = notes
+        .into_iter()
+        .map(|note| note_to_json(note, links))
+        .collect();
+
+    Json::Object(vec![("notes".to_string(), Json::Array(notes))])
+}
+
+/// 一篇笔记的 JSON，字段见 `to_json`
+pub fn note_to_json(note: &NoteMeta, links: &LinkIndex) -> Json {
+    let (date, time) = match &note.created {
+        Some((date, time)) => (Json::from(date.to_string()), Json::from(time.as_str())),
+        None => (Json::Null, Json::Null),
+    };
+    let note_links = note
+        .links
+        .iter()
+        .map(|link| {
+            Json::Object(vec![
+                ("target".to_string(), link.target.as_str().into()),
+                ("line".to_string(), link.line.into()),
+                ("resolved".to_string(), links.resolve(&link.target).into()),
+            ])
+        })
+        .collect();
+    Json::Object(vec![
+        ("name".to_string(), note.name.as_str().into()),
+        ("path".to_string(), note_file_name(&note.name).into()),
+        ("title".to_string(), note.title.as_str().into()),
+        ("date".to_string(), date),
+        ("time".to_string(), time),
+        ("tags".to_string(), note.tags.clone().into()),
+        ("aliases".to_string(), note.aliases.clone().into()),
+        ("id".to_string(), note.id.as_deref().into()),
+        ("words".to_string(), note.words.into()),
+        ("minutes".to_string(), reading_minutes(note.words).into()),
+        ("links".to_string(), Json::Array(note_links)),
+    ])
+}
+
+/// 指向 name 的反向链接，按来源文件名与行号排序
+///
+/// 每项为 `{"name", "path", "title", "line"}`，字段为来源笔记的文件名、路径、标题与链接所在行。
+pub fn backlinks_to_json(links: &LinkIndex, name: &str) -> Json {
+    let backlinks = links.backlinks();
+    let items = backlinks
+        .get(name)
+        .map(|sources| {
+            sources
+                .iter()
+                .map(|link| {
+                    Json::Object(vec![
+                        ("name".to_string(), link.source_name.as_str().into()),
+                        ("path".to_string(), note_file_name(&link.source_name).into()),
+                        ("title".to_string(), link.source_title.as_str().into()),
+                        ("line".to_string(), link.line.into()),
+                    ])
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    Json::Array(items)
+}
+
+/// 导出全部笔记的 CSV，按文件名排序，每篇笔记一行
+///
+/// 列为 path、title、date、time、tags、words，第一行为表头。标签以 `;` 连接，
+/// 没有标签的笔记与 JSON 导出一样为 NeedTag；没有创建时间的笔记 date 与 time 为空。
+pub fn to_csv(vault: &Vault) -> String {
+    let mut notes: Vec<&NoteMeta> = vault.published().collect();
+    notes.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut out = String::from("path,title,date,time,tags,words\r\n");
+    for note in notes {
+        let (date, time) = match &note.created {
+            Some((date, time)) => (date.to_string(), time.clone()),
+            None => (String::new(), String::new()),
+        };
+        let row = [
+            note_file_name(&note.name),
+            note.title.clone(),
+            date,
+            time,
+            note.tags.join(";"),
+            note.words.to_string(),
+        ];
+        let cells: Vec<String> = row.iter().map(|cell| csv_field(cell)).collect();
+        out.push_str(&cells.join(","));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+/// 导出标签层级的 OPML 大纲，可导入大纲与思维导图软件
+///
+/// ```text
+/// <outline text="rust">
+///   <outline text="async">
+///     <outline text="笔记A" type="link" url="a.md"/>
+///   </outline>
+///   <outline text="笔记B" type="link" url="b.md"/>
+/// </outline>
+/// ```
+///
+/// 层级标签 `rust/async` 嵌套在 `rust` 之下，每个标签先列出子标签，再列出直接带有该标签的笔记；
+/// 标签与笔记均按名称排序。笔记的 url 为相对 vault 的路径，没有标签的笔记不会列出。
+pub fn to_opml(vault: &Vault) -> String {
+    let mut tagged: BTreeMap<&str, Vec<&NoteMeta>> = BTreeMap::new();
+    for note in vault.published() {
+        for tag in note.tags.iter().filter(|tag| *tag != NEED_TAG) {
+            let levels = tag_levels(tag);
+            for level in &levels {
+                tagged.entry(level).or_default();
+            }
+            if let Some(tag) = levels.last() {
+                tagged.entry(tag).or_default().push(note);
+            }
+        }
+    }
+    for notes in tagged.values_mut() {
+        notes.sort_by(|a, b| a.name.cmp(&b.name));
+        notes.dedup_by(|a, b| a.name == b.name);
+    }
+
+    let mut out = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<opml version=\"2.0\">\n\
+         <head>\n  <title>标签</title>\n</head>\n<body>\n",
+    );
+    opml_outline(&mut out, &tagged, None, 1);
+    out.push_str("</body>\n</opml>\n");
+    out
+}
+
+// parent 下的子标签及其笔记，parent 为 None 时为顶层标签
+fn opml_outline(
+    out: &mut String,
+    tagged: &BTreeMap<&str, Vec<&NoteMeta>>,
+    parent: Option<&str>,
+    depth: usize,
+) {
+    let indent = "  ".repeat(depth);
+    let children = tagged.keys().filter(|tag| match parent {
+        None => !tag.contains('/'),
+        Some(parent) => tag
+            .strip_prefix(parent)
+            .and_then(|rest| rest.strip_prefix('/'))
+            .is_some_and(|rest| !rest.contains('/')),
+    });
+    for tag in children {
+        let leaf = tag.rsplit('/').next().unwrap_or(tag);
+        let _ = writeln!(out, "{}<outline text=\"{}\">", indent, escape(leaf));
+        opml_outline(out, tagged, Some(tag), depth + 1);
+        for note in &tagged[tag] {
+            let _ = writeln!(
+                out,
+                "{}  <outline text=\"{}\" type=\"link\" url=\"{}\"/>",
+                indent,
+                escape(&note.title),
+                escape(&encode_url(&note_file_name(&note.name)))
+            );
+        }
+        let _ = writeln!(out, "{}</outline>", indent);
+    }
+}
+
+// 含逗号、引号或换行的字段加双引号，其中的引号写两遍（RFC 4180）
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
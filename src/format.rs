@@ -0,0 +1,392 @@
+// 多列对齐输出
+
+use std::cmp::max;
+use std::env;
+use std::fs::File;
+use std::process::Command;
+
+/// 列内的对齐方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Align {
+    #[default]
+    Left,
+    Right,
+    Center,
+}
+
+/// 多列输出的列数
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Columns {
+    /// 固定列数
+    Fixed(usize),
+    /// 在给定的显示宽度内放下尽可能多的列
+    Fit(usize),
+}
+
+impl Columns {
+    /// 按当前终端宽度排列，不在终端中运行时按 80 列
+    pub fn auto() -> Columns {
+        Columns::Fit(terminal_width().unwrap_or(80))
+    }
+
+    /// 对 cells 实际使用的列数，至少为 1
+    pub fn resolve<S: AsRef<str>>(self, cells: &[S], padding: usize) -> usize {
+        match self {
+            Columns::Fixed(n) => n.max(1),
+            Columns::Fit(width) => fit_columns(cells, width, padding),
+        }
+    }
+}
+
+/// 将单元格排成等宽的多列；每个单元格整体占一格，其中的空白不会把它拆开，
+/// 补齐宽度的空格总是加在单元格之外
+pub struct ColumnFormatter {
+    columns_per_row: usize,
+    column_padding: usize,
+    aligns: Vec<Align>,
+}
+
+impl ColumnFormatter {
+    /// 每行 columns_per_row 列（至少 1 列），列间距默认为 2，各列左对齐
+    pub fn new(columns_per_row: usize) -> Self {
+        let columns_per_row = columns_per_row.max(1);
+        Self {
+            columns_per_row,
+            column_padding: 2, // 默认列间距
+            aligns: vec![Align::Left; columns_per_row],
+        }
+    }
+
+    /// 设置列间距
+    pub fn with_padding(mut self, padding: usize) -> Self {
+        self.column_padding = padding;
+        self
+    }
+
+    /// 设置第 column 列的对齐方式，超出列数时忽略
+    pub fn with_align(mut self, column: usize, align: Align) -> Self {
+        if column < self.aligns.len() {
+            self.aligns[column] = align;
+        }
+        self
+    }
+
+    /// 按终端显示宽度对齐，每行末尾换行
+    pub fn format<S: AsRef<str>>(&self, cells: &[S]) -> String {
+        let words: Vec<&str> = cells.iter().map(AsRef::as_ref).collect();
+
+        if words.is_empty() {
+            return String::new();
+        }
+
+        // 计算每列最大宽度
+        let mut col_widths = vec![0; self.columns_per_row];
+
+        for (i, word) in words.iter().enumerate() {
+            let col_index = i % self.columns_per_row;
+            col_widths[col_index] = max(col_widths[col_index], display_width(word));
+        }
+
+        // 构建输出
+        let mut output = String::new();
+        let padding_str = " ".repeat(self.column_padding);
+
+        for (i, word) in words.iter().enumerate() {
+            let col_index = i % self.columns_per_row;
+
+            // 计算需要填充的空格数，按对齐方式分到左右两侧
+            let padding_needed = col_widths[col_index].saturating_sub(display_width(word));
+            let (left, right) = match self.aligns[col_index] {
+                Align::Left => (0, padding_needed),
+                Align::Right => (padding_needed, 0),
+                Align::Center => (padding_needed / 2, padding_needed - padding_needed / 2),
+            };
+
+            output.push_str(&" ".repeat(left));
+            output.push_str(word);
+            output.push_str(&" ".repeat(right));
+
+            // 添加列间距或换行
+            if col_index < self.columns_per_row - 1 {
+                output.push_str(&padding_str);
+            } else {
+                output.push('\n');
+            }
+        }
+
+        // 确保最后有换行
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+
+        output
+    }
+}
+
+/// 将表头与各行排成纯文本表格，表头下方以 - 分隔，各列按 aligns 对齐（缺省左对齐）
+pub fn format_table(header: &[&str], rows: &[Vec<String>], aligns: &[Align]) -> String {
+    let columns = header.len();
+    let mut widths: Vec<usize> = header.iter().map(|h| display_width(h)).collect();
+    for row in rows {
+        for (i, cell) in row.iter().take(columns).enumerate() {
+            widths[i] = max(widths[i], display_width(cell));
+        }
+    }
+
+    let mut output = String::new();
+    let mut push_row = |cells: &mut dyn Iterator<Item = &str>| {
+        let mut line = String::new();
+        for (i, cell) in cells.take(columns).enumerate() {
+            let padding = widths[i].saturating_sub(display_width(cell));
+            if i > 0 {
+                line.push_str("  ");
+            }
+            match aligns.get(i).copied().unwrap_or_default() {
+                Align::Left => {
+                    line.push_str(cell);
+                    line.push_str(&" ".repeat(padding));
+                }
+                Align::Right => {
+                    line.push_str(&" ".repeat(padding));
+                    line.push_str(cell);
+                }
+                Align::Center => {
+                    line.push_str(&" ".repeat(padding / 2));
+                    line.push_str(cell);
+                    line.push_str(&" ".repeat(padding - padding / 2));
+                }
+            }
+        }
+        output.push_str(line.trim_end());
+        output.push('\n');
+    };
+
+    push_row(&mut header.iter().copied());
+    let rule: Vec<String> = widths.iter().map(|w| "-".repeat(*w)).collect();
+    push_row(&mut rule.iter().map(String::as_str));
+    for row in rows {
+        push_row(&mut row.iter().map(String::as_str));
+    }
+    output
+}
+
+/// 在 width 的显示宽度内，cells 最多能排成几列，至少为 1
+pub fn fit_columns<S: AsRef<str>>(cells: &[S], width: usize, padding: usize) -> usize {
+    let widths: Vec<usize> = cells
+        .iter()
+        .map(|cell| display_width(cell.as_ref()))
+        .collect();
+    for columns in (2..=widths.len()).rev() {
+        let mut col_widths = vec![0; columns];
+        for (i, w) in widths.iter().enumerate() {
+            col_widths[i % columns] = max(col_widths[i % columns], *w);
+        }
+        let total = col_widths.iter().sum::<usize>() + padding * (columns - 1);
+        if total <= width {
+            return columns;
+        }
+    }
+    1
+}
+
+/// 当前终端的宽度，依次尝试 $COLUMNS 与 `stty size`，无法确定时返回 None
+pub fn terminal_width() -> Option<usize> {
+    if let Some(width) = env::var("COLUMNS").ok().and_then(|v| v.parse().ok())
+        && width > 0
+    {
+        return Some(width);
+    }
+    terminal_size().map(|(_, width)| width)
+}
+
+/// 当前终端的（行数，列数），由 `stty size` 取得，无法确定时返回 None
+pub fn terminal_size() -> Option<(usize, usize)> {
+    let tty = File::open("/dev/tty").ok()?;
+    let output = Command::new("stty").arg("size").stdin(tty).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    // 输出格式为 "<行数> <列数>"
+    let text = String::from_utf8(output.stdout).ok()?;
+    let mut numbers = text.split_whitespace().map(|n| n.parse::<usize>().ok());
+    let rows = numbers.next()??;
+    let columns = numbers.next()??;
+    (rows > 0 && columns > 0).then_some((rows, columns))
+}
+
+/// 字符串在终端中占用的列数
+pub fn display_width(s: &str) -> usize {
+    s.chars().map(char_width).sum()
+}
+
+/// 单个字符在终端中占用的列数：控制字符、组合字符与零宽字符为 0，东亚宽字符为 2
+pub fn char_width(c: char) -> usize {
+    let code = c as u32;
+    if c.is_ascii() {
+        return usize::from(!c.is_ascii_control());
+    }
+    if c.is_control() || in_ranges(code, ZERO_WIDTH) {
+        0
+    } else if in_ranges(code, WIDE) {
+        2
+    } else {
+        1
+    }
+}
+
+fn in_ranges(code: u32, ranges: &[(u32, u32)]) -> bool {
+    ranges
+        .iter()
+        .any(|&(start, end)| code >= start && code <= end)
+}
+
+// 组合字符、变体选择符与零宽字符
+const ZERO_WIDTH: &[(u32, u32)] = &[
+    (0x0300, 0x036F), // 组合附加符号
+    (0x0483, 0x0489),
+    (0x0591, 0x05BD),
+    (0x0610, 0x061A),
+    (0x064B, 0x065F),
+    (0x0E31, 0x0E31),
+    (0x0E34, 0x0E3A),
+    (0x0E47, 0x0E4E),
+    (0x1AB0, 0x1AFF),
+    (0x1DC0, 0x1DFF),
+    (0x200B, 0x200F), // 零宽空格、连接符与方向标记
+    (0x202A, 0x202E),
+    (0x2060, 0x2064),
+    (0x20D0, 0x20FF),
+    (0x302A, 0x302D), // 汉字声调符号
+    (0x3099, 0x309A), // 假名浊点
+    (0xFE00, 0xFE0F), // 变体选择符
+    (0xFE20, 0xFE2F),
+    (0xFEFF, 0xFEFF), // BOM
+    (0xE0100, 0xE01EF),
+];
+
+// 东亚宽字符与全角字符
+const WIDE: &[(u32, u32)] = &[
+    (0x1100, 0x115F), // 谚文字母
+    (0x231A, 0x231B),
+    (0x2329, 0x232A),
+    (0x23E9, 0x23EC),
+    (0x23F0, 0x23F0),
+    (0x23F3, 0x23F3),
+    (0x25FD, 0x25FE),
+    (0x2614, 0x2615),
+    (0x2648, 0x2653),
+    (0x267F, 0x267F),
+    (0x2693, 0x2693),
+    (0x26A1, 0x26A1),
+    (0x26AA, 0x26AB),
+    (0x26BD, 0x26BE),
+    (0x26C4, 0x26C5),
+    (0x26CE, 0x26CE),
+    (0x26D4, 0x26D4),
+    (0x26EA, 0x26EA),
+    (0x26F2, 0x26F3),
+    (0x26F5, 0x26F5),
+    (0x26FA, 0x26FA),
+    (0x26FD, 0x26FD),
+    (0x2705, 0x2705),
+    (0x270A, 0x270B),
+    (0x2728, 0x2728),
+    (0x274C, 0x274C),
+    (0x274E, 0x274E),
+    (0x2753, 0x2755),
+    (0x2757, 0x2757),
+    (0x2795, 0x2797),
+    (0x27B0, 0x27B0),
+    (0x27BF, 0x27BF),
+    (0x2B1B, 0x2B1C),
+    (0x2B50, 0x2B50),
+    (0x2B55, 0x2B55),
+    (0x2E80, 0x303E), // CJK 部首、康熙部首、CJK 符号和标点
+    (0x3041, 0x33FF), // 假名、注音、谚文兼容字母、CJK 兼容字符
+    (0x3400, 0x4DBF), // CJK 扩展 A
+    (0x4E00, 0x9FFF), // CJK 统一表意文字
+    (0xA000, 0xA4CF), // 彝文
+    (0xA960, 0xA97F),
+    (0xAC00, 0xD7A3), // 谚文音节
+    (0xF900, 0xFAFF), // CJK 兼容表意文字
+    (0xFE10, 0xFE19), // 竖排标点
+    (0xFE30, 0xFE6F), // CJK 兼容形式、小写变体
+    (0xFF00, 0xFF60), // 全角 ASCII 与标点
+    (0xFFE0, 0xFFE6), // 全角符号
+    (0x16FE0, 0x16FE4),
+    (0x17000, 0x18CFF), // 西夏文
+    (0x1B000, 0x1B2FF), // 假名补充
+    (0x1F004, 0x1F004),
+    (0x1F0CF, 0x1F0CF),
+    (0x1F18E, 0x1F18E),
+    (0x1F191, 0x1F19A),
+    (0x1F200, 0x1F251),
+    (0x1F300, 0x1F320), // 表情符号
+    (0x1F32D, 0x1F335),
+    (0x1F337, 0x1F37C),
+    (0x1F37E, 0x1F393),
+    (0x1F3A0, 0x1F3CA),
+    (0x1F3CF, 0x1F3D3),
+    (0x1F3E0, 0x1F3F0),
+    (0x1F3F4, 0x1F3F4),
+    (0x1F3F8, 0x1F43E),
+    (0x1F440, 0x1F440),
+    (0x1F442, 0x1F4FC),
+    (0x1F4FF, 0x1F53D),
+    (0x1F54B, 0x1F54E),
+    (0x1F550, 0x1F567),
+    (0x1F57A, 0x1F57A),
+    (0x1F595, 0x1F596),
+    (0x1F5A4, 0x1F5A4),
+    (0x1F5FB, 0x1F64F),
+    (0x1F680, 0x1F6C5),
+    (0x1F6CC, 0x1F6CC),
+    (0x1F6D0, 0x1F6D2),
+    (0x1F6D5, 0x1F6D7),
+    (0x1F6EB, 0x1F6EC),
+    (0x1F6F4, 0x1F6FC),
+    (0x1F7E0, 0x1F7EB),
+    (0x1F90C, 0x1F93A),
+    (0x1F93C, 0x1F945),
+    (0x1F947, 0x1F9FF),
+    (0x1FA70, 0x1FAFF),
+    (0x20000, 0x2FFFD), // CJK 扩展 B 及之后
+    (0x30000, 0x3FFFD), // CJK 扩展 G 及之后
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cells_are_laid_out_whole() {
+        let cells = [
+            "[[tags/machine learning|machine learning]](2)",
+            "[[tags/rust|rust]](10)",
+            "[rust](tags/rust.md)(1)",
+        ];
+        let output = ColumnFormatter::new(2).format(&cells);
+        assert_eq!(
+            output,
+            "[[tags/machine learning|machine learning]](2)  [[tags/rust|rust]](10)\n\
+             [rust](tags/rust.md)(1)                        \n"
+        );
+        // 补齐的空格在链接之外
+        assert!(output.contains("(1)  "));
+        assert!(!output.contains("[rust ") && !output.contains("rust  ]"));
+    }
+
+    #[test]
+    fn fit_counts_whole_cells() {
+        let cells = ["a b c d", "e"];
+        assert_eq!(fit_columns(&cells, 10, 2), 2);
+        assert_eq!(fit_columns(&cells, 9, 2), 1);
+        assert_eq!(Columns::Fixed(0).resolve(&cells, 2), 1);
+    }
+
+    #[test]
+    fn wide_characters_count_twice() {
+        let output = ColumnFormatter::new(2).format(&["标签(1)", "ab(2)", "c(3)"]);
+        assert_eq!(output, "标签(1)  ab(2)\nc(3)     \n");
+    }
+}
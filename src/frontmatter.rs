@@ -0,0 +1,573 @@
+// 文件头（frontmatter）解析
+//
+// 文件头是笔记开头由两行 `---` 包围的 YAML 块。这里只实现笔记中会用到的子集：
+//   - `键: 值` 形式的标量，值可以带单引号或双引号
+//   - 行内列表 `[a, b]` 与缩进的块列表 `- a`
+//   - 块标量 `|` 与 `>`
+//   - `#` 开头的注释与空行
+// 嵌套映射会被跳过，对应的值记为 Null。
+
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Scalar(String),
+    List(Vec<String>),
+}
+
+impl Value {
+    /// 标量返回自身，列表返回 None
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::Scalar(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// 标量按空白拆分，列表逐项返回
+    pub fn words(&self) -> Vec<String> {
+        match self {
+            Value::Null => Vec::new(),
+            Value::Scalar(s) => s.split_whitespace().map(str::to_string).collect(),
+            Value::List(items) => items
+                .iter()
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect(),
+        }
+    }
+
+    /// 标签字段：标签中不能有空白，标量与列表的每一项都按空白与逗号拆分
+    ///
+    /// `Tags: rust cli`、`Tags: rust, cli`、`Tags: [rust, cli]` 与块列表得到相同的结果；
+    /// `Tags: machine learning` 与 `Tags: [machine learning]` 同样是两个标签。
+    pub fn tags(&self) -> Vec<String> {
+        let items: &[String] = match self {
+            Value::Null => &[],
+            Value::Scalar(s) => std::slice::from_ref(s),
+            Value::List(items) => items,
+        };
+        items
+            .iter()
+            .flat_map(|item| item.split(|c: char| c.is_whitespace() || matches!(c, ',' | '，')))
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// 标量按逗号拆分，列表逐项返回，用于可能含空格的条目
+    pub fn items(&self) -> Vec<String> {
+        match self {
+            Value::Null => Vec::new(),
+            Value::Scalar(s) => s
+                .split([',', '，'])
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect(),
+            Value::List(_) => self.words(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Frontmatter {
+    // 保留字段在文件中的顺序
+    fields: Vec<(String, Value)>,
+}
+
+impl Frontmatter {
+    /// 键名不区分大小写，`Title` 与 `title` 等价
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.fields
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v)
+    }
+
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        self.get(key).and_then(Value::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+}
+
+#[derive(Debug)]
+pub struct ParseError {
+    /// 相对文件头起始处的行号，从 1 开始
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "文件头第 {} 行: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// 将内容拆分为（文件头，正文）；没有文件头时返回 None
+pub fn split(content: &str) -> Option<(&str, &str)> {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    let mut lines = content.split_inclusive('\n');
+    let first = lines.next()?;
+    if first.trim_end() != "---" {
+        return None;
+    }
+
+    let start = first.len();
+    let mut offset = start;
+    for line in lines {
+        let trimmed = line.trim_end();
+        if trimmed == "---" || trimmed == "..." {
+            return Some((&content[start..offset], &content[offset + line.len()..]));
+        }
+        offset += line.len();
+    }
+
+    // 没有结束标记，不视为文件头
+    None
+}
+
+/// 解析内容开头的文件头；没有文件头时返回 Ok(None)
+pub fn parse(content: &str) -> Result<Option<(Frontmatter, &str)>, ParseError> {
+    match split(content) {
+        Some((yaml, body)) => Ok(Some((parse_yaml(yaml)?, body))),
+        None => Ok(None),
+    }
+}
+
+fn is_blank_or_comment(line: &str) -> bool {
+    let trimmed = line.trim();
+    trimmed.is_empty() || trimmed.starts_with('#')
+}
+
+fn is_indented(line: &str) -> bool {
+    line.starts_with(' ') || line.starts_with('\t')
+}
+
+// 块列表项 `- a`，返回去掉 `-` 后的部分
+fn list_item(line: &str) -> Option<&str> {
+    let trimmed = line.trim_start();
+    if trimmed == "-" {
+        Some("")
+    } else {
+        trimmed.strip_prefix("- ")
+    }
+}
+
+pub fn parse_yaml(yaml: &str) -> Result<Frontmatter, ParseError> {
+    let lines: Vec<&str> = yaml.lines().collect();
+    let mut fields = Vec::new();
+    let mut i = 0;
+
+    while i < lines.len() {
+        let line = lines[i];
+        let line_no = i + 1;
+        i += 1;
+
+        if is_blank_or_comment(line) {
+            continue;
+        }
+        if is_indented(line) || list_item(line).is_some() {
+            return Err(ParseError {
+                line: line_no,
+                message: format!("不属于任何键的内容 '{}'", line.trim()),
+            });
+        }
+
+        let Some((key, rest)) = line.split_once(':') else {
+            return Err(ParseError {
+                line: line_no,
+                message: format!("缺少 ':' 的行 '{}'", line.trim()),
+            });
+        };
+        let key = unquote_key(key.trim());
+        if key.is_empty() {
+            return Err(ParseError {
+                line: line_no,
+                message: "键名为空".to_string(),
+            });
+        }
+
+        // 收集属于该键的后续行：缩进行、空行与顶格的列表项
+        let child_start = i;
+        while i < lines.len()
+            && (is_indented(lines[i])
+                || lines[i].trim().is_empty()
+                || list_item(lines[i]).is_some())
+        {
+            i += 1;
+        }
+        let children = &lines[child_start..i];
+
+        let rest = rest.trim();
+        let value = if let Some(indicator) = block_indicator(rest) {
+            Value::Scalar(block_scalar(children, indicator))
+        } else {
+            let inline = if rest.is_empty() {
+                None
+            } else {
+                Some(parse_inline(rest).map_err(|message| ParseError {
+                    line: line_no,
+                    message,
+                })?)
+            };
+            let items = parse_block_list(children, child_start)?;
+            match (inline, items) {
+                (None, None) => {
+                    // 嵌套映射或空值
+                    Value::Null
+                }
+                // 缩进的续行属于多行标量，折叠为一行
+                (Some(Value::Scalar(first)), None) => {
+                    let mut folded = first;
+                    for line in children.iter().filter(|l| !is_blank_or_comment(l)) {
+                        folded.push(' ');
+                        folded.push_str(line.trim());
+                    }
+                    Value::Scalar(folded)
+                }
+                (Some(value), None) => value,
+                (None, Some(items)) => Value::List(items),
+                // 兼容旧格式 `Tags: a b` 后再跟 `  - c`，行内部分按空白拆分
+                (Some(value), Some(items)) => {
+                    let mut merged = value.words();
+                    merged.extend(items);
+                    Value::List(merged)
+                }
+            }
+        };
+
+        fields.push((key, value));
+    }
+
+    Ok(Frontmatter { fields })
+}
+
+fn unquote_key(key: &str) -> String {
+    for quote in ['"', '\''] {
+        if key.len() >= 2 && key.starts_with(quote) && key.ends_with(quote) {
+            return key[1..key.len() - 1].to_string();
+        }
+    }
+    key.to_string()
+}
+
+// `|`、`>` 及其 `-`/`+` 变体
+fn block_indicator(rest: &str) -> Option<char> {
+    let rest = strip_comment(rest).trim();
+    match rest {
+        "|" | "|-" | "|+" => Some('|'),
+        ">" | ">-" | ">+" => Some('>'),
+        _ => None,
+    }
+}
+
+// 缩进只计行首的半角空格与制表符，全角空格等属于内容，切片总落在字符边界上
+fn block_scalar(children: &[&str], indicator: char) -> String {
+    let leading = |l: &str| l.len() - l.trim_start_matches([' ', '\t']).len();
+    let indent = children
+        .iter()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| leading(l))
+        .min()
+        .unwrap_or(0);
+    let lines: Vec<&str> = children
+        .iter()
+        .map(|l| &l[leading(l).min(indent)..])
+        .collect();
+    let separator = if indicator == '|' { "\n" } else { " " };
+    lines.join(separator).trim_end().to_string()
+}
+
+// 子行中若含有列表项则解析为列表；含有其它内容（嵌套映射）时返回 None
+fn parse_block_list(
+    children: &[&str],
+    first_line: usize,
+) -> Result<Option<Vec<String>>, ParseError> {
+    let mut items = Vec::new();
+    for (offset, line) in children.iter().enumerate() {
+        if is_blank_or_comment(line) {
+            continue;
+        }
+        match list_item(line) {
+            Some(item) => items.push(parse_scalar(item).map_err(|message| ParseError {
+                line: first_line + offset + 1,
+                message,
+            })?),
+            // 嵌套映射，忽略
+            None => return Ok(None),
+        }
+    }
+    Ok(if items.is_empty() { None } else { Some(items) })
+}
+
+// 行内值：流式列表或标量
+fn parse_inline(raw: &str) -> Result<Value, String> {
+    let raw = raw.trim();
+    if raw.starts_with('[') {
+        let mut inner = strip_comment(raw).trim();
+        inner = inner
+            .strip_prefix('[')
+            .and_then(|s| s.strip_suffix(']'))
+            .ok_or_else(|| format!("未闭合的列表 '{}'", raw))?;
+        let items = split_flow(inner)
+            .into_iter()
+            .map(|item| parse_scalar(&item))
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .filter(|s| !s.is_empty())
+            .collect();
+        return Ok(Value::List(items));
+    }
+
+    let scalar = parse_scalar(raw)?;
+    Ok(match scalar.as_str() {
+        "" | "~" | "null" => Value::Null,
+        _ => Value::Scalar(scalar),
+    })
+}
+
+// 按顶层逗号拆分流式列表，忽略引号内的逗号
+fn split_flow(inner: &str) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut current = String::new();
+    let mut quote = None;
+    for c in inner.chars() {
+        match (quote, c) {
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                current.push(c);
+            }
+            (Some(q), _) if c == q => {
+                quote = None;
+                current.push(c);
+            }
+            (None, ',') => items.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    if !current.trim().is_empty() {
+        items.push(current);
+    }
+    items
+}
+
+// 去掉未加引号的值末尾的 ` #注释`
+fn strip_comment(raw: &str) -> &str {
+    match raw.find(" #") {
+        Some(pos) => &raw[..pos],
+        None => raw,
+    }
+}
+
+fn parse_scalar(raw: &str) -> Result<String, String> {
+    let raw = raw.trim();
+    if let Some(inner) = raw.strip_prefix('"') {
+        let mut value = String::new();
+        let mut chars = inner.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => return Ok(value),
+                '\\' => match chars.next() {
+                    Some('n') => value.push('\n'),
+                    Some('t') => value.push('\t'),
+                    Some(other) => value.push(other),
+                    None => break,
+                },
+                _ => value.push(c),
+            }
+        }
+        return Err(format!("未闭合的双引号 '{}'", raw));
+    }
+    if let Some(inner) = raw.strip_prefix('\'') {
+        let mut value = String::new();
+        let mut chars = inner.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c == '\'' {
+                // 单引号内 '' 表示一个单引号
+                if chars.peek() == Some(&'\'') {
+                    chars.next();
+                    value.push('\'');
+                } else {
+                    return Ok(value);
+                }
+            } else {
+                value.push(c);
+            }
+        }
+        return Err(format!("未闭合的单引号 '{}'", raw));
+    }
+    Ok(strip_comment(raw).trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(yaml: &str) -> Vec<String> {
+        parse_yaml(yaml)
+            .unwrap()
+            .get("Tags")
+            .map_or_else(Vec::new, Value::tags)
+    }
+
+    #[test]
+    fn every_tag_form_gives_the_same_tags() {
+        let expected = vec!["rust".to_string(), "cli".to_string()];
+        for yaml in [
+            "Tags: rust cli",
+            "Tags: rust, cli",
+            "Tags: rust，cli",
+            "Tags: [rust, cli]",
+            "Tags: [rust cli]",
+            "Tags:\n  - rust\n  - cli",
+            "Tags:\n  - rust cli",
+        ] {
+            assert_eq!(tags(yaml), expected, "{}", yaml);
+        }
+    }
+
+    #[test]
+    fn tags_never_contain_whitespace() {
+        let expected = vec!["machine".to_string(), "learning".to_string()];
+        assert_eq!(tags("Tags: machine learning"), expected);
+        assert_eq!(tags("Tags: [machine learning]"), expected);
+        assert_eq!(tags("Tags: ['machine learning']"), expected);
+        assert_eq!(tags("Tags:\n  - machine learning"), expected);
+        assert!(tags("Tags:").is_empty());
+        assert!(tags("Tags: []").is_empty());
+    }
+
+    fn scalar(s: &str) -> Value {
+        Value::Scalar(s.to_string())
+    }
+
+    fn list(items: &[&str]) -> Value {
+        Value::List(items.iter().map(|s| s.to_string()).collect())
+    }
+
+    #[test]
+    fn split_needs_both_markers() {
+        assert_eq!(
+            split("---\nTitle: a\n---\nbody\n"),
+            Some(("Title: a\n", "body\n"))
+        );
+        assert_eq!(split("---\nTitle: a\n...\n"), Some(("Title: a\n", "")));
+        assert_eq!(split("\u{feff}---\n---\nbody"), Some(("", "body")));
+        assert_eq!(
+            split("---\r\nTitle: a\r\n---\r\n"),
+            Some(("Title: a\r\n", ""))
+        );
+        assert_eq!(split("---\nTitle: a\n"), None);
+        assert_eq!(split("Title: a\n---\n"), None);
+        assert_eq!(split(""), None);
+        assert!(parse("# 标题\n").unwrap().is_none());
+    }
+
+    #[test]
+    fn scalars_and_quotes() {
+        let header = parse_yaml(
+            "Title: 简单的标题\n\
+             Quoted: \"a: b # c\"\n\
+             Single: 'it''s'\n\
+             Escaped: \"a\\\"b\\n\"\n\
+             Comment: value # 注释\n\
+             Hash: a#b\n\
+             'Key with space': x\n\
+             Empty:\n\
+             Tilde: ~\n",
+        )
+        .unwrap();
+        assert_eq!(header.get("title"), Some(&scalar("简单的标题")));
+        assert_eq!(header.get_str("Quoted"), Some("a: b # c"));
+        assert_eq!(header.get_str("Single"), Some("it's"));
+        assert_eq!(header.get_str("Escaped"), Some("a\"b\n"));
+        assert_eq!(header.get_str("Comment"), Some("value"));
+        assert_eq!(header.get_str("Hash"), Some("a#b"));
+        assert_eq!(header.get_str("Key with space"), Some("x"));
+        assert_eq!(header.get("Empty"), Some(&Value::Null));
+        assert_eq!(header.get("Tilde"), Some(&Value::Null));
+        assert_eq!(header.get("Missing"), None);
+        assert_eq!(header.len(), 9);
+    }
+
+    #[test]
+    fn lists() {
+        let header = parse_yaml(
+            "Flow: [a, 'b, c', \"d\"]  # 注释\n\
+             Block:\n  - a\n\n  # 注释\n  - 'b c'\n\
+             Flush:\n- a\n- b\n\
+             Mixed: a b\n  - c\n",
+        )
+        .unwrap();
+        assert_eq!(header.get("Flow"), Some(&list(&["a", "b, c", "d"])));
+        assert_eq!(header.get("Block"), Some(&list(&["a", "b c"])));
+        assert_eq!(header.get("Flush"), Some(&list(&["a", "b"])));
+        assert_eq!(header.get("Mixed"), Some(&list(&["a", "b", "c"])));
+    }
+
+    #[test]
+    fn block_and_folded_scalars() {
+        let header = parse_yaml(
+            "Literal: |\n  第一行\n    缩进\n  第三行\n\
+             Folded: >-\n  a\n  b\n\
+             Plain: 一行\n  续行\n",
+        )
+        .unwrap();
+        assert_eq!(header.get_str("Literal"), Some("第一行\n  缩进\n第三行"));
+        assert_eq!(header.get_str("Folded"), Some("a b"));
+        assert_eq!(header.get_str("Plain"), Some("一行 续行"));
+    }
+
+    #[test]
+    fn full_width_spaces_are_content_not_indent() {
+        let header =
+            parse_yaml("Summary: |\n  \u{3000}\u{3000}首行缩进的段落\n    第二行\n").unwrap();
+        assert_eq!(
+            header.get_str("Summary"),
+            Some("\u{3000}\u{3000}首行缩进的段落\n  第二行")
+        );
+    }
+
+    #[test]
+    fn nested_mappings_are_skipped() {
+        let header = parse_yaml("Meta:\n  author: x\n  - y\nTitle: t\n").unwrap();
+        assert_eq!(header.get("Meta"), Some(&Value::Null));
+        assert_eq!(header.get_str("Title"), Some("t"));
+    }
+
+    #[test]
+    fn errors_name_the_line() {
+        let error = |yaml: &str| parse_yaml(yaml).unwrap_err().line;
+        assert_eq!(error("Title: a\nno colon\n"), 2);
+        assert_eq!(error("  indented: a\n"), 1);
+        assert_eq!(error("- a\n"), 1);
+        assert_eq!(error("# c\n: a\n"), 2);
+        assert_eq!(error("Title: \"open\n"), 1);
+        assert_eq!(error("Title: 'open\n"), 1);
+        assert_eq!(error("Tags: [a, b\n"), 1);
+        assert_eq!(error("Tags:\n  - a\n  - \"b\n"), 3);
+    }
+
+    #[test]
+    fn words_and_items() {
+        assert_eq!(scalar(" a  b ").words(), vec!["a", "b"]);
+        assert_eq!(list(&[" a b ", ""]).words(), vec!["a b"]);
+        assert!(Value::Null.words().is_empty());
+        assert_eq!(
+            scalar("张 三, 李四，王五").items(),
+            vec!["张 三", "李四", "王五"]
+        );
+        assert_eq!(list(&["张 三"]).items(), vec!["张 三"]);
+        assert_eq!(list(&["a"]).as_str(), None);
+    }
+}
@@ -0,0 +1,448 @@
+// 全文搜索索引：保存在 <目录>/.gtx/search/index.json，供 gtx search 按相关度排序
+//
+// 由 gtx index --with-search 建立与更新，未变化的笔记沿用上次的结果。
+// 不依赖外部搜索库，而是自行维护倒排信息：
+//   - 英文等按字母与数字连续的片段切词并转为小写
+//   - 中文、日文与韩文每个字为一个词，连续的字按短语匹配
+//   - 每篇笔记记录各词出现的位置，用于短语查询；短语不跨行
+// 相关度使用 BM25，查询中的各个词或短语都必须出现。
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::cache::{Cache, FileStamp};
+use crate::json::{self, Json};
+use crate::note::note_file_name;
+use crate::output::write_atomic;
+use crate::query;
+
+/// 搜索索引所在的目录，相对博客目录
+pub const SEARCH_DIR: &str = ".gtx/search";
+
+// 索引格式变化时递增，旧版本的索引会被重建
+const SEARCH_VERSION: u64 = 2;
+
+// BM25 的参数
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+
+/// 切词结果中的一个词
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+    /// 已转为小写
+    pub term: String,
+    /// 在原文中的字节范围
+    pub start: usize,
+    pub end: usize,
+}
+
+/// 全文搜索索引
+#[derive(Default)]
+pub struct SearchIndex {
+    /// 文件名（不含 .md） -> 笔记的词与位置
+    docs: HashMap<String, Doc>,
+}
+
+struct Doc {
+    stamp: FileStamp,
+    /// 词数
+    length: usize,
+    /// 词 -> 出现的位置，从小到大
+    terms: HashMap<String, Vec<u32>>,
+}
+
+impl SearchIndex {
+    /// 索引文件的位置
+    pub fn path(root: &Path) -> PathBuf {
+        root.join(SEARCH_DIR).join("index.json")
+    }
+
+    /// 索引不存在或无法解析时返回 None，gtx search 改为逐行搜索
+    pub fn load(root: &Path) -> Option<SearchIndex> {
+        let text = fs::read_to_string(Self::path(root)).ok()?;
+        let root = json::parse(&text).ok()?;
+        if root.get("version").and_then(Json::as_u64) != Some(SEARCH_VERSION) {
+            return None;
+        }
+
+        let mut index = SearchIndex::default();
+        for (name, entry) in root.get("docs")?.as_object()? {
+            let stamp = entry.get("stamp")?.as_array()?;
+            let mut terms = HashMap::new();
+            for (term, positions) in entry.get("terms")?.as_object()? {
+                let positions = positions
+                    .as_array()?
+                    .iter()
+                    .map(|pos| pos.as_u64().map(|pos| pos as u32))
+                    .collect::<Option<Vec<_>>>()?;
+                terms.insert(term.clone(), positions);
+            }
+            let doc = Doc {
+                stamp: FileStamp {
+                    mtime_secs: stamp.first()?.as_u64()?,
+                    mtime_nanos: stamp.get(1)?.as_u64()? as u32,
+                    size: stamp.get(2)?.as_u64()?,
+                },
+                length: entry.get("length")?.as_u64()? as usize,
+                terms,
+            };
+            index.docs.insert(name.clone(), doc);
+        }
+        Some(index)
+    }
+
+    /// 按缓存中的笔记更新 root 下的索引，只重新读取修改时间或大小变化的笔记
+    ///
+    /// 返回重新读取的笔记数。
+    pub fn update(root: &Path, cache: &Cache) -> io::Result<usize> {
+        let mut old = Self::load(root).unwrap_or_default();
+        let mut index = SearchIndex::default();
+        let mut updated = 0;
+        for (name, cached) in &cache.notes {
+            if let Some(doc) = old.docs.remove(name)
+                && doc.stamp == cached.stamp
+            {
+                index.docs.insert(name.clone(), doc);
+                continue;
+            }
+            let file_path = root.join(note_file_name(name));
+            let content = match fs::read_to_string(&file_path) {
+                Ok(content) => content,
+                Err(e) => {
+                    eprintln!("读取文件失败 {}: {}", file_path.display(), e);
+                    continue;
+                }
+            };
+            index
+                .docs
+                .insert(name.clone(), Doc::new(cached.stamp, &content));
+            updated += 1;
+        }
+
+        let path = Self::path(root);
+        fs::create_dir_all(path.parent().unwrap())?;
+        write_atomic(&path, &index.to_json().to_string())?;
+        Ok(updated)
+    }
+
+    fn to_json(&self) -> Json {
+        let mut names: Vec<&String> = self.docs.keys().collect();
+        names.sort();
+        let docs = names
+            .into_iter()
+            .map(|name| {
+                let doc = &self.docs[name];
+                let mut terms: Vec<(&String, &Vec<u32>)> = doc.terms.iter().collect();
+                terms.sort();
+                let terms = terms
+                    .into_iter()
+                    .map(|(term, positions)| {
+                        let positions: Vec<u64> = positions.iter().map(|&pos| pos as u64).collect();
+                        (term.clone(), positions.into())
+                    })
+                    .collect();
+                let entry = Json::Object(vec![
+                    (
+                        "stamp".to_string(),
+                        vec![
+                            doc.stamp.mtime_secs,
+                            doc.stamp.mtime_nanos as u64,
+                            doc.stamp.size,
+                        ]
+                        .into(),
+                    ),
+                    ("length".to_string(), doc.length.into()),
+                    ("terms".to_string(), Json::Object(terms)),
+                ]);
+                (name.clone(), entry)
+            })
+            .collect();
+        Json::Object(vec![
+            ("version".to_string(), SEARCH_VERSION.into()),
+            ("docs".to_string(), Json::Object(docs)),
+        ])
+    }
+
+    /// 含有全部词与短语的笔记及其 BM25 得分，按得分从高到低排序
+    pub fn search(&self, phrases: &[Vec<String>]) -> Vec<(String, f64)> {
+        if phrases.is_empty() || self.docs.is_empty() {
+            return Vec::new();
+        }
+        let total = self.docs.len() as f64;
+        let average = self.docs.values().map(|doc| doc.length).sum::<usize>() as f64 / total;
+
+        // 每篇笔记中各短语的出现次数
+        let counts: HashMap<&String, Vec<usize>> = self
+            .docs
+            .iter()
+            .map(|(name, doc)| {
+                let counts = phrases.iter().map(|phrase| doc.count(phrase)).collect();
+                (name, counts)
+            })
+            .collect();
+        let frequencies: Vec<usize> = (0..phrases.len())
+            .map(|i| counts.values().filter(|counts| counts[i] > 0).count())
+            .collect();
+
+        let mut results: Vec<(String, f64)> = counts
+            .into_iter()
+            .filter(|(_, counts)| counts.iter().all(|&count| count > 0))
+            .map(|(name, counts)| {
+                let length = self.docs[name].length as f64;
+                let score = counts
+                    .iter()
+                    .zip(&frequencies)
+                    .map(|(&count, &frequency)| {
+                        let frequency = frequency as f64;
+                        let idf = (1.0 + (total - frequency + 0.5) / (frequency + 0.5)).ln();
+                        let count = count as f64;
+                        idf * count * (K1 + 1.0)
+                            / (count + K1 * (1.0 - B + B * length / average.max(1.0)))
+                    })
+                    .sum();
+                (name.clone(), score)
+            })
+            .collect();
+        results.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        results
+    }
+}
+
+impl Doc {
+    fn new(stamp: FileStamp, content: &str) -> Doc {
+        // 查询块与其结果不是笔记自身的文字，不计入
+        let content = query::blank_blocks(content);
+        // 行与行之间空出一个位置，短语不会跨行匹配
+        let mut terms: HashMap<String, Vec<u32>> = HashMap::new();
+        let mut length = 0;
+        let mut pos = 0;
+        for line in content.lines() {
+            for token in tokenize(line) {
+                terms.entry(token.term).or_default().push(pos);
+                pos += 1;
+                length += 1;
+            }
+            pos += 1;
+        }
+        Doc {
+            stamp,
+            length,
+            terms,
+        }
+    }
+
+    // 短语在笔记中出现的次数
+    fn count(&self, phrase: &[String]) -> usize {
+        let Some(first) = phrase.first().and_then(|term| self.terms.get(term)) else {
+            return 0;
+        };
+        let rest: Option<Vec<&Vec<u32>>> = phrase[1..]
+            .iter()
+            .map(|term| self.terms.get(term))
+            .collect();
+        let Some(rest) = rest else {
+            return 0;
+        };
+        first
+            .iter()
+            .filter(|&&pos| {
+                rest.iter()
+                    .enumerate()
+                    .all(|(i, positions)| positions.binary_search(&(pos + i as u32 + 1)).is_ok())
+            })
+            .count()
+    }
+}
+
+/// 把查询拆分为词与短语，双引号中的内容为一个短语
+///
+/// 未加引号但切出多个词的部分（如中文、`a-b`）也按短语匹配。
+pub fn parse_query(text: &str) -> Vec<Vec<String>> {
+    let mut phrases = Vec::new();
+    for (i, part) in text.split('"').enumerate() {
+        // 奇数段位于引号之内
+        let words: Vec<&str> = if i % 2 == 1 {
+            vec![part]
+        } else {
+            part.split_whitespace().collect()
+        };
+        for word in words {
+            let terms: Vec<String> = tokenize(word).into_iter().map(|token| token.term).collect();
+            if !terms.is_empty() {
+                phrases.push(terms);
+            }
+        }
+    }
+    phrases
+}
+
+/// 切词：字母与数字的连续片段为一个词，中日韩文字每个字为一个词，其余字符为分隔
+pub fn tokenize(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut word_start = None;
+    for (i, c) in text.char_indices() {
+        let cjk = is_cjk(c);
+        if c.is_alphanumeric() && !cjk {
+            word_start.get_or_insert(i);
+            continue;
+        }
+        if let Some(start) = word_start.take() {
+            tokens.push(Token {
+                term: text[start..i].to_lowercase(),
+                start,
+                end: i,
+            });
+        }
+        if cjk {
+            tokens.push(Token {
+                term: c.to_string(),
+                start: i,
+                end: i + c.len_utf8(),
+            });
+        }
+    }
+    if let Some(start) = word_start {
+        tokens.push(Token {
+            term: text[start..].to_lowercase(),
+            start,
+            end: text.len(),
+        });
+    }
+    tokens
+}
+
+/// 短语在一行中首次出现的字节范围
+pub fn find_phrase(line: &str, phrase: &[String]) -> Option<(usize, usize)> {
+    let tokens = tokenize(line);
+    tokens
+        .windows(phrase.len().max(1))
+        .find(|window| {
+            window.len() == phrase.len()
+                && window
+                    .iter()
+                    .zip(phrase)
+                    .all(|(token, term)| token.term == *term)
+        })
+        .map(|window| (window[0].start, window[window.len() - 1].end))
+}
+
+// 汉字、假名与谚文
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30ff}'
+        | '\u{3400}'..='\u{4dbf}'
+        | '\u{4e00}'..='\u{9fff}'
+        | '\u{ac00}'..='\u{d7af}'
+        | '\u{f900}'..='\u{faff}'
+        | '\u{20000}'..='\u{2fa1f}')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index(docs: &[(&str, &str)]) -> SearchIndex {
+        let stamp = FileStamp {
+            mtime_secs: 0,
+            mtime_nanos: 0,
+            size: 0,
+        };
+        SearchIndex {
+            docs: docs
+                .iter()
+                .map(|(name, content)| (name.to_string(), Doc::new(stamp, content)))
+                .collect(),
+        }
+    }
+
+    fn names(index: &SearchIndex, query: &str) -> Vec<String> {
+        let results = index.search(&parse_query(query));
+        results.into_iter().map(|(name, _)| name).collect()
+    }
+
+    fn terms(text: &str) -> Vec<String> {
+        tokenize(text).into_iter().map(|token| token.term).collect()
+    }
+
+    #[test]
+    fn ascii_words_are_lowercased_and_split_on_punctuation() {
+        assert_eq!(
+            terms("Hello, World-42 café_v2 ÉTÉ"),
+            ["hello", "world", "42", "café", "v2", "été"]
+        );
+        let tokens = tokenize("  Rust!");
+        assert_eq!((tokens[0].start, tokens[0].end), (2, 6));
+    }
+
+    #[test]
+    fn cjk_characters_are_single_terms() {
+        assert_eq!(
+            terms("用Rust写CLI工具"),
+            ["用", "rust", "写", "cli", "工", "具"]
+        );
+        assert_eq!(
+            terms("ひらがな、한국"),
+            ["ひ", "ら", "が", "な", "한", "국"]
+        );
+        let tokens = tokenize("a中b");
+        let ranges: Vec<(usize, usize)> = tokens.iter().map(|t| (t.start, t.end)).collect();
+        assert_eq!(ranges, [(0, 1), (1, 4), (4, 5)]);
+    }
+
+    #[test]
+    fn queries_split_into_words_and_phrases() {
+        assert_eq!(
+            parse_query("Rust \"全文 搜索\" a-b 中文 \"\""),
+            [
+                vec!["rust"],
+                vec!["全", "文", "搜", "索"],
+                vec!["a", "b"],
+                vec!["中", "文"],
+            ]
+        );
+        assert_eq!(
+            find_phrase("学习 Rust 全文搜索", &parse_query("全文")[0]),
+            Some((12, 18))
+        );
+        assert_eq!(find_phrase("全 x 文", &parse_query("全文")[0]), None);
+    }
+
+    #[test]
+    fn every_term_and_phrase_must_occur() {
+        let index = index(&[
+            ("both", "rust 全文搜索"),
+            ("split", "全文\n搜索 rust"),
+            ("gap", "全文的搜索 rust"),
+            ("rust", "rust"),
+        ]);
+        assert_eq!(names(&index, "全文搜索 rust"), ["both"]);
+        assert_eq!(names(&index, "\"全文 搜索\""), ["both"]);
+        // 词数相同时按文件名排序，多一个词的 gap 排在后面
+        assert_eq!(names(&index, "全文 搜索"), ["both", "split", "gap"]);
+        assert_eq!(names(&index, "python"), Vec::<String>::new());
+        assert_eq!(names(&index, "\"\""), Vec::<String>::new());
+    }
+
+    #[test]
+    fn ranking_follows_frequency_length_and_rarity() {
+        let index = index(&[
+            ("once", "rust guide"),
+            ("twice", "rust rust guide"),
+            (
+                "long",
+                "rust guide with many more words that dilute the match",
+            ),
+            ("tie", "rust guide"),
+            ("other", "python guide"),
+        ]);
+        assert_eq!(names(&index, "rust"), ["twice", "once", "tie", "long"]);
+
+        // 少见的词得分更高
+        let score = |query: &str| index.search(&parse_query(query))[0].1;
+        assert!(score("python") > score("rust"));
+        assert!(score("rust") > score("guide"));
+        assert!(score("guide") > 0.0);
+    }
+}
@@ -0,0 +1,46 @@
+// 从 git 历史取得笔记的创建与修改时间，用于没有 Created 字段的旧笔记
+
+use std::path::Path;
+use std::process::Command;
+
+use crate::date::Date;
+
+/// 文件在 git 历史中的时间，日期与时间的写法同 `NoteMeta::created`
+pub struct FileDates {
+    /// 最早一次提交（跟随重命名）的作者时间
+    pub created: (Date, String),
+    /// 最近一次提交的作者时间
+    pub modified: (Date, String),
+}
+
+/// 用 `git log --follow` 查询文件的提交时间
+///
+/// 文件不在 git 仓库中、尚未提交或无法运行 git 时返回 None。
+/// 时间取提交者所在时区的本地时间，与 Created 字段的写法一致。
+pub fn file_dates(path: &Path) -> Option<FileDates> {
+    let dir = path.parent()?;
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["log", "--follow", "--format=%aI", "--"])
+        .arg(path.file_name()?)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    // 输出从新到旧，每行一个 RFC 3339 时间
+    let text = String::from_utf8_lossy(&output.stdout);
+    let parse = |line: &str| {
+        let (date, time) = Date::parse(line.trim()).ok()?;
+        Some((date, time.unwrap_or_default()))
+    };
+    let mut lines = text.lines().filter(|line| !line.trim().is_empty());
+    let modified = parse(lines.next()?)?;
+    let created = match lines.next_back() {
+        Some(line) => parse(line)?,
+        None => modified.clone(),
+    };
+    Some(FileDates { created, modified })
+}
@@ -0,0 +1,209 @@
+// 文件名通配符匹配，用于排除笔记与目录
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// 博客目录下的忽略文件，写法同 .gitignore
+pub const IGNORE_FILE: &str = ".gtxignore";
+
+/// 读取 dir 下的忽略文件，返回其中的规则；文件不存在时返回空列表
+///
+/// 跳过空行与 `#` 开头的注释，`\#` 与 `\!` 表示以该字符开头的文件名。
+pub fn load_ignore(dir: &Path) -> io::Result<Vec<String>> {
+    let text = match fs::read_to_string(dir.join(IGNORE_FILE)) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    Ok(text
+        .lines()
+        .map(str::trim_end)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect())
+}
+
+/// 按 .gitignore 的规则判断文件或目录是否被排除，path 为相对博客目录、以 / 分隔的路径
+///
+/// 规则依次比较，最后一条匹配的规则生效：`!` 开头的规则重新包含文件。不含 `/` 的规则匹配任意一层的名称；
+/// 以 `/` 开头或中间含有 `/` 的规则从博客目录起匹配整个路径，其中 `**` 匹配任意层目录。
+/// 以 `/` 结尾的规则只匹配目录（is_dir 为 true），被排除的目录整个不再扫描。
+pub fn is_excluded(rules: &[String], path: &str, is_dir: bool) -> bool {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    let mut excluded = false;
+    for rule in rules {
+        let (negated, pattern) = match rule.strip_prefix('!') {
+            Some(pattern) => (true, pattern),
+            None => (false, rule.strip_prefix('\\').unwrap_or(rule)),
+        };
+        let pattern = match pattern.strip_suffix('/') {
+            Some(_) if !is_dir => continue,
+            Some(pattern) => pattern,
+            None => pattern,
+        };
+        let pattern = pattern.strip_prefix("**/").unwrap_or(pattern);
+        let matched = if pattern.contains('/') {
+            let pattern = pattern.strip_prefix('/').unwrap_or(pattern);
+            let pattern: Vec<&str> = pattern.split('/').collect();
+            let path: Vec<&str> = path.split('/').collect();
+            matches_segments(&pattern, &path)
+        } else {
+            matches(pattern, name)
+        };
+        if matched {
+            excluded = !negated;
+        }
+    }
+    excluded
+}
+
+// 逐层匹配路径，`**` 一层匹配任意多层（含零层）
+fn matches_segments(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|skip| matches_segments(rest, &path[skip..])),
+        Some((first, rest)) => path
+            .split_first()
+            .is_some_and(|(name, path)| matches(first, name) && matches_segments(rest, path)),
+    }
+}
+
+/// 判断 name 是否匹配 pattern
+///
+/// 支持 `*`（任意个字符）、`?`（单个字符）、`[abc]`、`[a-z]` 与 `[!abc]`，
+/// 其余字符按原样比较。
+pub fn matches(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // 最近一个 `*` 的位置，以及它当时对应的 name 位置，用于回溯
+    let mut star: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        let step = match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+                continue;
+            }
+            Some('?') => Some(1),
+            Some('[') => match_class(&pattern[p..], name[n]),
+            Some(&c) if c == name[n] => Some(1),
+            _ => None,
+        };
+        match (step, star) {
+            (Some(len), _) => {
+                p += len;
+                n += 1;
+            }
+            // 失配时让上一个 `*` 多吞一个字符
+            (None, Some((star_p, star_n))) => {
+                p = star_p + 1;
+                n = star_n + 1;
+                star = Some((star_p, star_n + 1));
+            }
+            (None, None) => return false,
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+// 匹配以 `[` 开头的字符类，成功时返回字符类在 pattern 中的长度；
+// 没有闭合的 `]` 时按普通字符 `[` 处理
+fn match_class(pattern: &[char], c: char) -> Option<usize> {
+    let Some(end) = pattern.iter().skip(2).position(|&ch| ch == ']') else {
+        return (c == '[').then_some(1);
+    };
+    let end = end + 2;
+    let (negated, body) = match pattern[1] {
+        '!' | '^' => (true, &pattern[2..end]),
+        _ => (false, &pattern[1..end]),
+    };
+
+    let mut found = false;
+    let mut i = 0;
+    while i < body.len() {
+        if i + 2 < body.len() && body[i + 1] == '-' {
+            found |= body[i] <= c && c <= body[i + 2];
+            i += 3;
+        } else {
+            found |= body[i] == c;
+            i += 1;
+        }
+    }
+    (found != negated).then_some(end + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wildcards_and_classes() {
+        assert!(matches("*.md", "笔记.md"));
+        assert!(!matches("*.md", "笔记.md.bak"));
+        assert!(matches("a*b*c", "aXbYbZc"));
+        assert!(!matches("a*b*c", "aXbYbZ"));
+        assert!(matches("?.md", "中.md"));
+        assert!(!matches("?.md", "ab.md"));
+        assert!(matches("[abc]1", "b1"));
+        assert!(matches("[a-c]1", "c1"));
+        assert!(!matches("[!a-c]1", "c1"));
+        assert!(matches("[^a-c]1", "d1"));
+        assert!(matches("[]]", "]"));
+        assert!(matches("[x", "[x"));
+        assert!(matches("*", ""));
+        assert!(matches("**", "a"));
+        assert!(!matches("", "a"));
+    }
+
+    #[test]
+    fn last_matching_rule_wins() {
+        let rules: Vec<String> = [
+            "*.md",
+            "!keep.md",
+            "\\!bang.md",
+            "/draft-*",
+            "**/tmp.md",
+            "dir/",
+        ]
+        .iter()
+        .map(|rule| rule.to_string())
+        .collect();
+        assert!(is_excluded(&rules, "note.md", false));
+        assert!(!is_excluded(&rules, "keep.md", false));
+        assert!(is_excluded(&rules, "!bang.md", false));
+        assert!(is_excluded(&rules, "draft-1.txt", false));
+        assert!(is_excluded(&rules, "tmp.md", false));
+        assert!(!is_excluded(&rules, "dir", false));
+        assert!(is_excluded(&rules, "dir", true));
+        assert!(!is_excluded(&rules, "note.txt", false));
+    }
+
+    #[test]
+    fn rules_for_nested_paths() {
+        let rules: Vec<String> = [
+            "tmp.md",
+            "/draft-*",
+            "a/**/b/*.md",
+            "private/",
+            "!x/private/",
+        ]
+        .iter()
+        .map(|rule| rule.to_string())
+        .collect();
+        // 不含 / 的规则匹配任意一层
+        assert!(is_excluded(&rules, "sub/dir/tmp.md", false));
+        // 以 / 开头的规则只匹配顶层
+        assert!(is_excluded(&rules, "draft-1.md", false));
+        assert!(!is_excluded(&rules, "sub/draft-1.md", false));
+        assert!(is_excluded(&rules, "a/b/n.md", false));
+        assert!(is_excluded(&rules, "a/x/y/b/n.md", false));
+        assert!(!is_excluded(&rules, "c/a/b/n.md", false));
+        assert!(is_excluded(&rules, "notes/private", true));
+        assert!(!is_excluded(&rules, "notes/private", false));
+        assert!(!is_excluded(&rules, "x/private", true));
+    }
+}
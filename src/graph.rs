@@ -0,0 +1,313 @@
+// 笔记链接关系图与标签共现图的导出，以及链接图的度数、中心度与连通分量
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::Write;
+
+use crate::index::IndexSet;
+use crate::note::{NEED_TAG, NoteMeta};
+use crate::vault::Vault;
+
+// 按主标签分配的节点颜色，标签多于颜色数时循环使用
+const PALETTE: &[&str] = &[
+    "#4e79a7", "#f28e2b", "#e15759", "#76b7b2", "#59a14f", "#edc948", "#b07aa1", "#ff9da7",
+    "#9c755f", "#bab0ac",
+];
+
+/// 输出 Graphviz DOT 格式的笔记关系图
+///
+/// 每篇笔记是一个节点，颜色由第一个标签决定；正文中能解析到其他笔记的 `[[链接]]` 是一条边，
+/// 同一对笔记之间的多条链接只保留一条。
+pub fn to_dot(vault: &Vault) -> String {
+    let mut notes: Vec<&NoteMeta> = vault.notes().collect();
+    notes.sort_by(|a, b| a.name.cmp(&b.name));
+    let links = &vault.indexes().links;
+
+    // 主标签按字典序分配颜色，保证多次导出的颜色一致
+    let primary_tags: BTreeSet<&str> = notes
+        .iter()
+        .filter_map(|note| note.tags.first().map(String::as_str))
+        .collect();
+    let colors: BTreeMap<&str, &str> = primary_tags
+        .into_iter()
+        .zip(PALETTE.iter().copied().cycle())
+        .collect();
+
+    let mut out = String::from("digraph notes {\n");
+    out.push_str("    node [shape=box, style=\"rounded,filled\", fontcolor=white];\n");
+    for note in &notes {
+        let tag = note.tags.first().map_or("", String::as_str);
+        let _ = writeln!(
+            out,
+            "    {} [label={}, tooltip={}, fillcolor=\"{}\"];",
+            quote(&note.name),
+            quote(&note.title),
+            quote(tag),
+            colors.get(tag).copied().unwrap_or(PALETTE[0])
+        );
+    }
+
+    for (source, target) in links.edges() {
+        let _ = writeln!(out, "    {} -> {};", quote(source), quote(target));
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// 输出 Graphviz DOT 格式的标签共现图
+///
+/// 每个标签是一个节点，标注使用该标签的笔记数；同时出现在一篇笔记中的两个标签之间有一条无向边，
+/// 标注共同出现的次数，次数越多边越粗。
+pub fn tags_to_dot(indexes: &IndexSet) -> String {
+    let tags = &indexes.tags;
+    let mut names: Vec<&String> = tags
+        .get_inputs()
+        .iter()
+        .filter(|tag| *tag != NEED_TAG)
+        .collect();
+    names.sort();
+
+    let mut out = String::from("graph tags {\n");
+    out.push_str(
+        "    node [shape=ellipse, style=filled, fillcolor=\"#4e79a7\", fontcolor=white];\n",
+    );
+    for tag in names {
+        let _ = writeln!(
+            out,
+            "    {} [label={}];",
+            quote(tag),
+            quote(&format!("{} ({})", tag, tags.get_i_count(tag)))
+        );
+    }
+    for pair in indexes.tag_pairs() {
+        let _ = writeln!(
+            out,
+            "    {} -- {} [label=\"{}\", penwidth={}];",
+            quote(pair.tags.0),
+            quote(pair.tags.1),
+            pair.count,
+            pair.count.min(8)
+        );
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// 输出 Mermaid `graph TD` 代码块，只含链接最多的 top 篇笔记及它们之间的链接
+///
+/// 笔记按相连的其他笔记数（不分方向）从多到少选取，数量相同时按文件名；没有任何链接的笔记不计入。
+/// top 为 0 或没有链接时返回空字符串。
+pub fn to_mermaid(indexes: &IndexSet, top: usize) -> String {
+    let links = &indexes.links;
+    let edges = links.edges();
+    let mut neighbours: HashMap<&str, BTreeSet<&str>> = HashMap::new();
+    for &(source, target) in &edges {
+        neighbours.entry(source).or_default().insert(target);
+        neighbours.entry(target).or_default().insert(source);
+    }
+    let mut notes: Vec<(&str, usize)> = neighbours
+        .iter()
+        .map(|(name, others)| (*name, others.len()))
+        .collect();
+    notes.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    notes.truncate(top);
+    if notes.is_empty() {
+        return String::new();
+    }
+
+    // 节点编号按选取顺序分配，文件名中的字符不必转义
+    let ids: HashMap<&str, usize> = notes
+        .iter()
+        .enumerate()
+        .map(|(i, (name, _))| (*name, i))
+        .collect();
+    let mut out = String::from("```mermaid\ngraph TD\n");
+    for (name, _) in &notes {
+        let title = links.get_title(name).unwrap_or(name);
+        let _ = writeln!(
+            out,
+            "    n{}[\"{}\"]",
+            ids[name],
+            title.replace('"', "#quot;")
+        );
+    }
+    for (source, target) in edges {
+        if let (Some(from), Some(to)) = (ids.get(source), ids.get(target)) {
+            let _ = writeln!(out, "    n{} --> n{}", from, to);
+        }
+    }
+    out.push_str("```\n");
+    out
+}
+
+/// PageRank 的阻尼系数
+pub const DAMPING: f64 = 0.85;
+
+// PageRank 的最大迭代次数与收敛阈值（两次迭代之间中心度变化的绝对值之和）
+const MAX_ITERATIONS: usize = 100;
+const TOLERANCE: f64 = 1e-9;
+
+/// 一篇笔记在链接图中的位置
+#[derive(Debug, Clone)]
+pub struct NoteCentrality {
+    pub name: String,
+    pub title: String,
+    /// 链接到这篇笔记的其他笔记数
+    pub incoming: usize,
+    /// 这篇笔记链接到的其他笔记数
+    pub outgoing: usize,
+    /// PageRank 中心度，全部笔记的平均值为 1
+    pub rank: f64,
+}
+
+/// 笔记链接图的统计
+#[derive(Debug, Clone)]
+pub struct GraphMetrics {
+    /// 笔记之间的链接数，同一对笔记之间的多条链接只算一条
+    pub edges: usize,
+    /// 全部笔记，按中心度从高到低排列，相同时按文件名
+    pub notes: Vec<NoteCentrality>,
+    /// 不分链接方向的连通分量，按笔记数从多到少排列，分量内按文件名排序
+    pub components: Vec<Vec<String>>,
+}
+
+impl GraphMetrics {
+    /// 统计 indexes 中全部笔记之间的链接，边与 `to_dot` 相同
+    pub fn collect(indexes: &IndexSet) -> GraphMetrics {
+        let links = &indexes.links;
+        let titles = indexes.titles();
+        let ids: HashMap<&str, usize> = titles
+            .iter()
+            .enumerate()
+            .map(|(i, (name, _))| (*name, i))
+            .collect();
+        let n = titles.len();
+        let mut targets: Vec<Vec<usize>> = vec![Vec::new(); n];
+        let mut incoming = vec![0; n];
+        let mut edges = 0;
+        for (source, target) in links.edges() {
+            if let (Some(&from), Some(&to)) = (ids.get(source), ids.get(target)) {
+                targets[from].push(to);
+                incoming[to] += 1;
+                edges += 1;
+            }
+        }
+
+        let rank = pagerank(&targets);
+        let mut notes: Vec<NoteCentrality> = titles
+            .iter()
+            .enumerate()
+            .map(|(i, (name, title))| NoteCentrality {
+                name: name.to_string(),
+                title: title.to_string(),
+                incoming: incoming[i],
+                outgoing: targets[i].len(),
+                rank: rank[i] * n as f64,
+            })
+            .collect();
+        // 文件名已排序，稳定排序保证中心度相同时按文件名排列
+        notes.sort_by(|a, b| b.rank.total_cmp(&a.rank));
+
+        let mut components: Vec<Vec<String>> = components(&targets)
+            .into_iter()
+            .map(|members| {
+                members
+                    .into_iter()
+                    .map(|i| titles[i].0.to_string())
+                    .collect()
+            })
+            .collect();
+        components.sort_by_key(|members| Reverse(members.len()));
+
+        GraphMetrics {
+            edges,
+            notes,
+            components,
+        }
+    }
+
+    /// 与任何笔记之间都没有链接的笔记数
+    pub fn isolated(&self) -> usize {
+        self.components
+            .iter()
+            .filter(|members| members.len() == 1)
+            .count()
+    }
+
+    /// 最大连通分量以外、至少有两篇笔记的连通分量，即与主体没有链接的笔记群
+    pub fn islands(&self) -> &[Vec<String>] {
+        let end = self
+            .components
+            .iter()
+            .position(|members| members.len() < 2)
+            .unwrap_or(self.components.len());
+        self.components.get(1..end).unwrap_or(&[])
+    }
+}
+
+// 幂迭代计算 PageRank，没有出链的笔记把中心度平均分给全部笔记；返回值之和为 1
+fn pagerank(targets: &[Vec<usize>]) -> Vec<f64> {
+    let n = targets.len();
+    if n == 0 {
+        return Vec::new();
+    }
+    let mut rank = vec![1.0 / n as f64; n];
+    for _ in 0..MAX_ITERATIONS {
+        let dangling: f64 = (0..n)
+            .filter(|&i| targets[i].is_empty())
+            .map(|i| rank[i])
+            .sum();
+        let base = (1.0 - DAMPING) / n as f64 + DAMPING * dangling / n as f64;
+        let mut next = vec![base; n];
+        for (i, out) in targets.iter().enumerate() {
+            for &j in out {
+                next[j] += DAMPING * rank[i] / out.len() as f64;
+            }
+        }
+        let delta: f64 = rank.iter().zip(&next).map(|(a, b)| (a - b).abs()).sum();
+        rank = next;
+        if delta < TOLERANCE {
+            break;
+        }
+    }
+    rank
+}
+
+// 不分方向的连通分量，每个分量中的编号从小到大排列
+fn components(targets: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    let n = targets.len();
+    let mut neighbours: Vec<Vec<usize>> = vec![Vec::new(); n];
+    for (i, out) in targets.iter().enumerate() {
+        for &j in out {
+            neighbours[i].push(j);
+            neighbours[j].push(i);
+        }
+    }
+    let mut seen = vec![false; n];
+    let mut result = Vec::new();
+    for start in 0..n {
+        if seen[start] {
+            continue;
+        }
+        seen[start] = true;
+        let mut members = vec![start];
+        let mut stack = vec![start];
+        while let Some(i) = stack.pop() {
+            for &j in &neighbours[i] {
+                if !seen[j] {
+                    seen[j] = true;
+                    members.push(j);
+                    stack.push(j);
+                }
+            }
+        }
+        members.sort_unstable();
+        result.push(members);
+    }
+    result
+}
+
+// DOT 的字符串字面量
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
@@ -0,0 +1,115 @@
+// git 钩子：提交时只重新解析本次提交涉及的笔记并更新索引
+
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// 钩子脚本中用于识别 gtx 所安装钩子的标记行
+const HOOK_MARKER: &str = "# 由 gtx hook install 生成";
+
+/// 钩子的时机
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookKind {
+    /// 提交前运行，索引失败时提交会被中止
+    PreCommit,
+    /// 提交后运行，不影响提交
+    PostCommit,
+}
+
+impl HookKind {
+    /// 钩子的文件名
+    pub fn file_name(self) -> &'static str {
+        match self {
+            HookKind::PreCommit => "pre-commit",
+            HookKind::PostCommit => "post-commit",
+        }
+    }
+}
+
+/// 在 root 所在的 git 仓库中安装钩子，运行 `<gtx> index --quiet --changed-only <root>`
+///
+/// 已有的钩子不是 gtx 安装的时，只有 force 为 true 才会覆盖。返回钩子文件的路径。
+pub fn install(root: &Path, kind: HookKind, gtx: &Path, force: bool) -> io::Result<PathBuf> {
+    // 遵循 core.hooksPath 与工作树的设置
+    let hooks_dir = git(root, &["rev-parse", "--git-path", "hooks"])?;
+    let hooks_dir = root.join(hooks_dir.trim());
+    let path = hooks_dir.join(kind.file_name());
+
+    if !force
+        && let Ok(existing) = fs::read_to_string(&path)
+        && !existing.contains(HOOK_MARKER)
+    {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} 已存在且不是 gtx 安装的钩子", path.display()),
+        ));
+    }
+
+    let root = fs::canonicalize(root)?;
+    let script = format!(
+        "#!/bin/sh\n{}\nexec {} index --quiet --changed-only {}\n",
+        HOOK_MARKER,
+        shell_quote(&gtx.to_string_lossy()),
+        shell_quote(&root.to_string_lossy())
+    );
+    fs::create_dir_all(&hooks_dir)?;
+    fs::write(&path, script)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+    }
+    Ok(path)
+}
+
+/// 本次提交涉及的、位于 root 下的文件，相对 root、以 / 分隔
+///
+/// 有暂存的改动时（pre-commit）取暂存区中的文件，否则（post-commit）取 HEAD 提交中的文件。
+pub fn changed_files(root: &Path) -> io::Result<HashSet<String>> {
+    let staged = git(
+        root,
+        &["diff", "--cached", "--name-only", "--relative", "-z"],
+    )?;
+    let files = if staged.is_empty() {
+        git(
+            root,
+            &[
+                "diff-tree",
+                "--no-commit-id",
+                "--name-only",
+                "--relative",
+                "-r",
+                "-z",
+                "HEAD",
+            ],
+        )?
+    } else {
+        staged
+    };
+    Ok(files
+        .split('\0')
+        .filter(|file| !file.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+// 在 dir 中运行 git，返回标准输出
+fn git(dir: &Path, args: &[&str]) -> io::Result<String> {
+    let output = Command::new("git").arg("-C").arg(dir).args(args).output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(io::Error::other(format!(
+            "git {} 失败: {}",
+            args.join(" "),
+            stderr.trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+// 以单引号包住，内部的单引号写作 '\''
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
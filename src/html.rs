@@ -0,0 +1,809 @@
+// HTML 解析与转换为 Markdown，用于导入其他笔记软件的笔记
+//
+// 解析器较为宽松，也可用于读取 XML：
+//   - 跳过注释、<!DOCTYPE> 与 <?xml?>，CDATA 作为文本
+//   - 标签与属性名转为小写，文本与属性值中的字符实体会被解码
+//   - 没有对应开始标签的结束标签被忽略，未闭合的元素在父元素结束时一并结束
+//
+// 转换支持标题、段落、换行、列表（含 Evernote 的 <en-todo> 任务）、引用、代码块、
+// 分隔线、表格、强调、加粗、删除线、行内代码、链接与图片，其余元素只保留其中的文本。
+
+/// 解析后的节点
+pub enum Node {
+    Element(Element),
+    Text(String),
+}
+
+/// 一个元素及其子节点
+pub struct Element {
+    pub name: String,
+    pub attrs: Vec<(String, String)>,
+    pub children: Vec<Node>,
+}
+
+impl Element {
+    /// 属性值，不存在时为空字符串
+    pub fn attr(&self, name: &str) -> &str {
+        self.attrs
+            .iter()
+            .find(|(key, _)| key == name)
+            .map_or("", |(_, value)| value.as_str())
+    }
+
+    /// 名为 name 的直接子元素
+    pub fn elements<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> {
+        self.children.iter().filter_map(move |child| match child {
+            Node::Element(element) if element.name == name => Some(element),
+            _ => None,
+        })
+    }
+
+    /// 全部后代文本节点拼接而成的文本
+    pub fn text(&self) -> String {
+        let mut text = String::new();
+        collect_text(&self.children, &mut text);
+        text
+    }
+}
+
+fn collect_text(nodes: &[Node], out: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Element(element) => collect_text(&element.children, out),
+        }
+    }
+}
+
+/// 解析 HTML 片段，br、img 等空元素没有结束标签
+pub fn parse(text: &str) -> Vec<Node> {
+    build_tree(text, true)
+}
+
+/// 解析 XML 文档，只有 `<a/>` 形式的元素没有结束标签
+pub fn parse_xml(text: &str) -> Vec<Node> {
+    build_tree(text, false)
+}
+
+// HTML 中没有结束标签的元素
+fn is_void(name: &str) -> bool {
+    matches!(
+        name,
+        "area"
+            | "base"
+            | "br"
+            | "col"
+            | "embed"
+            | "hr"
+            | "img"
+            | "input"
+            | "link"
+            | "meta"
+            | "source"
+            | "wbr"
+            | "en-media"
+            | "en-todo"
+    )
+}
+
+enum Token {
+    Open(String, Vec<(String, String)>, bool),
+    Close(String),
+    Text(String),
+}
+
+fn build_tree(text: &str, html: bool) -> Vec<Node> {
+    let root = Element {
+        name: String::new(),
+        attrs: Vec::new(),
+        children: Vec::new(),
+    };
+    let mut stack = vec![root];
+    for token in tokenize(text) {
+        match token {
+            Token::Text(text) => {
+                let parent = stack.last_mut().expect("根元素不会出栈");
+                match parent.children.last_mut() {
+                    Some(Node::Text(last)) => last.push_str(&text),
+                    _ => parent.children.push(Node::Text(text)),
+                }
+            }
+            Token::Open(name, attrs, self_closing) => {
+                let element = Element {
+                    name,
+                    attrs,
+                    children: Vec::new(),
+                };
+                if self_closing || (html && is_void(&element.name)) {
+                    let parent = stack.last_mut().expect("根元素不会出栈");
+                    parent.children.push(Node::Element(element));
+                } else {
+                    stack.push(element);
+                }
+            }
+            Token::Close(name) => {
+                let Some(depth) = stack.iter().skip(1).rposition(|e| e.name == name) else {
+                    continue;
+                };
+                while stack.len() > depth + 1 {
+                    close_top(&mut stack);
+                }
+            }
+        }
+    }
+    while stack.len() > 1 {
+        close_top(&mut stack);
+    }
+    stack.pop().map(|root| root.children).unwrap_or_default()
+}
+
+fn close_top(stack: &mut Vec<Element>) {
+    if let Some(element) = stack.pop()
+        && let Some(parent) = stack.last_mut()
+    {
+        parent.children.push(Node::Element(element));
+    }
+}
+
+fn tokenize(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        let Some(start) = rest.find('<') else {
+            tokens.push(Token::Text(unescape(rest)));
+            break;
+        };
+        if start > 0 {
+            tokens.push(Token::Text(unescape(&rest[..start])));
+            rest = &rest[start..];
+        }
+
+        let skip_to = |rest: &str, end: &str, from: usize| {
+            rest[from..]
+                .find(end)
+                .map_or(rest.len(), |i| from + i + end.len())
+        };
+        if rest.starts_with("<!--") {
+            rest = &rest[skip_to(rest, "-->", 4)..];
+        } else if let Some(cdata) = rest.strip_prefix("<![CDATA[") {
+            let end = cdata.find("]]>").unwrap_or(cdata.len());
+            tokens.push(Token::Text(cdata[..end].to_string()));
+            rest = cdata.get(end + 3..).unwrap_or_default();
+        } else if rest.starts_with("<!") || rest.starts_with("<?") {
+            rest = &rest[skip_to(rest, ">", 2)..];
+        } else if let Some(name) = rest.strip_prefix("</") {
+            let end = name.find('>').unwrap_or(name.len());
+            tokens.push(Token::Close(name[..end].trim().to_ascii_lowercase()));
+            rest = name.get(end + 1..).unwrap_or_default();
+        } else if rest[1..].starts_with(|c: char| c.is_ascii_alphabetic()) {
+            let (token, len) = open_tag(rest);
+            tokens.push(token);
+            rest = &rest[len..];
+        } else {
+            // 不是标签的 <，作为普通文本
+            tokens.push(Token::Text("<".to_string()));
+            rest = &rest[1..];
+        }
+    }
+    tokens
+}
+
+// 解析以 < 开头的开始标签，返回标签与其长度
+fn open_tag(text: &str) -> (Token, usize) {
+    let bytes = text.as_bytes();
+    let is_name_end = |b: u8| b.is_ascii_whitespace() || b == b'/' || b == b'>';
+    let mut i = 1;
+    while i < bytes.len() && !is_name_end(bytes[i]) {
+        i += 1;
+    }
+    let name = text[1..i].to_ascii_lowercase();
+
+    let mut attrs = Vec::new();
+    let mut self_closing = false;
+    loop {
+        while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        match bytes.get(i) {
+            None => break,
+            Some(b'>') => {
+                i += 1;
+                break;
+            }
+            Some(b'/') => {
+                self_closing = bytes.get(i + 1) == Some(&b'>');
+                i += 1;
+                continue;
+            }
+            Some(_) => {}
+        }
+
+        let start = i;
+        while i < bytes.len() && !is_name_end(bytes[i]) && bytes[i] != b'=' {
+            i += 1;
+        }
+        let key = text[start..i].to_ascii_lowercase();
+        while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        let mut value = String::new();
+        if bytes.get(i) == Some(&b'=') {
+            i += 1;
+            while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+                i += 1;
+            }
+            match bytes.get(i) {
+                Some(&quote @ (b'"' | b'\'')) => {
+                    let end = text[i + 1..]
+                        .find(quote as char)
+                        .map_or(text.len(), |end| i + 1 + end);
+                    value = unescape(&text[i + 1..end]);
+                    i = (end + 1).min(text.len());
+                }
+                _ => {
+                    let start = i;
+                    while i < bytes.len() && !bytes[i].is_ascii_whitespace() && bytes[i] != b'>' {
+                        i += 1;
+                    }
+                    value = unescape(&text[start..i]);
+                }
+            }
+        }
+        if !key.is_empty() {
+            attrs.push((key, value));
+        }
+    }
+    (Token::Open(name, attrs, self_closing), i)
+}
+
+/// 解码字符实体，如 `&amp;`、`&#20013;`、`&#x4e2d;`；无法识别的实体保持原样
+pub fn unescape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let decoded = rest[1..]
+            .find(';')
+            .filter(|&end| end > 0 && end <= 10)
+            .and_then(|end| Some((entity(&rest[1..end + 1])?, end + 2)));
+        match decoded {
+            Some((c, len)) => {
+                result.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                result.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+fn entity(name: &str) -> Option<char> {
+    if let Some(number) = name.strip_prefix('#') {
+        let code = match number.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => number.parse().ok()?,
+        };
+        return char::from_u32(code);
+    }
+    Some(match name {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => '\u{a0}',
+        "ndash" => '–',
+        "mdash" => '—',
+        "hellip" => '…',
+        "lsquo" => '‘',
+        "rsquo" => '’',
+        "ldquo" => '“',
+        "rdquo" => '”',
+        "laquo" => '«',
+        "raquo" => '»',
+        "middot" => '·',
+        "copy" => '©',
+        "reg" => '®',
+        "trade" => '™',
+        "times" => '×',
+        _ => return None,
+    })
+}
+
+/// 将 HTML 片段转换为 Markdown 文本，非空时以换行结尾
+pub fn to_markdown(html: &str) -> String {
+    let mut markdown = blocks(&parse(html), false);
+    if !markdown.is_empty() {
+        markdown.push('\n');
+    }
+    markdown
+}
+
+// 作为块输出的元素，其余元素视为行内元素
+fn is_block(name: &str) -> bool {
+    matches!(
+        name,
+        "address"
+            | "article"
+            | "aside"
+            | "blockquote"
+            | "body"
+            | "center"
+            | "dd"
+            | "details"
+            | "div"
+            | "dl"
+            | "dt"
+            | "en-note"
+            | "figcaption"
+            | "figure"
+            | "footer"
+            | "form"
+            | "h1"
+            | "h2"
+            | "h3"
+            | "h4"
+            | "h5"
+            | "h6"
+            | "header"
+            | "hr"
+            | "html"
+            | "li"
+            | "main"
+            | "nav"
+            | "ol"
+            | "p"
+            | "pre"
+            | "section"
+            | "summary"
+            | "table"
+            | "ul"
+    )
+}
+
+// 内容不输出的元素
+fn is_hidden(name: &str) -> bool {
+    matches!(
+        name,
+        "head" | "script" | "style" | "title" | "en-crypt" | "en-media"
+    )
+}
+
+// 转换一组节点，块之间以空行分隔；tight 为真时（列表项中）只以换行分隔
+fn blocks(nodes: &[Node], tight: bool) -> String {
+    let mut out: Vec<String> = Vec::new();
+    let mut run: Vec<&Node> = Vec::new();
+    for node in nodes {
+        match node {
+            Node::Element(element) if is_block(&element.name) => {
+                flush_paragraph(&run, tight, &mut out);
+                run.clear();
+                let block = block(element);
+                if !block.is_empty() {
+                    out.push(block);
+                }
+            }
+            Node::Element(element) if is_hidden(&element.name) => {}
+            _ => run.push(node),
+        }
+    }
+    flush_paragraph(&run, tight, &mut out);
+
+    let mut result = String::new();
+    for (i, block) in out.iter().enumerate() {
+        if i > 0 {
+            // 相邻的任务行合为一个列表
+            let task = |text: &str| text.starts_with("- [ ] ") || text.starts_with("- [x] ");
+            let joined = tight || (task(&out[i - 1]) && task(block));
+            result.push_str(if joined { "\n" } else { "\n\n" });
+        }
+        result.push_str(block);
+    }
+    result
+}
+
+// 连续的行内节点构成一个段落
+fn flush_paragraph(run: &[&Node], tight: bool, out: &mut Vec<String>) {
+    let mut text = String::new();
+    for node in run {
+        inline(node, &mut text);
+    }
+    let text = tidy_lines(&text);
+    if text.is_empty() {
+        return;
+    }
+    // Evernote 的任务是段落开头的 <en-todo>
+    if !tight && (text.starts_with("[ ] ") || text.starts_with("[x] ")) {
+        out.push(format!("- {}", text));
+    } else {
+        out.push(text);
+    }
+}
+
+// 合并行内的连续空白，行之间以 Markdown 的硬换行（行尾两个空格）连接
+fn tidy_lines(text: &str) -> String {
+    let lines: Vec<String> = text
+        .split('\n')
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .collect();
+    let start = lines.iter().position(|line| !line.is_empty());
+    let end = lines.iter().rposition(|line| !line.is_empty());
+    match (start, end) {
+        (Some(start), Some(end)) => lines[start..=end].join("  \n"),
+        _ => String::new(),
+    }
+}
+
+fn block(element: &Element) -> String {
+    match element.name.as_str() {
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+            let level = usize::from(element.name.as_bytes()[1] - b'0');
+            let mut text = String::new();
+            inline_children(element, &mut text);
+            let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+            if text.is_empty() {
+                String::new()
+            } else {
+                format!("{} {}", "#".repeat(level), text)
+            }
+        }
+        "hr" => "---".to_string(),
+        "pre" => code_block(element),
+        "div" if element.attr("style").contains("-en-codeblock") => code_block(element),
+        "blockquote" => {
+            let inner = blocks(&element.children, false);
+            prefix_lines(&inner, "> ", ">")
+        }
+        "ul" | "ol" => list(element),
+        "table" => table(element),
+        _ => blocks(&element.children, false),
+    }
+}
+
+// 每一行加上前缀，空行使用 empty
+fn prefix_lines(text: &str, prefix: &str, empty: &str) -> String {
+    text.lines()
+        .map(|line| {
+            if line.is_empty() {
+                empty.to_string()
+            } else {
+                format!("{}{}", prefix, line)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn list(element: &Element) -> String {
+    let ordered = element.name == "ol";
+    let mut number: usize = element.attr("start").trim().parse().unwrap_or(1);
+    let mut items: Vec<String> = Vec::new();
+    for child in &element.children {
+        let content = match child {
+            Node::Element(item) if item.name == "li" => blocks(&item.children, true),
+            // 嵌套列表直接写在 <ul> 中时，归入上一项
+            Node::Element(nested) if matches!(nested.name.as_str(), "ul" | "ol") => {
+                let nested = list(nested);
+                match items.last_mut() {
+                    Some(last) => {
+                        last.push('\n');
+                        last.push_str(&prefix_lines(&nested, "    ", ""));
+                    }
+                    None => items.push(nested),
+                }
+                continue;
+            }
+            Node::Element(_) | Node::Text(_) => continue,
+        };
+        let marker = if ordered {
+            let marker = format!("{}. ", number);
+            number += 1;
+            marker
+        } else {
+            "- ".to_string()
+        };
+        let mut lines = content.lines();
+        let mut item = format!("{}{}", marker, lines.next().unwrap_or_default());
+        let indent = " ".repeat(marker.len());
+        for line in lines {
+            item.push('\n');
+            if !line.is_empty() {
+                item.push_str(&indent);
+                item.push_str(line);
+            }
+        }
+        items.push(item.trim_end().to_string());
+    }
+    items.join("\n")
+}
+
+fn code_block(element: &Element) -> String {
+    let mut code = String::new();
+    code_text(&element.children, &mut code);
+    let code = code.trim_matches('\n').replace('\u{a0}', " ");
+    if code.trim().is_empty() {
+        return String::new();
+    }
+    let fence = if code.contains("```") { "~~~" } else { "```" };
+    format!("{}\n{}\n{}", fence, code, fence)
+}
+
+// 代码块的原始文本，<br> 与块元素的边界为换行
+fn code_text(nodes: &[Node], out: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Element(element) if element.name == "br" => out.push('\n'),
+            Node::Element(element) => {
+                let block = is_block(&element.name);
+                if block && !out.is_empty() && !out.ends_with('\n') {
+                    out.push('\n');
+                }
+                code_text(&element.children, out);
+                if block && !out.ends_with('\n') {
+                    out.push('\n');
+                }
+            }
+        }
+    }
+}
+
+fn table(element: &Element) -> String {
+    // 行可能直接位于 <table> 下，也可能位于 thead、tbody、tfoot 中
+    let mut rows: Vec<Vec<String>> = Vec::new();
+    let mut sections = vec![element];
+    sections.extend(
+        element
+            .elements("thead")
+            .chain(element.elements("tbody"))
+            .chain(element.elements("tfoot")),
+    );
+    for section in sections {
+        for row in section.elements("tr") {
+            let cells = row
+                .children
+                .iter()
+                .filter_map(|cell| match cell {
+                    Node::Element(cell) if matches!(cell.name.as_str(), "td" | "th") => {
+                        let mut text = String::new();
+                        inline_children(cell, &mut text);
+                        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+                        Some(text.replace('|', "\\|"))
+                    }
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            rows.push(cells);
+        }
+    }
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    if columns == 0 {
+        return String::new();
+    }
+
+    let line = |cells: &[String]| {
+        let mut line = String::from("|");
+        for i in 0..columns {
+            line.push(' ');
+            line.push_str(cells.get(i).map_or("", String::as_str));
+            line.push_str(" |");
+        }
+        line
+    };
+    let mut lines = vec![line(&rows[0]), format!("|{}", " --- |".repeat(columns))];
+    lines.extend(rows[1..].iter().map(|row| line(row)));
+    lines.join("\n")
+}
+
+fn inline_children(element: &Element, out: &mut String) {
+    for child in &element.children {
+        inline(child, out);
+    }
+}
+
+// 行内内容，<br> 输出为换行，留待 tidy_lines 处理
+fn inline(node: &Node, out: &mut String) {
+    let element = match node {
+        Node::Text(text) => {
+            escape_into(text, out);
+            return;
+        }
+        Node::Element(element) => element,
+    };
+    match element.name.as_str() {
+        name if is_hidden(name) => {}
+        "br" => out.push('\n'),
+        "en-todo" => {
+            let checked = element.attr("checked").eq_ignore_ascii_case("true");
+            out.push_str(if checked { "[x] " } else { "[ ] " });
+        }
+        "b" | "strong" => wrap(element, "**", out),
+        "i" | "em" => wrap(element, "*", out),
+        "s" | "strike" | "del" => wrap(element, "~~", out),
+        "code" | "tt" | "kbd" => {
+            let code = element.text().replace('\u{a0}', " ");
+            let code = code.split_whitespace().collect::<Vec<_>>().join(" ");
+            if !code.is_empty() {
+                if code.contains('`') {
+                    out.push_str(&format!("`` {} ``", code));
+                } else {
+                    out.push_str(&format!("`{}`", code));
+                }
+            }
+        }
+        "a" => {
+            let href = element.attr("href").trim();
+            let mut text = String::new();
+            inline_children(element, &mut text);
+            let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+            match (text.is_empty(), href.is_empty()) {
+                (_, true) => out.push_str(&text),
+                (true, false) => out.push_str(&format!("<{}>", href)),
+                (false, false) => out.push_str(&format!("[{}]({})", text, link_target(href))),
+            }
+        }
+        "img" => {
+            let src = element.attr("src").trim();
+            if !src.is_empty() {
+                let mut alt = String::new();
+                escape_into(element.attr("alt"), &mut alt);
+                out.push_str(&format!("![{}]({})", alt.trim(), link_target(src)));
+            }
+        }
+        _ => {
+            // 块元素出现在行内时前后换行
+            let block = is_block(&element.name);
+            if block {
+                out.push('\n');
+            }
+            inline_children(element, out);
+            if block {
+                out.push('\n');
+            }
+        }
+    }
+}
+
+// 强调等标记紧贴内容，首尾的空白留在标记外
+fn wrap(element: &Element, mark: &str, out: &mut String) {
+    let mut inner = String::new();
+    inline_children(element, &mut inner);
+    let trimmed = inner.trim();
+    if trimmed.is_empty() {
+        out.push_str(&inner);
+        return;
+    }
+    if inner.starts_with(char::is_whitespace) {
+        out.push(' ');
+    }
+    out.push_str(mark);
+    out.push_str(trimmed);
+    out.push_str(mark);
+    if inner.ends_with(char::is_whitespace) {
+        out.push(' ');
+    }
+}
+
+// 地址中有空格或括号时用尖括号包住
+fn link_target(url: &str) -> String {
+    if url.contains([' ', '(', ')']) {
+        format!("<{}>", url)
+    } else {
+        url.to_string()
+    }
+}
+
+// 文本中的空白合并为一个空格，并转义 Markdown 的特殊字符
+fn escape_into(text: &str, out: &mut String) {
+    let mut space = false;
+    for c in text.chars() {
+        if c.is_whitespace() {
+            space = true;
+            continue;
+        }
+        if space {
+            out.push(' ');
+            space = false;
+        }
+        if matches!(c, '\\' | '*' | '_' | '`' | '[' | ']' | '<') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    if space {
+        out.push(' ');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn only_element(nodes: &[Node]) -> &Element {
+        match nodes {
+            [Node::Element(element)] => element,
+            _ => panic!("应只有一个元素"),
+        }
+    }
+
+    #[test]
+    fn tree_and_attributes() {
+        let nodes = parse(
+            "<DIV Class=\"a &amp; b\" data-x=1 hidden><p>一<br>二</p><img src='i.png'></div>",
+        );
+        let div = only_element(&nodes);
+        assert_eq!(div.name, "div");
+        assert_eq!(div.attr("class"), "a & b");
+        assert_eq!(div.attr("data-x"), "1");
+        assert_eq!(div.attr("hidden"), "");
+        assert_eq!(div.attr("missing"), "");
+        assert_eq!(div.elements("p").count(), 1);
+        assert_eq!(div.elements("img").next().unwrap().attr("src"), "i.png");
+        assert_eq!(div.text(), "一二");
+    }
+
+    #[test]
+    fn lenient_nesting() {
+        // 多余的结束标签被忽略，未闭合的元素随父元素结束
+        let nodes = parse("<ul><li>a<li>b</span></ul>后");
+        let ul = match &nodes[0] {
+            Node::Element(ul) => ul,
+            Node::Text(_) => panic!("应为元素"),
+        };
+        assert_eq!(ul.elements("li").count(), 1);
+        assert_eq!(ul.text(), "ab");
+        assert!(matches!(&nodes[1], Node::Text(text) if text == "后"));
+
+        let nodes = parse("a < b <!-- 注释 --><!DOCTYPE html>c");
+        assert!(matches!(&nodes[..], [Node::Text(text)] if text == "a < b c"));
+    }
+
+    #[test]
+    fn xml_mode() {
+        let nodes = parse_xml(
+            "<?xml version=\"1.0\"?><note><img>x</img><br/><![CDATA[<b>&amp;</b>]]></note>",
+        );
+        let note = only_element(&nodes);
+        assert_eq!(note.elements("img").next().unwrap().text(), "x");
+        assert_eq!(note.elements("br").count(), 1);
+        assert_eq!(note.text(), "x<b>&amp;</b>");
+    }
+
+    #[test]
+    fn entities() {
+        assert_eq!(
+            unescape("&lt;a&gt; &amp;amp; &#20013;&#x6587; &nbsp;&mdash;"),
+            "<a> &amp; 中文 \u{a0}—"
+        );
+        assert_eq!(unescape("&unknown; & &; &#xZZ;"), "&unknown; & &; &#xZZ;");
+        assert_eq!(unescape("&#1114112;"), "&#1114112;");
+    }
+
+    #[test]
+    fn converts_to_markdown() {
+        assert_eq!(to_markdown(""), "");
+        assert_eq!(
+            to_markdown("<h2>标题</h2><p>一段 <b>加粗</b> 与 <i>斜体</i>、<code>code</code></p>"),
+            "## 标题\n\n一段 **加粗** 与 *斜体*、`code`\n"
+        );
+        assert_eq!(
+            to_markdown("<p><a href=\"https://e.com\">链接</a> <img src=\"a.png\" alt=\"图\"></p>"),
+            "[链接](https://e.com) ![图](a.png)\n"
+        );
+        assert_eq!(to_markdown("<ul><li>a</li><li>b</li></ul>"), "- a\n- b\n");
+        assert_eq!(to_markdown("<ol><li>a</li><li>b</li></ol>"), "1. a\n2. b\n");
+        assert_eq!(
+            to_markdown("<blockquote><p>引用</p></blockquote>"),
+            "> 引用\n"
+        );
+        assert_eq!(
+            to_markdown("<pre><code>let x = 1;\n&lt;y&gt;</code></pre>"),
+            "```\nlet x = 1;\n<y>\n```\n"
+        );
+    }
+}
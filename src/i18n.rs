@@ -0,0 +1,540 @@
+// 输出语言：命令行的提示与错误信息、生成页面中的固定文字
+//
+// 源码中的文字以中文书写，同时作为消息目录的键；其他语言在目录中查找译文，没有译文时原样输出。
+// 语言由环境变量 GTX_LANG 或配置项 lang 选择，默认中文。子命令的帮助信息只有中文。
+
+use std::fmt;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// 选择语言的环境变量，优先于配置项 lang
+pub const LANG_ENV: &str = "GTX_LANG";
+
+/// 输出语言
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Lang {
+    #[default]
+    Zh,
+    En,
+}
+
+impl Lang {
+    /// 解析语言名，接受 `zh`、`en` 以及 `zh_CN.UTF-8`、`en-US` 等带地区的写法
+    pub fn parse(value: &str) -> Option<Lang> {
+        let code = value
+            .split(['_', '-', '.'])
+            .next()
+            .unwrap_or(value)
+            .to_ascii_lowercase();
+        match code.as_str() {
+            "zh" => Some(Lang::Zh),
+            "en" => Some(Lang::En),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Lang::Zh => "zh",
+            Lang::En => "en",
+        }
+    }
+}
+
+// 整个进程使用同一种语言，启动时由命令行设置
+static LANG: AtomicU8 = AtomicU8::new(0);
+
+/// 设置之后输出使用的语言
+pub fn set_lang(lang: Lang) {
+    LANG.store(lang as u8, Ordering::Relaxed);
+}
+
+/// 当前的输出语言
+pub fn lang() -> Lang {
+    match LANG.load(Ordering::Relaxed) {
+        1 => Lang::En,
+        _ => Lang::Zh,
+    }
+}
+
+/// 当前语言中的文字，text 为中文原文
+pub fn tr(text: &'static str) -> &'static str {
+    match lang() {
+        Lang::Zh => text,
+        Lang::En => english(text).unwrap_or(text),
+    }
+}
+
+/// 翻译 template 后依次以 args 替换其中的 `{}`
+pub fn trf(template: &'static str, args: &[&dyn fmt::Display]) -> String {
+    let mut out = String::new();
+    let mut args = args.iter();
+    let mut rest = tr(template);
+    while let Some(start) = rest.find("{}") {
+        out.push_str(&rest[..start]);
+        if let Some(arg) = args.next() {
+            out.push_str(&arg.to_string());
+        }
+        rest = &rest[start + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+// 英文目录，译文中 `{}` 的个数与顺序须与原文相同
+fn english(text: &str) -> Option<&'static str> {
+    Some(match text {
+        // 命令行
+        "错误: {}" => "error: {}",
+        "使用 `gtx --help` 查看帮助" => "Run `gtx --help` for usage",
+        "无法确定用户主目录，请设置 HOME（Windows 上为 USERPROFILE）或 {}" => {
+            "cannot determine the home directory; set HOME (USERPROFILE on Windows) or {}"
+        }
+        "用户配置中的 [vault.{}] 没有设置 path" => {
+            "[vault.{}] in the user config has no path"
+        }
+        "用户配置中没有 [vault.{}]，尚未定义任何博客目录" => {
+            "no [vault.{}] in the user config; no vaults are defined"
+        }
+        "用户配置中没有 [vault.{}]，可用: {}" => {
+            "no [vault.{}] in the user config, available: {}"
+        }
+        "路径 '{}' 不存在" => "path '{}' does not exist",
+        "'{}' 不是目录" => "'{}' is not a directory",
+        "\n增量构建: {} 个标签页、{} 个日期页受影响" => {
+            "\nIncremental build: {} tag pages and {} date pages affected"
+        }
+        "以下 {} 个文件被跳过，未加入索引:" => {
+            "{} files were skipped and not indexed:"
+        }
+        "无法读取本次提交涉及的文件" => "cannot read the files changed by this commit",
+        "无法构建目录 '{}' 的索引" => "cannot build the index of '{}'",
+        "\n索引构建完成！" => "\nIndex built!",
+        "找不到 sqlite3 命令，SQLite 索引（--backend sqlite）需要先安装 sqlite3" => {
+            "sqlite3 not found; the SQLite index (--backend sqlite) needs sqlite3 installed"
+        }
+        "无法运行 sqlite3: {}" => "cannot run sqlite3: {}",
+        "sqlite3 -version 执行失败（{}），SQLite 索引需要可用的 sqlite3 命令" => {
+            "sqlite3 -version failed ({}); the SQLite index needs a working sqlite3"
+        }
+        "sqlite3 执行失败: {}" => "sqlite3 failed: {}",
+        "找不到生成文件清单 {}，请先运行 gtx index" => {
+            "generated file manifest {} not found, run gtx index first"
+        }
+        "\n清理完成" => "\nCleanup finished",
+        "\n演练模式，未修改任何文件" => "\nDry run, no files were changed",
+        "没有需要改动的页面" => "No pages would change",
+        "  新建: {}" => "  create: {}",
+        "  改写: {}" => "  update: {}",
+        "  删除: {}" => "  delete: {}",
+        "  移动到隔离目录: {} -> {}" => "  move to trash: {} -> {}",
+        "共 {} 项：新建 {}，改写 {}，删除 {}，移动到隔离目录 {}" => {
+            "{} in total: {} created, {} updated, {} deleted, {} moved to trash"
+        }
+        "{} 个文件解析失败" => "{} files failed to parse",
+        "索引构建完成，共 {} 篇笔记" => "Index built, {} notes",
+        "正在监视 '{}'，按 Ctrl-C 退出" => "Watching '{}', press Ctrl-C to quit",
+        "变化: {}" => "Changed: {}",
+        "错误: 构建失败: {}" => "error: build failed: {}",
+        "无法监听端口 {}" => "cannot listen on port {}",
+        "预览地址: http://127.0.0.1:{}/，按 Ctrl-C 退出" => {
+            "Preview at http://127.0.0.1:{}/, press Ctrl-C to quit"
+        }
+        "JSON 接口: http://127.0.0.1:{}/api/notes" => "JSON API: http://127.0.0.1:{}/api/notes",
+        "错误: 无法监视目录: {}" => "error: cannot watch the directory: {}",
+        "预览已更新" => "Preview updated",
+        "错误: 渲染失败: {}" => "error: render failed: {}",
+        "无法读取目录 '{}'" => "cannot read directory '{}'",
+        "没有找到匹配 '{}' 的内容" => "nothing matches '{}'",
+        "\n共 {} 篇笔记，按相关度排序" => "\n{} notes, sorted by relevance",
+        "\n共 {} 处匹配，涉及 {} 篇笔记" => "\n{} matches in {} notes",
+        "\n已写入: {}" => "\nWritten: {}",
+        "没有笔记满足查询 '{}'" => "no notes match the query '{}'",
+        "\n共 {} 篇笔记" => "\n{} notes",
+        "创建" => "created",
+        "修改" => "modified",
+        "最近 {} 天没有创建或修改的笔记" => {
+            "no notes created or modified in the last {} days"
+        }
+        " (截止 {})" => " (due {})",
+        "没有待办事项" => "No open tasks",
+        "没有失效链接" => "No broken links",
+        "\n共 {} 条失效链接，涉及 {} 篇笔记" => "\n{} broken links in {} notes",
+        "无法读取附件目录 '{}'" => "cannot read the attachments directory '{}'",
+        "{}.md:{}: 找不到附件 '{}'" => "{}.md:{}: attachment '{}' not found",
+        "未引用: {}" => "Unused: {}",
+        "\n共 {} 个附件，没有缺失或未引用的附件" => {
+            "\n{} attachments, none missing or unused"
+        }
+        "\n共 {} 个附件，{} 处嵌入找不到文件，{} 个文件未被引用" => {
+            "\n{} attachments, {} embeds point to missing files, {} files are unused"
+        }
+        "已修复: {}" => "Fixed: {}",
+        "共修复 {} 个问题，涉及 {} 篇笔记\n" => "Fixed {} problems in {} notes\n",
+        "没有发现问题" => "No problems found",
+        "，其中 {} 个可以用 --fix 自动修复" => ", {} of them can be fixed with --fix",
+        "\n共 {} 个问题{}" => "\n{} problems{}",
+        "已导出 {} 个页面到 {}" => "Exported {} pages to {}",
+        "已导出 {} 篇笔记到 {}" => "Exported {} notes to {}",
+        "将改写" => "Would rewrite",
+        "已改写" => "Rewrote",
+        "跳过（文件头中找不到标签 '{}'）: {}" => {
+            "Skipped (tag '{}' not in the frontmatter): {}"
+        }
+        "没有笔记含有标签 '{}'" => "no notes have the tag '{}'",
+        "没有笔记" => "no notes",
+        "警告: {}.md 的 {} 字段无法识别，应为 日期 [间隔天数] [难度系数]" => {
+            "warning: {}.md: unrecognized {} field, expected date [interval days] [ease]"
+        }
+        "没有需要复习的笔记" => "No notes due for review",
+        "应于 {} 复习" => "due {}",
+        "评分 0–5（5 为毫不费力），回车跳过，q 退出: " => {
+            "Grade 0-5 (5 = effortless), Enter to skip, q to quit: "
+        }
+        "无效的评分 '{}'" => "invalid grade '{}'",
+        "无法更新 '{}.md' 的复习日期" => "cannot update the review date of '{}.md'",
+        "下次复习: {}（{} 天后）" => "Next review: {} (in {} days)",
+        "\n复习了 {} 篇笔记" => "\nReviewed {} notes",
+        "没有需要改写的笔记" => "No notes need rewriting",
+        "无法归档笔记" => "cannot archive notes",
+        "将归档: {} -> {}" => "Would archive: {} -> {}",
+        "已归档: {} -> {}" => "Archived: {} -> {}",
+        "跳过（{} 中已有同名文件）: {}" => {
+            "skipped (a file with the same name exists in {}): {}"
+        }
+        "没有创建于 {} 之前的笔记需要归档" => "no notes created before {} to archive",
+        "原文件已备份到: {}" => "Originals backed up to: {}",
+        "找不到笔记 '{}.md'" => "note '{}.md' not found",
+        "新文件名与旧文件名相同" => "the new file name is the same as the old one",
+        "无法重命名 '{}.md'" => "cannot rename '{}.md'",
+        "将重命名: {} -> {}" => "Would rename: {} -> {}",
+        "已重命名: {} -> {}" => "Renamed: {} -> {}",
+        "将改写 {} 处链接: {}" => "Would rewrite {} links: {}",
+        "已改写 {} 处链接: {}" => "Rewrote {} links: {}",
+        "{} 处链接指向归档的笔记（未改写）: {}" => {
+            "{} links point to archived notes (left unchanged): {}"
+        }
+        "\n指向归档笔记的链接:" => "\nLinks to archived notes:",
+        "不能合并 org 笔记 '{}'" => "cannot merge org note '{}'",
+        "不能把笔记并入它自己" => "cannot merge a note into itself",
+        "无法合并 '{}.md'" => "cannot merge '{}.md'",
+        "将合并: {} -> {}" => "Would merge: {} -> {}",
+        "已合并: {} -> {}" => "Merged: {} -> {}",
+        "将加上标签: {}" => "Would add tags: {}",
+        "已加上标签: {}" => "Added tags: {}",
+        "将改用创建时间: {}" => "Would use the earlier creation time: {}",
+        "已改用创建时间: {}" => "Using the earlier creation time: {}",
+        "来源目录与合并到的博客目录相同" => {
+            "the source is the same directory as the vault"
+        }
+        "无法合并 '{}'" => "cannot merge '{}'",
+        "重名: {}" => "Conflict: {}",
+        "{} 篇笔记与当前目录中的笔记重名而内容不同，没有复制任何文件；使用 --rename 为它们改名" => {
+            "{} notes have the same name as different notes in the vault, nothing was copied; use --rename to rename them"
+        }
+        "将复制: {} -> {}" => "Would copy: {} -> {}",
+        "已复制: {} -> {}" => "Copied: {} -> {}",
+        "警告: 附件 {} 已存在且内容不同，保留原文件" => {
+            "warning: attachment {} already exists with different content, kept the existing file"
+        }
+        "共 {} 篇笔记、{} 个附件，{} 篇笔记已存在且内容相同" => {
+            "{} notes, {} attachments; {} notes already present with the same content"
+        }
+        "已写入: {}" => "Written: {}",
+        "无法创建笔记 '{}'" => "cannot create note '{}'",
+        "文件已存在" => "file already exists",
+        "已创建: {}" => "Created: {}",
+        "{}: 无法识别的创建时间 '{}'" => "{}: unrecognized creation date '{}'",
+        "无法确定 gtx 的路径" => "cannot determine the path of gtx",
+        "无法安装 {} 钩子" => "cannot install the {} hook",
+        "已安装: {}" => "Installed: {}",
+        "'{}' 不存在" => "'{}' does not exist",
+        "无法导入 '{}'" => "cannot import '{}'",
+        "'{}' 不是目录，请先解压 Notion 导出的 ZIP 文件" => {
+            "'{}' is not a directory; unzip the Notion export first"
+        }
+        "已从 {} 导入 {} 篇笔记、{} 个附件" => {
+            "Imported from {}: {} notes, {} attachments"
+        }
+        "没有标题匹配 '{}' 的笔记" => "no note titles match '{}'",
+        "模板 {} 解析失败，{}" => "cannot parse template {}: {}",
+        "无法读取模板 '{}'" => "cannot read template '{}'",
+        "终端界面出错" => "terminal UI failed",
+        "无法启动编辑器 '{}'" => "cannot start editor '{}'",
+        "编辑器 '{}' 异常退出" => "editor '{}' exited abnormally",
+        "{} ({}.md，匹配: {})" => "{} ({}.md, matched: {})",
+        "     …… 另有 {} 篇，请使用更具体的查询" => {
+            "     ... {} more, try a more specific query"
+        }
+        "选择要打开的笔记 [1-{}]，直接回车取消: " => {
+            "Open which note [1-{}]? Press Enter to cancel: "
+        }
+        "已取消" => "Cancelled",
+        "无效的序号 '{}'" => "invalid number '{}'",
+        // 参数解析
+        "选项 {} 需要一个值" => "option {} needs a value",
+        "选项 {} 需要一个非负整数，而不是 '{}'" => {
+            "option {} needs a non-negative integer, not '{}'"
+        }
+        "选项 {} 的列数必须大于 0" => {
+            "option {}: the number of columns must be greater than 0"
+        }
+        "选项 {} 应为 memory 或 sqlite，而不是 '{}'" => {
+            "option {} must be memory or sqlite, not '{}'"
+        }
+        "选项 {} 应为 wiki、markdown、obsidian 或 org，而不是 '{}'" => {
+            "option {} must be wiki, markdown, obsidian or org, not '{}'"
+        }
+        "选项 {} 应为 frontmatter 或 git，而不是 '{}'" => {
+            "option {} must be frontmatter or git, not '{}'"
+        }
+        "选项 {} 应为 created、created-desc、title 或 modified，而不是 '{}'" => {
+            "option {} must be created, created-desc, title or modified, not '{}'"
+        }
+        "未知选项 '{}'" => "unknown option '{}'",
+        "多余的参数 '{}'" => "unexpected argument '{}'",
+        "未知子命令 '{}'" => "unknown subcommand '{}'",
+        "--purge 与 --trash-dir 不能同时使用" => "--purge cannot be used with --trash-dir",
+        "--full 与 --changed-only 不能同时使用" => {
+            "--full cannot be used with --changed-only"
+        }
+        "--quiet 与 --verbose 不能同时使用" => "--quiet cannot be used with --verbose",
+        "缺少参数 <标题>" => "missing argument <title>",
+        "缺少参数 <查询>" => "missing argument <query>",
+        "无效的页面名称 '{}'" => "invalid page name '{}'",
+        "不支持的格式 '{}'，可选: plain, fzf" => {
+            "unsupported format '{}', expected: plain, fzf"
+        }
+        "不支持的格式 '{}'，可选: dot" => "unsupported format '{}', expected: dot",
+        "不支持的格式 '{}'，可选: json, csv, opml, html, hugo, zola, jekyll, anki, epub" => {
+            "unsupported format '{}', expected: json, csv, opml, html, hugo, zola, jekyll, anki, epub"
+        }
+        "--base-url 与 --robots 只用于 html 格式" => {
+            "--base-url and --robots only apply to the html format"
+        }
+        "--base-url 应为以 http:// 或 https:// 开头的绝对地址: {}" => {
+            "--base-url must be an absolute address starting with http:// or https://: {}"
+        }
+        "--robots 需要用 --base-url 指定站点地址" => "--robots requires --base-url",
+        "警告: 没有用 --base-url 指定站点的绝对地址，未写入 sitemap.xml" => {
+            "warning: no absolute site address given with --base-url, sitemap.xml not written"
+        }
+        "epub 格式需要用 -o 指定输出文件" => {
+            "the epub format needs an output file given with -o"
+        }
+        "--tag 与 --title 只用于 epub 格式" => {
+            "--tag and --title only apply to the epub format"
+        }
+        "没有可以收入电子书的笔记" => "no notes to put in the book",
+        "没有带有标签 '{}' 的笔记" => "no notes tagged '{}'",
+        "已导出 {} 章到 {}" => "Exported {} chapters to {}",
+        "{} 格式需要用 -o 指定站点目录" => {
+            "the {} format needs a site directory given with -o"
+        }
+        "不支持的来源 '{}'，可选: joplin、enex、notion" => {
+            "unsupported source '{}', expected: joplin, enex, notion"
+        }
+        "缺少参数 <路径>" => "missing argument <path>",
+        "未知的 hook 子命令 '{}'" => "unknown hook subcommand '{}'",
+        "未知的 tag 子命令 '{}'" => "unknown tag subcommand '{}'",
+        "缺少参数 <旧标签>" => "missing argument <old tag>",
+        "缺少参数 <新标签>" => "missing argument <new tag>",
+        "缺少参数 <标签>" => "missing argument <tag>",
+        "缺少选项 --into <新标签>" => "missing option --into <new tag>",
+        "缺少选项 --query <查询>" => "missing option --query <query>",
+        "缺少选项 --before <日期>" => "missing option --before <date>",
+        "选项 {} 需要一个日期，如 2022-01-01，而不是 '{}'" => {
+            "option {} needs a date such as 2022-01-01, not '{}'"
+        }
+        "缺少参数 <旧文件名>" => "missing argument <old name>",
+        "缺少参数 <新文件名>" => "missing argument <new name>",
+        "缺少参数 <来源目录>" => "missing argument <source>",
+        "缺少参数 <保留的笔记>" => "missing argument <kept note>",
+        "缺少参数 <并入的笔记>" => "missing argument <merged note>",
+        "无效的文件名 '{}'" => "invalid file name '{}'",
+        "无效的标签 '{}'" => "invalid tag '{}'",
+        "新旧标签相同" => "the old and new tags are the same",
+        "选项 {} 需要一个端口号，而不是 '{}'" => {
+            "option {} needs a port number, not '{}'"
+        }
+        // 配置文件
+        "无法读取配置文件 {}: {}" => "cannot read config file {}: {}",
+        "配置文件 {} 第 {} 行: {}" => "config file {} line {}: {}",
+        "表头缺少 ']'" => "table header is missing ']'",
+        "未知的表 [{}]" => "unknown table [{}]",
+        "[{}] 只能写在用户配置中" => "[{}] is only allowed in the user config",
+        "vault 只能写在用户配置中" => "vault is only allowed in the user config",
+        "应为 `键 = 值`" => "expected `key = value`",
+        "数组缺少 ']'" => "array is missing ']'",
+        "link_style 应为 \"wiki\"、\"markdown\"、\"obsidian\" 或 \"org\"，而不是 \"{}\"" => {
+            "link_style must be \"wiki\", \"markdown\", \"obsidian\" or \"org\", not \"{}\""
+        }
+        "backend 应为 \"memory\" 或 \"sqlite\"，而不是 \"{}\"" => {
+            "backend must be \"memory\" or \"sqlite\", not \"{}\""
+        }
+        "time_format 应为 \"24h\" 或 \"12h\"，而不是 \"{}\"" => {
+            "time_format must be \"24h\" or \"12h\", not \"{}\""
+        }
+        "az_groups 应为 \"letter\" 或 \"block\"，而不是 \"{}\"" => {
+            "az_groups must be \"letter\" or \"block\", not \"{}\""
+        }
+        "dates_from 应为 \"frontmatter\" 或 \"git\"，而不是 \"{}\"" => {
+            "dates_from must be \"frontmatter\" or \"git\", not \"{}\""
+        }
+        "lang 应为 \"zh\" 或 \"en\"，而不是 \"{}\"" => {
+            "lang must be \"zh\" or \"en\", not \"{}\""
+        }
+        "环境变量 {} 应为 zh 或 en，而不是 '{}'" => {
+            "environment variable {} must be zh or en, not '{}'"
+        }
+        "未知的配置项 '{}'" => "unknown setting '{}'",
+        "未知的配置项 '{}.{}'" => "unknown setting '{}.{}'",
+        "字符串" => "a string",
+        "整数" => "an integer",
+        "布尔值" => "a boolean",
+        "数组" => "an array",
+        "{} 应为字符串，而不是{}" => "{} must be a string, not {}",
+        "{} 应为字符串数组，而不是{}" => "{} must be an array of strings, not {}",
+        "{} 不能为空数组" => "{} cannot be an empty array",
+        "{} 应为字符串或字符串数组，而不是{}" => {
+            "{} must be a string or an array of strings, not {}"
+        }
+        "{} 应为 \"created\"、\"created-desc\"、\"title\" 或 \"modified\"，而不是 \"{}\"" => {
+            "{} must be \"created\", \"created-desc\", \"title\" or \"modified\", not \"{}\""
+        }
+        "{} 应为 true 或 false，而不是{}" => "{} must be true or false, not {}",
+        "{} 应为非负整数，而不是 {}" => "{} must be a non-negative integer, not {}",
+        "{} 应为非负整数，而不是{}" => "{} must be a non-negative integer, not {}",
+        "{} 应为正整数或 \"auto\"，而不是 {}" => {
+            "{} must be a positive integer or \"auto\", not {}"
+        }
+        "{} 应为正整数或 \"auto\"，而不是 \"{}\"" => {
+            "{} must be a positive integer or \"auto\", not \"{}\""
+        }
+        "{} 应为正整数或 \"auto\"，而不是{}" => {
+            "{} must be a positive integer or \"auto\", not {}"
+        }
+        "无法解析的值 '{}'" => "cannot parse value '{}'",
+        "缺少值" => "missing value",
+        "字符串缺少结尾的 '\"'" => "string is missing the closing '\"'",
+        "不支持的转义 '\\{}'" => "unsupported escape '\\{}'",
+        "字符串缺少结尾的 \"'\"" => "string is missing the closing \"'\"",
+        "数组中缺少 ',' 或 ']'" => "expected ',' or ']' in array",
+        // 构建过程
+        "\n=== 处理文件: {} ===" => "\n=== Processing: {} ===",
+        "清理生成页面失败 {}: {}" => "cannot clean up generated page {}: {}",
+        "模板 {}.md 渲染失败，{}" => "cannot render template {}.md: {}",
+        "扫描笔记" => "Scanning",
+        "清理旧页面" => "Cleanup",
+        "写入页面" => "Writing",
+        "保存索引" => "Saving",
+        "警告: {} 不是生成的页面，未覆盖" => {
+            "warning: {} is not a generated page, not overwritten"
+        }
+        "提示: 可设置 tag_dir 把标签页写入子目录" => {
+            "hint: set tag_dir to write tag pages into a subdirectory"
+        }
+        "耗时: {}，共 {}" => "Time: {}, total {}",
+        "跳过 {}: 与 {} 是同一文件" => "skipped {}: same file as {}",
+        "跳过目录 {}: {}" => "skipped directory {}: {}",
+        "文件头格式错误" => "malformed frontmatter",
+        "缺少 {}" => "missing {}",
+        "{} 无法解析" => "unparsable {}",
+        "{}（{}）:" => "{} ({}):",
+        "无法识别的日期 '{}'，应为 YYYYMMDD、YYYY-MM-DD、YYYY/MM/DD 或 RFC 3339 格式" => {
+            "unrecognized date '{}', expected YYYYMMDD, YYYY-MM-DD, YYYY/MM/DD or RFC 3339"
+        }
+        "日期 '{}' 不存在" => "date '{}' does not exist",
+        "没有文件头" => "no frontmatter",
+        "没有读取权限" => "permission denied",
+        "解析时程序出错: {}" => "internal error while parsing: {}",
+        "不是 UTF-8 编码的文本" => "not UTF-8 text",
+        "字段为空" => "empty field",
+        // 生成的页面
+        "{}\n# 失效链接\n" => "{}\n# Broken Links\n",
+        "没有失效链接\n" => "No broken links\n",
+        "{}\n# 链接\n" => "{}\n# Links\n",
+        "没有链接\n" => "No links\n",
+        "`{}`（失效）" => "`{}` (broken)",
+        "{}\n# 归档\n" => "{}\n# Archive\n",
+        "没有归档的笔记\n" => "No archived notes\n",
+        "\n## 没有创建时间\n" => "\n## No creation date\n",
+        "{}\n# 待办\n" => "{}\n# Tasks\n",
+        "没有待办事项\n" => "No open tasks\n",
+        "\n## 截止日期\n" => "\n## Due\n",
+        "\n## 标签\n" => "\n## Tags\n",
+        "{}\n# 附件\n" => "{}\n# Attachments\n",
+        "\n## 已引用\n" => "\n## Used\n",
+        "没有笔记嵌入附件\n" => "No notes embed attachments\n",
+        "\n## 缺失\n" => "\n## Missing\n",
+        "\n## 未引用\n" => "\n## Unused\n",
+        "项目" => "Item",
+        "数值" => "Value",
+        "\n每月笔记数" => "\nNotes per month",
+        "月份" => "Month",
+        "笔记数" => "Notes",
+        "最长的笔记" => "Longest notes",
+        "最短的笔记" => "Shortest notes",
+        "笔记" => "Note",
+        "标题" => "Title",
+        "字数" => "Words",
+        "阅读时间" => "Reading time",
+        "\n没有标签的笔记" => "\nUntagged notes",
+        "{}\n# 统计\n" => "{}\n# Statistics\n",
+        "\n# 每月笔记数\n" => "\n# Notes per Month\n",
+        "{} 分钟" => "{} min",
+        "\n# 没有标签的笔记\n" => "\n# Untagged Notes\n",
+        "笔记总数" => "Notes",
+        "标签总数" => "Tag uses",
+        "不同标签数" => "Distinct tags",
+        "平均每篇标签数" => "Tags per note",
+        "没有标签的笔记" => "Untagged notes",
+        "总字数" => "Words",
+        "总阅读时间" => "Reading time",
+        "\n链接图" => "\nLink graph",
+        "\n# 链接图\n" => "\n# Link Graph\n",
+        "\n中心笔记" => "\nHubs",
+        "\n# 中心笔记\n" => "\n# Hubs\n",
+        "\n孤岛" => "\nIslands",
+        "\n# 孤岛\n" => "\n# Islands\n",
+        "入链" => "In",
+        "出链" => "Out",
+        "中心度" => "Rank",
+        "链接数" => "Links",
+        "连通分量数" => "Components",
+        "最大连通分量的笔记数" => "Largest component",
+        "孤岛数" => "Islands",
+        "没有链接的笔记" => "Notes without links",
+        "未知" => "unknown",
+        "上午" => "Morning",
+        "下午" => "Afternoon",
+        "晚上" => "Evening",
+        "时间未知" => "Unknown time",
+        "{}\n# 标签共现\n" => "{}\n# Tag Co-occurrence\n",
+        "没有在同一篇笔记中出现的标签\n" => "No tags appear together in a note\n",
+        "\n| 标签 | 标签 | 共同笔记 | 提示 |\n| --- | --- | --- | --- |\n" => {
+            "\n| Tag | Tag | Shared notes | Hint |\n| --- | --- | --- | --- |\n"
+        }
+        "总是一起出现，可考虑合并" => "always used together, consider merging",
+        "{} 只与 {} 一起出现，可考虑改为 {}/{}" => {
+            "{} only appears with {}, consider renaming it to {}/{}"
+        }
+        "{}\n{}{} – {}，共 {} 篇笔记\n" => "{}\n{}{} – {}, {} notes\n",
+        "{}\n# 创建热力图\n\n{} 至 {}，共 {} 篇笔记\n\n" => {
+            "{}\n# Creation Heatmap\n\n{} to {}, {} notes\n\n"
+        }
+        " {}月 |" => " M{} |",
+        "一 二 三 四 五 六 日" => "Mon Tue Wed Thu Fri Sat Sun",
+        "\n少 {} 多\n" => "\nLess {} More\n",
+        "{}\n# 标签云\n" => "{}\n# Tag Cloud\n",
+        "没有标签\n" => "No tags\n",
+        "{}\n# 标题索引\n" => "{}\n# Title Index\n",
+        "没有笔记\n" => "No notes\n",
+        "其他" => "Other",
+        "汉字" => "Han",
+        "假名" => "Kana",
+        "谚文" => "Hangul",
+        "希腊字母" => "Greek",
+        "西里尔字母" => "Cyrillic",
+        _ => return None,
+    })
+}
@@ -0,0 +1,964 @@
+// 从其他笔记软件导入：把导出的笔记转换为带有 Title、Created、Tags 文件头的 markdown
+//
+// Joplin 的 JEX 文件（tar 归档）与 RAW 导出目录的内容相同：
+// 每个条目（笔记、笔记本、标签、附件、笔记与标签的关联）是一个 <id>.md 文件，
+// 第一行为标题，随后是正文，末尾是 `键: 值` 形式的元数据块，type_ 字段区分条目类型；
+// 附件的文件位于 resources/<id>.<扩展名>。
+//
+// Notion 导出的 ZIP 解压后，每个页面是一个 `标题 <32 位十六进制>.md` 文件，子页面与附件
+// 位于同名（不含 .md）的目录中。页面第一行为 `# 标题`，随后一段 `键: 值` 为页面属性，
+// 页面之间以百分号编码的相对路径链接。
+//
+// Evernote 的 ENEX 文件是 XML，<en-export> 下每个 <note> 含有 <title>、<created>、
+// <updated>、若干 <tag> 与 <content>，content 为 CDATA 中的 ENML（XHTML 的子集）。
+
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::date::{Date, Time};
+use crate::html::{self, Node};
+use crate::note::FrontmatterKeys;
+use crate::serve::percent_decode;
+
+/// 导入的附件所在的目录，位于博客目录下
+pub const ATTACHMENTS_DIR: &str = "attachments";
+
+/// 一次导入的结果
+pub struct ImportReport {
+    /// 新建的笔记文件
+    pub notes: Vec<PathBuf>,
+    /// 放入 ATTACHMENTS_DIR 的附件数
+    pub attachments: usize,
+}
+
+// 导入后的一篇笔记，created 与 updated 为 (YYYYMMDD, HH:MM)，没有时刻时 HH:MM 为空
+struct ImportedNote {
+    title: String,
+    created: Option<(String, String)>,
+    updated: Option<(String, String)>,
+    tags: Vec<String>,
+    body: String,
+}
+
+// Joplin 条目的 type_
+const JOPLIN_NOTE: &str = "1";
+const JOPLIN_RESOURCE: &str = "4";
+const JOPLIN_TAG: &str = "5";
+const JOPLIN_NOTE_TAG: &str = "6";
+
+// Joplin 的一个条目
+struct JoplinItem {
+    title: String,
+    body: String,
+    meta: HashMap<String, String>,
+}
+
+impl JoplinItem {
+    fn get(&self, key: &str) -> &str {
+        self.meta.get(key).map_or("", String::as_str)
+    }
+}
+
+/// 导入 Joplin 导出的 JEX 文件或 RAW 目录，笔记写入 dir，附件复制到 dir/ATTACHMENTS_DIR
+///
+/// 文件名取自标题，与已有文件重名时加上序号，已有的文件不会被覆盖。
+/// 指向其他笔记的 `[文字](:/id)` 改写为 `[[文件名|文字]]`，指向附件的改写为附件的相对路径。
+/// 时间保持 Joplin 记录的 UTC 时间，已删除到回收站的笔记不会导入。
+pub fn import_joplin(
+    source: &Path,
+    dir: &Path,
+    keys: &FrontmatterKeys,
+) -> io::Result<ImportReport> {
+    let files = if source.is_dir() {
+        read_dir_files(source)?
+    } else {
+        read_tar(&fs::read(source)?)?
+    };
+
+    // 条目与附件文件分开，附件以 id（不含扩展名的文件名）查找
+    let mut items = Vec::new();
+    let mut resource_files = HashMap::new();
+    for (path, data) in files {
+        let (parent, file_name) = path.rsplit_once('/').unwrap_or(("", &path));
+        if parent.ends_with("resources") {
+            let id = file_name.split('.').next().unwrap_or(file_name).to_string();
+            resource_files.insert(id, (file_name.to_string(), data));
+        } else if parent.is_empty()
+            && let Some(id) = file_name.strip_suffix(".md")
+            && is_joplin_id(id)
+            && let Some(item) = parse_joplin_item(&String::from_utf8_lossy(&data))
+        {
+            items.push(item);
+        }
+    }
+
+    let of_type = |kind: &'static str| items.iter().filter(move |item| item.get("type_") == kind);
+    let tag_names: HashMap<&str, &str> = of_type(JOPLIN_TAG)
+        .map(|tag| (tag.get("id"), tag.title.as_str()))
+        .collect();
+    let mut note_tags: HashMap<&str, Vec<String>> = HashMap::new();
+    for link in of_type(JOPLIN_NOTE_TAG) {
+        if let Some(name) = tag_names.get(link.get("tag_id")) {
+            note_tags
+                .entry(link.get("note_id"))
+                .or_default()
+                .push(tag_name(name));
+        }
+    }
+    let mut notes: Vec<&JoplinItem> = of_type(JOPLIN_NOTE)
+        .filter(|note| matches!(note.get("deleted_time"), "" | "0"))
+        .collect();
+    notes.sort_by(|a, b| a.get("created_time").cmp(b.get("created_time")));
+
+    // 先确定全部文件名与附件路径，才能改写笔记之间的链接
+    let mut taken = HashSet::new();
+    let names: HashMap<&str, String> = notes
+        .iter()
+        .map(|note| (note.get("id"), unique_name(dir, &note.title, &mut taken)))
+        .collect();
+    let mut resources: Vec<(&str, String, Vec<u8>)> = Vec::new();
+    for resource in of_type(JOPLIN_RESOURCE) {
+        if let Some((file_name, data)) = resource_files.remove(resource.get("id")) {
+            resources.push((resource.get("id"), file_name, data));
+        }
+    }
+    resources.sort_by(|a, b| a.1.cmp(&b.1));
+
+    let mut report = ImportReport {
+        notes: Vec::new(),
+        attachments: 0,
+    };
+    // 附件的 id 到 ATTACHMENTS_DIR 中的文件名
+    let mut attachments: HashMap<&str, String> = HashMap::new();
+    if !resources.is_empty() {
+        let attachments_dir = dir.join(ATTACHMENTS_DIR);
+        fs::create_dir_all(&attachments_dir)?;
+        let mut taken = HashSet::new();
+        for (id, file_name, data) in resources {
+            let file_name = unique_attachment(&attachments_dir, &file_name, &data, &mut taken);
+            let path = attachments_dir.join(&file_name);
+            // 同一附件再次导入时内容相同，不必重写
+            if !path.exists() {
+                fs::write(&path, &data)?;
+            }
+            attachments.insert(id, file_name);
+            report.attachments += 1;
+        }
+    }
+
+    for note in notes {
+        let body = rewrite_links(&note.body, &|target, text, embed| {
+            let id = target.strip_prefix(":/")?;
+            if !is_joplin_id(id.split('#').next().unwrap_or_default()) {
+                return None;
+            }
+            if let Some(file_name) = attachments.get(id) {
+                let bang = if embed { "!" } else { "" };
+                let file_name = file_name.replace(' ', "%20");
+                return Some(format!(
+                    "{}[{}]({}/{})",
+                    bang, text, ATTACHMENTS_DIR, file_name
+                ));
+            }
+            let (id, heading) = match id.split_once('#') {
+                Some((id, heading)) => (id, format!("#{}", heading)),
+                None => (id, String::new()),
+            };
+            let name = names.get(id)?;
+            Some(if text.is_empty() || text == name {
+                format!("[[{}{}]]", name, heading)
+            } else {
+                format!("[[{}{}|{}]]", name, heading, text)
+            })
+        });
+        let imported = ImportedNote {
+            title: note.title.clone(),
+            created: joplin_datetime(note, "user_created_time", "created_time"),
+            updated: joplin_datetime(note, "user_updated_time", "updated_time"),
+            tags: note_tags.remove(note.get("id")).unwrap_or_default(),
+            body,
+        };
+        let path = dir.join(format!("{}.md", names[note.get("id")]));
+        write_note(&path, &imported, keys)?;
+        report.notes.push(path);
+    }
+    Ok(report)
+}
+
+// Notion 页面属性中表示标签、创建与修改时间的名称，不区分大小写
+const NOTION_TAGS: &[&str] = &["tags", "tag", "标签"];
+const NOTION_CREATED: &[&str] = &["created", "created time", "date created", "创建时间"];
+const NOTION_UPDATED: &[&str] = &["last edited time", "updated", "last edited", "编辑时间"];
+
+/// 导入解压后的 Notion markdown 导出目录，页面写入 dir，附件复制到 dir/ATTACHMENTS_DIR
+///
+/// 文件名取自页面标题（去掉 Notion 附加的哈希），与已有文件重名时加上序号。
+/// 属性中的 Tags（逗号分隔）与 Created、Last edited time 写入文件头，其余属性保留在正文开头；
+/// 指向导出中其他页面的链接改写为 `[[文件名|文字]]`，指向附件的改写为附件的相对路径。
+/// 数据库的 CSV 文件不会导入。
+pub fn import_notion(
+    source: &Path,
+    dir: &Path,
+    keys: &FrontmatterKeys,
+) -> io::Result<ImportReport> {
+    let mut files = read_dir_files(source)?;
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+    let (pages, others): (Vec<_>, Vec<_>) = files
+        .into_iter()
+        .partition(|(path, _)| path.ends_with(".md"));
+
+    let mut taken = HashSet::new();
+    let pages: Vec<(String, String, ImportedNote)> = pages
+        .into_iter()
+        .map(|(path, data)| {
+            let note = parse_notion_page(&path, &String::from_utf8_lossy(&data));
+            let name = unique_name(dir, &note.title, &mut taken);
+            (path, name, note)
+        })
+        .collect();
+    let names: HashMap<String, String> = pages
+        .iter()
+        .map(|(path, name, _)| (path.clone(), name.clone()))
+        .collect();
+
+    let mut report = ImportReport {
+        notes: Vec::new(),
+        attachments: 0,
+    };
+    let mut attachments: HashMap<String, String> = HashMap::new();
+    let others: Vec<_> = others
+        .into_iter()
+        .filter(|(path, _)| !path.ends_with(".csv"))
+        .collect();
+    if !others.is_empty() {
+        let attachments_dir = dir.join(ATTACHMENTS_DIR);
+        fs::create_dir_all(&attachments_dir)?;
+        let mut taken = HashSet::new();
+        for (path, data) in others {
+            let file_name = path.rsplit('/').next().unwrap_or(&path);
+            let file_name = unique_attachment(&attachments_dir, file_name, &data, &mut taken);
+            let target = attachments_dir.join(&file_name);
+            if !target.exists() {
+                fs::write(&target, &data)?;
+            }
+            attachments.insert(path, file_name);
+            report.attachments += 1;
+        }
+    }
+
+    for (path, name, mut note) in pages {
+        let base = path.rsplit_once('/').map_or("", |(parent, _)| parent);
+        note.body = rewrite_links(&note.body, &|target, text, embed| {
+            if target.contains("://") || target.starts_with(['#', '/']) {
+                return None;
+            }
+            let target = percent_decode(target.split('#').next().unwrap_or_default())?;
+            let target = resolve_relative(base, &target);
+            if let Some(file_name) = attachments.get(&target) {
+                let bang = if embed { "!" } else { "" };
+                let file_name = file_name.replace(' ', "%20");
+                return Some(format!(
+                    "{}[{}]({}/{})",
+                    bang, text, ATTACHMENTS_DIR, file_name
+                ));
+            }
+            let name = names.get(&target)?;
+            Some(if text.is_empty() || text == name {
+                format!("[[{}]]", name)
+            } else {
+                format!("[[{}|{}]]", name, text)
+            })
+        });
+        let path = dir.join(format!("{}.md", name));
+        write_note(&path, &note, keys)?;
+        report.notes.push(path);
+    }
+    Ok(report)
+}
+
+// 解析 Notion 页面：标题取自第一行的 `# 标题`，没有时取自去掉哈希的文件名
+fn parse_notion_page(path: &str, content: &str) -> ImportedNote {
+    let file_name = path.rsplit('/').next().unwrap_or(path);
+    let stem = file_name.strip_suffix(".md").unwrap_or(file_name);
+    let mut title = strip_notion_hash(stem).to_string();
+
+    let mut lines = content.lines().peekable();
+    if let Some(heading) = lines.peek().and_then(|line| line.strip_prefix("# ")) {
+        title = heading.trim().to_string();
+        lines.next();
+    }
+    while lines.next_if(|line| line.trim().is_empty()).is_some() {}
+
+    // 紧接标题的一段全部为 `键: 值` 时是页面属性
+    let paragraph: Vec<&str> = lines
+        .clone()
+        .take_while(|line| !line.trim().is_empty())
+        .collect();
+    let properties: Option<Vec<(&str, &str)>> = paragraph
+        .iter()
+        .map(|line| {
+            let (key, value) = line.split_once(": ")?;
+            let plain = !key.is_empty()
+                && key.chars().count() <= 40
+                && !key.starts_with(['#', '-', '*', '>', '|', '!', '['])
+                && !key.contains(['[', '`']);
+            plain.then_some((key.trim(), value.trim()))
+        })
+        .collect();
+
+    let mut note = ImportedNote {
+        title,
+        created: None,
+        updated: None,
+        tags: Vec::new(),
+        body: String::new(),
+    };
+    let mut rest = Vec::new();
+    if let Some(properties) = properties.filter(|p| !p.is_empty()) {
+        for _ in 0..properties.len() {
+            lines.next();
+        }
+        for (key, value) in properties {
+            let key_lower = key.to_lowercase();
+            let is = |names: &[&str]| names.contains(&key_lower.as_str());
+            if is(NOTION_TAGS) {
+                note.tags
+                    .extend(value.split(',').map(tag_name).filter(|tag| !tag.is_empty()));
+            } else if is(NOTION_CREATED) && notion_datetime(value).is_some() {
+                note.created = notion_datetime(value);
+            } else if is(NOTION_UPDATED) && notion_datetime(value).is_some() {
+                note.updated = notion_datetime(value);
+            } else {
+                rest.push(format!("{}: {}", key, value));
+            }
+        }
+    }
+
+    // 未识别的属性保留在正文开头，与正文之间的空行随后续行一同保留
+    let mut body = String::new();
+    for line in rest.iter().map(String::as_str).chain(lines) {
+        body.push_str(line);
+        body.push('\n');
+    }
+    let mut body = body.trim_start_matches('\n').trim_end().to_string();
+    if !body.is_empty() {
+        body.push('\n');
+    }
+    note.body = body;
+    note
+}
+
+// 去掉 Notion 在文件名末尾附加的 ` <32 位十六进制>`
+fn strip_notion_hash(stem: &str) -> &str {
+    match stem.rsplit_once(' ') {
+        Some((name, hash)) if hash.len() == 32 && hash.bytes().all(|b| b.is_ascii_hexdigit()) => {
+            name
+        }
+        _ => stem,
+    }
+}
+
+// Notion 的时间依导出时的语言而定，如 `May 1, 2024 9:30 AM`、`2024年5月1日 09:30`、
+// `2024/05/01 9:30`
+fn notion_datetime(value: &str) -> Option<(String, String)> {
+    const MONTHS: [&str; 12] = [
+        "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+    ];
+    let value = value.trim();
+    let (date, time) = if let Some((month_day, rest)) = value.split_once(", ") {
+        let (month, day) = month_day.split_once(' ')?;
+        let month = month.to_lowercase();
+        let month = MONTHS.iter().position(|m| month.starts_with(m))?;
+        let (year, time) = rest.split_once(' ').unwrap_or((rest, ""));
+        let date = Date::new(year.parse().ok()?, month as u8 + 1, day.parse().ok()?)?;
+        (date, time)
+    } else if let Some((year, rest)) = value.split_once('年') {
+        let (month, rest) = rest.split_once('月')?;
+        let (day, time) = rest.split_once('日')?;
+        let date = Date::new(
+            year.trim().parse().ok()?,
+            month.trim().parse().ok()?,
+            day.trim().parse().ok()?,
+        )?;
+        (date, time)
+    } else {
+        let (date, time) = value.split_once(' ').unwrap_or((value, ""));
+        (Date::parse(date).ok()?.0, time)
+    };
+    let time = match time.trim() {
+        "" => String::new(),
+        time => Time::parse(time)?.to_string(),
+    };
+    Some((date.to_string(), time))
+}
+
+// 以 / 分隔的相对路径 target 相对于目录 base 的位置，处理 . 与 ..
+fn resolve_relative(base: &str, target: &str) -> String {
+    let mut parts: Vec<&str> = base.split('/').filter(|part| !part.is_empty()).collect();
+    for part in target.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            part => parts.push(part),
+        }
+    }
+    parts.join("/")
+}
+
+// 附件在 attachments_dir 中的文件名：同名且内容相同的已有附件直接复用，否则加上序号
+fn unique_attachment(
+    attachments_dir: &Path,
+    file_name: &str,
+    data: &[u8],
+    taken: &mut HashSet<String>,
+) -> String {
+    let (stem, ext) = match file_name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => (stem, format!(".{}", ext)),
+        _ => (file_name, String::new()),
+    };
+    let mut name = file_name.to_string();
+    let mut n = 2;
+    loop {
+        let path = attachments_dir.join(&name);
+        let reusable = !path.exists() || fs::read(&path).is_ok_and(|existing| existing == data);
+        if !taken.contains(&name) && reusable {
+            break;
+        }
+        name = format!("{} {}{}", stem, n, ext);
+        n += 1;
+    }
+    taken.insert(name.clone());
+    name
+}
+
+/// 导入 Evernote 导出的 ENEX 文件，每篇笔记写入 dir 下的一个文件
+///
+/// 文件名取自标题，与已有文件重名时加上序号，已有的文件不会被覆盖。
+/// 正文由 ENML 转换为 markdown，附件（<en-media>）与加密内容不会导入；时间保持 UTC。
+pub fn import_enex(source: &Path, dir: &Path, keys: &FrontmatterKeys) -> io::Result<ImportReport> {
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, "不是有效的 ENEX 文件");
+    let nodes = html::parse_xml(&fs::read_to_string(source)?);
+    let export = nodes
+        .iter()
+        .find_map(|node| match node {
+            Node::Element(element) if element.name == "en-export" => Some(element),
+            _ => None,
+        })
+        .ok_or_else(invalid)?;
+
+    let text = |note: &html::Element, name: &str| {
+        note.elements(name)
+            .next()
+            .map(|element| element.text().trim().to_string())
+            .unwrap_or_default()
+    };
+    let mut notes: Vec<ImportedNote> = export
+        .elements("note")
+        .map(|note| ImportedNote {
+            title: text(note, "title"),
+            created: enex_datetime(&text(note, "created")),
+            updated: enex_datetime(&text(note, "updated")),
+            tags: note
+                .elements("tag")
+                .map(|tag| tag_name(&tag.text()))
+                .filter(|tag| !tag.is_empty())
+                .collect(),
+            body: html::to_markdown(&text(note, "content")),
+        })
+        .collect();
+    notes.sort_by(|a, b| a.created.cmp(&b.created));
+
+    let mut report = ImportReport {
+        notes: Vec::new(),
+        attachments: 0,
+    };
+    let mut taken = HashSet::new();
+    for note in notes {
+        let path = dir.join(format!("{}.md", unique_name(dir, &note.title, &mut taken)));
+        write_note(&path, &note, keys)?;
+        report.notes.push(path);
+    }
+    Ok(report)
+}
+
+// ENEX 的时间形如 20240501T093000Z，改写为 RFC 3339 后解析
+fn enex_datetime(text: &str) -> Option<(String, String)> {
+    let (date, time) = text.split_once('T')?;
+    let clock = time.strip_suffix('Z').unwrap_or(time);
+    if clock.len() != 6 || !clock.is_ascii() {
+        return None;
+    }
+    let rfc3339 = format!("{}T{}:{}:{}Z", date, &clock[..2], &clock[2..4], &clock[4..]);
+    let (date, time) = Date::parse(&rfc3339).ok()?;
+    let date = format!("{:04}{:02}{:02}", date.year, date.month, date.day);
+    Some((date, time.unwrap_or_default()))
+}
+
+// gtx 的标签以空白分隔，标签中的空白改为 -
+fn tag_name(name: &str) -> String {
+    name.split_whitespace().collect::<Vec<_>>().join("-")
+}
+
+// 写入一篇导入的笔记，文件已存在时报错而不是覆盖
+fn write_note(path: &Path, note: &ImportedNote, keys: &FrontmatterKeys) -> io::Result<()> {
+    let mut header = format!("---\n{}: {}\n", keys.title, note.title);
+    let datetime = |(date, time): &(String, String)| format!("{} {}", date, time);
+    if let Some(updated) = &note.updated {
+        header.push_str(&format!(
+            "{}: {}\n",
+            keys.updated,
+            datetime(updated).trim_end()
+        ));
+    }
+    if let Some(created) = &note.created {
+        header.push_str(&format!(
+            "{}: {}\n",
+            keys.created,
+            datetime(created).trim_end()
+        ));
+    }
+    header.push_str(&format!("{}: {}\n---\n", keys.tags, note.tags.join(" ")));
+
+    let mut file = File::create_new(path)?;
+    file.write_all(header.as_bytes())?;
+    file.write_all(note.body.as_bytes())?;
+    if !note.body.is_empty() && !note.body.ends_with('\n') {
+        file.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+// 由标题得到不与 dir 中已有文件及本次导入的其他笔记重名的文件名（不含 .md）
+fn unique_name(dir: &Path, title: &str, taken: &mut HashSet<String>) -> String {
+    // 文件名中不能出现路径分隔符
+    let base = title.trim().replace(['/', '\\'], "-");
+    let base = if base.is_empty() {
+        "未命名".to_string()
+    } else {
+        base
+    };
+    let mut name = base.clone();
+    let mut n = 2;
+    while taken.contains(&name) || dir.join(format!("{}.md", name)).exists() {
+        name = format!("{} {}", base, n);
+        n += 1;
+    }
+    taken.insert(name.clone());
+    name
+}
+
+// 解析 Joplin 条目：末尾连续的 `键: 值` 行为元数据，与正文之间以空行分隔
+fn parse_joplin_item(content: &str) -> Option<JoplinItem> {
+    let lines: Vec<&str> = content.trim_end().lines().collect();
+    let mut meta = HashMap::new();
+    let mut end = lines.len();
+    while end > 0 {
+        let line = lines[end - 1];
+        let Some((key, value)) = line.split_once(':') else {
+            break;
+        };
+        if key.is_empty() || !key.bytes().all(|b| b.is_ascii_lowercase() || b == b'_') {
+            break;
+        }
+        meta.insert(key.to_string(), value.trim().to_string());
+        end -= 1;
+    }
+    if !meta.contains_key("type_") {
+        return None;
+    }
+
+    let rest = &lines[..end];
+    let title = rest.first().copied().unwrap_or_default().to_string();
+    // 标题后有一个空行，正文与元数据之间也有一个空行
+    let body_lines = rest.get(2..).unwrap_or_default();
+    let body_lines = body_lines.strip_suffix(&[""]).unwrap_or(body_lines);
+    let mut body = body_lines.join("\n");
+    if !body.is_empty() {
+        body.push('\n');
+    }
+    Some(JoplinItem { title, body, meta })
+}
+
+// Joplin 的时间形如 2024-05-01T09:30:00.000Z，优先使用用户修改过的时间
+fn joplin_datetime(item: &JoplinItem, user_key: &str, key: &str) -> Option<(String, String)> {
+    let value = match item.get(user_key) {
+        "" => item.get(key),
+        value => value,
+    };
+    let (date, time) = Date::parse(value).ok()?;
+    let date = format!("{:04}{:02}{:02}", date.year, date.month, date.day);
+    let time = time.unwrap_or_default();
+    Some((date, time))
+}
+
+// Joplin 的 id 为 32 位小写十六进制
+fn is_joplin_id(text: &str) -> bool {
+    text.len() == 32
+        && text
+            .bytes()
+            .all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+}
+
+// 改写正文中的 `[文字](地址)` 与 `![文字](地址)`，f 返回 None 时保持原样；跳过代码块
+fn rewrite_links(body: &str, f: &dyn Fn(&str, &str, bool) -> Option<String>) -> String {
+    let mut result = String::with_capacity(body.len());
+    let mut in_code_block = false;
+    for line in body.split_inclusive('\n') {
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+            result.push_str(line);
+            continue;
+        }
+        if in_code_block {
+            result.push_str(line);
+            continue;
+        }
+
+        let mut rest = line;
+        while let Some(start) = rest.find("](") {
+            let Some(open) = rest[..start].rfind('[') else {
+                result.push_str(&rest[..start + 2]);
+                rest = &rest[start + 2..];
+                continue;
+            };
+            let target_start = start + 2;
+            let Some(close) = rest[target_start..].find(')') else {
+                break;
+            };
+            let target = &rest[target_start..target_start + close];
+            let embed = open > 0 && rest.as_bytes()[open - 1] == b'!';
+            let link_start = if embed { open - 1 } else { open };
+            match f(target, &rest[open + 1..start], embed) {
+                Some(link) => {
+                    result.push_str(&rest[..link_start]);
+                    result.push_str(&link);
+                }
+                None => result.push_str(&rest[..target_start + close + 1]),
+            }
+            rest = &rest[target_start + close + 1..];
+        }
+        result.push_str(rest);
+    }
+    result
+}
+
+// RAW 导出目录中的文件，路径相对于 root，以 / 分隔
+fn read_dir_files(root: &Path) -> io::Result<Vec<(String, Vec<u8>)>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(root)? {
+        let entry = entry?;
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if path.is_dir() {
+            for (sub, data) in read_dir_files(&path)? {
+                files.push((format!("{}/{}", name, sub), data));
+            }
+        } else {
+            files.push((name, fs::read(&path)?));
+        }
+    }
+    Ok(files)
+}
+
+// 读取 tar 归档（JEX）中的普通文件，支持 ustar 前缀与 GNU 长文件名
+fn read_tar(data: &[u8]) -> io::Result<Vec<(String, Vec<u8>)>> {
+    const BLOCK: usize = 512;
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, "不是有效的 JEX（tar）文件");
+    let field = |bytes: &[u8]| {
+        let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+        String::from_utf8_lossy(&bytes[..end]).into_owned()
+    };
+
+    let mut files = Vec::new();
+    let mut long_name = None;
+    let mut offset = 0;
+    while offset + BLOCK <= data.len() {
+        let header = &data[offset..offset + BLOCK];
+        // 归档以全零块结束
+        if header.iter().all(|&b| b == 0) {
+            break;
+        }
+        let size_text = field(&header[124..136]);
+        let size = usize::from_str_radix(size_text.trim(), 8).map_err(|_| invalid())?;
+        let start = offset + BLOCK;
+        let content = data.get(start..start + size).ok_or_else(invalid)?;
+        offset = start + size.div_ceil(BLOCK) * BLOCK;
+
+        let mut name = field(&header[..100]);
+        if &header[257..262] == b"ustar" {
+            let prefix = field(&header[345..500]);
+            if !prefix.is_empty() {
+                name = format!("{}/{}", prefix, name);
+            }
+        }
+        match header[156] {
+            b'L' => long_name = Some(field(content)),
+            b'0' | 0 => {
+                let name = long_name.take().unwrap_or(name);
+                let name = name.trim_start_matches("./").to_string();
+                files.push((name, content.to_vec()));
+            }
+            // 目录、pax 扩展头等
+            _ => long_name = None,
+        }
+    }
+    if files.is_empty() && offset == 0 {
+        return Err(invalid());
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTE: &str = "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa";
+    const OTHER: &str = "bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb";
+    const DELETED: &str = "cccccccccccccccccccccccccccccccc";
+    const RESOURCE: &str = "dddddddddddddddddddddddddddddddd";
+    const TAG: &str = "eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee";
+    const NOTE_TAG: &str = "ffffffffffffffffffffffffffffffff";
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("gtx-import-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    // 一个 tar 条目：ustar 头（不填校验和，read_tar 不检查）与补齐到 512 字节的内容
+    fn tar_entry(name: &str, kind: u8, data: &[u8]) -> Vec<u8> {
+        let mut header = vec![0u8; 512];
+        let (prefix, name) = match name.rsplit_once('/') {
+            Some((prefix, name)) if name.len() < 100 => (prefix, name),
+            _ => ("", name),
+        };
+        let name = &name.as_bytes()[..name.len().min(100)];
+        header[..name.len()].copy_from_slice(name);
+        header[124..135].copy_from_slice(format!("{:011o}", data.len()).as_bytes());
+        header[156] = kind;
+        header[257..263].copy_from_slice(b"ustar\0");
+        header[345..345 + prefix.len()].copy_from_slice(prefix.as_bytes());
+        let mut entry = header;
+        entry.extend_from_slice(data);
+        entry.resize(512 + data.len().div_ceil(512) * 512, 0);
+        entry
+    }
+
+    fn tar(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut data = Vec::new();
+        for (name, content) in entries {
+            data.extend(tar_entry(name, b'0', content));
+        }
+        data.extend([0u8; 1024]);
+        data
+    }
+
+    fn joplin_note(title: &str, body: &str, id: &str, meta: &str) -> String {
+        format!(
+            "{}\n\n{}\nid: {}\ncreated_time: 2024-05-01T09:30:00.000Z\n\
+             updated_time: 2024-05-02T10:00:00.000Z\n{}type_: 1",
+            title, body, id, meta
+        )
+    }
+
+    #[test]
+    fn tar_prefixes_long_names_and_directories() {
+        let long = format!("{}/{}.md", "d".repeat(120), "n".repeat(120));
+        let mut data = tar_entry("./resources/", b'5', b"");
+        data.extend(tar_entry(
+            "././@LongLink",
+            b'L',
+            format!("{}\0", long).as_bytes(),
+        ));
+        data.extend(tar_entry("truncated", b'0', b"long"));
+        data.extend(tar_entry(
+            &format!("{}/x.md", "p".repeat(120)),
+            b'0',
+            b"prefixed",
+        ));
+        data.extend(tar_entry("./resources/a.png", 0, &[7; 600]));
+        data.extend([0u8; 1024]);
+
+        let files = read_tar(&data).unwrap();
+        let names: Vec<&str> = files.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                long.clone(),
+                format!("{}/x.md", "p".repeat(120)),
+                "resources/a.png".to_string()
+            ]
+        );
+        assert_eq!(files[0].1, b"long");
+        assert_eq!(files[2].1, vec![7; 600]);
+
+        assert!(read_tar(b"not a tar").is_err());
+        assert!(read_tar(&[b'x'; 512]).is_err());
+        let mut truncated = tar_entry("a.md", b'0', &[1; 600]);
+        truncated.truncate(700);
+        assert!(read_tar(&truncated).is_err());
+    }
+
+    #[test]
+    fn joplin_notes_tags_links_and_attachments() {
+        let dir = temp_dir("joplin");
+        fs::write(dir.join("B.md"), "已有的笔记\n").unwrap();
+        fs::create_dir_all(dir.join(ATTACHMENTS_DIR)).unwrap();
+        let existing = format!("{}/{}.png", ATTACHMENTS_DIR, RESOURCE);
+        fs::write(dir.join(&existing), "另一张图").unwrap();
+
+        let body = format!(
+            "见 [B](:/{0}) 与 [](:/{0}#节)\n\n![图](:/{1})\n\n```\n[代码](:/{0})\n```\n",
+            OTHER, RESOURCE
+        );
+        let note = joplin_note(
+            "Rust/入门",
+            &body,
+            NOTE,
+            "user_created_time: 2020-01-02T03:04:00.000Z\ndeleted_time: 0\n",
+        );
+        let other = joplin_note("B", "", OTHER, "");
+        let deleted = joplin_note("删除", "", DELETED, "deleted_time: 1714556000000\n");
+        let resource = format!("图.png\n\nid: {}\nfile_extension: png\ntype_: 4", RESOURCE);
+        let tag = format!("rust lang\n\nid: {}\ntype_: 5", TAG);
+        let note_tag = format!(
+            "id: {}\nnote_id: {}\ntag_id: {}\ntype_: 6",
+            NOTE_TAG, NOTE, TAG
+        );
+        let names: Vec<String> = [NOTE, OTHER, DELETED, RESOURCE, TAG, NOTE_TAG]
+            .iter()
+            .map(|id| format!("{}.md", id))
+            .collect();
+        let jex = dir.join("export.jex");
+        fs::write(
+            &jex,
+            tar(&[
+                (&names[0], note.as_bytes()),
+                (&names[1], other.as_bytes()),
+                (&names[2], deleted.as_bytes()),
+                (&names[3], resource.as_bytes()),
+                (&names[4], tag.as_bytes()),
+                (&names[5], note_tag.as_bytes()),
+                (&format!("resources/{}.png", RESOURCE), b"png"),
+                ("README.md", b"not an item"),
+            ]),
+        )
+        .unwrap();
+
+        let report = import_joplin(&jex, &dir, &FrontmatterKeys::default()).unwrap();
+        assert_eq!(report.notes, [dir.join("Rust-入门.md"), dir.join("B 2.md")]);
+        assert_eq!(report.attachments, 1);
+        assert_eq!(fs::read_to_string(dir.join(&existing)).unwrap(), "另一张图");
+        let attachment = format!("{}/{} 2.png", ATTACHMENTS_DIR, RESOURCE);
+        assert_eq!(fs::read(dir.join(&attachment)).unwrap(), b"png");
+        assert_eq!(
+            fs::read_to_string(dir.join("Rust-入门.md")).unwrap(),
+            format!(
+                "---\nTitle: Rust/入门\nUpdated: 20240502 10:00\nCreated: 20200102 03:04\n\
+                 Tags: rust-lang\n---\n见 [[B 2|B]] 与 [[B 2#节]]\n\n![图]({})\n\n\
+                 ```\n[代码](:/{})\n```\n",
+                attachment.replace(' ', "%20"),
+                OTHER
+            )
+        );
+        assert_eq!(
+            fs::read_to_string(dir.join("B 2.md")).unwrap(),
+            "---\nTitle: B\nUpdated: 20240502 10:00\nCreated: 20240501 09:30\nTags: \n---\n"
+        );
+        assert_eq!(
+            fs::read_to_string(dir.join("B.md")).unwrap(),
+            "已有的笔记\n"
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn notion_pages_properties_links_and_attachments() {
+        let dir = temp_dir("notion");
+        let source = dir.join("export");
+        let hash = "0123456789abcdef0123456789abcdef";
+        let sub_dir = source.join(format!("Home {}", hash));
+        fs::create_dir_all(&sub_dir).unwrap();
+        fs::write(
+            source.join(format!("Home {}.md", hash)),
+            format!(
+                "# Home\n\nTags: rust, web dev\nCreated: May 1, 2024 9:30 AM\n\
+                 Last edited time: 2024年5月3日 18:05\nStatus: Done\n\n\
+                 见 [Sub](Home%20{0}/Sub%20{1}.md) 与 ![图](Home%20{0}/pic.png)\n\
+                 [外部](https://example.org) [锚点](#节)\n",
+                hash,
+                "f".repeat(32)
+            ),
+        )
+        .unwrap();
+        fs::write(
+            sub_dir.join(format!("Sub {}.md", "f".repeat(32))),
+            format!("# Sub\n\n[返回](../Home%20{}.md)\n", hash),
+        )
+        .unwrap();
+        fs::write(sub_dir.join("pic.png"), "png").unwrap();
+        fs::write(source.join("Table.csv"), "a,b\n").unwrap();
+        fs::create_dir_all(dir.join(ATTACHMENTS_DIR)).unwrap();
+        fs::write(dir.join(ATTACHMENTS_DIR).join("pic.png"), "另一张图").unwrap();
+
+        let report = import_notion(&source, &dir, &FrontmatterKeys::default()).unwrap();
+        assert_eq!(report.notes, [dir.join("Home.md"), dir.join("Sub.md")]);
+        assert_eq!(report.attachments, 1);
+        assert_eq!(
+            fs::read_to_string(dir.join(ATTACHMENTS_DIR).join("pic 2.png")).unwrap(),
+            "png"
+        );
+        assert_eq!(
+            fs::read_to_string(dir.join("Home.md")).unwrap(),
+            "---\nTitle: Home\nUpdated: 20240503 18:05\nCreated: 20240501 09:30\n\
+             Tags: rust web-dev\n---\nStatus: Done\n\n\
+             见 [[Sub]] 与 ![图](attachments/pic%202.png)\n\
+             [外部](https://example.org) [锚点](#节)\n"
+        );
+        assert_eq!(
+            fs::read_to_string(dir.join("Sub.md")).unwrap(),
+            "---\nTitle: Sub\nTags: \n---\n[[Home|返回]]\n"
+        );
+        assert!(!dir.join("Table.md").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn enex_notes_in_created_order() {
+        let dir = temp_dir("enex");
+        let source = dir.join("export.enex");
+        fs::write(
+            &source,
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <!DOCTYPE en-export SYSTEM \"http://xml.evernote.com/pub/evernote-export3.dtd\">\n\
+             <en-export>\
+             <note><title>第二篇</title><created>20240502T100000Z</created>\
+             <content><![CDATA[<?xml version=\"1.0\"?><en-note><div>你好 <b>世界</b></div></en-note>]]>\
+             </content><tag>web dev</tag><tag>rust</tag></note>\
+             <note><title>第一篇</title><created>20240501T093000Z</created>\
+             <updated>20240503T000000Z</updated><content><![CDATA[<en-note/>]]></content></note>\
+             </en-export>\n",
+        )
+        .unwrap();
+
+        let report = import_enex(&source, &dir, &FrontmatterKeys::default()).unwrap();
+        assert_eq!(report.notes, [dir.join("第一篇.md"), dir.join("第二篇.md")]);
+        assert_eq!(
+            fs::read_to_string(dir.join("第一篇.md")).unwrap(),
+            "---\nTitle: 第一篇\nUpdated: 20240503 00:00\nCreated: 20240501 09:30\nTags: \n---\n"
+        );
+        assert_eq!(
+            fs::read_to_string(dir.join("第二篇.md")).unwrap(),
+            "---\nTitle: 第二篇\nCreated: 20240502 10:00\nTags: web-dev rust\n---\n你好 **世界**\n"
+        );
+
+        fs::write(&source, "<html></html>").unwrap();
+        assert!(import_enex(&source, &dir, &FrontmatterKeys::default()).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,584 @@
+// 标签与日期索引
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::date::{Date, Time, to_timestamp};
+use crate::links::LinkIndex;
+use crate::note::{NEED_TAG, NoteMeta, STATUS_ORDER};
+
+/// 从某个键（标签、日期）到笔记列表的映射
+pub struct Index {
+    // 存储所有出现过的输入
+    inputs: HashSet<String>,
+    // 存储映射
+    map: HashMap<String, Vec<(String, String, String)>>,
+}
+
+impl Index {
+    pub fn new() -> Self {
+        Index {
+            inputs: HashSet::new(),
+            map: HashMap::new(),
+        }
+    }
+
+    /// 添加一个节点
+    pub fn add_node(
+        &mut self,
+        file_name: &str,
+        file_title: &str,
+        extra_info: &str,
+        input: Vec<&str>,
+    ) {
+        for i in input {
+            // 清理i（去除前后空格，转为小写）
+            let normalized_i = i.trim().to_string();
+
+            if !normalized_i.is_empty() {
+                // 添加到所有i集合
+                self.inputs.insert(normalized_i.clone());
+
+                // 添加到i到节点的映射
+                self.map.entry(normalized_i).or_default().push((
+                    file_name.to_string(),
+                    file_title.to_string(),
+                    extra_info.to_string(),
+                ));
+            }
+        }
+    }
+
+    /// 根据i获取节点名字列表
+    pub fn get_files_by_i(&self, i: &str) -> Option<&Vec<(String, String, String)>> {
+        let normalized_i = i.trim().to_string();
+        self.map.get(&normalized_i)
+    }
+
+    /// 获取i对应的节点数量
+    pub fn get_i_count(&self, i: &str) -> usize {
+        let normalized_i = i.trim().to_string();
+        self.map.get(&normalized_i).map_or(0, |files| files.len())
+    }
+
+    /// 获取所有出现过的i名称
+    pub fn get_inputs(&self) -> &HashSet<String> {
+        &self.inputs
+    }
+
+    /// 合并另一个索引的全部节点
+    pub fn merge(&mut self, other: Index) {
+        self.inputs.extend(other.inputs);
+        for (i, files) in other.map {
+            self.map.entry(i).or_default().extend(files);
+        }
+    }
+}
+
+impl Default for Index {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 层级标签 `a/b/c` 的各级前缀 `a`、`a/b`、`a/b/c`，普通标签只返回自身
+pub fn tag_levels(tag: &str) -> Vec<&str> {
+    let tag = tag.trim_matches('/');
+    let mut levels: Vec<&str> = tag
+        .match_indices('/')
+        .map(|(i, _)| &tag[..i])
+        .filter(|level| !level.is_empty() && !level.ends_with('/'))
+        .collect();
+    levels.push(tag);
+    levels
+}
+
+/// 层级标签的直接子标签
+pub fn child_tags<'a>(tags: &'a Index, parent: &str) -> Vec<&'a str> {
+    let prefix = format!("{}/", parent);
+    let mut children: Vec<&str> = tags
+        .get_inputs()
+        .iter()
+        .map(String::as_str)
+        .filter(|tag| {
+            tag.strip_prefix(&prefix)
+                .is_some_and(|rest| !rest.contains('/'))
+        })
+        .collect();
+    children.sort_unstable();
+    children
+}
+
+/// 年份 → 月份 → 日期页，用于生成年、月汇总页，键为 `2024` 与 `2024-05`，均从早到晚排列
+pub type DateTree<'a> = BTreeMap<String, BTreeMap<String, Vec<&'a str>>>;
+
+/// 将日期索引中的日期按年、月分组
+pub fn date_tree(dates: &Index) -> DateTree<'_> {
+    let mut tree = DateTree::new();
+    for day in dates.get_inputs() {
+        // 日期索引的键来自 Date 的显示，总能解析
+        let Ok((date, _)) = Date::parse(day) else {
+            continue;
+        };
+        tree.entry(date.year_key())
+            .or_default()
+            .entry(date.month_key())
+            .or_default()
+            .push(day.as_str());
+    }
+    for months in tree.values_mut() {
+        for days in months.values_mut() {
+            days.sort_unstable();
+        }
+    }
+    tree
+}
+
+/// 周 → 日期页，键为 `2024-W21`，均从早到晚排列
+pub type WeekTree<'a> = BTreeMap<String, Vec<&'a str>>;
+
+/// 将日期索引中的日期按 ISO 8601 周分组
+pub fn week_tree(dates: &Index) -> WeekTree<'_> {
+    let mut tree = WeekTree::new();
+    for day in dates.get_inputs() {
+        let Ok((date, _)) = Date::parse(day) else {
+            continue;
+        };
+        tree.entry(date.week_key()).or_default().push(day.as_str());
+    }
+    for days in tree.values_mut() {
+        days.sort_unstable();
+    }
+    tree
+}
+
+/// 一篇相关笔记：文件名、标题与共同的标签
+pub struct Related<'a> {
+    pub name: &'a str,
+    pub title: &'a str,
+    /// 按标签名排序
+    pub shared_tags: Vec<&'a str>,
+}
+
+/// 两个标签共同出现的次数
+pub struct TagPair<'a> {
+    /// 两个标签按字典序排列
+    pub tags: (&'a str, &'a str),
+    /// 同时含有两个标签的笔记数
+    pub count: usize,
+}
+
+/// 标签云中的一个标签
+pub struct CloudTag<'a> {
+    pub tag: &'a str,
+    /// 含有该标签的笔记数
+    pub count: usize,
+    /// 权重，1 到 `CLOUD_WEIGHTS`，笔记越多越大
+    pub weight: usize,
+}
+
+/// 标签云的权重级数
+pub const CLOUD_WEIGHTS: usize = 5;
+
+/// 最近创建或修改的笔记
+pub struct RecentNote<'a> {
+    pub name: &'a str,
+    pub title: &'a str,
+    /// 日期索引中的创建日期与时间，没有 Created 时为 None
+    pub created: Option<(&'a str, &'a str)>,
+    /// 文件的修改时间（Unix 秒）
+    pub modified: Option<u64>,
+}
+
+impl RecentNote<'_> {
+    /// 创建时间对应的 Unix 秒，Created 未做时区换算，按 UTC 计算
+    pub fn created_at(&self) -> Option<u64> {
+        let (day, time) = self.created?;
+        let (date, _) = Date::parse(day).ok()?;
+        Some(to_timestamp(date, Time::parse(time)))
+    }
+
+    /// 是否在创建之后又修改过：修改时间晚于创建时间一天以上，
+    /// 留出的一天用来抵消 Created 未做时区换算的误差
+    pub fn is_modified(&self) -> bool {
+        match (self.created_at(), self.modified) {
+            (Some(created), Some(modified)) => modified > created + 86_400,
+            (None, Some(_)) => true,
+            _ => false,
+        }
+    }
+
+    /// 最近一次活动的时间：修改过时为修改时间，否则为创建时间
+    pub fn latest(&self) -> u64 {
+        if self.is_modified() {
+            self.modified.unwrap_or(0)
+        } else {
+            self.created_at().unwrap_or(0)
+        }
+    }
+}
+
+/// 标签、日期与链接索引
+pub struct IndexSet {
+    pub tags: Index,
+    pub dates: Index,
+    /// 按 Updated 字段建立的日期索引
+    pub updated: Index,
+    pub links: LinkIndex,
+    /// 创建时间无法解析的笔记：（文件名，标题，字段原文）
+    pub invalid_created: Vec<(String, String, String)>,
+    /// 文件名 -> 正文字数
+    pub words: HashMap<String, usize>,
+    /// 文件名 -> 摘要，没有摘要的笔记不记入
+    pub summaries: HashMap<String, String>,
+    /// 文件名 -> 文件的修改时间（Unix 秒），由扫描目录时填入
+    pub modified: HashMap<String, u64>,
+    /// 草稿：（文件名，标题），由扫描目录时填入
+    pub drafts: Vec<(String, String)>,
+    /// 置顶笔记：文件名 -> 标题
+    pub pinned: HashMap<String, String>,
+    /// 有状态的笔记：（状态，文件名，标题）
+    pub statuses: Vec<(String, String, String)>,
+}
+
+impl IndexSet {
+    pub fn new() -> Self {
+        IndexSet {
+            tags: Index::new(),
+            dates: Index::new(),
+            updated: Index::new(),
+            links: LinkIndex::new(),
+            invalid_created: Vec::new(),
+            words: HashMap::new(),
+            summaries: HashMap::new(),
+            modified: HashMap::new(),
+            drafts: Vec::new(),
+            pinned: HashMap::new(),
+            statuses: Vec::new(),
+        }
+    }
+
+    /// 将一篇笔记加入标签、日期与链接索引
+    pub fn add_note(&mut self, meta: &NoteMeta) {
+        if let Some((date, ltime)) = &meta.created {
+            self.dates
+                .add_node(&meta.name, &meta.title, ltime, vec![&date.to_string()]);
+        }
+        if let Some((date, ltime)) = &meta.updated {
+            self.updated
+                .add_node(&meta.name, &meta.title, ltime, vec![&date.to_string()]);
+        }
+        // 层级标签的每一级都建立索引，同一篇笔记在每个标签下只出现一次
+        let mut tags: Vec<&str> = meta.tags.iter().flat_map(|tag| tag_levels(tag)).collect();
+        tags.sort_unstable();
+        tags.dedup();
+        self.tags.add_node(&meta.name, &meta.title, "", tags);
+        self.links.add_note(
+            &meta.name,
+            &meta.title,
+            &meta.aliases,
+            meta.id.as_deref(),
+            &meta.links,
+        );
+        self.words.insert(meta.name.clone(), meta.words);
+        if let Some(summary) = &meta.summary {
+            self.summaries.insert(meta.name.clone(), summary.clone());
+        }
+        if meta.pinned {
+            self.pinned.insert(meta.name.clone(), meta.title.clone());
+        }
+        if let Some(status) = &meta.status {
+            self.statuses
+                .push((status.clone(), meta.name.clone(), meta.title.clone()));
+        }
+        if let Some(value) = &meta.invalid_created {
+            self.invalid_created
+                .push((meta.name.clone(), meta.title.clone(), value.clone()));
+        }
+    }
+
+    /// 只把草稿记为链接目标：指向它的链接有效，但它不出现在标签页与日期页中，正文中的链接也不计入
+    pub fn add_draft(&mut self, meta: &NoteMeta) {
+        self.links.add_note(
+            &meta.name,
+            &meta.title,
+            &meta.aliases,
+            meta.id.as_deref(),
+            &[],
+        );
+    }
+
+    /// 笔记的正文字数，不在索引中时为 0
+    pub fn words(&self, name: &str) -> usize {
+        self.words.get(name).copied().unwrap_or(0)
+    }
+
+    /// 没有标签的笔记，返回（文件名，标题），按文件名排序
+    pub fn untagged(&self) -> Vec<(&str, &str)> {
+        let mut notes: Vec<(&str, &str)> = self
+            .tags
+            .get_files_by_i(NEED_TAG)
+            .into_iter()
+            .flatten()
+            .map(|(name, title, _)| (name.as_str(), title.as_str()))
+            .collect();
+        notes.sort();
+        notes
+    }
+
+    /// 各状态及其笔记（文件名，标题）：`STATUS_ORDER` 中的状态在前，其余按名称排序；笔记按标题排序
+    pub fn by_status(&self) -> Vec<(&str, Vec<(&str, &str)>)> {
+        let mut groups: BTreeMap<&str, Vec<(&str, &str)>> = BTreeMap::new();
+        for (status, name, title) in &self.statuses {
+            groups
+                .entry(status.as_str())
+                .or_default()
+                .push((name.as_str(), title.as_str()));
+        }
+        let mut groups: Vec<(&str, Vec<(&str, &str)>)> = groups.into_iter().collect();
+        groups.sort_by_key(|(status, _)| {
+            STATUS_ORDER
+                .iter()
+                .position(|known| known == status)
+                .unwrap_or(STATUS_ORDER.len())
+        });
+        for (_, notes) in &mut groups {
+            notes.sort_by(|a, b| a.1.cmp(b.1).then(a.0.cmp(b.0)));
+        }
+        groups
+    }
+
+    /// 全部笔记按最近一次活动从新到旧排列，时间相同时按文件名排序
+    pub fn recent(&self) -> Vec<RecentNote<'_>> {
+        let mut created: HashMap<&str, (&str, &str)> = HashMap::new();
+        for day in self.dates.get_inputs() {
+            for (name, _, time) in self.dates.get_files_by_i(day).into_iter().flatten() {
+                created.insert(name, (day, time));
+            }
+        }
+        // 每篇笔记至少有一个标签（没有时为 NeedTag），标题取自标签索引
+        let mut titles: HashMap<&str, &str> = HashMap::new();
+        for tag in self.tags.get_inputs() {
+            for (name, title, _) in self.tags.get_files_by_i(tag).into_iter().flatten() {
+                titles.insert(name, title);
+            }
+        }
+
+        let mut notes: Vec<RecentNote> = titles
+            .into_iter()
+            .map(|(name, title)| RecentNote {
+                name,
+                title,
+                created: created.get(name).copied(),
+                modified: self.modified.get(name).copied(),
+            })
+            .collect();
+        notes.sort_by_cached_key(|note| (Reverse(note.latest()), note.name));
+        notes
+    }
+
+    /// 索引中的全部笔记（文件名，标题），按文件名排序；没有标签的笔记记在 NeedTag 下，同样包括在内
+    pub fn titles(&self) -> Vec<(&str, &str)> {
+        let mut notes: Vec<(&str, &str)> = self
+            .tags
+            .get_inputs()
+            .iter()
+            .filter_map(|tag| self.tags.get_files_by_i(tag))
+            .flatten()
+            .map(|(name, title, _)| (name.as_str(), title.as_str()))
+            .collect();
+        notes.sort();
+        notes.dedup_by_key(|(name, _)| *name);
+        notes
+    }
+
+    /// 带有 tag 或其下层级标签的笔记，返回（文件名，标题），按文件名排序
+    pub fn tagged(&self, tag: &str) -> Vec<(&str, &str)> {
+        let tag = tag.trim_matches('/');
+        let mut notes: Vec<(&str, &str)> = self
+            .tags
+            .get_inputs()
+            .iter()
+            .filter(|input| tag_levels(input).contains(&tag))
+            .filter_map(|input| self.tags.get_files_by_i(input))
+            .flatten()
+            .map(|(name, title, _)| (name.as_str(), title.as_str()))
+            .collect();
+        notes.sort();
+        notes.dedup_by_key(|(name, _)| *name);
+        notes
+    }
+
+    /// 孤立的笔记：没有标签，也没有与其他笔记之间的链接，返回（文件名，标题），按文件名排序
+    pub fn orphans(&self) -> Vec<(&str, &str)> {
+        let linked = self.links.linked_notes();
+        let mut orphans = self.untagged();
+        orphans.retain(|(name, _)| !linked.contains(name));
+        orphans
+    }
+
+    /// 每篇笔记的相关笔记：与其共享标签最多的至多 limit 篇其他笔记
+    ///
+    /// 共享的标签越少见权重越高（每个标签记 ln(1 + 笔记数 / 含该标签的笔记数)），
+    /// 权重相同时按文件名排序。层级标签的每一级都计入，NeedTag 不计入。
+    pub fn related(&self, limit: usize) -> HashMap<&str, Vec<Related<'_>>> {
+        // 每个标签下的笔记去掉层级标签重复计入的部分后即为全部笔记
+        let mut titles: HashMap<&str, &str> = HashMap::new();
+        for tag in self.tags.get_inputs() {
+            for (name, title, _) in self.tags.get_files_by_i(tag).into_iter().flatten() {
+                titles.insert(name, title);
+            }
+        }
+        let total = titles.len() as f64;
+
+        // 笔记 -> 其他笔记 -> (分数, 共同的标签)
+        let mut scores: HashMap<&str, HashMap<&str, (f64, Vec<&str>)>> = HashMap::new();
+        for tag in self.tags.get_inputs() {
+            if tag == NEED_TAG {
+                continue;
+            }
+            let notes = self.tags.get_files_by_i(tag).map_or(&[][..], Vec::as_slice);
+            let weight = (1.0 + total / notes.len() as f64).ln();
+            for (a, _, _) in notes {
+                for (b, _, _) in notes {
+                    if a != b {
+                        let entry = scores.entry(a).or_default().entry(b).or_default();
+                        entry.0 += weight;
+                        entry.1.push(tag);
+                    }
+                }
+            }
+        }
+
+        scores
+            .into_iter()
+            .map(|(name, others)| {
+                let mut others: Vec<(&str, (f64, Vec<&str>))> = others.into_iter().collect();
+                others.sort_by(|a, b| b.1.0.total_cmp(&a.1.0).then_with(|| a.0.cmp(b.0)));
+                let related = others
+                    .into_iter()
+                    .take(limit)
+                    .map(|(other, (_, mut shared_tags))| {
+                        shared_tags.sort_unstable();
+                        Related {
+                            name: other,
+                            title: titles[other],
+                            shared_tags,
+                        }
+                    })
+                    .collect();
+                (name, related)
+            })
+            .collect()
+    }
+
+    /// 至少在一篇笔记中同时出现的标签对，按次数从多到少、再按标签名排序
+    ///
+    /// 层级标签与其上级总是同时出现，不计入；NeedTag 不计入。
+    pub fn tag_pairs(&self) -> Vec<TagPair<'_>> {
+        let mut note_tags: HashMap<&str, Vec<&str>> = HashMap::new();
+        for tag in self.tags.get_inputs() {
+            if tag == NEED_TAG {
+                continue;
+            }
+            for (name, _, _) in self.tags.get_files_by_i(tag).into_iter().flatten() {
+                note_tags.entry(name).or_default().push(tag);
+            }
+        }
+
+        let mut counts: HashMap<(&str, &str), usize> = HashMap::new();
+        for tags in note_tags.values_mut() {
+            tags.sort_unstable();
+            for (i, a) in tags.iter().enumerate() {
+                for b in &tags[i + 1..] {
+                    let nested = b.strip_prefix(*a).is_some_and(|rest| rest.starts_with('/'));
+                    if !nested {
+                        *counts.entry((a, b)).or_default() += 1;
+                    }
+                }
+            }
+        }
+
+        let mut pairs: Vec<TagPair> = counts
+            .into_iter()
+            .map(|(tags, count)| TagPair { tags, count })
+            .collect();
+        pairs.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.tags.cmp(&b.tags)));
+        pairs
+    }
+
+    /// 每篇笔记最具体的标签：层级标签只保留最深的一级，上级不重复列出；按标签名排序
+    pub fn note_tags(&self) -> HashMap<&str, Vec<&str>> {
+        let mut note_tags: HashMap<&str, Vec<&str>> = HashMap::new();
+        for tag in self.tags.get_inputs() {
+            for (name, _, _) in self.tags.get_files_by_i(tag).into_iter().flatten() {
+                note_tags.entry(name).or_default().push(tag);
+            }
+        }
+        for tags in note_tags.values_mut() {
+            tags.sort_unstable();
+            let all = tags.clone();
+            tags.retain(|tag| {
+                !all.iter().any(|other| {
+                    other
+                        .strip_prefix(*tag)
+                        .is_some_and(|rest| rest.starts_with('/'))
+                })
+            });
+        }
+        note_tags
+    }
+
+    /// 标签云：全部标签按标签名排序，权重按笔记数的对数在最少与最多之间均分
+    ///
+    /// 笔记数都相同时权重取中间一级；NeedTag 不计入。
+    pub fn tag_cloud(&self) -> Vec<CloudTag<'_>> {
+        let mut cloud: Vec<CloudTag> = self
+            .tags
+            .get_inputs()
+            .iter()
+            .filter(|tag| *tag != NEED_TAG)
+            .map(|tag| CloudTag {
+                tag,
+                count: self.tags.get_i_count(tag),
+                weight: 0,
+            })
+            .collect();
+        cloud.sort_by(|a, b| a.tag.cmp(b.tag));
+
+        let min = cloud.iter().map(|tag| tag.count).min().unwrap_or(1).max(1);
+        let max = cloud.iter().map(|tag| tag.count).max().unwrap_or(1).max(1);
+        // 笔记数相差悬殊时按对数分级，避免少数大标签以外全部挤在最小一级
+        let span = (max as f64).ln() - (min as f64).ln();
+        for tag in &mut cloud {
+            tag.weight = if span == 0.0 {
+                CLOUD_WEIGHTS.div_ceil(2)
+            } else {
+                let ratio = ((tag.count.max(1) as f64).ln() - (min as f64).ln()) / span;
+                1 + (ratio * (CLOUD_WEIGHTS - 1) as f64).round() as usize
+            };
+        }
+        cloud
+    }
+
+    /// 合并另一组索引
+    pub fn merge(&mut self, other: IndexSet) {
+        self.tags.merge(other.tags);
+        self.dates.merge(other.dates);
+        self.updated.merge(other.updated);
+        self.links.merge(other.links);
+        self.invalid_created.extend(other.invalid_created);
+        self.words.extend(other.words);
+        self.summaries.extend(other.summaries);
+        self.modified.extend(other.modified);
+        self.drafts.extend(other.drafts);
+        self.pinned.extend(other.pinned);
+        self.statuses.extend(other.statuses);
+    }
+}
+
+impl Default for IndexSet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
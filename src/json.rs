@@ -0,0 +1,522 @@
+// 最小的 JSON 读写实现，用于缓存等内部文件
+
+use std::fmt::{self, Write};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    // 保留键的插入顺序
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            Json::Number(n) if *n >= 0.0 && n.fract() == 0.0 => Some(*n as u64),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Json]> {
+        match self {
+            Json::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&[(String, Json)]> {
+        match self {
+            Json::Object(fields) => Some(fields),
+            _ => None,
+        }
+    }
+
+    /// 带缩进的多行输出
+    pub fn to_pretty(&self) -> String {
+        let mut out = String::new();
+        self.write_pretty(&mut out, 0);
+        out
+    }
+
+    fn write_pretty(&self, out: &mut String, depth: usize) {
+        let indent = "  ".repeat(depth + 1);
+        match self {
+            Json::Array(items) if !items.is_empty() => {
+                out.push_str("[\n");
+                for (i, item) in items.iter().enumerate() {
+                    out.push_str(&indent);
+                    item.write_pretty(out, depth + 1);
+                    out.push_str(if i + 1 < items.len() { ",\n" } else { "\n" });
+                }
+                out.push_str(&"  ".repeat(depth));
+                out.push(']');
+            }
+            Json::Object(fields) if !fields.is_empty() => {
+                out.push_str("{\n");
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    out.push_str(&indent);
+                    write_string(out, key);
+                    out.push_str(": ");
+                    value.write_pretty(out, depth + 1);
+                    out.push_str(if i + 1 < fields.len() { ",\n" } else { "\n" });
+                }
+                out.push_str(&"  ".repeat(depth));
+                out.push('}');
+            }
+            _ => {
+                let _ = write!(out, "{}", self);
+            }
+        }
+    }
+}
+
+impl From<&str> for Json {
+    fn from(s: &str) -> Self {
+        Json::String(s.to_string())
+    }
+}
+
+impl From<String> for Json {
+    fn from(s: String) -> Self {
+        Json::String(s)
+    }
+}
+
+impl From<u64> for Json {
+    fn from(n: u64) -> Self {
+        Json::Number(n as f64)
+    }
+}
+
+impl From<usize> for Json {
+    fn from(n: usize) -> Self {
+        Json::Number(n as f64)
+    }
+}
+
+impl From<bool> for Json {
+    fn from(b: bool) -> Self {
+        Json::Bool(b)
+    }
+}
+
+impl<T: Into<Json>> From<Option<T>> for Json {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Json::Null, Into::into)
+    }
+}
+
+impl<T: Into<Json>> From<Vec<T>> for Json {
+    fn from(items: Vec<T>) -> Self {
+        Json::Array(items.into_iter().map(Into::into).collect())
+    }
+}
+
+fn write_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+// 紧凑的单行输出
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Json::Null => f.write_str("null"),
+            Json::Bool(b) => write!(f, "{}", b),
+            Json::Number(n) if n.is_finite() => write!(f, "{}", n),
+            Json::Number(_) => f.write_str("null"),
+            Json::String(s) => {
+                let mut out = String::new();
+                write_string(&mut out, s);
+                f.write_str(&out)
+            }
+            Json::Array(items) => {
+                f.write_str("[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                f.write_str("]")
+            }
+            Json::Object(fields) => {
+                f.write_str("{")?;
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    let mut out = String::new();
+                    write_string(&mut out, key);
+                    write!(f, "{}:{}", out, value)?;
+                }
+                f.write_str("}")
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ParseError {
+    /// 出错位置（字符偏移）
+    pub offset: usize,
+    pub message: &'static str,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "JSON 第 {} 个字符处: {}", self.offset, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// 数组与对象的最大嵌套层数，更深的输入视为错误，避免递归解析耗尽栈
+pub const MAX_DEPTH: usize = 128;
+
+pub fn parse(text: &str) -> Result<Json, ParseError> {
+    let mut parser = Parser {
+        chars: text.chars().collect(),
+        pos: 0,
+        depth: 0,
+    };
+    let value = parser.value()?;
+    parser.skip_whitespace();
+    if parser.pos < parser.chars.len() {
+        return Err(parser.error("多余的内容"));
+    }
+    Ok(value)
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    /// 当前所在的数组与对象的层数
+    depth: usize,
+}
+
+impl Parser {
+    fn error(&self, message: &'static str) -> ParseError {
+        ParseError {
+            offset: self.pos,
+            message,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), ParseError> {
+        self.skip_whitespace();
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error("意外的字符"))
+        }
+    }
+
+    fn keyword(&mut self, word: &str, value: Json) -> Result<Json, ParseError> {
+        for expected in word.chars() {
+            if self.peek() != Some(expected) {
+                return Err(self.error("无效的关键字"));
+            }
+            self.pos += 1;
+        }
+        Ok(value)
+    }
+
+    fn value(&mut self) -> Result<Json, ParseError> {
+        self.skip_whitespace();
+        match self.peek() {
+            None => Err(self.error("意外的结尾")),
+            Some('n') => self.keyword("null", Json::Null),
+            Some('t') => self.keyword("true", Json::Bool(true)),
+            Some('f') => self.keyword("false", Json::Bool(false)),
+            Some('"') => self.string().map(Json::String),
+            Some('[') | Some('{') => {
+                if self.depth == MAX_DEPTH {
+                    return Err(self.error("嵌套层数过多"));
+                }
+                self.depth += 1;
+                let value = if self.peek() == Some('[') {
+                    self.array()
+                } else {
+                    self.object()
+                };
+                self.depth -= 1;
+                value
+            }
+            Some(c) if c == '-' || c.is_ascii_digit() => self.number(),
+            Some(_) => Err(self.error("意外的字符")),
+        }
+    }
+
+    fn number(&mut self) -> Result<Json, ParseError> {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E'))
+        {
+            self.pos += 1;
+        }
+        let text: String = self.chars[start..self.pos].iter().collect();
+        text.parse()
+            .map(Json::Number)
+            .map_err(|_| self.error("无效的数字"))
+    }
+
+    fn string(&mut self) -> Result<String, ParseError> {
+        self.expect('"')?;
+        let mut value = String::new();
+        loop {
+            let Some(c) = self.peek() else {
+                return Err(self.error("未闭合的字符串"));
+            };
+            self.pos += 1;
+            match c {
+                '"' => return Ok(value),
+                '\\' => {
+                    let Some(escaped) = self.peek() else {
+                        return Err(self.error("未闭合的字符串"));
+                    };
+                    self.pos += 1;
+                    match escaped {
+                        'n' => value.push('\n'),
+                        'r' => value.push('\r'),
+                        't' => value.push('\t'),
+                        'b' => value.push('\u{8}'),
+                        'f' => value.push('\u{c}'),
+                        'u' => value.push(self.unicode_escape()?),
+                        other => value.push(other),
+                    }
+                }
+                c => value.push(c),
+            }
+        }
+    }
+
+    fn hex4(&mut self) -> Result<u32, ParseError> {
+        if self.pos + 4 > self.chars.len() {
+            return Err(self.error("无效的 \\u 转义"));
+        }
+        let hex: String = self.chars[self.pos..self.pos + 4].iter().collect();
+        self.pos += 4;
+        u32::from_str_radix(&hex, 16).map_err(|_| self.error("无效的 \\u 转义"))
+    }
+
+    fn unicode_escape(&mut self) -> Result<char, ParseError> {
+        let high = self.hex4()?;
+        // 代理对
+        if (0xD800..0xDC00).contains(&high)
+            && self.chars.get(self.pos) == Some(&'\\')
+            && self.chars.get(self.pos + 1) == Some(&'u')
+        {
+            self.pos += 2;
+            let low = self.hex4()?;
+            let code = 0x10000 + ((high - 0xD800) << 10) + (low.wrapping_sub(0xDC00) & 0x3FF);
+            return char::from_u32(code).ok_or_else(|| self.error("无效的 \\u 转义"));
+        }
+        char::from_u32(high).ok_or_else(|| self.error("无效的 \\u 转义"))
+    }
+
+    fn array(&mut self) -> Result<Json, ParseError> {
+        self.expect('[')?;
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.pos += 1;
+            return Ok(Json::Array(items));
+        }
+        loop {
+            items.push(self.value()?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some(']') => {
+                    self.pos += 1;
+                    return Ok(Json::Array(items));
+                }
+                _ => return Err(self.error("数组中缺少 ',' 或 ']'")),
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<Json, ParseError> {
+        self.expect('{')?;
+        let mut fields = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.pos += 1;
+            return Ok(Json::Object(fields));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.expect(':')?;
+            fields.push((key, self.value()?));
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some('}') => {
+                    self.pos += 1;
+                    return Ok(Json::Object(fields));
+                }
+                _ => return Err(self.error("对象中缺少 ',' 或 '}'")),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nesting_is_limited() {
+        let nested = |depth: usize| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+        assert!(parse(&nested(MAX_DEPTH)).is_ok());
+        let error = parse(&nested(MAX_DEPTH + 1)).unwrap_err();
+        assert_eq!(error.offset, MAX_DEPTH);
+        // 远超限制的输入同样只返回错误，不会耗尽栈
+        assert!(parse(&"[".repeat(100_000)).is_err());
+        assert!(parse(&"{\"a\":".repeat(100_000)).is_err());
+    }
+
+    #[test]
+    fn parses_every_kind_of_value() {
+        let value = parse(
+            r#" { "null": null, "bool": [true, false], "number": [0, -1.5, 2e3, 1E-2],
+                 "string": "a\"b\\c\/\n\t\u4e2d\ud83d\ude00", "empty": [{}, []] } "#,
+        )
+        .unwrap();
+        assert_eq!(value.get("null"), Some(&Json::Null));
+        assert_eq!(
+            value.get("bool"),
+            Some(&Json::Array(vec![Json::Bool(true), Json::Bool(false)]))
+        );
+        assert_eq!(
+            value.get("number"),
+            Some(&Json::Array(vec![
+                Json::Number(0.0),
+                Json::Number(-1.5),
+                Json::Number(2000.0),
+                Json::Number(0.01)
+            ]))
+        );
+        assert_eq!(
+            value.get("string").and_then(Json::as_str),
+            Some("a\"b\\c/\n\t中😀")
+        );
+        assert_eq!(
+            value.get("empty"),
+            Some(&Json::Array(vec![
+                Json::Object(Vec::new()),
+                Json::Array(Vec::new())
+            ]))
+        );
+        assert_eq!(value.get("missing"), None);
+    }
+
+    #[test]
+    fn keeps_key_order() {
+        let value = parse(r#"{"b": 1, "a": 2, "c": 3}"#).unwrap();
+        let keys: Vec<&str> = value
+            .as_object()
+            .unwrap()
+            .iter()
+            .map(|(key, _)| key.as_str())
+            .collect();
+        assert_eq!(keys, ["b", "a", "c"]);
+        assert_eq!(value.get("a").and_then(Json::as_u64), Some(2));
+    }
+
+    #[test]
+    fn rejects_invalid_input() {
+        for (text, offset) in [
+            ("", 0),
+            ("nul", 3),
+            ("[1,]", 3),
+            ("[1 2]", 3),
+            ("{\"a\" 1}", 5),
+            ("{\"a\": 1,}", 8),
+            ("{a: 1}", 1),
+            ("\"open", 5),
+            ("\"\\u12\"", 3),
+            ("\"\\ud800\"", 7),
+            ("-", 1),
+            ("1.2.3", 5),
+            ("+1", 0),
+            ("[] []", 3),
+        ] {
+            let error = parse(text).unwrap_err();
+            assert_eq!(error.offset, offset, "{:?}: {}", text, error);
+        }
+    }
+
+    #[test]
+    fn output_round_trips() {
+        let value = Json::Object(vec![
+            (
+                "text".to_string(),
+                "引号\" 反斜杠\\ 换行\n 控制\u{1}".into(),
+            ),
+            ("list".to_string(), vec![1u64, 2].into()),
+            ("none".to_string(), Option::<&str>::None.into()),
+            ("empty".to_string(), Json::Array(Vec::new())),
+        ]);
+        let compact = value.to_string();
+        assert_eq!(
+            compact,
+            r#"{"text":"引号\" 反斜杠\\ 换行\n 控制\u0001","list":[1,2],"none":null,"empty":[]}"#
+        );
+        assert_eq!(parse(&compact).unwrap(), value);
+        let pretty = value.to_pretty();
+        assert!(pretty.starts_with("{\n  \"text\": "), "{}", pretty);
+        assert!(
+            pretty.contains("\"list\": [\n    1,\n    2\n  ],"),
+            "{}",
+            pretty
+        );
+        assert_eq!(parse(&pretty).unwrap(), value);
+        assert_eq!(Json::Number(f64::NAN).to_string(), "null");
+    }
+}
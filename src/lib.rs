@@ -0,0 +1,74 @@
+//! gtx: 为博客目录生成标签、日期与反向链接大纲
+//!
+//! ```no_run
+//! use std::path::Path;
+//!
+//! let vault = gtx::Vault::scan(Path::new("notes"))?;
+//! vault.indexes().write_to(vault.root())?;
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+pub mod anki;
+pub mod api;
+pub mod archive;
+pub mod attachments;
+pub mod build;
+pub mod cache;
+pub mod check;
+pub mod cleanup;
+pub mod cli;
+pub mod commands;
+pub mod config;
+pub mod date;
+pub mod doctor;
+pub mod epub;
+pub mod error;
+pub mod export;
+pub mod format;
+pub mod frontmatter;
+pub mod fulltext;
+pub mod git;
+pub mod glob;
+pub mod graph;
+pub mod hook;
+pub mod html;
+pub mod i18n;
+pub mod import;
+pub mod index;
+pub mod json;
+pub mod links;
+pub mod log;
+pub mod lsp;
+pub mod manifest;
+pub mod markdown;
+pub mod merge;
+pub mod note;
+pub mod open;
+pub mod org;
+pub mod output;
+pub mod plan;
+pub mod query;
+pub mod regex;
+pub mod rename;
+pub mod retag;
+pub mod review;
+pub mod rpc;
+pub mod search;
+pub mod serve;
+pub mod site;
+pub mod sqlite;
+pub mod ssg;
+pub mod stats;
+pub mod template;
+pub mod todos;
+pub mod tui;
+pub mod vault;
+pub mod walk;
+pub mod watch;
+pub mod zip;
+
+pub use error::GtxError;
+pub use format::{Align, ColumnFormatter};
+pub use index::{Index, IndexSet};
+pub use note::NoteMeta;
+pub use vault::Vault;
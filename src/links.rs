@@ -0,0 +1,442 @@
+// 正文中的 [[wikilink]] 提取与反向链接索引
+
+use std::borrow::Cow;
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use crate::serve::percent_decode;
+
+/// 正文中的一条链接
+#[derive(Debug, Clone, PartialEq)]
+pub struct Link {
+    /// 链接目标，已去掉别名、标题锚点与 .md 后缀
+    pub target: String,
+    /// 所在行号，从 1 开始，相对整个文件
+    pub line: usize,
+}
+
+/// 指向某篇笔记的一条反向链接
+pub struct Backlink {
+    pub source_name: String,
+    pub source_title: String,
+    pub line: usize,
+}
+
+pub struct LinkIndex {
+    // 文件名 -> 标题
+    notes: HashMap<String, String>,
+    // 子目录中的笔记：最后一级文件名 -> 文件名，重名时保留路径最小的
+    basenames: HashMap<String, String>,
+    // 标题 -> 文件名，标题重复时保留先出现的
+    titles: HashMap<String, String>,
+    // 别名 -> 文件名，别名重复时保留先出现的
+    aliases: HashMap<String, String>,
+    // ID -> 文件名，ID 重复时保留先出现的
+    ids: HashMap<String, String>,
+    // 文件名 -> 别名
+    note_aliases: HashMap<String, Vec<String>>,
+    // 链接目标（原样） -> 来源列表
+    incoming: HashMap<String, Vec<Backlink>>,
+    // 文件名 -> 正文中的链接，按出现顺序
+    outgoing: HashMap<String, Vec<Link>>,
+}
+
+impl LinkIndex {
+    pub fn new() -> Self {
+        LinkIndex {
+            notes: HashMap::new(),
+            basenames: HashMap::new(),
+            titles: HashMap::new(),
+            aliases: HashMap::new(),
+            ids: HashMap::new(),
+            note_aliases: HashMap::new(),
+            incoming: HashMap::new(),
+            outgoing: HashMap::new(),
+        }
+    }
+
+    /// 记录一篇笔记、它的别名、ID 及正文中的链接
+    pub fn add_note(
+        &mut self,
+        file_name: &str,
+        file_title: &str,
+        aliases: &[String],
+        id: Option<&str>,
+        links: &[Link],
+    ) {
+        self.notes
+            .insert(file_name.to_string(), file_title.to_string());
+        if let Some((_, base)) = file_name.rsplit_once('/') {
+            insert_min(&mut self.basenames, base, file_name);
+        }
+        self.titles
+            .entry(file_title.to_string())
+            .or_insert_with(|| file_name.to_string());
+        for alias in aliases {
+            self.aliases
+                .entry(alias.clone())
+                .or_insert_with(|| file_name.to_string());
+        }
+        if let Some(id) = id {
+            self.ids
+                .entry(id.to_string())
+                .or_insert_with(|| file_name.to_string());
+        }
+        if !aliases.is_empty() {
+            self.note_aliases
+                .insert(file_name.to_string(), aliases.to_vec());
+        }
+
+        if !links.is_empty() {
+            self.outgoing.insert(file_name.to_string(), links.to_vec());
+        }
+        for link in links {
+            self.incoming
+                .entry(link.target.clone())
+                .or_default()
+                .push(Backlink {
+                    source_name: file_name.to_string(),
+                    source_title: file_title.to_string(),
+                    line: link.line,
+                });
+        }
+    }
+
+    /// 合并另一个链接索引
+    pub fn merge(&mut self, other: LinkIndex) {
+        self.notes.extend(other.notes);
+        for (base, name) in other.basenames {
+            insert_min(&mut self.basenames, &base, &name);
+        }
+        for (title, name) in other.titles {
+            self.titles.entry(title).or_insert(name);
+        }
+        for (alias, name) in other.aliases {
+            self.aliases.entry(alias).or_insert(name);
+        }
+        for (id, name) in other.ids {
+            self.ids.entry(id).or_insert(name);
+        }
+        self.note_aliases.extend(other.note_aliases);
+        for (target, links) in other.incoming {
+            self.incoming.entry(target).or_default().extend(links);
+        }
+        self.outgoing.extend(other.outgoing);
+    }
+
+    /// 将链接目标解析为文件名：依次按文件名（含子目录的路径）、子目录中笔记的最后一级文件名、标题、别名、ID 匹配
+    pub fn resolve(&self, target: &str) -> Option<&str> {
+        if let Some((name, _)) = self.notes.get_key_value(target) {
+            return Some(name);
+        }
+        self.basenames
+            .get(target)
+            .or_else(|| self.titles.get(target))
+            .or_else(|| self.aliases.get(target))
+            .or_else(|| self.ids.get(target))
+            .map(String::as_str)
+    }
+
+    /// 按目标笔记汇总反向链接，键为文件名；无法解析的目标不包含在内
+    pub fn backlinks(&self) -> HashMap<&str, Vec<&Backlink>> {
+        let mut result: HashMap<&str, Vec<&Backlink>> = HashMap::new();
+        for (target, links) in &self.incoming {
+            if let Some(name) = self.resolve(target) {
+                // 指向自身的链接不计入
+                result
+                    .entry(name)
+                    .or_default()
+                    .extend(links.iter().filter(|link| link.source_name != name));
+            }
+        }
+        result.retain(|_, links| !links.is_empty());
+        for links in result.values_mut() {
+            links.sort_by(|a, b| a.source_name.cmp(&b.source_name).then(a.line.cmp(&b.line)));
+        }
+        result
+    }
+
+    /// 笔记正文中的链接，按出现顺序；没有链接时为空
+    pub fn outgoing(&self, file_name: &str) -> &[Link] {
+        self.outgoing.get(file_name).map_or(&[], Vec::as_slice)
+    }
+
+    /// 有链接的笔记的文件名，按字典序排列
+    pub fn sources(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.outgoing.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// 笔记之间的链接（来源，目标），均为文件名；无法解析与指向自身的链接不计入，
+    /// 同一对笔记之间的多条链接只保留一条
+    pub fn edges(&self) -> BTreeSet<(&str, &str)> {
+        let mut edges = BTreeSet::new();
+        for (source, links) in &self.outgoing {
+            for link in links {
+                if let Some(target) = self.resolve(&link.target)
+                    && target != source
+                {
+                    edges.insert((source.as_str(), target));
+                }
+            }
+        }
+        edges
+    }
+
+    /// 与其他笔记之间至少有一条链接（指出或指入）的笔记
+    pub fn linked_notes(&self) -> HashSet<&str> {
+        let mut linked = HashSet::new();
+        for (target, links) in &self.incoming {
+            let Some(name) = self.resolve(target) else {
+                continue;
+            };
+            for link in links {
+                if link.source_name != name {
+                    linked.insert(name);
+                    linked.insert(link.source_name.as_str());
+                }
+            }
+        }
+        linked
+    }
+
+    /// 无法解析的链接目标及其来源，目标按字典序排列
+    pub fn unresolved(&self) -> Vec<(&str, &[Backlink])> {
+        let mut result: Vec<(&str, &[Backlink])> = self
+            .incoming
+            .iter()
+            .filter(|(target, _)| self.resolve(target).is_none())
+            .map(|(target, links)| (target.as_str(), links.as_slice()))
+            .collect();
+        result.sort_by_key(|(target, _)| *target);
+        result
+    }
+
+    pub fn get_title(&self, file_name: &str) -> Option<&str> {
+        self.notes.get(file_name).map(String::as_str)
+    }
+
+    /// 笔记的别名，没有别名时为空
+    pub fn get_aliases(&self, file_name: &str) -> &[String] {
+        self.note_aliases.get(file_name).map_or(&[], Vec::as_slice)
+    }
+}
+
+// 记下 key -> name，已有时保留较小的 name，使结果与扫描顺序无关
+fn insert_min(map: &mut HashMap<String, String>, key: &str, name: &str) {
+    match map.get_mut(key) {
+        Some(kept) if kept.as_str() <= name => {}
+        Some(kept) => *kept = name.to_string(),
+        None => {
+            map.insert(key.to_string(), name.to_string());
+        }
+    }
+}
+
+impl Default for LinkIndex {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 一行中各个 `[[...]]` 的字节范围，含两侧的括号；行内代码中的不算
+pub fn link_ranges(line: &str) -> Vec<(usize, usize)> {
+    let masked = mask_code_spans(line);
+    let mut ranges = Vec::new();
+    let mut offset = 0;
+    while let Some(found) = masked[offset..].find("[[") {
+        let start = offset + found;
+        let Some(length) = masked[start + 2..].find("]]") else {
+            break;
+        };
+        let end = start + 2 + length + 2;
+        ranges.push((start, end));
+        offset = end;
+    }
+    ranges
+}
+
+// 把行内代码（连同两侧的反引号）换成同样字节数的空格，其余位置不变。反引号串与之后
+// 第一个同样长度的反引号串配对，找不到时按普通字符处理；不处理跨行的行内代码
+fn mask_code_spans(line: &str) -> Cow<'_, str> {
+    if !line.contains('`') {
+        return Cow::Borrowed(line);
+    }
+    let bytes = line.as_bytes();
+    let run = |i: usize| bytes[i..].iter().take_while(|&&b| b == b'`').count();
+    let mut masked = String::with_capacity(line.len());
+    let mut copied = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] != b'`' {
+            i += 1;
+            continue;
+        }
+        let open = run(i);
+        let mut j = i + open;
+        let close = loop {
+            if j >= bytes.len() {
+                break None;
+            }
+            if bytes[j] != b'`' {
+                j += 1;
+                continue;
+            }
+            let length = run(j);
+            if length == open {
+                break Some(j + length);
+            }
+            j += length;
+        };
+        match close {
+            Some(end) => {
+                masked.push_str(&line[copied..i]);
+                masked.push_str(&" ".repeat(end - i));
+                copied = end;
+                i = end;
+            }
+            None => i += open,
+        }
+    }
+    masked.push_str(&line[copied..]);
+    Cow::Owned(masked)
+}
+
+/// 提取正文中的 `[[目标]]`、`[[目标|别名]]`、`[[目标#标题]]`，跳过 `![[嵌入]]`、代码块与行内代码
+pub fn extract_links(body: &str, first_line: usize) -> Vec<Link> {
+    let mut links = Vec::new();
+    let mut in_code_block = false;
+
+    for (offset, line) in body.lines().enumerate() {
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            continue;
+        }
+
+        for (start, end) in link_ranges(line) {
+            if line[..start].ends_with('!') {
+                continue;
+            }
+            let target = normalize_target(&line[start + 2..end - 2]);
+            if !target.is_empty() {
+                links.push(Link {
+                    target,
+                    line: first_line + offset,
+                });
+            }
+        }
+    }
+
+    links
+}
+
+/// 提取正文中嵌入的图片与文件：`![[文件]]` 与 `![说明](路径)`，跳过代码块、网址与嵌入的笔记
+///
+/// `![[文件]]` 的目标去掉尺寸与锚点，`![](路径)` 的目标去掉标题并解码 %XX。
+pub fn extract_embeds(body: &str, first_line: usize) -> Vec<Link> {
+    let mut embeds = Vec::new();
+    let mut in_code_block = false;
+
+    for (offset, line) in body.lines().enumerate() {
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            continue;
+        }
+
+        let mut rest = line;
+        while let Some(start) = rest.find('!') {
+            let after = &rest[start + 1..];
+            let target = if let Some(inner) = after.strip_prefix("[[") {
+                let Some(end) = inner.find("]]") else {
+                    break;
+                };
+                rest = &inner[end + 2..];
+                normalize_target(&inner[..end])
+            } else if let Some(inner) = after.strip_prefix('[')
+                && let Some(close) = inner.find("](")
+                && let Some(end) = inner[close + 2..].find(')')
+            {
+                let path = &inner[close + 2..close + 2 + end];
+                rest = &inner[close + 2 + end + 1..];
+                embed_path(path)
+            } else {
+                rest = after;
+                continue;
+            };
+            if is_attachment(&target) {
+                embeds.push(Link {
+                    target,
+                    line: first_line + offset,
+                });
+            }
+        }
+    }
+
+    embeds
+}
+
+// `![](路径 "标题")` 中的路径：去掉标题与尖括号，解码 %XX 与开头的 ./
+fn embed_path(path: &str) -> String {
+    let path = path.trim();
+    let path = match path.strip_prefix('<') {
+        Some(rest) => rest.split('>').next().unwrap_or(""),
+        None => path.split_whitespace().next().unwrap_or(""),
+    };
+    let path = percent_decode(path).unwrap_or_else(|| path.to_string());
+    path.trim_start_matches("./").to_string()
+}
+
+// 本地的、带扩展名的文件；笔记与网址不算附件
+fn is_attachment(target: &str) -> bool {
+    if target.contains("://") || target.starts_with("data:") {
+        return false;
+    }
+    let file_name = target.rsplit('/').next().unwrap_or(target);
+    match file_name.rsplit_once('.') {
+        Some((stem, ext)) => !stem.is_empty() && !matches!(ext, "md" | "org"),
+        None => false,
+    }
+}
+
+/// 把正文中 `[[链接]]` 的内容交给 f 改写，f 的返回值替换整个链接，返回 None 时保持原样；
+/// 跳过 `![[嵌入]]`、代码块与行内代码
+pub fn replace_links(body: &str, f: &dyn Fn(&str) -> Option<String>) -> String {
+    let mut out = String::with_capacity(body.len());
+    let mut in_code_block = false;
+
+    for line in body.split_inclusive('\n') {
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+        }
+        if in_code_block || line.trim_start().starts_with("```") {
+            out.push_str(line);
+            continue;
+        }
+
+        let mut copied = 0;
+        for (start, end) in link_ranges(line) {
+            out.push_str(&line[copied..start]);
+            match f(&line[start + 2..end - 2]).filter(|_| !line[..start].ends_with('!')) {
+                Some(replaced) => out.push_str(&replaced),
+                None => out.push_str(&line[start..end]),
+            }
+            copied = end;
+        }
+        out.push_str(&line[copied..]);
+    }
+    out
+}
+
+/// `[[...]]` 中的内容对应的链接目标：去掉别名、标题锚点与 .md 后缀
+pub fn normalize_target(inner: &str) -> String {
+    let target = inner.split('|').next().unwrap_or("");
+    let target = target.split('#').next().unwrap_or("");
+    let target = target.trim();
+    target.strip_suffix(".md").unwrap_or(target).to_string()
+}
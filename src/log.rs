@@ -0,0 +1,179 @@
+// 命令行输出的详细程度、进度条与各阶段耗时
+//
+// 级别由 --quiet 与 --verbose 选择：quiet 只输出错误；默认输出结果与进度条；
+// verbose 另外列出每个重新解析的文件，此时不显示进度条。
+// 进度条写到标准错误，只在标准错误是终端时显示，输出重定向到文件时不会混入其中。
+
+use std::fmt;
+use std::io::{self, IsTerminal, Write};
+use std::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::i18n;
+
+/// 输出的详细程度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum Level {
+    /// 只输出错误
+    Quiet,
+    /// 输出结果、进度条与耗时
+    #[default]
+    Normal,
+    /// 另外列出处理过程中的每个文件
+    Verbose,
+}
+
+// 整个进程使用同一级别，启动时由命令行设置
+static LEVEL: AtomicU8 = AtomicU8::new(Level::Normal as u8);
+
+/// 设置之后输出的详细程度
+pub fn set_level(level: Level) {
+    LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+/// 当前的详细程度
+pub fn level() -> Level {
+    match LEVEL.load(Ordering::Relaxed) {
+        0 => Level::Quiet,
+        2 => Level::Verbose,
+        _ => Level::Normal,
+    }
+}
+
+/// 默认级别及以上输出到标准输出
+pub fn info(message: impl fmt::Display) {
+    if level() >= Level::Normal {
+        println!("{}", message);
+    }
+}
+
+/// 只在 verbose 级别输出到标准输出
+pub fn debug(message: impl fmt::Display) {
+    if level() >= Level::Verbose {
+        println!("{}", message);
+    }
+}
+
+// 进度条的宽度，按字符计
+const BAR_WIDTH: usize = 30;
+
+/// 标准错误上的进度条，可在多个线程中共用，离开作用域时清除
+pub struct Progress {
+    label: &'static str,
+    total: AtomicUsize,
+    done: AtomicUsize,
+    // 上次绘制时的百分比加一，0 表示还没有绘制；百分比不变时不重绘
+    drawn: AtomicUsize,
+    enabled: bool,
+}
+
+impl Progress {
+    /// 共 total 项的进度条；enabled 为 false、级别不是默认级别或标准错误不是终端时不显示
+    pub fn new(label: &'static str, total: usize, enabled: bool) -> Progress {
+        Progress {
+            label,
+            total: AtomicUsize::new(total),
+            done: AtomicUsize::new(0),
+            drawn: AtomicUsize::new(0),
+            enabled: enabled && level() == Level::Normal && io::stderr().is_terminal(),
+        }
+    }
+
+    /// 开始之后才知道总数时设置总数
+    pub fn set_total(&self, total: usize) {
+        self.total.store(total, Ordering::Relaxed);
+        self.draw();
+    }
+
+    /// 完成一项
+    pub fn inc(&self) {
+        self.done.fetch_add(1, Ordering::Relaxed);
+        self.draw();
+    }
+
+    fn draw(&self) {
+        if !self.enabled {
+            return;
+        }
+        let total = self.total.load(Ordering::Relaxed);
+        let done = self.done.load(Ordering::Relaxed).min(total);
+        let percent = done * 100 / total.max(1);
+        if self.drawn.swap(percent + 1, Ordering::Relaxed) == percent + 1 {
+            return;
+        }
+        let filled = done * BAR_WIDTH / total.max(1);
+        let mut stderr = io::stderr().lock();
+        let _ = write!(
+            stderr,
+            "\r{} [{}{}] {}/{} {:>3}%",
+            i18n::tr(self.label),
+            "#".repeat(filled),
+            " ".repeat(BAR_WIDTH - filled),
+            done,
+            total,
+            percent
+        );
+        let _ = stderr.flush();
+    }
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        if self.enabled && self.drawn.load(Ordering::Relaxed) > 0 {
+            // 清除整行，之后的输出从行首开始
+            let _ = write!(io::stderr().lock(), "\r\x1b[2K");
+        }
+    }
+}
+
+/// 各阶段的耗时，按发生顺序排列
+#[derive(Debug, Clone)]
+pub struct Timings {
+    last: Instant,
+    pub phases: Vec<(&'static str, Duration)>,
+}
+
+impl Timings {
+    /// 从现在开始计时
+    pub fn start() -> Timings {
+        Timings {
+            last: Instant::now(),
+            phases: Vec::new(),
+        }
+    }
+
+    /// 记下从上一阶段结束到现在的耗时，phase 为阶段的中文名称
+    pub fn lap(&mut self, phase: &'static str) {
+        let now = Instant::now();
+        self.phases.push((phase, now - self.last));
+        self.last = now;
+    }
+
+    pub fn total(&self) -> Duration {
+        self.phases.iter().map(|(_, elapsed)| *elapsed).sum()
+    }
+}
+
+impl fmt::Display for Timings {
+    /// 形如 `耗时: 扫描 12ms · 写入页面 1.20s，共 1.21s`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let phases: Vec<String> = self
+            .phases
+            .iter()
+            .map(|(phase, elapsed)| format!("{} {}", i18n::tr(phase), duration(*elapsed)))
+            .collect();
+        f.write_str(&i18n::trf(
+            "耗时: {}，共 {}",
+            &[&phases.join(" · "), &duration(self.total())],
+        ))
+    }
+}
+
+// 一秒以内按毫秒显示
+fn duration(elapsed: Duration) -> String {
+    if elapsed < Duration::from_secs(1) {
+        format!("{}ms", elapsed.as_millis())
+    } else {
+        format!("{:.2}s", elapsed.as_secs_f64())
+    }
+}
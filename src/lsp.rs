@@ -0,0 +1,455 @@
+// gtx lsp：最小的语言服务器，在编辑器中补全链接与标签、跳转到链接目标并提示失效链接
+//
+// 通过标准输入输出收发 LSP 消息（Content-Length 头加 JSON-RPC 正文），支持:
+//   - 补全：`[[` 之后补全笔记文件名，行首或空白之后的 `#` 之后补全标签
+//   - 跳转到定义：光标所在的 `[[链接]]` 跳转到目标笔记
+//   - 诊断：打开或修改文档时报告找不到目标的 `[[链接]]`，规则同 gtx check
+// 文档内容按整篇同步；保存文档时重新扫描目录，其余时候使用内存中的索引。
+
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+use crate::check::is_generated_page;
+use crate::index::date_tree;
+use crate::json::{self, Json};
+use crate::links::{link_ranges, normalize_target};
+use crate::vault::Vault;
+
+// JSON-RPC 规定的错误码
+const METHOD_NOT_FOUND: i64 = -32601;
+
+// LSP 的常量
+const SYNC_FULL: u64 = 1;
+const SEVERITY_WARNING: u64 = 2;
+const KIND_FILE: u64 = 17;
+const KIND_KEYWORD: u64 = 14;
+
+/// 语言服务器的状态
+pub struct LanguageServer<'a> {
+    scan: &'a dyn Fn() -> io::Result<Vault>,
+    vault: Vault,
+    /// 已打开的文档：URI -> 内容
+    documents: HashMap<String, String>,
+}
+
+impl<'a> LanguageServer<'a> {
+    /// 用 scan 扫描目录，保存文档时再次调用
+    pub fn new(scan: &'a dyn Fn() -> io::Result<Vault>) -> io::Result<LanguageServer<'a>> {
+        Ok(LanguageServer {
+            scan,
+            vault: scan()?,
+            documents: HashMap::new(),
+        })
+    }
+
+    /// 处理消息直到收到 exit 或输入结束
+    pub fn run(&mut self, mut input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+        while let Some(body) = read_message(&mut input)? {
+            let message = match json::parse(&body) {
+                Ok(message) => message,
+                Err(e) => {
+                    eprintln!("gtx lsp: 无法解析消息: {}", e);
+                    continue;
+                }
+            };
+            let Some(method) = message.get("method").and_then(Json::as_str) else {
+                // 客户端对服务器请求的应答，不需要处理
+                continue;
+            };
+            if method == "exit" {
+                break;
+            }
+            let params = message.get("params").cloned().unwrap_or(Json::Null);
+            let replies = match message.get("id") {
+                Some(id) => vec![self.request(id.clone(), method, &params)],
+                None => self.notification(method, &params),
+            };
+            for reply in replies {
+                write_message(&mut output, &reply)?;
+            }
+        }
+        Ok(())
+    }
+
+    // 应答请求
+    fn request(&mut self, id: Json, method: &str, params: &Json) -> Json {
+        let result = match method {
+            "initialize" => Json::Object(vec![
+                (
+                    "capabilities".to_string(),
+                    Json::Object(vec![
+                        ("textDocumentSync".to_string(), SYNC_FULL.into()),
+                        (
+                            "completionProvider".to_string(),
+                            Json::Object(vec![(
+                                "triggerCharacters".to_string(),
+                                vec!["[", "#"].into(),
+                            )]),
+                        ),
+                        ("definitionProvider".to_string(), true.into()),
+                    ]),
+                ),
+                (
+                    "serverInfo".to_string(),
+                    Json::Object(vec![
+                        ("name".to_string(), "gtx".into()),
+                        ("version".to_string(), env!("CARGO_PKG_VERSION").into()),
+                    ]),
+                ),
+            ]),
+            "shutdown" => Json::Null,
+            "textDocument/completion" => self.completion(params),
+            "textDocument/definition" => self.definition(params),
+            _ => {
+                return Json::Object(vec![
+                    ("jsonrpc".to_string(), "2.0".into()),
+                    ("id".to_string(), id),
+                    (
+                        "error".to_string(),
+                        Json::Object(vec![
+                            ("code".to_string(), Json::Number(METHOD_NOT_FOUND as f64)),
+                            (
+                                "message".to_string(),
+                                format!("未知的方法 '{}'", method).into(),
+                            ),
+                        ]),
+                    ),
+                ]);
+            }
+        };
+        Json::Object(vec![
+            ("jsonrpc".to_string(), "2.0".into()),
+            ("id".to_string(), id),
+            ("result".to_string(), result),
+        ])
+    }
+
+    // 处理通知，返回需要发给客户端的通知
+    fn notification(&mut self, method: &str, params: &Json) -> Vec<Json> {
+        let uri = params
+            .get("textDocument")
+            .and_then(|document| document.get("uri"))
+            .and_then(Json::as_str)
+            .map(str::to_string);
+        match (method, uri) {
+            ("textDocument/didOpen", Some(uri)) => {
+                let text = params
+                    .get("textDocument")
+                    .and_then(|document| document.get("text"))
+                    .and_then(Json::as_str)
+                    .unwrap_or_default();
+                self.documents.insert(uri.clone(), text.to_string());
+                vec![self.diagnostics(&uri)]
+            }
+            ("textDocument/didChange", Some(uri)) => {
+                // 整篇同步时最后一项即为完整内容
+                let text = params
+                    .get("contentChanges")
+                    .and_then(Json::as_array)
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change.get("text"))
+                    .and_then(Json::as_str);
+                if let Some(text) = text {
+                    self.documents.insert(uri.clone(), text.to_string());
+                }
+                vec![self.diagnostics(&uri)]
+            }
+            ("textDocument/didSave", Some(_)) => {
+                match (self.scan)() {
+                    Ok(vault) => self.vault = vault,
+                    Err(e) => eprintln!("gtx lsp: 无法扫描目录: {}", e),
+                }
+                // 其他文档中的链接可能因此变为有效或失效
+                let mut uris: Vec<String> = self.documents.keys().cloned().collect();
+                uris.sort();
+                uris.iter().map(|uri| self.diagnostics(uri)).collect()
+            }
+            ("textDocument/didClose", Some(uri)) => {
+                self.documents.remove(&uri);
+                vec![publish_diagnostics(&uri, Vec::new())]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    // 文档中找不到目标的链接
+    fn diagnostics(&self, uri: &str) -> Json {
+        let text = self
+            .documents
+            .get(uri)
+            .map(String::as_str)
+            .unwrap_or_default();
+        let indexes = self.vault.indexes();
+        let rollups = date_tree(&indexes.dates);
+        let diagnostics = link_spans(text)
+            .into_iter()
+            .filter_map(|span| {
+                let target = normalize_target(span.inner);
+                if target.is_empty()
+                    || indexes.links.resolve(&target).is_some()
+                    || is_generated_page(indexes, &rollups, &target)
+                {
+                    return None;
+                }
+                Some(Json::Object(vec![
+                    (
+                        "range".to_string(),
+                        range(span.line, span.line_text, span.start, span.end),
+                    ),
+                    ("severity".to_string(), SEVERITY_WARNING.into()),
+                    ("source".to_string(), "gtx".into()),
+                    (
+                        "message".to_string(),
+                        format!("找不到链接目标 '{}'", target).into(),
+                    ),
+                ]))
+            })
+            .collect();
+        publish_diagnostics(uri, diagnostics)
+    }
+
+    fn completion(&self, params: &Json) -> Json {
+        let Some((line_number, line, cursor)) = self.cursor(params) else {
+            return Json::Null;
+        };
+        let before = &line[..cursor];
+
+        // [[ 之后尚未闭合时补全笔记
+        if let Some(start) = before.rfind("[[")
+            && !before[start..].contains("]]")
+        {
+            let typed_start = start + 2;
+            let edit_range = range(line_number, line, typed_start, cursor);
+            let mut notes: Vec<_> = self.vault.notes().collect();
+            notes.sort_by(|a, b| a.name.cmp(&b.name));
+            let items = notes
+                .into_iter()
+                .map(|note| {
+                    Json::Object(vec![
+                        ("label".to_string(), note.name.as_str().into()),
+                        ("kind".to_string(), KIND_FILE.into()),
+                        ("detail".to_string(), note.title.as_str().into()),
+                        (
+                            "filterText".to_string(),
+                            format!("{} {}", note.name, note.title).into(),
+                        ),
+                        (
+                            "textEdit".to_string(),
+                            Json::Object(vec![
+                                ("range".to_string(), edit_range.clone()),
+                                ("newText".to_string(), note.name.as_str().into()),
+                            ]),
+                        ),
+                    ])
+                })
+                .collect();
+            return Json::Array(items);
+        }
+
+        // 行首或空白之后的 #标签，# 之后紧跟空格的是标题，不补全
+        if let Some(hash) = before.rfind('#') {
+            let typed = &before[hash + 1..];
+            let at_word_start = before[..hash]
+                .chars()
+                .last()
+                .is_none_or(char::is_whitespace);
+            if at_word_start && !typed.contains(char::is_whitespace) && !typed.contains('#') {
+                let edit_range = range(line_number, line, hash + 1, cursor);
+                let tags = &self.vault.indexes().tags;
+                let mut names: Vec<&String> = tags.get_inputs().iter().collect();
+                names.sort();
+                let items = names
+                    .into_iter()
+                    .map(|tag| {
+                        Json::Object(vec![
+                            ("label".to_string(), tag.as_str().into()),
+                            ("kind".to_string(), KIND_KEYWORD.into()),
+                            (
+                                "detail".to_string(),
+                                format!("{} 篇笔记", tags.get_i_count(tag)).into(),
+                            ),
+                            (
+                                "textEdit".to_string(),
+                                Json::Object(vec![
+                                    ("range".to_string(), edit_range.clone()),
+                                    ("newText".to_string(), tag.as_str().into()),
+                                ]),
+                            ),
+                        ])
+                    })
+                    .collect();
+                return Json::Array(items);
+            }
+        }
+        Json::Array(Vec::new())
+    }
+
+    fn definition(&self, params: &Json) -> Json {
+        let Some((_, line, cursor)) = self.cursor(params) else {
+            return Json::Null;
+        };
+        let Some(span) = link_spans(line)
+            .into_iter()
+            .find(|span| span.start <= cursor && cursor <= span.end)
+        else {
+            return Json::Null;
+        };
+        let target = normalize_target(span.inner);
+        let Some(name) = self.vault.indexes().links.resolve(&target) else {
+            return Json::Null;
+        };
+        Json::Object(vec![
+            (
+                "uri".to_string(),
+                file_uri(&self.vault.note_path(name)).into(),
+            ),
+            ("range".to_string(), range(0, "", 0, 0)),
+        ])
+    }
+
+    // 请求中光标的行号、所在行与光标在该行中的字节偏移
+    fn cursor<'t>(&'t self, params: &Json) -> Option<(usize, &'t str, usize)> {
+        let uri = params.get("textDocument")?.get("uri")?.as_str()?;
+        let text = self.documents.get(uri)?;
+        let position = params.get("position")?;
+        let number = position.get("line")?.as_u64()? as usize;
+        let line = text.lines().nth(number).unwrap_or_default();
+        let column = position.get("character")?.as_u64()? as usize;
+        Some((number, line, utf16_to_byte(line, column)))
+    }
+}
+
+/// 一行中的 `[[...]]`
+struct LinkSpan<'t> {
+    /// 行号，从 0 开始
+    line: usize,
+    line_text: &'t str,
+    /// `[[` 与 `]]` 在行中的字节偏移，end 在 `]]` 之后
+    start: usize,
+    end: usize,
+    inner: &'t str,
+}
+
+// 文档中的链接，跳过 `![[嵌入]]`、代码块与行内代码，规则同 `links::extract_links`
+fn link_spans(text: &str) -> Vec<LinkSpan<'_>> {
+    let mut spans = Vec::new();
+    let mut in_code_block = false;
+    for (number, line) in text.lines().enumerate() {
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            continue;
+        }
+        for (start, end) in link_ranges(line) {
+            if !line[..start].ends_with('!') {
+                spans.push(LinkSpan {
+                    line: number,
+                    line_text: line,
+                    start,
+                    end,
+                    inner: &line[start + 2..end - 2],
+                });
+            }
+        }
+    }
+    spans
+}
+
+// LSP 的范围，列为 UTF-16 编码单元
+fn range(line: usize, text: &str, start: usize, end: usize) -> Json {
+    let position = |byte: usize| {
+        Json::Object(vec![
+            ("line".to_string(), line.into()),
+            ("character".to_string(), byte_to_utf16(text, byte).into()),
+        ])
+    };
+    Json::Object(vec![
+        ("start".to_string(), position(start)),
+        ("end".to_string(), position(end)),
+    ])
+}
+
+fn byte_to_utf16(text: &str, byte: usize) -> usize {
+    text[..byte.min(text.len())]
+        .chars()
+        .map(char::len_utf16)
+        .sum()
+}
+
+// 超出行尾时取行尾
+fn utf16_to_byte(text: &str, column: usize) -> usize {
+    let mut units = 0;
+    for (byte, c) in text.char_indices() {
+        if units >= column {
+            return byte;
+        }
+        units += c.len_utf16();
+    }
+    text.len()
+}
+
+fn publish_diagnostics(uri: &str, diagnostics: Vec<Json>) -> Json {
+    Json::Object(vec![
+        ("jsonrpc".to_string(), "2.0".into()),
+        (
+            "method".to_string(),
+            "textDocument/publishDiagnostics".into(),
+        ),
+        (
+            "params".to_string(),
+            Json::Object(vec![
+                ("uri".to_string(), uri.into()),
+                ("diagnostics".to_string(), Json::Array(diagnostics)),
+            ]),
+        ),
+    ])
+}
+
+/// 文件路径对应的 file:// URI，字母、数字与 `-._~/` 之外的字节以 %XX 表示
+pub fn file_uri(path: &Path) -> String {
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let mut uri = String::from("file://");
+    for &byte in path.to_string_lossy().as_bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~/".contains(&byte) {
+            uri.push(byte as char);
+        } else {
+            uri.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    uri
+}
+
+// 读取一条消息的正文，输入结束时返回 None
+fn read_message(input: &mut impl BufRead) -> io::Result<Option<String>> {
+    let mut length = None;
+    loop {
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            if length.is_some() {
+                break;
+            }
+            continue;
+        }
+        if let Some((name, value)) = line.split_once(':')
+            && name.eq_ignore_ascii_case("Content-Length")
+        {
+            length = value.trim().parse::<usize>().ok();
+        }
+    }
+    let mut body = vec![0; length.unwrap_or(0)];
+    input.read_exact(&mut body)?;
+    Ok(Some(String::from_utf8_lossy(&body).into_owned()))
+}
+
+fn write_message(output: &mut impl Write, message: &Json) -> io::Result<()> {
+    let body = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    output.flush()
+}
@@ -0,0 +1,66 @@
+// 生成文件清单 .gtx/manifest.json：记录 gtx 生成的每个页面
+//
+// 缓存格式升级或缓存被删除后，仍可据此找到上次生成的页面，清理不再生成的旧标签页与日期页。
+// 清单的格式与缓存版本无关，只包含输出目录与页面列表。
+
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::json::{self, Json};
+use crate::output::write_atomic;
+
+/// 清单的文件名，位于 .gtx 下
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// 清单文件的位置
+pub fn manifest_path(root: &Path) -> PathBuf {
+    root.join(".gtx").join(MANIFEST_FILE)
+}
+
+/// 上次生成的页面
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Manifest {
+    /// 输出目录，None 表示博客目录本身
+    pub output: Option<PathBuf>,
+    /// 相对输出目录的路径，以 / 分隔
+    pub files: HashSet<String>,
+}
+
+impl Manifest {
+    /// 清单不存在或无法解析时返回 None
+    pub fn load(path: &Path) -> Option<Manifest> {
+        let text = fs::read_to_string(path).ok()?;
+        let root = json::parse(&text).ok()?;
+        let files = root
+            .get("files")?
+            .as_array()?
+            .iter()
+            .map(|file| file.as_str().map(str::to_string))
+            .collect::<Option<HashSet<String>>>()?;
+        Some(Manifest {
+            output: root.get("output").and_then(Json::as_str).map(PathBuf::from),
+            files,
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut files: Vec<&str> = self.files.iter().map(String::as_str).collect();
+        files.sort();
+        let root = Json::Object(vec![
+            (
+                "output".to_string(),
+                self.output
+                    .as_ref()
+                    .map(|dir| dir.to_string_lossy().into_owned())
+                    .into(),
+            ),
+            ("files".to_string(), files.into()),
+        ]);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        write_atomic(path, &root.to_pretty())
+    }
+}
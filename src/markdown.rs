@@ -0,0 +1,954 @@
+// Markdown 转 HTML，用于静态站点导出
+//
+// 只实现笔记中常用的 CommonMark 子集：
+//   - ATX 与 Setext 标题、段落、分隔线、引用、围栏代码块、GFM 表格
+//   - 有序与无序列表，可嵌套，支持 `- [ ]` 任务列表
+//   - 强调、加粗、删除线、行内代码、链接、图片、自动链接、反斜杠转义、
+//     行尾两个空格的换行
+//   - `[[目标]]`、`[[目标|别名]]` 维基链接与 `![[图片.png]]` 嵌入
+//
+// 原始 HTML 一律转义输出；不支持缩进代码块。
+
+/// 将 Markdown 文本转换为 HTML 片段
+///
+/// wikilink 将维基链接目标（已去掉别名、标题锚点与 .md 后缀）解析为地址，
+/// 无法解析时返回 None，此时链接输出为 `<span class="broken-link">`。
+/// 普通链接中指向本地 `.md` 文件的地址改为 `.html`。
+pub fn to_html(text: &str, wikilink: &dyn Fn(&str) -> Option<String>) -> String {
+    let lines: Vec<String> = text.lines().map(expand_tabs).collect();
+    let mut out = String::new();
+    Renderer { wikilink }.blocks(&lines, false, &mut out);
+    out
+}
+
+/// 转义 HTML 中的特殊字符，可用于文本与属性值
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// 行首的制表符按 4 列展开，便于按缩进判断列表层级
+fn expand_tabs(line: &str) -> String {
+    let trimmed = line.trim_start_matches([' ', '\t']);
+    let mut indent = 0;
+    for c in line[..line.len() - trimmed.len()].chars() {
+        indent = if c == '\t' {
+            indent / 4 * 4 + 4
+        } else {
+            indent + 1
+        };
+    }
+    format!("{}{}", " ".repeat(indent), trimmed)
+}
+
+fn indent_of(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+fn is_blank(line: &str) -> bool {
+    line.trim().is_empty()
+}
+
+// 围栏代码块的开始行，返回（围栏字符，长度，语言）
+fn fence_start(trimmed: &str) -> Option<(char, usize, &str)> {
+    let c = trimmed.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let len = trimmed.len() - trimmed.trim_start_matches(c).len();
+    let info = trimmed[len..].trim();
+    // 反引号围栏的语言中不能再有反引号
+    (len >= 3 && !(c == '`' && info.contains('`'))).then_some((c, len, info))
+}
+
+fn heading_level(trimmed: &str) -> Option<usize> {
+    let level = trimmed.len() - trimmed.trim_start_matches('#').len();
+    let rest = &trimmed[level..];
+    ((1..=6).contains(&level) && (rest.is_empty() || rest.starts_with(' '))).then_some(level)
+}
+
+// 分隔线：三个以上相同的 - * _，中间可以有空格
+fn is_rule(trimmed: &str) -> bool {
+    let Some(c) = trimmed
+        .chars()
+        .next()
+        .filter(|c| matches!(c, '-' | '*' | '_'))
+    else {
+        return false;
+    };
+    trimmed.chars().all(|ch| ch == c || ch == ' ') && trimmed.matches(c).count() >= 3
+}
+
+// 列表标记，返回（是否有序，起始序号，标记加其后空格的宽度）
+fn list_marker(trimmed: &str) -> Option<(bool, usize, usize)> {
+    let digits = trimmed.len()
+        - trimmed
+            .trim_start_matches(|c: char| c.is_ascii_digit())
+            .len();
+    let (ordered, marker_len) = match trimmed.as_bytes().first()? {
+        b'-' | b'*' | b'+' => (false, 1),
+        _ if (1..=9).contains(&digits)
+            && matches!(trimmed.as_bytes().get(digits), Some(b'.' | b')')) =>
+        {
+            (true, digits + 1)
+        }
+        _ => return None,
+    };
+    let rest = &trimmed[marker_len..];
+    if !rest.is_empty() && !rest.starts_with(' ') {
+        return None;
+    }
+    let spaces = rest.len() - rest.trim_start_matches(' ').len();
+    // 标记后超过 4 个空格或内容为空时，内容从标记后一列开始
+    let width = if rest.trim().is_empty() || spaces > 4 {
+        marker_len + 1
+    } else {
+        marker_len + spaces
+    };
+    let start = if ordered {
+        trimmed[..digits].parse().ok()?
+    } else {
+        1
+    };
+    Some((ordered, start, width))
+}
+
+// 拆分表格的一行，忽略行首行尾的竖线、转义的竖线与维基链接中的竖线
+fn table_cells(line: &str) -> Vec<String> {
+    let line = line.trim();
+    let line = line.strip_prefix('|').unwrap_or(line);
+    let line = if line.ends_with('|') && !line.ends_with("\\|") {
+        &line[..line.len() - 1]
+    } else {
+        line
+    };
+
+    let mut cells = Vec::new();
+    let mut current = String::new();
+    let mut chars = line.chars().peekable();
+    let mut in_wikilink = false;
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&'|') => {
+                current.push('|');
+                chars.next();
+            }
+            '[' if chars.peek() == Some(&'[') => {
+                in_wikilink = true;
+                current.push_str("[[");
+                chars.next();
+            }
+            ']' if chars.peek() == Some(&']') => {
+                in_wikilink = false;
+                current.push_str("]]");
+                chars.next();
+            }
+            '|' if !in_wikilink => cells.push(std::mem::take(&mut current).trim().to_string()),
+            c => current.push(c),
+        }
+    }
+    cells.push(current.trim().to_string());
+    cells
+}
+
+// 表格的分隔行，返回各列的对齐方式
+fn table_aligns(line: &str) -> Option<Vec<&'static str>> {
+    if !line.contains('-') {
+        return None;
+    }
+    table_cells(line)
+        .iter()
+        .map(|cell| {
+            let dashes = cell.trim_start_matches(':').trim_end_matches(':');
+            if dashes.is_empty() || !dashes.chars().all(|c| c == '-') {
+                return None;
+            }
+            Some(match (cell.starts_with(':'), cell.ends_with(':')) {
+                (true, true) => "center",
+                (false, true) => "right",
+                (true, false) => "left",
+                (false, false) => "",
+            })
+        })
+        .collect()
+}
+
+struct Renderer<'a> {
+    wikilink: &'a dyn Fn(&str) -> Option<String>,
+}
+
+impl Renderer<'_> {
+    // 渲染一组块级元素；tight 为 true 时段落不加 <p>，用于紧凑列表的列表项
+    fn blocks(&self, lines: &[String], tight: bool, out: &mut String) {
+        let mut i = 0;
+        while i < lines.len() {
+            let line = &lines[i];
+            let trimmed = line.trim_start();
+            if trimmed.is_empty() {
+                i += 1;
+                continue;
+            }
+
+            if let Some((fence, len, info)) = fence_start(trimmed) {
+                i = self.code_block(lines, i, (fence, len, info), out);
+            } else if let Some(level) = heading_level(trimmed) {
+                let text = trimmed[level..].trim();
+                // 去掉结尾可选的 #
+                let text = match text.trim_end_matches('#') {
+                    rest if rest.is_empty() || rest.ends_with(' ') => rest.trim_end(),
+                    _ => text,
+                };
+                out.push_str(&format!("<h{}>", level));
+                self.inline(text, out);
+                out.push_str(&format!("</h{}>\n", level));
+                i += 1;
+            } else if is_rule(trimmed) {
+                out.push_str("<hr>\n");
+                i += 1;
+            } else if trimmed.starts_with('>') {
+                let mut quoted = Vec::new();
+                while i < lines.len() && lines[i].trim_start().starts_with('>') {
+                    let rest = &lines[i].trim_start()[1..];
+                    quoted.push(rest.strip_prefix(' ').unwrap_or(rest).to_string());
+                    i += 1;
+                }
+                out.push_str("<blockquote>\n");
+                self.blocks(&quoted, false, out);
+                out.push_str("</blockquote>\n");
+            } else if list_marker(trimmed).is_some() {
+                i = self.list(lines, i, out);
+            } else if trimmed.contains('|')
+                && let Some(aligns) = lines.get(i + 1).and_then(|next| table_aligns(next))
+                && aligns.len() == table_cells(trimmed).len()
+            {
+                i = self.table(lines, i, &aligns, out);
+            } else {
+                i = self.paragraph(lines, i, tight, out);
+            }
+        }
+    }
+
+    fn code_block(
+        &self,
+        lines: &[String],
+        start: usize,
+        (fence, len, info): (char, usize, &str),
+        out: &mut String,
+    ) -> usize {
+        let indent = indent_of(&lines[start]);
+        let language = info.split_whitespace().next().unwrap_or("");
+        if language.is_empty() {
+            out.push_str("<pre><code>");
+        } else {
+            out.push_str(&format!(
+                "<pre><code class=\"language-{}\">",
+                escape(language)
+            ));
+        }
+
+        let mut i = start + 1;
+        while i < lines.len() {
+            let trimmed = lines[i].trim_start();
+            let closing = trimmed.len() - trimmed.trim_start_matches(fence).len();
+            if closing >= len && trimmed[closing..].trim().is_empty() {
+                i += 1;
+                break;
+            }
+            // 去掉与开始行相同的缩进
+            let strip = indent.min(indent_of(&lines[i]));
+            out.push_str(&escape(&lines[i][strip..]));
+            out.push('\n');
+            i += 1;
+        }
+        out.push_str("</code></pre>\n");
+        i
+    }
+
+    fn paragraph(&self, lines: &[String], start: usize, tight: bool, out: &mut String) -> usize {
+        // 保留行尾的空格，用于判断换行
+        let mut text = lines[start].trim_start().to_string();
+        let mut i = start + 1;
+        while i < lines.len() {
+            let trimmed = lines[i].trim_start();
+            // Setext 标题：段落后紧跟 === 或 ---
+            if !trimmed.is_empty() {
+                let underline = trimmed.trim_end();
+                let level = if underline.chars().all(|c| c == '=') {
+                    Some(1)
+                } else if underline.chars().all(|c| c == '-') {
+                    Some(2)
+                } else {
+                    None
+                };
+                if let Some(level) = level {
+                    out.push_str(&format!("<h{}>", level));
+                    self.inline(&text, out);
+                    out.push_str(&format!("</h{}>\n", level));
+                    return i + 1;
+                }
+            }
+            if trimmed.is_empty()
+                || fence_start(trimmed).is_some()
+                || heading_level(trimmed).is_some()
+                || is_rule(trimmed)
+                || trimmed.starts_with('>')
+                || list_marker(trimmed).is_some_and(|(ordered, start, _)| !ordered || start == 1)
+            {
+                break;
+            }
+            text.push('\n');
+            text.push_str(lines[i].trim_start());
+            i += 1;
+        }
+
+        let text = text.trim_end();
+        if tight {
+            self.inline(text, out);
+            out.push('\n');
+        } else {
+            out.push_str("<p>");
+            self.inline(text, out);
+            out.push_str("</p>\n");
+        }
+        i
+    }
+
+    fn list(&self, lines: &[String], start: usize, out: &mut String) -> usize {
+        let base = indent_of(&lines[start]);
+        let (ordered, first, _) = list_marker(lines[start].trim_start()).unwrap();
+
+        let mut items: Vec<Vec<String>> = Vec::new();
+        let mut loose = false;
+        let mut i = start;
+        while i < lines.len() {
+            let line = &lines[i];
+            let trimmed = line.trim_start();
+            // 同一层级的下一个列表项，标记种类不同时结束列表
+            let Some((item_ordered, _, width)) = list_marker(trimmed)
+                .filter(|_| indent_of(line) == base)
+                .filter(|_| !is_rule(trimmed))
+            else {
+                break;
+            };
+            if item_ordered != ordered {
+                break;
+            }
+
+            let content = base + width;
+            let mut item = vec![trimmed[width.min(trimmed.len())..].to_string()];
+            i += 1;
+            while i < lines.len() {
+                let line = &lines[i];
+                if is_blank(line) {
+                    let Some(offset) = lines[i..].iter().position(|line| !is_blank(line)) else {
+                        i = lines.len();
+                        break;
+                    };
+                    let next = &lines[i + offset];
+                    if indent_of(next) > base {
+                        // 空行之后仍有缩进的内容时属于当前列表项
+                        item.extend(std::iter::repeat_n(String::new(), offset));
+                        i += offset;
+                    } else if indent_of(next) == base
+                        && !is_rule(next.trim_start())
+                        && list_marker(next.trim_start()).is_some_and(|(o, _, _)| o == ordered)
+                    {
+                        // 列表项之间有空行时为松散列表
+                        loose = true;
+                        i += offset;
+                        break;
+                    } else {
+                        break;
+                    }
+                } else if indent_of(line) > base {
+                    item.push(line[indent_of(line).min(content)..].to_string());
+                    i += 1;
+                } else if item.last().is_some_and(|last| !is_blank(last))
+                    && list_marker(line.trim_start()).is_none()
+                    && !starts_block(line.trim_start())
+                {
+                    // 段落的延续行可以不缩进
+                    item.push(line.trim_start().to_string());
+                    i += 1;
+                } else {
+                    break;
+                }
+            }
+            // 列表项内部的空行分隔了多个块时也是松散列表
+            while item.last().is_some_and(|last| is_blank(last)) {
+                item.pop();
+            }
+            if item
+                .windows(2)
+                .any(|pair| is_blank(&pair[0]) && !is_blank(&pair[1]) && indent_of(&pair[1]) == 0)
+            {
+                loose = true;
+            }
+            items.push(item);
+        }
+
+        match (ordered, first) {
+            (true, 1) => out.push_str("<ol>\n"),
+            (true, first) => out.push_str(&format!("<ol start=\"{}\">\n", first)),
+            (false, _) => out.push_str("<ul>\n"),
+        }
+        for mut item in items {
+            out.push_str("<li>");
+            // 任务列表
+            let first_line = item.first().map(String::as_str).unwrap_or("");
+            let checkbox = ["[ ]", "[x]", "[X]"]
+                .into_iter()
+                .find(|mark| first_line == *mark || first_line.starts_with(&format!("{} ", mark)));
+            if let Some(mark) = checkbox {
+                let checked = if mark == "[ ]" { "" } else { " checked" };
+                out.push_str(&format!("<input type=\"checkbox\" disabled{}> ", checked));
+                item[0] = item[0][mark.len()..].trim_start().to_string();
+            }
+            self.blocks(&item, !loose, out);
+            out.push_str("</li>\n");
+        }
+        out.push_str(if ordered { "</ol>\n" } else { "</ul>\n" });
+        i
+    }
+
+    fn table(&self, lines: &[String], start: usize, aligns: &[&str], out: &mut String) -> usize {
+        let row = |cells: Vec<String>, tag: &str, out: &mut String| {
+            out.push_str("<tr>");
+            for (index, align) in aligns.iter().enumerate() {
+                if align.is_empty() {
+                    out.push_str(&format!("<{}>", tag));
+                } else {
+                    out.push_str(&format!("<{} style=\"text-align: {}\">", tag, align));
+                }
+                self.inline(cells.get(index).map_or("", String::as_str), out);
+                out.push_str(&format!("</{}>", tag));
+            }
+            out.push_str("</tr>\n");
+        };
+
+        out.push_str("<table>\n<thead>\n");
+        row(table_cells(&lines[start]), "th", out);
+        out.push_str("</thead>\n<tbody>\n");
+        let mut i = start + 2;
+        while i < lines.len() && !is_blank(&lines[i]) && !starts_block(lines[i].trim_start()) {
+            row(table_cells(&lines[i]), "td", out);
+            i += 1;
+        }
+        out.push_str("</tbody>\n</table>\n");
+        i
+    }
+
+    fn inline(&self, text: &str, out: &mut String) {
+        let mut i = 0;
+        while i < text.len() {
+            let rest = &text[i..];
+            let c = rest.chars().next().unwrap();
+            let consumed = match c {
+                '\\' => match rest[1..].chars().next() {
+                    Some(next) if next.is_ascii_punctuation() => {
+                        out.push_str(&escape(&next.to_string()));
+                        Some(2)
+                    }
+                    Some('\n') => {
+                        out.push_str("<br>\n");
+                        Some(2)
+                    }
+                    _ => None,
+                },
+                '`' => self.code_span(rest, out),
+                '!' if rest.starts_with("![[") => {
+                    self.wikilink(&rest[1..], true, out).map(|n| n + 1)
+                }
+                '[' if rest.starts_with("[[") => self.wikilink(rest, false, out),
+                '!' if rest.starts_with("![") => self.link(&rest[1..], true, out).map(|n| n + 1),
+                '[' => self.link(rest, false, out),
+                '<' => autolink(rest, out),
+                '*' | '_' | '~' => self.emphasis(text, i, out),
+                '\n' => {
+                    // 行尾两个以上空格为换行
+                    let kept = out.trim_end_matches(' ').len();
+                    if out.len() - kept >= 2 {
+                        out.truncate(kept);
+                        out.push_str("<br>");
+                    }
+                    out.push('\n');
+                    Some(1)
+                }
+                _ => None,
+            };
+            // 不构成任何行内元素时原样输出当前字符
+            i += consumed.unwrap_or_else(|| {
+                out.push_str(&escape(&c.to_string()));
+                c.len_utf8()
+            });
+        }
+    }
+
+    // 行内代码，返回消耗的字节数；没有配对的反引号时原样输出
+    fn code_span(&self, rest: &str, out: &mut String) -> Option<usize> {
+        let ticks = rest.len() - rest.trim_start_matches('`').len();
+        let body = &rest[ticks..];
+        let mut search = 0;
+        while let Some(pos) = body[search..].find('`') {
+            let pos = search + pos;
+            let run = body[pos..].len() - body[pos..].trim_start_matches('`').len();
+            if run == ticks {
+                let code = body[..pos].replace('\n', " ");
+                let code = match code.strip_prefix(' ').and_then(|c| c.strip_suffix(' ')) {
+                    Some(inner) if !inner.trim().is_empty() => inner.to_string(),
+                    _ => code,
+                };
+                out.push_str(&format!("<code>{}</code>", escape(&code)));
+                return Some(ticks + pos + run);
+            }
+            search = pos + run;
+        }
+        out.push_str(&"`".repeat(ticks));
+        Some(ticks)
+    }
+
+    // [[目标|别名]]；embed 为 true 时是 ![[嵌入]]，图片输出为 <img>
+    fn wikilink(&self, rest: &str, embed: bool, out: &mut String) -> Option<usize> {
+        let end = rest[2..].find("]]")?;
+        let inner = &rest[2..2 + end];
+        if inner.contains('\n') {
+            return None;
+        }
+        let (target, label) = match inner.split_once('|') {
+            Some((target, label)) => (target.trim(), label.trim()),
+            None => (inner.trim(), inner.trim()),
+        };
+        let name = target.split('#').next().unwrap_or("").trim();
+        let name = name.strip_suffix(".md").unwrap_or(name);
+
+        if embed && is_image(name) {
+            out.push_str(&format!(
+                "<img src=\"{}\" alt=\"{}\">",
+                escape(&encode_url(name)),
+                escape(label)
+            ));
+        } else {
+            match (self.wikilink)(name) {
+                Some(href) => out.push_str(&format!(
+                    "<a class=\"wikilink\" href=\"{}\">{}</a>",
+                    escape(&href),
+                    escape(label)
+                )),
+                None => out.push_str(&format!(
+                    "<span class=\"broken-link\">{}</span>",
+                    escape(label)
+                )),
+            }
+        }
+        Some(end + 4)
+    }
+
+    // [文字](地址 "标题") 与 ![说明](地址)
+    fn link(&self, rest: &str, image: bool, out: &mut String) -> Option<usize> {
+        // 找到与开头配对的 ]
+        let mut depth = 0;
+        let mut close = None;
+        let mut escaped = false;
+        for (i, c) in rest.char_indices() {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '[' => depth += 1,
+                ']' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        close = Some(i);
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+        let close = close?;
+        let label = &rest[1..close];
+        let (dest, title, len) = link_destination(&rest[close + 1..])?;
+        let dest = rewrite_dest(&dest);
+
+        if image {
+            out.push_str(&format!(
+                "<img src=\"{}\" alt=\"{}\"",
+                escape(&dest),
+                escape(&plain_text(label))
+            ));
+            if let Some(title) = title {
+                out.push_str(&format!(" title=\"{}\"", escape(&title)));
+            }
+            out.push('>');
+        } else {
+            out.push_str(&format!("<a href=\"{}\"", escape(&dest)));
+            if let Some(title) = title {
+                out.push_str(&format!(" title=\"{}\"", escape(&title)));
+            }
+            out.push('>');
+            self.inline(label, out);
+            out.push_str("</a>");
+        }
+        Some(close + 1 + len)
+    }
+
+    // *强调*、**加粗**、***两者***、~~删除线~~，没有配对的分隔符时原样输出
+    fn emphasis(&self, text: &str, start: usize, out: &mut String) -> Option<usize> {
+        let rest = &text[start..];
+        let delim = rest.chars().next()?;
+        let run = rest.len() - rest.trim_start_matches(delim).len();
+        let before = text[..start].chars().next_back();
+
+        // ~ 只有 ~~ 有意义；_ 在单词内部不表示强调
+        let intraword = |c: Option<char>| c.is_some_and(char::is_alphanumeric);
+        let (len, open, close) = match (delim, run) {
+            ('~', 2) => (2, "<del>", "</del>"),
+            ('~', _) => return None,
+            (_, 1) => (1, "<em>", "</em>"),
+            (_, 3) => (3, "<em><strong>", "</strong></em>"),
+            (_, _) => (2, "<strong>", "</strong>"),
+        };
+        let after_open = rest[len..].chars().next();
+        if after_open.is_none_or(char::is_whitespace) || (delim == '_' && intraword(before)) {
+            return None;
+        }
+
+        let body = &rest[len..];
+        let mut search = 0;
+        while let Some(pos) = body[search..].find(delim) {
+            let pos = search + pos;
+            let close_run = body[pos..].len() - body[pos..].trim_start_matches(delim).len();
+            let prev = body[..pos].chars().next_back();
+            let next = body[pos + close_run..].chars().next();
+            let can_close = pos > 0
+                && prev.is_some_and(|c| !c.is_whitespace())
+                && !(delim == '_' && intraword(next));
+            if can_close && (close_run == len || (close_run == 3 && len == 1)) {
+                // `*a **b***`：最后一个 * 结束强调，前面两个属于内层的加粗
+                let inner = pos + close_run - len;
+                out.push_str(open);
+                self.inline(&body[..inner], out);
+                out.push_str(close);
+                return Some(len + inner + len);
+            }
+            search = pos + close_run;
+        }
+        None
+    }
+}
+
+// 可以打断段落与表格的块
+fn starts_block(trimmed: &str) -> bool {
+    fence_start(trimmed).is_some()
+        || heading_level(trimmed).is_some()
+        || is_rule(trimmed)
+        || trimmed.starts_with('>')
+}
+
+// 解析 `(地址 "标题")`，返回（地址，标题，消耗的字节数）
+fn link_destination(rest: &str) -> Option<(String, Option<String>, usize)> {
+    let inner = rest.strip_prefix('(')?;
+    let body = inner.trim_start();
+    let mut pos = inner.len() - body.len();
+
+    let dest = if let Some(quoted) = body.strip_prefix('<') {
+        let end = quoted.find('>')?;
+        pos += end + 2;
+        quoted[..end].to_string()
+    } else {
+        // 地址中的括号需要配对
+        let mut depth = 0;
+        let end = body
+            .char_indices()
+            .find(|&(_, c)| match c {
+                '(' => {
+                    depth += 1;
+                    false
+                }
+                ')' if depth == 0 => true,
+                ')' => {
+                    depth -= 1;
+                    false
+                }
+                c => c.is_whitespace(),
+            })
+            .map_or(body.len(), |(i, _)| i);
+        pos += end;
+        body[..end].to_string()
+    };
+
+    let after = &inner[pos..];
+    let trimmed = after.trim_start();
+    pos += after.len() - trimmed.len();
+    let mut title = None;
+    if let Some(quote) = trimmed.chars().next().filter(|c| matches!(c, '"' | '\'')) {
+        let end = trimmed[1..].find(quote)?;
+        title = Some(trimmed[1..1 + end].to_string());
+        let after = &trimmed[end + 2..];
+        pos += end + 2 + (after.len() - after.trim_start().len());
+    }
+    inner[pos..]
+        .starts_with(')')
+        .then(|| (dest, title, pos + 2))
+}
+
+// 本地 .md 链接改为 .html；javascript: 地址替换为空链接
+fn rewrite_dest(dest: &str) -> String {
+    if dest
+        .trim_start()
+        .to_ascii_lowercase()
+        .starts_with("javascript:")
+    {
+        return "#".to_string();
+    }
+    if dest.contains("://") || dest.starts_with("mailto:") {
+        return dest.to_string();
+    }
+    let (path, anchor) = match dest.find('#') {
+        Some(pos) => dest.split_at(pos),
+        None => (dest, ""),
+    };
+    match path.strip_suffix(".md") {
+        Some(stem) => format!("{}.html{}", stem, anchor),
+        None => dest.to_string(),
+    }
+}
+
+// <https://example.com> 与 <name@example.com>
+fn autolink(rest: &str, out: &mut String) -> Option<usize> {
+    let end = rest.find('>')?;
+    let inner = &rest[1..end];
+    if inner.is_empty() || inner.contains(char::is_whitespace) || inner.contains('<') {
+        return None;
+    }
+    let href = if inner.contains("://") {
+        inner.to_string()
+    } else if inner.contains('@') && !inner.contains(':') {
+        format!("mailto:{}", inner)
+    } else {
+        return None;
+    };
+    out.push_str(&format!(
+        "<a href=\"{}\">{}</a>",
+        escape(&href),
+        escape(inner)
+    ));
+    Some(end + 1)
+}
+
+// 去掉 Markdown 标记，用于图片的 alt
+fn plain_text(label: &str) -> String {
+    label
+        .chars()
+        .filter(|c| !matches!(c, '*' | '_' | '`' | '~' | '[' | ']'))
+        .collect()
+}
+
+fn is_image(name: &str) -> bool {
+    let lower = name.to_ascii_lowercase();
+    [".png", ".jpg", ".jpeg", ".gif", ".svg", ".webp"]
+        .iter()
+        .any(|ext| lower.ends_with(ext))
+}
+
+/// 将文件名编码为链接地址：空白、括号、`#`、`?` 与 `%` 需要转义，其余字符原样保留
+pub fn encode_url(name: &str) -> String {
+    let mut encoded = String::with_capacity(name.len());
+    for c in name.chars() {
+        match c {
+            ' ' | '(' | ')' | '#' | '?' | '%' | '"' | '<' | '>' => {
+                encoded.push_str(&format!("%{:02X}", c as u32));
+            }
+            c => encoded.push(c),
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 只有 `存在` 这篇笔记可以解析
+    fn html(text: &str) -> String {
+        to_html(text, &|name| {
+            (name == "存在").then(|| "存在.html".to_string())
+        })
+    }
+
+    #[test]
+    fn headings_and_rules() {
+        assert_eq!(html("# 标题 #\n## 二级"), "<h1>标题</h1>\n<h2>二级</h2>\n");
+        assert_eq!(html("####### 七级"), "<p>####### 七级</p>\n");
+        assert_eq!(html("#标签"), "<p>#标签</p>\n");
+        assert_eq!(html("标题\n==="), "<h1>标题</h1>\n");
+        assert_eq!(html("标题\n---"), "<h2>标题</h2>\n");
+        assert_eq!(html("---\n* * *"), "<hr>\n<hr>\n");
+    }
+
+    #[test]
+    fn paragraphs_and_breaks() {
+        assert_eq!(
+            html("第一行\n第二行\n\n另一段"),
+            "<p>第一行\n第二行</p>\n<p>另一段</p>\n"
+        );
+        assert_eq!(html("换行  \n下一行"), "<p>换行<br>\n下一行</p>\n");
+        assert_eq!(html("换行\\\n下一行"), "<p>换行<br>\n下一行</p>\n");
+        assert_eq!(
+            html("<b>a & b</b>"),
+            "<p>&lt;b&gt;a &amp; b&lt;/b&gt;</p>\n"
+        );
+        assert_eq!(html("\\*不是强调\\*"), "<p>*不是强调*</p>\n");
+    }
+
+    #[test]
+    fn code() {
+        assert_eq!(
+            html("```rust\nfn main() {}\n<x>\n```\n后文"),
+            "<pre><code class=\"language-rust\">fn main() {}\n&lt;x&gt;\n</code></pre>\n<p>后文</p>\n"
+        );
+        assert_eq!(html("~~~~\n~~~\n~~~~"), "<pre><code>~~~\n</code></pre>\n");
+        assert_eq!(html("```\n未闭合"), "<pre><code>未闭合\n</code></pre>\n");
+        assert_eq!(
+            html("`a` `` b`c `` `x"),
+            "<p><code>a</code> <code>b`c</code> `x</p>\n"
+        );
+        assert_eq!(html("`*a*`"), "<p><code>*a*</code></p>\n");
+    }
+
+    #[test]
+    fn emphasis() {
+        assert_eq!(
+            html("*斜体* **加粗** _a_ __b__ ~~删除~~"),
+            "<p><em>斜体</em> <strong>加粗</strong> <em>a</em> <strong>b</strong> <del>删除</del></p>\n"
+        );
+        assert_eq!(
+            html("***两者***"),
+            "<p><em><strong>两者</strong></em></p>\n"
+        );
+        assert_eq!(html("*a **b***"), "<p><em>a <strong>b</strong></em></p>\n");
+        assert_eq!(html("snake_case_name"), "<p>snake_case_name</p>\n");
+        assert_eq!(html("a * b * c"), "<p>a * b * c</p>\n");
+        assert_eq!(html("**未闭合"), "<p>**未闭合</p>\n");
+        assert_eq!(html("~单个~"), "<p>~单个~</p>\n");
+    }
+
+    #[test]
+    fn links_and_images() {
+        assert_eq!(
+            html("[文字](a.md#节 \"标题\") [外部](https://e.com/a(b)) [x](<a b.md>)"),
+            "<p><a href=\"a.html#节\" title=\"标题\">文字</a> \
+             <a href=\"https://e.com/a(b)\">外部</a> <a href=\"a b.html\">x</a></p>\n"
+        );
+        assert_eq!(
+            html("![**图**](p.png 'T')"),
+            "<p><img src=\"p.png\" alt=\"图\" title=\"T\"></p>\n"
+        );
+        assert_eq!(
+            html("[x](javascript:alert(1))"),
+            "<p><a href=\"#\">x</a></p>\n"
+        );
+        assert_eq!(html("[没有地址]"), "<p>[没有地址]</p>\n");
+        assert_eq!(
+            html("<https://e.com> <a@b.c> <not a link>"),
+            "<p><a href=\"https://e.com\">https://e.com</a> \
+             <a href=\"mailto:a@b.c\">a@b.c</a> &lt;not a link&gt;</p>\n"
+        );
+    }
+
+    #[test]
+    fn wikilinks() {
+        assert_eq!(
+            html("[[存在]] [[存在.md#节|别名]] [[缺失]]"),
+            "<p><a class=\"wikilink\" href=\"存在.html\">存在</a> \
+             <a class=\"wikilink\" href=\"存在.html\">别名</a> \
+             <span class=\"broken-link\">缺失</span></p>\n"
+        );
+        assert_eq!(
+            html("![[图 1.png|说明]]"),
+            "<p><img src=\"图%201.png\" alt=\"说明\"></p>\n"
+        );
+        assert_eq!(html("[[未闭合"), "<p>[[未闭合</p>\n");
+    }
+
+    #[test]
+    fn lists() {
+        assert_eq!(
+            html("- a\n- b\n  - 嵌套\n- [x] 完成\n- [ ] 待办"),
+            "<ul>\n<li>a\n</li>\n<li>b\n<ul>\n<li>嵌套\n</li>\n</ul>\n</li>\n\
+             <li><input type=\"checkbox\" disabled checked> 完成\n</li>\n\
+             <li><input type=\"checkbox\" disabled> 待办\n</li>\n</ul>\n"
+        );
+        assert_eq!(
+            html("3. 三\n4. 四"),
+            "<ol start=\"3\">\n<li>三\n</li>\n<li>四\n</li>\n</ol>\n"
+        );
+        assert_eq!(
+            html("1. a\n\n2. b"),
+            "<ol>\n<li><p>a</p>\n</li>\n<li><p>b</p>\n</li>\n</ol>\n"
+        );
+        assert_eq!(
+            html("- a\n延续\n\n段落"),
+            "<ul>\n<li>a\n延续\n</li>\n</ul>\n<p>段落</p>\n"
+        );
+        assert_eq!(
+            html("- a\n1. b"),
+            "<ul>\n<li>a\n</li>\n</ul>\n<ol>\n<li>b\n</li>\n</ol>\n"
+        );
+        assert_eq!(
+            html("2024. 年"),
+            "<ol start=\"2024\">\n<li>年\n</li>\n</ol>\n"
+        );
+        assert_eq!(html("-不是列表"), "<p>-不是列表</p>\n");
+    }
+
+    #[test]
+    fn blockquotes() {
+        assert_eq!(
+            html("> 引用\n> - 列表\n>\n> > 嵌套"),
+            "<blockquote>\n<p>引用</p>\n<ul>\n<li>列表\n</li>\n</ul>\n\
+             <blockquote>\n<p>嵌套</p>\n</blockquote>\n</blockquote>\n"
+        );
+    }
+
+    #[test]
+    fn tables() {
+        assert_eq!(
+            html("| 名称 | 数量 |\n|:---|---:|\n| [[存在\\|别名]] | 1 |\n| a \\| b |\n\n后文"),
+            "<table>\n<thead>\n\
+             <tr><th style=\"text-align: left\">名称</th><th style=\"text-align: right\">数量</th></tr>\n\
+             </thead>\n<tbody>\n\
+             <tr><td style=\"text-align: left\"><a class=\"wikilink\" href=\"存在.html\">别名</a></td>\
+             <td style=\"text-align: right\">1</td></tr>\n\
+             <tr><td style=\"text-align: left\">a | b</td><td style=\"text-align: right\"></td></tr>\n\
+             </tbody>\n</table>\n<p>后文</p>\n"
+        );
+        // 分隔行的列数不同时不是表格
+        assert_eq!(html("a | b\n---"), "<h2>a | b</h2>\n");
+        assert_eq!(
+            html("a | b\n--- | --- | ---"),
+            "<p>a | b\n--- | --- | ---</p>\n"
+        );
+    }
+
+    #[test]
+    fn tabs_and_urls() {
+        assert_eq!(
+            html("- a\n\t- b"),
+            "<ul>\n<li>a\n<ul>\n<li>b\n</li>\n</ul>\n</li>\n</ul>\n"
+        );
+        assert_eq!(
+            encode_url("a b(1)#?%\"<>.md"),
+            "a%20b%281%29%23%3F%25%22%3C%3E.md"
+        );
+        assert_eq!(
+            escape("<a href=\"x\">&</a>"),
+            "&lt;a href=&quot;x&quot;&gt;&amp;&lt;/a&gt;"
+        );
+    }
+}
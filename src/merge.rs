@@ -0,0 +1,182 @@
+// 合并博客目录：把另一个博客目录中的笔记与附件复制到当前目录
+//
+// 与当前目录中的文件同名且内容相同的笔记视为已经合并，直接跳过；内容不同的笔记为冲突，
+// 默认不复制任何文件，指定改名时在文件名后加上序号，并改写来源笔记中指向它的 [[链接]]。
+// 来源目录本身不会被修改。
+
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::note::note_file_name;
+use crate::org;
+use crate::rename::rewrite_links;
+use crate::vault::Vault;
+
+/// 合并选项
+pub struct MergeOptions<'a> {
+    /// 为重名且内容不同的笔记改名后复制；为 false 时有冲突就不复制任何文件
+    pub rename: bool,
+    /// 附件目录，相对于博客目录，两边使用同一路径
+    pub attachments: &'a str,
+    /// 只返回将要进行的复制与改名，不写入任何文件
+    pub dry_run: bool,
+}
+
+/// 一次合并的结果
+#[derive(Debug, Default)]
+pub struct MergeReport {
+    /// 复制的笔记：来源路径与目标路径，按来源路径排序
+    pub copied: Vec<(PathBuf, PathBuf)>,
+    /// 与目标中同名文件内容相同、没有复制的笔记
+    pub identical: Vec<PathBuf>,
+    /// 重名且内容不同的笔记；改名时同时列在 renamed 中，否则没有复制
+    pub conflicts: Vec<PathBuf>,
+    /// 改名的笔记：原名称与新名称
+    pub renamed: Vec<(String, String)>,
+    /// 改写了指向改名笔记的链接的来源笔记及各自改写的链接数
+    pub rewritten: Vec<(PathBuf, usize)>,
+    /// 复制的附件数
+    pub attachments: usize,
+    /// 与目标中同名文件内容不同、没有复制的附件，相对于博客目录
+    pub skipped_attachments: Vec<String>,
+}
+
+impl MergeReport {
+    /// 是否因为冲突没有复制任何文件
+    pub fn blocked(&self) -> bool {
+        !self.conflicts.is_empty() && self.renamed.is_empty()
+    }
+}
+
+/// 把 source 中的笔记与附件复制到 target
+///
+/// 有冲突且没有指定改名时只返回冲突列表，不复制任何文件。
+pub fn merge_vaults(
+    source: &Vault,
+    target: &Path,
+    options: &MergeOptions,
+) -> io::Result<MergeReport> {
+    let mut report = MergeReport::default();
+    let mut names: Vec<&str> = source.notes().map(|meta| meta.name.as_str()).collect();
+    names.sort_unstable();
+
+    // 新名称不能与两边已有的任何笔记重名
+    let mut taken: HashSet<String> = names.iter().map(|name| name.to_string()).collect();
+    let mut copies = Vec::new();
+    for &name in &names {
+        let from = source.note_path(name);
+        let to = target.join(note_file_name(name));
+        let content = fs::read(&from)?;
+        match fs::read(&to) {
+            Ok(existing) if existing == content => report.identical.push(from),
+            Ok(_) => {
+                report.conflicts.push(from.clone());
+                if options.rename {
+                    let new = unique_name(target, name, &mut taken);
+                    report.renamed.push((name.to_string(), new.clone()));
+                    copies.push((from, target.join(note_file_name(&new)), content));
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => copies.push((from, to, content)),
+            Err(e) => return Err(e),
+        }
+    }
+    if !report.conflicts.is_empty() && !options.rename {
+        return Ok(report);
+    }
+
+    // 改名的笔记在来源中按原名称链接，复制时改为新名称
+    for (from, to, content) in copies {
+        let mut count = 0;
+        let mut text = String::from_utf8_lossy(&content).into_owned();
+        for (old, new) in &report.renamed {
+            if let Some((rewritten, n)) = rewrite_links(&text, &[old.as_str()], new) {
+                text = rewritten;
+                count += n;
+            }
+        }
+        if count > 0 {
+            report.rewritten.push((from.clone(), count));
+        }
+        if !options.dry_run {
+            // 子目录中的笔记复制到同样的子目录
+            if let Some(parent) = to.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&to, if count > 0 { text.as_bytes() } else { &content })?;
+        }
+        report.copied.push((from, to));
+    }
+
+    let dir = options.attachments.trim_end_matches('/');
+    let from_dir = source.root().join(dir);
+    if from_dir.is_dir() {
+        copy_attachments(
+            &from_dir,
+            &target.join(dir),
+            dir,
+            options.dry_run,
+            &mut report,
+        )?;
+    }
+    Ok(report)
+}
+
+// 在名称后加上序号直到与两边的笔记都不重名，org 笔记的序号加在后缀之前
+fn unique_name(target: &Path, name: &str, taken: &mut HashSet<String>) -> String {
+    let (base, ext) = if org::is_org(name) {
+        name.split_at(name.len() - ".org".len())
+    } else {
+        (name, "")
+    };
+    let mut n = 2;
+    loop {
+        let candidate = format!("{} {}{}", base, n, ext);
+        if !taken.contains(&candidate) && !target.join(note_file_name(&candidate)).exists() {
+            taken.insert(candidate.clone());
+            return candidate;
+        }
+        n += 1;
+    }
+}
+
+// 递归复制附件目录，跳过隐藏文件；prefix 为报告中使用的相对路径
+fn copy_attachments(
+    from: &Path,
+    to: &Path,
+    prefix: &str,
+    dry_run: bool,
+    report: &mut MergeReport,
+) -> io::Result<()> {
+    let mut entries = fs::read_dir(from)?.collect::<io::Result<Vec<_>>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name.starts_with('.') {
+            continue;
+        }
+        let path = format!("{}/{}", prefix, name);
+        let source = entry.path();
+        if source.is_dir() {
+            copy_attachments(&source, &to.join(&name), &path, dry_run, report)?;
+            continue;
+        }
+        let content = fs::read(&source)?;
+        let destination = to.join(&name);
+        match fs::read(&destination) {
+            Ok(existing) if existing == content => {}
+            Ok(_) => report.skipped_attachments.push(path),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                if !dry_run {
+                    fs::create_dir_all(to)?;
+                    fs::write(&destination, &content)?;
+                }
+                report.attachments += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,457 @@
+// 单篇笔记的解析结果
+
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::date::Date;
+use crate::format;
+use crate::frontmatter::{self, Frontmatter, Value};
+use crate::i18n;
+use crate::links::{self, Link};
+use crate::org;
+use crate::output::GENERATOR_KEY;
+use crate::query;
+use crate::todos::{self, Task};
+
+/// 建立索引所需的笔记信息
+#[derive(Debug, Clone, PartialEq)]
+pub struct NoteMeta {
+    /// 文件名（不含 .md；org 笔记保留 .org 后缀）
+    pub name: String,
+    pub title: String,
+    /// 创建日期与时间，时间可能为空
+    pub created: Option<(Date, String)>,
+    /// 创建时间字段存在但无法解析时为字段原文，此时 created 为 None
+    pub invalid_created: Option<String>,
+    /// 最后更新的日期与时间，取自 Updated 字段，写法与 Created 相同；无法解析时为 None
+    pub updated: Option<(Date, String)>,
+    /// 没有标签的笔记记为 NeedTag
+    pub tags: Vec<String>,
+    /// 笔记的其他名称，可作为链接目标
+    pub aliases: Vec<String>,
+    /// 卡片盒笔记的 ID，取自 ID 字段，没有时取文件名开头的时间戳；可作为链接目标
+    pub id: Option<String>,
+    pub links: Vec<Link>,
+    /// 正文中嵌入的图片与文件，目标为 `![[文件]]` 中的文件名或 `![](路径)` 中的路径
+    pub embeds: Vec<Link>,
+    /// 正文中的待办事项
+    pub tasks: Vec<Task>,
+    /// 草稿：Draft 字段为 true，或带有 draft 标签
+    pub draft: bool,
+    /// 置顶：Pinned 字段为 true，或带有 pin 标签
+    pub pinned: bool,
+    /// Status 字段，规范为小写、空白换成 `-`，如 `in-progress`
+    pub status: Option<String>,
+    /// 正文字数，中日韩文字每字计一个词
+    pub words: usize,
+    /// 摘要：Summary 字段，没有时取正文中第一个不是标题的段落，去掉 Markdown 标记；
+    /// 至多 `SUMMARY_CHARS` 个字符
+    pub summary: Option<String>,
+    /// Review 字段原文，见 `review::Schedule`
+    pub review: Option<String>,
+    /// 正文中有 ```gtx 查询块，构建索引时需要重新生成查询结果
+    pub queries: bool,
+}
+
+/// 保存的摘要的最大字符数，标签页与日期页中的摘要另按配置截短
+pub const SUMMARY_CHARS: usize = 300;
+
+/// 没有标签的笔记使用的占位标签
+pub const NEED_TAG: &str = "NeedTag";
+
+/// 标记草稿的标签，不区分大小写
+pub const DRAFT_TAG: &str = "draft";
+
+/// 标记置顶笔记的标签，不区分大小写
+pub const PIN_TAG: &str = "pin";
+
+/// 状态页面按此顺序排在前面，其他状态随后按名称排序
+pub const STATUS_ORDER: [&str; 3] = ["todo", "in-progress", "done"];
+
+/// Status 字段的规范写法：小写，空白与 `_` 换成 `-`；为空时返回 None
+pub fn normalize_status(value: &str) -> Option<String> {
+    let words: Vec<String> = value
+        .split(|c: char| c.is_whitespace() || c == '_' || c == '-')
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    (!words.is_empty()).then(|| words.join("-"))
+}
+
+/// 带有 draft 标签的笔记是草稿
+pub fn has_draft_tag(tags: &[String]) -> bool {
+    tags.iter().any(|tag| tag.eq_ignore_ascii_case(DRAFT_TAG))
+}
+
+/// 带有 pin 标签的笔记置顶
+pub fn has_pin_tag(tags: &[String]) -> bool {
+    tags.iter().any(|tag| tag.eq_ignore_ascii_case(PIN_TAG))
+}
+
+/// 文件头中各字段使用的键名，比较时不区分大小写
+///
+/// 新建与修复笔记时写入这些键名；读取时没有该键名的字段再依次尝试 fallbacks 中为它配置的其他键名。
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrontmatterKeys {
+    pub title: String,
+    pub created: String,
+    pub updated: String,
+    pub tags: String,
+    pub aliases: String,
+    pub id: String,
+    pub draft: String,
+    pub pinned: String,
+    pub status: String,
+    pub summary: String,
+    pub review: String,
+    /// (键名, 其他键名)，同一键名的其他键名按优先顺序排列
+    pub fallbacks: Vec<(String, String)>,
+}
+
+impl FrontmatterKeys {
+    /// 按 key 读取字段，没有时依次尝试 key 的其他键名
+    pub fn lookup<'h>(&self, header: &'h Frontmatter, key: &str) -> Option<&'h Value> {
+        header.get(key).or_else(|| {
+            self.fallbacks
+                .iter()
+                .filter(|(primary, _)| primary.eq_ignore_ascii_case(key))
+                .find_map(|(_, other)| header.get(other))
+        })
+    }
+}
+
+impl Default for FrontmatterKeys {
+    fn default() -> Self {
+        FrontmatterKeys {
+            title: "Title".to_string(),
+            created: "Created".to_string(),
+            updated: "Updated".to_string(),
+            tags: "Tags".to_string(),
+            aliases: "Aliases".to_string(),
+            id: "ID".to_string(),
+            draft: "Draft".to_string(),
+            pinned: "Pinned".to_string(),
+            status: "Status".to_string(),
+            summary: "Summary".to_string(),
+            review: "Review".to_string(),
+            fallbacks: Vec::new(),
+        }
+    }
+}
+
+pub enum ParsedFile {
+    Note(Box<NoteMeta>),
+    /// 生成页面的文件头，是此前生成的页面
+    Generated,
+}
+
+/// 文件头是否来自生成的页面：带有 `generator: gtx`。只有 Title 的文件头同样可能是手写的笔记，
+/// 不作为判断依据；上次生成的页面另由缓存中记录的页面列表识别
+pub fn is_generated(header: &Frontmatter) -> bool {
+    header.get_str(GENERATOR_KEY) == Some("gtx")
+}
+
+/// 笔记名对应的文件名：markdown 笔记加上 .md，org 笔记的名称本身带有后缀
+pub fn note_file_name(name: &str) -> String {
+    if org::is_org(name) {
+        name.to_string()
+    } else {
+        format!("{}.md", name)
+    }
+}
+
+pub fn read_note(file_path: &Path) -> io::Result<ParsedFile> {
+    read_note_with(file_path, &FrontmatterKeys::default())
+}
+
+/// 与 `read_note` 相同，但按 keys 读取文件头字段
+pub fn read_note_with(file_path: &Path, keys: &FrontmatterKeys) -> io::Result<ParsedFile> {
+    let content = read_text(file_path)?;
+    let file_name = file_path.file_name().unwrap().to_str().unwrap().to_string();
+    if org::is_org(&file_name) {
+        return Ok(ParsedFile::Note(Box::new(org::parse(&content, &file_name))));
+    }
+    let file_name_without_ext = file_name.strip_suffix(".md").unwrap();
+
+    let (header, body) = match frontmatter::parse(&content) {
+        Ok(Some(parsed)) => parsed,
+        Ok(None) => (Frontmatter::default(), content.as_str()),
+        Err(e) => return Err(invalid(e.to_string())),
+    };
+
+    if is_generated(&header) {
+        return Ok(ParsedFile::Generated);
+    }
+
+    // 没有 Title 时使用文件名
+    let title = keys
+        .lookup(&header, &keys.title)
+        .and_then(Value::as_str)
+        .unwrap_or(file_name_without_ext);
+
+    // 无法解析的创建时间不影响笔记加入索引，记下原文以便在 index.md 中列出
+    let mut invalid_created = None;
+    let created = match keys.lookup(&header, &keys.created).map(parse_datetime) {
+        Some(Ok(created)) => Some(created),
+        Some(Err(value)) => {
+            invalid_created = Some(value);
+            None
+        }
+        None => None,
+    };
+    // 更新时间只用于更新日期页，无法解析时忽略
+    let updated = keys
+        .lookup(&header, &keys.updated)
+        .and_then(|value| parse_datetime(value).ok());
+
+    let mut tags = keys
+        .lookup(&header, &keys.tags)
+        .map_or_else(Vec::new, Value::tags);
+    let flag = |key: &str| {
+        keys.lookup(&header, key)
+            .and_then(Value::as_str)
+            .is_some_and(|value| {
+                ["true", "yes", "on"]
+                    .iter()
+                    .any(|yes| value.eq_ignore_ascii_case(yes))
+            })
+    };
+    let draft = has_draft_tag(&tags) || flag(&keys.draft);
+    let pinned = has_pin_tag(&tags) || flag(&keys.pinned);
+    let status = keys
+        .lookup(&header, &keys.status)
+        .and_then(Value::as_str)
+        .and_then(normalize_status);
+    if tags.is_empty() {
+        tags.push(NEED_TAG.to_string());
+    }
+
+    // 与标题、文件名相同或重复的别名没有意义
+    let mut seen = HashSet::new();
+    let mut aliases = keys
+        .lookup(&header, &keys.aliases)
+        .map_or_else(Vec::new, Value::items);
+    aliases.retain(|alias| {
+        alias != title && alias != file_name_without_ext && seen.insert(alias.clone())
+    });
+
+    let id = keys
+        .lookup(&header, &keys.id)
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .or_else(|| timestamp_id(file_name_without_ext));
+
+    // 正文首行的行号 = 文件头占用的行数 + 1
+    let first_line = content[..content.len() - body.len()].lines().count() + 1;
+    // 查询块的结果由 gtx 生成，不算作笔记的内容
+    let queries = query::has_blocks(body);
+    let body = &*query::strip_results(body);
+
+    let summary = keys
+        .lookup(&header, &keys.summary)
+        .and_then(Value::as_str)
+        .map(plain_text)
+        .or_else(|| first_paragraph(body))
+        .filter(|summary| !summary.is_empty())
+        .map(|summary| truncate(&summary, SUMMARY_CHARS));
+
+    let review = keys
+        .lookup(&header, &keys.review)
+        .and_then(Value::as_str)
+        .filter(|value| !value.trim().is_empty())
+        .map(str::to_string);
+
+    Ok(ParsedFile::Note(Box::new(NoteMeta {
+        name: file_name_without_ext.to_string(),
+        title: title.to_string(),
+        created,
+        invalid_created,
+        updated,
+        tags,
+        aliases,
+        id,
+        links: links::extract_links(body, first_line),
+        embeds: links::extract_embeds(body, first_line),
+        tasks: todos::extract_tasks(body, first_line),
+        draft,
+        pinned,
+        status,
+        words: count_words(body),
+        summary,
+        review,
+        queries,
+    })))
+}
+
+// 正文中第一个不是标题的段落，跳过代码块、注释、分隔线、表格与只有图片的行
+fn first_paragraph(body: &str) -> Option<String> {
+    let mut lines = Vec::new();
+    let mut in_code_block = false;
+    for line in body.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") {
+            in_code_block = !in_code_block;
+            if lines.is_empty() {
+                continue;
+            }
+            break;
+        }
+        if in_code_block {
+            continue;
+        }
+        let skipped = trimmed.starts_with('#')
+            || trimmed.starts_with("<!--")
+            || trimmed.starts_with('|')
+            || trimmed.starts_with("![")
+            || (trimmed.len() >= 3 && trimmed.chars().all(|c| matches!(c, '-' | '*' | '_')));
+        if trimmed.is_empty() || skipped {
+            if lines.is_empty() {
+                continue;
+            }
+            break;
+        }
+        lines.push(trimmed.trim_start_matches(['>', ' ']));
+    }
+    (!lines.is_empty()).then(|| plain_text(&lines.join(" ")))
+}
+
+// 去掉行内的 Markdown 标记：链接只留文字，去掉强调与行内代码的符号
+fn plain_text(text: &str) -> String {
+    let text = links::replace_links(text, &|inner| {
+        let shown = inner.split_once('|').map_or(inner, |(_, alias)| alias);
+        Some(shown.trim().to_string())
+    });
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text.as_str();
+    while let Some(start) = rest.find('[') {
+        // [文字](地址) 只留文字
+        let after = &rest[start + 1..];
+        let link = after.find("](").and_then(|mid| {
+            let close = after[mid + 2..].find(')')?;
+            Some((mid, mid + 2 + close + 1))
+        });
+        out.push_str(&rest[..start]);
+        match link {
+            Some((mid, end)) => {
+                out.push_str(&after[..mid]);
+                rest = &after[end..];
+            }
+            None => {
+                out.push('[');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out.replace('*', "")
+        .replace("__", "")
+        .replace('`', "")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// 截取前 max 个字符，截短时末尾加上省略号
+pub fn truncate(text: &str, max: usize) -> String {
+    match text.char_indices().nth(max) {
+        Some((end, _)) => format!("{}…", text[..end].trim_end()),
+        None => text.to_string(),
+    }
+}
+
+// Created、Updated 字段的日期与时间，无法解析时返回字段原文
+fn parse_datetime(value: &Value) -> Result<(Date, String), String> {
+    let full_date = value.words();
+    match full_date.first().map(|date| Date::parse(date)) {
+        Some(Ok((date, time))) => {
+            // RFC 3339 的时间在日期中，其他格式的时间是第二个词；
+            // 12 小时制的 AM/PM 可以是单独的第三个词，如 `2024-05-01 09:00 PM`
+            let ltime = match (time, full_date.get(1), full_date.get(2)) {
+                (Some(time), _, _) => time,
+                (None, Some(clock), Some(suffix))
+                    if suffix.eq_ignore_ascii_case("am") || suffix.eq_ignore_ascii_case("pm") =>
+                {
+                    format!("{} {}", clock, suffix)
+                }
+                (None, clock, _) => clock.cloned().unwrap_or_default(),
+            };
+            Ok((date, ltime))
+        }
+        _ => Err(full_date.join(" ")),
+    }
+}
+
+/// 文件名开头 12 或 14 位数字的时间戳 ID，如 `202405211432 标题` 中的 202405211432
+pub fn timestamp_id(name: &str) -> Option<String> {
+    let digits = name.bytes().take_while(u8::is_ascii_digit).count();
+    matches!(digits, 12 | 14).then(|| name[..digits].to_string())
+}
+
+/// 读取笔记的全文；不是 UTF-8 文本时的错误说明换成易懂的文字
+pub fn read_text(file_path: &Path) -> io::Result<String> {
+    let bytes = fs::read(file_path)?;
+    String::from_utf8(bytes).map_err(|_| invalid(i18n::tr("不是 UTF-8 编码的文本").to_string()))
+}
+
+fn invalid(reason: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, reason)
+}
+
+/// 统计字数：连续的非空白字符计一个词，中日韩文字每字单独计数
+pub fn count_words(text: &str) -> usize {
+    let mut count = 0;
+    let mut in_word = false;
+    for c in text.chars() {
+        if c.is_whitespace() {
+            in_word = false;
+        } else if format::char_width(c) == 2 && c.is_alphanumeric() {
+            count += 1;
+            in_word = false;
+        } else if !in_word {
+            count += 1;
+            in_word = true;
+        }
+    }
+    count
+}
+
+/// 估算阅读时间所用的速度，每分钟的字数（按 `count_words` 计）
+pub const WORDS_PER_MINUTE: usize = 300;
+
+/// 估算的阅读时间，单位为分钟，不足一分钟按一分钟计；空笔记为 0
+pub fn reading_minutes(words: usize) -> usize {
+    words.div_ceil(WORDS_PER_MINUTE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::date::Time;
+
+    fn created(text: &str) -> Result<(Date, String), String> {
+        parse_datetime(&Value::Scalar(text.to_string()))
+    }
+
+    #[test]
+    fn created_time_keeps_am_pm() {
+        let (date, time) = created("2024-05-01 09:00 PM").unwrap();
+        assert_eq!(date, Date::new(2024, 5, 1).unwrap());
+        assert_eq!(Time::parse(&time), Time::new(21, 0));
+
+        let (_, time) = created("2024-05-01 12:30 am").unwrap();
+        assert_eq!(Time::parse(&time), Time::new(0, 30));
+
+        let (_, time) = created("2024-05-01 9:30pm").unwrap();
+        assert_eq!(Time::parse(&time), Time::new(21, 30));
+    }
+
+    #[test]
+    fn created_time_without_suffix() {
+        assert_eq!(created("2024-05-01 09:00").unwrap().1, "09:00");
+        assert_eq!(created("2024-05-01T10:00:00+08:00").unwrap().1, "10:00");
+        assert_eq!(created("2024-05-01").unwrap().1, "");
+        assert!(created("2023-02-29 10:00").is_err());
+    }
+}
@@ -0,0 +1,81 @@
+// 按标题模糊查找笔记，供 gtx open 使用
+//
+// 查询中的字符按顺序出现在标题、别名或文件名中即算匹配（忽略大小写与空白），
+// 完全相同、前缀、连续子串与靠前的匹配得分更高。
+
+use crate::vault::Vault;
+
+/// 一篇匹配的笔记
+#[derive(Debug, Clone, PartialEq)]
+pub struct FuzzyMatch {
+    pub name: String,
+    pub title: String,
+    /// 匹配到的文本：标题、别名或文件名中得分最高的一个
+    pub matched: String,
+    pub score: u32,
+    /// 匹配的文本与查询完全相同（忽略大小写）
+    pub exact: bool,
+}
+
+/// 查找标题、别名或文件名与 query 模糊匹配的笔记，按得分从高到低、再按文件名排序
+pub fn find(vault: &Vault, query: &str) -> Vec<FuzzyMatch> {
+    let query: Vec<char> = normalize(query).chars().collect();
+    if query.is_empty() {
+        return Vec::new();
+    }
+    let mut matches: Vec<FuzzyMatch> = vault
+        .notes()
+        .filter_map(|meta| {
+            let candidates = [&meta.title, &meta.name].into_iter().chain(&meta.aliases);
+            let (score, matched) = candidates
+                .filter_map(|text| score(&query, text).map(|score| (score, text)))
+                .max_by(|a, b| a.0.cmp(&b.0).then_with(|| b.1.cmp(a.1)))?;
+            Some(FuzzyMatch {
+                name: meta.name.clone(),
+                title: meta.title.clone(),
+                matched: matched.clone(),
+                score,
+                exact: normalize(matched).chars().eq(query.iter().copied()),
+            })
+        })
+        .collect();
+    matches.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.name.cmp(&b.name)));
+    matches
+}
+
+// 小写并去掉空白
+fn normalize(text: &str) -> String {
+    text.chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+// 查询的每个字符依次出现在 text 中时返回得分，否则为 None
+fn score(query: &[char], text: &str) -> Option<u32> {
+    let text: Vec<char> = normalize(text).chars().collect();
+    if text.len() == query.len() && text.as_slice() == query {
+        return Some(10_000);
+    }
+    // 连续子串：越靠前越好
+    if let Some(start) = text.windows(query.len()).position(|window| window == query) {
+        let prefix_bonus = if start == 0 { 2_000 } else { 0 };
+        return Some(5_000 + prefix_bonus - (start as u32).min(1_000));
+    }
+
+    // 子序列：相邻字符连续出现时加分，跨过的字符越多分数越低
+    let mut score: u32 = 1_000;
+    let mut pos = 0;
+    let mut previous: Option<usize> = None;
+    for c in query {
+        let found = pos + text[pos..].iter().position(|t| t == c)?;
+        match previous {
+            Some(prev) if found == prev + 1 => score += 20,
+            Some(prev) => score = score.saturating_sub((found - prev - 1) as u32),
+            None => score = score.saturating_sub(found as u32),
+        }
+        previous = Some(found);
+        pos = found + 1;
+    }
+    Some(score.min(4_999))
+}
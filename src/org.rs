@@ -0,0 +1,188 @@
+// Org-mode 笔记的解析
+//
+// 文件开头的 `#+KEYWORD: 值` 行相当于 markdown 笔记的文件头：
+//   - `#+TITLE:` 标题
+//   - `#+DATE:` 创建时间，如 `<2024-05-01 Wed 10:30>`、`[2024-05-01]` 或 `2024-05-01`
+//   - `#+FILETAGS:` 标签，如 `:rust:cli:`，也接受以空白分隔的写法；带有 draft 标签的笔记是草稿，
+//     带有 pin 标签的笔记置顶
+//   - `#+ID:` 卡片盒 ID，没有时取文件名开头的时间戳
+//   - `#+STATUS:` 状态，如 `todo`、`in-progress`、`done`
+//   - `#+SUMMARY:` 或 `#+DESCRIPTION:` 摘要
+// 正文中的 `[[file:其他.org][描述]]`、`[[file:其他.md]]` 与 `[[目标]]` 记为链接，
+// 跳过 `#+BEGIN_SRC` 与 `#+BEGIN_EXAMPLE` 等块。
+//
+// Org 笔记的文件名保留 .org 后缀（如 `读书.org`），以区分同名的 markdown 笔记；
+// 不含后缀的文件名记为别名，因此 `[[读书]]` 也能解析到它。
+
+use crate::date::{Date, Time};
+use crate::links::Link;
+use crate::note::{
+    NEED_TAG, NoteMeta, SUMMARY_CHARS, count_words, has_draft_tag, has_pin_tag, normalize_status,
+    timestamp_id, truncate,
+};
+
+/// 笔记的文件名是否来自 org 笔记
+pub fn is_org(name: &str) -> bool {
+    name.ends_with(".org")
+}
+
+/// 解析 org 笔记，file_name 含 .org 后缀
+pub fn parse(content: &str, file_name: &str) -> NoteMeta {
+    let stem = file_name
+        .strip_suffix(".org")
+        .unwrap_or(file_name)
+        .to_string();
+
+    let mut title = None;
+    let mut date = None;
+    let mut id = None;
+    let mut status = None;
+    let mut summary = None;
+    let mut tags = Vec::new();
+    let mut header_lines = 0;
+    for line in content.lines() {
+        let trimmed = line.trim();
+        // 文件开头的关键字行之间可以有空行与 `# ` 注释
+        if trimmed.is_empty() || trimmed == "#" || trimmed.starts_with("# ") {
+            header_lines += 1;
+            continue;
+        }
+        let Some((keyword, value)) = trimmed
+            .strip_prefix("#+")
+            .and_then(|rest| rest.split_once(':'))
+        else {
+            break;
+        };
+        header_lines += 1;
+        let value = value.trim();
+        match keyword.to_ascii_uppercase().as_str() {
+            "TITLE" if !value.is_empty() => title = Some(value.to_string()),
+            "DATE" if !value.is_empty() => date = Some(value.to_string()),
+            "ID" if !value.is_empty() => id = Some(value.to_string()),
+            "STATUS" => status = normalize_status(value),
+            "SUMMARY" | "DESCRIPTION" if !value.is_empty() => {
+                summary = Some(truncate(value, SUMMARY_CHARS))
+            }
+            "FILETAGS" => tags.extend(
+                value
+                    .split(|c: char| c == ':' || c.is_whitespace())
+                    .filter(|tag| !tag.is_empty())
+                    .map(str::to_string),
+            ),
+            _ => {}
+        }
+    }
+    let draft = has_draft_tag(&tags);
+    let pinned = has_pin_tag(&tags);
+    if tags.is_empty() {
+        tags.push(NEED_TAG.to_string());
+    }
+
+    let mut invalid_created = None;
+    let created = date.and_then(|date| {
+        let parsed = parse_timestamp(&date);
+        if parsed.is_none() {
+            invalid_created = Some(date);
+        }
+        parsed
+    });
+
+    let id = id.or_else(|| timestamp_id(&stem));
+    let title = title.unwrap_or_else(|| stem.clone());
+    let aliases = if stem != title {
+        vec![stem]
+    } else {
+        Vec::new()
+    };
+    let body: Vec<&str> = content.lines().skip(header_lines).collect();
+
+    NoteMeta {
+        name: file_name.to_string(),
+        title,
+        created,
+        invalid_created,
+        updated: None,
+        tags,
+        aliases,
+        id,
+        links: extract_links(&body, header_lines + 1),
+        embeds: Vec::new(),
+        tasks: Vec::new(),
+        draft,
+        pinned,
+        status,
+        words: count_words(&body.join("\n")),
+        summary,
+        review: None,
+        queries: false,
+    }
+}
+
+// Org 的时间戳：日期之后可以有星期与时刻，两端可以有 <> 或 []
+fn parse_timestamp(text: &str) -> Option<(Date, String)> {
+    let inner = text
+        .trim()
+        .trim_start_matches(['<', '['])
+        .trim_end_matches(['>', ']']);
+    let mut words = inner.split_whitespace();
+    let (date, time) = Date::parse(words.next()?).ok()?;
+    // 星期的写法随语言而定，取第一个能解析为时刻的词；时刻范围 10:00-11:00 取开始时刻
+    let time = time.or_else(|| {
+        words.find_map(|word| Time::parse(word.split('-').next()?).map(|time| time.to_string()))
+    });
+    Some((date, time.unwrap_or_default()))
+}
+
+// 提取 `[[目标][描述]]` 与 `[[目标]]` 中指向笔记的链接，body 的首行行号为 first_line
+fn extract_links(body: &[&str], first_line: usize) -> Vec<Link> {
+    let mut links = Vec::new();
+    let mut in_block = false;
+    for (offset, line) in body.iter().enumerate() {
+        let upper = line.trim_start().to_ascii_uppercase();
+        if upper.starts_with("#+BEGIN_") {
+            in_block = true;
+            continue;
+        }
+        if upper.starts_with("#+END_") {
+            in_block = false;
+            continue;
+        }
+        if in_block {
+            continue;
+        }
+
+        let mut rest = *line;
+        while let Some(start) = rest.find("[[") {
+            let after = &rest[start + 2..];
+            let Some(end) = after.find(']') else {
+                break;
+            };
+            if let Some(target) = link_target(&after[..end]) {
+                links.push(Link {
+                    target,
+                    line: first_line + offset,
+                });
+            }
+            rest = &after[end..];
+        }
+    }
+    links
+}
+
+// 链接目标对应的笔记文件名：.md 后缀去掉，.org 后缀保留；网址、标题与 id 链接不计入
+fn link_target(target: &str) -> Option<String> {
+    let target = target.trim();
+    let target = target.strip_prefix("file:").unwrap_or(target);
+    // file:笔记.org::*标题 中 :: 之后为文件内的位置
+    let target = target.split("::").next().unwrap_or_default();
+    let target = target.strip_prefix("./").unwrap_or(target);
+    if target.is_empty()
+        || target.contains("://")
+        || target.starts_with(['*', '#', '/', '('])
+        || target.starts_with("id:")
+        || target.starts_with("mailto:")
+    {
+        return None;
+    }
+    Some(target.strip_suffix(".md").unwrap_or(target).to_string())
+}
@@ -0,0 +1,1986 @@
+// 生成 index.md、标签页、日期页（含年、月汇总页）与反向链接页面
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::cache::Affected;
+use crate::check::is_generated_page;
+use crate::cleanup::Cleanup;
+use crate::date::{Date, Time, from_timestamp};
+use crate::format::{ColumnFormatter, Columns};
+use crate::frontmatter;
+use crate::graph::{self, GraphMetrics};
+use crate::i18n::{self, Lang};
+use crate::index::{
+    CLOUD_WEIGHTS, Index, IndexSet, Related, child_tags, date_tree, tag_levels, week_tree,
+};
+use crate::json::Json;
+use crate::links::LinkIndex;
+use crate::log::Progress;
+use crate::note::{is_generated, note_file_name, reading_minutes, truncate};
+use crate::plan;
+use crate::template::Template;
+use crate::walk;
+
+/// 生成页面中链接的写法
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LinkStyle {
+    /// `[[笔记|标题]]`
+    #[default]
+    Wiki,
+    /// `[标题](笔记.md)`
+    Markdown,
+    /// 与 Obsidian 兼容的维基链接：文件头写为带 tags 的 YAML，不使用 `#list` 与多段别名
+    Obsidian,
+    /// Org-mode 的文件链接 `[[file:笔记.md][标题]]`，org 笔记链接到 .org 文件
+    Org,
+}
+
+/// 生成页面在文件头中带有 `generator: gtx`，用于识别旧的生成页面
+pub const GENERATOR_KEY: &str = "generator";
+
+impl LinkStyle {
+    /// 配置文件与缓存中使用的名称
+    pub fn name(self) -> &'static str {
+        match self {
+            LinkStyle::Wiki => "wiki",
+            LinkStyle::Markdown => "markdown",
+            LinkStyle::Obsidian => "obsidian",
+            LinkStyle::Org => "org",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<LinkStyle> {
+        match name {
+            "wiki" => Some(LinkStyle::Wiki),
+            "markdown" => Some(LinkStyle::Markdown),
+            "obsidian" => Some(LinkStyle::Obsidian),
+            "org" => Some(LinkStyle::Org),
+            _ => None,
+        }
+    }
+
+    /// 是否为 `[[...]]` 形式的链接
+    pub fn is_wiki(self) -> bool {
+        matches!(self, LinkStyle::Wiki | LinkStyle::Obsidian)
+    }
+
+    /// 指向 prefix + target 的链接，label 为空时显示 target
+    pub fn link(self, prefix: &str, target: &str, label: Option<&str>) -> String {
+        match (self, label) {
+            (LinkStyle::Wiki, Some(label)) => format!("[[{}{}|{}]]", prefix, target, label),
+            // Obsidian 的别名中不能再出现 |
+            (LinkStyle::Obsidian, Some(label)) => {
+                format!("[[{}{}|{}]]", prefix, target, label.replace('|', "｜"))
+            }
+            (LinkStyle::Wiki | LinkStyle::Obsidian, None) => format!("[[{}{}]]", prefix, target),
+            (LinkStyle::Markdown, label) => format!(
+                "[{}]({}{})",
+                label.unwrap_or(target),
+                prefix,
+                encode_path(&note_file_name(target))
+            ),
+            // 描述中不能出现 ]
+            (LinkStyle::Org, label) => format!(
+                "[[file:{}{}][{}]]",
+                prefix,
+                note_file_name(target),
+                label.unwrap_or(target).replace(['[', ']'], "")
+            ),
+        }
+    }
+
+    /// 生成页面的文件头，kind 为页面类别（index、tag、date 等），Obsidian 风格下记为 gtx/<kind> 标签；
+    /// 总是带有 `generator: gtx`，以便与笔记区分
+    pub fn frontmatter(self, title: &str, kind: &str) -> String {
+        match self {
+            LinkStyle::Obsidian => format!(
+                "---\nTitle: {}\ntags: [gtx/{}]\n{}: gtx\n---\n",
+                title, kind, GENERATOR_KEY
+            ),
+            _ => format!("---\nTitle: {}\n{}: gtx\n---\n", title, GENERATOR_KEY),
+        }
+    }
+
+    /// 列表页面开头的 `#list` 行；Obsidian 会把它当作标签，因此不写
+    pub fn list_marker(self) -> &'static str {
+        match self {
+            LinkStyle::Obsidian => "",
+            _ => "#list\n",
+        }
+    }
+}
+
+// Markdown 链接地址中不能直接出现空白与括号
+fn encode_path(target: &str) -> String {
+    let mut encoded = String::new();
+    for c in target.chars() {
+        match c {
+            ' ' => encoded.push_str("%20"),
+            '(' => encoded.push_str("%28"),
+            ')' => encoded.push_str("%29"),
+            c => encoded.push(c),
+        }
+    }
+    encoded
+}
+
+/// 孤立笔记页面的文件名
+pub const ORPHANS_PAGE: &str = "orphans.md";
+
+/// 草稿列表页面的文件名
+pub const DRAFTS_PAGE: &str = "drafts.md";
+
+/// 状态页面的文件名前缀，页面名形如 status-todo.md
+pub const STATUS_PAGE_PREFIX: &str = "status-";
+
+/// 标签页默认所在的目录，相对输出目录，与静态站点的 tags/ 相同
+pub const DEFAULT_TAG_DIR: &str = "tags";
+
+/// 标签页相对输出目录的路径，不含 .md，同时用作指向标签页的链接目标；
+/// tag_dir 为空时标签页直接位于输出目录下，与同名笔记共用文件名
+pub fn tag_page(tag_dir: &str, tag: &str) -> String {
+    if tag_dir.is_empty() {
+        tag_slug(tag)
+    } else {
+        format!("{}/{}", tag_dir, tag_slug(tag))
+    }
+}
+
+/// 标签页的文件名，不含扩展名：/ 仍分隔层级标签，每一级中文件系统或维基链接不允许的字符
+/// 换成对应的全角字符，页面标题与链接文字仍使用原来的标签
+///
+/// 结尾的 . 与空格（Windows 会去掉）、空的一级与 Windows 保留的设备名（CON、NUL 等）同样改写。
+pub fn tag_slug(tag: &str) -> String {
+    tag.split('/').map(slug_level).collect::<Vec<_>>().join("/")
+}
+
+// Windows 上不能作为文件名（不论扩展名）的设备名
+const RESERVED_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+fn slug_level(level: &str) -> String {
+    let mut slug: String = level
+        .chars()
+        .filter(|c| !c.is_control())
+        .map(|c| match c {
+            ':' => '：',
+            '*' => '＊',
+            '?' => '？',
+            '"' => '＂',
+            '<' => '＜',
+            '>' => '＞',
+            '|' => '｜',
+            '\\' => '＼',
+            // [[ ]] 中 # 与 ^ 引用标题与段落
+            '#' => '＃',
+            '^' => '＾',
+            '[' => '［',
+            ']' => '］',
+            c => c,
+        })
+        .collect();
+    let kept = slug.trim_end_matches(['.', ' ']).len();
+    let tail: String = slug[kept..]
+        .chars()
+        .map(|c| if c == '.' { '．' } else { '_' })
+        .collect();
+    slug.truncate(kept);
+    slug.push_str(&tail);
+    if slug.is_empty() {
+        slug.push('_');
+    }
+    let stem = slug.split('.').next().unwrap_or_default();
+    if RESERVED_NAMES
+        .iter()
+        .any(|name| name.eq_ignore_ascii_case(stem))
+    {
+        slug.insert(stem.len(), '_');
+    }
+    slug
+}
+
+/// 标签页中生成内容的开始与结束标记，标记之外手写的内容在重新生成时保留
+pub const MANUAL_BEGIN: &str = "<!-- gtx:begin -->";
+pub const MANUAL_END: &str = "<!-- gtx:end -->";
+
+/// 标签共现页面的文件名
+pub const TAG_GRAPH_PAGE: &str = "tag-graph.md";
+
+/// 标签云页面的文件名
+pub const TAG_CLOUD_PAGE: &str = "tag-cloud.md";
+
+/// 每周回顾页面所在的目录，页面名形如 2024-W21.md
+pub const WEEKLY_DIR: &str = "weekly";
+
+/// 反向链接页面所在的目录，子目录中的笔记的页面位于同样的子目录下
+pub const BACKLINKS_DIR: &str = "backlinks";
+
+/// 创建热力图页面的文件名
+pub const HEATMAP_PAGE: &str = "heatmap.md";
+
+/// 热力图覆盖的周数，到本周为止
+pub const HEATMAP_WEEKS: usize = 53;
+
+// 热力图的色阶，从没有笔记到最多
+const HEATMAP_SHADES: [&str; 5] = ["·", "░", "▒", "▓", "█"];
+
+/// 按标题首字母分组的标题索引页面的文件名
+pub const AZ_PAGE: &str = "a-z.md";
+
+/// 每篇笔记链接到哪些笔记的页面的文件名
+pub const LINKS_PAGE: &str = "links.md";
+
+/// 更新日期页所在的目录，页面名与日期页相同，形如 20240501.md
+pub const UPDATED_DIR: &str = "updated";
+
+/// index.md 的 Stale 部分列出的笔记数
+pub const STALE_NOTES: usize = 10;
+
+/// index.md 的 Recent 部分列出的笔记数
+pub const RECENT_NOTES: usize = 10;
+
+/// 反向链接页面中列出的相关笔记数
+pub const RELATED_NOTES: usize = 5;
+
+/// 自定义模板所在的目录，相对博客目录
+pub const TEMPLATES_DIR: &str = ".gtx/templates";
+
+// 内置模板，与自定义模板使用相同的变量
+const INDEX_TEMPLATE: &str = "{{ frontmatter }}{% if pinned %}
+# Pinned
+{% for note in pinned %}{{ note.link }}
+{% endfor %}{% endif %}{% if status_table %}
+# Status
+{{ status_table }}{% endif %}{% if recent %}
+# Recent
+{% for note in recent %}{{ note.link }} {{ note.kind }}于 {{ note.date }}{% if note.time %} {{ note.time }}{% endif %}
+{% endfor %}{% endif %}
+# Tags
+{{ tag_table }}
+# Dates
+{{ date_table }}{% if mermaid %}
+# Graph
+{{ mermaid }}{% endif %}{% if hubs %}
+# Hubs
+{% for note in hubs %}{{ note.link }} · 中心度 {{ note.rank }} · {{ note.incoming }} 篇链接到此
+{% endfor %}{% endif %}{% if updated %}
+# Updated
+{% for day in updated %}{{ day.link }}({{ day.count }}) {% endfor %}
+{% endif %}{% if stale %}
+# Stale
+{% for note in stale %}{{ note.link }} 更新于 {{ note.date }}
+{% endfor %}{% endif %}{% if untagged %}
+# Untagged
+{% for note in untagged %}{{ note.link }}
+{% endfor %}{% endif %}{% if invalid_created %}
+# Invalid Created
+{% for note in invalid_created %}{{ note.link }}: `{{ note.value }}`
+{% endfor %}{% endif %}";
+
+const TAG_TEMPLATE: &str = "{{ frontmatter }}
+{{ list_marker }}{% for note in notes %}{{ note.link }}{% if note.aliases %} ({% for alias in note.aliases %}{{ alias }}{% if not loop.last %}, {% endif %}{% endfor %}){% endif %} · {{ note.words }} 字 · {{ note.minutes }} 分钟{% if note.pinned %} · 置顶{% endif %}{% if note.summary %}
+> {{ note.summary }}{% endif %}
+{% endfor %}{% if children %}
+# 子标签
+{% for child in children %}{{ child.link }}({{ child.count }})
+{% endfor %}{% endif %}";
+
+const DATE_TEMPLATE: &str = "{{ frontmatter }}
+{{ list_marker }}{% for group in groups %}## {{ group.name }}
+{% for note in group.notes %}{{ note.link }} {% if note.aliases %}({% for alias in note.aliases %}{{ alias }}{% if not loop.last %}, {% endif %}{% endfor %}) {% endif %}· {{ note.words }} 字 · {{ note.minutes }} 分钟{% if note.summary %}
+> {{ note.summary }}{% endif %}
+{% endfor %}{% endfor %}";
+
+// 英文的内置模板，GTX_LANG 或配置项 lang 为 en 时使用
+const INDEX_TEMPLATE_EN: &str = "{{ frontmatter }}{% if pinned %}
+# Pinned
+{% for note in pinned %}{{ note.link }}
+{% endfor %}{% endif %}{% if status_table %}
+# Status
+{{ status_table }}{% endif %}{% if recent %}
+# Recent
+{% for note in recent %}{{ note.link }} {{ note.kind }} {{ note.date }}{% if note.time %} {{ note.time }}{% endif %}
+{% endfor %}{% endif %}
+# Tags
+{{ tag_table }}
+# Dates
+{{ date_table }}{% if mermaid %}
+# Graph
+{{ mermaid }}{% endif %}{% if hubs %}
+# Hubs
+{% for note in hubs %}{{ note.link }} · rank {{ note.rank }} · {{ note.incoming }} incoming
+{% endfor %}{% endif %}{% if updated %}
+# Updated
+{% for day in updated %}{{ day.link }}({{ day.count }}) {% endfor %}
+{% endif %}{% if stale %}
+# Stale
+{% for note in stale %}{{ note.link }} updated {{ note.date }}
+{% endfor %}{% endif %}{% if untagged %}
+# Untagged
+{% for note in untagged %}{{ note.link }}
+{% endfor %}{% endif %}{% if invalid_created %}
+# Invalid Created
+{% for note in invalid_created %}{{ note.link }}: `{{ note.value }}`
+{% endfor %}{% endif %}";
+
+const TAG_TEMPLATE_EN: &str = "{{ frontmatter }}
+{{ list_marker }}{% for note in notes %}{{ note.link }}{% if note.aliases %} ({% for alias in note.aliases %}{{ alias }}{% if not loop.last %}, {% endif %}{% endfor %}){% endif %} · {{ note.words }} words · {{ note.minutes }} min{% if note.pinned %} · pinned{% endif %}{% if note.summary %}
+> {{ note.summary }}{% endif %}
+{% endfor %}{% if children %}
+# Subtags
+{% for child in children %}{{ child.link }}({{ child.count }})
+{% endfor %}{% endif %}";
+
+const DATE_TEMPLATE_EN: &str = "{{ frontmatter }}
+{{ list_marker }}{% for group in groups %}## {{ group.name }}
+{% for note in group.notes %}{{ note.link }} {% if note.aliases %}({% for alias in note.aliases %}{{ alias }}{% if not loop.last %}, {% endif %}{% endfor %}) {% endif %}· {{ note.words }} words · {{ note.minutes }} min{% if note.summary %}
+> {{ note.summary }}{% endif %}
+{% endfor %}{% endfor %}";
+
+/// index.md、标签页与日期页的模板
+///
+/// 模板中可用的变量：
+/// - index.md: `pinned`（置顶的笔记，name、title、link，按标题排序）、
+///   `statuses`（name、count、link、notes，todo、in-progress、done 在前）、`status_table`（默认的看板表格）、`tags`（name、count、link，按笔记数从多到少）、`tag_table`（默认的标签表格）、
+///   `years`（name、count、link、months，月份中有 days，均从新到旧）、`date_table`（默认的日期列表）、
+///   `untagged`（没有标签的笔记，name、title、link）、`invalid_created`（创建时间无法解析的笔记，另有 value）
+/// - 标签页: `title`、`count`、`notes`（name、title、aliases、words、minutes、pinned、summary、link，置顶的笔记在前）、`children`（name、leaf、count、link）
+/// - 日期页: `title`、`count`、`notes`（name、title、aliases、time、period、words、minutes、summary、link）、
+///   `groups`（按上午、下午、晚上分组的 name 与 notes）
+/// - 所有页面: `frontmatter`（按链接写法生成的文件头）、`list_marker`（`#list` 行，Obsidian 风格下为空）
+pub struct PageTemplates {
+    pub index: Template,
+    pub tag: Template,
+    pub date: Template,
+    /// 自定义模板内容与输出语言的摘要，没有自定义模板且为默认语言时为空；变化时需要重写全部页面
+    pub fingerprint: String,
+}
+
+impl Default for PageTemplates {
+    fn default() -> Self {
+        PageTemplates {
+            index: Template::parse(builtin(INDEX_TEMPLATE, INDEX_TEMPLATE_EN))
+                .expect("内置模板有效"),
+            tag: Template::parse(builtin(TAG_TEMPLATE, TAG_TEMPLATE_EN)).expect("内置模板有效"),
+            date: Template::parse(builtin(DATE_TEMPLATE, DATE_TEMPLATE_EN)).expect("内置模板有效"),
+            fingerprint: String::new(),
+        }
+    }
+}
+
+impl PageTemplates {
+    /// 读取 dir 下的 index.md、tag.md 与 date.md，不存在的使用内置模板
+    pub fn load(dir: &Path) -> io::Result<PageTemplates> {
+        let mut templates = PageTemplates::default();
+        // FNV-1a，只用于判断模板是否变化
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        let mut custom = false;
+        for (name, slot) in [
+            ("index", &mut templates.index),
+            ("tag", &mut templates.tag),
+            ("date", &mut templates.date),
+        ] {
+            let path = dir.join(format!("{}.md", name));
+            let text = match fs::read_to_string(&path) {
+                Ok(text) => text,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e),
+            };
+            *slot = Template::parse(&text).map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    i18n::trf("模板 {} 解析失败，{}", &[&path.display(), &e]),
+                )
+            })?;
+            custom = true;
+            for byte in name.bytes().chain([0]).chain(text.bytes()).chain([0]) {
+                hash = (hash ^ u64::from(byte)).wrapping_mul(0x100_0000_01b3);
+            }
+        }
+        if custom {
+            templates.fingerprint = format!("{:016x}", hash);
+        }
+        // 语言决定内置模板与页面中的固定文字，同样计入摘要；默认语言不改变摘要
+        let lang = i18n::lang();
+        if lang != Lang::default() {
+            if !templates.fingerprint.is_empty() {
+                templates.fingerprint.push(' ');
+            }
+            templates.fingerprint.push_str(lang.name());
+        }
+        Ok(templates)
+    }
+}
+
+// 当前语言的内置模板
+fn builtin(zh: &'static str, en: &'static str) -> &'static str {
+    match i18n::lang() {
+        Lang::Zh => zh,
+        Lang::En => en,
+    }
+}
+
+/// a-z.md 中不以拉丁字母或数字开头的标题的分组方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TitleGroups {
+    /// 全部归入"其他"
+    #[default]
+    Letter,
+    /// 按首字所在的 Unicode 区块分为汉字、假名、谚文等
+    Block,
+}
+
+impl TitleGroups {
+    /// 配置文件中使用的名称
+    pub fn name(self) -> &'static str {
+        match self {
+            TitleGroups::Letter => "letter",
+            TitleGroups::Block => "block",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<TitleGroups> {
+        match name {
+            "letter" => Some(TitleGroups::Letter),
+            "block" => Some(TitleGroups::Block),
+            _ => None,
+        }
+    }
+
+    /// 标题所属的分组，返回排序用的序号与分组名；跳过开头的标点与空白
+    fn group(self, title: &str) -> (u32, String) {
+        // 不属于 A–Z 与 0–9 的分组排在其后，按区块依次排列
+        const BLOCKS: [(&str, &[(u32, u32)]); 5] = [
+            (
+                "汉字",
+                &[
+                    (0x3400, 0x4DBF),
+                    (0x4E00, 0x9FFF),
+                    (0xF900, 0xFAFF),
+                    (0x20000, 0x3FFFF),
+                ],
+            ),
+            ("假名", &[(0x3040, 0x30FF), (0x31F0, 0x31FF)]),
+            (
+                "谚文",
+                &[(0x1100, 0x11FF), (0x3130, 0x318F), (0xAC00, 0xD7AF)],
+            ),
+            ("希腊字母", &[(0x0370, 0x03FF)]),
+            ("西里尔字母", &[(0x0400, 0x04FF)]),
+        ];
+        let other = (
+            u32::from(b'Z') + 2 + BLOCKS.len() as u32,
+            i18n::tr("其他").to_string(),
+        );
+        let Some(first) = title.chars().find(|c| c.is_alphanumeric()) else {
+            return other;
+        };
+        if first.is_ascii_alphabetic() {
+            let letter = first.to_ascii_uppercase();
+            return (u32::from(letter), letter.to_string());
+        }
+        if first.is_ascii_digit() {
+            return (u32::from(b'Z') + 1, "0–9".to_string());
+        }
+        if self == TitleGroups::Letter {
+            return other;
+        }
+        let code = u32::from(first);
+        BLOCKS
+            .iter()
+            .position(|(_, ranges)| ranges.iter().any(|(lo, hi)| (*lo..=*hi).contains(&code)))
+            .map_or(other, |i| {
+                (
+                    u32::from(b'Z') + 2 + i as u32,
+                    i18n::tr(BLOCKS[i].0).to_string(),
+                )
+            })
+    }
+}
+
+/// 日期页、年月汇总页中时间的写法
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimeFormat {
+    /// 24 小时制，如 09:05
+    #[default]
+    H24,
+    /// 12 小时制，如 9:05 AM
+    H12,
+}
+
+impl TimeFormat {
+    /// 配置文件与缓存中使用的名称
+    pub fn name(self) -> &'static str {
+        match self {
+            TimeFormat::H24 => "24h",
+            TimeFormat::H12 => "12h",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<TimeFormat> {
+        match name {
+            "24h" => Some(TimeFormat::H24),
+            "12h" => Some(TimeFormat::H12),
+            _ => None,
+        }
+    }
+
+    /// 按此写法重写 Created 字段中的时间，无法识别的时间原样返回
+    pub fn format(self, time: &str) -> String {
+        match (Time::parse(time), self) {
+            (Some(time), TimeFormat::H24) => time.to_string(),
+            (Some(time), TimeFormat::H12) => time.to_12h(),
+            (None, _) => time.to_string(),
+        }
+    }
+}
+
+/// 标签页中笔记的排列顺序
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TagSort {
+    /// 按创建时间从早到晚，没有创建时间的排在最后
+    Created,
+    /// 按创建时间从晚到早，没有创建时间的排在最后
+    CreatedDesc,
+    /// 按标题
+    Title,
+    /// 按文件修改时间从新到旧
+    Modified,
+}
+
+impl TagSort {
+    /// 配置文件与命令行中使用的名称
+    pub fn name(self) -> &'static str {
+        match self {
+            TagSort::Created => "created",
+            TagSort::CreatedDesc => "created-desc",
+            TagSort::Title => "title",
+            TagSort::Modified => "modified",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<TagSort> {
+        match name {
+            "created" => Some(TagSort::Created),
+            "created-desc" => Some(TagSort::CreatedDesc),
+            "title" => Some(TagSort::Title),
+            "modified" => Some(TagSort::Modified),
+            _ => None,
+        }
+    }
+}
+
+/// 标签页的排序设置
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TagSorts {
+    /// 所有标签页的默认顺序，None 时按笔记加入索引的顺序
+    pub default: Option<TagSort>,
+    /// 单个标签的顺序，同时作用于其下的层级标签，后出现的优先
+    pub per_tag: Vec<(String, TagSort)>,
+}
+
+impl TagSorts {
+    /// tag 页面使用的顺序：最近一级设置了顺序的上级标签优先，其次为默认顺序
+    pub fn for_tag(&self, tag: &str) -> Option<TagSort> {
+        tag_levels(tag)
+            .into_iter()
+            .rev()
+            .find_map(|level| {
+                self.per_tag
+                    .iter()
+                    .rev()
+                    .find(|(name, _)| name == level)
+                    .map(|(_, sort)| *sort)
+            })
+            .or(self.default)
+    }
+
+    /// 记入缓存的摘要，设置变化时需要重写全部标签页
+    pub fn fingerprint(&self) -> String {
+        let mut parts: Vec<String> = self
+            .default
+            .iter()
+            .map(|sort| sort.name().to_string())
+            .collect();
+        parts.extend(
+            self.per_tag
+                .iter()
+                .map(|(tag, sort)| format!("{}={}", tag, sort.name())),
+        );
+        parts.join(";")
+    }
+}
+
+/// 写入页面时的选项
+pub struct WriteOptions<'a> {
+    /// 增量构建时受影响的页面，None 表示全部重写
+    pub affected: Option<&'a Affected>,
+    /// 处理旧页面的策略
+    pub cleanup: &'a Cleanup,
+    /// 指向笔记的链接前缀，输出目录在博客目录之外时为从输出目录到博客目录的相对路径，以 / 结尾
+    pub link_prefix: &'a str,
+    /// index.md 中标签表格的列数，None 时为 4 列
+    pub tag_columns: Option<Columns>,
+    /// index.md 中日期表格的列数，None 时为 7 列
+    pub date_columns: Option<Columns>,
+    pub link_style: LinkStyle,
+    /// 页面模板，None 时使用内置模板
+    pub templates: Option<&'a PageTemplates>,
+    /// 标签页中笔记的顺序
+    pub tag_sorts: &'a TagSorts,
+    pub time_format: TimeFormat,
+    /// a-z.md 中非拉丁字母标题的分组方式
+    pub title_groups: TitleGroups,
+    /// index.md 中 Mermaid 关系图包含的笔记数，0 表示不生成
+    pub mermaid: usize,
+    /// index.md 的 Hubs 部分列出的中心笔记数，0 表示不列出
+    pub hubs: usize,
+    /// 标签页与日期页中每篇笔记下的摘要的最大字符数，0 表示不显示
+    pub excerpt: usize,
+    /// 按标签页与日期页报告写入进度，None 时不报告
+    pub progress: Option<&'a Progress>,
+    /// 标签页所在的目录，相对 dir，为空时直接写在 dir 下，见 `tag_page`
+    pub tag_dir: &'a str,
+    /// 上次生成的页面，为相对 dir 的路径；其中的文件即使文件头没有 `generator: gtx` 也可以覆盖
+    pub previous: Option<&'a HashSet<String>>,
+}
+
+impl IndexSet {
+    /// 在 dir 下生成全部页面（index.md、标签页、日期页、年月汇总页、每周回顾页、更新日期页、orphans.md、
+    /// drafts.md、tag-graph.md、tag-cloud.md、heatmap.md、a-z.md、links.md 与反向链接页面），
+    /// 旧的反向链接页面移动到 dir/.gtx/trash
+    ///
+    /// 返回生成的页面，为相对 dir 的路径，以 / 分隔
+    pub fn write_to(&self, dir: &Path) -> io::Result<HashSet<String>> {
+        let cleanup = Cleanup::quarantine(dir.join(".gtx").join("trash"));
+        self.write_with(
+            dir,
+            &WriteOptions {
+                affected: None,
+                cleanup: &cleanup,
+                link_prefix: "",
+                tag_columns: None,
+                date_columns: None,
+                link_style: LinkStyle::Wiki,
+                templates: None,
+                tag_sorts: &TagSorts::default(),
+                time_format: TimeFormat::default(),
+                title_groups: TitleGroups::default(),
+                mermaid: 0,
+                hubs: 0,
+                excerpt: 0,
+                progress: None,
+                tag_dir: DEFAULT_TAG_DIR,
+                previous: None,
+            },
+        )
+    }
+
+    /// 按选项在 dir 下生成页面，返回值同 `write_to`
+    pub fn write_with(&self, dir: &Path, options: &WriteOptions) -> io::Result<HashSet<String>> {
+        let mut pages = Pages::new(dir, options.previous);
+        // 页面受影响或已被删除时需要重写
+        let style = options.link_style;
+        let needs_write = |changed: &dyn Fn(&Affected) -> bool, page: &Path| {
+            options.affected.is_none_or(changed) || !page.exists()
+        };
+        // 笔记的别名，在标签页与日期页中显示在标题后的括号中
+        let aliases = |name: &str| self.links.get_aliases(name).to_vec();
+        let alias_note = |name: &str| match self.links.get_aliases(name) {
+            [] => String::new(),
+            aliases => format!("({})", aliases.join(", ")),
+        };
+        // 笔记的摘要，按 excerpt 截短；不显示摘要时为空
+        let excerpt = |name: &str| match self.summaries.get(name) {
+            Some(summary) if options.excerpt > 0 => truncate(summary, options.excerpt),
+            _ => String::new(),
+        };
+        let default_templates;
+        let templates = match options.templates {
+            Some(templates) => templates,
+            None => {
+                default_templates = PageTemplates::default();
+                &default_templates
+            }
+        };
+
+        // 笔记的创建时间，标签页按创建时间排序时使用；日期为 YYYYMMDD，可直接比较
+        let mut created: HashMap<&str, (&str, &str)> = HashMap::new();
+        for date in self.dates.get_inputs() {
+            for (name, _, time) in self.dates.get_files_by_i(date).into_iter().flatten() {
+                created.insert(name, (date, time));
+            }
+        }
+
+        if let Some(progress) = options.progress {
+            progress.set_total(self.tags.get_inputs().len() + self.dates.get_inputs().len());
+        }
+        pages.claim("index.md");
+        let tags = &self.tags;
+        let mut tags_data: Vec<(&str, usize)> = Vec::new();
+        // 输出tag的名字和对应含有tag的节点数量
+        for tag in tags.get_inputs() {
+            if let Some(progress) = options.progress {
+                progress.inc();
+            }
+            let count = tags.get_i_count(tag);
+            tags_data.push((tag, count));
+            let page = tag_page(options.tag_dir, tag);
+            let tag_with_ext = format!("{}.md", page);
+            let tag_path = dir.join(&tag_with_ext);
+            if !pages.claim(&tag_with_ext) {
+                eprintln!("{}", i18n::tr("提示: 可设置 tag_dir 把标签页写入子目录"));
+                continue;
+            }
+            let sort = options.tag_sorts.for_tag(tag);
+            // 修改时间不记入受影响的范围，按修改时间排序的页面每次重写
+            if sort != Some(TagSort::Modified) && !needs_write(&|a| a.tags.contains(tag), &tag_path)
+            {
+                continue;
+            }
+            // 标签目录与层级标签的页面位于子目录中，相对路径链接需要先回到 dir
+            if let Some(parent) = tag_path.parent() {
+                plan::create_dir_all(parent)?;
+            }
+            let depth = page.matches('/').count();
+            let up = nested_prefix(style, "", depth);
+            let note_prefix = nested_prefix(style, options.link_prefix, depth);
+            let mut files: Vec<&(String, String, String)> =
+                tags.get_files_by_i(tag).into_iter().flatten().collect();
+            match sort {
+                Some(TagSort::Created) => files.sort_by_key(|(name, _, _)| {
+                    let when = created.get(name.as_str());
+                    (when.is_none(), when.copied())
+                }),
+                Some(TagSort::CreatedDesc) => files.sort_by_key(|(name, _, _)| {
+                    let when = created.get(name.as_str());
+                    (when.is_none(), Reverse(when.copied()))
+                }),
+                Some(TagSort::Title) => {
+                    files.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.cmp(&b.0)))
+                }
+                Some(TagSort::Modified) => {
+                    files.sort_by_key(|(name, _, _)| Reverse(self.modified.get(name).copied()))
+                }
+                None => {}
+            }
+            // 置顶的笔记排在最前，其余顺序不变
+            files.sort_by_key(|(name, _, _)| !self.pinned.contains_key(name));
+            let notes: Vec<Json> = files
+                .into_iter()
+                .map(|(file_name, file_title, _)| {
+                    Json::Object(vec![
+                        ("name".to_string(), file_name.as_str().into()),
+                        ("title".to_string(), file_title.as_str().into()),
+                        ("aliases".to_string(), aliases(file_name).into()),
+                        ("words".to_string(), self.words(file_name).into()),
+                        ("summary".to_string(), excerpt(file_name).into()),
+                        (
+                            "minutes".to_string(),
+                            reading_minutes(self.words(file_name)).into(),
+                        ),
+                        (
+                            "pinned".to_string(),
+                            self.pinned.contains_key(file_name).into(),
+                        ),
+                        (
+                            "link".to_string(),
+                            style.link(&note_prefix, file_name, Some(file_title)).into(),
+                        ),
+                    ])
+                })
+                .collect();
+            let children: Vec<Json> = child_tags(tags, tag)
+                .into_iter()
+                .map(|child| {
+                    Json::Object(vec![
+                        ("name".to_string(), child.into()),
+                        ("leaf".to_string(), tag_leaf(child).into()),
+                        ("count".to_string(), tags.get_i_count(child).into()),
+                        (
+                            "link".to_string(),
+                            style
+                                .link(
+                                    &up,
+                                    &tag_page(options.tag_dir, child),
+                                    Some(tag_leaf(child)),
+                                )
+                                .into(),
+                        ),
+                    ])
+                })
+                .collect();
+            let fields = vec![
+                ("title".to_string(), tag.as_str().into()),
+                ("count".to_string(), count.into()),
+                ("notes".to_string(), Json::Array(notes)),
+                ("children".to_string(), Json::Array(children)),
+            ];
+            let page = render_page(&templates.tag, "tag", tag, style, fields)?;
+            let existing = fs::read_to_string(&tag_path).ok();
+            pages.write(&tag_with_ext, &keep_manual(existing.as_deref(), &page))?;
+        }
+        // 标签集合的遍历顺序不固定，笔记数相同时按名称排列，使 index.md 的内容只取决于笔记
+        tags_data.sort_by_key(|&(tag, count)| (Reverse(count), tag));
+        let tag_table = if tags_data.iter().any(|(tag, _)| tag.contains('/')) {
+            // 有层级标签时按层级输出缩进的列表
+            let mut tree = String::new();
+            tag_tree(&mut tree, style, options.tag_dir, &tags_data, None, 0);
+            tree
+        } else {
+            let mut output_tags = Vec::new();
+            for (tag, count) in &tags_data {
+                let link = style.link("", &tag_page(options.tag_dir, tag), Some(tag));
+                output_tags.push(format!("{}({})", link, count));
+            }
+            let columns = options.tag_columns.unwrap_or(Columns::Fixed(4));
+            let formatter = ColumnFormatter::new(columns.resolve(&output_tags, 2)).with_padding(2);
+            formatter.format(&output_tags)
+        };
+        let tags_list: Vec<Json> = tags_data
+            .iter()
+            .map(|(tag, count)| {
+                Json::Object(vec![
+                    ("name".to_string(), (*tag).into()),
+                    ("count".to_string(), (*count).into()),
+                    (
+                        "link".to_string(),
+                        style
+                            .link("", &tag_page(options.tag_dir, tag), Some(tag))
+                            .into(),
+                    ),
+                ])
+            })
+            .collect();
+
+        let dates = &self.dates;
+        // 每个日期一个页面
+        for date in dates.get_inputs() {
+            if let Some(progress) = options.progress {
+                progress.inc();
+            }
+            let date_with_ext = format!("{}.md", date);
+            let date_path = dir.join(&date_with_ext);
+            if !pages.claim(&date_with_ext) || !needs_write(&|a| a.dates.contains(date), &date_path)
+            {
+                continue;
+            }
+            let mut groups: Vec<(&str, Vec<Json>)> = Vec::new();
+            let notes: Vec<Json> = date_files(dates, date, options.time_format)
+                .into_iter()
+                .map(|(file_name, file_title, ltime)| {
+                    let link =
+                        date_link(style, options.link_prefix, &file_name, &file_title, &ltime);
+                    let period = day_period(Time::parse(&ltime));
+                    let note = Json::Object(vec![
+                        ("aliases".to_string(), aliases(&file_name).into()),
+                        ("words".to_string(), self.words(&file_name).into()),
+                        ("summary".to_string(), excerpt(&file_name).into()),
+                        (
+                            "minutes".to_string(),
+                            reading_minutes(self.words(&file_name)).into(),
+                        ),
+                        ("name".to_string(), file_name.into()),
+                        ("title".to_string(), file_title.into()),
+                        ("time".to_string(), ltime.into()),
+                        ("period".to_string(), period.into()),
+                        ("link".to_string(), link.into()),
+                    ]);
+                    // 已按时间排序，同一时段的笔记相邻
+                    match groups.last_mut() {
+                        Some((name, notes)) if *name == period => notes.push(note.clone()),
+                        _ => groups.push((period, vec![note.clone()])),
+                    }
+                    note
+                })
+                .collect();
+            let groups: Vec<Json> = groups
+                .into_iter()
+                .map(|(name, notes)| {
+                    Json::Object(vec![
+                        ("name".to_string(), name.into()),
+                        ("notes".to_string(), Json::Array(notes)),
+                    ])
+                })
+                .collect();
+            let fields = vec![
+                ("title".to_string(), date.as_str().into()),
+                ("count".to_string(), notes.len().into()),
+                ("notes".to_string(), Json::Array(notes)),
+                ("groups".to_string(), Json::Array(groups)),
+            ];
+            pages.write(
+                &date_with_ext,
+                &render_page(&templates.date, "date", date, style, fields)?,
+            )?;
+        }
+
+        // 年、月汇总页，以及 index.md 中按年 → 月 → 日排列的日期
+        let rollup_changed = |a: &Affected, key: &str| {
+            a.dates.iter().any(|date| {
+                Date::parse(date)
+                    .is_ok_and(|(date, _)| date.year_key() == key || date.month_key() == key)
+            })
+        };
+        let columns = options.date_columns.unwrap_or(Columns::Fixed(7));
+        let mut date_table = String::new();
+        let mut years_list = Vec::new();
+        let tree = date_tree(dates);
+        for (year, months) in tree.iter().rev() {
+            let count: usize = months
+                .values()
+                .flatten()
+                .map(|date| dates.get_i_count(date))
+                .sum();
+            date_table.push_str(&format!("## {}({})\n", style.link("", year, None), count));
+            let mut months_list = Vec::new();
+            for (month, days) in months.iter().rev() {
+                let count: usize = days.iter().map(|date| dates.get_i_count(date)).sum();
+                date_table.push_str(&format!("### {}({})\n", style.link("", month, None), count));
+                let mut output_dates = Vec::new();
+                let mut days_list = Vec::new();
+                for date in days.iter().rev() {
+                    let link = style.link("", date, None);
+                    output_dates.push(format!("{}({})", link, dates.get_i_count(date)));
+                    days_list.push(Json::Object(vec![
+                        ("name".to_string(), (*date).into()),
+                        ("count".to_string(), dates.get_i_count(date).into()),
+                        ("link".to_string(), link.into()),
+                    ]));
+                }
+                let formatter = ColumnFormatter::new(columns.resolve(&output_dates, 2));
+                date_table.push_str(&formatter.format(&output_dates));
+                date_table.push('\n');
+                months_list.push(Json::Object(vec![
+                    ("name".to_string(), month.as_str().into()),
+                    ("count".to_string(), count.into()),
+                    ("link".to_string(), style.link("", month, None).into()),
+                    ("days".to_string(), Json::Array(days_list)),
+                ]));
+
+                let month_with_ext = format!("{}.md", month);
+                let month_path = dir.join(&month_with_ext);
+                if pages.claim(&month_with_ext)
+                    && needs_write(&|a| rollup_changed(a, month), &month_path)
+                {
+                    let mut page = format!(
+                        "{}\n{}",
+                        style.frontmatter(month, "date"),
+                        style.list_marker()
+                    );
+                    for date in days {
+                        page.push_str(&format!("## {}\n", style.link("", date, None)));
+                        for (file_name, file_title, ltime) in
+                            date_files(dates, date, options.time_format)
+                        {
+                            let link = date_link(
+                                style,
+                                options.link_prefix,
+                                &file_name,
+                                &file_title,
+                                &ltime,
+                            );
+                            page.push_str(&format!("{} {}\n", link, alias_note(&file_name)));
+                        }
+                    }
+                    pages.write(&month_with_ext, &page)?;
+                }
+            }
+            years_list.push(Json::Object(vec![
+                ("name".to_string(), year.as_str().into()),
+                ("count".to_string(), count.into()),
+                ("link".to_string(), style.link("", year, None).into()),
+                ("months".to_string(), Json::Array(months_list)),
+            ]));
+
+            let year_with_ext = format!("{}.md", year);
+            let year_path = dir.join(&year_with_ext);
+            if pages.claim(&year_with_ext) && needs_write(&|a| rollup_changed(a, year), &year_path)
+            {
+                let mut page = format!(
+                    "{}\n{}",
+                    style.frontmatter(year, "date"),
+                    style.list_marker()
+                );
+                for (month, days) in months {
+                    page.push_str(&format!("## {}\n", style.link("", month, None)));
+                    for date in days {
+                        for (file_name, file_title, ltime) in
+                            date_files(dates, date, options.time_format)
+                        {
+                            let when = format!("{} {}", date, ltime);
+                            let link = date_link(
+                                style,
+                                options.link_prefix,
+                                &file_name,
+                                &file_title,
+                                when.trim_end(),
+                            );
+                            page.push_str(&format!("{} {}\n", link, alias_note(&file_name)));
+                        }
+                    }
+                }
+                pages.write(&year_with_ext, &page)?;
+            }
+        }
+
+        // 更新日期页，位于 updated/ 下，比输出目录深一层
+        let updated_prefix = nested_prefix(style, options.link_prefix, 1);
+        let mut updated_list = Vec::new();
+        let mut updated_days: Vec<&String> = self.updated.get_inputs().iter().collect();
+        updated_days.sort_unstable_by(|a, b| b.cmp(a));
+        for date in updated_days {
+            let page_name = format!("{}/{}", UPDATED_DIR, date);
+            updated_list.push(Json::Object(vec![
+                ("name".to_string(), date.as_str().into()),
+                ("count".to_string(), self.updated.get_i_count(date).into()),
+                (
+                    "link".to_string(),
+                    style.link("", &page_name, Some(date)).into(),
+                ),
+            ]));
+            let page_file = format!("{}.md", page_name);
+            let page_path = dir.join(&page_file);
+            if !pages.claim(&page_file) || !needs_write(&|a| a.updated.contains(date), &page_path) {
+                continue;
+            }
+            plan::create_dir_all(&dir.join(UPDATED_DIR))?;
+            let mut page = format!(
+                "{}\n{}",
+                style.frontmatter(date, "updated"),
+                style.list_marker()
+            );
+            for (file_name, file_title, ltime) in
+                date_files(&self.updated, date, options.time_format)
+            {
+                let link = date_link(style, &updated_prefix, &file_name, &file_title, &ltime);
+                page.push_str(&format!("{} {}\n", link, alias_note(&file_name)));
+            }
+            pages.write(&page_file, &page)?;
+        }
+
+        // 每周回顾页，只依赖日期与标签索引；weekly/ 比输出目录深一层
+        let note_tags = self.note_tags();
+        for (week, days) in week_tree(dates) {
+            let page_name = format!("{}/{}.md", WEEKLY_DIR, week);
+            let page_path = dir.join(&page_name);
+            let week_changed = |a: &Affected| {
+                a.dates
+                    .iter()
+                    .any(|date| Date::parse(date).is_ok_and(|(date, _)| date.week_key() == week))
+            };
+            if !pages.claim(&page_name) || !needs_write(&week_changed, &page_path) {
+                continue;
+            }
+            plan::create_dir_all(&dir.join(WEEKLY_DIR))?;
+            let page = self.weekly_page(&week, &days, &note_tags, style, options);
+            pages.write(&page_name, &page)?;
+        }
+
+        // 任何笔记的变化都可能改变孤立状态，每次重写
+        let mut page = format!(
+            "{}\n{}",
+            style.frontmatter("orphans", "orphans"),
+            style.list_marker()
+        );
+        for (name, title) in self.orphans() {
+            page.push_str(&style.link(options.link_prefix, name, Some(title)));
+            page.push('\n');
+        }
+        pages.write(ORPHANS_PAGE, &page)?;
+
+        // 草稿数量很少，每次重写
+        let mut drafts: Vec<&(String, String)> = self.drafts.iter().collect();
+        drafts.sort();
+        let mut page = format!(
+            "{}\n{}",
+            style.frontmatter("drafts", "drafts"),
+            style.list_marker()
+        );
+        for (name, title) in drafts {
+            page.push_str(&style.link(options.link_prefix, name, Some(title)));
+            page.push('\n');
+        }
+        pages.write(DRAFTS_PAGE, &page)?;
+
+        // 状态页面与草稿相同，每次重写；不再使用的状态由 remove_stale_pages 删除
+        let by_status = self.by_status();
+        for (status, notes) in &by_status {
+            let page_name = format!("{}{}.md", STATUS_PAGE_PREFIX, status);
+            let mut page = format!(
+                "{}\n{}",
+                style.frontmatter(status, "status"),
+                style.list_marker()
+            );
+            for (name, title) in notes {
+                page.push_str(&style.link(options.link_prefix, name, Some(title)));
+                page.push('\n');
+            }
+            pages.write(&page_name, &page)?;
+        }
+
+        // 与孤立笔记相同，每次重写
+        pages.write(TAG_GRAPH_PAGE, &self.tag_graph_page(style, options.tag_dir))?;
+
+        // 权重取决于全部标签的笔记数，每次重写
+        pages.write(TAG_CLOUD_PAGE, &self.tag_cloud_page(style, options.tag_dir))?;
+
+        // 任何标题的变化都会改变分组，每次重写
+        pages.write(
+            AZ_PAGE,
+            &self.az_page(style, options.link_prefix, options.title_groups),
+        )?;
+
+        // 与反向链接相同，任何笔记的变化都可能改变链接能否解析，每次重写
+        pages.write(LINKS_PAGE, &self.links_page(style, options.link_prefix))?;
+
+        // 热力图截止到今天（UTC），日期变化后即使没有笔记变化也要重写
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        let (today, _) = from_timestamp(now);
+        pages.write(HEATMAP_PAGE, &self.heatmap_page(style, today))?;
+
+        // 相关笔记取决于全部笔记的标签，任何标签变化时都重写
+        write_backlinks(
+            &mut pages,
+            &self.links,
+            &self.related(RELATED_NOTES),
+            options.cleanup,
+            style,
+            options.link_prefix,
+            &|name, title| {
+                options.affected.is_none_or(|a| {
+                    !a.tags.is_empty() || a.links.contains(name) || a.links.contains(title)
+                })
+            },
+        )?;
+
+        // index.md 链接到上面的各个页面，最后写入：中途出错时保留上一次完整的 index.md
+        let note_entry = |name: &str, title: &str| {
+            vec![
+                ("name".to_string(), name.into()),
+                ("title".to_string(), title.into()),
+                (
+                    "link".to_string(),
+                    style.link(options.link_prefix, name, Some(title)).into(),
+                ),
+            ]
+        };
+        let mut pinned: Vec<(&String, &String)> = self.pinned.iter().collect();
+        pinned.sort_by(|a, b| a.1.cmp(b.1).then(a.0.cmp(b.0)));
+        let pinned: Vec<Json> = pinned
+            .into_iter()
+            .map(|(name, title)| Json::Object(note_entry(name, title)))
+            .collect();
+        let statuses: Vec<Json> = by_status
+            .iter()
+            .map(|(status, notes)| {
+                let notes: Vec<Json> = notes
+                    .iter()
+                    .map(|(name, title)| Json::Object(note_entry(name, title)))
+                    .collect();
+                Json::Object(vec![
+                    ("name".to_string(), (*status).into()),
+                    ("count".to_string(), notes.len().into()),
+                    (
+                        "link".to_string(),
+                        style
+                            .link(
+                                "",
+                                &format!("{}{}", STATUS_PAGE_PREFIX, status),
+                                Some(status),
+                            )
+                            .into(),
+                    ),
+                    ("notes".to_string(), Json::Array(notes)),
+                ])
+            })
+            .collect();
+        let status_table = status_table(&by_status, style, options.link_prefix);
+        let untagged: Vec<Json> = self
+            .untagged()
+            .into_iter()
+            .map(|(name, title)| Json::Object(note_entry(name, title)))
+            .collect();
+        let mut invalid: Vec<&(String, String, String)> = self.invalid_created.iter().collect();
+        invalid.sort();
+        let invalid_created: Vec<Json> = invalid
+            .into_iter()
+            .map(|(name, title, value)| {
+                let mut entry = note_entry(name, title);
+                entry.push(("value".to_string(), value.as_str().into()));
+                Json::Object(entry)
+            })
+            .collect();
+        // 最近创建或修改的笔记，时间按 UTC 显示
+        let recent: Vec<Json> = self
+            .recent()
+            .into_iter()
+            .filter(|note| note.latest() > 0)
+            .take(RECENT_NOTES)
+            .map(|note| {
+                let (kind, date, time) = match (note.is_modified(), note.created) {
+                    (false, Some((date, time))) => {
+                        (i18n::tr("创建"), date.to_string(), time.to_string())
+                    }
+                    _ => {
+                        let (date, time) = from_timestamp(note.latest());
+                        (i18n::tr("修改"), date.to_string(), time.to_string())
+                    }
+                };
+                let mut entry = note_entry(note.name, note.title);
+                entry.push(("kind".to_string(), kind.into()));
+                entry.push(("date".to_string(), date.into()));
+                entry.push(("time".to_string(), options.time_format.format(&time).into()));
+                Json::Object(entry)
+            })
+            .collect();
+        // 最久没有更新的笔记，更新日期相同时按文件名排序
+        let mut stale: Vec<(&str, &str, &str)> = self
+            .updated
+            .get_inputs()
+            .iter()
+            .flat_map(|date| {
+                self.updated
+                    .get_files_by_i(date)
+                    .into_iter()
+                    .flatten()
+                    .map(move |(name, title, _)| (date.as_str(), name.as_str(), title.as_str()))
+            })
+            .collect();
+        stale.sort_unstable();
+        let stale: Vec<Json> = stale
+            .into_iter()
+            .take(STALE_NOTES)
+            .map(|(date, name, title)| {
+                let mut entry = note_entry(name, title);
+                entry.push(("date".to_string(), date.into()));
+                Json::Object(entry)
+            })
+            .collect();
+        // 中心度最高的笔记，没有任何链接的笔记不计入
+        let hubs: Vec<Json> = if options.hubs == 0 {
+            Vec::new()
+        } else {
+            GraphMetrics::collect(self)
+                .notes
+                .into_iter()
+                .filter(|note| note.incoming + note.outgoing > 0)
+                .take(options.hubs)
+                .map(|note| {
+                    let mut entry = note_entry(&note.name, &note.title);
+                    entry.push(("rank".to_string(), format!("{:.2}", note.rank).into()));
+                    entry.push(("incoming".to_string(), note.incoming.into()));
+                    entry.push(("outgoing".to_string(), note.outgoing.into()));
+                    Json::Object(entry)
+                })
+                .collect()
+        };
+        let fields = vec![
+            ("pinned".to_string(), Json::Array(pinned)),
+            ("statuses".to_string(), Json::Array(statuses)),
+            ("status_table".to_string(), status_table.into()),
+            ("recent".to_string(), Json::Array(recent)),
+            ("updated".to_string(), Json::Array(updated_list)),
+            ("stale".to_string(), Json::Array(stale)),
+            (
+                "mermaid".to_string(),
+                graph::to_mermaid(self, options.mermaid).into(),
+            ),
+            ("hubs".to_string(), Json::Array(hubs)),
+            ("tags".to_string(), Json::Array(tags_list)),
+            ("tag_table".to_string(), tag_table.into()),
+            ("years".to_string(), Json::Array(years_list)),
+            ("date_table".to_string(), date_table.into()),
+            ("untagged".to_string(), Json::Array(untagged)),
+            ("invalid_created".to_string(), Json::Array(invalid_created)),
+        ];
+        pages.write(
+            "index.md",
+            &render_page(&templates.index, "index", "index", style, fields)?,
+        )?;
+
+        Ok(pages.generated)
+    }
+}
+
+// 一天中的笔记，按时间先后排列，无法识别的时间排在最后；时间按 time_format 重写
+fn date_files(dates: &Index, date: &str, time_format: TimeFormat) -> Vec<(String, String, String)> {
+    let mut file_list = dates.get_files_by_i(date).cloned().unwrap_or_default();
+    file_list.sort_by_cached_key(|(_, _, ltime)| {
+        let time = Time::parse(ltime);
+        (time.is_none(), time, ltime.clone())
+    });
+    for (_, _, ltime) in &mut file_list {
+        *ltime = time_format.format(ltime);
+    }
+    file_list
+}
+
+// 日期页中按时段分组的小标题
+fn day_period(time: Option<Time>) -> &'static str {
+    match time {
+        Some(time) if time.hour < 12 => i18n::tr("上午"),
+        Some(time) if time.hour < 18 => i18n::tr("下午"),
+        Some(_) => i18n::tr("晚上"),
+        None => i18n::tr("时间未知"),
+    }
+}
+
+// 日期页中指向笔记的链接，维基链接沿用 [[笔记|时间|标题]] 的写法；
+// 其余风格在时间与标题间加空格，没有时间时只写标题
+fn date_link(style: LinkStyle, link_prefix: &str, name: &str, title: &str, time: &str) -> String {
+    let label = match style {
+        LinkStyle::Wiki => format!("{}|{}", time, title),
+        LinkStyle::Markdown | LinkStyle::Obsidian | LinkStyle::Org if time.is_empty() => {
+            title.to_string()
+        }
+        LinkStyle::Markdown | LinkStyle::Obsidian | LinkStyle::Org => {
+            format!("{} {}", time, title)
+        }
+    };
+    style.link(link_prefix, name, Some(&label))
+}
+
+// 用模板渲染页面，fields 之外加入各页面共用的 frontmatter 与 list_marker，
+// name 为模板名，同时作为文件头中的页面类别
+fn render_page(
+    template: &Template,
+    name: &str,
+    title: &str,
+    style: LinkStyle,
+    mut fields: Vec<(String, Json)>,
+) -> io::Result<String> {
+    fields.push((
+        "frontmatter".to_string(),
+        style.frontmatter(title, name).into(),
+    ));
+    fields.push(("list_marker".to_string(), style.list_marker().into()));
+    template.render(&Json::Object(fields)).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            i18n::trf("模板 {}.md 渲染失败，{}", &[&name, &e]),
+        )
+    })
+}
+
+// 把生成的正文放在标记之间；existing 中有开始标记时保留标记之外手写的内容，文件头取生成的页面
+fn keep_manual(existing: Option<&str>, generated: &str) -> String {
+    let body_start = |content: &str| {
+        frontmatter::split(content).map_or(0, |(_, body)| content.len() - body.len())
+    };
+    let (head, body) = generated.split_at(body_start(generated));
+    // 文件头之后的空行放在开始标记之前
+    let (mut before, body) =
+        body.split_at(body.len() - body.trim_start_matches(['\r', '\n']).len());
+    let mut after = "";
+    if let Some(existing) = existing
+        && let Some(begin) = existing.find(MANUAL_BEGIN)
+        && begin >= body_start(existing)
+    {
+        before = &existing[body_start(existing)..begin];
+        // 没有结束标记时开始标记之后的内容都是生成的
+        let rest = &existing[begin + MANUAL_BEGIN.len()..];
+        if let Some(end) = rest.find(MANUAL_END) {
+            let rest = &rest[end + MANUAL_END.len()..];
+            after = rest
+                .strip_prefix("\r\n")
+                .or_else(|| rest.strip_prefix('\n'))
+                .unwrap_or(rest);
+        }
+    }
+    let mut page = format!("{}{}", head, before);
+    page.push_str(MANUAL_BEGIN);
+    page.push('\n');
+    page.push_str(body);
+    if !body.ends_with('\n') {
+        page.push('\n');
+    }
+    page.push_str(MANUAL_END);
+    page.push('\n');
+    page.push_str(after);
+    page
+}
+
+/// 先写入同目录下的临时文件，再重命名覆盖 path，中途出错时 path 保持原样
+///
+/// 演练模式下只记录将要进行的写入，见 `plan`
+pub fn write_atomic(path: &Path, content: &str) -> io::Result<()> {
+    if plan::enabled() {
+        plan::record_write(path, content);
+        return Ok(());
+    }
+    let mut tmp_name = path.file_name().unwrap().to_os_string();
+    tmp_name.push(".gtx-tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+    fs::write(&tmp_path, content)?;
+    if let Err(e) = fs::rename(&tmp_path, path) {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// 在 parent 下新建以当前时间（秒与纳秒）命名的目录，已存在时加序号；
+/// 用 create_dir 创建，同一时刻的两次操作不会共用一个目录
+pub fn unique_dir(parent: &Path) -> io::Result<PathBuf> {
+    fs::create_dir_all(parent)?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    let stamp = format!("{}-{:09}", now.as_secs(), now.subsec_nanos());
+    let mut dir = parent.join(&stamp);
+    let mut i = 0;
+    loop {
+        match fs::create_dir(&dir) {
+            Ok(()) => return Ok(dir),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                i += 1;
+                dir = parent.join(format!("{}-{}", stamp, i));
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+// 一次生成写入的页面；所有页面都经由这里写入，dir 下已有同名的笔记等用户文件时不覆盖
+struct Pages<'a> {
+    dir: &'a Path,
+    previous: Option<&'a HashSet<String>>,
+    /// 本次生成的页面，为相对 dir 的路径
+    generated: HashSet<String>,
+}
+
+impl<'a> Pages<'a> {
+    fn new(dir: &'a Path, previous: Option<&'a HashSet<String>>) -> Pages<'a> {
+        Pages {
+            dir,
+            previous,
+            generated: HashSet::new(),
+        }
+    }
+
+    // 把 page 记为生成的页面；该位置是用户文件时报告冲突并返回 false，
+    // 不记为生成页面，以免之后被当作旧页面清理
+    fn claim(&mut self, page: &str) -> bool {
+        if self.generated.contains(page) {
+            return true;
+        }
+        let path = self.dir.join(page);
+        if is_user_file(&path, self.previous.is_some_and(|p| p.contains(page))) {
+            eprintln!(
+                "{}",
+                i18n::trf("警告: {} 不是生成的页面，未覆盖", &[&path.display()])
+            );
+            return false;
+        }
+        self.generated.insert(page.to_string());
+        true
+    }
+
+    // 写入页面 page，与用户文件冲突时跳过
+    fn write(&mut self, page: &str, content: &str) -> io::Result<()> {
+        if self.claim(page) {
+            write_atomic(&self.dir.join(page), content)?;
+        }
+        Ok(())
+    }
+}
+
+/// 在 dir 下写入单独生成的页面 page（相对 dir 的路径），previous 同 `WriteOptions::previous`；
+/// 该位置已有用户文件时报告冲突、不写入并返回 false
+pub fn write_page(
+    dir: &Path,
+    page: &str,
+    previous: Option<&HashSet<String>>,
+    content: &str,
+) -> io::Result<bool> {
+    let mut pages = Pages::new(dir, previous);
+    pages.write(page, content)?;
+    Ok(pages.generated.contains(page))
+}
+
+// path 处已有文件且不是生成的页面（如与标签同名的笔记），这样的文件不能覆盖或清理；
+// previous 为 true 表示缓存记录了上次在这里生成过页面，较早版本生成的页面文件头中没有 generator
+fn is_user_file(path: &Path, previous: bool) -> bool {
+    match fs::read_to_string(path) {
+        Ok(_) if previous => false,
+        Ok(content) => !matches!(
+            frontmatter::parse(&content),
+            Ok(Some((header, _))) if is_generated(&header)
+        ),
+        Err(e) => e.kind() != io::ErrorKind::NotFound,
+    }
+}
+
+// 位于 dir 下 depth 层子目录中的页面指向笔记的链接前缀
+// 维基链接在没有前缀时按文件名解析，不受目录影响；Markdown 与 Org 链接总是按相对路径解析
+fn nested_prefix(style: LinkStyle, link_prefix: &str, depth: usize) -> String {
+    if link_prefix.is_empty() && style.is_wiki() {
+        String::new()
+    } else {
+        format!("{}{}", "../".repeat(depth), link_prefix)
+    }
+}
+
+// 层级标签的最后一级
+fn tag_leaf(tag: &str) -> &str {
+    tag.rsplit('/').next().unwrap_or(tag)
+}
+
+// 看板表格：每个状态一列，表头链接到状态页面；没有状态时为空
+fn status_table(
+    groups: &[(&str, Vec<(&str, &str)>)],
+    style: LinkStyle,
+    link_prefix: &str,
+) -> String {
+    if groups.is_empty() {
+        return String::new();
+    }
+    // 表格单元格中的 | 需要转义，包括 wiki 链接中分隔标题的 |
+    let cell = |text: String| text.replace('|', "\\|");
+    let mut table = String::from("|");
+    for (status, notes) in groups {
+        let link = style.link(
+            "",
+            &format!("{}{}", STATUS_PAGE_PREFIX, status),
+            Some(status),
+        );
+        table.push_str(&format!(" {}({}) |", cell(link), notes.len()));
+    }
+    table.push_str("\n|");
+    table.push_str(&" --- |".repeat(groups.len()));
+    table.push('\n');
+    let rows = groups
+        .iter()
+        .map(|(_, notes)| notes.len())
+        .max()
+        .unwrap_or(0);
+    for row in 0..rows {
+        table.push('|');
+        for (_, notes) in groups {
+            match notes.get(row) {
+                Some((name, title)) => table.push_str(&format!(
+                    " {} |",
+                    cell(style.link(link_prefix, name, Some(title)))
+                )),
+                None => table.push_str(" |"),
+            }
+        }
+        table.push('\n');
+    }
+    table
+}
+
+// 以缩进列表输出 parent 下的标签树，同级标签按笔记数从多到少排列
+fn tag_tree(
+    out: &mut String,
+    style: LinkStyle,
+    tag_dir: &str,
+    sorted: &[(&str, usize)],
+    parent: Option<&str>,
+    depth: usize,
+) {
+    for (tag, count) in sorted {
+        let is_child = match parent {
+            None => !tag.contains('/'),
+            Some(parent) => tag
+                .strip_prefix(parent)
+                .and_then(|rest| rest.strip_prefix('/'))
+                .is_some_and(|rest| !rest.contains('/')),
+        };
+        if !is_child {
+            continue;
+        }
+        out.push_str(&format!(
+            "{}- {}({})\n",
+            "  ".repeat(depth),
+            style.link(
+                "",
+                &tag_page(tag_dir, tag),
+                Some(if depth > 0 { tag_leaf(tag) } else { tag })
+            ),
+            count
+        ));
+        tag_tree(out, style, tag_dir, sorted, Some(tag), depth + 1);
+    }
+}
+
+/// 清理上次生成、本次不再生成的标签页与日期页
+///
+/// 反向链接页面在写入时已按目录内容清理，这里跳过 `BACKLINKS_DIR`
+pub fn remove_stale_pages(
+    dir: &Path,
+    previous: &HashSet<String>,
+    current: &HashSet<String>,
+    cleanup: &Cleanup,
+) -> io::Result<()> {
+    for page in previous {
+        if !current.contains(page) && !page.starts_with(&format!("{}/", BACKLINKS_DIR)) {
+            let page_path = dir.join(page);
+            // 缓存记录了上次在这里生成页面，以缓存为准；默认移到隔离目录，误判时可以找回
+            if page_path.exists() && !is_user_file(&page_path, true) {
+                cleanup.apply(dir, &page_path)?;
+            }
+            // 层级标签页所在的子目录清空后一并删除，非空时 remove_dir 会失败
+            if let Some(parent) = page_path.parent()
+                && parent != dir
+                && !plan::enabled()
+            {
+                let _ = fs::remove_dir(parent);
+            }
+        }
+    }
+    Ok(())
+}
+
+impl IndexSet {
+    // 标签共现页面：经常一起出现的标签可以考虑合并，或整理为层级标签
+    fn tag_graph_page(&self, style: LinkStyle, tag_dir: &str) -> String {
+        let pairs = self.tag_pairs();
+        let mut out = i18n::trf(
+            "{}\n# 标签共现\n",
+            &[&style.frontmatter("tag-graph", "tag-graph")],
+        );
+        if pairs.is_empty() {
+            out.push_str(i18n::tr("没有在同一篇笔记中出现的标签\n"));
+            return out;
+        }
+        out.push_str(i18n::tr(
+            "\n| 标签 | 标签 | 共同笔记 | 提示 |\n| --- | --- | --- | --- |\n",
+        ));
+        for pair in pairs {
+            let (a, b) = pair.tags;
+            let (count_a, count_b) = (self.tags.get_i_count(a), self.tags.get_i_count(b));
+            // 只出现在一篇笔记中的标签总是与其他标签同时出现，不作提示
+            let hint = if pair.count < 2 {
+                String::new()
+            } else if pair.count == count_a && pair.count == count_b {
+                i18n::tr("总是一起出现，可考虑合并").to_string()
+            } else if pair.count == count_a.min(count_b) {
+                let (narrow, broad) = if count_a < count_b { (a, b) } else { (b, a) };
+                i18n::trf(
+                    "{} 只与 {} 一起出现，可考虑改为 {}/{}",
+                    &[&narrow, &broad, &broad, &tag_leaf(narrow)],
+                )
+            } else {
+                String::new()
+            };
+            out.push_str(&format!(
+                "| {} ({}) | {} ({}) | {} | {} |\n",
+                style.link("", &tag_page(tag_dir, a), Some(a)),
+                count_a,
+                style.link("", &tag_page(tag_dir, b), Some(b)),
+                count_b,
+                pair.count,
+                hint
+            ));
+        }
+        out
+    }
+}
+
+impl IndexSet {
+    // 每周回顾页面：先写出本周的起止日期与笔记数，再按标签分组列出笔记，
+    // 笔记多的标签在前；有多个标签的笔记在每个标签下各出现一次
+    fn weekly_page(
+        &self,
+        week: &str,
+        days: &[&str],
+        note_tags: &HashMap<&str, Vec<&str>>,
+        style: LinkStyle,
+        options: &WriteOptions,
+    ) -> String {
+        let note_prefix = nested_prefix(style, options.link_prefix, 1);
+        let tag_prefix = nested_prefix(style, "", 1);
+
+        let mut groups: HashMap<&str, Vec<(String, String, String)>> = HashMap::new();
+        let mut count = 0;
+        for date in days {
+            for (name, title, time) in date_files(&self.dates, date, options.time_format) {
+                count += 1;
+                let when = format!("{} {}", date, time);
+                for tag in note_tags.get(name.as_str()).into_iter().flatten() {
+                    groups.entry(tag).or_default().push((
+                        name.clone(),
+                        title.clone(),
+                        when.trim_end().to_string(),
+                    ));
+                }
+            }
+        }
+        let mut groups: Vec<_> = groups.into_iter().collect();
+        groups.sort_by(|a, b| b.1.len().cmp(&a.1.len()).then(a.0.cmp(b.0)));
+
+        let (monday, sunday) = match days.first().map(|day| Date::parse(day)) {
+            Some(Ok((date, _))) => {
+                let monday = date.add_days(1 - i64::from(date.weekday()));
+                (monday.to_string(), monday.add_days(6).to_string())
+            }
+            _ => (String::new(), String::new()),
+        };
+        let mut page = i18n::trf(
+            "{}\n{}{} – {}，共 {} 篇笔记\n",
+            &[
+                &style.frontmatter(week, "weekly"),
+                &style.list_marker(),
+                &monday,
+                &sunday,
+                &count,
+            ],
+        );
+        for (tag, notes) in groups {
+            page.push_str(&format!(
+                "## {}({})\n",
+                style.link(&tag_prefix, &tag_page(options.tag_dir, tag), Some(tag)),
+                notes.len()
+            ));
+            for (name, title, when) in notes {
+                page.push_str(&date_link(style, &note_prefix, &name, &title, &when));
+                page.push('\n');
+            }
+        }
+        page
+    }
+
+    // 创建热力图：行为星期一到星期日，列为截至 today 所在周的 HEATMAP_WEEKS 周，
+    // 每格按当天创建的笔记数取色阶，最多的一天为最深
+    fn heatmap_page(&self, style: LinkStyle, today: Date) -> String {
+        let first_monday =
+            today.add_days(1 - i64::from(today.weekday()) - 7 * (HEATMAP_WEEKS as i64 - 1));
+        let count = |date: Date| self.dates.get_i_count(&date.to_string());
+        let days: Vec<Date> = (0..HEATMAP_WEEKS as i64 * 7)
+            .map(|offset| first_monday.add_days(offset))
+            .take_while(|date| *date <= today)
+            .collect();
+        let total: usize = days.iter().map(|date| count(*date)).sum();
+        let max = days.iter().map(|date| count(*date)).max().unwrap_or(0);
+
+        let mut out = i18n::trf(
+            "{}\n# 创建热力图\n\n{} 至 {}，共 {} 篇笔记\n\n",
+            &[
+                &style.frontmatter("heatmap", "heatmap"),
+                &first_monday,
+                &today,
+                &total,
+            ],
+        );
+        // 表头在每月第一次出现的周写出月份，第一列为星期
+        out.push_str("|   |");
+        let mut last_month = 0;
+        for week in 0..HEATMAP_WEEKS {
+            let month = first_monday.add_days(7 * week as i64).month;
+            if month != last_month {
+                out.push_str(&i18n::trf(" {}月 |", &[&month]));
+                last_month = month;
+            } else {
+                out.push_str(" |");
+            }
+        }
+        out.push_str("\n|");
+        out.push_str(&" --- |".repeat(HEATMAP_WEEKS + 1));
+        out.push('\n');
+        for (row, weekday) in i18n::tr("一 二 三 四 五 六 日").split(' ').enumerate() {
+            out.push_str(&format!("| {} |", weekday));
+            for week in 0..HEATMAP_WEEKS {
+                let date = first_monday.add_days(7 * week as i64 + row as i64);
+                if date > today {
+                    out.push_str(" |");
+                    continue;
+                }
+                let level = match count(date) {
+                    0 => 0,
+                    n => (n * (HEATMAP_SHADES.len() - 1)).div_ceil(max),
+                };
+                out.push_str(&format!(" {} |", HEATMAP_SHADES[level]));
+            }
+            out.push('\n');
+        }
+        out.push_str(&i18n::trf("\n少 {} 多\n", &[&HEATMAP_SHADES.join(" ")]));
+        out
+    }
+
+    // 标签云页面：笔记越多的标签标题级别越高，同一级别的标签写在同一行
+    fn az_page(&self, style: LinkStyle, link_prefix: &str, groups: TitleGroups) -> String {
+        let mut out = i18n::trf("{}\n# 标题索引\n", &[&style.frontmatter("a-z", "a-z")]);
+        let mut by_group: BTreeMap<(u32, String), Vec<(&str, &str)>> = BTreeMap::new();
+        for (name, title) in self.titles() {
+            by_group
+                .entry(groups.group(title))
+                .or_default()
+                .push((name, title));
+        }
+        if by_group.is_empty() {
+            out.push_str(i18n::tr("没有笔记\n"));
+        }
+        for ((_, group), notes) in &mut by_group {
+            notes.sort_by_key(|(name, title)| (title.to_lowercase(), *name));
+            out.push_str(&format!("\n## {}\n", group));
+            for (name, title) in notes.iter() {
+                out.push_str(&format!(
+                    "- {}\n",
+                    style.link(link_prefix, name, Some(title))
+                ));
+            }
+        }
+        out
+    }
+
+    // 每篇笔记一行：笔记 → 链接目标，按出现顺序去重，指向同一笔记的链接只列一次；找不到的目标标为失效，
+    // 标签页、日期页等生成的页面不算失效
+    fn links_page(&self, style: LinkStyle, link_prefix: &str) -> String {
+        let links = &self.links;
+        let rollups = date_tree(&self.dates);
+        let mut out = i18n::trf("{}\n# 链接\n", &[&style.frontmatter("links", "links")]);
+        let sources = links.sources();
+        if sources.is_empty() {
+            out.push_str(i18n::tr("没有链接\n"));
+        }
+        for source in sources {
+            let mut seen = HashSet::new();
+            let mut targets = Vec::new();
+            for link in links.outgoing(source) {
+                let resolved = links.resolve(&link.target);
+                if !seen.insert(resolved.unwrap_or(&link.target)) {
+                    continue;
+                }
+                targets.push(match resolved {
+                    Some(name) => style.link(link_prefix, name, links.get_title(name)),
+                    None if is_generated_page(self, &rollups, &link.target) => {
+                        format!("`{}`", link.target)
+                    }
+                    None => i18n::trf("`{}`（失效）", &[&link.target]),
+                });
+            }
+            out.push_str(&format!(
+                "- {} → {}\n",
+                style.link(link_prefix, source, links.get_title(source)),
+                targets.join(", ")
+            ));
+        }
+        out
+    }
+
+    fn tag_cloud_page(&self, style: LinkStyle, tag_dir: &str) -> String {
+        let cloud = self.tag_cloud();
+        let mut out = i18n::trf(
+            "{}\n# 标签云\n",
+            &[&style.frontmatter("tag-cloud", "tag-cloud")],
+        );
+        if cloud.is_empty() {
+            out.push_str(i18n::tr("没有标签\n"));
+            return out;
+        }
+        for weight in (1..=CLOUD_WEIGHTS).rev() {
+            let tags: Vec<String> = cloud
+                .iter()
+                .filter(|tag| tag.weight == weight)
+                .map(|tag| {
+                    let link = style.link("", &tag_page(tag_dir, tag.tag), Some(tag.tag));
+                    format!("{} ({})", link, tag.count)
+                })
+                .collect();
+            if tags.is_empty() {
+                continue;
+            }
+            // 权重最高的一级为二级标题，依次递减到六级标题
+            let level = 2 + CLOUD_WEIGHTS - weight;
+            out.push_str(&format!("\n{} {}\n", "#".repeat(level), tags.join(" · ")));
+        }
+        out
+    }
+}
+
+// 为每篇有反向链接或相关笔记的笔记在 backlinks/ 下生成同名页面，子目录中的笔记的页面位于同样的子目录下
+fn write_backlinks(
+    pages: &mut Pages,
+    links: &LinkIndex,
+    related: &HashMap<&str, Vec<Related>>,
+    cleanup: &Cleanup,
+    style: LinkStyle,
+    link_prefix: &str,
+    needs_write: &dyn Fn(&str, &str) -> bool,
+) -> io::Result<()> {
+    let dir = &pages.dir.join(BACKLINKS_DIR);
+    plan::create_dir_all(dir)?;
+    let backlinks = links.backlinks();
+    let has_page = |name: &str| backlinks.contains_key(name) || related.contains_key(name);
+
+    // 不再有反向链接与相关笔记的旧页面按清理策略处理，用户放在这里的文件保留；
+    // 演练模式下目录可能还不存在
+    let entries = if dir.is_dir() {
+        walk::files(dir, false, &|_| false)?
+    } else {
+        Vec::new()
+    };
+    for entry in entries {
+        let page = format!("{}/{}", BACKLINKS_DIR, entry.relative);
+        let previous = pages.previous.is_some_and(|p| p.contains(&page));
+        if let Some(name) = entry.relative.strip_suffix(".md")
+            && !has_page(name)
+            && !is_user_file(&entry.path, previous)
+        {
+            cleanup.apply(pages.dir, &entry.path)?;
+            // 子目录清空后一并删除，非空时 remove_dir 会失败
+            if let Some(parent) = entry.path.parent()
+                && parent != dir
+                && !plan::enabled()
+            {
+                let _ = fs::remove_dir(parent);
+            }
+        }
+    }
+
+    let names: HashSet<&str> = backlinks.keys().chain(related.keys()).copied().collect();
+    for name in names {
+        let title = links.get_title(name).unwrap_or(name);
+        let page_name = format!("{}/{}.md", BACKLINKS_DIR, name);
+        if !pages.claim(&page_name)
+            || !needs_write(name, title) && pages.dir.join(&page_name).exists()
+        {
+            continue;
+        }
+        // 页面比输出目录深一层，子目录中的笔记再加上子目录的层数
+        let depth = 1 + name.matches('/').count();
+        let link_prefix = &nested_prefix(style, link_prefix, depth);
+        if depth > 1 {
+            plan::create_dir_all(pages.dir.join(&page_name).parent().unwrap())?;
+        }
+        let mut page = format!(
+            "{}\n{}\n",
+            style.frontmatter(&format!("{} - Backlinks", title), "backlinks"),
+            style.link(link_prefix, name, Some(title))
+        );
+        if let Some(sources) = backlinks.get(name) {
+            page.push_str("\n# Backlinks\n");
+            for link in sources {
+                page.push_str(&format!(
+                    "{} (L{})\n",
+                    style.link(link_prefix, &link.source_name, Some(&link.source_title)),
+                    link.line
+                ));
+            }
+        }
+        if let Some(notes) = related.get(name) {
+            page.push_str("\n# Related\n");
+            for note in notes {
+                page.push_str(&format!(
+                    "{} ({})\n",
+                    style.link(link_prefix, note.name, Some(note.title)),
+                    note.shared_tags.join(", ")
+                ));
+            }
+        }
+        pages.write(&page_name, &page)?;
+    }
+
+    Ok(())
+}
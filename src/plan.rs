@@ -0,0 +1,85 @@
+// 演练模式：照常扫描并生成页面内容，但只记下将要进行的文件操作，不修改文件系统
+//
+// 启用后 `output::write_atomic`、`Cleanup::apply` 与本模块的 `create_dir_all` 只记录操作；
+// 内容与现有文件相同的页面不算作改动。整个进程共用一份记录，由 `gtx index --dry-run` 启用。
+
+use std::fs;
+use std::io;
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// 一项将要进行的文件操作
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    /// 新建文件
+    Create(PathBuf),
+    /// 改写内容不同的已有文件
+    Update(PathBuf),
+    /// 直接删除文件
+    Remove(PathBuf),
+    /// 移动到隔离目录：原路径与目标路径
+    Move(PathBuf, PathBuf),
+}
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static ACTIONS: Mutex<Vec<Action>> = Mutex::new(Vec::new());
+
+/// 启用演练模式，之后的文件操作只记录不执行
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// 是否处于演练模式
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// 记下一项操作
+pub fn record(action: Action) {
+    ACTIONS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .push(action);
+}
+
+/// 取出目前记下的全部操作，按路径排序；同一文件的多项操作保持发生的顺序
+pub fn take() -> Vec<Action> {
+    let mut actions = mem::take(&mut *ACTIONS.lock().unwrap_or_else(|e| e.into_inner()));
+    actions.sort_by(|a, b| a.path().cmp(b.path()));
+    actions.dedup();
+    actions
+}
+
+/// 记下写入 path 的操作：文件不存在或已记下删除时为新建，内容不同时为改写，相同时不记录
+pub fn record_write(path: &Path, content: &str) {
+    let mut actions = ACTIONS.lock().unwrap_or_else(|e| e.into_inner());
+    let removed = actions.iter().any(|action| {
+        matches!(action, Action::Remove(_) | Action::Move(..)) && action.path() == path
+    });
+    match fs::read(path) {
+        Ok(_) if removed => actions.push(Action::Create(path.to_path_buf())),
+        Ok(old) if old == content.as_bytes() => {}
+        Ok(_) => actions.push(Action::Update(path.to_path_buf())),
+        Err(_) => actions.push(Action::Create(path.to_path_buf())),
+    }
+}
+
+/// 演练模式下不创建目录，其余时候同 `fs::create_dir_all`
+pub fn create_dir_all(path: &Path) -> io::Result<()> {
+    if enabled() {
+        return Ok(());
+    }
+    fs::create_dir_all(path)
+}
+
+impl Action {
+    /// 操作的文件，移动时为原路径
+    pub fn path(&self) -> &Path {
+        match self {
+            Action::Create(path) | Action::Update(path) | Action::Remove(path) => path,
+            Action::Move(path, _) => path,
+        }
+    }
+}
@@ -0,0 +1,897 @@
+// 笔记查询：按标签、创建日期与标题筛选笔记
+//
+// 语法:
+//   tag:rust                  含有标签 rust，或其下的层级标签 rust/...
+//   created:2024-05           创建于 2024 年 5 月，也可写年份或完整日期；date: 与 created: 相同
+//   created:>=2024-01-01      比较创建日期，支持 >=、<=、>、<、=，没有创建时间的笔记不匹配
+//   title:异步 或 异步        标题包含该文本，忽略大小写
+//   "a b"                     含空白的值加双引号，如 title:"rust 入门"
+//   AND、OR、NOT、( )         组合条件，相邻的条件之间默认为 AND；优先级 NOT > AND > OR
+//
+// 保存的查询写入输出目录下的 queries/<名称>.md，文件头的 Query 字段记录查询，
+// 每次构建索引时按当前的笔记重新生成。
+//
+// 笔记中也可以写查询块，查询之外可加 `sort:created|updated|title|name [asc|desc]` 与 `limit:N`：
+//
+//   ```gtx
+//   tag:rust sort:created desc limit:10
+//   ```
+//
+// 构建索引时查询结果写在查询块之后的 `<!-- gtx:begin -->` 与 `<!-- gtx:end -->` 之间，每次按当前的
+// 笔记重新生成；结果中的链接不计入笔记的链接与字数。导出 html 时查询块直接换成结果列表。
+
+use std::borrow::Cow;
+use std::fmt;
+use std::fmt::Write;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::date::Date;
+use crate::frontmatter;
+use crate::note::NoteMeta;
+use crate::output::{GENERATOR_KEY, LinkStyle, MANUAL_BEGIN, MANUAL_END, write_atomic};
+
+/// 保存的查询页面所在的子目录，相对输出目录
+pub const QUERIES_DIR: &str = "queries";
+
+/// 查询解析失败
+#[derive(Debug, Clone, PartialEq)]
+pub struct Error {
+    /// 出错位置（字符偏移）
+    pub offset: usize,
+    pub message: String,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "查询第 {} 个字符处: {}", self.offset, self.message)
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// 解析后的查询
+#[derive(Debug, Clone)]
+pub struct Query {
+    expr: Expr,
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Tag(String),
+    /// 日期键为 YYYY、YYYYMM 或 YYYYMMDD，与笔记日期的同长前缀比较
+    Created(Compare, String),
+    /// 已转为小写
+    Title(String),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Compare {
+    Eq,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Open,
+    Close,
+    And,
+    Or,
+    Not,
+    Word(String),
+}
+
+impl Query {
+    pub fn parse(text: &str) -> Result<Query, Error> {
+        let tokens = tokenize(text)?;
+        if tokens.is_empty() {
+            return Err(Error {
+                offset: 0,
+                message: "查询为空".to_string(),
+            });
+        }
+        let mut parser = Parser {
+            tokens: &tokens,
+            pos: 0,
+            end: text.chars().count(),
+        };
+        let expr = parser.or()?;
+        if let Some((token, offset)) = parser.tokens.get(parser.pos) {
+            let message = match token {
+                Token::Close => "多余的 ')'".to_string(),
+                _ => "无法识别的查询".to_string(),
+            };
+            return Err(Error {
+                offset: *offset,
+                message,
+            });
+        }
+        Ok(Query { expr })
+    }
+
+    /// 笔记是否满足查询
+    pub fn matches(&self, meta: &NoteMeta) -> bool {
+        self.expr.matches(meta)
+    }
+
+    /// 满足查询的笔记，按创建时间与文件名排序，没有创建时间的排在最后
+    pub fn filter<'a>(&self, notes: impl Iterator<Item = &'a NoteMeta>) -> Vec<&'a NoteMeta> {
+        let mut matched: Vec<&NoteMeta> = notes.filter(|meta| self.matches(meta)).collect();
+        matched.sort_by(|a, b| {
+            let key = |meta: &NoteMeta| (meta.created.is_none(), meta.created.clone());
+            key(a).cmp(&key(b)).then_with(|| a.name.cmp(&b.name))
+        });
+        matched
+    }
+}
+
+impl Expr {
+    fn matches(&self, meta: &NoteMeta) -> bool {
+        match self {
+            Expr::Tag(tag) => meta.tags.iter().any(|t| {
+                t == tag
+                    || t.strip_prefix(tag.as_str())
+                        .is_some_and(|rest| rest.starts_with('/'))
+            }),
+            Expr::Created(compare, key) => {
+                let Some((date, _)) = &meta.created else {
+                    return false;
+                };
+                let date = date.to_string();
+                let prefix = &date[..key.len()];
+                match compare {
+                    Compare::Eq => prefix == key,
+                    Compare::Lt => prefix < key.as_str(),
+                    Compare::Le => prefix <= key.as_str(),
+                    Compare::Gt => prefix > key.as_str(),
+                    Compare::Ge => prefix >= key.as_str(),
+                }
+            }
+            Expr::Title(text) => meta.title.to_lowercase().contains(text),
+            Expr::Not(inner) => !inner.matches(meta),
+            Expr::And(a, b) => a.matches(meta) && b.matches(meta),
+            Expr::Or(a, b) => a.matches(meta) || b.matches(meta),
+        }
+    }
+}
+
+// 拆分为括号、运算符与词，附带字符偏移；加引号的 AND、OR、NOT 是普通的词
+fn tokenize(text: &str) -> Result<Vec<(Token, usize)>, Error> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().enumerate().peekable();
+    while let Some(&(offset, c)) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' | ')' => {
+                chars.next();
+                let token = if c == '(' { Token::Open } else { Token::Close };
+                tokens.push((token, offset));
+            }
+            _ => {
+                let mut word = String::new();
+                let mut quoted = false;
+                while let Some(&(_, c)) = chars.peek() {
+                    if c.is_whitespace() || c == '(' || c == ')' {
+                        break;
+                    }
+                    chars.next();
+                    if c != '"' {
+                        word.push(c);
+                        continue;
+                    }
+                    quoted = true;
+                    loop {
+                        match chars.next() {
+                            Some((_, '"')) => break,
+                            Some((_, c)) => word.push(c),
+                            None => {
+                                return Err(Error {
+                                    offset,
+                                    message: "未闭合的双引号".to_string(),
+                                });
+                            }
+                        }
+                    }
+                }
+                let token = match word.as_str() {
+                    "AND" if !quoted => Token::And,
+                    "OR" if !quoted => Token::Or,
+                    "NOT" if !quoted => Token::Not,
+                    _ => Token::Word(word),
+                };
+                tokens.push((token, offset));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: &'a [(Token, usize)],
+    pos: usize,
+    // 查询的字符数，用于报告末尾的错误
+    end: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(token, _)| token)
+    }
+
+    fn offset(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .map_or(self.end, |(_, offset)| *offset)
+    }
+
+    fn or(&mut self) -> Result<Expr, Error> {
+        let mut expr = self.and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    // 相邻的条件之间省略 AND
+    fn and(&mut self) -> Result<Expr, Error> {
+        let mut expr = self.not()?;
+        loop {
+            match self.peek() {
+                Some(Token::And) => self.pos += 1,
+                Some(Token::Open | Token::Not | Token::Word(_)) => {}
+                _ => return Ok(expr),
+            }
+            expr = Expr::And(Box::new(expr), Box::new(self.not()?));
+        }
+    }
+
+    fn not(&mut self) -> Result<Expr, Error> {
+        if self.peek() == Some(&Token::Not) {
+            self.pos += 1;
+            return Ok(Expr::Not(Box::new(self.not()?)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr, Error> {
+        let offset = self.offset();
+        let error = |message: &str| Error {
+            offset,
+            message: message.to_string(),
+        };
+        match self.tokens.get(self.pos) {
+            Some((Token::Open, _)) => {
+                self.pos += 1;
+                let expr = self.or()?;
+                if self.peek() != Some(&Token::Close) {
+                    return Err(Error {
+                        offset: self.offset(),
+                        message: format!("缺少与第 {} 个字符处的 '(' 对应的 ')'", offset),
+                    });
+                }
+                self.pos += 1;
+                Ok(expr)
+            }
+            Some((Token::Word(word), _)) => {
+                self.pos += 1;
+                term(word).map_err(|message| error(&message))
+            }
+            Some((Token::Close, _)) => Err(error("多余的 ')'")),
+            Some(_) => Err(error("运算符前后缺少条件")),
+            None => Err(error("查询不完整，末尾缺少条件")),
+        }
+    }
+}
+
+fn term(word: &str) -> Result<Expr, String> {
+    let Some((field, value)) = word.split_once(':') else {
+        return Ok(Expr::Title(word.to_lowercase()));
+    };
+    if value.is_empty() && matches!(field, "tag" | "created" | "date" | "title") {
+        return Err(format!("{}: 后缺少值", field));
+    }
+    match field {
+        "tag" => Ok(Expr::Tag(value.trim_start_matches('#').to_string())),
+        "created" | "date" => {
+            let (compare, value) = [
+                (">=", Compare::Ge),
+                ("<=", Compare::Le),
+                (">", Compare::Gt),
+                ("<", Compare::Lt),
+                ("=", Compare::Eq),
+            ]
+            .into_iter()
+            .find_map(|(op, compare)| value.strip_prefix(op).map(|rest| (compare, rest)))
+            .unwrap_or((Compare::Eq, value));
+            Ok(Expr::Created(compare, date_key(value)?))
+        }
+        "title" => Ok(Expr::Title(value.to_lowercase())),
+        _ => Err(format!(
+            "未知的字段 '{}'，可用的字段为 tag、created、date、title",
+            field
+        )),
+    }
+}
+
+// 完整日期转为 YYYYMMDD，年份与年月转为 YYYY、YYYYMM
+fn date_key(value: &str) -> Result<String, String> {
+    if let Ok((date, _)) = Date::parse(value) {
+        return Ok(date.to_string());
+    }
+    let is_year = |s: &str| s.len() == 4 && s.chars().all(|c| c.is_ascii_digit());
+    let month = |s: &str| {
+        (1..=2)
+            .contains(&s.len())
+            .then(|| s.parse::<u8>().ok())
+            .flatten()
+            .filter(|m| (1..=12).contains(m))
+    };
+    let (year, rest) = match value.split_once(['-', '/']) {
+        Some((year, rest)) => (year, Some(rest)),
+        None if value.len() == 6 && value.is_char_boundary(4) => (&value[..4], Some(&value[4..])),
+        None => (value, None),
+    };
+    match rest {
+        _ if !is_year(year) => {}
+        None => return Ok(year.to_string()),
+        Some(rest) => {
+            if let Some(month) = month(rest) {
+                return Ok(format!("{}{:02}", year, month));
+            }
+        }
+    }
+    Err(format!(
+        "无法识别的日期 '{}'，应为年份、年月或完整日期，如 2024、2024-05、2024-05-01",
+        value
+    ))
+}
+
+/// 保存的查询页面的内容，link_prefix 含义同 `WriteOptions`
+pub fn saved_page(
+    name: &str,
+    query: &str,
+    notes: &[&NoteMeta],
+    style: LinkStyle,
+    link_prefix: &str,
+) -> String {
+    let mut out = format!(
+        "---\nTitle: {}\nQuery: '{}'\n{}: gtx\n---\n\n# {}\n查询: `{}`，共 {} 篇\n\n",
+        name,
+        query.replace('\'', "''"),
+        GENERATOR_KEY,
+        name,
+        query,
+        notes.len()
+    );
+    for meta in notes {
+        let link = style.link(link_prefix, &meta.name, Some(&meta.title));
+        match &meta.created {
+            Some((date, _)) => {
+                let _ = writeln!(out, "{} ({})", link, date);
+            }
+            None => {
+                let _ = writeln!(out, "{}", link);
+            }
+        }
+    }
+    out
+}
+
+/// 按当前的笔记重新生成保存的查询页面；页面中找不到可用的查询时保持原样并返回 false
+pub fn refresh_saved<'a>(
+    page_path: &Path,
+    notes: impl Iterator<Item = &'a NoteMeta>,
+    style: LinkStyle,
+    link_prefix: &str,
+) -> io::Result<bool> {
+    let content = fs::read_to_string(page_path)?;
+    let Ok(Some((header, _))) = frontmatter::parse(&content) else {
+        return Ok(false);
+    };
+    let (Some(name), Some(text)) = (header.get_str("Title"), header.get_str("Query")) else {
+        return Ok(false);
+    };
+    let Ok(query) = Query::parse(text) else {
+        return Ok(false);
+    };
+    let page = saved_page(name, text, &query.filter(notes), style, link_prefix);
+    if page != content {
+        write_atomic(page_path, &page)?;
+    }
+    Ok(true)
+}
+
+/// 笔记中查询块的语言标记
+pub const BLOCK_LANG: &str = "gtx";
+
+/// 查询块中的查询：查询条件加上排序与数量
+#[derive(Debug, Clone)]
+pub struct BlockQuery {
+    /// 只有排序与数量时为 None，匹配全部笔记
+    query: Option<Query>,
+    sort: SortKey,
+    descending: bool,
+    limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SortKey {
+    Created,
+    /// Updated 字段，没有时取创建时间
+    Updated,
+    Title,
+    Name,
+}
+
+impl BlockQuery {
+    /// 解析查询块的内容；默认按创建时间从早到晚排列，不限数量
+    pub fn parse(text: &str) -> Result<BlockQuery, Error> {
+        let mut sort = SortKey::Created;
+        let mut descending = false;
+        let mut limit = None;
+        // 选项所在的范围，换成空格后其余部分作为查询，出错位置不变
+        let mut options = Vec::new();
+        let words = words(text);
+        let mut i = 0;
+        while i < words.len() {
+            let (start, word) = words[i];
+            let lower = word.to_lowercase();
+            let mut end = start + word.len();
+            let error = |message: String| Error {
+                offset: text[..start].chars().count(),
+                message,
+            };
+            if let Some(key) = lower.strip_prefix("sort:") {
+                sort = match key {
+                    "created" | "date" => SortKey::Created,
+                    "updated" => SortKey::Updated,
+                    "title" => SortKey::Title,
+                    "name" => SortKey::Name,
+                    _ => {
+                        return Err(error(format!(
+                            "未知的排序 '{}'，可选: created、updated、title、name",
+                            key
+                        )));
+                    }
+                };
+                descending = false;
+                if let Some(&(next, order)) = words.get(i + 1)
+                    && ["asc", "desc"].contains(&order.to_lowercase().as_str())
+                {
+                    descending = order.eq_ignore_ascii_case("desc");
+                    end = next + order.len();
+                    i += 1;
+                }
+            } else if let Some(value) = lower.strip_prefix("limit:") {
+                match value.parse::<usize>() {
+                    Ok(n) if n > 0 => limit = Some(n),
+                    _ => {
+                        return Err(error(format!("limit 应为正整数，而不是 '{}'", value)));
+                    }
+                }
+            } else {
+                i += 1;
+                continue;
+            }
+            options.push(start..end);
+            i += 1;
+        }
+        let rest: String = text
+            .char_indices()
+            .map(|(i, c)| {
+                if c != '\n' && options.iter().any(|range| range.contains(&i)) {
+                    ' '
+                } else {
+                    c
+                }
+            })
+            .collect();
+        let query = if rest.trim().is_empty() {
+            None
+        } else {
+            Some(Query::parse(&rest)?)
+        };
+        Ok(BlockQuery {
+            query,
+            sort,
+            descending,
+            limit,
+        })
+    }
+
+    /// 满足查询的笔记，按排序取前 limit 篇；排序的字段为空的笔记排在最后，相同时按文件名
+    pub fn run<'a>(&self, notes: impl Iterator<Item = &'a NoteMeta>) -> Vec<&'a NoteMeta> {
+        let mut matched: Vec<&NoteMeta> = match &self.query {
+            Some(query) => notes.filter(|meta| query.matches(meta)).collect(),
+            None => notes.collect(),
+        };
+        matched.sort_by(|a, b| {
+            let order = match self.sort {
+                SortKey::Created => compare_missing_last(&a.created, &b.created, self.descending),
+                SortKey::Updated => compare_missing_last(
+                    &a.updated.as_ref().or(a.created.as_ref()),
+                    &b.updated.as_ref().or(b.created.as_ref()),
+                    self.descending,
+                ),
+                SortKey::Title => {
+                    let order = a.title.to_lowercase().cmp(&b.title.to_lowercase());
+                    if self.descending {
+                        order.reverse()
+                    } else {
+                        order
+                    }
+                }
+                SortKey::Name if self.descending => b.name.cmp(&a.name),
+                SortKey::Name => a.name.cmp(&b.name),
+            };
+            order.then_with(|| a.name.cmp(&b.name))
+        });
+        if let Some(limit) = self.limit {
+            matched.truncate(limit);
+        }
+        matched
+    }
+}
+
+// 比较可能为空的值，空值总在最后
+fn compare_missing_last<T: Ord>(
+    a: &Option<T>,
+    b: &Option<T>,
+    descending: bool,
+) -> std::cmp::Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) if descending => b.cmp(a),
+        (Some(a), Some(b)) => a.cmp(b),
+        _ => a.is_none().cmp(&b.is_none()),
+    }
+}
+
+// 以空白分隔的词及其字节偏移，双引号中的空白不分隔
+fn words(text: &str) -> Vec<(usize, &str)> {
+    let mut words = Vec::new();
+    let mut start = None;
+    let mut quoted = false;
+    for (i, c) in text.char_indices() {
+        if c == '"' {
+            quoted = !quoted;
+        }
+        if c.is_whitespace() && !quoted {
+            if let Some(start) = start.take() {
+                words.push((start, &text[start..i]));
+            }
+        } else if start.is_none() {
+            start = Some(i);
+        }
+    }
+    if let Some(start) = start {
+        words.push((start, &text[start..]));
+    }
+    words
+}
+
+// 正文中的一个查询块，偏移均为字节
+struct Block {
+    /// 开始围栏所在行的起点
+    start: usize,
+    /// 结束围栏所在行之后
+    fence_end: usize,
+    /// 紧随其后的结果区域的结束标记所在行之后，没有结果区域时为 None
+    results_end: Option<usize>,
+    query: String,
+}
+
+// 正文中的查询块；其他围栏代码块中的 ```gtx 不算，没有结束围栏的查询块忽略
+fn blocks(body: &str) -> Vec<Block> {
+    let mut lines = Vec::new();
+    let mut offset = 0;
+    for line in body.split_inclusive('\n') {
+        lines.push((offset, line));
+        offset += line.len();
+    }
+
+    let mut blocks = Vec::new();
+    // 所在的其他代码块的围栏字符
+    let mut fence = None;
+    let mut i = 0;
+    while i < lines.len() {
+        let (start, line) = lines[i];
+        let trimmed = line.trim();
+        i += 1;
+        if let Some(c) = fence {
+            if fence_char(trimmed) == Some(c) && trimmed.trim_start_matches(c).is_empty() {
+                fence = None;
+            }
+            continue;
+        }
+        let Some(c) = fence_char(trimmed) else {
+            continue;
+        };
+        let info = trimmed.trim_start_matches(c).trim();
+        let close = (c == '`' && info.split_whitespace().next() == Some(BLOCK_LANG))
+            .then(|| lines[i..].iter().position(|(_, line)| line.trim() == "```"))
+            .flatten();
+        let Some(close) = close else {
+            fence = Some(c);
+            continue;
+        };
+        let query_lines: Vec<&str> = lines[i..i + close]
+            .iter()
+            .map(|(_, line)| line.trim_end_matches(['\n', '\r']))
+            .collect();
+        i += close + 1;
+        let fence_end = lines.get(i).map_or(body.len(), |(offset, _)| *offset);
+        let mut results_end = None;
+        if lines
+            .get(i)
+            .is_some_and(|(_, line)| line.trim() == MANUAL_BEGIN)
+            && let Some(end) = lines[i..]
+                .iter()
+                .position(|(_, line)| line.trim() == MANUAL_END)
+        {
+            i += end + 1;
+            results_end = Some(lines.get(i).map_or(body.len(), |(offset, _)| *offset));
+        }
+        blocks.push(Block {
+            start,
+            fence_end,
+            results_end,
+            query: query_lines.join("\n"),
+        });
+    }
+    blocks
+}
+
+// 围栏代码块开头的字符，``` 或 ~~~
+fn fence_char(line: &str) -> Option<char> {
+    ['`', '~']
+        .into_iter()
+        .find(|&c| line.chars().take(3).filter(|&d| d == c).count() == 3)
+}
+
+// 查询块的结果，每篇笔记一行列表项，带创建日期；查询无效时为错误说明
+fn block_results(query: &str, notes: &[&NoteMeta], style: LinkStyle) -> String {
+    let query = match BlockQuery::parse(query) {
+        Ok(query) => query,
+        Err(e) => return format!("查询无效: {}\n", e),
+    };
+    let matched = query.run(notes.iter().copied());
+    if matched.is_empty() {
+        return "没有符合查询的笔记\n".to_string();
+    }
+    let mut out = String::new();
+    for meta in matched {
+        let link = style.link("", &meta.name, Some(&meta.title));
+        match &meta.created {
+            Some((date, _)) => {
+                let _ = writeln!(out, "- {} ({})", link, date);
+            }
+            None => {
+                let _ = writeln!(out, "- {}", link);
+            }
+        }
+    }
+    out
+}
+
+/// 正文中是否有查询块
+pub fn has_blocks(body: &str) -> bool {
+    !blocks(body).is_empty()
+}
+
+/// 把查询块之后的结果区域换成空行，行数不变，用于提取链接、待办与统计字数
+pub fn strip_results(body: &str) -> Cow<'_, str> {
+    let blocks = blocks(body);
+    if blocks.iter().all(|block| block.results_end.is_none()) {
+        return Cow::Borrowed(body);
+    }
+    let mut out = String::with_capacity(body.len());
+    let mut last = 0;
+    for block in &blocks {
+        let Some(end) = block.results_end else {
+            continue;
+        };
+        out.push_str(&body[last..block.fence_end]);
+        out.extend(body[block.fence_end..end].matches('\n').map(|_| '\n'));
+        last = end;
+    }
+    out.push_str(&body[last..]);
+    Cow::Owned(out)
+}
+
+/// 把查询块连同其后的结果区域换成空行，行数不变，用于 gtx search：只搜索笔记自身的文字
+pub fn blank_blocks(text: &str) -> Cow<'_, str> {
+    let blocks = blocks(text);
+    if blocks.is_empty() {
+        return Cow::Borrowed(text);
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut last = 0;
+    for block in &blocks {
+        let end = block.results_end.unwrap_or(block.fence_end);
+        out.push_str(&text[last..block.start]);
+        out.extend(text[block.start..end].matches('\n').map(|_| '\n'));
+        last = end;
+    }
+    out.push_str(&text[last..]);
+    Cow::Owned(out)
+}
+
+/// 按当前的笔记重新生成笔记中每个查询块之后的结果区域；内容没有变化时不写入，返回是否写入
+pub fn refresh_blocks(note_path: &Path, notes: &[&NoteMeta], style: LinkStyle) -> io::Result<bool> {
+    let content = fs::read_to_string(note_path)?;
+    let body_start = frontmatter::split(&content).map_or(0, |(_, body)| content.len() - body.len());
+    let mut out = String::with_capacity(content.len());
+    let mut last = 0;
+    for block in blocks(&content[body_start..]) {
+        let fence_end = body_start + block.fence_end;
+        out.push_str(&content[last..fence_end]);
+        if !out.ends_with('\n') {
+            out.push('\n');
+        }
+        out.push_str(MANUAL_BEGIN);
+        out.push('\n');
+        out.push_str(&block_results(&block.query, notes, style));
+        out.push_str(MANUAL_END);
+        out.push('\n');
+        last = block.results_end.map_or(fence_end, |end| body_start + end);
+    }
+    out.push_str(&content[last..]);
+    if out == content {
+        return Ok(false);
+    }
+    write_atomic(note_path, &out)?;
+    Ok(true)
+}
+
+/// 把正文中的查询块连同其后的结果区域换成结果列表，链接为维基链接，用于导出 html
+pub fn expand_blocks<'b>(body: &'b str, notes: &[&NoteMeta]) -> Cow<'b, str> {
+    let blocks = blocks(body);
+    if blocks.is_empty() {
+        return Cow::Borrowed(body);
+    }
+    let mut out = String::with_capacity(body.len());
+    let mut last = 0;
+    for block in &blocks {
+        // 前后加空行，结果列表不与相邻的段落相连
+        out.push_str(&body[last..block.start]);
+        out.push('\n');
+        out.push_str(&block_results(&block.query, notes, LinkStyle::Wiki));
+        out.push('\n');
+        last = block.results_end.unwrap_or(block.fence_end);
+    }
+    out.push_str(&body[last..]);
+    Cow::Owned(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(name: &str, tags: &[&str], created: Option<&str>) -> NoteMeta {
+        NoteMeta {
+            name: name.to_string(),
+            title: name.to_string(),
+            created: created.map(|text| (Date::parse(text).unwrap().0, String::new())),
+            invalid_created: None,
+            updated: None,
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            aliases: Vec::new(),
+            id: None,
+            links: Vec::new(),
+            embeds: Vec::new(),
+            tasks: Vec::new(),
+            draft: false,
+            pinned: false,
+            status: None,
+            words: 0,
+            summary: None,
+            review: None,
+            queries: false,
+        }
+    }
+
+    // 满足查询的笔记名
+    fn names(query: &str, notes: &[NoteMeta]) -> Vec<String> {
+        Query::parse(query)
+            .unwrap()
+            .filter(notes.iter())
+            .into_iter()
+            .map(|meta| meta.name.clone())
+            .collect()
+    }
+
+    fn tagged() -> Vec<NoteMeta> {
+        vec![
+            note("a", &["a"], None),
+            note("b", &["b"], None),
+            note("bc", &["b", "c"], None),
+            note("ac", &["a", "c"], None),
+        ]
+    }
+
+    #[test]
+    fn not_binds_tighter_than_and_than_or() {
+        let notes = tagged();
+        assert_eq!(names("tag:a OR tag:b tag:c", &notes), ["a", "ac", "bc"]);
+        assert_eq!(names("tag:a OR tag:b AND tag:c", &notes), ["a", "ac", "bc"]);
+        assert_eq!(names("(tag:a OR tag:b) tag:c", &notes), ["ac", "bc"]);
+        assert_eq!(names("NOT tag:a tag:c", &notes), ["bc"]);
+        assert_eq!(names("NOT (tag:a tag:c)", &notes), ["a", "b", "bc"]);
+        assert_eq!(names("NOT NOT tag:a", &notes), ["a", "ac"]);
+        assert_eq!(names("tag:c OR NOT tag:b", &notes), ["a", "ac", "bc"]);
+    }
+
+    #[test]
+    fn tags_match_their_subtags() {
+        let notes = vec![
+            note("rust", &["rust"], None),
+            note("async", &["rust/async"], None),
+            note("rustc", &["rustc"], None),
+        ];
+        assert_eq!(names("tag:#rust", &notes), ["async", "rust"]);
+        assert_eq!(names("tag:rust/async", &notes), ["async"]);
+    }
+
+    #[test]
+    fn quoted_values() {
+        let mut notes = vec![note("a", &[], None), note("b", &[], None)];
+        notes[0].title = "Rust 入门 (上)".to_string();
+        notes[1].title = "OR".to_string();
+        assert_eq!(names("title:\"rust 入门 (上)\"", &notes), ["a"]);
+        assert_eq!(names("\"rust 入门\"", &notes), ["a"]);
+        assert_eq!(names("\"OR\"", &notes), ["b"]);
+        assert_eq!(names("title:\"OR\" OR title:入门", &notes), ["a", "b"]);
+    }
+
+    #[test]
+    fn created_compares_partial_dates() {
+        let notes = vec![
+            note("2022", &[], Some("20221231")),
+            note("2023", &[], Some("20230101")),
+            note("2024-04", &[], Some("20240430")),
+            note("2024-05", &[], Some("20240501")),
+            note("2024-05-31", &[], Some("20240531")),
+            note("none", &[], None),
+        ];
+        assert_eq!(names("created:<2023", &notes), ["2022"]);
+        assert_eq!(names("created:<=2023", &notes), ["2022", "2023"]);
+        assert_eq!(names("created:2024-05", &notes), ["2024-05", "2024-05-31"]);
+        assert_eq!(names("created:202405", &notes), ["2024-05", "2024-05-31"]);
+        assert_eq!(names("date:2024/5", &notes), ["2024-05", "2024-05-31"]);
+        assert_eq!(names("created:>2024-04", &notes), ["2024-05", "2024-05-31"]);
+        assert_eq!(
+            names("created:>=2024-04-30", &notes),
+            ["2024-04", "2024-05", "2024-05-31"]
+        );
+        assert_eq!(names("created:=20230101", &notes), ["2023"]);
+        assert_eq!(names("NOT created:>=2023", &notes), ["2022", "none"]);
+    }
+
+    #[test]
+    fn errors_point_at_the_offending_character() {
+        let error = |query: &str| {
+            let error = Query::parse(query).unwrap_err();
+            (error.offset, error.message)
+        };
+        assert_eq!(error("").0, 0);
+        assert_eq!(error("(tag:a OR tag:b").0, 15);
+        assert_eq!(error("tag:a)").0, 5);
+        assert_eq!(error("tag:a OR").0, 8);
+        assert_eq!(error("tag:a AND OR tag:b").0, 10);
+        assert_eq!(error("title:\"rust").0, 0);
+        assert_eq!(error("x tag:").0, 2);
+        assert_eq!(error("created:2024-13").0, 0);
+        assert_eq!(error("foo:bar").0, 0);
+        assert_eq!(error("(tag:a").1, "缺少与第 0 个字符处的 '(' 对应的 ')'");
+    }
+}
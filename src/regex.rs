@@ -0,0 +1,600 @@
+// 用于搜索的小型正则表达式引擎
+//
+// 支持: 字面量、`.`、`[...]`/`[^...]`、`\d \w \s \D \W \S` 与转义、`^`、`$`、
+// 分组 `( )`/`(?: )`、选择 `|`、量词 `* + ? {n} {n,} {n,m}` 及其非贪婪形式。
+// 编译为指令序列后用带记忆的回溯执行，最坏情况为 O(模式长度 × 文本长度)。
+
+use std::fmt;
+
+#[derive(Debug)]
+pub struct Error {
+    /// 出错位置（字符偏移）
+    pub offset: usize,
+    pub message: &'static str,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "正则表达式第 {} 个字符处: {}", self.offset, self.message)
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[derive(Debug, Clone)]
+enum ClassItem {
+    Range(char, char),
+    Digit(bool),
+    Word(bool),
+    Space(bool),
+}
+
+impl ClassItem {
+    fn matches(&self, c: char) -> bool {
+        match *self {
+            ClassItem::Range(lo, hi) => lo <= c && c <= hi,
+            ClassItem::Digit(positive) => c.is_ascii_digit() == positive,
+            ClassItem::Word(positive) => (c.is_alphanumeric() || c == '_') == positive,
+            ClassItem::Space(positive) => c.is_whitespace() == positive,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Char(char),
+    Any,
+    Class(Vec<ClassItem>, bool),
+    Start,
+    End,
+    Group(Vec<Vec<Node>>),
+    Repeat {
+        node: Box<Node>,
+        min: usize,
+        max: Option<usize>,
+        greedy: bool,
+    },
+}
+
+#[derive(Debug, Clone)]
+enum Inst {
+    Char(char),
+    Any,
+    Class(Vec<ClassItem>, bool),
+    Start,
+    End,
+    // 优先尝试第一个分支
+    Split(usize, usize),
+    Jump(usize),
+    Match,
+}
+
+/// 编译后的正则表达式
+#[derive(Debug, Clone)]
+pub struct Regex {
+    program: Vec<Inst>,
+    ignore_case: bool,
+}
+
+impl Regex {
+    pub fn new(pattern: &str) -> Result<Regex, Error> {
+        Regex::with_case(pattern, false)
+    }
+
+    /// ignore_case 为 true 时忽略大小写
+    pub fn with_case(pattern: &str, ignore_case: bool) -> Result<Regex, Error> {
+        let mut parser = Parser {
+            chars: pattern.chars().collect(),
+            pos: 0,
+        };
+        let alternatives = parser.alternation()?;
+        if parser.pos < parser.chars.len() {
+            return Err(parser.error("多余的 ')'"));
+        }
+
+        let mut program = Vec::new();
+        compile_alternation(&alternatives, &mut program);
+        program.push(Inst::Match);
+        Ok(Regex {
+            program,
+            ignore_case,
+        })
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        self.find(text).is_some()
+    }
+
+    /// 返回最左侧匹配的字节范围
+    pub fn find(&self, text: &str) -> Option<(usize, usize)> {
+        let chars: Vec<(usize, char)> = text.char_indices().collect();
+        let byte_at = |i: usize| chars.get(i).map_or(text.len(), |&(b, _)| b);
+        let mut visited = vec![false; self.program.len() * (chars.len() + 1)];
+
+        for start in 0..=chars.len() {
+            if let Some(end) = self.run(&chars, start, &mut visited) {
+                return Some((byte_at(start), byte_at(end)));
+            }
+        }
+        None
+    }
+
+    fn char_eq(&self, expected: char, actual: char) -> bool {
+        expected == actual
+            || (self.ignore_case && expected.to_lowercase().eq(actual.to_lowercase()))
+    }
+
+    fn class_matches(&self, items: &[ClassItem], negated: bool, c: char) -> bool {
+        let hit = |c: char| items.iter().any(|item| item.matches(c));
+        let mut matched = hit(c);
+        if !matched && self.ignore_case {
+            matched = c.to_lowercase().any(hit) || c.to_uppercase().any(hit);
+        }
+        matched != negated
+    }
+
+    // 从 start 开始回溯执行；visited 记录已失败的 (指令, 位置)，跨起点复用
+    fn run(&self, chars: &[(usize, char)], start: usize, visited: &mut [bool]) -> Option<usize> {
+        let width = chars.len() + 1;
+        let mut stack = vec![(0usize, start)];
+
+        while let Some((mut pc, mut pos)) = stack.pop() {
+            loop {
+                let slot = pc * width + pos;
+                if visited[slot] {
+                    break;
+                }
+                visited[slot] = true;
+
+                let current = chars.get(pos).map(|&(_, c)| c);
+                match &self.program[pc] {
+                    Inst::Match => return Some(pos),
+                    Inst::Char(expected) => match current {
+                        Some(c) if self.char_eq(*expected, c) => {
+                            pc += 1;
+                            pos += 1;
+                        }
+                        _ => break,
+                    },
+                    Inst::Any => match current {
+                        Some(c) if c != '\n' => {
+                            pc += 1;
+                            pos += 1;
+                        }
+                        _ => break,
+                    },
+                    Inst::Class(items, negated) => match current {
+                        Some(c) if self.class_matches(items, *negated, c) => {
+                            pc += 1;
+                            pos += 1;
+                        }
+                        _ => break,
+                    },
+                    Inst::Start => {
+                        if pos != 0 {
+                            break;
+                        }
+                        pc += 1;
+                    }
+                    Inst::End => {
+                        if pos != chars.len() {
+                            break;
+                        }
+                        pc += 1;
+                    }
+                    Inst::Jump(target) => pc = *target,
+                    Inst::Split(first, second) => {
+                        stack.push((*second, pos));
+                        pc = *first;
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+fn compile_alternation(alternatives: &[Vec<Node>], program: &mut Vec<Inst>) {
+    if alternatives.len() == 1 {
+        compile_sequence(&alternatives[0], program);
+        return;
+    }
+
+    let mut jumps = Vec::new();
+    for (i, sequence) in alternatives.iter().enumerate() {
+        if i + 1 < alternatives.len() {
+            let split = program.len();
+            program.push(Inst::Split(split + 1, 0));
+            compile_sequence(sequence, program);
+            jumps.push(program.len());
+            program.push(Inst::Jump(0));
+            let next = program.len();
+            program[split] = Inst::Split(split + 1, next);
+        } else {
+            compile_sequence(sequence, program);
+        }
+    }
+    let end = program.len();
+    for jump in jumps {
+        program[jump] = Inst::Jump(end);
+    }
+}
+
+fn compile_sequence(sequence: &[Node], program: &mut Vec<Inst>) {
+    for node in sequence {
+        compile_node(node, program);
+    }
+}
+
+fn compile_node(node: &Node, program: &mut Vec<Inst>) {
+    match node {
+        Node::Char(c) => program.push(Inst::Char(*c)),
+        Node::Any => program.push(Inst::Any),
+        Node::Class(items, negated) => program.push(Inst::Class(items.clone(), *negated)),
+        Node::Start => program.push(Inst::Start),
+        Node::End => program.push(Inst::End),
+        Node::Group(alternatives) => compile_alternation(alternatives, program),
+        Node::Repeat {
+            node,
+            min,
+            max,
+            greedy,
+        } => {
+            for _ in 0..*min {
+                compile_node(node, program);
+            }
+            match max {
+                // 剩余部分为 e*
+                None => {
+                    let start = program.len();
+                    program.push(Inst::Split(0, 0));
+                    compile_node(node, program);
+                    program.push(Inst::Jump(start));
+                    let exit = program.len();
+                    program[start] = repeat_split(*greedy, start + 1, exit);
+                }
+                // 剩余部分为 (max - min) 个 e?
+                Some(max) => {
+                    for _ in *min..*max {
+                        let start = program.len();
+                        program.push(Inst::Split(0, 0));
+                        compile_node(node, program);
+                        let exit = program.len();
+                        program[start] = repeat_split(*greedy, start + 1, exit);
+                    }
+                }
+            }
+        }
+    }
+}
+
+// 贪婪时优先进入循环体，非贪婪时优先跳出
+fn repeat_split(greedy: bool, body: usize, exit: usize) -> Inst {
+    if greedy {
+        Inst::Split(body, exit)
+    } else {
+        Inst::Split(exit, body)
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn error(&self, message: &'static str) -> Error {
+        Error {
+            offset: self.pos,
+            message,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn alternation(&mut self) -> Result<Vec<Vec<Node>>, Error> {
+        let mut alternatives = vec![self.sequence()?];
+        while self.peek() == Some('|') {
+            self.pos += 1;
+            alternatives.push(self.sequence()?);
+        }
+        Ok(alternatives)
+    }
+
+    fn sequence(&mut self) -> Result<Vec<Node>, Error> {
+        let mut sequence = Vec::new();
+        while let Some(c) = self.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            let atom = self.atom()?;
+            sequence.push(self.quantifier(atom)?);
+        }
+        Ok(sequence)
+    }
+
+    fn atom(&mut self) -> Result<Node, Error> {
+        let c = self.peek().ok_or_else(|| self.error("意外的结尾"))?;
+        self.pos += 1;
+        Ok(match c {
+            '.' => Node::Any,
+            '^' => Node::Start,
+            '$' => Node::End,
+            '(' => {
+                // 非捕获分组与普通分组等价
+                if self.chars[self.pos..].starts_with(&['?', ':']) {
+                    self.pos += 2;
+                }
+                let alternatives = self.alternation()?;
+                if self.peek() != Some(')') {
+                    return Err(self.error("缺少 ')'"));
+                }
+                self.pos += 1;
+                Node::Group(alternatives)
+            }
+            '[' => self.class()?,
+            '\\' => self.escape()?,
+            '*' | '+' | '?' => return Err(self.error("量词前缺少内容")),
+            c => Node::Char(c),
+        })
+    }
+
+    fn escape(&mut self) -> Result<Node, Error> {
+        let c = self.peek().ok_or_else(|| self.error("'\\' 后缺少字符"))?;
+        self.pos += 1;
+        Ok(match escape_class(c) {
+            Some(item) => Node::Class(vec![item], false),
+            None => Node::Char(escape_char(c)),
+        })
+    }
+
+    fn class(&mut self) -> Result<Node, Error> {
+        let negated = self.peek() == Some('^');
+        if negated {
+            self.pos += 1;
+        }
+
+        let mut items = Vec::new();
+        let mut first = true;
+        loop {
+            let c = self.peek().ok_or_else(|| self.error("缺少 ']'"))?;
+            self.pos += 1;
+            // 开头的 ']' 视为字面量
+            if c == ']' && !first {
+                break;
+            }
+            first = false;
+
+            let lo = if c == '\\' {
+                let escaped = self.peek().ok_or_else(|| self.error("'\\' 后缺少字符"))?;
+                self.pos += 1;
+                if let Some(item) = escape_class(escaped) {
+                    items.push(item);
+                    continue;
+                }
+                escape_char(escaped)
+            } else {
+                c
+            };
+
+            if self.peek() == Some('-') && self.chars.get(self.pos + 1).is_some_and(|&c| c != ']') {
+                self.pos += 1;
+                let mut hi = self.peek().unwrap();
+                self.pos += 1;
+                if hi == '\\' {
+                    hi = escape_char(self.peek().ok_or_else(|| self.error("'\\' 后缺少字符"))?);
+                    self.pos += 1;
+                }
+                if hi < lo {
+                    return Err(self.error("字符范围顺序错误"));
+                }
+                items.push(ClassItem::Range(lo, hi));
+            } else {
+                items.push(ClassItem::Range(lo, lo));
+            }
+        }
+        Ok(Node::Class(items, negated))
+    }
+
+    fn number(&mut self) -> Option<usize> {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        let digits: String = self.chars[start..self.pos].iter().collect();
+        digits.parse().ok()
+    }
+
+    fn quantifier(&mut self, atom: Node) -> Result<Node, Error> {
+        let (min, max) = match self.peek() {
+            Some('*') => (0, None),
+            Some('+') => (1, None),
+            Some('?') => (0, Some(1)),
+            Some('{') => {
+                let saved = self.pos;
+                self.pos += 1;
+                let Some(min) = self.number() else {
+                    // 不是量词，'{' 按字面量处理
+                    self.pos = saved;
+                    return Ok(atom);
+                };
+                let max = if self.peek() == Some(',') {
+                    self.pos += 1;
+                    self.number()
+                } else {
+                    Some(min)
+                };
+                if self.peek() != Some('}') {
+                    return Err(self.error("缺少 '}'"));
+                }
+                if max.is_some_and(|max| max < min) {
+                    return Err(self.error("量词范围顺序错误"));
+                }
+                (min, max)
+            }
+            _ => return Ok(atom),
+        };
+        self.pos += 1;
+
+        if matches!(atom, Node::Start | Node::End) {
+            return Err(self.error("锚点不能重复"));
+        }
+        let greedy = if self.peek() == Some('?') {
+            self.pos += 1;
+            false
+        } else {
+            true
+        };
+        Ok(Node::Repeat {
+            node: Box::new(atom),
+            min,
+            max,
+            greedy,
+        })
+    }
+}
+
+fn escape_class(c: char) -> Option<ClassItem> {
+    match c {
+        'd' => Some(ClassItem::Digit(true)),
+        'D' => Some(ClassItem::Digit(false)),
+        'w' => Some(ClassItem::Word(true)),
+        'W' => Some(ClassItem::Word(false)),
+        's' => Some(ClassItem::Space(true)),
+        'S' => Some(ClassItem::Space(false)),
+        _ => None,
+    }
+}
+
+fn escape_char(c: char) -> char {
+    match c {
+        'n' => '\n',
+        't' => '\t',
+        'r' => '\r',
+        c => c,
+    }
+}
+
+/// 转义文本中的特殊字符，使其按字面量匹配
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "\\.^$|()[]{}*+?".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find(pattern: &str, text: &str) -> Option<(usize, usize)> {
+        Regex::new(pattern).unwrap().find(text)
+    }
+
+    fn matched<'a>(pattern: &str, text: &'a str) -> Option<&'a str> {
+        find(pattern, text).map(|(start, end)| &text[start..end])
+    }
+
+    #[test]
+    fn literals_and_classes() {
+        assert_eq!(matched("cat", "concatenate"), Some("cat"));
+        assert_eq!(matched("c.t", "cut\ncat"), Some("cut"));
+        assert_eq!(matched("a.b", "a\nb"), None);
+        assert_eq!(matched(r"\d+", "v12.3"), Some("12"));
+        assert_eq!(matched(r"\w+", "  中文_ab!"), Some("中文_ab"));
+        assert_eq!(matched(r"\s\S", "ab c"), Some(" c"));
+        assert_eq!(matched(r"\D\W", "1a-"), Some("a-"));
+        assert_eq!(matched("[a-c]+", "xxbcaz"), Some("bca"));
+        assert_eq!(matched("[^a-c ]+", "abc def"), Some("def"));
+        assert_eq!(matched("[]x]+", "a]x]"), Some("]x]"));
+        assert_eq!(matched("[a-]+", "b-a-"), Some("-a-"));
+        assert_eq!(matched(r"[\d.]+", "v1.2"), Some("1.2"));
+        assert_eq!(matched(r"\.\*\\", r"a.*\b"), Some(r".*\"));
+        assert_eq!(matched(r"a\tb", "a\tb"), Some("a\tb"));
+        assert_eq!(matched("x{", "ax{"), Some("x{"));
+    }
+
+    #[test]
+    fn anchors_and_alternation() {
+        assert_eq!(find("^ab", "abab"), Some((0, 2)));
+        assert_eq!(find("ab$", "abab"), Some((2, 4)));
+        assert_eq!(find("^b", "ab"), None);
+        assert_eq!(find("^$", ""), Some((0, 0)));
+        assert_eq!(matched("cat|dog", "hotdog cat"), Some("dog"));
+        assert_eq!(matched("gr(a|e)y", "grey"), Some("grey"));
+        assert_eq!(matched("(?:ab)+", "xababa"), Some("abab"));
+        assert_eq!(matched("a|ab", "ab"), Some("a"));
+        assert_eq!(find("", "abc"), Some((0, 0)));
+    }
+
+    #[test]
+    fn quantifiers() {
+        assert_eq!(matched("ab*", "abbbc"), Some("abbb"));
+        assert_eq!(matched("ab+", "ac ab"), Some("ab"));
+        assert_eq!(matched("colou?r", "color"), Some("color"));
+        assert_eq!(matched("a{2}", "aaa"), Some("aa"));
+        assert_eq!(matched("a{2,}", "aaaa"), Some("aaaa"));
+        assert_eq!(matched("a{1,3}", "aaaa"), Some("aaa"));
+        assert_eq!(matched("a{0}b", "ab"), Some("b"));
+        assert_eq!(matched("a+?", "aaa"), Some("a"));
+        assert_eq!(matched("<.*?>", "<a><b>"), Some("<a>"));
+        assert_eq!(matched("<.*>", "<a><b>"), Some("<a><b>"));
+        assert_eq!(matched("a{2,3}?", "aaa"), Some("aa"));
+        assert_eq!(matched("(a*)*b", "aab"), Some("aab"));
+    }
+
+    #[test]
+    fn offsets_are_bytes() {
+        assert_eq!(find("文", "中文"), Some((3, 6)));
+        assert_eq!(find("$", "中文"), Some((6, 6)));
+    }
+
+    #[test]
+    fn ignore_case() {
+        let regex = Regex::with_case("rust [a-c]", true).unwrap();
+        assert!(regex.is_match("RUST B"));
+        assert!(!regex.is_match("RUST D"));
+        assert!(Regex::with_case("ÄB", true).unwrap().is_match("äb"));
+        assert!(!Regex::new("rust").unwrap().is_match("RUST"));
+    }
+
+    #[test]
+    fn backtracking_is_bounded() {
+        let text = "a".repeat(5000);
+        assert!(!Regex::new("(a*)*b").unwrap().is_match(&text));
+        assert!(!Regex::new("(a|aa)+$b").unwrap().is_match(&text));
+    }
+
+    #[test]
+    fn errors_name_the_offset() {
+        for (pattern, offset) in [
+            ("(a", 2),
+            ("a)", 1),
+            ("*a", 1),
+            ("a|+", 3),
+            ("[a", 2),
+            ("[z-a]", 4),
+            ("a{2", 3),
+            ("a{3,2}", 5),
+            ("^*", 2),
+            ("\\", 1),
+        ] {
+            let error = Regex::new(pattern).unwrap_err();
+            assert_eq!(error.offset, offset, "{:?}: {}", pattern, error);
+        }
+    }
+
+    #[test]
+    fn escaped_text_matches_literally() {
+        let text = r"a.b*c\d(e)[f]{2}^$|?+";
+        let regex = Regex::new(&escape(text)).unwrap();
+        assert_eq!(regex.find(text), Some((0, text.len())));
+        assert!(!regex.is_match("axb*c"));
+    }
+}
@@ -0,0 +1,386 @@
+// 笔记重命名与合并：改名或删除笔记文件，并改写其他笔记中指向它的 [[链接]]
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::frontmatter;
+use crate::links::link_ranges;
+use crate::note::NEED_TAG;
+use crate::output::write_atomic;
+use crate::retag::{add_tag, backup};
+use crate::vault::Vault;
+
+/// 一次笔记重命名的结果
+pub struct NoteRename {
+    pub from: PathBuf,
+    pub to: PathBuf,
+    /// 改写了链接的笔记及各自改写的链接数，路径为改名前的位置
+    pub rewritten: Vec<(PathBuf, usize)>,
+    /// 原文件的备份目录，没有改写任何文件时为 None
+    pub backup_dir: Option<PathBuf>,
+}
+
+/// 一次笔记合并的结果
+pub struct NoteMerge {
+    /// 保留的笔记
+    pub kept: PathBuf,
+    /// 并入保留的笔记后删除的笔记
+    pub removed: PathBuf,
+    /// 保留的笔记新加上的标签
+    pub tags: Vec<String>,
+    /// 改用的较早的创建时间，保留的笔记本来就更早时为 None
+    pub created: Option<String>,
+    /// 改写了链接的笔记及各自改写的链接数，不含被删除的笔记
+    pub rewritten: Vec<(PathBuf, usize)>,
+    /// 原文件的备份目录，没有改写任何文件时为 None
+    pub backup_dir: Option<PathBuf>,
+}
+
+/// 把正文中目标为 olds 之一的 `[[链接]]`（含 `![[嵌入]]`）改为指向 new
+///
+/// 保留链接中的 `#标题`、`|别名` 与 `.md` 后缀，跳过文件头、代码块与行内代码。
+/// 返回改写后的内容与改写的链接数，没有改动时返回 None。
+pub fn rewrite_links(content: &str, olds: &[&str], new: &str) -> Option<(String, usize)> {
+    let body_start = frontmatter::split(content).map_or(0, |(_, body)| content.len() - body.len());
+    let mut result = String::with_capacity(content.len());
+    result.push_str(&content[..body_start]);
+    let mut count = 0;
+    let mut in_code_block = false;
+
+    for line in content[body_start..].split_inclusive('\n') {
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+        }
+        if in_code_block || line.trim_start().starts_with("```") {
+            result.push_str(line);
+            continue;
+        }
+
+        let mut copied = 0;
+        for (start, end) in link_ranges(line) {
+            let inner = &line[start + 2..end - 2];
+            result.push_str(&line[copied..start + 2]);
+            // 目标为 | 与 # 之前的部分，去掉两侧空白与 .md 后缀后比较
+            let target_end = inner.find(['|', '#']).unwrap_or(inner.len());
+            let target = inner[..target_end].trim();
+            let (name, suffix) = match target.strip_suffix(".md") {
+                Some(name) => (name, ".md"),
+                None => (target, ""),
+            };
+            if olds.contains(&name) {
+                result.push_str(new);
+                result.push_str(suffix);
+                result.push_str(&inner[target_end..]);
+                count += 1;
+            } else {
+                result.push_str(inner);
+            }
+            result.push_str("]]");
+            copied = end;
+        }
+        result.push_str(&line[copied..]);
+    }
+
+    (count > 0).then_some((result, count))
+}
+
+/// 把文件头中 key 字段的值改为 title，没有该字段时加在文件头末尾，没有文件头时新建
+pub fn set_title(content: &str, key: &str, title: &str) -> String {
+    let Some((header, body)) = frontmatter::split(content) else {
+        return format!("---\n{}: {}\n---\n{}", key, title, content);
+    };
+    let header_start = header.as_ptr() as usize - content.as_ptr() as usize;
+    let header_end = content.len() - body.len();
+    let mut offset = header_start;
+    for line in header.split_inclusive('\n') {
+        if let Some((name, _)) = line.split_once(':')
+            && !line.starts_with([' ', '\t'])
+            && name.trim().eq_ignore_ascii_case(key)
+        {
+            let ending = &line[line.trim_end_matches(['\n', '\r']).len()..];
+            return format!(
+                "{}{}: {}{}{}",
+                &content[..offset],
+                name,
+                title,
+                ending,
+                &content[offset + line.len()..]
+            );
+        }
+        offset += line.len();
+    }
+    // 新字段加在文件头的结束标记之前，换行符与文件头一致
+    let closing = content[..header_end]
+        .trim_end_matches(['\n', '\r'])
+        .rfind('\n')
+        .map_or(0, |i| i + 1);
+    let ending = if header.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+    format!(
+        "{}{}: {}{}{}",
+        &content[..closing],
+        key,
+        title,
+        ending,
+        &content[closing..]
+    )
+}
+
+/// 把笔记 old 改名为 new，并改写所有笔记中指向它的链接；title 不为 None 时同时修改标题
+///
+/// 按旧标题书写的链接在修改标题后改为新标题。改写前将原文件复制到 backup_root 下
+/// 以时间戳命名的目录。dry_run 为 true 时只返回将被改写的笔记，不改动任何文件。
+pub fn rename_note(
+    vault: &Vault,
+    old: &str,
+    new: &str,
+    title: Option<(&str, &str)>,
+    backup_root: &Path,
+    dry_run: bool,
+) -> io::Result<NoteRename> {
+    let from = vault.note_path(old);
+    let to = vault.note_path(new);
+    let Some(meta) = vault.notes().find(|meta| meta.name == old) else {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("找不到笔记 {}", from.display()),
+        ));
+    };
+    if to.exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} 已存在", to.display()),
+        ));
+    }
+
+    // 文件名链接总是指向该笔记；标题链接只有在确实解析到该笔记时才改写
+    let links = &vault.indexes().links;
+    let retitle = title.filter(|(_, title)| *title != meta.title);
+    let old_title = retitle
+        .is_some()
+        .then_some(meta.title.as_str())
+        .filter(|title| links.resolve(title) == Some(old));
+
+    let mut notes: Vec<&str> = vault.notes().map(|meta| meta.name.as_str()).collect();
+    notes.sort_unstable();
+    let mut report = NoteRename {
+        from: from.clone(),
+        to: to.clone(),
+        rewritten: Vec::new(),
+        backup_dir: None,
+    };
+    for name in notes {
+        let path = vault.note_path(name);
+        let content = fs::read_to_string(&path)?;
+        let (mut rewritten, mut count) =
+            rewrite_links(&content, &[old], new).unwrap_or((content.clone(), 0));
+        if let (Some(old_title), Some((_, new_title))) = (old_title, retitle)
+            && let Some((text, n)) = rewrite_links(&rewritten, &[old_title], new_title)
+        {
+            rewritten = text;
+            count += n;
+        }
+        if name == old
+            && let Some((key, title)) = retitle
+        {
+            rewritten = set_title(&rewritten, key, title);
+        }
+        if rewritten == content {
+            continue;
+        }
+        if !dry_run {
+            backup(vault.root(), &path, backup_root, &mut report.backup_dir)?;
+            write_atomic(&path, &rewritten)?;
+        }
+        if count > 0 {
+            report.rewritten.push((path, count));
+        }
+    }
+
+    if !dry_run {
+        fs::rename(&from, &to)?;
+    }
+    Ok(report)
+}
+
+/// 把笔记 absorb 并入 keep：正文接在 keep 末尾，标签取两者之和，创建时间取较早的一个，
+/// 指向 absorb 的链接改为指向 keep，最后删除 absorb
+///
+/// tag_keys 与 created_keys 为标签与创建时间字段的键名，写入第一个存在的字段。
+/// 按 absorb 的标题书写的链接只有在确实解析到 absorb 时才改写。改写与删除前将原文件
+/// 复制到 backup_root 下以时间戳命名的目录。dry_run 为 true 时不改动任何文件。
+pub fn merge_notes(
+    vault: &Vault,
+    keep: &str,
+    absorb: &str,
+    tag_keys: &[&str],
+    created_keys: &[&str],
+    backup_root: &Path,
+    dry_run: bool,
+) -> io::Result<NoteMerge> {
+    let find = |name: &str| {
+        vault.notes().find(|meta| meta.name == name).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("找不到笔记 {}", vault.note_path(name).display()),
+            )
+        })
+    };
+    let kept = find(keep)?;
+    let absorbed = find(absorb)?;
+    let removed = vault.note_path(absorb);
+
+    let links = &vault.indexes().links;
+    let mut olds = vec![absorb];
+    if absorbed.title != absorb && links.resolve(&absorbed.title) == Some(absorb) {
+        olds.push(&absorbed.title);
+    }
+
+    // 并入的正文中指向自身的链接同样改为指向 keep
+    let absorbed_content = fs::read_to_string(&removed)?;
+    let absorbed_content =
+        rewrite_links(&absorbed_content, &olds, keep).map_or(absorbed_content, |(text, _)| text);
+    let body =
+        frontmatter::split(&absorbed_content).map_or(absorbed_content.as_str(), |(_, body)| body);
+
+    let tags: Vec<String> = absorbed
+        .tags
+        .iter()
+        .filter(|tag| *tag != NEED_TAG && !kept.tags.contains(tag))
+        .cloned()
+        .collect();
+    let earlier = match (&kept.created, &absorbed.created) {
+        (None, Some(_)) => true,
+        (Some(a), Some(b)) => b < a,
+        _ => false,
+    };
+    let created = if earlier {
+        frontmatter::parse(&absorbed_content)
+            .ok()
+            .flatten()
+            .and_then(|(header, _)| {
+                created_keys
+                    .iter()
+                    .find_map(|key| header.get_str(key).map(str::to_string))
+            })
+    } else {
+        None
+    };
+
+    let mut report = NoteMerge {
+        kept: vault.note_path(keep),
+        removed: removed.clone(),
+        tags,
+        created,
+        rewritten: Vec::new(),
+        backup_dir: None,
+    };
+    let mut notes: Vec<&str> = vault
+        .notes()
+        .map(|meta| meta.name.as_str())
+        .filter(|name| *name != absorb)
+        .collect();
+    notes.sort_unstable();
+    for name in notes {
+        let path = vault.note_path(name);
+        let content = fs::read_to_string(&path)?;
+        let (mut rewritten, count) =
+            rewrite_links(&content, &olds, keep).unwrap_or((content.clone(), 0));
+        if name == keep {
+            if let Some(created) = &report.created {
+                let key = existing_key(&rewritten, created_keys);
+                rewritten = set_title(&rewritten, key, created);
+            }
+            for tag in &report.tags {
+                rewritten = add_tag(&rewritten, tag_keys, tag);
+            }
+            let body = body.trim();
+            if !body.is_empty() {
+                rewritten = format!("{}\n\n{}\n", rewritten.trim_end(), body);
+            }
+        }
+        if rewritten == content {
+            continue;
+        }
+        if !dry_run {
+            backup(vault.root(), &path, backup_root, &mut report.backup_dir)?;
+            write_atomic(&path, &rewritten)?;
+        }
+        if count > 0 {
+            report.rewritten.push((path, count));
+        }
+    }
+
+    if !dry_run {
+        backup(vault.root(), &removed, backup_root, &mut report.backup_dir)?;
+        fs::remove_file(&removed)?;
+    }
+    Ok(report)
+}
+
+/// 文件头中第一个存在的键名，都不存在时为 keys 的第一项
+pub fn existing_key<'k>(content: &str, keys: &[&'k str]) -> &'k str {
+    let header = frontmatter::parse(content).ok().flatten();
+    header
+        .and_then(|(header, _)| keys.iter().find(|key| header.get(key).is_some()).copied())
+        .unwrap_or(keys[0])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rewrite(content: &str) -> Option<(String, usize)> {
+        rewrite_links(content, &["a", "旧 名"], "sub/b")
+    }
+
+    #[test]
+    fn links_keep_alias_heading_and_suffix() {
+        assert_eq!(
+            rewrite("[[a]] [[a|别名]] [[a#标题]] [[a.md#h|x]] ![[a]]\n"),
+            Some((
+                "[[sub/b]] [[sub/b|别名]] [[sub/b#标题]] [[sub/b.md#h|x]] ![[sub/b]]\n".to_string(),
+                5
+            ))
+        );
+        assert_eq!(
+            rewrite("见 [[ 旧 名 ]]，不是 [[ab]] 或 [[c|a]]"),
+            Some(("见 [[sub/b]]，不是 [[ab]] 或 [[c|a]]".to_string(), 1))
+        );
+        assert_eq!(rewrite("[[ab]] [[b#a]] [[a\n"), None);
+    }
+
+    #[test]
+    fn code_and_frontmatter_are_left_alone() {
+        let content = "---\nTitle: [[a]]\n---\n\
+                       `[[a]]` ``x `[[a]]` y`` [[a]]\n\
+                       ```\n[[a]]\n```\n\
+                       未闭合的 ` [[a]]\n";
+        let expected = "---\nTitle: [[a]]\n---\n\
+                        `[[a]]` ``x `[[a]]` y`` [[sub/b]]\n\
+                        ```\n[[a]]\n```\n\
+                        未闭合的 ` [[sub/b]]\n";
+        assert_eq!(rewrite(content), Some((expected.to_string(), 2)));
+        assert_eq!(rewrite("`[[a]]`\n```\n[[a]]\n```\n"), None);
+    }
+
+    #[test]
+    fn set_title_replaces_or_adds_the_field() {
+        assert_eq!(
+            set_title("---\ntitle: 旧\nTags: a\n---\n正文\n", "Title", "新"),
+            "---\ntitle: 新\nTags: a\n---\n正文\n"
+        );
+        assert_eq!(
+            set_title("---\r\nTags: a\r\n---\r\n", "Title", "新"),
+            "---\r\nTags: a\r\nTitle: 新\r\n---\r\n"
+        );
+        assert_eq!(
+            set_title("正文\n", "Title", "新"),
+            "---\nTitle: 新\n---\n正文\n"
+        );
+    }
+}
@@ -0,0 +1,545 @@
+// 标签重命名与批量增删：直接改写笔记文件头中的标签
+
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::frontmatter;
+use crate::output::{unique_dir, write_atomic};
+use crate::query::Query;
+use crate::rename::set_title;
+use crate::vault::Vault;
+
+/// tag 是否为 old 本身或其下级标签
+pub fn has_tag(tag: &str, old: &str) -> bool {
+    tag == old
+        || tag
+            .strip_prefix(old)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
+
+/// 将内容文件头中 key 字段里的标签 olds（及其下级标签 old/...）改为 new
+///
+/// 支持 `键: a b`、`键: [a, b]`、块列表与两者混用的写法，其余内容原样保留。
+/// 改名后重复的标签只保留第一个。没有改动时返回 None。
+pub fn rename_tags(content: &str, key: &str, olds: &[&str], new: &str) -> Option<String> {
+    let rename = |tag: &str| {
+        olds.iter()
+            .find(|old| has_tag(tag, old))
+            .map(|old| format!("{}{}", new, &tag[old.len()..]))
+    };
+    rewrite_tags(content, key, &rename, HashSet::new())
+}
+
+/// 从内容文件头的 key 字段中删去标签 tag，下级标签保留；没有改动时返回 None
+pub fn remove_tag(content: &str, key: &str, tag: &str) -> Option<String> {
+    // 预先记为已出现，字段中的 tag 便按重复的标签删去
+    rewrite_tags(content, key, &|_| None, HashSet::from([tag.to_string()]))
+}
+
+// 按 rename 改写文件头 key 字段中的标签，已在 seen 中的标签删去
+fn rewrite_tags(
+    content: &str,
+    key: &str,
+    rename: &dyn Fn(&str) -> Option<String>,
+    mut seen: HashSet<String>,
+) -> Option<String> {
+    let (header, _) = frontmatter::split(content)?;
+    let start = header.as_ptr() as usize - content.as_ptr() as usize;
+
+    let mut rewritten = String::with_capacity(header.len());
+    let mut in_tags = false;
+    for line in header.split_inclusive('\n') {
+        let indented = line.starts_with(' ') || line.starts_with('\t');
+        let trimmed = line.trim_start();
+
+        if !indented && !trimmed.starts_with('-') && !trimmed.starts_with('#') {
+            in_tags = false;
+            if let Some((name, value)) = line.split_once(':') {
+                in_tags = name.trim().eq_ignore_ascii_case(key);
+                if in_tags {
+                    rewritten.push_str(name);
+                    rewritten.push(':');
+                    rewritten.push_str(&rename_in_value(value, rename, &mut seen).0);
+                    continue;
+                }
+            }
+        } else if in_tags {
+            // 块列表项或缩进的续行，其中的标签全部因重复被删去时整行删去
+            let indent = &line[..line.len() - trimmed.len()];
+            let (marker, value) = match trimmed.strip_prefix('-') {
+                Some(item) => ("-", item),
+                None => ("", trimmed),
+            };
+            let (renamed, emptied) = rename_in_value(value, rename, &mut seen);
+            if emptied {
+                continue;
+            }
+            rewritten.push_str(indent);
+            rewritten.push_str(marker);
+            rewritten.push_str(&renamed);
+            continue;
+        }
+        rewritten.push_str(line);
+    }
+
+    if rewritten == header {
+        return None;
+    }
+    let mut result = String::with_capacity(content.len());
+    result.push_str(&content[..start]);
+    result.push_str(&rewritten);
+    result.push_str(&content[start + header.len()..]);
+    Some(result)
+}
+
+/// 在文件头的标签字段中加上 tag，不检查是否已有该标签
+///
+/// keys 为标签字段的键名，加在第一个存在的字段中：行内写法追加在末尾，`[a, b]` 追加在方括号内，
+/// 块列表追加一项。都不存在时以 keys 的第一项新建字段，没有文件头时新建文件头。
+pub fn add_tag(content: &str, keys: &[&str], tag: &str) -> String {
+    let Some((header, _)) = frontmatter::split(content) else {
+        return set_title(content, keys[0], tag);
+    };
+    let start = header.as_ptr() as usize - content.as_ptr() as usize;
+    let lines: Vec<&str> = header.split_inclusive('\n').collect();
+    let field = |line: &str, key: &str| {
+        !line.starts_with([' ', '\t'])
+            && line
+                .split_once(':')
+                .is_some_and(|(name, _)| name.trim().eq_ignore_ascii_case(key))
+    };
+    let Some(index) = keys
+        .iter()
+        .find_map(|key| lines.iter().position(|line| field(line, key)))
+    else {
+        return set_title(content, keys[0], tag);
+    };
+
+    let line = lines[index];
+    let (name, value) = line.split_once(':').unwrap_or((line, ""));
+    let ending = &value[value.trim_end_matches(['\n', '\r']).len()..];
+    let value = &value[..value.len() - ending.len()];
+    let (_, comment) = split_value(value);
+    let body = value[..value.len() - comment.len()].trim_end();
+    let padding = &value[body.len()..value.len() - comment.len()];
+
+    // 改写从字段所在行到 end 行（含）的内容
+    let items = lines[index + 1..]
+        .iter()
+        .take_while(|line| line.starts_with([' ', '\t', '-']))
+        .count();
+    let (end, replaced) = if body.trim().is_empty() && items > 0 {
+        // 块列表：照最后一项的缩进加一项
+        let last = lines[index + items];
+        let indent = &last[..last.len() - last.trim_start().len()];
+        let ending = &last[last.trim_end_matches(['\n', '\r']).len()..];
+        let block = lines[index..=index + items].concat();
+        (
+            index + items,
+            format!("{}{}- {}{}", block, indent, tag, ending),
+        )
+    } else if body.trim().is_empty() {
+        (index, format!("{}: {}{}{}", name, tag, comment, ending))
+    } else if let Some(list) = body.strip_suffix(']') {
+        let list = list.trim_end();
+        let separator = if list.ends_with('[') { "" } else { ", " };
+        let line = format!(
+            "{}:{}{}{}]{}{}{}",
+            name, list, separator, tag, padding, comment, ending
+        );
+        (index, line)
+    } else {
+        let separator = if body.contains([',', '，']) {
+            ", "
+        } else {
+            " "
+        };
+        let line = format!(
+            "{}:{}{}{}{}{}{}",
+            name, body, separator, tag, padding, comment, ending
+        );
+        (index, line)
+    };
+    let offset = start + lines[..index].concat().len();
+    let end_offset = start + lines[..=end].concat().len();
+    format!(
+        "{}{}{}",
+        &content[..offset],
+        replaced,
+        &content[end_offset..]
+    )
+}
+
+// 字段值拆分后的片段
+enum Part {
+    // 空白、方括号与逗号
+    Sep(String),
+    // 一个标签，可能带引号
+    Tag(String),
+}
+
+// 改写一个字段值：行内列表逐项处理，否则按空白分隔逐词处理，分隔符与注释原样保留。
+// 已在 seen 中的标签连同它前面（位于开头时为后面）的分隔符一起删去。
+// 另返回原有的标签是否全部被删去
+fn rename_in_value(
+    value: &str,
+    rename: &dyn Fn(&str) -> Option<String>,
+    seen: &mut HashSet<String>,
+) -> (String, bool) {
+    let (parts, comment) = split_value(value);
+    let had_tags = parts.iter().any(|part| matches!(part, Part::Tag(_)));
+
+    let mut out: Vec<Part> = Vec::with_capacity(parts.len());
+    let mut drop_next_sep = false;
+    for part in parts {
+        match part {
+            Part::Sep(sep) => {
+                // 删去开头的标签时，其后的分隔符也一并删去，但保留列表的右括号与换行
+                if drop_next_sep && !sep.contains(']') {
+                    drop_next_sep = false;
+                    let ending = &sep[sep.trim_end_matches(['\n', '\r']).len()..];
+                    if !ending.is_empty() {
+                        out.push(Part::Sep(ending.to_string()));
+                    }
+                    continue;
+                }
+                drop_next_sep = false;
+                out.push(Part::Sep(sep));
+            }
+            Part::Tag(token) => {
+                let (quote, inner) = unquote(&token);
+                let renamed = rename(inner);
+                let tag = renamed.as_deref().unwrap_or(inner);
+                if seen.insert(tag.to_string()) {
+                    out.push(Part::Tag(match &renamed {
+                        Some(tag) => format!("{}{}{}", quote, tag, quote),
+                        None => token,
+                    }));
+                } else if out.iter().any(|part| matches!(part, Part::Tag(_))) {
+                    if matches!(out.last(), Some(Part::Sep(_))) {
+                        out.pop();
+                    }
+                } else {
+                    drop_next_sep = true;
+                }
+            }
+        }
+    }
+
+    let mut result = String::with_capacity(value.len());
+    for part in &out {
+        match part {
+            Part::Sep(s) | Part::Tag(s) => result.push_str(s),
+        }
+    }
+    result.push_str(comment);
+    let emptied = had_tags && !out.iter().any(|part| matches!(part, Part::Tag(_)));
+    (result, emptied)
+}
+
+// 将字段值拆成分隔符与标签交替的片段，另返回从 `#` 开始的注释
+fn split_value(value: &str) -> (Vec<Part>, &str) {
+    let mut parts = Vec::new();
+    let mut token = String::new();
+    let mut sep = String::new();
+    let mut quote = None;
+    for (i, c) in value.char_indices() {
+        match quote {
+            Some(q) => {
+                token.push(c);
+                if c == q {
+                    quote = None;
+                }
+            }
+            // 注释之后的内容不改动
+            None if c == '#' && token.is_empty() => {
+                if !sep.is_empty() {
+                    parts.push(Part::Sep(sep));
+                }
+                return (parts, &value[i..]);
+            }
+            None if c.is_whitespace() || matches!(c, '[' | ']' | ',' | '，') => {
+                if !token.is_empty() {
+                    parts.push(Part::Tag(std::mem::take(&mut token)));
+                }
+                sep.push(c);
+            }
+            None => {
+                if !sep.is_empty() {
+                    parts.push(Part::Sep(std::mem::take(&mut sep)));
+                }
+                if c == '"' || c == '\'' {
+                    quote = Some(c);
+                }
+                token.push(c);
+            }
+        }
+    }
+    if !token.is_empty() {
+        parts.push(Part::Tag(token));
+    }
+    if !sep.is_empty() {
+        parts.push(Part::Sep(sep));
+    }
+    (parts, "")
+}
+
+// 拆出两侧的引号
+fn unquote(token: &str) -> (&str, &str) {
+    match token.chars().next() {
+        Some(q @ ('"' | '\'')) if token.len() >= 2 && token.ends_with(q) => {
+            (&token[..1], &token[1..token.len() - 1])
+        }
+        _ => ("", token),
+    }
+}
+
+/// 一次重命名的结果
+pub struct RenameReport {
+    /// 已改写的笔记
+    pub renamed: Vec<PathBuf>,
+    /// 含有该标签但文件头中找不到对应写法的笔记
+    pub skipped: Vec<PathBuf>,
+    /// 原文件的备份目录，没有改写任何文件时为 None
+    pub backup_dir: Option<PathBuf>,
+}
+
+/// 在 vault 的所有笔记中把标签 olds 改为 new，合并多个标签时 olds 含多项；
+/// keys 为标签字段的键名，依次尝试，改写第一个含有这些标签的字段
+///
+/// 改写前将原文件复制到 backup_root 下以时间戳命名的目录，
+/// 写入时先写临时文件再重命名，避免中途失败留下不完整的笔记。
+/// dry_run 为 true 时只返回将被改写的笔记。
+pub fn rename_in_vault(
+    vault: &Vault,
+    keys: &[&str],
+    olds: &[&str],
+    new: &str,
+    backup_root: &Path,
+    dry_run: bool,
+) -> io::Result<RenameReport> {
+    let mut names: Vec<&str> = vault
+        .notes()
+        .filter(|note| {
+            note.tags
+                .iter()
+                .any(|tag| olds.iter().any(|old| has_tag(tag, old)))
+        })
+        .map(|note| note.name.as_str())
+        .collect();
+    names.sort_unstable();
+
+    let mut report = RenameReport {
+        renamed: Vec::new(),
+        skipped: Vec::new(),
+        backup_dir: None,
+    };
+    for name in names {
+        let path = vault.note_path(name);
+        let content = fs::read_to_string(&path)?;
+        let Some(rewritten) = keys
+            .iter()
+            .find_map(|key| rename_tags(&content, key, olds, new))
+        else {
+            report.skipped.push(path);
+            continue;
+        };
+        if !dry_run {
+            backup(vault.root(), &path, backup_root, &mut report.backup_dir)?;
+            write_atomic(&path, &rewritten)?;
+        }
+        report.renamed.push(path);
+    }
+    Ok(report)
+}
+
+/// 在 query 匹配的笔记中加上标签 adds、删去标签 removes，keys 含义同 `add_tag`
+///
+/// 先计算全部笔记的新内容，读取都成功后才开始写入；已有要加的标签、没有要删的标签的笔记
+/// 不改写。备份与 dry_run 同 `rename_in_vault`，skipped 为文件头中找不到要删的标签的笔记。
+pub fn edit_in_vault(
+    vault: &Vault,
+    query: &Query,
+    keys: &[&str],
+    adds: &[&str],
+    removes: &[&str],
+    backup_root: &Path,
+    dry_run: bool,
+) -> io::Result<RenameReport> {
+    let mut report = RenameReport {
+        renamed: Vec::new(),
+        skipped: Vec::new(),
+        backup_dir: None,
+    };
+    let mut edits = Vec::new();
+    for note in query.filter(vault.notes()) {
+        let has = |tag: &str| note.tags.iter().any(|t| t == tag);
+        let adds: Vec<&str> = adds.iter().copied().filter(|tag| !has(tag)).collect();
+        let removes: Vec<&str> = removes.iter().copied().filter(|tag| has(tag)).collect();
+        if adds.is_empty() && removes.is_empty() {
+            continue;
+        }
+        let path = vault.note_path(&note.name);
+        let content = fs::read_to_string(&path)?;
+        let mut rewritten = content.clone();
+        let mut missing = false;
+        for tag in removes {
+            match keys.iter().find_map(|key| remove_tag(&rewritten, key, tag)) {
+                Some(text) => rewritten = text,
+                None => missing = true,
+            }
+        }
+        for tag in adds {
+            rewritten = add_tag(&rewritten, keys, tag);
+        }
+        if missing {
+            report.skipped.push(path.clone());
+        }
+        if rewritten != content {
+            edits.push((path, rewritten));
+        }
+    }
+    edits.sort_by(|a, b| a.0.cmp(&b.0));
+    report.skipped.sort();
+
+    for (path, rewritten) in edits {
+        if !dry_run {
+            backup(vault.root(), &path, backup_root, &mut report.backup_dir)?;
+            write_atomic(&path, &rewritten)?;
+        }
+        report.renamed.push(path);
+    }
+    Ok(report)
+}
+
+// 把 path 复制到备份目录中相对 root 的同一位置；backup_dir 为 None 时先在 backup_root 下
+// 为这次操作新建以时间戳命名的目录
+pub(crate) fn backup(
+    root: &Path,
+    path: &Path,
+    backup_root: &Path,
+    backup_dir: &mut Option<PathBuf>,
+) -> io::Result<()> {
+    let dir = match backup_dir {
+        Some(dir) => dir,
+        None => backup_dir.insert(unique_dir(backup_root)?),
+    };
+    let relative = path
+        .strip_prefix(root)
+        .unwrap_or_else(|_| Path::new(path.file_name().unwrap()));
+    let target = dir.join(relative);
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::copy(path, target)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 文件头之外与标签字段之外的内容用于确认改写只涉及标签
+    const BODY: &str = "---\n正文 Tags: rust\n";
+
+    fn note(header: &str) -> String {
+        format!(
+            "---\nTitle: \"a: b\"\n{}Created: 20240501 # 时间\n{}",
+            header, BODY
+        )
+    }
+
+    #[test]
+    fn rename_keeps_everything_but_the_tags() {
+        let rename = |header: &str| rename_tags(&note(header), "Tags", &["rust"], "lang");
+        assert_eq!(
+            rename("Tags: rust cli # 注释\n"),
+            Some(note("Tags: lang cli # 注释\n"))
+        );
+        assert_eq!(
+            rename("tags: [rust/async, 'rust', \"cli\"]\n"),
+            Some(note("tags: [lang/async, 'lang', \"cli\"]\n"))
+        );
+        assert_eq!(
+            rename("Tags:\n  - cli\n  - rust\n"),
+            Some(note("Tags:\n  - cli\n  - lang\n"))
+        );
+        assert_eq!(
+            rename("Tags: rust,cli\r\n"),
+            Some(note("Tags: lang,cli\r\n"))
+        );
+        // 前缀相同但不是下级标签的不改
+        assert_eq!(rename("Tags: rustacean cli\n"), None);
+        assert_eq!(rename("Keywords: rust\n"), None);
+    }
+
+    #[test]
+    fn merge_drops_the_duplicates_it_creates() {
+        let merge = |header: &str| rename_tags(&note(header), "Tags", &["rs", "rust"], "lang");
+        assert_eq!(merge("Tags: rs rust cli\n"), Some(note("Tags: lang cli\n")));
+        assert_eq!(
+            merge("Tags: [cli, rs, lang]\n"),
+            Some(note("Tags: [cli, lang]\n"))
+        );
+        assert_eq!(
+            merge("Tags:\n  - rs\n  - rust\n  - cli\n"),
+            Some(note("Tags:\n  - lang\n  - cli\n"))
+        );
+    }
+
+    #[test]
+    fn remove_deletes_one_tag_and_its_separator() {
+        let remove = |header: &str, tag: &str| remove_tag(&note(header), "Tags", tag);
+        assert_eq!(
+            remove("Tags: [a, b, c]\n", "b"),
+            Some(note("Tags: [a, c]\n"))
+        );
+        assert_eq!(
+            remove("Tags: [a, b, c]\n", "a"),
+            Some(note("Tags: [b, c]\n"))
+        );
+        assert_eq!(
+            remove("Tags: a b # 注释\n", "b"),
+            Some(note("Tags: a # 注释\n"))
+        );
+        assert_eq!(
+            remove("Tags:\n  - a\n  - 'b'\n", "b"),
+            Some(note("Tags:\n  - a\n"))
+        );
+        // 下级标签保留
+        assert_eq!(
+            remove("Tags: rust rust/async\n", "rust"),
+            Some(note("Tags: rust/async\n"))
+        );
+        assert_eq!(remove("Tags: a\n", "b"), None);
+    }
+
+    #[test]
+    fn add_follows_the_existing_layout() {
+        let add = |header: &str| add_tag(&note(header), &["Tags", "Keywords"], "new");
+        assert_eq!(add("Tags: a b\n"), note("Tags: a b new\n"));
+        assert_eq!(add("Tags: a, b # 注释\n"), note("Tags: a, b, new # 注释\n"));
+        assert_eq!(add("Tags: ['a']\n"), note("Tags: ['a', new]\n"));
+        assert_eq!(add("Tags: []\n"), note("Tags: [new]\n"));
+        assert_eq!(add("Tags:\n"), note("Tags: new\n"));
+        assert_eq!(add("Tags:\n    - a\n"), note("Tags:\n    - a\n    - new\n"));
+        assert_eq!(add("Keywords: a\n"), note("Keywords: a new\n"));
+    }
+
+    #[test]
+    fn add_creates_the_field_when_missing() {
+        let added = add_tag(&note(""), &["Tags"], "new");
+        assert!(added.contains("\nTags: new\n"), "{}", added);
+        assert!(added.ends_with(BODY), "{}", added);
+        assert_eq!(added.replace("Tags: new\n", ""), note(""), "只多出标签字段");
+        let added = add_tag("正文\n", &["Tags"], "new");
+        assert!(
+            added.starts_with("---\n") && added.contains("Tags: new\n"),
+            "{}",
+            added
+        );
+        assert!(added.ends_with("正文\n"), "{}", added);
+    }
+}
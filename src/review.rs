@@ -0,0 +1,223 @@
+// 间隔重复复习：按 SM-2 算法安排笔记的下次复习日期
+//
+// 文件头中的 Review 字段写作 `Review: 2024-06-10 6 2.50`，依次为下次复习的日期、当前间隔天数与
+// 难度系数；后两项可以省略（间隔为 0，难度系数为 2.5），写上今天的日期即可把笔记加入复习。
+// 每次复习以 0–5 评分：低于 3 分时间隔回到 1 天，否则间隔依次为 1 天、6 天，之后乘以难度系数；
+// 难度系数随评分调整，不低于 1.3。
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::date::Date;
+use crate::note::NoteMeta;
+use crate::output::write_atomic;
+use crate::rename::{existing_key, set_title};
+use crate::vault::Vault;
+
+/// 新加入复习的笔记的难度系数
+pub const DEFAULT_EASE: f64 = 2.5;
+
+/// 难度系数的下限
+pub const MIN_EASE: f64 = 1.3;
+
+/// 评分的上限，0 为完全忘记，5 为毫不费力
+pub const MAX_QUALITY: u8 = 5;
+
+/// 一篇笔记的复习安排
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Schedule {
+    /// 下次复习的日期
+    pub due: Date,
+    /// 当前间隔天数，0 表示还没有复习过
+    pub interval: u32,
+    pub ease: f64,
+}
+
+impl Schedule {
+    /// 解析 Review 字段，日期写法同 Created；无法解析时返回 None
+    pub fn parse(value: &str) -> Option<Schedule> {
+        let mut words = value.split_whitespace();
+        let (due, _) = Date::parse(words.next()?).ok()?;
+        let interval = match words.next() {
+            Some(word) => word.parse().ok()?,
+            None => 0,
+        };
+        let ease = match words.next() {
+            Some(word) => word.parse::<f64>().ok()?.max(MIN_EASE),
+            None => DEFAULT_EASE,
+        };
+        if words.next().is_some() {
+            return None;
+        }
+        Some(Schedule {
+            due,
+            interval,
+            ease,
+        })
+    }
+
+    /// 在 today 完成一次评分为 quality 的复习之后的安排
+    pub fn review(&self, today: Date, quality: u8) -> Schedule {
+        let quality = quality.min(MAX_QUALITY);
+        let (interval, ease) = if quality < 3 {
+            (1, self.ease)
+        } else {
+            let interval = match self.interval {
+                0 => 1,
+                1 => 6,
+                n => (f64::from(n) * self.ease).round() as u32,
+            };
+            let miss = f64::from(MAX_QUALITY - quality);
+            let ease = self.ease + 0.1 - miss * (0.08 + miss * 0.02);
+            (interval, ease.max(MIN_EASE))
+        };
+        Schedule {
+            due: today.add_days(i64::from(interval)),
+            interval,
+            ease,
+        }
+    }
+}
+
+/// Review 字段的写法，日期写作 YYYY-MM-DD
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let due = self.due;
+        write!(
+            f,
+            "{:04}-{:02}-{:02} {} {:.2}",
+            due.year, due.month, due.day, self.interval, self.ease
+        )
+    }
+}
+
+/// 待复习的笔记
+pub struct Due<'a> {
+    pub note: &'a NoteMeta,
+    pub schedule: Schedule,
+}
+
+/// 下次复习日期不晚于 today 的笔记，按日期先后排列，同一天按文件名；
+/// 同时返回 Review 字段无法解析的笔记，按文件名排序
+pub fn due_notes(vault: &Vault, today: Date) -> (Vec<Due<'_>>, Vec<&NoteMeta>) {
+    let mut due = Vec::new();
+    let mut invalid = Vec::new();
+    for note in vault.notes() {
+        let Some(value) = &note.review else {
+            continue;
+        };
+        match Schedule::parse(value) {
+            Some(schedule) if schedule.due <= today => due.push(Due { note, schedule }),
+            Some(_) => {}
+            None => invalid.push(note),
+        }
+    }
+    due.sort_by(|a, b| {
+        a.schedule
+            .due
+            .cmp(&b.schedule.due)
+            .then_with(|| a.note.name.cmp(&b.note.name))
+    });
+    invalid.sort_by(|a, b| a.name.cmp(&b.name));
+    (due, invalid)
+}
+
+/// 把新的安排写入笔记文件头中 keys 里第一个存在的字段，都不存在时写入第一个键名
+pub fn record(path: &Path, keys: &[&str], schedule: &Schedule) -> io::Result<()> {
+    let content = fs::read_to_string(path)?;
+    let key = existing_key(&content, keys);
+    write_atomic(path, &set_title(&content, key, &schedule.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: u16, month: u8, day: u8) -> Date {
+        Date::new(year, month, day).unwrap()
+    }
+
+    fn schedule(interval: u32, ease: f64) -> Schedule {
+        Schedule {
+            due: date(2024, 5, 1),
+            interval,
+            ease,
+        }
+    }
+
+    // 评分后的 (间隔, 难度系数)，难度系数保留两位小数
+    fn after(before: Schedule, quality: u8) -> (u32, f64) {
+        let next = before.review(date(2024, 5, 1), quality);
+        (next.interval, (next.ease * 100.0).round() / 100.0)
+    }
+
+    #[test]
+    fn grades_below_three_restart_without_changing_ease() {
+        for quality in 0..3 {
+            assert_eq!(after(schedule(15, 2.2), quality), (1, 2.2), "{}", quality);
+            assert_eq!(after(schedule(0, 2.5), quality), (1, 2.5), "{}", quality);
+        }
+    }
+
+    #[test]
+    fn passing_grades_grow_the_interval_and_adjust_ease() {
+        // 间隔按复习前的难度系数计算
+        assert_eq!(after(schedule(6, 2.5), 3), (15, 2.36));
+        assert_eq!(after(schedule(6, 2.5), 4), (15, 2.5));
+        assert_eq!(after(schedule(6, 2.5), 5), (15, 2.6));
+        assert_eq!(after(schedule(6, 2.5), 9), (15, 2.6));
+        assert_eq!(after(schedule(15, 1.3), 3), (20, 1.3));
+        assert_eq!(after(schedule(15, 1.35), 3), (20, 1.3));
+
+        let mut next = schedule(0, DEFAULT_EASE);
+        let mut intervals = Vec::new();
+        for _ in 0..4 {
+            next = next.review(next.due, 4);
+            intervals.push(next.interval);
+        }
+        assert_eq!(intervals, [1, 6, 15, 38]);
+    }
+
+    #[test]
+    fn due_dates_cross_month_and_year_ends() {
+        let due = |today: Date, before: Schedule, quality: u8| before.review(today, quality).due;
+        assert_eq!(
+            due(date(2024, 2, 28), schedule(0, 2.5), 5),
+            date(2024, 2, 29)
+        );
+        assert_eq!(
+            due(date(2023, 2, 28), schedule(0, 2.5), 5),
+            date(2023, 3, 1)
+        );
+        assert_eq!(
+            due(date(2024, 2, 25), schedule(1, 2.5), 4),
+            date(2024, 3, 2)
+        );
+        assert_eq!(
+            due(date(2024, 12, 20), schedule(10, 2.5), 4),
+            date(2025, 1, 14)
+        );
+        assert_eq!(
+            due(date(2024, 12, 31), schedule(30, 2.5), 1),
+            date(2025, 1, 1)
+        );
+    }
+
+    #[test]
+    fn review_field_round_trips() {
+        let parsed = Schedule::parse("2024-06-10 6 2.36").unwrap();
+        assert_eq!((parsed.due, parsed.interval), (date(2024, 6, 10), 6));
+        assert_eq!(parsed.to_string(), "2024-06-10 6 2.36");
+        assert_eq!(
+            Schedule::parse("20240610").unwrap().to_string(),
+            "2024-06-10 0 2.50"
+        );
+        assert_eq!(Schedule::parse("2024-06-10 3 1.1").unwrap().ease, MIN_EASE);
+        assert_eq!(Schedule::parse("2024-06-10 3 2.5 x"), None);
+        assert_eq!(Schedule::parse("2024-06-10 -1"), None);
+        assert_eq!(Schedule::parse("明天"), None);
+        assert_eq!(Schedule::parse(""), None);
+    }
+}
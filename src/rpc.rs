@@ -0,0 +1,213 @@
+// gtx rpc：在标准输入输出上应答 JSON-RPC 2.0 请求，供编辑器与插件长期使用
+//
+// 每行一个请求，每个请求的应答占一行；没有 id 的通知不应答。索引只在启动与
+// reload 时扫描，之后的请求直接使用内存中的结果。
+//
+// 方法与参数:
+//   query     {"query": "tag:rust created:2024"}    满足查询的笔记，写法同 gtx query
+//   tag       {"tag": "rust"}                      含有该标签或其下层级标签的笔记
+//   search    {"query": "...", "limit": 20}        全文搜索，有搜索索引时按相关度排序
+//   resolve   {"target": "链接目标"}                链接指向的笔记，找不到时为 null
+//   backlinks {"name": "文件名、标题或别名"}        指向该笔记的链接
+//   reload    {}                                   重新扫描目录，返回笔记数
+// 笔记的字段与 gtx export 导出的相同。
+
+use std::io::{self, BufRead, Write};
+
+use crate::export::{backlinks_to_json, note_to_json};
+use crate::fulltext::SearchIndex;
+use crate::json::{self, Json};
+use crate::note::NoteMeta;
+use crate::query::Query;
+use crate::search::{self, SearchOptions};
+use crate::vault::Vault;
+
+// JSON-RPC 规定的错误码
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const INTERNAL_ERROR: i64 = -32603;
+
+// search 默认返回的笔记数
+const DEFAULT_LIMIT: usize = 20;
+
+/// 请求处理失败：错误码与说明
+struct RpcError(i64, String);
+
+/// 内存中的索引，reload 时重新扫描
+pub struct Server<'a> {
+    scan: &'a dyn Fn() -> io::Result<Vault>,
+    vault: Vault,
+    search_index: Option<SearchIndex>,
+}
+
+impl<'a> Server<'a> {
+    /// 用 scan 扫描目录，reload 时再次调用
+    pub fn new(scan: &'a dyn Fn() -> io::Result<Vault>) -> io::Result<Server<'a>> {
+        let vault = scan()?;
+        let search_index = SearchIndex::load(vault.root());
+        Ok(Server {
+            scan,
+            vault,
+            search_index,
+        })
+    }
+
+    /// 逐行读取请求并写出应答，直到输入结束
+    pub fn run(&mut self, input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+        for line in input.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            if let Some(response) = self.handle_line(&line) {
+                writeln!(output, "{}", response)?;
+                output.flush()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 处理一行请求，通知返回 None
+    pub fn handle_line(&mut self, line: &str) -> Option<Json> {
+        let request = match json::parse(line) {
+            Ok(request) => request,
+            Err(e) => return Some(error_response(Json::Null, PARSE_ERROR, e.to_string())),
+        };
+        let id = request.get("id").cloned();
+        let Some(method) = request.get("method").and_then(Json::as_str) else {
+            return Some(error_response(
+                id.unwrap_or(Json::Null),
+                INVALID_REQUEST,
+                "缺少 method".to_string(),
+            ));
+        };
+        let params = request
+            .get("params")
+            .cloned()
+            .unwrap_or(Json::Object(Vec::new()));
+        let result = self.call(method, &params);
+
+        let id = id?;
+        Some(match result {
+            Ok(result) => Json::Object(vec![
+                ("jsonrpc".to_string(), "2.0".into()),
+                ("id".to_string(), id),
+                ("result".to_string(), result),
+            ]),
+            Err(RpcError(code, message)) => error_response(id, code, message),
+        })
+    }
+
+    fn call(&mut self, method: &str, params: &Json) -> Result<Json, RpcError> {
+        match method {
+            "query" => {
+                let text = string_param(params, "query")?;
+                let query =
+                    Query::parse(text).map_err(|e| RpcError(INVALID_PARAMS, e.to_string()))?;
+                Ok(self.notes_json(query.filter(self.vault.published())))
+            }
+            "tag" => {
+                let tag = string_param(params, "tag")?;
+                let mut notes: Vec<&NoteMeta> = self
+                    .vault
+                    .published()
+                    .filter(|note| {
+                        note.tags.iter().any(|t| {
+                            t == tag
+                                || t.strip_prefix(tag)
+                                    .is_some_and(|rest| rest.starts_with('/'))
+                        })
+                    })
+                    .collect();
+                notes.sort_by(|a, b| a.name.cmp(&b.name));
+                Ok(self.notes_json(notes))
+            }
+            "search" => self.search(params),
+            "resolve" => {
+                let target = string_param(params, "target")?;
+                let links = &self.vault.indexes().links;
+                Ok(links
+                    .resolve(target)
+                    .and_then(|name| self.vault.published_note(name))
+                    .map_or(Json::Null, |note| note_to_json(note, links)))
+            }
+            "backlinks" => {
+                let target = string_param(params, "name")?;
+                let links = &self.vault.indexes().links;
+                let Some(name) = links.resolve(target) else {
+                    return Err(RpcError(INVALID_PARAMS, format!("找不到笔记 '{}'", target)));
+                };
+                Ok(backlinks_to_json(links, name))
+            }
+            "reload" => {
+                self.vault = (self.scan)().map_err(|e| RpcError(INTERNAL_ERROR, e.to_string()))?;
+                self.search_index = SearchIndex::load(self.vault.root());
+                Ok(Json::Object(vec![(
+                    "notes".to_string(),
+                    self.vault.published().count().into(),
+                )]))
+            }
+            _ => Err(RpcError(
+                METHOD_NOT_FOUND,
+                format!("未知的方法 '{}'", method),
+            )),
+        }
+    }
+
+    fn search(&self, params: &Json) -> Result<Json, RpcError> {
+        let text = string_param(params, "query")?;
+        let limit = match params.get("limit") {
+            Some(limit) => limit
+                .as_u64()
+                .ok_or_else(|| RpcError(INVALID_PARAMS, "limit 应为非负整数".to_string()))?
+                as usize,
+            None => DEFAULT_LIMIT,
+        };
+        let matches = search::search_with(
+            &self.vault,
+            self.search_index.as_ref(),
+            text,
+            &SearchOptions {
+                published: true,
+                ..SearchOptions::default()
+            },
+            limit,
+        )
+        .map_err(|e| RpcError(INVALID_PARAMS, e.to_string()))?;
+        Ok(Json::Array(matches.iter().map(|m| m.to_json()).collect()))
+    }
+
+    fn notes_json<'n>(&self, notes: impl IntoIterator<Item = &'n NoteMeta>) -> Json {
+        let links = &self.vault.indexes().links;
+        Json::Array(
+            notes
+                .into_iter()
+                .map(|note| note_to_json(note, links))
+                .collect(),
+        )
+    }
+}
+
+// 必需的字符串参数
+fn string_param<'p>(params: &'p Json, key: &str) -> Result<&'p str, RpcError> {
+    params
+        .get(key)
+        .and_then(Json::as_str)
+        .ok_or_else(|| RpcError(INVALID_PARAMS, format!("缺少字符串参数 {}", key)))
+}
+
+fn error_response(id: Json, code: i64, message: String) -> Json {
+    Json::Object(vec![
+        ("jsonrpc".to_string(), "2.0".into()),
+        ("id".to_string(), id),
+        (
+            "error".to_string(),
+            Json::Object(vec![
+                ("code".to_string(), Json::Number(code as f64)),
+                ("message".to_string(), message.into()),
+            ]),
+        ),
+    ])
+}
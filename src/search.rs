@@ -0,0 +1,234 @@
+// 全文搜索
+//
+// 有 gtx index --with-search 建立的搜索索引时按相关度排序，见 fulltext.rs；
+// 否则逐行扫描全部笔记。
+
+use std::fs;
+
+use crate::fulltext::{self, SearchIndex};
+use crate::json::Json;
+use crate::note::{NoteMeta, note_file_name};
+use crate::query::{self, Query};
+use crate::regex::{self, Regex};
+use crate::vault::Vault;
+
+/// 搜索选项
+#[derive(Debug, Default, Clone)]
+pub struct SearchOptions {
+    /// 忽略大小写
+    pub ignore_case: bool,
+    /// 将查询视为正则表达式，否则按字面量匹配
+    pub regex: bool,
+    /// 匹配行前后各显示的行数
+    pub context: usize,
+    /// 只搜索满足查询的笔记，写法同 gtx query，如 `tag:rust created:2024`
+    pub filter: Option<Query>,
+    /// 只搜索可以发布的笔记，见 `Vault::published`；gtx serve --api 与 gtx rpc 使用
+    pub published: bool,
+}
+
+/// 一处匹配
+#[derive(Debug, Clone)]
+pub struct SearchMatch {
+    pub name: String,
+    pub title: String,
+    /// 行号，从 1 开始
+    pub line: usize,
+    /// 匹配所在行的片段
+    pub snippet: String,
+    /// 匹配行之前的上下文（行号，内容）
+    pub before: Vec<(usize, String)>,
+    /// 匹配行之后的上下文（行号，内容）
+    pub after: Vec<(usize, String)>,
+}
+
+impl SearchMatch {
+    /// gtx rpc 与 gtx serve --api 返回的 JSON
+    pub fn to_json(&self) -> Json {
+        Json::Object(vec![
+            ("name".to_string(), self.name.as_str().into()),
+            ("path".to_string(), note_file_name(&self.name).into()),
+            ("title".to_string(), self.title.as_str().into()),
+            ("line".to_string(), self.line.into()),
+            ("snippet".to_string(), self.snippet.as_str().into()),
+        ])
+    }
+}
+
+// 片段的最大显示字符数
+const SNIPPET_WIDTH: usize = 80;
+
+/// 在所有笔记的全文（含文件头）中逐行搜索，结果按文件名与行号排序
+pub fn search(
+    vault: &Vault,
+    query: &str,
+    options: &SearchOptions,
+) -> Result<Vec<SearchMatch>, regex::Error> {
+    let pattern = if options.regex {
+        query.to_string()
+    } else {
+        regex::escape(query)
+    };
+    let matcher = Regex::with_case(&pattern, options.ignore_case)?;
+
+    let mut notes: Vec<_> = vault
+        .notes()
+        .filter(|note| accepts(vault, options, note))
+        .collect();
+    notes.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut matches = Vec::new();
+    for note in notes {
+        let file_path = vault.note_path(&note.name);
+        let content = match fs::read_to_string(&file_path) {
+            Ok(content) if note.queries => query::blank_blocks(&content).into_owned(),
+            Ok(content) => content,
+            Err(e) => {
+                eprintln!("读取文件失败 {}: {}", file_path.display(), e);
+                continue;
+            }
+        };
+
+        let lines: Vec<&str> = content.lines().collect();
+        for (i, line) in lines.iter().enumerate() {
+            let Some((start, end)) = matcher.find(line) else {
+                continue;
+            };
+            let context = |range: std::ops::Range<usize>| {
+                range
+                    .map(|j| (j + 1, lines[j].to_string()))
+                    .collect::<Vec<_>>()
+            };
+            matches.push(SearchMatch {
+                name: note.name.clone(),
+                title: note.title.clone(),
+                line: i + 1,
+                snippet: snippet(line, start, end),
+                before: context(i.saturating_sub(options.context)..i),
+                after: context(i + 1..(i + 1 + options.context).min(lines.len())),
+            });
+        }
+    }
+
+    Ok(matches)
+}
+
+/// 有搜索索引且查询中有可检索的词时用 `ranked`，否则用 `search`，最多返回 limit 处匹配
+pub fn search_with(
+    vault: &Vault,
+    index: Option<&SearchIndex>,
+    query: &str,
+    options: &SearchOptions,
+    limit: usize,
+) -> Result<Vec<SearchMatch>, regex::Error> {
+    match index {
+        Some(index) if !options.regex && !fulltext::parse_query(query).is_empty() => {
+            Ok(ranked(vault, index, query, options, limit))
+        }
+        _ => {
+            let mut matches = search(vault, query, options)?;
+            matches.truncate(limit);
+            Ok(matches)
+        }
+    }
+}
+
+/// 用搜索索引查找含有查询中全部词与短语的笔记，按 BM25 得分从高到低排序
+///
+/// 每篇笔记给出第一处匹配所在的行，最多 limit 篇。索引中已不存在于目录的笔记被跳过。
+pub fn ranked(
+    vault: &Vault,
+    index: &SearchIndex,
+    query: &str,
+    options: &SearchOptions,
+    limit: usize,
+) -> Vec<SearchMatch> {
+    let phrases = fulltext::parse_query(query);
+    let notes: std::collections::HashMap<&str, &NoteMeta> = vault
+        .notes()
+        .filter(|note| accepts(vault, options, note))
+        .map(|note| (note.name.as_str(), note))
+        .collect();
+
+    let mut matches = Vec::new();
+    for (name, _) in index.search(&phrases) {
+        if matches.len() == limit {
+            break;
+        }
+        let Some(note) = notes.get(name.as_str()) else {
+            continue;
+        };
+        let file_path = vault.note_path(&note.name);
+        let content = match fs::read_to_string(&file_path) {
+            Ok(content) if note.queries => query::blank_blocks(&content).into_owned(),
+            Ok(content) => content,
+            Err(e) => {
+                eprintln!("读取文件失败 {}: {}", file_path.display(), e);
+                continue;
+            }
+        };
+
+        // 笔记在建立索引之后修改过时可能找不到匹配的行，显示第一行
+        let lines: Vec<&str> = content.lines().collect();
+        let (i, start, end) = lines
+            .iter()
+            .enumerate()
+            .find_map(|(i, line)| {
+                phrases
+                    .iter()
+                    .find_map(|phrase| fulltext::find_phrase(line, phrase))
+                    .map(|(start, end)| (i, start, end))
+            })
+            .unwrap_or((0, 0, 0));
+        let line = lines.get(i).copied().unwrap_or_default();
+        let context = |range: std::ops::Range<usize>| {
+            range
+                .map(|j| (j + 1, lines[j].to_string()))
+                .collect::<Vec<_>>()
+        };
+        matches.push(SearchMatch {
+            name: note.name.clone(),
+            title: note.title.clone(),
+            line: i + 1,
+            snippet: snippet(line, start, end),
+            before: context(i.saturating_sub(options.context)..i),
+            after: context((i + 1).min(lines.len())..(i + 1 + options.context).min(lines.len())),
+        });
+    }
+    matches
+}
+
+// 笔记是否满足筛选条件
+fn accepts(vault: &Vault, options: &SearchOptions, note: &NoteMeta) -> bool {
+    (!options.published || vault.published_note(&note.name).is_some())
+        && options
+            .filter
+            .as_ref()
+            .is_none_or(|filter| filter.matches(note))
+}
+
+// 截取匹配附近的内容，过长时两端以 … 省略
+fn snippet(line: &str, start: usize, end: usize) -> String {
+    let line_chars = line.chars().count();
+    if line_chars <= SNIPPET_WIDTH {
+        return line.trim().to_string();
+    }
+
+    let match_start = line[..start].chars().count();
+    let match_len = line[start..end].chars().count();
+    // 匹配前保留约三分之一的宽度
+    let from = match_start
+        .saturating_sub(SNIPPET_WIDTH / 3)
+        .min(line_chars - SNIPPET_WIDTH.max(match_len).min(line_chars));
+    let to = (from + SNIPPET_WIDTH.max(match_len)).min(line_chars);
+
+    let mut result = String::new();
+    if from > 0 {
+        result.push('…');
+    }
+    result.extend(line.chars().skip(from).take(to - from));
+    if to < line_chars {
+        result.push('…');
+    }
+    result
+}
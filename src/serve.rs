@@ -0,0 +1,311 @@
+// 本地预览服务器：在 localhost 上以网页形式浏览博客目录
+//
+// 页面由 site 模块渲染并保存在内存中；站点之外的路径按博客目录下的文件返回，
+// 只限于笔记中引用的图片等附件与样式表、脚本等资源，笔记原文（包括站点中略去的草稿）不会返回。开启自动刷新时，每个页面嵌入一段脚本，
+// 定期请求 /__gtx/version，版本变化后重新加载页面。开启 API 时 /api/ 下为
+// JSON 接口，见 api.rs。
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use crate::api::{API_PREFIX, Api};
+use crate::json::Json;
+use crate::markdown::escape;
+use crate::vault::Vault;
+
+const VERSION_PATH: &str = "/__gtx/version";
+
+/// 预览服务器共享的状态
+pub struct Preview {
+    root: PathBuf,
+    pages: Mutex<BTreeMap<String, String>>,
+    /// 每次更新页面加一，浏览器据此判断是否需要刷新
+    version: AtomicU64,
+    live_reload: bool,
+    /// 开启时应答 /api/ 下的请求
+    api: Option<Mutex<Api>>,
+}
+
+impl Preview {
+    /// root 为博客目录，pages 为 `site::render_site` 渲染的页面
+    pub fn new(root: &Path, pages: BTreeMap<String, String>, live_reload: bool) -> Preview {
+        Preview {
+            root: root.to_path_buf(),
+            pages: Mutex::new(pages),
+            version: AtomicU64::new(0),
+            live_reload,
+            api: None,
+        }
+    }
+
+    /// 同时在 /api/ 下提供 vault 的 JSON 接口
+    pub fn with_api(mut self, vault: Vault) -> Preview {
+        self.api = Some(Mutex::new(Api::new(vault)));
+        self
+    }
+
+    /// 替换 JSON 接口使用的索引，没有开启接口时忽略
+    pub fn update_api(&self, vault: Vault) {
+        if let Some(api) = &self.api {
+            *api.lock().unwrap() = Api::new(vault);
+        }
+    }
+
+    /// 替换全部页面，已打开的页面在开启自动刷新时会重新加载
+    pub fn update(&self, pages: BTreeMap<String, String>) {
+        *self.pages.lock().unwrap() = pages;
+        self.version.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn page(&self, path: &str) -> Option<String> {
+        let html = self.pages.lock().unwrap().get(path)?.clone();
+        if !self.live_reload {
+            return Some(html);
+        }
+        let script = format!(
+            "<script>\n(function () {{\n  var version = \"{}\";\n  setInterval(function () {{\n    \
+             fetch(\"{}\").then(function (r) {{ return r.text(); }}).then(function (v) {{\n      \
+             if (v !== version) location.reload();\n    }}).catch(function () {{}});\n  }}, 1000);\n\
+             }})();\n</script>\n",
+            self.version.load(Ordering::SeqCst),
+            VERSION_PATH
+        );
+        Some(html.replacen("</body>", &format!("{}</body>", script), 1))
+    }
+}
+
+/// 在 listener 上处理请求，每个连接一个线程，直到出错才返回
+pub fn serve(listener: TcpListener, preview: Arc<Preview>) -> io::Result<()> {
+    for stream in listener.incoming() {
+        let stream = stream?;
+        let preview = Arc::clone(&preview);
+        thread::spawn(move || {
+            // 浏览器提前断开连接等错误只影响这一个请求
+            let _ = handle(stream, &preview);
+        });
+    }
+    Ok(())
+}
+
+fn handle(stream: TcpStream, preview: &Preview) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // 读完请求头，不需要其中的内容
+    let mut line = String::new();
+    while reader.read_line(&mut line)? > 0 && line.trim_end() != "" {
+        line.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return respond(
+            stream,
+            "400 Bad Request",
+            "text/plain; charset=utf-8",
+            b"400",
+        );
+    };
+    if method != "GET" && method != "HEAD" {
+        return respond(
+            stream,
+            "405 Method Not Allowed",
+            "text/plain; charset=utf-8",
+            b"405",
+        );
+    }
+    let head_only = method == "HEAD";
+    let send = |stream: TcpStream, status: &str, content_type: &str, body: &[u8]| {
+        respond(
+            stream,
+            status,
+            content_type,
+            if head_only { &[] } else { body },
+        )
+    };
+
+    let target = target.split('#').next().unwrap_or("/");
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    if let (Some(api), Some(rest)) = (&preview.api, path.strip_prefix(API_PREFIX)) {
+        let (status, json) = match percent_decode(rest) {
+            Some(rest) => api.lock().unwrap().get(&rest, query),
+            None => (
+                "400 Bad Request",
+                Json::Object(vec![("error".to_string(), "无法解码的路径".into())]),
+            ),
+        };
+        return send(
+            stream,
+            status,
+            "application/json; charset=utf-8",
+            json.to_string().as_bytes(),
+        );
+    }
+    if path == VERSION_PATH {
+        let version = preview.version.load(Ordering::SeqCst).to_string();
+        return send(
+            stream,
+            "200 OK",
+            "text/plain; charset=utf-8",
+            version.as_bytes(),
+        );
+    }
+    let Some(path) = percent_decode(path) else {
+        return send(
+            stream,
+            "400 Bad Request",
+            "text/plain; charset=utf-8",
+            b"400",
+        );
+    };
+    let mut path = path.trim_start_matches('/').to_string();
+    if path.is_empty() || path.ends_with('/') {
+        path.push_str("index.html");
+    }
+
+    if let Some(html) = preview.page(&path) {
+        return send(
+            stream,
+            "200 OK",
+            "text/html; charset=utf-8",
+            html.as_bytes(),
+        );
+    }
+    // 不允许访问博客目录之外与隐藏的文件
+    let relative = Path::new(&path);
+    let safe = relative.components().all(|component| {
+        matches!(component, Component::Normal(name) if !name.to_string_lossy().starts_with('.'))
+    });
+    if safe
+        && let Some(content_type) = asset_type(relative)
+        && let Ok(bytes) = fs::read(preview.root.join(relative))
+    {
+        return send(stream, "200 OK", content_type, &bytes);
+    }
+
+    let body = format!(
+        "<!DOCTYPE html>\n<meta charset=\"utf-8\">\n<title>404</title>\n\
+         <p>找不到页面 {}，<a href=\"/index.html\">返回首页</a></p>\n",
+        escape(&path)
+    );
+    send(
+        stream,
+        "404 Not Found",
+        "text/html; charset=utf-8",
+        body.as_bytes(),
+    )
+}
+
+fn respond(mut stream: TcpStream, status: &str, content_type: &str, body: &[u8]) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\n\
+         Cache-Control: no-cache\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    )?;
+    stream.write_all(body)?;
+    stream.flush()
+}
+
+// 可以直接返回的附件与资源文件的类型；笔记、页面与其他文件返回 None
+fn asset_type(path: &Path) -> Option<&'static str> {
+    let ext = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default();
+    Some(match ext.as_str() {
+        "css" => "text/css; charset=utf-8",
+        "js" => "text/javascript; charset=utf-8",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "webp" => "image/webp",
+        "avif" => "image/avif",
+        "ico" => "image/x-icon",
+        "pdf" => "application/pdf",
+        "mp3" => "audio/mpeg",
+        "ogg" => "audio/ogg",
+        "wav" => "audio/wav",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        _ => return None,
+    })
+}
+
+/// 解码地址中的 %XX，结果不是合法的 UTF-8 时返回 None
+pub fn percent_decode(path: &str) -> Option<String> {
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = path.get(i + 1..i + 3)?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    // 向 serve 发出一个 GET 请求，返回状态行与正文
+    fn get(addr: std::net::SocketAddr, path: &str) -> (String, String) {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(stream, "GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        (head.lines().next().unwrap().to_string(), body.to_string())
+    }
+
+    #[test]
+    fn only_assets_are_served_from_the_vault() {
+        let dir = std::env::temp_dir().join(format!("gtx-serve-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("attachments")).unwrap();
+        fs::create_dir_all(dir.join(".gtx")).unwrap();
+        fs::write(dir.join("draft.md"), "---\nDraft: true\n---\n秘密\n").unwrap();
+        fs::write(dir.join("note.org"), "#+TITLE: 秘密\n").unwrap();
+        fs::write(dir.join("attachments/a.PNG"), "png").unwrap();
+        fs::write(dir.join(".gtx/style.css"), "css").unwrap();
+
+        let pages = BTreeMap::from([("index.html".to_string(), "<body>首页</body>".to_string())]);
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let preview = Arc::new(Preview::new(&dir, pages, false));
+        thread::spawn(move || serve(listener, preview));
+
+        assert_eq!(
+            get(addr, "/"),
+            (
+                "HTTP/1.1 200 OK".to_string(),
+                "<body>首页</body>".to_string()
+            )
+        );
+        assert_eq!(get(addr, "/attachments/a.PNG").1, "png");
+        for path in ["/draft.md", "/note.org", "/.gtx/style.css", "/../draft.md"] {
+            let (status, body) = get(addr, path);
+            assert_eq!(status, "HTTP/1.1 404 Not Found", "{}", path);
+            assert!(!body.contains("秘密"), "{}", path);
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
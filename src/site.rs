@@ -0,0 +1,484 @@
+// 静态站点导出：把笔记渲染为 HTML，并生成与 index.md 结构相同的 index.html
+//
+// 目录结构:
+//   index.html          标签与日期索引
+//   tag-cloud.html      标签云，标签的字号按笔记数加权
+//   <笔记>.html         每篇笔记
+//   tags/<标签>.html    标签页，层级标签位于子目录中，文件名见 `output::tag_slug`
+//   dates/<日期>.html   日期页，以及 dates/2024.html、dates/2024-05.html 年月汇总页
+//   sitemap.xml         全部页面的地址与最后修改日期
+//   robots.txt          可选，允许抓取全部页面并指向 sitemap.xml
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::date::Date;
+use crate::index::{CLOUD_WEIGHTS, Index, IndexSet, child_tags, date_tree, tag_levels};
+use crate::markdown::{self, encode_url, escape};
+use crate::note::NoteMeta;
+use crate::output::{tag_slug, write_atomic};
+use crate::vault::Vault;
+
+const STYLE: &str = "\
+body { max-width: 48rem; margin: 2rem auto; padding: 0 1rem; font-family: sans-serif; line-height: 1.6; color: #222; }
+nav { margin-bottom: 1.5rem; }
+a { color: #1f5fa8; text-decoration: none; }
+a:hover { text-decoration: underline; }
+.meta { color: #666; }
+.tag { margin-right: 0.5rem; }
+.broken-link { color: #b33; border-bottom: 1px dashed #b33; }
+.cloud { line-height: 2.2; }
+.cloud a { margin-right: 0.75rem; white-space: nowrap; }
+.grid { display: flex; flex-wrap: wrap; gap: 0.25rem 1.25rem; padding: 0; list-style: none; }
+pre { background: #f5f5f5; padding: 0.75rem; overflow-x: auto; }
+code { background: #f5f5f5; padding: 0 0.2rem; }
+pre code { padding: 0; }
+blockquote { margin-left: 0; padding-left: 1rem; border-left: 3px solid #ddd; color: #555; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #ddd; padding: 0.25rem 0.5rem; }
+img { max-width: 100%; }
+";
+
+/// 站点地图的文件名，位于站点根目录
+pub const SITEMAP: &str = "sitemap.xml";
+
+/// 将 vault 导出为静态站点，写入 dir，返回写入的页面数，不含 sitemap.xml 与 robots.txt
+///
+/// base_url 为站点的绝对地址，如 `https://example.com/notes/`，sitemap.xml 中的地址以它开头；
+/// 站点地图必须使用绝对地址，为 None 时不写入 sitemap.xml 与 robots.txt。robots 为 true 时
+/// 同时写入 robots.txt。图片等附件不会复制到 dir。
+pub fn write_site(
+    vault: &Vault,
+    dir: &Path,
+    base_url: Option<&str>,
+    robots: bool,
+) -> io::Result<usize> {
+    let pages = render_site(vault)?;
+    for (page, html) in &pages {
+        let page_path = dir.join(page);
+        fs::create_dir_all(page_path.parent().unwrap())?;
+        write_atomic(&page_path, html)?;
+    }
+    let Some(base_url) = base_url else {
+        return Ok(pages.len());
+    };
+    let base_url = base_url_prefix(base_url);
+    write_atomic(
+        &dir.join(SITEMAP),
+        &sitemap(vault, pages.keys().map(String::as_str), &base_url),
+    )?;
+    if robots {
+        write_atomic(
+            &dir.join("robots.txt"),
+            &format!(
+                "User-agent: *\nAllow: /\n\nSitemap: {}{}\n",
+                base_url, SITEMAP
+            ),
+        )?;
+    }
+    Ok(pages.len())
+}
+
+/// sitemap.xml 的内容，pages 为相对站点根目录的页面路径
+///
+/// 每篇笔记的最后修改日期取 Created 与 Updated 中较晚的一个；标签页、日期页与年月汇总页取其中
+/// 笔记的最晚日期，index.html 与 tag-cloud.html 取全部笔记的最晚日期。没有日期的页面不写 lastmod。
+pub fn sitemap<'a>(vault: &Vault, pages: impl Iterator<Item = &'a str>, base_url: &str) -> String {
+    let mut lastmod: HashMap<String, Date> = HashMap::new();
+    let mut latest = |page: String, date: Date| {
+        let entry = lastmod.entry(page).or_insert(date);
+        *entry = (*entry).max(date);
+    };
+    for note in vault.published() {
+        let Some(date) = [&note.created, &note.updated]
+            .into_iter()
+            .flatten()
+            .map(|(date, _)| *date)
+            .max()
+        else {
+            continue;
+        };
+        latest(format!("{}.html", note.name), date);
+        for tag in &note.tags {
+            for level in tag_levels(tag) {
+                latest(format!("tags/{}.html", tag_slug(level)), date);
+            }
+        }
+        if let Some((created, _)) = &note.created {
+            for key in [created.to_string(), created.month_key(), created.year_key()] {
+                latest(format!("dates/{}.html", key), date);
+            }
+        }
+        latest("index.html".to_string(), date);
+        latest("tag-cloud.html".to_string(), date);
+    }
+
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n",
+    );
+    for page in pages {
+        let _ = write!(
+            xml,
+            "  <url>\n    <loc>{}</loc>\n",
+            escape(&format!("{}{}", base_url, percent_encode(page)))
+        );
+        if let Some(date) = lastmod.get(page) {
+            let _ = writeln!(
+                xml,
+                "    <lastmod>{:04}-{:02}-{:02}</lastmod>",
+                date.year, date.month, date.day
+            );
+        }
+        xml.push_str("  </url>\n");
+    }
+    xml.push_str("</urlset>\n");
+    xml
+}
+
+// 站点地图要求地址完全转义：除字母、数字、`-._~` 与 / 外的字节都写作 %XX，非 ASCII 字符按 UTF-8 编码
+fn percent_encode(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~/".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            let _ = write!(encoded, "%{:02X}", byte);
+        }
+    }
+    encoded
+}
+
+// 站点地址以 / 结尾，便于直接拼接页面路径；为空时保持为空
+fn base_url_prefix(base_url: &str) -> String {
+    if base_url.ends_with('/') {
+        base_url.to_string()
+    } else {
+        format!("{}/", base_url)
+    }
+}
+
+/// 渲染站点的全部页面，键为相对站点根目录、以 / 分隔的路径
+///
+/// 维基链接按笔记文件名、笔记标题、标签、日期（含年、月）的顺序解析为相对链接。
+pub fn render_site(vault: &Vault) -> io::Result<BTreeMap<String, String>> {
+    let indexes = vault.indexes();
+    let mut pages = BTreeMap::new();
+
+    let mut notes: Vec<&NoteMeta> = vault.published().collect();
+    notes.sort_by(|a, b| a.name.cmp(&b.name));
+    let backlinks = indexes.links.backlinks();
+    for note in notes {
+        let body = vault.note_body(note)?;
+        // 子目录中的笔记的页面位于同样的子目录下，站内链接需要先回到站点根目录
+        let depth = note.name.matches('/').count();
+        let up = "../".repeat(depth);
+
+        let mut html = format!("<h1>{}</h1>\n<p class=\"meta\">", escape(&note.title));
+        if !note.aliases.is_empty() {
+            let _ = write!(html, "别名: {} ", escape(&note.aliases.join(", ")));
+        }
+        if let Some((date, time)) = &note.created {
+            let _ = write!(
+                html,
+                "<a href=\"{}dates/{}.html\">{}</a> {} ",
+                up,
+                date,
+                date,
+                escape(time)
+            );
+        }
+        for tag in &note.tags {
+            let _ = write!(
+                html,
+                "<a class=\"tag\" href=\"{}tags/{}.html\">#{}</a>",
+                up,
+                encode_url(&tag_slug(tag)),
+                escape(tag)
+            );
+        }
+        html.push_str("</p>\n");
+        html.push_str(&markdown::to_html(&body, &|target| {
+            resolve(vault, target).map(|href| format!("{}{}", up, href))
+        }));
+
+        if let Some(sources) = backlinks.get(note.name.as_str()) {
+            html.push_str("<h2>反向链接</h2>\n<ul>\n");
+            for link in sources {
+                let _ = writeln!(
+                    html,
+                    "<li><a href=\"{}{}.html\">{}</a></li>",
+                    up,
+                    encode_url(&link.source_name),
+                    escape(&link.source_title)
+                );
+            }
+            html.push_str("</ul>\n");
+        }
+        pages.insert(
+            format!("{}.html", note.name),
+            page(&note.title, depth, &html),
+        );
+    }
+
+    let tags = &indexes.tags;
+    for tag in tags.get_inputs() {
+        let depth = 1 + tag.matches('/').count();
+        let up = "../".repeat(depth);
+        let mut html = format!("<h1>#{}</h1>\n<ul>\n", escape(tag));
+        let mut files = tags.get_files_by_i(tag).cloned().unwrap_or_default();
+        files.sort();
+        for (name, title, _) in &files {
+            let _ = writeln!(
+                html,
+                "<li><a href=\"{}{}.html\">{}</a></li>",
+                up,
+                encode_url(name),
+                escape(title)
+            );
+        }
+        html.push_str("</ul>\n");
+        let children = child_tags(tags, tag);
+        if !children.is_empty() {
+            html.push_str("<h2>子标签</h2>\n<ul>\n");
+            for child in children {
+                let _ = writeln!(
+                    html,
+                    "<li><a href=\"{}tags/{}.html\">{}</a>({})</li>",
+                    up,
+                    encode_url(&tag_slug(child)),
+                    escape(tag_leaf(child)),
+                    tags.get_i_count(child)
+                );
+            }
+            html.push_str("</ul>\n");
+        }
+        pages.insert(
+            format!("tags/{}.html", tag_slug(tag)),
+            page(tag, depth, &html),
+        );
+    }
+
+    let dates = &indexes.dates;
+    for date in dates.get_inputs() {
+        let mut html = format!("<h1>{}</h1>\n", escape(date));
+        date_list(&mut html, dates, date, false);
+        pages.insert(format!("dates/{}.html", date), page(date, 1, &html));
+    }
+    for (year, months) in date_tree(dates) {
+        let mut year_html = format!("<h1>{}</h1>\n", escape(&year));
+        for (month, days) in months {
+            let mut month_html = format!("<h1>{}</h1>\n", escape(&month));
+            for date in &days {
+                let _ = writeln!(
+                    month_html,
+                    "<h2><a href=\"{}.html\">{}</a></h2>",
+                    date, date
+                );
+                date_list(&mut month_html, dates, date, false);
+            }
+            pages.insert(
+                format!("dates/{}.html", month),
+                page(&month, 1, &month_html),
+            );
+
+            let _ = writeln!(
+                year_html,
+                "<h2><a href=\"{}.html\">{}</a></h2>",
+                month, month
+            );
+            for date in &days {
+                date_list(&mut year_html, dates, date, true);
+            }
+        }
+        pages.insert(format!("dates/{}.html", year), page(&year, 1, &year_html));
+    }
+
+    pages.insert(
+        "tag-cloud.html".to_string(),
+        page("标签云", 0, &tag_cloud_html(indexes)),
+    );
+    pages.insert(
+        "index.html".to_string(),
+        page("index", 0, &index_html(indexes)),
+    );
+    Ok(pages)
+}
+
+// 与 index.md 相同的结构：标签按笔记数从多到少，有层级标签时输出标签树；日期从新到旧
+fn index_html(indexes: &IndexSet) -> String {
+    let tags = &indexes.tags;
+    let mut tags_data: Vec<(&str, usize)> = tags
+        .get_inputs()
+        .iter()
+        .map(|tag| (tag.as_str(), tags.get_i_count(tag)))
+        .collect();
+    tags_data.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+
+    let mut html = String::from("<h1>Tags</h1>\n<p><a href=\"tag-cloud.html\">标签云</a></p>\n");
+    if tags_data.iter().any(|(tag, _)| tag.contains('/')) {
+        tag_tree(&mut html, &tags_data, None);
+    } else {
+        html.push_str("<ul class=\"grid\">\n");
+        for (tag, count) in &tags_data {
+            let _ = writeln!(
+                html,
+                "<li><a href=\"tags/{}.html\">{}</a>({})</li>",
+                encode_url(&tag_slug(tag)),
+                escape(tag),
+                count
+            );
+        }
+        html.push_str("</ul>\n");
+    }
+
+    // 日期按年 → 月 → 日从新到旧排列
+    let dates = &indexes.dates;
+    html.push_str("<h1>Dates</h1>\n");
+    for (year, months) in date_tree(dates).iter().rev() {
+        let count: usize = months
+            .values()
+            .flatten()
+            .map(|date| dates.get_i_count(date))
+            .sum();
+        let _ = writeln!(
+            html,
+            "<h2><a href=\"dates/{}.html\">{}</a>({})</h2>",
+            year, year, count
+        );
+        for (month, days) in months.iter().rev() {
+            let count: usize = days.iter().map(|date| dates.get_i_count(date)).sum();
+            let _ = writeln!(
+                html,
+                "<h3><a href=\"dates/{}.html\">{}</a>({})</h3>\n<ul class=\"grid\">",
+                month, month, count
+            );
+            for date in days.iter().rev() {
+                let _ = writeln!(
+                    html,
+                    "<li><a href=\"dates/{}.html\">{}</a>({})</li>",
+                    date,
+                    date,
+                    dates.get_i_count(date)
+                );
+            }
+            html.push_str("</ul>\n");
+        }
+    }
+    html
+}
+
+// 标签云：字号从 1em 起按权重逐级放大
+fn tag_cloud_html(indexes: &IndexSet) -> String {
+    let mut html = String::from("<h1>标签云</h1>\n<p class=\"cloud\">\n");
+    for tag in indexes.tag_cloud() {
+        let size = 1.0 + (tag.weight - 1) as f64 * 1.5 / (CLOUD_WEIGHTS - 1) as f64;
+        let _ = writeln!(
+            html,
+            "<a href=\"tags/{}.html\" style=\"font-size: {:.2}em\" title=\"{} 篇笔记\">{}</a>",
+            encode_url(&tag_slug(tag.tag)),
+            size,
+            tag.count,
+            escape(tag.tag)
+        );
+    }
+    html.push_str("</p>\n");
+    html
+}
+
+// 日期页中一天的笔记列表，按时间排列；with_date 时带上日期，用于年汇总页
+fn date_list(html: &mut String, dates: &Index, date: &str, with_date: bool) {
+    let mut files = dates.get_files_by_i(date).cloned().unwrap_or_default();
+    files.sort_by(|a, b| a.2.cmp(&b.2));
+    html.push_str("<ul>\n");
+    for (name, title, time) in &files {
+        let when = if with_date {
+            format!("{} {}", date, time)
+        } else {
+            time.clone()
+        };
+        let _ = writeln!(
+            html,
+            "<li>{} <a href=\"../{}.html\">{}</a></li>",
+            escape(when.trim_end()),
+            encode_url(name),
+            escape(title)
+        );
+    }
+    html.push_str("</ul>\n");
+}
+
+// 以嵌套列表输出 parent 下的标签树
+fn tag_tree(html: &mut String, sorted: &[(&str, usize)], parent: Option<&str>) {
+    let children: Vec<&(&str, usize)> = sorted
+        .iter()
+        .filter(|(tag, _)| match parent {
+            None => !tag.contains('/'),
+            Some(parent) => tag
+                .strip_prefix(parent)
+                .and_then(|rest| rest.strip_prefix('/'))
+                .is_some_and(|rest| !rest.contains('/')),
+        })
+        .collect();
+    if children.is_empty() {
+        return;
+    }
+    html.push_str("<ul>\n");
+    for (tag, count) in children {
+        let _ = write!(
+            html,
+            "<li><a href=\"tags/{}.html\">{}</a>({})",
+            encode_url(&tag_slug(tag)),
+            escape(tag_leaf(tag)),
+            count
+        );
+        tag_tree(html, sorted, Some(tag));
+        html.push_str("</li>\n");
+    }
+    html.push_str("</ul>\n");
+}
+
+// 笔记页面中维基链接目标对应的地址
+fn resolve(vault: &Vault, target: &str) -> Option<String> {
+    let indexes = vault.indexes();
+    // 草稿不导出时也是链接目标，这时指向草稿的链接不能解析
+    if let Some(name) = indexes.links.resolve(target) {
+        return vault
+            .published_note(name)
+            .map(|_| format!("{}.html", encode_url(name)));
+    }
+    if indexes.tags.get_i_count(target) > 0 {
+        return Some(format!("tags/{}.html", encode_url(&tag_slug(target))));
+    }
+    let rollups = date_tree(&indexes.dates);
+    if indexes.dates.get_i_count(target) > 0
+        || rollups.contains_key(target)
+        || rollups.values().any(|months| months.contains_key(target))
+    {
+        return Some(format!("dates/{}.html", encode_url(target)));
+    }
+    (target == "index").then(|| "index.html".to_string())
+}
+
+// 层级标签的最后一级
+fn tag_leaf(tag: &str) -> &str {
+    tag.rsplit('/').next().unwrap_or(tag)
+}
+
+// 完整的 HTML 页面，depth 为页面所在的子目录层数
+fn page(title: &str, depth: usize, body: &str) -> String {
+    let up = "../".repeat(depth);
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"zh-CN\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{}</title>\n<style>\n{}</style>\n</head>\n<body>\n\
+         <nav><a href=\"{}index.html\">首页</a></nav>\n<main>\n{}</main>\n</body>\n</html>\n",
+        escape(title),
+        STYLE,
+        up,
+        body
+    )
+}
@@ -0,0 +1,282 @@
+// SQLite 索引：把笔记、标签、别名与链接写入 <目录>/.gtx/index.db，便于直接用 SQL 查询
+//
+// 不链接 SQLite 库，而是生成 SQL 语句交给 sqlite3 命令执行，因此需要系统中装有 sqlite3；
+// 构建开始前用 `check_available` 确认，找不到时不写入任何页面。写入的值都经 `quote` 转为
+// 字符串字面量，控制字符写作 char(N)，脚本中不会出现值里的换行与 NUL。
+//
+// 表结构:
+//   notes(name, title, date, time, words)   date 为 YYYYMMDD，没有创建时间时为 NULL
+//   tags(note, tag)                         层级标签只记录原样的标签
+//   aliases(note, alias)
+//   links(source, target, line)             target 为链接原文
+//   resolved_links(source, target, line, note)  视图，note 为按文件名、标题、别名解析出的笔记
+//   state(key, value)                       gtx 自用，记录数据库对应的缓存摘要
+
+use std::collections::HashSet;
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::cache::Cache;
+use crate::i18n;
+use crate::note::NoteMeta;
+
+/// 数据库文件的位置，相对博客目录
+pub const INDEX_DB: &str = ".gtx/index.db";
+
+// 表结构变化时递增，版本不同的数据库会被重建
+const SCHEMA_VERSION: u32 = 1;
+
+const SCHEMA: &str = "\
+DROP VIEW IF EXISTS resolved_links;
+DROP TABLE IF EXISTS notes;
+DROP TABLE IF EXISTS tags;
+DROP TABLE IF EXISTS aliases;
+DROP TABLE IF EXISTS links;
+DROP TABLE IF EXISTS state;
+CREATE TABLE notes (name TEXT PRIMARY KEY, title TEXT NOT NULL, date TEXT, time TEXT, words INTEGER NOT NULL);
+CREATE TABLE tags (note TEXT NOT NULL, tag TEXT NOT NULL);
+CREATE TABLE aliases (note TEXT NOT NULL, alias TEXT NOT NULL);
+CREATE TABLE links (source TEXT NOT NULL, target TEXT NOT NULL, line INTEGER NOT NULL);
+CREATE TABLE state (key TEXT PRIMARY KEY, value TEXT NOT NULL);
+CREATE INDEX tags_tag ON tags (tag);
+CREATE INDEX tags_note ON tags (note);
+CREATE INDEX aliases_note ON aliases (note);
+CREATE INDEX links_source ON links (source);
+CREATE INDEX links_target ON links (target);
+CREATE INDEX notes_date ON notes (date);
+CREATE VIEW resolved_links AS
+SELECT links.source, links.target, links.line,
+       COALESCE(
+           (SELECT name FROM notes WHERE name = links.target),
+           (SELECT name FROM notes WHERE title = links.target ORDER BY name LIMIT 1),
+           (SELECT note FROM aliases WHERE alias = links.target ORDER BY note LIMIT 1)
+       ) AS note
+FROM links;
+";
+
+/// 按 new 更新数据库；old 为上次构建的缓存，为 None 时重建整个数据库
+///
+/// 增量更新时只删除并重新写入变化了的笔记。数据库不是由 old 生成的（例如中间有几次构建
+/// 没有使用 SQLite 索引）或表结构版本不同时，同样重建整个数据库。
+pub fn update(db: &Path, old: Option<&Cache>, new: &Cache) -> io::Result<()> {
+    let incremental = match old {
+        Some(old) => db.exists() && stored_digest(db)?.is_some_and(|d| d == digest(old)),
+        None => false,
+    };
+
+    let mut script = String::from("BEGIN;\n");
+    let mut names: Vec<&String> = new.notes.keys().collect();
+    match old {
+        Some(old) if incremental => {
+            // 已删除或修改过的笔记先删去旧记录
+            let removed = old
+                .notes
+                .keys()
+                .filter(|name| !new.notes.contains_key(*name));
+            let changed: Vec<&String> = names
+                .iter()
+                .copied()
+                .filter(|name| {
+                    old.notes.get(*name).map(|note| &note.meta) != Some(&new.notes[*name].meta)
+                })
+                .collect();
+            for name in removed.chain(changed.iter().copied()) {
+                let name = quote(name);
+                for (table, column) in [
+                    ("notes", "name"),
+                    ("tags", "note"),
+                    ("aliases", "note"),
+                    ("links", "source"),
+                ] {
+                    script.push_str(&format!(
+                        "DELETE FROM {} WHERE {} = {};\n",
+                        table, column, name
+                    ));
+                }
+            }
+            names = changed;
+        }
+        _ => {
+            script.push_str(SCHEMA);
+            script.push_str(&format!("PRAGMA user_version = {};\n", SCHEMA_VERSION));
+        }
+    }
+
+    names.sort();
+    for name in names {
+        insert_note(&mut script, &new.notes[name].meta);
+    }
+    script.push_str(&format!(
+        "INSERT OR REPLACE INTO state VALUES ('cache', {});\nCOMMIT;\n",
+        quote(&digest(new))
+    ));
+    run(db, &script).map(|_| ())
+}
+
+fn insert_note(script: &mut String, meta: &NoteMeta) {
+    let name = quote(&meta.name);
+    let (date, time) = match &meta.created {
+        Some((date, time)) => (quote(&date.to_string()), quote(time)),
+        None => ("NULL".to_string(), "NULL".to_string()),
+    };
+    script.push_str(&format!(
+        "INSERT INTO notes VALUES ({}, {}, {}, {}, {});\n",
+        name,
+        quote(&meta.title),
+        date,
+        time,
+        meta.words
+    ));
+    let tags: HashSet<&String> = meta.tags.iter().collect();
+    for tag in tags {
+        script.push_str(&format!(
+            "INSERT INTO tags VALUES ({}, {});\n",
+            name,
+            quote(tag)
+        ));
+    }
+    for alias in &meta.aliases {
+        script.push_str(&format!(
+            "INSERT INTO aliases VALUES ({}, {});\n",
+            name,
+            quote(alias)
+        ));
+    }
+    for link in &meta.links {
+        script.push_str(&format!(
+            "INSERT INTO links VALUES ({}, {}, {});\n",
+            name,
+            quote(&link.target),
+            link.line
+        ));
+    }
+}
+
+// SQL 字符串字面量：单引号写两次，控制字符拼接为 char(N)，如 'a' || char(10) || 'b'
+fn quote(text: &str) -> String {
+    let mut quoted = String::from("'");
+    for c in text.chars() {
+        match c {
+            '\'' => quoted.push_str("''"),
+            c if c.is_control() => quoted.push_str(&format!("' || char({}) || '", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('\'');
+    quoted
+}
+
+// 数据库记录的缓存摘要，表结构版本不同时为 None
+fn stored_digest(db: &Path) -> io::Result<Option<String>> {
+    let version: u32 = run(db, "PRAGMA user_version;\n")?
+        .trim()
+        .parse()
+        .unwrap_or(0);
+    if version != SCHEMA_VERSION {
+        return Ok(None);
+    }
+    let value = run(db, "SELECT value FROM state WHERE key = 'cache';\n")?;
+    Ok(Some(value.trim().to_string()))
+}
+
+// 缓存中各笔记文件名与文件状态的摘要（FNV-1a）
+fn digest(cache: &Cache) -> String {
+    let mut notes: Vec<_> = cache.notes.iter().collect();
+    notes.sort_by_key(|(name, _)| *name);
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for (name, note) in notes {
+        let stamp = format!(
+            "{}\0{}.{}\0{}\0",
+            name, note.stamp.mtime_secs, note.stamp.mtime_nanos, note.stamp.size
+        );
+        for byte in stamp.bytes() {
+            hash = (hash ^ u64::from(byte)).wrapping_mul(0x100_0000_01b3);
+        }
+    }
+    format!("{:016x}", hash)
+}
+
+/// 确认系统中可以运行 sqlite3 命令，不能运行时返回说明原因的错误
+pub fn check_available() -> io::Result<()> {
+    let status = Command::new("sqlite3")
+        .arg("-version")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map_err(not_runnable)?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(i18n::trf(
+            "sqlite3 -version 执行失败（{}），SQLite 索引需要可用的 sqlite3 命令",
+            &[&status],
+        )))
+    }
+}
+
+fn not_runnable(e: io::Error) -> io::Error {
+    let message = if e.kind() == io::ErrorKind::NotFound {
+        i18n::tr("找不到 sqlite3 命令，SQLite 索引（--backend sqlite）需要先安装 sqlite3")
+            .to_string()
+    } else {
+        i18n::trf("无法运行 sqlite3: {}", &[&e])
+    };
+    io::Error::new(e.kind(), message)
+}
+
+// 以 sqlite3 执行 script，返回标准输出
+fn run(db: &Path, script: &str) -> io::Result<String> {
+    if let Some(parent) = db.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut child = Command::new("sqlite3")
+        .arg("-batch")
+        .arg("-bail")
+        .arg(db)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(not_runnable)?;
+    // -bail 时 sqlite3 遇到错误即退出，此时写入会失败，错误以其标准错误输出为准
+    let written = child
+        .stdin
+        .take()
+        .expect("stdin 已设置为管道")
+        .write_all(script.as_bytes());
+    let output = child.wait_with_output()?;
+    if let Err(e) = written
+        && output.status.success()
+    {
+        return Err(e);
+    }
+    if !output.status.success() {
+        return Err(io::Error::other(i18n::trf(
+            "sqlite3 执行失败: {}",
+            &[&String::from_utf8_lossy(&output.stderr).trim()],
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_are_quoted() {
+        assert_eq!(quote("标题"), "'标题'");
+        assert_eq!(quote("it's"), "'it''s'");
+        assert_eq!(
+            quote("'); DROP TABLE notes; --"),
+            "'''); DROP TABLE notes; --'"
+        );
+        assert_eq!(
+            quote("a\n.shell b\r\0"),
+            "'a' || char(10) || '.shell b' || char(13) || '' || char(0) || ''"
+        );
+        assert_eq!(quote(""), "''");
+    }
+}
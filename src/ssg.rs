@@ -0,0 +1,263 @@
+// 静态站点生成器导出：把笔记复制为 Hugo、Zola 或 Jekyll 站点的内容
+//
+// 文件头改写为目标使用的 title、date、tags，维基链接改写为目标的站内链接：
+//   Hugo    [[笔记|文字]] → [文字]({{< ref "笔记.md" >}})
+//   Zola    [[笔记|文字]] → [文字](@/笔记.md)
+//   Jekyll  [[笔记|文字]] → [文字]({% post_url 2024-05-01-标题 %})
+// 找不到目标的链接只保留文字，嵌入与图片等附件保持原样，附件不会复制。
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::date::Time;
+use crate::links::{LinkIndex, replace_links};
+use crate::note::{NEED_TAG, NoteMeta};
+use crate::output::write_atomic;
+use crate::vault::Vault;
+
+/// Jekyll 分类索引页的文件名，位于站点目录
+pub const JEKYLL_CATEGORIES_PAGE: &str = "categories.md";
+
+/// 目标静态站点生成器
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Generator {
+    Hugo,
+    Zola,
+    Jekyll,
+}
+
+/// 把全部笔记写入 dir，返回写入的笔记数
+///
+/// Hugo 与 Zola 写入 dir/content/，Zola 另外需要 content/_index.md 作为根栏目，不存在时一并生成。
+/// 草稿只在扫描时选择包含草稿时写入，见 `Vault::published`，Hugo 与 Zola 在文件头中标记 draft。
+/// Jekyll 按创建日期与标题写入 dir/_posts/YYYY-MM-DD-标题.md，没有创建日期的笔记与草稿写入 dir/_drafts/，
+/// 标签同时作为分类，并在 dir/categories.md 中按分类列出文章。
+pub fn write_content(vault: &Vault, dir: &Path, generator: Generator) -> io::Result<usize> {
+    let links = &vault.indexes().links;
+    let mut notes: Vec<&NoteMeta> = vault.published().collect();
+    notes.sort_by(|a, b| a.name.cmp(&b.name));
+
+    // 文件名 -> 输出路径（相对 dir）与站内链接的目标；Jekyll 的草稿不能被链接
+    let mut pages: HashMap<&str, (PathBuf, Option<String>)> = HashMap::new();
+    let mut posts = HashSet::new();
+    for note in &notes {
+        let page = match generator {
+            Generator::Hugo | Generator::Zola => (
+                Path::new("content").join(format!("{}.md", note.name)),
+                Some(format!("{}.md", note.name)),
+            ),
+            Generator::Jekyll => match &note.created {
+                Some((date, _)) if !note.draft => {
+                    let base = format!(
+                        "{:04}-{:02}-{:02}-{}",
+                        date.year,
+                        date.month,
+                        date.day,
+                        slug(&note.title, &note.name)
+                    );
+                    // 同一天标题相同的文章加上序号
+                    let mut id = base.clone();
+                    let mut n = 2;
+                    while !posts.insert(id.clone()) {
+                        id = format!("{}-{}", base, n);
+                        n += 1;
+                    }
+                    (Path::new("_posts").join(format!("{}.md", id)), Some(id))
+                }
+                _ => (
+                    Path::new("_drafts").join(format!("{}.md", slug(&note.title, &note.name))),
+                    None,
+                ),
+            },
+        };
+        pages.insert(note.name.as_str(), page);
+    }
+
+    for note in &notes {
+        let body = vault.note_body(note)?;
+        let body = replace_links(&body, &|inner| Some(link(links, &pages, generator, inner)));
+        let page_path = dir.join(&pages[note.name.as_str()].0);
+        if let Some(parent) = page_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        write_atomic(
+            &page_path,
+            &format!("{}{}", front_matter(note, generator), body),
+        )?;
+    }
+
+    match generator {
+        Generator::Hugo => {}
+        Generator::Zola => {
+            let section = dir.join("content").join("_index.md");
+            if !section.exists() {
+                write_atomic(&section, "+++\ntitle = \"笔记\"\nsort_by = \"date\"\n+++\n")?;
+            }
+        }
+        Generator::Jekyll => {
+            write_atomic(
+                &dir.join(JEKYLL_CATEGORIES_PAGE),
+                &jekyll_categories(&notes, &pages),
+            )?;
+        }
+    }
+    Ok(notes.len())
+}
+
+// 目标格式的文件头：Hugo 与 Jekyll 为 YAML，Zola 为 TOML，标签放在 [taxonomies] 中
+fn front_matter(note: &NoteMeta, generator: Generator) -> String {
+    let tags: Vec<String> = note
+        .tags
+        .iter()
+        .filter(|tag| *tag != NEED_TAG)
+        .map(|tag| quote(tag))
+        .collect();
+    let tags = format!("[{}]", tags.join(", "));
+    // Hugo 与 Zola 使用 RFC 3339，Jekyll 使用 YYYY-MM-DD HH:MM:SS
+    let time_separator = if generator == Generator::Jekyll {
+        " "
+    } else {
+        "T"
+    };
+    let date = note.created.as_ref().map(|(date, time)| {
+        let day = format!("{:04}-{:02}-{:02}", date.year, date.month, date.day);
+        match Time::parse(time) {
+            Some(time) => format!("{}{}{}:00", day, time_separator, time),
+            None => day,
+        }
+    });
+
+    let mut out = String::new();
+    match generator {
+        Generator::Hugo | Generator::Jekyll => {
+            out.push_str("---\n");
+            if generator == Generator::Jekyll {
+                out.push_str("layout: post\n");
+            }
+            out.push_str(&format!("title: {}\n", quote(&note.title)));
+            if let Some(date) = date {
+                out.push_str(&format!("date: {}\n", date));
+            }
+            // Jekyll 的草稿由 _drafts/ 目录表示
+            if note.draft && generator == Generator::Hugo {
+                out.push_str("draft: true\n");
+            }
+            if generator == Generator::Jekyll {
+                out.push_str(&format!("categories: {}\n", tags));
+            }
+            out.push_str(&format!("tags: {}\n", tags));
+            out.push_str("---\n");
+        }
+        Generator::Zola => {
+            out.push_str("+++\n");
+            out.push_str(&format!("title = {}\n", quote(&note.title)));
+            if let Some(date) = date {
+                out.push_str(&format!("date = {}\n", date));
+            }
+            if note.draft {
+                out.push_str("draft = true\n");
+            }
+            out.push_str(&format!("\n[taxonomies]\ntags = {}\n", tags));
+            out.push_str("+++\n");
+        }
+    }
+    out
+}
+
+// 维基链接改写为站内链接，显示文字为别名，没有别名时为链接目标原文
+fn link(
+    links: &LinkIndex,
+    pages: &HashMap<&str, (PathBuf, Option<String>)>,
+    generator: Generator,
+    inner: &str,
+) -> String {
+    let (target, label) = match inner.split_once('|') {
+        Some((target, label)) => (target, label.trim()),
+        None => (inner, inner.trim()),
+    };
+    let (target, heading) = match target.split_once('#') {
+        Some((target, heading)) => (target.trim(), Some(heading.trim())),
+        None => (target.trim(), None),
+    };
+    let target = target.strip_suffix(".md").unwrap_or(target);
+    let Some(id) = links
+        .resolve(target)
+        .and_then(|name| pages.get(name))
+        .and_then(|(_, id)| id.as_deref())
+    else {
+        return label.to_string();
+    };
+    let anchor = heading.map_or_else(String::new, |heading| format!("#{}", anchor(heading)));
+    match generator {
+        Generator::Hugo => format!("[{}]({{{{< ref \"{}{}\" >}}}})", label, id, anchor),
+        Generator::Zola => format!("[{}](@/{}{})", label, id, anchor),
+        Generator::Jekyll => format!("[{}]({{% post_url {} %}}{})", label, id, anchor),
+    }
+}
+
+// Jekyll 的分类索引页：分类按文章数从多到少，文章按日期从新到旧
+fn jekyll_categories(
+    notes: &[&NoteMeta],
+    pages: &HashMap<&str, (PathBuf, Option<String>)>,
+) -> String {
+    let mut categories: BTreeMap<&str, Vec<(&str, &str)>> = BTreeMap::new();
+    for note in notes {
+        let Some((_, Some(id))) = pages.get(note.name.as_str()) else {
+            continue;
+        };
+        for tag in note.tags.iter().filter(|tag| *tag != NEED_TAG) {
+            categories
+                .entry(tag)
+                .or_default()
+                .push((id.as_str(), note.title.as_str()));
+        }
+    }
+    let mut categories: Vec<(&str, Vec<(&str, &str)>)> = categories.into_iter().collect();
+    categories.sort_by(|a, b| b.1.len().cmp(&a.1.len()).then(a.0.cmp(b.0)));
+
+    let mut out =
+        String::from("---\nlayout: page\ntitle: \"分类\"\npermalink: /categories/\n---\n");
+    for (category, mut posts) in categories {
+        posts.sort_by(|a, b| b.0.cmp(a.0));
+        out.push_str(&format!("\n## {} ({})\n\n", category, posts.len()));
+        for (id, title) in posts {
+            out.push_str(&format!("- [{}]({{% post_url {} %}})\n", title, id));
+        }
+    }
+    out
+}
+
+// Jekyll 文件名中的标题：字母与数字小写保留，其余字符改为 -，标题为空时使用文件名
+fn slug(title: &str, name: &str) -> String {
+    let mut slug = String::new();
+    for c in title.chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() {
+        name.to_string()
+    } else {
+        slug.to_string()
+    }
+}
+
+// 标题的锚点：小写，空白改为 -，去掉标点
+fn anchor(heading: &str) -> String {
+    heading
+        .chars()
+        .filter(|c| c.is_alphanumeric() || c.is_whitespace() || *c == '-' || *c == '_')
+        .flat_map(char::to_lowercase)
+        .map(|c| if c.is_whitespace() { '-' } else { c })
+        .collect()
+}
+
+// YAML 与 TOML 都接受的双引号字符串
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
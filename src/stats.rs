@@ -0,0 +1,373 @@
+// 博客目录的统计信息
+
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Write;
+
+use crate::format::{Align, format_table};
+use crate::graph::GraphMetrics;
+use crate::i18n;
+use crate::note::{NEED_TAG, NoteMeta, reading_minutes};
+use crate::output::LinkStyle;
+use crate::vault::Vault;
+
+/// 统计页面的文件名，位于输出目录下
+pub const STATS_PAGE: &str = "stats.md";
+
+/// 一篇笔记的字数
+#[derive(Debug, Clone)]
+pub struct NoteWords {
+    pub name: String,
+    pub title: String,
+    pub words: usize,
+}
+
+/// 整个博客目录的统计
+#[derive(Debug, Clone)]
+pub struct Stats {
+    pub notes: usize,
+    /// 所有笔记的标签数之和，不含 NeedTag
+    pub tag_uses: usize,
+    pub unique_tags: usize,
+    /// 所有笔记的正文字数之和
+    pub words: usize,
+    /// 每月新建的笔记数，键为 YYYY-MM，按时间排序；没有创建日期的笔记记在 None 下
+    pub per_month: Vec<(Option<String>, usize)>,
+    /// 字数最多的笔记，从多到少
+    pub longest: Vec<NoteWords>,
+    /// 字数最少的笔记，从少到多
+    pub shortest: Vec<NoteWords>,
+    /// 没有标签的笔记，按文件名排序
+    pub untagged: Vec<NoteWords>,
+    /// 链接图的统计，notes 只保留有链接的笔记中中心度最高的 top 篇；没有要求时为 None
+    pub graph: Option<GraphMetrics>,
+}
+
+impl Stats {
+    /// 统计 vault 中的笔记，最长与最短的笔记各取 top 篇；graph 为 true 时同时统计链接图
+    pub fn collect(vault: &Vault, top: usize, graph: bool) -> Stats {
+        let mut notes: Vec<&NoteMeta> = vault.notes().collect();
+        notes.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut tag_uses = 0;
+        let mut unique_tags = HashSet::new();
+        let mut per_month: BTreeMap<Option<String>, usize> = BTreeMap::new();
+        let mut untagged = Vec::new();
+
+        for note in &notes {
+            if note.tags.len() == 1 && note.tags[0] == NEED_TAG {
+                untagged.push(note_words(note));
+            } else {
+                tag_uses += note.tags.len();
+                unique_tags.extend(note.tags.iter());
+            }
+            let month = note.created.as_ref().map(|(date, _)| date.month_key());
+            *per_month.entry(month).or_default() += 1;
+        }
+
+        // 文件名已排序，稳定排序保证字数相同时按文件名排列
+        let mut by_words: Vec<NoteWords> = notes.iter().map(|note| note_words(note)).collect();
+        by_words.sort_by_key(|note| note.words);
+        let shortest = by_words.iter().take(top).cloned().collect();
+        by_words.sort_by_key(|note| std::cmp::Reverse(note.words));
+        let longest = by_words.into_iter().take(top).collect();
+
+        // 没有日期的笔记排在最后
+        let mut per_month: Vec<_> = per_month.into_iter().collect();
+        per_month.sort_by_key(|(month, _)| (month.is_none(), month.clone()));
+
+        let graph = graph.then(|| {
+            let mut metrics = GraphMetrics::collect(vault.indexes());
+            metrics
+                .notes
+                .retain(|note| note.incoming + note.outgoing > 0);
+            metrics.notes.truncate(top);
+            metrics
+        });
+
+        Stats {
+            notes: notes.len(),
+            tag_uses,
+            unique_tags: unique_tags.len(),
+            words: notes.iter().map(|note| note.words).sum(),
+            per_month,
+            longest,
+            shortest,
+            untagged,
+            graph,
+        }
+    }
+
+    /// 平均每篇笔记的标签数
+    pub fn average_tags(&self) -> f64 {
+        if self.notes == 0 {
+            0.0
+        } else {
+            self.tag_uses as f64 / self.notes as f64
+        }
+    }
+
+    /// 纯文本报告，用于终端输出
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        out.push_str(&format_table(
+            &[i18n::tr("项目"), i18n::tr("数值")],
+            &self.summary_rows(),
+            &[Align::Left, Align::Right],
+        ));
+
+        let _ = writeln!(out, "{}", i18n::tr("\n每月笔记数"));
+        out.push_str(&format_table(
+            &[i18n::tr("月份"), i18n::tr("笔记数")],
+            &self.month_rows(),
+            &[Align::Left, Align::Right],
+        ));
+
+        for (heading, notes) in [
+            (i18n::tr("最长的笔记"), &self.longest),
+            (i18n::tr("最短的笔记"), &self.shortest),
+        ] {
+            let _ = writeln!(out, "\n{}", heading);
+            out.push_str(&format_table(
+                &[
+                    i18n::tr("笔记"),
+                    i18n::tr("标题"),
+                    i18n::tr("字数"),
+                    i18n::tr("阅读时间"),
+                ],
+                &word_rows(notes),
+                &[Align::Left, Align::Left, Align::Right, Align::Right],
+            ));
+        }
+
+        if !self.untagged.is_empty() {
+            let _ = writeln!(out, "{}", i18n::tr("\n没有标签的笔记"));
+            for note in &self.untagged {
+                let _ = writeln!(out, "{}  {}", note.name, note.title);
+            }
+        }
+
+        if let Some(graph) = &self.graph {
+            let _ = writeln!(out, "{}", i18n::tr("\n链接图"));
+            out.push_str(&format_table(
+                &[i18n::tr("项目"), i18n::tr("数值")],
+                &graph_rows(graph),
+                &[Align::Left, Align::Right],
+            ));
+            if !graph.notes.is_empty() {
+                let _ = writeln!(out, "{}", i18n::tr("\n中心笔记"));
+                let rows: Vec<Vec<String>> = graph
+                    .notes
+                    .iter()
+                    .map(|note| {
+                        vec![
+                            note.name.clone(),
+                            note.title.clone(),
+                            note.incoming.to_string(),
+                            note.outgoing.to_string(),
+                            format!("{:.2}", note.rank),
+                        ]
+                    })
+                    .collect();
+                out.push_str(&format_table(
+                    &[
+                        i18n::tr("笔记"),
+                        i18n::tr("标题"),
+                        i18n::tr("入链"),
+                        i18n::tr("出链"),
+                        i18n::tr("中心度"),
+                    ],
+                    &rows,
+                    &[
+                        Align::Left,
+                        Align::Left,
+                        Align::Right,
+                        Align::Right,
+                        Align::Right,
+                    ],
+                ));
+            }
+            if !graph.islands().is_empty() {
+                let _ = writeln!(out, "{}", i18n::tr("\n孤岛"));
+                for island in graph.islands() {
+                    let _ = writeln!(out, "{}", island.join(", "));
+                }
+            }
+        }
+        out
+    }
+
+    /// 统计页面的内容，笔记以 style 的写法链接，link_prefix 含义同 `WriteOptions`
+    pub fn to_markdown(&self, style: LinkStyle, link_prefix: &str) -> String {
+        let link = |note: &NoteWords| style.link(link_prefix, &note.name, Some(&note.title));
+        let mut out = i18n::trf("{}\n# 统计\n", &[&style.frontmatter("stats", "stats")]);
+        markdown_table(
+            &mut out,
+            &[i18n::tr("项目"), i18n::tr("数值")],
+            &self.summary_rows(),
+        );
+
+        out.push_str(i18n::tr("\n# 每月笔记数\n"));
+        markdown_table(
+            &mut out,
+            &[i18n::tr("月份"), i18n::tr("笔记数")],
+            &self.month_rows(),
+        );
+
+        for (heading, notes) in [
+            (i18n::tr("最长的笔记"), &self.longest),
+            (i18n::tr("最短的笔记"), &self.shortest),
+        ] {
+            let _ = writeln!(out, "\n# {}", heading);
+            let rows: Vec<Vec<String>> = notes
+                .iter()
+                .map(|note| {
+                    vec![
+                        link(note),
+                        note.words.to_string(),
+                        i18n::trf("{} 分钟", &[&reading_minutes(note.words)]),
+                    ]
+                })
+                .collect();
+            markdown_table(
+                &mut out,
+                &[i18n::tr("笔记"), i18n::tr("字数"), i18n::tr("阅读时间")],
+                &rows,
+            );
+        }
+
+        if !self.untagged.is_empty() {
+            out.push_str(i18n::tr("\n# 没有标签的笔记\n"));
+            for note in &self.untagged {
+                let _ = writeln!(out, "{}", link(note));
+            }
+        }
+
+        if let Some(graph) = &self.graph {
+            out.push_str(i18n::tr("\n# 链接图\n"));
+            markdown_table(
+                &mut out,
+                &[i18n::tr("项目"), i18n::tr("数值")],
+                &graph_rows(graph),
+            );
+            if !graph.notes.is_empty() {
+                out.push_str(i18n::tr("\n# 中心笔记\n"));
+                let rows: Vec<Vec<String>> = graph
+                    .notes
+                    .iter()
+                    .map(|note| {
+                        vec![
+                            style.link(link_prefix, &note.name, Some(&note.title)),
+                            note.incoming.to_string(),
+                            note.outgoing.to_string(),
+                            format!("{:.2}", note.rank),
+                        ]
+                    })
+                    .collect();
+                markdown_table(
+                    &mut out,
+                    &[
+                        i18n::tr("笔记"),
+                        i18n::tr("入链"),
+                        i18n::tr("出链"),
+                        i18n::tr("中心度"),
+                    ],
+                    &rows,
+                );
+            }
+            if !graph.islands().is_empty() {
+                out.push_str(i18n::tr("\n# 孤岛\n"));
+                for island in graph.islands() {
+                    let members: Vec<String> = island
+                        .iter()
+                        .map(|name| style.link(link_prefix, name, None))
+                        .collect();
+                    let _ = writeln!(out, "- {}", members.join(", "));
+                }
+            }
+        }
+        out
+    }
+
+    fn summary_rows(&self) -> Vec<Vec<String>> {
+        [
+            (i18n::tr("笔记总数"), self.notes.to_string()),
+            (i18n::tr("标签总数"), self.tag_uses.to_string()),
+            (i18n::tr("不同标签数"), self.unique_tags.to_string()),
+            (
+                i18n::tr("平均每篇标签数"),
+                format!("{:.2}", self.average_tags()),
+            ),
+            (i18n::tr("没有标签的笔记"), self.untagged.len().to_string()),
+            (i18n::tr("总字数"), self.words.to_string()),
+            (
+                i18n::tr("总阅读时间"),
+                i18n::trf("{} 分钟", &[&reading_minutes(self.words)]),
+            ),
+        ]
+        .into_iter()
+        .map(|(name, value)| vec![name.to_string(), value])
+        .collect()
+    }
+
+    fn month_rows(&self) -> Vec<Vec<String>> {
+        self.per_month
+            .iter()
+            .map(|(month, count)| {
+                vec![
+                    month
+                        .clone()
+                        .unwrap_or_else(|| i18n::tr("未知").to_string()),
+                    count.to_string(),
+                ]
+            })
+            .collect()
+    }
+}
+
+fn graph_rows(graph: &GraphMetrics) -> Vec<Vec<String>> {
+    [
+        (i18n::tr("链接数"), graph.edges),
+        (i18n::tr("连通分量数"), graph.components.len()),
+        (
+            i18n::tr("最大连通分量的笔记数"),
+            graph.components.first().map_or(0, Vec::len),
+        ),
+        (i18n::tr("孤岛数"), graph.islands().len()),
+        (i18n::tr("没有链接的笔记"), graph.isolated()),
+    ]
+    .into_iter()
+    .map(|(name, value)| vec![name.to_string(), value.to_string()])
+    .collect()
+}
+
+fn note_words(note: &NoteMeta) -> NoteWords {
+    NoteWords {
+        name: note.name.clone(),
+        title: note.title.clone(),
+        words: note.words,
+    }
+}
+
+fn word_rows(notes: &[NoteWords]) -> Vec<Vec<String>> {
+    notes
+        .iter()
+        .map(|note| {
+            vec![
+                note.name.clone(),
+                note.title.clone(),
+                note.words.to_string(),
+                i18n::trf("{} 分钟", &[&reading_minutes(note.words)]),
+            ]
+        })
+        .collect()
+}
+
+// Markdown 表格，单元格中的 | 需要转义
+fn markdown_table(out: &mut String, header: &[&str], rows: &[Vec<String>]) {
+    let _ = writeln!(out, "| {} |", header.join(" | "));
+    let _ = writeln!(out, "|{}", " --- |".repeat(header.len()));
+    for row in rows {
+        let cells: Vec<String> = row.iter().map(|cell| cell.replace('|', "\\|")).collect();
+        let _ = writeln!(out, "| {} |", cells.join(" | "));
+    }
+}
@@ -0,0 +1,394 @@
+// 生成页面使用的模板，语法取自 Tera 的一个子集：
+//
+//   {{ note.title }}                      输出变量，按 . 访问字段或下标
+//   {% for note in notes %}…{% endfor %}  循环，循环内可用 loop.index、loop.first、loop.last
+//   {% if children %}…{% else %}…{% endif %}  条件，空字符串、空列表、0、false 与 null 为假，可加 not
+//   {# 注释 #}
+//
+// 标签写作 {{-、{%-、-}}、-%} 时去掉相邻一侧的空白。变量的值用 Json 表示。
+
+use std::fmt;
+
+use crate::json::Json;
+
+/// 模板解析或渲染失败
+#[derive(Debug, Clone, PartialEq)]
+pub struct Error {
+    /// 出错位置所在的行，从 1 开始
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "第 {} 行: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// 解析后的模板
+#[derive(Debug, Clone)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Text(String),
+    Var {
+        path: Vec<String>,
+        line: usize,
+    },
+    For {
+        var: String,
+        list: Vec<String>,
+        body: Vec<Node>,
+        line: usize,
+    },
+    If {
+        negate: bool,
+        path: Vec<String>,
+        then: Vec<Node>,
+        otherwise: Vec<Node>,
+        line: usize,
+    },
+}
+
+// 词法单元：文本，或 {{ }}、{% %} 中的内容
+enum Token {
+    Text(String),
+    Var(String, usize),
+    Tag(String, usize),
+}
+
+impl Template {
+    pub fn parse(text: &str) -> Result<Template, Error> {
+        let tokens = tokenize(text)?;
+        let mut pos = 0;
+        let (nodes, end) = parse_nodes(&tokens, &mut pos)?;
+        if let Some((tag, line)) = end {
+            return Err(Error {
+                line,
+                message: format!("多余的 {{% {} %}}", tag),
+            });
+        }
+        Ok(Template { nodes })
+    }
+
+    /// 以 context 中的字段为变量渲染模板，引用不存在的变量时报错
+    pub fn render(&self, context: &Json) -> Result<String, Error> {
+        let mut out = String::new();
+        let mut scope = Vec::new();
+        render_nodes(&self.nodes, context, &mut scope, &mut out)?;
+        Ok(out)
+    }
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, Error> {
+    let mut tokens = Vec::new();
+    let mut rest = text;
+    let mut line = 1;
+    // 上一个标签以 - 结尾时去掉下一段文本开头的空白
+    let mut trim_next = false;
+    loop {
+        let start = ["{{", "{%", "{#"]
+            .iter()
+            .filter_map(|open| rest.find(open))
+            .min();
+        let Some(start) = start else {
+            push_text(&mut tokens, rest, trim_next, false);
+            return Ok(tokens);
+        };
+        let open = &rest[start..start + 2];
+        let close = match open {
+            "{{" => "}}",
+            "{%" => "%}",
+            _ => "#}",
+        };
+        let inner_start = start + 2;
+        let trim_before = rest[inner_start..].starts_with('-');
+        push_text(&mut tokens, &rest[..start], trim_next, trim_before);
+        line += rest[..start].matches('\n').count();
+
+        let Some(len) = rest[inner_start..].find(close) else {
+            return Err(Error {
+                line,
+                message: format!("{} 没有对应的 {}", open, close),
+            });
+        };
+        let mut inner = &rest[inner_start..inner_start + len];
+        trim_next = inner.ends_with('-') && open != "{#";
+        if open != "{#" {
+            inner = inner.strip_prefix('-').unwrap_or(inner);
+            inner = inner.strip_suffix('-').unwrap_or(inner);
+        }
+        match open {
+            "{{" => tokens.push(Token::Var(inner.trim().to_string(), line)),
+            "{%" => tokens.push(Token::Tag(inner.trim().to_string(), line)),
+            _ => {}
+        }
+        line += inner.matches('\n').count();
+        rest = &rest[inner_start + len + 2..];
+    }
+}
+
+fn push_text(tokens: &mut Vec<Token>, text: &str, trim_start: bool, trim_end: bool) {
+    let text = if trim_start { text.trim_start() } else { text };
+    let text = if trim_end { text.trim_end() } else { text };
+    if !text.is_empty() {
+        tokens.push(Token::Text(text.to_string()));
+    }
+}
+
+// 解析出的节点，以及结束处的标签与所在行
+type Block = (Vec<Node>, Option<(String, usize)>);
+
+// 解析到 endfor、else、endif 或末尾为止
+fn parse_nodes(tokens: &[Token], pos: &mut usize) -> Result<Block, Error> {
+    let mut nodes = Vec::new();
+    while let Some(token) = tokens.get(*pos) {
+        *pos += 1;
+        match token {
+            Token::Text(text) => nodes.push(Node::Text(text.clone())),
+            Token::Var(expr, line) => nodes.push(Node::Var {
+                path: parse_path(expr, *line)?,
+                line: *line,
+            }),
+            Token::Tag(tag, line) => {
+                let line = *line;
+                let words: Vec<&str> = tag.split_whitespace().collect();
+                match words.as_slice() {
+                    ["for", var, "in", list] => {
+                        let list = parse_path(list, line)?;
+                        let (body, end) = parse_nodes(tokens, pos)?;
+                        expect_end(end, "endfor", line)?;
+                        nodes.push(Node::For {
+                            var: var.to_string(),
+                            list,
+                            body,
+                            line,
+                        });
+                    }
+                    ["if", condition @ ..] => {
+                        let (negate, path) = match condition {
+                            ["not", path] => (true, parse_path(path, line)?),
+                            [path] => (false, parse_path(path, line)?),
+                            _ => {
+                                return Err(Error {
+                                    line,
+                                    message: format!("无法识别的条件 '{}'", condition.join(" ")),
+                                });
+                            }
+                        };
+                        let (then, end) = parse_nodes(tokens, pos)?;
+                        let otherwise = match &end {
+                            Some((tag, _)) if tag == "else" => {
+                                let (otherwise, end) = parse_nodes(tokens, pos)?;
+                                expect_end(end, "endif", line)?;
+                                otherwise
+                            }
+                            _ => {
+                                expect_end(end, "endif", line)?;
+                                Vec::new()
+                            }
+                        };
+                        nodes.push(Node::If {
+                            negate,
+                            path,
+                            then,
+                            otherwise,
+                            line,
+                        });
+                    }
+                    ["endfor"] | ["endif"] | ["else"] => {
+                        return Ok((nodes, Some((tag.clone(), line))));
+                    }
+                    _ => {
+                        return Err(Error {
+                            line,
+                            message: format!("无法识别的标签 {{% {} %}}", tag),
+                        });
+                    }
+                }
+            }
+        }
+    }
+    Ok((nodes, None))
+}
+
+fn expect_end(end: Option<(String, usize)>, expected: &str, line: usize) -> Result<(), Error> {
+    match end {
+        Some((tag, _)) if tag == expected => Ok(()),
+        Some((tag, line)) => Err(Error {
+            line,
+            message: format!("应为 {{% {} %}}，遇到 {{% {} %}}", expected, tag),
+        }),
+        None => Err(Error {
+            line,
+            message: format!("缺少 {{% {} %}}", expected),
+        }),
+    }
+}
+
+fn parse_path(expr: &str, line: usize) -> Result<Vec<String>, Error> {
+    let path: Vec<String> = expr.split('.').map(str::to_string).collect();
+    let valid = path
+        .iter()
+        .all(|part| !part.is_empty() && part.chars().all(|c| c.is_alphanumeric() || c == '_'));
+    if !valid {
+        return Err(Error {
+            line,
+            message: format!("无法识别的变量 '{}'", expr),
+        });
+    }
+    Ok(path)
+}
+
+fn render_nodes(
+    nodes: &[Node],
+    context: &Json,
+    scope: &mut Vec<(String, Json)>,
+    out: &mut String,
+) -> Result<(), Error> {
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Var { path, line } => match lookup(path, context, scope, *line)? {
+                Json::Null => {}
+                Json::String(s) => out.push_str(s),
+                Json::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+                value => out.push_str(&value.to_string()),
+            },
+            Node::For {
+                var,
+                list,
+                body,
+                line,
+            } => {
+                let items = match lookup(list, context, scope, *line)? {
+                    Json::Array(items) => items.clone(),
+                    Json::Null => Vec::new(),
+                    _ => {
+                        return Err(Error {
+                            line: *line,
+                            message: format!("'{}' 不是列表", list.join(".")),
+                        });
+                    }
+                };
+                let len = items.len();
+                for (i, item) in items.into_iter().enumerate() {
+                    let state = Json::Object(vec![
+                        ("index".to_string(), (i + 1).into()),
+                        ("first".to_string(), (i == 0).into()),
+                        ("last".to_string(), (i + 1 == len).into()),
+                    ]);
+                    scope.push(("loop".to_string(), state));
+                    scope.push((var.clone(), item));
+                    let result = render_nodes(body, context, scope, out);
+                    scope.truncate(scope.len() - 2);
+                    result?;
+                }
+            }
+            Node::If {
+                negate,
+                path,
+                then,
+                otherwise,
+                line,
+            } => {
+                let value = lookup(path, context, scope, *line)?;
+                let truthy = match value {
+                    Json::Null => false,
+                    Json::Bool(b) => *b,
+                    Json::Number(n) => *n != 0.0,
+                    Json::String(s) => !s.is_empty(),
+                    Json::Array(items) => !items.is_empty(),
+                    Json::Object(fields) => !fields.is_empty(),
+                };
+                let branch = if truthy != *negate { then } else { otherwise };
+                render_nodes(branch, context, scope, out)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// 先在循环变量中查找，再在 context 中查找
+fn lookup<'a>(
+    path: &[String],
+    context: &'a Json,
+    scope: &'a [(String, Json)],
+    line: usize,
+) -> Result<&'a Json, Error> {
+    let undefined = || Error {
+        line,
+        message: format!("未定义的变量 '{}'", path.join(".")),
+    };
+    let mut value = scope
+        .iter()
+        .rev()
+        .find(|(name, _)| *name == path[0])
+        .map(|(_, value)| value)
+        .or_else(|| context.get(&path[0]))
+        .ok_or_else(undefined)?;
+    for part in &path[1..] {
+        value = match value {
+            Json::Array(items) => part.parse::<usize>().ok().and_then(|i| items.get(i)),
+            _ => value.get(part),
+        }
+        .ok_or_else(undefined)?;
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json;
+
+    fn render(template: &str, context: &str) -> Result<String, Error> {
+        Template::parse(template)?.render(&json::parse(context).unwrap())
+    }
+
+    #[test]
+    fn variables_loops_and_conditions() {
+        let context =
+            r#"{"title": "a < b", "notes": [{"name": "x"}, {"name": "y"}], "empty": [], "n": 0}"#;
+        assert_eq!(
+            render("{{ title }}|{{ notes.1.name }}", context).unwrap(),
+            "a < b|y"
+        );
+        assert_eq!(
+            render(
+                "{% for note in notes %}{{ loop.index }}{{ note.name }}{% if not loop.last %},{% endif %}{% endfor %}",
+                context
+            )
+            .unwrap(),
+            "1x,2y"
+        );
+        assert_eq!(
+            render(
+                "{% if empty %}有{% else %}无{% endif %}{% if n %}有{% else %}无{% endif %}",
+                context
+            )
+            .unwrap(),
+            "无无"
+        );
+        assert_eq!(render("a{# 注释 #}b", context).unwrap(), "ab");
+        assert_eq!(
+            render("a\n  {%- if title -%}\n  b\n{%- endif %}", context).unwrap(),
+            "ab"
+        );
+    }
+
+    #[test]
+    fn errors_name_the_line() {
+        let error = |template: &str| render(template, r#"{"a": 1}"#).unwrap_err().line;
+        assert_eq!(error("\n{% for x in a %}{% endfor %}"), 2);
+        assert_eq!(error("\n\n{{ missing }}"), 3);
+        assert_eq!(error("{% if a %}"), 1);
+        assert_eq!(error("\n{% endif %}"), 2);
+        assert_eq!(error("{% while a %}"), 1);
+    }
+}
@@ -0,0 +1,187 @@
+// 正文中的待办事项：`- [ ] 任务` 与 `- [x] 已完成`
+//
+// 任务文本中的 `@due(2024-06-01)` 为截止日期，日期写法同 Created 字段。
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use crate::date::Date;
+use crate::i18n;
+use crate::index::tag_levels;
+use crate::note::NoteMeta;
+use crate::output::{LinkStyle, tag_page};
+
+/// 待办页面的文件名，位于输出目录下
+pub const TODOS_PAGE: &str = "todos.md";
+
+/// 正文中的一条待办事项
+#[derive(Debug, Clone, PartialEq)]
+pub struct Task {
+    /// 去掉复选框与截止日期后的文本
+    pub text: String,
+    pub done: bool,
+    /// 所在行号，从 1 开始，相对整个文件
+    pub line: usize,
+    /// `@due(...)` 中的日期，无法解析时为 None
+    pub due: Option<Date>,
+}
+
+/// 待办事项及其来源笔记
+pub struct TodoItem<'a> {
+    pub note: &'a NoteMeta,
+    pub task: &'a Task,
+}
+
+/// 提取正文中以 `-`、`*` 或 `+` 开头的复选框列表项，跳过代码块
+pub fn extract_tasks(body: &str, first_line: usize) -> Vec<Task> {
+    let mut tasks = Vec::new();
+    let mut in_code_block = false;
+
+    for (offset, line) in body.lines().enumerate() {
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            continue;
+        }
+
+        let Some(item) = line
+            .trim_start()
+            .strip_prefix(['-', '*', '+'])
+            .and_then(|rest| rest.strip_prefix(' '))
+        else {
+            continue;
+        };
+        let item = item.trim_start();
+        let (done, text) = if let Some(text) = item.strip_prefix("[ ]") {
+            (false, text)
+        } else if let Some(text) = item
+            .strip_prefix("[x]")
+            .or_else(|| item.strip_prefix("[X]"))
+        {
+            (true, text)
+        } else {
+            continue;
+        };
+        if !text.is_empty() && !text.starts_with(' ') {
+            continue;
+        }
+        let (text, due) = split_due(text.trim());
+        tasks.push(Task {
+            text,
+            done,
+            line: first_line + offset,
+            due,
+        });
+    }
+
+    tasks
+}
+
+// 取出 `@due(...)`，返回其余文本与日期
+fn split_due(text: &str) -> (String, Option<Date>) {
+    let Some(start) = text.find("@due(") else {
+        return (text.to_string(), None);
+    };
+    let after = &text[start + "@due(".len()..];
+    let Some(end) = after.find(')') else {
+        return (text.to_string(), None);
+    };
+    let due = Date::parse(after[..end].trim()).ok().map(|(date, _)| date);
+    let rest = format!(
+        "{} {}",
+        text[..start].trim_end(),
+        after[end + 1..].trim_start()
+    );
+    (rest.trim().to_string(), due)
+}
+
+/// 各笔记中的待办事项，按截止日期排序，没有截止日期的排在最后；all 为 false 时只包含未完成的
+pub fn collect<'a>(notes: impl IntoIterator<Item = &'a NoteMeta>, all: bool) -> Vec<TodoItem<'a>> {
+    let mut items: Vec<TodoItem> = notes
+        .into_iter()
+        .flat_map(|note| {
+            note.tasks
+                .iter()
+                .filter(move |task| all || !task.done)
+                .map(move |task| TodoItem { note, task })
+        })
+        .collect();
+    items.sort_by(|a, b| {
+        (a.task.due.is_none(), a.task.due)
+            .cmp(&(b.task.due.is_none(), b.task.due))
+            .then(a.note.name.cmp(&b.note.name))
+            .then(a.task.line.cmp(&b.task.line))
+    });
+    items
+}
+
+/// 待办页面的内容：按截止日期与标签分组，link_prefix 与 tag_dir 含义同 `WriteOptions`
+///
+/// 有截止日期的任务另在所属日期下列出一次；标签链接到标签页，层级标签记在每一级下。
+pub fn todos_page(
+    items: &[TodoItem],
+    style: LinkStyle,
+    link_prefix: &str,
+    tag_dir: &str,
+) -> String {
+    let mut out = i18n::trf("{}\n# 待办\n", &[&style.frontmatter("todos", "todos")]);
+    if items.is_empty() {
+        out.push_str(i18n::tr("没有待办事项\n"));
+        return out;
+    }
+
+    let mut by_due: BTreeMap<Date, Vec<&TodoItem>> = BTreeMap::new();
+    let mut by_tag: BTreeMap<&str, Vec<&TodoItem>> = BTreeMap::new();
+    for item in items {
+        if let Some(due) = item.task.due {
+            by_due.entry(due).or_default().push(item);
+        }
+        let mut tags: Vec<&str> = item
+            .note
+            .tags
+            .iter()
+            .flat_map(|tag| tag_levels(tag))
+            .collect();
+        tags.sort_unstable();
+        tags.dedup();
+        for tag in tags {
+            by_tag.entry(tag).or_default().push(item);
+        }
+    }
+
+    if !by_due.is_empty() {
+        out.push_str(i18n::tr("\n## 截止日期\n"));
+        for (due, items) in &by_due {
+            let _ = writeln!(out, "\n### {}", due);
+            for item in items {
+                push_item(&mut out, item, style, link_prefix);
+            }
+        }
+    }
+
+    out.push_str(i18n::tr("\n## 标签\n"));
+    for (tag, items) in &by_tag {
+        let _ = writeln!(
+            out,
+            "\n### {}",
+            style.link("", &tag_page(tag_dir, tag), Some(tag))
+        );
+        for item in items {
+            push_item(&mut out, item, style, link_prefix);
+        }
+    }
+    out
+}
+
+fn push_item(out: &mut String, item: &TodoItem, style: LinkStyle, link_prefix: &str) {
+    let _ = writeln!(
+        out,
+        "- [{}] {} —— {} (L{})",
+        if item.task.done { "x" } else { " " },
+        item.task.text,
+        style.link(link_prefix, &item.note.name, Some(&item.note.title)),
+        item.task.line
+    );
+}
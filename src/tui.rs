@@ -0,0 +1,669 @@
+// gtx tui：在终端中浏览标签、日期与笔记
+//
+// 不依赖终端库：以 stty 切换原始模式，用 ANSI 转义序列绘制整屏，按键从 /dev/tty 读取。
+// 四栏从左到右为标签、月份、笔记列表与所选笔记的预览；按标签、月份与过滤文本筛选笔记。
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::process::Command;
+
+use crate::format::{char_width, terminal_size};
+use crate::frontmatter;
+use crate::note::{NoteMeta, note_file_name};
+use crate::vault::Vault;
+
+const HELP_LINE: &str = "Tab/h/l 切换栏  j/k 移动  / 过滤  Esc 清除过滤  Enter 打开  q 退出";
+
+/// 打开 vault 的浏览界面，直到用户退出
+///
+/// 在笔记栏按 Enter 时暂时恢复终端并调用 open 打开笔记文件，返回后重新绘制界面；
+/// open 出错时在状态栏显示错误信息而不退出。
+pub fn run(vault: &Vault, open: &mut dyn FnMut(&Path) -> io::Result<()>) -> io::Result<()> {
+    let mut browser = Browser::new(vault);
+    let mut terminal = Terminal::enter()?;
+    loop {
+        let (rows, columns) = terminal_size().unwrap_or((24, 80));
+        terminal.draw(&browser.render(rows, columns))?;
+        for key in terminal.read_keys()? {
+            match browser.handle(key) {
+                Action::None => {}
+                Action::Quit => return Ok(()),
+                Action::Open(name) => {
+                    terminal.leave()?;
+                    let result = open(&vault.note_path(&name));
+                    terminal.resume()?;
+                    browser.message = match result {
+                        Ok(()) => String::new(),
+                        Err(e) => format!("无法打开 {}.md: {}", name, e),
+                    };
+                    // 笔记可能已被修改
+                    browser.previews.remove(&name);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pane {
+    Tags,
+    Dates,
+    Notes,
+}
+
+impl Pane {
+    fn index(self) -> usize {
+        match self {
+            Pane::Tags => 0,
+            Pane::Dates => 1,
+            Pane::Notes => 2,
+        }
+    }
+
+    fn next(self) -> Pane {
+        match self {
+            Pane::Tags => Pane::Dates,
+            Pane::Dates => Pane::Notes,
+            Pane::Notes => Pane::Tags,
+        }
+    }
+
+    fn previous(self) -> Pane {
+        self.next().next()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+    Tab,
+    BackTab,
+    Enter,
+    Backspace,
+    Esc,
+    Ctrl(char),
+    Char(char),
+}
+
+enum Action {
+    None,
+    Quit,
+    Open(String),
+}
+
+// 标签栏与月份栏的第一项，表示不按此项筛选
+const ALL: &str = "全部";
+// 月份栏中没有创建时间的笔记
+const UNDATED: &str = "无日期";
+
+struct Browser<'a> {
+    root: &'a Path,
+    // 全部笔记，按创建时间从新到旧排列
+    notes: Vec<&'a NoteMeta>,
+    // (标签, 笔记数)，第一项为全部
+    tags: Vec<(String, usize)>,
+    // (月份, 笔记数)，第一项为全部
+    months: Vec<(String, usize)>,
+    // 当前筛选结果，为 notes 中的下标
+    visible: Vec<usize>,
+    focus: Pane,
+    cursors: [usize; 3],
+    offsets: [usize; 3],
+    filter: String,
+    filtering: bool,
+    message: String,
+    // 已读取的预览文本，按文件名缓存
+    previews: HashMap<String, Vec<String>>,
+    // 最近一次绘制时列表的可见行数，用于翻页
+    page: usize,
+}
+
+impl<'a> Browser<'a> {
+    fn new(vault: &'a Vault) -> Browser<'a> {
+        let mut notes: Vec<&NoteMeta> = vault.notes().collect();
+        notes.sort_by(|a, b| b.created.cmp(&a.created).then_with(|| a.name.cmp(&b.name)));
+
+        // 层级标签的每一级都单独列出，选中父标签时包括子标签
+        let mut tag_counts: BTreeMap<String, usize> = BTreeMap::new();
+        let mut month_counts: BTreeMap<String, usize> = BTreeMap::new();
+        for meta in &notes {
+            let mut tags: Vec<&str> = meta
+                .tags
+                .iter()
+                .flat_map(|tag| {
+                    tag.match_indices('/')
+                        .map(|(i, _)| &tag[..i])
+                        .chain([tag.as_str()])
+                })
+                .collect();
+            tags.sort_unstable();
+            tags.dedup();
+            for tag in tags {
+                *tag_counts.entry(tag.to_string()).or_default() += 1;
+            }
+            *month_counts.entry(month_of(meta)).or_default() += 1;
+        }
+        let mut tags: Vec<(String, usize)> = tag_counts.into_iter().collect();
+        tags.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        tags.insert(0, (ALL.to_string(), notes.len()));
+        // 月份从新到旧，无日期排在最后
+        let undated = month_counts.remove(UNDATED);
+        let mut months: Vec<(String, usize)> = month_counts.into_iter().rev().collect();
+        months.insert(0, (ALL.to_string(), notes.len()));
+        if let Some(count) = undated {
+            months.push((UNDATED.to_string(), count));
+        }
+
+        let mut browser = Browser {
+            root: vault.root(),
+            notes,
+            tags,
+            months,
+            visible: Vec::new(),
+            focus: Pane::Notes,
+            cursors: [0; 3],
+            offsets: [0; 3],
+            filter: String::new(),
+            filtering: false,
+            message: String::new(),
+            previews: HashMap::new(),
+            page: 10,
+        };
+        browser.refilter();
+        browser
+    }
+
+    // 按当前的标签、月份与过滤文本重新筛选笔记
+    fn refilter(&mut self) {
+        let tag = &self.tags[self.cursors[0]].0;
+        let month = &self.months[self.cursors[1]].0;
+        let filter: Vec<String> = self
+            .filter
+            .split_whitespace()
+            .map(str::to_lowercase)
+            .collect();
+        self.visible = self
+            .notes
+            .iter()
+            .enumerate()
+            .filter(|(_, meta)| {
+                (tag == ALL
+                    || meta.tags.iter().any(|t| {
+                        t == tag
+                            || t.strip_prefix(tag.as_str())
+                                .is_some_and(|r| r.starts_with('/'))
+                    }))
+                    && (month == ALL || month_of(meta) == *month)
+                    && filter.iter().all(|word| matches_text(meta, word))
+            })
+            .map(|(i, _)| i)
+            .collect();
+        self.cursors[2] = self.cursors[2].min(self.visible.len().saturating_sub(1));
+    }
+
+    fn len(&self, pane: Pane) -> usize {
+        match pane {
+            Pane::Tags => self.tags.len(),
+            Pane::Dates => self.months.len(),
+            Pane::Notes => self.visible.len(),
+        }
+    }
+
+    fn selected(&self) -> Option<&'a NoteMeta> {
+        self.visible.get(self.cursors[2]).map(|&i| self.notes[i])
+    }
+
+    // 把当前栏的光标移到 target（超出范围时取边界）
+    fn move_to(&mut self, target: isize) {
+        let pane = self.focus;
+        let last = self.len(pane).saturating_sub(1) as isize;
+        let target = target.clamp(0, last.max(0)) as usize;
+        if target == self.cursors[pane.index()] {
+            return;
+        }
+        self.cursors[pane.index()] = target;
+        if pane != Pane::Notes {
+            self.cursors[2] = 0;
+            self.refilter();
+        }
+    }
+
+    fn handle(&mut self, key: Key) -> Action {
+        let cursor = self.cursors[self.focus.index()] as isize;
+        let page = self.page.max(1) as isize;
+        if self.filtering {
+            match key {
+                Key::Char(c) => {
+                    self.filter.push(c);
+                    self.cursors[2] = 0;
+                    self.refilter();
+                    return Action::None;
+                }
+                Key::Backspace => {
+                    self.filter.pop();
+                    self.refilter();
+                    return Action::None;
+                }
+                Key::Enter => {
+                    self.filtering = false;
+                    return Action::None;
+                }
+                Key::Esc => {
+                    self.filtering = false;
+                    self.filter.clear();
+                    self.refilter();
+                    return Action::None;
+                }
+                _ => {}
+            }
+        }
+        match key {
+            Key::Char('q') | Key::Ctrl('c') => return Action::Quit,
+            Key::Tab | Key::Right | Key::Char('l') => self.focus = self.focus.next(),
+            Key::BackTab | Key::Left | Key::Char('h') => self.focus = self.focus.previous(),
+            Key::Up | Key::Char('k') => self.move_to(cursor - 1),
+            Key::Down | Key::Char('j') => self.move_to(cursor + 1),
+            Key::PageUp => self.move_to(cursor - page),
+            Key::PageDown => self.move_to(cursor + page),
+            Key::Home | Key::Char('g') => self.move_to(0),
+            Key::End | Key::Char('G') => self.move_to(isize::MAX),
+            Key::Char('/') => {
+                self.filtering = true;
+                self.focus = Pane::Notes;
+            }
+            Key::Esc => {
+                self.filter.clear();
+                self.refilter();
+            }
+            Key::Enter if self.focus == Pane::Notes => {
+                if let Some(meta) = self.selected() {
+                    return Action::Open(meta.name.clone());
+                }
+            }
+            Key::Enter => self.focus = Pane::Notes,
+            _ => {}
+        }
+        Action::None
+    }
+
+    // 绘制整屏，返回 rows 行文本（不含换行）
+    fn render(&mut self, rows: usize, columns: usize) -> Vec<String> {
+        let rows = rows.max(4);
+        let height = rows - 3;
+        self.page = height;
+
+        // 各栏宽度，终端太窄时不显示预览
+        let tags_width = (columns * 18 / 100).max(12);
+        let dates_width = 14;
+        let notes_width = (columns * 30 / 100).max(16);
+        let used = tags_width + dates_width + notes_width + 3;
+        let preview_width = columns.saturating_sub(used);
+
+        let tag_lines = self.list_lines(Pane::Tags, height, |(tag, count)| {
+            format!("{} ({})", tag, count)
+        });
+        let month_lines = self.list_lines(Pane::Dates, height, |(month, count)| {
+            format!("{} ({})", month, count)
+        });
+        let notes: Vec<String> = self
+            .visible
+            .iter()
+            .map(|&i| self.notes[i].title.clone())
+            .collect();
+        let note_lines = self.window(Pane::Notes, height, notes);
+        let preview = match self.selected() {
+            Some(meta) if preview_width > 0 => self.preview(meta, preview_width, height),
+            _ => Vec::new(),
+        };
+
+        let headers = [
+            (Pane::Tags, "标签", tags_width),
+            (Pane::Dates, "日期", dates_width),
+            (Pane::Notes, "笔记", notes_width),
+        ];
+        let mut header = String::new();
+        for (pane, name, width) in headers {
+            let title = format!(
+                " {} ({})",
+                name,
+                self.len(pane) - usize::from(pane != Pane::Notes)
+            );
+            if pane == self.focus {
+                header.push_str(&format!("\x1b[1;7m{}\x1b[0m│", fit(&title, width)));
+            } else {
+                header.push_str(&format!("\x1b[1m{}\x1b[0m│", fit(&title, width)));
+            }
+        }
+        if preview_width > 0 {
+            let title = self
+                .selected()
+                .map_or(String::new(), |m| format!(" {}.md", m.name));
+            header.push_str(&format!("\x1b[1m{}\x1b[0m", fit(&title, preview_width)));
+        }
+
+        let mut screen = vec![header];
+        for row in 0..height {
+            let mut line = String::new();
+            for (lines, width) in [
+                (&tag_lines, tags_width),
+                (&month_lines, dates_width),
+                (&note_lines, notes_width),
+            ] {
+                match lines.get(row) {
+                    Some((text, true)) => {
+                        line.push_str(&format!("\x1b[7m{}\x1b[0m", fit(text, width)))
+                    }
+                    Some((text, false)) => line.push_str(&fit(text, width)),
+                    None => line.push_str(&" ".repeat(width)),
+                }
+                line.push('│');
+            }
+            if let Some(text) = preview.get(row) {
+                line.push_str(&fit(text, preview_width));
+            }
+            screen.push(line);
+        }
+
+        let status = if self.filtering {
+            format!(" 过滤: {}_", self.filter)
+        } else if !self.message.is_empty() {
+            format!(" {}", self.message)
+        } else if !self.filter.is_empty() {
+            format!(" 过滤: {}（Esc 清除）", self.filter)
+        } else {
+            String::new()
+        };
+        screen.push(format!("\x1b[7m{}\x1b[0m", fit(&status, columns)));
+        screen.push(fit(&format!(" {}", HELP_LINE), columns));
+        screen
+    }
+
+    fn list_lines(
+        &mut self,
+        pane: Pane,
+        height: usize,
+        label: impl Fn(&(String, usize)) -> String,
+    ) -> Vec<(String, bool)> {
+        let items = match pane {
+            Pane::Tags => &self.tags,
+            _ => &self.months,
+        };
+        let labels = items.iter().map(label).collect();
+        self.window(pane, height, labels)
+    }
+
+    // 取出包含光标的 height 行，标记光标所在行
+    fn window(&mut self, pane: Pane, height: usize, labels: Vec<String>) -> Vec<(String, bool)> {
+        let i = pane.index();
+        let cursor = self.cursors[i];
+        if cursor < self.offsets[i] {
+            self.offsets[i] = cursor;
+        } else if cursor >= self.offsets[i] + height {
+            self.offsets[i] = cursor + 1 - height;
+        }
+        labels
+            .into_iter()
+            .enumerate()
+            .skip(self.offsets[i])
+            .take(height)
+            .map(|(j, text)| (format!(" {}", text), j == cursor))
+            .collect()
+    }
+
+    // 预览：标题、创建时间、标签与别名，之后为按宽度折行的正文
+    fn preview(&mut self, meta: &NoteMeta, width: usize, height: usize) -> Vec<String> {
+        let root = self.root;
+        let body = self.previews.entry(meta.name.clone()).or_insert_with(|| {
+            let content = fs::read_to_string(root.join(note_file_name(&meta.name)))
+                .unwrap_or_else(|e| format!("无法读取: {}", e));
+            let body = frontmatter::split(&content).map_or(content.as_str(), |(_, body)| body);
+            body.lines().map(clean).collect()
+        });
+
+        let mut lines = vec![format!(" \x1b[1m{}\x1b[0m", meta.title)];
+        if let Some((date, time)) = &meta.created {
+            lines.push(format!(" 创建: {} {}", date, time).trim_end().to_string());
+        }
+        if !meta.tags.is_empty() {
+            lines.push(format!(" 标签: {}", meta.tags.join(", ")));
+        }
+        if !meta.aliases.is_empty() {
+            lines.push(format!(" 别名: {}", meta.aliases.join(", ")));
+        }
+        lines.push(String::new());
+        for line in body.iter().skip_while(|line| line.trim().is_empty()) {
+            if lines.len() >= height {
+                break;
+            }
+            lines.extend(
+                wrap(line, width.saturating_sub(1))
+                    .into_iter()
+                    .map(|l| format!(" {}", l)),
+            );
+        }
+        lines.truncate(height);
+        lines
+    }
+}
+
+// 笔记所属的月份，如 2024-05
+fn month_of(meta: &NoteMeta) -> String {
+    match &meta.created {
+        Some((date, _)) => format!("{:04}-{:02}", date.year, date.month),
+        None => UNDATED.to_string(),
+    }
+}
+
+// word 已转为小写
+fn matches_text(meta: &NoteMeta, word: &str) -> bool {
+    [&meta.title, &meta.name]
+        .into_iter()
+        .chain(&meta.aliases)
+        .any(|text| text.to_lowercase().contains(word))
+}
+
+// 去掉控制字符，制表符换为空格
+fn clean(line: &str) -> String {
+    line.replace('\t', "    ")
+        .chars()
+        .filter(|c| !c.is_control())
+        .collect()
+}
+
+// 按显示宽度折行
+fn wrap(line: &str, width: usize) -> Vec<String> {
+    if width == 0 {
+        return Vec::new();
+    }
+    let mut lines = vec![String::new()];
+    let mut used = 0;
+    for c in line.chars() {
+        let w = char_width(c);
+        if used + w > width {
+            lines.push(String::new());
+            used = 0;
+        }
+        lines.last_mut().expect("至少有一行").push(c);
+        used += w;
+    }
+    lines
+}
+
+// 截断或以空格补齐到恰好 width 列；text 中的转义序列不计宽度
+fn fit(text: &str, width: usize) -> String {
+    let mut result = String::new();
+    let mut used = 0;
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // 原样保留 CSI 序列，直到结尾的字母
+            result.push(c);
+            for c in chars.by_ref() {
+                result.push(c);
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+            continue;
+        }
+        let w = char_width(c);
+        if used + w > width {
+            break;
+        }
+        result.push(c);
+        used += w;
+    }
+    if result.contains('\x1b') {
+        result.push_str("\x1b[0m");
+    }
+    result.push_str(&" ".repeat(width - used));
+    result
+}
+
+// 终端的原始模式与备用屏幕，离开时（包括出错与 panic）恢复原来的设置
+struct Terminal {
+    tty: File,
+    saved: String,
+}
+
+impl Terminal {
+    fn enter() -> io::Result<Terminal> {
+        let tty = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open("/dev/tty")
+            .map_err(|e| io::Error::new(e.kind(), format!("gtx tui 需要在终端中运行: {}", e)))?;
+        let saved = stty(&["-g"])?.trim().to_string();
+        let mut terminal = Terminal { tty, saved };
+        terminal.resume()?;
+        Ok(terminal)
+    }
+
+    // 进入原始模式与备用屏幕并隐藏光标
+    fn resume(&mut self) -> io::Result<()> {
+        stty(&["raw", "-echo"])?;
+        self.tty.write_all(b"\x1b[?1049h\x1b[?25l")?;
+        self.tty.flush()
+    }
+
+    // 恢复终端原来的状态
+    fn leave(&mut self) -> io::Result<()> {
+        self.tty.write_all(b"\x1b[?25h\x1b[?1049l")?;
+        self.tty.flush()?;
+        stty(&[&self.saved]).map(|_| ())
+    }
+
+    fn draw(&mut self, screen: &[String]) -> io::Result<()> {
+        let mut frame = String::from("\x1b[H");
+        for (i, line) in screen.iter().enumerate() {
+            if i > 0 {
+                frame.push_str("\r\n");
+            }
+            frame.push_str(line);
+            frame.push_str("\x1b[K");
+        }
+        self.tty.write_all(frame.as_bytes())?;
+        self.tty.flush()
+    }
+
+    // 阻塞到有输入为止，返回这次读到的全部按键
+    fn read_keys(&mut self) -> io::Result<Vec<Key>> {
+        let mut buf = [0u8; 256];
+        let n = self.tty.read(&mut buf)?;
+        if n == 0 {
+            return Ok(vec![Key::Ctrl('c')]);
+        }
+        Ok(parse_keys(&buf[..n]))
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        let _ = self.leave();
+    }
+}
+
+// 以 /dev/tty 为标准输入运行 stty
+fn stty(args: &[&str]) -> io::Result<String> {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(File::open("/dev/tty")?)
+        .output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "stty 执行失败: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+// 把一次读到的字节解析为按键；无法识别的转义序列忽略
+fn parse_keys(bytes: &[u8]) -> Vec<Key> {
+    let mut keys = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i];
+        i += 1;
+        let key = match b {
+            0x1b if i < bytes.len() && (bytes[i] == b'[' || bytes[i] == b'O') => {
+                // CSI / SS3 序列：参数之后以字母或 ~ 结尾
+                let start = i + 1;
+                let mut end = start;
+                while end < bytes.len() && !(bytes[end].is_ascii_alphabetic() || bytes[end] == b'~')
+                {
+                    end += 1;
+                }
+                if end == bytes.len() {
+                    i = end;
+                    continue;
+                }
+                i = end + 1;
+                match (&bytes[start..end], bytes[end]) {
+                    (_, b'A') => Key::Up,
+                    (_, b'B') => Key::Down,
+                    (_, b'C') => Key::Right,
+                    (_, b'D') => Key::Left,
+                    (_, b'H') | (b"1" | b"7", b'~') => Key::Home,
+                    (_, b'F') | (b"4" | b"8", b'~') => Key::End,
+                    (b"5", b'~') => Key::PageUp,
+                    (b"6", b'~') => Key::PageDown,
+                    (_, b'Z') => Key::BackTab,
+                    _ => continue,
+                }
+            }
+            0x1b => Key::Esc,
+            b'\r' | b'\n' => Key::Enter,
+            b'\t' => Key::Tab,
+            0x7f | 0x08 => Key::Backspace,
+            1..=26 => Key::Ctrl(char::from(b'a' + b - 1)),
+            _ => {
+                // UTF-8 字符，输入法输入的中文等
+                let len = match b {
+                    0xc0..=0xdf => 2,
+                    0xe0..=0xef => 3,
+                    0xf0..=0xf7 => 4,
+                    _ => 1,
+                };
+                let end = (i - 1 + len).min(bytes.len());
+                let text = String::from_utf8_lossy(&bytes[i - 1..end]).into_owned();
+                i = end;
+                match text.chars().next() {
+                    Some(c) if !c.is_control() && c != char::REPLACEMENT_CHARACTER => Key::Char(c),
+                    _ => continue,
+                }
+            }
+        };
+        keys.push(key);
+    }
+    keys
+}
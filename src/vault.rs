@@ -0,0 +1,487 @@
+// 博客目录的扫描
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::thread;
+
+use crate::archive::ARCHIVE_DIR;
+use crate::cache::{Cache, CachedNote, FileStamp};
+use crate::frontmatter;
+use crate::git;
+use crate::glob;
+use crate::i18n;
+use crate::index::IndexSet;
+use crate::log::{self, Progress};
+use crate::note::{FrontmatterKeys, NoteMeta, ParsedFile, note_file_name, read_note_with};
+use crate::query;
+use crate::walk;
+
+/// 没有 Created 字段的笔记的创建时间来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DateSource {
+    /// 只使用文件头，没有 Created 的笔记不出现在日期页
+    #[default]
+    Frontmatter,
+    /// 按 `git log --follow` 补全创建与修改时间
+    Git,
+}
+
+impl DateSource {
+    /// 配置文件、命令行与缓存中使用的名称
+    pub fn name(self) -> &'static str {
+        match self {
+            DateSource::Frontmatter => "frontmatter",
+            DateSource::Git => "git",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<DateSource> {
+        match name {
+            "frontmatter" => Some(DateSource::Frontmatter),
+            "git" => Some(DateSource::Git),
+            _ => None,
+        }
+    }
+}
+
+/// 扫描选项
+pub struct ScanOptions<'a> {
+    /// 未变化的笔记直接使用缓存中的解析结果
+    pub cache: &'a Cache,
+    /// 报告进度：显示进度条，verbose 级别下另外列出每个重新解析的文件，见 `log`
+    pub verbose: bool,
+    /// 跳过路径匹配这些通配符的笔记与目录，排在博客目录下 .gtxignore 的规则之后，写法相同
+    pub exclude: &'a [String],
+    pub keys: &'a FrontmatterKeys,
+    /// 同时扫描 .org 笔记
+    pub org: bool,
+    pub date_source: DateSource,
+    /// 只重新解析这些路径（相对博客目录）的笔记，其余笔记有缓存时直接使用，不比较修改时间
+    pub changed: Option<&'a HashSet<String>>,
+    /// 把草稿加入标签、日期索引；为 false 时草稿只作为链接目标，并列在 drafts.md 中
+    pub drafts: bool,
+    /// 跟随指向笔记或目录的符号链接；为 false 时跳过符号链接，见 `note_files`
+    pub follow_symlinks: bool,
+}
+
+/// 无法加入索引的文件
+#[derive(Debug, Clone)]
+pub struct Problem {
+    pub path: PathBuf,
+    pub reason: String,
+}
+
+impl Problem {
+    /// 读取或解析 path 失败；没有权限时的说明换成易懂的文字
+    pub fn from_io(path: &Path, e: &io::Error) -> Problem {
+        let reason = match e.kind() {
+            io::ErrorKind::PermissionDenied => i18n::tr("没有读取权限").to_string(),
+            _ => e.to_string(),
+        };
+        Problem {
+            path: path.to_path_buf(),
+            reason,
+        }
+    }
+}
+
+/// 扫描中跳过的文件：单个文件读取或解析失败不会中断扫描，其余笔记照常建立索引，
+/// 跳过的文件在最后统一报告
+#[derive(Debug, Clone, Default)]
+pub struct ScanReport {
+    /// 按路径排序
+    pub skipped: Vec<Problem>,
+}
+
+impl ScanReport {
+    pub fn is_empty(&self) -> bool {
+        self.skipped.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.skipped.len()
+    }
+
+    /// 记下一个跳过的文件
+    pub fn skip(&mut self, path: &Path, e: &io::Error) {
+        self.skipped.push(Problem::from_io(path, e));
+    }
+
+    fn sort(&mut self) {
+        self.skipped.sort_by(|a, b| a.path.cmp(&b.path));
+    }
+}
+
+impl fmt::Display for ScanReport {
+    /// 跳过的文件列表，每个文件一行，附上原因
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            i18n::trf("以下 {} 个文件被跳过，未加入索引:", &[&self.len()])
+        )?;
+        for problem in &self.skipped {
+            write!(f, "\n  {}: {}", problem.path.display(), problem.reason)?;
+        }
+        Ok(())
+    }
+}
+
+/// 扫描一个博客目录得到的笔记与索引
+pub struct Vault {
+    root: PathBuf,
+    /// 文件名（不含 .md，org 笔记含 .org） -> 笔记
+    notes: HashMap<String, CachedNote>,
+    /// 扫描时发现的旧生成页面（只有 Title 的文件头）
+    stale_pages: Vec<PathBuf>,
+    /// 读取或解析失败的文件，其余笔记照常建立索引
+    report: ScanReport,
+    indexes: IndexSet,
+    keys: FrontmatterKeys,
+    date_source: DateSource,
+    /// 扫描时是否把草稿加入索引，见 `ScanOptions::drafts`
+    drafts: bool,
+}
+
+impl Vault {
+    /// 扫描目录下所有 .md 笔记并建立索引
+    pub fn scan(path: &Path) -> io::Result<Vault> {
+        Vault::scan_with_cache(path, &Cache::default(), false)
+    }
+
+    /// 与 `scan` 相同，但未变化的笔记直接使用缓存，缓存中记录的生成页面会被跳过
+    ///
+    /// verbose 为 true 时报告进度，见 `ScanOptions`
+    pub fn scan_with_cache(path: &Path, cache: &Cache, verbose: bool) -> io::Result<Vault> {
+        Vault::scan_with(
+            path,
+            &ScanOptions {
+                cache,
+                verbose,
+                exclude: &[],
+                keys: &FrontmatterKeys::default(),
+                org: false,
+                date_source: DateSource::Frontmatter,
+                changed: None,
+                drafts: false,
+                follow_symlinks: true,
+            },
+        )
+    }
+
+    /// 按选项扫描目录
+    pub fn scan_with(path: &Path, options: &ScanOptions) -> io::Result<Vault> {
+        let cache = options.cache;
+        let files = note_files(
+            path,
+            cache,
+            options.exclude,
+            options.org,
+            options.follow_symlinks,
+        )?;
+
+        // 按 CPU 核数分块并行解析，每个线程产生一份局部索引，最后合并
+        let workers = thread::available_parallelism().map_or(1, |n| n.get());
+        let chunk_size = files.len().div_ceil(workers).max(1);
+        // 键名或时间来源变化后缓存的解析结果不再可用
+        let cache = if cache.keys == *options.keys && cache.date_source == options.date_source {
+            Some(cache)
+        } else {
+            None
+        };
+        let keys = options.keys;
+        let git = options.date_source == DateSource::Git;
+        let progress = Progress::new("扫描笔记", files.len(), options.verbose);
+        let results: Vec<ScanResult> = thread::scope(|scope| {
+            let handles: Vec<_> = files
+                .chunks(chunk_size)
+                .map(|chunk| {
+                    let handle =
+                        scope.spawn(|| scan_files(chunk, cache, keys, git, options, &progress));
+                    (chunk, handle)
+                })
+                .collect();
+            // 解析单篇笔记时的 panic 已在 scan_files 中记为跳过；线程仍然意外结束时，
+            // 整块文件记为跳过，其余线程的结果照常使用
+            handles
+                .into_iter()
+                .map(|(chunk, handle)| {
+                    handle.join().unwrap_or_else(|payload| {
+                        let reason = panic_reason(payload.as_ref());
+                        let mut result = ScanResult::default();
+                        for entry in chunk {
+                            result.report.skipped.push(Problem {
+                                path: entry.path.clone(),
+                                reason: reason.clone(),
+                            });
+                        }
+                        result
+                    })
+                })
+                .collect()
+        });
+        drop(progress);
+
+        let mut vault = Vault {
+            root: path.to_path_buf(),
+            notes: HashMap::new(),
+            stale_pages: Vec::new(),
+            report: ScanReport::default(),
+            indexes: IndexSet::new(),
+            keys: options.keys.clone(),
+            date_source: options.date_source,
+            drafts: options.drafts,
+        };
+        for result in results {
+            vault.indexes.merge(result.indexes);
+            vault.notes.extend(result.notes);
+            vault.stale_pages.extend(result.generated);
+            vault.report.skipped.extend(result.report.skipped);
+        }
+        vault.report.sort();
+
+        Ok(vault)
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn indexes(&self) -> &IndexSet {
+        &self.indexes
+    }
+
+    /// 所有笔记，顺序不固定
+    pub fn notes(&self) -> impl Iterator<Item = &NoteMeta> {
+        self.notes.values().map(|note| &note.meta)
+    }
+
+    /// 可以发布的笔记，顺序不固定：与索引一致，扫描时没有选择包含草稿则不含草稿。
+    /// 导出、静态站点与 API 都经由这里取笔记
+    pub fn published(&self) -> impl Iterator<Item = &NoteMeta> {
+        self.notes().filter(|note| self.drafts || !note.draft)
+    }
+
+    /// 按文件名查找可以发布的笔记，见 `published`
+    pub fn published_note(&self, name: &str) -> Option<&NoteMeta> {
+        self.note(name).filter(|note| self.drafts || !note.draft)
+    }
+
+    /// 按文件名查找笔记
+    pub fn note(&self, name: &str) -> Option<&NoteMeta> {
+        self.notes.get(name).map(|note| &note.meta)
+    }
+
+    /// 笔记的正文，不含文件头；查询块连同上次写入的结果换成按可以发布的笔记得到的结果列表，
+    /// 见 `query::expand_blocks`。导出与静态站点都经由这里读取正文
+    pub fn note_body(&self, note: &NoteMeta) -> io::Result<String> {
+        let content = fs::read_to_string(self.note_path(&note.name))?;
+        let body = frontmatter::split(&content).map_or(content.as_str(), |(_, body)| body);
+        if !note.queries {
+            return Ok(body.to_string());
+        }
+        let notes: Vec<&NoteMeta> = self.published().collect();
+        Ok(query::expand_blocks(body, &notes).into_owned())
+    }
+
+    /// 笔记文件的路径
+    pub fn note_path(&self, name: &str) -> PathBuf {
+        self.root.join(note_file_name(name))
+    }
+
+    pub fn stale_pages(&self) -> &[PathBuf] {
+        &self.stale_pages
+    }
+
+    /// 读取或解析失败、未加入索引的文件
+    pub fn report(&self) -> &ScanReport {
+        &self.report
+    }
+
+    /// 以本次扫描结果生成新的缓存，`generated`、`output` 与生成页面所用的各项设置需在写入页面后补充
+    pub fn to_cache(&self) -> Cache {
+        Cache {
+            notes: self
+                .notes
+                .iter()
+                .map(|(name, note)| (name.clone(), note.clone()))
+                .collect(),
+            generated: Default::default(),
+            output: None,
+            keys: self.keys.clone(),
+            date_source: self.date_source,
+            link_style: Default::default(),
+            templates: String::new(),
+            tag_sorts: String::new(),
+            time_format: Default::default(),
+            include_drafts: false,
+            tag_dir: String::new(),
+            excerpt: 0,
+        }
+    }
+}
+
+/// 目录下待处理的 .md 文件（org 为 true 时包括 .org 文件），包括各层子目录中的笔记：跳过缓存中记录的生成页面、
+/// .gtxignore 与 exclude 排除的文件与目录，以及 `skipped_dirs` 列出的目录。
+/// 缓存中没有记录、文件头带有 `generator: gtx` 的页面在解析时识别
+///
+/// follow_symlinks 为 true 时，指向文件或目录的符号链接与普通文件、目录一样加入，链接成环时不会重复进入；
+/// 多个路径指向同一文件时只保留一个，优先保留文件本身，其次是路径最小的链接。为 false 时跳过全部符号链接。
+pub fn note_files(
+    path: &Path,
+    cache: &Cache,
+    exclude: &[String],
+    org: bool,
+    follow_symlinks: bool,
+) -> io::Result<Vec<walk::Entry>> {
+    let mut rules = glob::load_ignore(path)?;
+    rules.extend(exclude.iter().cloned());
+    let skipped = skipped_dirs(path, cache);
+    let skip = |dir: &str| skipped.contains(dir) || glob::is_excluded(&rules, dir, true);
+    let files = walk::files(path, follow_symlinks, &skip)?
+        .into_iter()
+        .filter(|entry| {
+            let note = entry.relative.ends_with(".md") || (org && entry.relative.ends_with(".org"));
+            // 上次生成在博客目录中的页面稍后会重新生成
+            let generated = cache.output.is_none() && cache.generated.contains(&entry.relative);
+            note && !generated && !glob::is_excluded(&rules, &entry.relative, false)
+        })
+        .collect();
+    Ok(walk::dedup(files))
+}
+
+/// 扫描笔记时不进入的子目录，相对博客目录：归档目录，以及位于博客目录之中的输出目录。
+/// 页面生成在博客目录中时，反向链接、标签等页面所在的目录照常扫描，其中的用户笔记同样建立索引，
+/// 生成的页面按 `note_files` 的说明跳过
+pub fn skipped_dirs(root: &Path, cache: &Cache) -> HashSet<String> {
+    let mut dirs = HashSet::from([ARCHIVE_DIR.to_string()]);
+    if let Some(output) = &cache.output
+        && let (Ok(root), Ok(output)) = (fs::canonicalize(root), fs::canonicalize(output))
+        && let Ok(relative) = output.strip_prefix(&root)
+        && !relative.as_os_str().is_empty()
+    {
+        let relative: Vec<_> = relative
+            .components()
+            .map(|part| part.as_os_str().to_string_lossy())
+            .collect();
+        dirs.insert(relative.join("/"));
+    }
+    dirs
+}
+
+// 单个扫描线程的结果
+#[derive(Default)]
+struct ScanResult {
+    indexes: IndexSet,
+    notes: Vec<(String, CachedNote)>,
+    // 扫描到的旧生成页面，由调用方统一清理
+    generated: Vec<PathBuf>,
+    report: ScanReport,
+}
+
+// git 为 true 时按 git 历史补全没有 Created 字段的笔记的时间；options 中的 changed、
+// drafts 与 verbose 同样生效，cache 与 keys 以参数为准
+fn scan_files(
+    files: &[walk::Entry],
+    cache: Option<&Cache>,
+    keys: &FrontmatterKeys,
+    git: bool,
+    options: &ScanOptions,
+    progress: &Progress,
+) -> ScanResult {
+    let changed = options.changed;
+    let mut result = ScanResult::default();
+
+    for entry in files {
+        progress.inc();
+        let file_path = &entry.path;
+        // 笔记名称为相对博客目录的路径，不含 .md；org 笔记的名称保留后缀
+        let name = entry
+            .relative
+            .strip_suffix(".md")
+            .unwrap_or(&entry.relative)
+            .to_string();
+        let stamp = match fs::metadata(file_path) {
+            Ok(metadata) => FileStamp::from_metadata(&metadata),
+            Err(e) => {
+                result.report.skip(file_path, &e);
+                continue;
+            }
+        };
+
+        // 未变化的笔记直接使用缓存；上次尚未提交的笔记再查询一次 git 历史。
+        // 缓存中保留原来的修改时间，下次完整扫描时仍会发现变化
+        let trusted = changed.is_some_and(|changed| !changed.contains(&entry.relative));
+        if let Some(cached) = cache.and_then(|cache| cache.notes.get(&name))
+            && (trusted || cached.stamp == stamp)
+        {
+            let mut cached = cached.clone();
+            if git {
+                fill_git_dates(file_path, &mut cached.meta);
+            }
+            add_to_indexes(&mut result.indexes, &cached.meta, &stamp, options.drafts);
+            result.notes.push((name, cached));
+            continue;
+        }
+
+        if options.verbose {
+            log::debug(i18n::trf("\n=== 处理文件: {} ===", &[&file_path.display()]));
+        }
+
+        // 解析器的缺陷只让这一篇笔记被跳过，不中断扫描
+        let parsed = panic::catch_unwind(AssertUnwindSafe(|| read_note_with(file_path, keys)))
+            .unwrap_or_else(|payload| Err(io::Error::other(panic_reason(payload.as_ref()))));
+        match parsed {
+            Ok(ParsedFile::Note(mut meta)) => {
+                meta.name = name.clone();
+                if git {
+                    fill_git_dates(file_path, &mut meta);
+                }
+                add_to_indexes(&mut result.indexes, &meta, &stamp, options.drafts);
+                result.notes.push((name, CachedNote { stamp, meta: *meta }));
+            }
+            Ok(ParsedFile::Generated) => result.generated.push(file_path.clone()),
+            Err(e) => result.report.skip(file_path, &e),
+        }
+    }
+
+    result
+}
+
+// panic 的说明文字，用作跳过文件的原因
+fn panic_reason(payload: &(dyn std::any::Any + Send)) -> String {
+    let message = payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("?");
+    i18n::trf("解析时程序出错: {}", &[&message])
+}
+
+// drafts 为 false 时草稿只作为链接目标，不进入标签、日期索引与最近修改的笔记
+fn add_to_indexes(indexes: &mut IndexSet, meta: &NoteMeta, stamp: &FileStamp, drafts: bool) {
+    if meta.draft {
+        indexes.drafts.push((meta.name.clone(), meta.title.clone()));
+    }
+    if meta.draft && !drafts {
+        indexes.add_draft(meta);
+        return;
+    }
+    indexes.add_note(meta);
+    indexes.modified.insert(meta.name.clone(), stamp.mtime_secs);
+}
+
+// 没有 Created 字段的笔记以首次提交为创建时间；没有 Updated 字段时以最近一次提交为更新时间
+fn fill_git_dates(file_path: &Path, meta: &mut NoteMeta) {
+    if meta.created.is_some() || meta.invalid_created.is_some() {
+        return;
+    }
+    if let Some(dates) = git::file_dates(file_path) {
+        meta.created = Some(dates.created);
+        meta.updated = meta.updated.take().or(Some(dates.modified));
+    }
+}
@@ -0,0 +1,188 @@
+// 目录的递归遍历，笔记扫描、gtx watch 与附件索引共用
+//
+// 先走完不经过符号链接的部分，再进入符号链接指向的目录：同一目录既能直接到达、又能经由链接到达时，
+// 保留直接到达的路径。已进入的目录按真实路径记下，链接成环或多个链接指向同一目录时只进入一次。
+// 以 . 开头的目录（.git、.gtx、.obsidian 等）总是跳过。
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::i18n;
+use crate::log;
+
+/// 遍历得到的一个文件
+#[derive(Debug, Clone)]
+pub struct Entry {
+    /// 以遍历起点开头的路径
+    pub path: PathBuf,
+    /// 相对遍历起点、以 / 分隔的路径
+    pub relative: String,
+    /// 文件本身是符号链接
+    pub symlink: bool,
+}
+
+// 待进入的目录
+#[derive(Default)]
+struct Pending {
+    direct: Vec<(PathBuf, String)>,
+    linked: Vec<(PathBuf, String)>,
+}
+
+/// 递归列出 root 下的文件，按相对路径排序
+///
+/// follow_symlinks 为 false 时跳过全部符号链接。skip 收到子目录的相对路径，返回 true 时不进入。
+/// 指向不存在的位置的链接被跳过；无法读取的子目录也被跳过，在 verbose 级别下列出。
+pub fn files(
+    root: &Path,
+    follow_symlinks: bool,
+    skip: &dyn Fn(&str) -> bool,
+) -> io::Result<Vec<Entry>> {
+    let mut seen = HashSet::from([fs::canonicalize(root)?]);
+    let mut files = Vec::new();
+    let mut pending = Pending::default();
+    read_dir(root, "", follow_symlinks, skip, &mut files, &mut pending)?;
+
+    while let Some((path, relative)) = pending.direct.pop().or_else(|| pending.linked.pop()) {
+        let Ok(target) = fs::canonicalize(&path) else {
+            continue;
+        };
+        if !seen.insert(target) {
+            continue;
+        }
+        if let Err(e) = read_dir(
+            &path,
+            &relative,
+            follow_symlinks,
+            skip,
+            &mut files,
+            &mut pending,
+        ) {
+            log::debug(i18n::trf("跳过目录 {}: {}", &[&path.display(), &e]));
+        }
+    }
+
+    files.sort_by(|a, b| a.relative.cmp(&b.relative));
+    Ok(files)
+}
+
+fn read_dir(
+    dir: &Path,
+    relative: &str,
+    follow_symlinks: bool,
+    skip: &dyn Fn(&str) -> bool,
+    files: &mut Vec<Entry>,
+    pending: &mut Pending,
+) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let symlink = entry.file_type()?.is_symlink();
+        if symlink && !follow_symlinks {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let child = if relative.is_empty() {
+            name.clone()
+        } else {
+            format!("{}/{}", relative, name)
+        };
+        let path = entry.path();
+        // 跟随链接取得目标的类型；指向不存在的位置的链接跳过
+        let Ok(metadata) = fs::metadata(&path) else {
+            continue;
+        };
+        if metadata.is_dir() {
+            if name.starts_with('.') || skip(&child) {
+                continue;
+            }
+            if symlink {
+                pending.linked.push((path, child));
+            } else {
+                pending.direct.push((path, child));
+            }
+        } else if metadata.is_file() {
+            files.push(Entry {
+                path,
+                relative: child,
+                symlink,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// 去掉指向同一文件的重复条目：文件本身优先于符号链接，其次取相对路径最小的；
+/// 无法解析真实路径的文件原样保留，读取时再报告。结果按相对路径排序
+pub fn dedup(mut files: Vec<Entry>) -> Vec<Entry> {
+    files.sort_by(|a, b| (a.symlink, &a.relative).cmp(&(b.symlink, &b.relative)));
+    let mut seen: HashMap<PathBuf, String> = HashMap::new();
+    let mut kept = Vec::new();
+    for entry in files {
+        if let Ok(target) = fs::canonicalize(&entry.path) {
+            if let Some(first) = seen.get(&target) {
+                log::debug(i18n::trf(
+                    "跳过 {}: 与 {} 是同一文件",
+                    &[&entry.relative, first],
+                ));
+                continue;
+            }
+            seen.insert(target, entry.relative.clone());
+        }
+        kept.push(entry);
+    }
+    kept.sort_by(|a, b| a.relative.cmp(&b.relative));
+    kept
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("gtx-walk-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn relative(files: &[Entry]) -> Vec<&str> {
+        files.iter().map(|entry| entry.relative.as_str()).collect()
+    }
+
+    #[test]
+    fn walks_subdirectories_and_skips_hidden_and_skipped() {
+        let dir = temp_dir("skip");
+        fs::create_dir_all(dir.join("a/b")).unwrap();
+        fs::create_dir_all(dir.join(".git")).unwrap();
+        fs::create_dir_all(dir.join("out")).unwrap();
+        for file in ["x.md", "a/y.md", "a/b/z.md", ".git/h.md", "out/o.md"] {
+            fs::write(dir.join(file), "").unwrap();
+        }
+        let files = files(&dir, true, &|path| path == "out").unwrap();
+        assert_eq!(relative(&files), ["a/b/z.md", "a/y.md", "x.md"]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlink_cycles_are_entered_once() {
+        let dir = temp_dir("cycle");
+        fs::create_dir_all(dir.join("a")).unwrap();
+        fs::write(dir.join("a/n.md"), "").unwrap();
+        std::os::unix::fs::symlink("..", dir.join("a/up")).unwrap();
+        std::os::unix::fs::symlink("a", dir.join("link")).unwrap();
+        std::os::unix::fs::symlink("a/n.md", dir.join("n.md")).unwrap();
+
+        let files = files(&dir, true, &|_| false).unwrap();
+        assert_eq!(relative(&files), ["a/n.md", "n.md"]);
+        assert_eq!(relative(&dedup(files)), ["a/n.md"]);
+        assert!(
+            super::files(&dir, false, &|_| false)
+                .unwrap()
+                .iter()
+                .all(|entry| !entry.symlink)
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,107 @@
+// 轮询方式的目录监视
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+use crate::build::cache_path;
+use crate::cache::{Cache, FileStamp};
+use crate::glob::IGNORE_FILE;
+use crate::vault::skipped_dirs;
+use crate::walk;
+
+/// 监视目录及各层子目录下的 .md、.org 文件与 .gtxignore，通过定期比较修改时间与大小发现变化；
+/// 与扫描笔记时一样跳过隐藏目录与 `vault::skipped_dirs` 列出的目录
+pub struct Watcher {
+    root: PathBuf,
+    /// 不监视的子目录，按上次构建的缓存得出
+    skipped: HashSet<String>,
+    /// 两次检查之间的间隔
+    interval: Duration,
+    /// 发现变化后需保持稳定的时间，期间的新变化会重新计时
+    debounce: Duration,
+    snapshot: HashMap<PathBuf, FileStamp>,
+}
+
+impl Watcher {
+    pub fn new(root: &Path, interval: Duration, debounce: Duration) -> io::Result<Watcher> {
+        let skipped = load_skipped(root);
+        Ok(Watcher {
+            root: root.to_path_buf(),
+            snapshot: snapshot(root, &skipped)?,
+            skipped,
+            interval,
+            debounce,
+        })
+    }
+
+    /// 以当前目录状态为基准，用于忽略自身写入的页面；构建可能生成了新的目录，跳过的目录一并更新
+    pub fn resync(&mut self) -> io::Result<()> {
+        self.skipped = load_skipped(&self.root);
+        self.snapshot = snapshot(&self.root, &self.skipped)?;
+        Ok(())
+    }
+
+    /// 阻塞直到有文件新增、修改或删除，并在 debounce 时间内不再变化，返回变化的文件
+    pub fn wait_for_changes(&mut self) -> io::Result<Vec<PathBuf>> {
+        let mut changed = Vec::new();
+        loop {
+            // 已发现变化时改用 debounce 间隔确认文件是否稳定
+            thread::sleep(if changed.is_empty() {
+                self.interval
+            } else {
+                self.debounce
+            });
+            let current = snapshot(&self.root, &self.skipped)?;
+            let changes = diff(&self.snapshot, &current);
+            self.snapshot = current;
+
+            if changes.is_empty() && !changed.is_empty() {
+                changed.sort();
+                return Ok(changed);
+            }
+            for path in changes {
+                if !changed.contains(&path) {
+                    changed.push(path);
+                }
+            }
+        }
+    }
+}
+
+fn load_skipped(root: &Path) -> HashSet<String> {
+    let cache = Cache::load(&cache_path(root)).unwrap_or_default();
+    skipped_dirs(root, &cache)
+}
+
+fn snapshot(root: &Path, skipped: &HashSet<String>) -> io::Result<HashMap<PathBuf, FileStamp>> {
+    let mut files = HashMap::new();
+    for entry in walk::files(root, true, &|dir| skipped.contains(dir))? {
+        if (entry.relative.ends_with(".md")
+            || entry.relative.ends_with(".org")
+            || entry.relative == IGNORE_FILE)
+            && let Ok(metadata) = fs::metadata(&entry.path)
+        {
+            files.insert(entry.path, FileStamp::from_metadata(&metadata));
+        }
+    }
+    Ok(files)
+}
+
+fn diff(before: &HashMap<PathBuf, FileStamp>, after: &HashMap<PathBuf, FileStamp>) -> Vec<PathBuf> {
+    let mut changed: Vec<PathBuf> = after
+        .iter()
+        .filter(|(path, stamp)| before.get(*path) != Some(stamp))
+        .map(|(path, _)| path.clone())
+        .collect();
+    changed.extend(
+        before
+            .keys()
+            .filter(|path| !after.contains_key(*path))
+            .cloned(),
+    );
+    changed
+}
@@ -0,0 +1,156 @@
+// 只存储、不压缩的 ZIP 写入，用于 EPUB 导出
+//
+// 每个文件写一个本地文件头与原始内容，最后写中央目录与目录结束记录；文件名按 UTF-8 标记，
+// 修改时间固定为 1980-01-01，使同样的内容得到同样的文件。不支持 ZIP64，总大小须小于 4 GiB。
+
+// 通用标志位 11：文件名为 UTF-8
+const UTF8_NAMES: u16 = 0x0800;
+
+// MS-DOS 日期 1980-01-01
+const DOS_DATE: u16 = (1 << 5) | 1;
+
+/// 在内存中构建 ZIP 文件
+#[derive(Default)]
+pub struct ZipWriter {
+    data: Vec<u8>,
+    entries: Vec<Entry>,
+}
+
+struct Entry {
+    name: String,
+    crc: u32,
+    size: u32,
+    offset: u32,
+}
+
+impl ZipWriter {
+    pub fn new() -> ZipWriter {
+        ZipWriter::default()
+    }
+
+    /// 按顺序加入一个文件，name 以 / 分隔目录
+    pub fn add(&mut self, name: &str, content: &[u8]) {
+        let entry = Entry {
+            name: name.to_string(),
+            crc: crc32(content),
+            size: content.len() as u32,
+            offset: self.data.len() as u32,
+        };
+        let out = &mut self.data;
+        put32(out, 0x0403_4b50);
+        put16(out, 20);
+        put16(out, UTF8_NAMES);
+        put16(out, 0);
+        put16(out, 0);
+        put16(out, DOS_DATE);
+        put32(out, entry.crc);
+        put32(out, entry.size);
+        put32(out, entry.size);
+        put16(out, name.len() as u16);
+        put16(out, 0);
+        out.extend_from_slice(name.as_bytes());
+        out.extend_from_slice(content);
+        self.entries.push(entry);
+    }
+
+    /// 写入中央目录，返回完整的 ZIP 文件内容
+    pub fn finish(mut self) -> Vec<u8> {
+        let start = self.data.len() as u32;
+        let out = &mut self.data;
+        for entry in &self.entries {
+            put32(out, 0x0201_4b50);
+            put16(out, 20);
+            put16(out, 20);
+            put16(out, UTF8_NAMES);
+            put16(out, 0);
+            put16(out, 0);
+            put16(out, DOS_DATE);
+            put32(out, entry.crc);
+            put32(out, entry.size);
+            put32(out, entry.size);
+            put16(out, entry.name.len() as u16);
+            put16(out, 0);
+            put16(out, 0);
+            put16(out, 0);
+            put16(out, 0);
+            put32(out, 0);
+            put32(out, entry.offset);
+            out.extend_from_slice(entry.name.as_bytes());
+        }
+        let size = out.len() as u32 - start;
+        put32(out, 0x0605_4b50);
+        put16(out, 0);
+        put16(out, 0);
+        put16(out, self.entries.len() as u16);
+        put16(out, self.entries.len() as u16);
+        put32(out, size);
+        put32(out, start);
+        put16(out, 0);
+        self.data
+    }
+}
+
+fn put16(out: &mut Vec<u8>, value: u16) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put32(out: &mut Vec<u8>, value: u32) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+// CRC-32（IEEE 802.3，反射多项式 0xEDB88320），逐位计算
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crc_matches_known_values() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(
+            crc32(b"The quick brown fox jumps over the lazy dog"),
+            0x414F_A339
+        );
+    }
+
+    #[test]
+    fn layout_of_stored_entries() {
+        let mut zip = ZipWriter::new();
+        zip.add("mimetype", b"application/epub+zip");
+        zip.add("目录/a.txt", b"abc");
+        let data = zip.finish();
+        let u16_at = |i: usize| u16::from_le_bytes([data[i], data[i + 1]]);
+        let u32_at = |i: usize| u32::from_le_bytes(data[i..i + 4].try_into().unwrap());
+
+        // 第一个文件不压缩，内容紧跟在 30 字节的文件头与文件名之后
+        assert_eq!(u32_at(0), 0x0403_4b50);
+        assert_eq!(u16_at(8), 0);
+        assert_eq!(&data[30..38], b"mimetype");
+        assert_eq!(&data[38..58], b"application/epub+zip");
+
+        // 目录结束记录位于末尾 22 字节，记录文件数与中央目录的位置
+        let end = data.len() - 22;
+        assert_eq!(u32_at(end), 0x0605_4b50);
+        assert_eq!(u16_at(end + 10), 2);
+        let central = u32_at(end + 16) as usize;
+        assert_eq!(u32_at(central), 0x0201_4b50);
+        assert_eq!(u16_at(central + 8), UTF8_NAMES);
+        assert_eq!(u32_at(central + 16), crc32(b"application/epub+zip"));
+        assert_eq!(central + u32_at(end + 12) as usize, end);
+    }
+}
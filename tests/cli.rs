@@ -0,0 +1,748 @@
+// 命令行的端到端测试：在临时目录中建立笔记，运行编译好的 gtx，检查生成与保留的文件
+
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// 测试用的临时博客目录，结束时删除
+struct TempVault {
+    root: PathBuf,
+}
+
+impl TempVault {
+    fn new(name: &str) -> TempVault {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let root = std::env::temp_dir().join(format!(
+            "gtx-test-{}-{}-{}",
+            name,
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::SeqCst)
+        ));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        TempVault { root }
+    }
+
+    fn write(&self, name: &str, content: &str) {
+        let path = self.root.join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    fn read(&self, name: &str) -> String {
+        fs::read_to_string(self.root.join(name)).unwrap()
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        self.root.join(name)
+    }
+
+    // 在博客目录上运行 gtx，args 之后附上目录路径；用户配置指向不存在的目录
+    fn gtx(&self, args: &[&str]) -> Output {
+        self.command(args).arg(&self.root).output().unwrap()
+    }
+
+    // 同 gtx，但用 -d 指定博客目录，用于不接受目录参数的子命令
+    fn gtx_dir(&self, args: &[&str]) -> Output {
+        self.command(args)
+            .arg("-d")
+            .arg(&self.root)
+            .output()
+            .unwrap()
+    }
+
+    // 同 gtx，并把 input 写入标准输入
+    fn gtx_input(&self, args: &[&str], input: &str) -> Output {
+        let mut child = self
+            .command(args)
+            .arg(&self.root)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(input.as_bytes())
+            .unwrap();
+        child.wait_with_output().unwrap()
+    }
+
+    fn command(&self, args: &[&str]) -> Command {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_gtx"));
+        command
+            .args(args)
+            .env("HOME", &self.root)
+            .env("XDG_CONFIG_HOME", self.root.join(".no-config"))
+            .env_remove("GTX_VAULT")
+            .env("GTX_LANG", "zh");
+        command
+    }
+}
+
+impl Drop for TempVault {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.root);
+    }
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[test]
+fn title_only_note_survives_index_and_purge() {
+    let vault = TempVault::new("title-only");
+    let note = "---\nTitle: My important note\n---\nbody text\n";
+    vault.write("mine.md", note);
+    vault.write(
+        "other.md",
+        "---\nTitle: Other\nCreated: 2024-05-01\ntags: [rust]\n---\nsee [[mine]]\n",
+    );
+
+    let output = vault.gtx(&["index"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(vault.read("mine.md"), note);
+    assert!(!vault.path(".gtx/trash/mine.md").exists());
+    assert!(vault.read("index.md").contains("mine"));
+
+    let output = vault.gtx(&["index", "--purge", "--full"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(vault.read("mine.md"), note);
+    // 没有缓存时同样按笔记处理
+    fs::remove_dir_all(vault.path(".gtx")).unwrap();
+    let output = vault.gtx(&["index", "--purge"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(vault.read("mine.md"), note);
+    assert!(!stdout(&output).contains("mine.md"));
+}
+
+#[test]
+fn user_notes_named_like_pages_are_not_overwritten() {
+    let vault = TempVault::new("page-names");
+    let links = "---\nTitle: Links I like\n---\nhttps://example.com\n";
+    let year = "# 2024\nplans for the year\n";
+    vault.write("links.md", links);
+    vault.write("2024.md", year);
+    vault.write(
+        "note.md",
+        "---\nTitle: Note\nCreated: 2024-05-01 09:00\ntags: [rust]\n---\nbody\n",
+    );
+
+    let output = vault.gtx(&["index"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(vault.read("links.md"), links);
+    assert_eq!(vault.read("2024.md"), year);
+    assert!(stderr(&output).contains("links.md"));
+    assert!(vault.path("2024-05.md").exists());
+
+    // 再次构建与清理时同样保留
+    let output = vault.gtx(&["index", "--purge", "--full"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(vault.read("links.md"), links);
+    assert_eq!(vault.read("2024.md"), year);
+}
+
+#[test]
+fn each_operation_gets_its_own_backup() {
+    let vault = TempVault::new("backup");
+    let note = "---\nTitle: Note\ntags: [old]\n---\nbody\n";
+    vault.write("note.md", note);
+
+    for args in [
+        &["tag", "add", "one", "--query", "tag:old"][..],
+        &["tag", "add", "two", "--query", "tag:old"][..],
+    ] {
+        let output = vault.gtx_dir(args);
+        assert!(output.status.success(), "{}", stderr(&output));
+    }
+    let backups: Vec<String> = fs::read_dir(vault.path(".gtx/backup"))
+        .unwrap()
+        .map(|entry| fs::read_to_string(entry.unwrap().path().join("note.md")).unwrap())
+        .collect();
+    // 两次操作紧接着进行，各自的备份都保留
+    assert_eq!(backups.len(), 2);
+    assert!(backups.iter().any(|backup| backup == note));
+    assert!(backups.iter().any(|backup| backup.contains("one")));
+}
+
+// dir 下全部文件的内容，按路径排序
+fn files_under(dir: &std::path::Path) -> Vec<(PathBuf, String)> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            files.extend(files_under(&path));
+        } else {
+            let content = fs::read_to_string(&path).unwrap();
+            files.push((path, content));
+        }
+    }
+    files.sort();
+    files
+}
+
+#[test]
+fn trash_keeps_same_named_pages_apart() {
+    let vault = TempVault::new("trash");
+    vault.write("a.md", "---\nTitle: A\ntags: [lang/x]\n---\n");
+    vault.write("b.md", "---\nTitle: B\ntags: [tool/x]\n---\n");
+    let output = vault.gtx(&["index"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    // 两个同名的层级标签页在同一次运行中移入隔离目录
+    vault.write("a.md", "---\nTitle: A\n---\n");
+    vault.write("b.md", "---\nTitle: B\n---\n");
+    let output = vault.gtx(&["index"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let trashed = files_under(&vault.path(".gtx/trash"));
+    let names = |trashed: &[(PathBuf, String)]| {
+        trashed
+            .iter()
+            .filter(|(path, _)| path.file_name().unwrap() == "x.md")
+            .count()
+    };
+    assert_eq!(names(&trashed), 2, "{:?}", trashed);
+
+    // 以后的运行不覆盖已隔离的页面
+    vault.write("a.md", "---\nTitle: A\ntags: [lang/x]\n---\n");
+    let output = vault.gtx(&["index"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    vault.write("a.md", "---\nTitle: A\n---\n");
+    let output = vault.gtx(&["index"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(names(&files_under(&vault.path(".gtx/trash"))), 3);
+}
+
+#[test]
+fn exports_leave_out_drafts_unless_included() {
+    let vault = TempVault::new("drafts");
+    vault.write(
+        "public.md",
+        "---\nTitle: Public\nCreated: 2024-05-01\ntags: [rust]\n---\nsee [[secret]]\n",
+    );
+    vault.write(
+        "secret.md",
+        "---\nTitle: Secret\nCreated: 2024-05-02\ntags: [rust]\nDraft: true\n---\nunfinished\n",
+    );
+
+    let output = vault.gtx(&["export", "-f", "csv"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains("Public"));
+    assert!(!stdout(&output).contains("Secret"));
+
+    let site = vault.path("site");
+    let output = vault.gtx(&["export", "-f", "html", "-o", site.to_str().unwrap()]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(site.join("public.html").exists());
+    assert!(!site.join("secret.html").exists());
+    assert!(!vault.read("site/public.html").contains("secret.html"));
+
+    let hugo = vault.path("hugo");
+    let output = vault.gtx(&[
+        "export",
+        "-f",
+        "hugo",
+        "--include-drafts",
+        "-o",
+        hugo.to_str().unwrap(),
+    ]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(
+        vault
+            .read("hugo/content/secret.md")
+            .contains("draft: true\n")
+    );
+    assert!(!vault.read("hugo/content/public.md").contains("draft"));
+}
+
+#[test]
+fn rpc_rejects_deeply_nested_requests() {
+    let vault = TempVault::new("rpc-nesting");
+    vault.write("note.md", "---\nTitle: Note\n---\n");
+    let input = format!(
+        "{}\n{{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"query\",\"params\":{{\"query\":\"title:Note\"}}}}\n",
+        "[".repeat(100_000)
+    );
+    let output = vault.gtx_input(&["rpc"], &input);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let lines: Vec<String> = stdout(&output).lines().map(str::to_string).collect();
+    assert_eq!(lines.len(), 2, "{:?}", lines);
+    assert!(lines[0].contains("-32700"), "{}", lines[0]);
+    // 出错后继续应答之后的请求
+    assert!(lines[1].contains("note.md"), "{}", lines[1]);
+}
+
+#[test]
+fn index_is_stable_across_runs() {
+    let vault = TempVault::new("stable");
+    let tags = [
+        "alpha", "beta", "gamma", "delta", "epsilon", "zeta", "eta", "theta",
+    ];
+    for (i, tag) in tags.iter().enumerate() {
+        vault.write(
+            &format!("n{}.md", i),
+            &format!(
+                "---\nTitle: N{}\nCreated: 2024-05-0{}\ntags: [{}]\n---\n",
+                i,
+                i % 9 + 1,
+                tag
+            ),
+        );
+    }
+    let output = vault.gtx(&["index"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let first = vault.read("index.md");
+    let alpha = first.find("alpha").unwrap();
+    assert!(alpha < first.find("beta").unwrap() && alpha < first.find("theta").unwrap());
+
+    // 每个进程中集合的遍历顺序不同，多次重建的结果应当相同
+    for _ in 0..5 {
+        let output = vault.gtx(&["index", "--dry-run", "--full"]);
+        assert!(output.status.success(), "{}", stderr(&output));
+        assert!(!stdout(&output).contains("index.md"), "{}", stdout(&output));
+    }
+}
+
+#[test]
+fn query_blocks_are_expanded_for_every_consumer() {
+    let vault = TempVault::new("query-blocks");
+    vault.write(
+        "list.md",
+        "---\nTitle: List\ntags: [meta]\n---\nIntro\n\n```gtx\ntag:rust\n```\n",
+    );
+    vault.write(
+        "target.md",
+        "---\nTitle: Zebrafish\ntags: [rust]\n---\nbody\n",
+    );
+    let output = vault.gtx(&["index"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(vault.read("list.md").contains("<!-- gtx:begin -->"));
+
+    // 导出到博客目录之外，免得导出的页面作为子目录中的笔记被搜索到
+    let out = TempVault::new("query-blocks-hugo");
+    let hugo = out.path("hugo");
+    let output = vault.gtx(&["export", "-f", "hugo", "-o", hugo.to_str().unwrap()]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let page = out.read("hugo/content/list.md");
+    assert!(
+        !page.contains("```gtx") && !page.contains("gtx:begin"),
+        "{}",
+        page
+    );
+    assert!(page.contains("ref \"target.md\""), "{}", page);
+
+    // 结果列表中的标题不属于 list.md 的文字
+    let output = vault.gtx_dir(&["search", "Zebrafish"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains("target"), "{}", stdout(&output));
+    assert!(!stdout(&output).contains("list"), "{}", stdout(&output));
+}
+
+#[test]
+fn sitemap_requires_an_absolute_base_url() {
+    let vault = TempVault::new("sitemap");
+    vault.write(
+        "note.md",
+        "---\nTitle: Note\nCreated: 2024-05-01\ntags: [rust]\n---\n",
+    );
+    let site = vault.path("site");
+    let site = site.to_str().unwrap();
+
+    let output = vault.gtx(&["export", "-f", "html", "-o", site]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(vault.path("site/note.html").exists());
+    assert!(!vault.path("site/sitemap.xml").exists());
+    assert!(stderr(&output).contains("--base-url"));
+
+    for args in [
+        &["export", "-f", "html", "-o", site, "--robots"][..],
+        &["export", "-f", "html", "-o", site, "--base-url", "notes/"][..],
+    ] {
+        let output = vault.gtx(args);
+        assert!(!output.status.success(), "{:?}", args);
+    }
+
+    let output = vault.gtx(&[
+        "export",
+        "-f",
+        "html",
+        "-o",
+        site,
+        "--base-url",
+        "https://example.com/notes",
+        "--robots",
+    ]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let sitemap = vault.read("site/sitemap.xml");
+    assert!(sitemap.contains("<loc>https://example.com/notes/note.html</loc>"));
+    assert!(
+        vault
+            .read("site/robots.txt")
+            .contains("Sitemap: https://example.com/notes/sitemap.xml")
+    );
+}
+
+#[test]
+fn sqlite_backend_needs_sqlite3_up_front() {
+    let vault = TempVault::new("sqlite-missing");
+    vault.write(
+        "a.md",
+        "---\nTitle: A\nCreated: 2024-05-01\ntags: [rust]\n---\n",
+    );
+    let empty_path = vault.path(".no-bin");
+    fs::create_dir_all(&empty_path).unwrap();
+
+    let output = vault
+        .command(&["index", "--backend", "sqlite"])
+        .arg(&vault.root)
+        .env("PATH", &empty_path)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(
+        stderr(&output).contains("找不到 sqlite3 命令"),
+        "{}",
+        stderr(&output)
+    );
+    // 构建开始前就已失败，没有写入任何页面
+    assert!(!vault.path("index.md").exists());
+    assert!(!vault.path(".gtx/cache.json").exists());
+}
+
+#[test]
+fn sqlite_values_survive_quotes_and_control_characters() {
+    if Command::new("sqlite3").arg("-version").output().is_err() {
+        return;
+    }
+    let vault = TempVault::new("sqlite-quote");
+    vault.write(
+        "a.md",
+        "---\nTitle: \"it's\\n.shell touch pwned\\n\\t\"\nCreated: 2024-05-01\ntags: [\"o'brien\"]\n---\n[[x'); DROP TABLE notes; --]]\n",
+    );
+
+    let output = vault.gtx(&["index", "--backend", "sqlite"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(!vault.path("pwned").exists());
+
+    let query = |sql: &str| {
+        let output = Command::new("sqlite3")
+            .arg(vault.path(".gtx/index.db"))
+            .arg(sql)
+            .output()
+            .unwrap();
+        stdout(&output)
+    };
+    assert_eq!(
+        query("SELECT hex(title) FROM notes"),
+        format!("{}\n", hex(b"it's\n.shell touch pwned\n\t"))
+    );
+    assert_eq!(query("SELECT tag FROM tags"), "o'brien\n");
+    assert_eq!(
+        query("SELECT target FROM links"),
+        "x'); DROP TABLE notes; --\n"
+    );
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02X}", b)).collect()
+}
+
+#[test]
+fn notes_in_subdirectories_are_indexed() {
+    let vault = TempVault::new("nested");
+    vault.write(
+        "a.md",
+        "---\nTitle: A\ntags: [top]\n---\n[[plan]] [[projects/plan]]\n",
+    );
+    vault.write(
+        "projects/plan.md",
+        "---\nTitle: Plan\ntags: [work]\n---\n[[a]]\n",
+    );
+    vault.write("archive/old.md", "---\nTitle: Old\ntags: [old]\n---\n");
+    vault.write(
+        "private/secret.md",
+        "---\nTitle: Secret\ntags: [secret]\n---\n",
+    );
+    vault.write(".gtxignore", "private/\n");
+    #[cfg(unix)]
+    std::os::unix::fs::symlink("..", vault.path("projects/loop")).unwrap();
+
+    for _ in 0..2 {
+        let output = vault.gtx(&["index", "--full"]);
+        assert!(output.status.success(), "{}", stderr(&output));
+        let index = vault.read("index.md");
+        assert!(index.contains("Plan"), "{}", index);
+        assert!(
+            !index.contains("Old") && !index.contains("Secret"),
+            "{}",
+            index
+        );
+        assert!(vault.path("tags/work.md").exists());
+        assert!(!vault.path("tags/old.md").exists());
+        // 生成的反向链接页面位于同样的子目录下，下次构建不会当作笔记扫描
+        let backlinks = vault.read("backlinks/projects/plan.md");
+        assert_eq!(backlinks.matches("A]]").count(), 2, "{}", backlinks);
+        assert!(!vault.path("backlinks/backlinks").exists());
+    }
+}
+
+#[test]
+fn archived_notes_keep_their_links_and_are_reported_apart() {
+    let vault = TempVault::new("archive");
+    vault.write(
+        "old.md",
+        "---\nTitle: Old\nCreated: 2020-01-01\ntags: [x]\n---\nbody\n",
+    );
+    vault.write(
+        "new.md",
+        "---\nTitle: New\nCreated: 2024-01-01\ntags: [x]\n---\n[[old]] [[gone]]\n",
+    );
+    let output = vault.gtx(&["archive", "--before", "2022-01-01"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains("new.md"), "{}", stdout(&output));
+    assert!(vault.path("archive/old.md").exists());
+    assert!(vault.read("archive/old.md").contains("archived"));
+    assert!(vault.read("new.md").contains("[[old]]"));
+
+    // 指向归档笔记的链接单独列出，只有 [[gone]] 算失效
+    let output = vault.gtx(&["check"]);
+    let out = stdout(&output);
+    let (broken, archived) = out.split_once("指向归档笔记的链接").unwrap();
+    assert!(
+        broken.contains("[[gone]]") && !broken.contains("[[old]]"),
+        "{}",
+        out
+    );
+    assert!(archived.contains("[[old]]"), "{}", out);
+    assert!(
+        stderr(&output).contains("共 1 条失效链接"),
+        "{}",
+        stderr(&output)
+    );
+}
+
+#[test]
+fn markdown_date_pages_omit_a_missing_time() {
+    let vault = TempVault::new("date-links");
+    vault.write("n0.md", "---\nTitle: N0\nCreated: 20240501\n---\n");
+    vault.write("n1.md", "---\nTitle: N1\nCreated: 20240501 09:30\n---\n");
+
+    let output = vault.gtx(&["index", "--link-style", "markdown"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let page = vault.read("20240501.md");
+    assert!(page.contains("[09:30 N1](n1.md)"), "{}", page);
+    assert!(page.contains("[N0](n0.md)"), "{}", page);
+}
+
+#[test]
+fn invalid_created_dates_are_reported_on_stderr() {
+    let vault = TempVault::new("invalid-created");
+    vault.write("bad.md", "---\nTitle: Bad\nCreated: 2023-02-29\n---\n");
+    vault.write("good.md", "---\nTitle: Good\nCreated: 2024-05-01\n---\n");
+
+    let output = vault.gtx(&["index", "--quiet"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let errors = stderr(&output);
+    assert!(errors.contains("bad.md"), "{}", errors);
+    assert!(errors.contains("'2023-02-29'"), "{}", errors);
+    assert!(!errors.contains("good.md"), "{}", errors);
+}
+
+#[test]
+fn user_files_under_backlinks_are_kept() {
+    let vault = TempVault::new("backlinks-user");
+    vault.write("a.md", "---\nTitle: A\n---\n[[b]]\n");
+    vault.write("b.md", "---\nTitle: B\n---\n");
+    vault.write("backlinks/mine.md", "---\nTitle: Mine\n---\n我的笔记\n");
+
+    for args in [&["index"][..], &["index", "--purge"]] {
+        let output = vault.gtx(args);
+        assert!(output.status.success(), "{}", stderr(&output));
+        assert_eq!(
+            vault.read("backlinks/mine.md"),
+            "---\nTitle: Mine\n---\n我的笔记\n"
+        );
+        assert!(vault.path("backlinks/b.md").exists());
+    }
+    assert!(!vault.path(".gtx/trash").exists());
+}
+
+#[test]
+fn user_notes_in_generated_directories_are_indexed() {
+    let vault = TempVault::new("generated-dirs");
+    for dir in ["weekly", "tags", "updated", "backlinks"] {
+        vault.write(
+            &format!("{}/mine.md", dir),
+            &format!("---\nTitle: {0} note\ntags: [in-{0}]\n---\n", dir),
+        );
+    }
+
+    for args in [&["index"][..], &["index", "--full"]] {
+        let output = vault.gtx(args);
+        assert!(output.status.success(), "{}", stderr(&output));
+        for dir in ["weekly", "tags", "updated", "backlinks"] {
+            let page = vault.read(&format!("tags/in-{}.md", dir));
+            assert!(page.contains(&format!("{} note", dir)), "{}", page);
+        }
+    }
+    // 生成的页面不当作笔记：标签页自身不会出现在标签页中
+    assert!(!vault.read("tags/in-tags.md").contains("in-weekly"));
+    assert!(!vault.path(".gtx/trash").exists());
+}
+
+#[test]
+fn merge_joins_bodies_and_frontmatter_and_redirects_links() {
+    let vault = TempVault::new("merge");
+    let keep = "---\nTitle: D\nCreated: 20240301 10:00\nTags: [a, b] # 标签\n---\nD 的正文\n";
+    let absorb = "---\nTitle: E 标题\nCreated: 20230101 09:00\nTags: b c\n---\nE 的正文 [[e]]\n";
+    let other = "---\nTitle: X\n---\n[[e]] [[e|别名]] [[e#小节]] [[E 标题]] `[[e]]`\n";
+    vault.write("sub/d.md", keep);
+    vault.write("e.md", absorb);
+    vault.write("x.md", other);
+
+    let output = vault.gtx_dir(&["merge", "-n", "sub/d", "e"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(vault.read("sub/d.md"), keep);
+    assert_eq!(vault.read("e.md"), absorb);
+    assert_eq!(vault.read("x.md"), other);
+
+    let output = vault.gtx_dir(&["merge", "sub/d", "e"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(
+        vault.read("sub/d.md"),
+        "---\nTitle: D\nCreated: 20230101 09:00\nTags: [a, b, c] # 标签\n---\n\
+         D 的正文\n\nE 的正文 [[sub/d]]\n"
+    );
+    assert_eq!(
+        vault.read("x.md"),
+        "---\nTitle: X\n---\n[[sub/d]] [[sub/d|别名]] [[sub/d#小节]] [[sub/d]] `[[e]]`\n"
+    );
+    assert!(!vault.path("e.md").exists());
+    // 改写与删除的文件都有备份
+    let backups: Vec<String> = files_under(&vault.path(".gtx/backup"))
+        .into_iter()
+        .map(|(_, content)| content)
+        .collect();
+    for content in [keep, absorb, other] {
+        assert!(
+            backups.iter().any(|backup| backup == content),
+            "{:?}",
+            backups
+        );
+    }
+}
+
+#[test]
+fn merge_vaults_stops_on_conflicts_unless_renaming() {
+    let vault = TempVault::new("merge-target");
+    let source = TempVault::new("merge-source");
+    vault.write("same.md", "---\nTitle: Same\n---\n");
+    vault.write("clash.md", "---\nTitle: Mine\n---\n");
+    source.write("same.md", "---\nTitle: Same\n---\n");
+    source.write("clash.md", "---\nTitle: Theirs\n---\n");
+    source.write(
+        "sub/new.md",
+        "---\nTitle: New\n---\n[[clash]] [[clash|别名]]\n",
+    );
+    let source_dir = source.path("").display().to_string();
+
+    let output = vault.gtx_dir(&["merge-vaults", &source_dir]);
+    assert!(!output.status.success());
+    assert!(stdout(&output).contains("clash.md") || stderr(&output).contains("clash.md"));
+    assert!(!vault.path("sub/new.md").exists());
+    assert_eq!(vault.read("clash.md"), "---\nTitle: Mine\n---\n");
+
+    let output = vault.gtx_dir(&["merge-vaults", "--rename", &source_dir]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(vault.read("clash.md"), "---\nTitle: Mine\n---\n");
+    assert_eq!(vault.read("clash 2.md"), "---\nTitle: Theirs\n---\n");
+    assert_eq!(
+        vault.read("sub/new.md"),
+        "---\nTitle: New\n---\n[[clash 2]] [[clash 2|别名]]\n"
+    );
+    // 来源目录不变
+    assert_eq!(
+        source.read("sub/new.md"),
+        "---\nTitle: New\n---\n[[clash]] [[clash|别名]]\n"
+    );
+}
+
+#[test]
+fn doctor_reports_follow_the_output_language() {
+    let vault = TempVault::new("doctor-lang");
+    vault.write("a.md", "---\nTitle: A\nCreated: not\nTags: x\n---\n");
+    vault.write("b.md", "---\nTitle: B\nCreated: 2023-02-29\nTags: x\n---\n");
+
+    let output = vault
+        .command(&["doctor"])
+        .arg(vault.path(""))
+        .env("GTX_LANG", "en")
+        .output()
+        .unwrap();
+    let text = stdout(&output);
+    assert!(text.contains("unparsable Created (2):"), "{}", text);
+    assert!(text.contains("a.md: unrecognized date 'not'"), "{}", text);
+    assert!(
+        text.contains("b.md: date '2023-02-29' does not exist"),
+        "{}",
+        text
+    );
+    assert!(
+        !text
+            .chars()
+            .any(|c| ('\u{4e00}'..='\u{9fff}').contains(&c) || c == '（')
+    );
+
+    let text = stdout(&vault.gtx(&["doctor"]));
+    assert!(text.contains("Created 无法解析（2）:"), "{}", text);
+    assert!(text.contains("a.md: 无法识别的日期 'not'"), "{}", text);
+}
+
+#[test]
+fn new_notes_without_tags_pass_doctor() {
+    let vault = TempVault::new("new-untagged");
+    let output = vault.gtx_dir(&["new", "-z", "卡片"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let output = vault.gtx_dir(&["new", "-t", "rust, cli", "标签"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let notes = files_under(&vault.path(""));
+    let card = notes
+        .iter()
+        .find(|(path, _)| path.to_string_lossy().ends_with(" 卡片.md"))
+        .unwrap();
+    assert!(card.1.contains("\nTags: NeedTag\n"), "{}", card.1);
+    assert!(vault.read("标签.md").contains("\nTags: rust cli\n"));
+
+    let output = vault.gtx(&["doctor"]);
+    assert!(output.status.success(), "{}", stdout(&output));
+}
+
+#[test]
+fn csv_and_json_exports_agree_on_untagged_notes() {
+    let vault = TempVault::new("export-untagged");
+    vault.write("a.md", "---\nTitle: A\nCreated: 2024-05-01\n---\n");
+    vault.write("b.md", "---\nTitle: B\ntags: [rust, cli]\n---\n");
+
+    let output = vault.gtx(&["export", "-f", "csv"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(
+        stdout(&output),
+        "path,title,date,time,tags,words\r\n\
+         a.md,A,20240501,,NeedTag,0\r\n\
+         b.md,B,,,rust;cli,0\r\n"
+    );
+
+    let output = vault.gtx(&["export", "-f", "json"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output).matches("\"NeedTag\"").count(), 1);
+}